@@ -36,6 +36,16 @@ pub enum Action {
     DumpsGet = actions::DUMPS_GET,
     #[serde(rename = "version")]
     Version = actions::VERSION,
+    #[serde(rename = "dashboard")]
+    Dashboard = actions::DASHBOARD,
+    #[serde(rename = "migrations.create")]
+    MigrationsCreate = actions::MIGRATIONS_CREATE,
+    #[serde(rename = "migrations.get")]
+    MigrationsGet = actions::MIGRATIONS_GET,
+    #[serde(rename = "suggest")]
+    Suggest = actions::SUGGEST,
+    #[serde(rename = "documents.export")]
+    DocumentsExport = actions::DOCUMENTS_EXPORT,
 }
 
 impl Action {
@@ -58,6 +68,11 @@ impl Action {
             DUMPS_CREATE => Some(Self::DumpsCreate),
             DUMPS_GET => Some(Self::DumpsGet),
             VERSION => Some(Self::Version),
+            DASHBOARD => Some(Self::Dashboard),
+            MIGRATIONS_CREATE => Some(Self::MigrationsCreate),
+            MIGRATIONS_GET => Some(Self::MigrationsGet),
+            SUGGEST => Some(Self::Suggest),
+            DOCUMENTS_EXPORT => Some(Self::DocumentsExport),
             _otherwise => None,
         }
     }
@@ -81,6 +96,11 @@ impl Action {
             Self::DumpsCreate => DUMPS_CREATE,
             Self::DumpsGet => DUMPS_GET,
             Self::Version => VERSION,
+            Self::Dashboard => DASHBOARD,
+            Self::MigrationsCreate => MIGRATIONS_CREATE,
+            Self::MigrationsGet => MIGRATIONS_GET,
+            Self::Suggest => SUGGEST,
+            Self::DocumentsExport => DOCUMENTS_EXPORT,
         }
     }
 }
@@ -101,4 +121,9 @@ pub mod actions {
     pub const DUMPS_CREATE: u8 = 13;
     pub const DUMPS_GET: u8 = 14;
     pub const VERSION: u8 = 15;
+    pub const DASHBOARD: u8 = 16;
+    pub const MIGRATIONS_CREATE: u8 = 17;
+    pub const MIGRATIONS_GET: u8 = 18;
+    pub const SUGGEST: u8 = 19;
+    pub const DOCUMENTS_EXPORT: u8 = 20;
 }
@@ -1,7 +1,7 @@
 use std::error::Error;
 
-use meilisearch_error::ErrorCode;
 use meilisearch_error::{internal_error, Code};
+use meilisearch_error::{ErrorCode, ErrorDetails};
 use serde_json::Value;
 
 pub type Result<T> = std::result::Result<T, AuthControllerError>;
@@ -16,10 +16,26 @@ pub enum AuthControllerError {
     InvalidApiKeyIndexes(Value),
     #[error("`expiresAt` field value `{0}` is invalid. It should follow the RFC 3339 format to represents a date or datetime in the future or specified as a null value. e.g. 'YYYY-MM-DD' or 'YYYY-MM-DD HH:MM:SS'.")]
     InvalidApiKeyExpiresAt(Value),
+    #[error("`activatesAt` field value `{0}` is invalid. It should follow the RFC 3339 format to represents a date or datetime or specified as a null value. e.g. 'YYYY-MM-DD' or 'YYYY-MM-DD HH:MM:SS'.")]
+    InvalidApiKeyActivatesAt(Value),
+    #[error("`schedule` field value `{0}` is invalid. It should be an object with the `days` of the week and the `start` and `end` times in the `HH:MM` format, in UTC, or specified as a null value.")]
+    InvalidApiKeySchedule(Value),
     #[error("`description` field value `{0}` is invalid. It should be a string or specified as a null value.")]
     InvalidApiKeyDescription(Value),
+    #[error("`retrievableAttributes` field value `{0}` is invalid. It should be an array of string representing attribute names or specified as a null value.")]
+    InvalidApiKeyRetrievableAttributes(Value),
+    #[error("`defaultSearchParams` field value `{0}` is invalid. It should be an object of search parameters, except `q`, or specified as a null value.")]
+    InvalidApiKeyDefaultSearchParams(Value),
+    #[error("`namespace` field value `{0}` is invalid. It should be a string of alphanumeric characters and hyphens terminated by an underscore, or specified as a null value.")]
+    InvalidApiKeyNamespace(Value),
+    #[error("`url` field value `{0}` is invalid. It should be an `http` or `https` URL, without credentials, whose host is not a loopback, private or link-local address.")]
+    InvalidApiKeyUsageWebhook(Value),
     #[error("API key `{0}` not found.")]
     ApiKeyNotFound(String),
+    #[error("The provided key can't open a dashboard session. It must be the master key or an API key with the `dashboard` action.")]
+    InvalidSessionKey,
+    #[error("Meilisearch is running without a master key. A dashboard session can only be opened once a master key is set.")]
+    MissingMasterKey,
     #[error("Internal error: {0}")]
     Internal(Box<dyn Error + Send + Sync + 'static>),
 }
@@ -38,9 +54,59 @@ impl ErrorCode for AuthControllerError {
             Self::InvalidApiKeyActions(_) => Code::InvalidApiKeyActions,
             Self::InvalidApiKeyIndexes(_) => Code::InvalidApiKeyIndexes,
             Self::InvalidApiKeyExpiresAt(_) => Code::InvalidApiKeyExpiresAt,
+            Self::InvalidApiKeyActivatesAt(_) => Code::InvalidApiKeyActivatesAt,
+            Self::InvalidApiKeySchedule(_) => Code::InvalidApiKeySchedule,
             Self::InvalidApiKeyDescription(_) => Code::InvalidApiKeyDescription,
+            Self::InvalidApiKeyRetrievableAttributes(_) => Code::InvalidApiKeyRetrievableAttributes,
+            Self::InvalidApiKeyDefaultSearchParams(_) => Code::InvalidApiKeyDefaultSearchParams,
+            Self::InvalidApiKeyNamespace(_) => Code::InvalidApiKeyNamespace,
+            Self::InvalidApiKeyUsageWebhook(_) => Code::InvalidApiKeyUsageWebhook,
             Self::ApiKeyNotFound(_) => Code::ApiKeyNotFound,
+            Self::InvalidSessionKey => Code::InvalidToken,
+            Self::MissingMasterKey => Code::MissingMasterKey,
             Self::Internal(_) => Code::Internal,
         }
     }
+
+    fn details(&self) -> Option<ErrorDetails> {
+        let (field, value) = match self {
+            Self::MissingParameter(field) => (*field, Value::Null),
+            Self::InvalidApiKeyActions(value) => ("actions", value.clone()),
+            Self::InvalidApiKeyIndexes(value) => ("indexes", value.clone()),
+            Self::InvalidApiKeyExpiresAt(value) => ("expiresAt", value.clone()),
+            Self::InvalidApiKeyActivatesAt(value) => ("activatesAt", value.clone()),
+            Self::InvalidApiKeySchedule(value) => ("schedule", value.clone()),
+            Self::InvalidApiKeyDescription(value) => ("description", value.clone()),
+            Self::InvalidApiKeyRetrievableAttributes(value) => {
+                ("retrievableAttributes", value.clone())
+            }
+            Self::InvalidApiKeyDefaultSearchParams(value) => ("defaultSearchParams", value.clone()),
+            Self::InvalidApiKeyNamespace(value) => ("namespace", value.clone()),
+            Self::InvalidApiKeyUsageWebhook(value) => ("url", value.clone()),
+            _ => return None,
+        };
+
+        Some(ErrorDetails {
+            field: field.to_string(),
+            expected: expected_format(field).to_string(),
+            value,
+        })
+    }
+}
+
+/// Returns a description of the values accepted by a field of a key payload.
+fn expected_format(field: &str) -> &'static str {
+    match field {
+        "actions" => "an array of action names",
+        "indexes" => "an array of index names",
+        "expiresAt" => "an RFC 3339 date or datetime in the future, or null",
+        "activatesAt" => "an RFC 3339 date or datetime, or null",
+        "schedule" => "an object with the `days` of the week and the `start` and `end` times in the `HH:MM` format, or null",
+        "description" => "a string, or null",
+        "retrievableAttributes" => "an array of attribute names, or null",
+        "defaultSearchParams" => "an object of search parameters, or null",
+        "namespace" => "a string of alphanumeric characters and hyphens terminated by an underscore, or null",
+        "url" => "an `http` or `https` URL",
+        _ => "a valid value",
+    }
 }
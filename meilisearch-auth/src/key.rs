@@ -1,9 +1,11 @@
 use crate::action::Action;
 use crate::error::{AuthControllerError, Result};
+use crate::schedule::Schedule;
 use crate::store::{KeyId, KEY_ID_LENGTH};
+use crate::usage::UsageWebhook;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use serde_json::{from_value, Value};
+use serde_json::{from_value, Map, Value};
 use time::format_description::well_known::Rfc3339;
 use time::macros::{format_description, time};
 use time::{Date, OffsetDateTime, PrimitiveDateTime};
@@ -15,8 +17,28 @@ pub struct Key {
     pub id: KeyId,
     pub actions: Vec<Action>,
     pub indexes: Vec<String>,
+    /// The attributes of the documents the key can retrieve when searching, all of them when it
+    /// is `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retrievable_attributes: Option<Vec<String>>,
+    /// The search parameters applied to the searches of the key that omit them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_search_params: Option<Map<String, Value>>,
+    /// The prefix of the uids of the indexes the key can access. The key addresses its indexes
+    /// without it, and its `indexes` are relative to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// The date before which the key can't be used.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub activates_at: Option<OffsetDateTime>,
     #[serde(with = "time::serde::rfc3339::option")]
     pub expires_at: Option<OffsetDateTime>,
+    /// The weekly window outside of which the key can't be used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<Schedule>,
+    /// The callback receiving the usage digests of the key, registered by its owner.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage_webhook: Option<UsageWebhook>,
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
     #[serde(with = "time::serde::rfc3339")]
@@ -26,11 +48,7 @@ pub struct Key {
 impl Key {
     pub fn create_from_value(value: Value) -> Result<Self> {
         let description = match value.get("description") {
-            Some(Value::Null) => None,
-            Some(des) => Some(
-                from_value(des.clone())
-                    .map_err(|_| AuthControllerError::InvalidApiKeyDescription(des.clone()))?,
-            ),
+            Some(des) => parse_description(des)?,
             None => None,
         };
 
@@ -38,25 +56,44 @@ impl Key {
 
         let actions = value
             .get("actions")
-            .map(|act| {
-                from_value(act.clone())
-                    .map_err(|_| AuthControllerError::InvalidApiKeyActions(act.clone()))
-            })
+            .map(parse_actions)
             .ok_or(AuthControllerError::MissingParameter("actions"))??;
 
         let indexes = value
             .get("indexes")
-            .map(|ind| {
-                from_value(ind.clone())
-                    .map_err(|_| AuthControllerError::InvalidApiKeyIndexes(ind.clone()))
-            })
+            .map(parse_indexes)
             .ok_or(AuthControllerError::MissingParameter("indexes"))??;
 
+        let retrievable_attributes = match value.get("retrievableAttributes") {
+            Some(attributes) => parse_retrievable_attributes(attributes)?,
+            None => None,
+        };
+
+        let default_search_params = match value.get("defaultSearchParams") {
+            Some(params) => parse_default_search_params(params)?,
+            None => None,
+        };
+
+        let namespace = match value.get("namespace") {
+            Some(namespace) => parse_namespace(namespace)?,
+            None => None,
+        };
+
+        let activates_at = match value.get("activatesAt") {
+            Some(date) => parse_activation_date(date)?,
+            None => None,
+        };
+
         let expires_at = value
             .get("expiresAt")
             .map(parse_expiration_date)
             .ok_or(AuthControllerError::MissingParameter("expiresAt"))??;
 
+        let schedule = match value.get("schedule") {
+            Some(schedule) => parse_schedule(schedule)?,
+            None => None,
+        };
+
         let created_at = OffsetDateTime::now_utc();
         let updated_at = created_at;
 
@@ -65,7 +102,13 @@ impl Key {
             id,
             actions,
             indexes,
+            retrievable_attributes,
+            default_search_params,
+            namespace,
+            activates_at,
             expires_at,
+            schedule,
+            usage_webhook: None,
             created_at,
             updated_at,
         })
@@ -73,32 +116,109 @@ impl Key {
 
     pub fn update_from_value(&mut self, value: Value) -> Result<()> {
         if let Some(des) = value.get("description") {
-            let des = from_value(des.clone())
-                .map_err(|_| AuthControllerError::InvalidApiKeyDescription(des.clone()));
-            self.description = des?;
+            self.description = parse_description(des)?;
         }
 
         if let Some(act) = value.get("actions") {
-            let act = from_value(act.clone())
-                .map_err(|_| AuthControllerError::InvalidApiKeyActions(act.clone()));
-            self.actions = act?;
+            self.actions = parse_actions(act)?;
         }
 
         if let Some(ind) = value.get("indexes") {
-            let ind = from_value(ind.clone())
-                .map_err(|_| AuthControllerError::InvalidApiKeyIndexes(ind.clone()));
-            self.indexes = ind?;
+            self.indexes = parse_indexes(ind)?;
+        }
+
+        if let Some(attributes) = value.get("retrievableAttributes") {
+            self.retrievable_attributes = parse_retrievable_attributes(attributes)?;
+        }
+
+        if let Some(params) = value.get("defaultSearchParams") {
+            self.default_search_params = parse_default_search_params(params)?;
+        }
+
+        if let Some(namespace) = value.get("namespace") {
+            self.namespace = parse_namespace(namespace)?;
+        }
+
+        if let Some(date) = value.get("activatesAt") {
+            self.activates_at = parse_activation_date(date)?;
         }
 
         if let Some(exp) = value.get("expiresAt") {
             self.expires_at = parse_expiration_date(exp)?;
         }
 
+        if let Some(schedule) = value.get("schedule") {
+            self.schedule = parse_schedule(schedule)?;
+        }
+
         self.updated_at = OffsetDateTime::now_utc();
 
         Ok(())
     }
 
+    /// Runs the validation of `create_from_value`, or of `update_from_value` when `is_update` is
+    /// set, on every field instead of stopping at the first invalid one.
+    pub fn validate_value(value: &Value, is_update: bool) -> Vec<InvalidField> {
+        let mut errors = Vec::new();
+        let mut check = |field: &'static str, result: Option<Result<()>>| match result {
+            Some(Err(error)) => errors.push(InvalidField { field, error }),
+            None if !is_update && !OPTIONAL_FIELDS.contains(&field) => errors.push(InvalidField {
+                field,
+                error: AuthControllerError::MissingParameter(field),
+            }),
+            _ => (),
+        };
+
+        check(
+            "description",
+            value
+                .get("description")
+                .map(|v| parse_description(v).map(drop)),
+        );
+        check(
+            "actions",
+            value.get("actions").map(|v| parse_actions(v).map(drop)),
+        );
+        check(
+            "indexes",
+            value.get("indexes").map(|v| parse_indexes(v).map(drop)),
+        );
+        check(
+            "retrievableAttributes",
+            value
+                .get("retrievableAttributes")
+                .map(|v| parse_retrievable_attributes(v).map(drop)),
+        );
+        check(
+            "defaultSearchParams",
+            value
+                .get("defaultSearchParams")
+                .map(|v| parse_default_search_params(v).map(drop)),
+        );
+        check(
+            "namespace",
+            value.get("namespace").map(|v| parse_namespace(v).map(drop)),
+        );
+        check(
+            "activatesAt",
+            value
+                .get("activatesAt")
+                .map(|v| parse_activation_date(v).map(drop)),
+        );
+        check(
+            "expiresAt",
+            value
+                .get("expiresAt")
+                .map(|v| parse_expiration_date(v).map(drop)),
+        );
+        check(
+            "schedule",
+            value.get("schedule").map(|v| parse_schedule(v).map(drop)),
+        );
+
+        errors
+    }
+
     pub(crate) fn default_admin() -> Self {
         let now = OffsetDateTime::now_utc();
         Self {
@@ -106,7 +226,13 @@ impl Key {
             id: generate_id(),
             actions: vec![Action::All],
             indexes: vec!["*".to_string()],
+            retrievable_attributes: None,
+            default_search_params: None,
+            namespace: None,
+            activates_at: None,
             expires_at: None,
+            schedule: None,
+            usage_webhook: None,
             created_at: now,
             updated_at: now,
         }
@@ -121,11 +247,138 @@ impl Key {
             id: generate_id(),
             actions: vec![Action::Search],
             indexes: vec!["*".to_string()],
+            retrievable_attributes: None,
+            default_search_params: None,
+            namespace: None,
+            activates_at: None,
             expires_at: None,
+            schedule: None,
+            usage_webhook: None,
             created_at: now,
             updated_at: now,
         }
     }
+
+    /// Returns whether the key can be used at this date, according to its activation date and
+    /// schedule. The expiration date is checked separately.
+    pub fn is_active(&self, now: OffsetDateTime) -> bool {
+        self.activates_at.map_or(true, |date| date <= now)
+            && self
+                .schedule
+                .as_ref()
+                .map_or(true, |schedule| schedule.contains(now))
+    }
+
+    /// Returns the uid of the index `index` stands for in the namespace of the key.
+    pub fn namespaced(&self, index: &str) -> String {
+        match self.namespace {
+            Some(ref namespace) => format!("{}{}", namespace, index),
+            None => index.to_string(),
+        }
+    }
+
+    /// Whether the index belongs to the namespace of the key, any index does without namespace.
+    pub fn is_in_namespace(&self, index: &str) -> bool {
+        self.namespace
+            .as_ref()
+            .map_or(true, |namespace| index.starts_with(namespace.as_str()))
+    }
+}
+
+/// The fields of a key creation that can be omitted.
+const OPTIONAL_FIELDS: &[&str] = &[
+    "description",
+    "retrievableAttributes",
+    "defaultSearchParams",
+    "namespace",
+    "activatesAt",
+    "schedule",
+];
+
+/// A field of a key payload, along with the reason it is invalid.
+#[derive(Debug)]
+pub struct InvalidField {
+    pub field: &'static str,
+    pub error: AuthControllerError,
+}
+
+fn parse_retrievable_attributes(value: &Value) -> Result<Option<Vec<String>>> {
+    from_value(value.clone())
+        .map_err(|_| AuthControllerError::InvalidApiKeyRetrievableAttributes(value.clone()))
+}
+
+fn parse_default_search_params(value: &Value) -> Result<Option<Map<String, Value>>> {
+    match value {
+        Value::Object(params)
+            if params
+                .iter()
+                .all(|(name, value)| is_valid_default_search_param(name, value)) =>
+        {
+            Ok(Some(params.clone()))
+        }
+        Value::Null => Ok(None),
+        _otherwise => Err(AuthControllerError::InvalidApiKeyDefaultSearchParams(
+            value.clone(),
+        )),
+    }
+}
+
+/// Whether `name` is a search parameter a key can set a default for, and `value` has its type.
+fn is_valid_default_search_param(name: &str, value: &Value) -> bool {
+    let is_string_array = |value: &Value| {
+        value
+            .as_array()
+            .map_or(false, |values| values.iter().all(Value::is_string))
+    };
+
+    match name {
+        "offset" | "limit" | "page" | "hitsPerPage" | "cropLength" => value.is_u64(),
+        "matches" | "showRankingScore" | "showRankingScoreDetails" => value.is_boolean(),
+        "attributesToRetrieve"
+        | "attributesToCrop"
+        | "attributesToHighlight"
+        | "sort"
+        | "facetsDistribution"
+        | "locales" => is_string_array(value),
+        "distinct" | "highlightPreTag" | "highlightPostTag" | "cropMarker" | "ruleset" => {
+            value.is_string()
+        }
+        "matchingStrategy" => matches!(value.as_str(), Some("last" | "all" | "frequency")),
+        "filter" => value.is_string() || value.is_array(),
+        _ => false,
+    }
+}
+
+/// A namespace is made of the characters of the index uids, so that it can prefix them, and
+/// ends with its only underscore, so that no namespace can prefix another one.
+fn parse_namespace(value: &Value) -> Result<Option<String>> {
+    match value {
+        Value::String(namespace) if is_valid_namespace(namespace) => Ok(Some(namespace.clone())),
+        Value::Null => Ok(None),
+        _otherwise => Err(AuthControllerError::InvalidApiKeyNamespace(value.clone())),
+    }
+}
+
+fn is_valid_namespace(namespace: &str) -> bool {
+    match namespace.strip_suffix('_') {
+        Some(name) => {
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        }
+        None => false,
+    }
+}
+
+fn parse_description(value: &Value) -> Result<Option<String>> {
+    from_value(value.clone())
+        .map_err(|_| AuthControllerError::InvalidApiKeyDescription(value.clone()))
+}
+
+fn parse_actions(value: &Value) -> Result<Vec<Action>> {
+    from_value(value.clone()).map_err(|_| AuthControllerError::InvalidApiKeyActions(value.clone()))
+}
+
+fn parse_indexes(value: &Value) -> Result<Vec<String>> {
+    from_value(value.clone()).map_err(|_| AuthControllerError::InvalidApiKeyIndexes(value.clone()))
 }
 
 /// Generate a printable key of 64 characters using thread_rng.
@@ -141,31 +394,24 @@ fn generate_id() -> [u8; KEY_ID_LENGTH] {
     bytes
 }
 
+fn parse_schedule(value: &Value) -> Result<Option<Schedule>> {
+    from_value(value.clone()).map_err(|_| AuthControllerError::InvalidApiKeySchedule(value.clone()))
+}
+
+fn parse_activation_date(value: &Value) -> Result<Option<OffsetDateTime>> {
+    match value {
+        Value::String(string) => parse_date(string)
+            .map(Option::Some)
+            .ok_or_else(|| AuthControllerError::InvalidApiKeyActivatesAt(value.clone())),
+        Value::Null => Ok(None),
+        _otherwise => Err(AuthControllerError::InvalidApiKeyActivatesAt(value.clone())),
+    }
+}
+
 fn parse_expiration_date(value: &Value) -> Result<Option<OffsetDateTime>> {
     match value {
-        Value::String(string) => OffsetDateTime::parse(string, &Rfc3339)
-            .or_else(|_| {
-                PrimitiveDateTime::parse(
-                    string,
-                    format_description!(
-                        "[year repr:full base:calendar]-[month repr:numerical]-[day]T[hour]:[minute]:[second]"
-                    ),
-                ).map(|datetime| datetime.assume_utc())
-            })
-            .or_else(|_| {
-                PrimitiveDateTime::parse(
-                    string,
-                    format_description!(
-                        "[year repr:full base:calendar]-[month repr:numerical]-[day] [hour]:[minute]:[second]"
-                    ),
-                ).map(|datetime| datetime.assume_utc())
-            })
-            .or_else(|_| {
-                    Date::parse(string, format_description!(
-                        "[year repr:full base:calendar]-[month repr:numerical]-[day]"
-                    )).map(|date| PrimitiveDateTime::new(date, time!(00:00)).assume_utc())
-            })
-            .map_err(|_| AuthControllerError::InvalidApiKeyExpiresAt(value.clone()))
+        Value::String(string) => parse_date(string)
+            .ok_or_else(|| AuthControllerError::InvalidApiKeyExpiresAt(value.clone()))
             // check if the key is already expired.
             .and_then(|d| {
                 if d > OffsetDateTime::now_utc() {
@@ -179,3 +425,30 @@ fn parse_expiration_date(value: &Value) -> Result<Option<OffsetDateTime>> {
         _otherwise => Err(AuthControllerError::InvalidApiKeyExpiresAt(value.clone())),
     }
 }
+
+/// Parses an RFC 3339 date, or a date or datetime without offset standing for UTC.
+fn parse_date(string: &str) -> Option<OffsetDateTime> {
+    OffsetDateTime::parse(string, &Rfc3339)
+        .or_else(|_| {
+            PrimitiveDateTime::parse(
+                string,
+                format_description!(
+                    "[year repr:full base:calendar]-[month repr:numerical]-[day]T[hour]:[minute]:[second]"
+                ),
+            ).map(|datetime| datetime.assume_utc())
+        })
+        .or_else(|_| {
+            PrimitiveDateTime::parse(
+                string,
+                format_description!(
+                    "[year repr:full base:calendar]-[month repr:numerical]-[day] [hour]:[minute]:[second]"
+                ),
+            ).map(|datetime| datetime.assume_utc())
+        })
+        .or_else(|_| {
+            Date::parse(string, format_description!(
+                "[year repr:full base:calendar]-[month repr:numerical]-[day]"
+            )).map(|date| PrimitiveDateTime::new(date, time!(00:00)).assume_utc())
+        })
+        .ok()
+}
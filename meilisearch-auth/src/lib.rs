@@ -2,28 +2,44 @@ mod action;
 mod dump;
 pub mod error;
 mod key;
+mod permissions;
+mod replication;
+mod schedule;
+mod session;
 mod store;
+mod usage;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
 use std::str::from_utf8;
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 use sha2::{Digest, Sha256};
 use time::OffsetDateTime;
 
 pub use action::{actions, Action};
 use error::{AuthControllerError, Result};
-pub use key::Key;
+pub use key::{InvalidField, Key};
+pub use permissions::{ActionPermission, KeyPermissions, MatchingRule, PermissionCheck};
+pub use schedule::{Day, Schedule, TimeOfDay};
+pub use session::SESSION_DURATION;
+use session::{constant_time_eq, SessionClaims, SessionSigner};
 pub use store::open_auth_store_env;
 use store::HeedAuthStore;
+use usage::UsageCounters;
+pub use usage::{
+    is_public_address, sign_digest, webhook_host, DigestFrequency, IndexUsage, PendingDigest,
+    UsageDigest, UsageWebhook,
+};
 
 #[derive(Clone)]
 pub struct AuthController {
     store: Arc<HeedAuthStore>,
     master_key: Option<String>,
+    sessions: SessionSigner,
+    usage: Arc<UsageCounters>,
 }
 
 impl AuthController {
@@ -37,6 +53,8 @@ impl AuthController {
         Ok(Self {
             store: Arc::new(store),
             master_key: master_key.clone(),
+            sessions: SessionSigner::new(),
+            usage: Arc::default(),
         })
     }
 
@@ -45,6 +63,21 @@ impl AuthController {
         self.store.put_api_key(key)
     }
 
+    /// Validates a key creation without creating the key.
+    pub fn validate_key_creation(&self, value: &Value) -> Vec<InvalidField> {
+        Key::validate_value(value, false)
+    }
+
+    /// Validates the update of an existing key without updating it.
+    pub fn validate_key_update(
+        &self,
+        key: impl AsRef<str>,
+        value: &Value,
+    ) -> Result<Vec<InvalidField>> {
+        self.get_key(key)?;
+        Ok(Key::validate_value(value, true))
+    }
+
     pub fn update_key(&self, key: impl AsRef<str>, value: Value) -> Result<Key> {
         let mut key = self.get_key(key)?;
         key.update_from_value(value)?;
@@ -92,6 +125,21 @@ impl AuthController {
                 filters.search_rules = search_rules;
             }
 
+            if let Some(attributes) = key.retrievable_attributes {
+                filters.search_rules = filters
+                    .search_rules
+                    .restrict_attributes(attributes.into_iter().collect());
+            }
+
+            if let Some(params) = key.default_search_params {
+                filters.search_rules = filters.search_rules.with_default_search_params(params);
+            }
+
+            if let Some(namespace) = key.namespace {
+                filters.search_rules = filters.search_rules.with_namespace(&namespace);
+                filters.namespace = Some(namespace);
+            }
+
             filters.allow_index_creation = key
                 .actions
                 .iter()
@@ -115,6 +163,16 @@ impl AuthController {
         }
     }
 
+    /// Returns the namespace of the key, or of the key with this prefix.
+    pub fn get_key_namespace(&self, key: impl AsRef<str>) -> Result<Option<String>> {
+        Ok(self.store.get_api_key(key)?.and_then(|key| key.namespace))
+    }
+
+    /// Whether the keys can be read from the store.
+    pub fn is_accessible(&self) -> bool {
+        self.store.is_empty().is_ok()
+    }
+
     pub fn get_master_key(&self) -> Option<&String> {
         self.master_key.as_ref()
     }
@@ -135,7 +193,7 @@ impl AuthController {
         action: Action,
         index: Option<&str>,
     ) -> Result<bool> {
-        match self
+        let authorized = match self
             .store
             // check if the key has access to all indexes.
             .get_expiration_date(key, action, None)?
@@ -149,12 +207,22 @@ impl AuthController {
                 None => self.store.prefix_first_expiration_date(key, action)?,
             }) {
             // check expiration date.
-            Some(Some(exp)) => Ok(OffsetDateTime::now_utc() < exp),
+            Some(Some(exp)) => OffsetDateTime::now_utc() < exp,
             // no expiration date.
-            Some(None) => Ok(true),
+            Some(None) => true,
             // action or index forbidden.
-            None => Ok(false),
+            None => false,
+        };
+
+        // check the namespace, activation date and schedule of the key.
+        if authorized {
+            if let Some(key) = self.store.get_api_key(from_utf8(key)?)? {
+                let in_namespace = index.map_or(true, |index| key.is_in_namespace(index));
+                return Ok(in_namespace && key.is_active(OffsetDateTime::now_utc()));
+            }
         }
+
+        Ok(authorized)
     }
 
     /// Check if the provided key is valid
@@ -179,11 +247,57 @@ impl AuthController {
             Ok(false)
         }
     }
+
+    /// Open a dashboard session for the master key or a key holding the `dashboard` action.
+    /// Returns the session token along with its expiration date. Without a master key, no key
+    /// can be checked and no session is opened.
+    pub fn create_session(&self, key: &str) -> Result<(String, OffsetDateTime)> {
+        let master_key = self
+            .master_key
+            .as_deref()
+            .ok_or(AuthControllerError::MissingMasterKey)?;
+        let key_id = if constant_time_eq(master_key.as_bytes(), key.as_bytes()) {
+            None
+        } else if self.authenticate(key.as_bytes(), Action::Dashboard, None)? {
+            let id = self
+                .store
+                .get_key_id(key.as_bytes())
+                .ok_or(AuthControllerError::InvalidSessionKey)?;
+            Some(from_utf8(&id)?.to_string())
+        } else {
+            return Err(AuthControllerError::InvalidSessionKey);
+        };
+
+        let expires_at = OffsetDateTime::now_utc() + SESSION_DURATION;
+        let claims = SessionClaims {
+            key_id,
+            exp: expires_at.unix_timestamp(),
+        };
+
+        Ok((self.sessions.sign(&claims), expires_at))
+    }
+
+    /// Returns the key a valid session stands for. The session is rejected as soon as its key
+    /// is deleted or loses the `dashboard` action.
+    pub fn authenticate_session(&self, session: &str) -> Option<String> {
+        let claims = self.sessions.verify(session)?;
+        match claims.key_id {
+            None => self.master_key.clone(),
+            Some(id) => {
+                let key = self.generate_key(&id)?;
+                self.authenticate(key.as_bytes(), Action::Dashboard, None)
+                    .ok()?
+                    .then(|| key)
+            }
+        }
+    }
 }
 
 pub struct AuthFilter {
     pub search_rules: SearchRules,
     pub allow_index_creation: bool,
+    /// The namespace of the key, prefixing the uids of the indexes it accesses.
+    pub namespace: Option<String>,
 }
 
 impl Default for AuthFilter {
@@ -191,8 +305,26 @@ impl Default for AuthFilter {
         Self {
             search_rules: SearchRules::default(),
             allow_index_creation: true,
+            namespace: None,
+        }
+    }
+}
+
+impl AuthFilter {
+    /// Returns the uid of the index `uid` stands for in the namespace of the key.
+    pub fn namespaced(&self, uid: String) -> String {
+        match self.namespace {
+            Some(ref namespace) => format!("{}{}", namespace, uid),
+            None => uid,
         }
     }
+
+    /// Whether the index belongs to the namespace of the key, any index does without namespace.
+    pub fn is_in_namespace(&self, uid: &str) -> bool {
+        self.namespace
+            .as_ref()
+            .map_or(true, |namespace| uid.starts_with(namespace.as_str()))
+    }
 }
 
 /// Transparent wrapper around a list of allowed indexes with the search rules to apply for each.
@@ -217,6 +349,56 @@ impl SearchRules {
         }
     }
 
+    /// Restricts the attributes retrieved by the searches on every authorized index.
+    pub fn restrict_attributes(self, attributes: BTreeSet<String>) -> Self {
+        let restricted = self
+            .into_iter()
+            .map(|(index, rules)| {
+                let rules = IndexSearchRules {
+                    retrievable_attributes: Some(attributes.clone()),
+                    ..rules
+                };
+                (index, Some(rules))
+            })
+            .collect();
+
+        Self::Map(restricted)
+    }
+
+    /// Sets the default parameters of the searches on every authorized index.
+    pub fn with_default_search_params(self, params: Map<String, Value>) -> Self {
+        let rules = self
+            .into_iter()
+            .map(|(index, rules)| {
+                let rules = IndexSearchRules {
+                    default_search_params: Some(params.clone()),
+                    ..rules
+                };
+                (index, Some(rules))
+            })
+            .collect();
+
+        Self::Map(rules)
+    }
+
+    /// Prefixes the authorized indexes with the namespace, `*` then standing for every index of
+    /// the namespace.
+    pub fn with_namespace(self, namespace: &str) -> Self {
+        let namespaced = |index: String| match index.as_str() {
+            "*" => index,
+            _ => format!("{}{}", namespace, index),
+        };
+
+        match self {
+            Self::Set(set) => Self::Set(set.into_iter().map(namespaced).collect()),
+            Self::Map(map) => Self::Map(
+                map.into_iter()
+                    .map(|(index, rules)| (namespaced(index), rules))
+                    .collect(),
+            ),
+        }
+    }
+
     pub fn get_index_search_rules(&self, index: &str) -> Option<IndexSearchRules> {
         match self {
             Self::Set(set) => {
@@ -253,9 +435,15 @@ impl IntoIterator for SearchRules {
 /// Contains the rules to apply on the top of the search query for a specific index.
 ///
 /// filter: search filter to apply in addition to query filters.
+/// retrievable_attributes: the only attributes the search can retrieve, set from the API key.
+/// default_search_params: the parameters of the searches that omit them, set from the API key.
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct IndexSearchRules {
     pub filter: Option<serde_json::Value>,
+    #[serde(skip)]
+    pub retrievable_attributes: Option<BTreeSet<String>>,
+    #[serde(skip)]
+    pub default_search_params: Option<Map<String, Value>>,
 }
 
 fn generate_key(master_key: &[u8], keyid: &str) -> String {
@@ -0,0 +1,121 @@
+use enum_iterator::IntoEnumIterator;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use crate::{Action, AuthController, Key, Result};
+
+/// The action and index of a key granting a permission, `*` standing for all the actions or all
+/// the indexes.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchingRule {
+    pub action: Action,
+    pub index: String,
+}
+
+/// Whether a key is authorized to perform an action, returned by the `/keys/{key}/can` route.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionCheck {
+    pub authorized: bool,
+    /// The rule granting the action, even when the key is expired or inactive.
+    pub matching_rule: Option<MatchingRule>,
+    pub expired: bool,
+    /// Whether the key is within its activation date and schedule.
+    pub active: bool,
+}
+
+/// The indexes on which a key can perform an action.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionPermission {
+    pub action: Action,
+    pub indexes: Vec<String>,
+}
+
+/// Every action a key can perform, with the `*` action expanded, returned by the
+/// `/keys/{key}/permissions` route.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyPermissions {
+    pub expired: bool,
+    pub active: bool,
+    pub results: Vec<ActionPermission>,
+}
+
+impl Key {
+    /// Returns the rule granting the action on the index, or on any index when none is given.
+    pub fn matching_rule(&self, action: Action, index: Option<&str>) -> Option<MatchingRule> {
+        let action = if self.actions.contains(&action) {
+            action
+        } else if self.actions.contains(&Action::All) {
+            Action::All
+        } else {
+            return None;
+        };
+
+        let index = if self.indexes.iter().any(|i| i == "*") {
+            "*"
+        } else {
+            match index {
+                Some(index) => self.indexes.iter().find(|i| *i == index)?,
+                None => self.indexes.first()?,
+            }
+        };
+
+        Some(MatchingRule {
+            action,
+            index: index.to_string(),
+        })
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .map_or(false, |exp| exp <= OffsetDateTime::now_utc())
+    }
+
+    pub fn permissions(&self) -> KeyPermissions {
+        let results = Action::into_enum_iter()
+            .filter(|&action| action != Action::All)
+            .filter(|&action| self.actions.contains(&action) || self.actions.contains(&Action::All))
+            .map(|action| ActionPermission {
+                action,
+                indexes: self.indexes.clone(),
+            })
+            .filter(|permission| !permission.indexes.is_empty())
+            .collect();
+
+        KeyPermissions {
+            expired: self.is_expired(),
+            active: self.is_active(OffsetDateTime::now_utc()),
+            results,
+        }
+    }
+}
+
+impl AuthController {
+    /// Check whether the key is authorized to perform the action on the index, and which of its
+    /// rules grants it, without checking the key itself.
+    pub fn check_permission(
+        &self,
+        key: impl AsRef<str>,
+        action: Action,
+        index: Option<&str>,
+    ) -> Result<PermissionCheck> {
+        let key = self.get_key(key)?;
+        let matching_rule = key.matching_rule(action, index);
+        let expired = key.is_expired();
+        let active = key.is_active(OffsetDateTime::now_utc());
+
+        Ok(PermissionCheck {
+            authorized: matching_rule.is_some() && !expired && active,
+            matching_rule,
+            expired,
+            active,
+        })
+    }
+
+    pub fn key_permissions(&self, key: impl AsRef<str>) -> Result<KeyPermissions> {
+        Ok(self.get_key(key)?.permissions())
+    }
+}
@@ -0,0 +1,32 @@
+use std::collections::{HashMap, HashSet};
+use std::str::from_utf8;
+
+use crate::{AuthController, Key, Result};
+
+impl AuthController {
+    /// Replaces the API keys by the ones of the primary a replica follows. The keys missing from
+    /// `keys` are deleted, the others are created or updated.
+    ///
+    /// The values of the keys are derived from their id and the master key, so a replica sharing
+    /// the master key of its primary accepts the same API keys.
+    pub fn replicate_keys(&self, keys: Vec<Key>) -> Result<()> {
+        let ids: HashSet<_> = keys.iter().map(|key| key.id).collect();
+        let mut existing = HashMap::new();
+        for key in self.store.list_api_keys()? {
+            if ids.contains(&key.id) {
+                existing.insert(key.id, key.updated_at);
+            } else {
+                self.store.delete_api_key(from_utf8(&key.id)?)?;
+            }
+        }
+
+        for key in keys {
+            // The keys are replicated at each poll of the primary, the unchanged ones are skipped.
+            if existing.get(&key.id) != Some(&key.updated_at) {
+                self.store.put_api_key(key)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use time::{OffsetDateTime, UtcOffset, Weekday};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Day {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl From<Weekday> for Day {
+    fn from(weekday: Weekday) -> Self {
+        match weekday {
+            Weekday::Monday => Day::Monday,
+            Weekday::Tuesday => Day::Tuesday,
+            Weekday::Wednesday => Day::Wednesday,
+            Weekday::Thursday => Day::Thursday,
+            Weekday::Friday => Day::Friday,
+            Weekday::Saturday => Day::Saturday,
+            Weekday::Sunday => Day::Sunday,
+        }
+    }
+}
+
+/// A time of the day with a minute precision, written `HH:MM`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TimeOfDay {
+    minutes: u16,
+}
+
+impl TimeOfDay {
+    fn of(datetime: OffsetDateTime) -> Self {
+        Self {
+            minutes: datetime.hour() as u16 * 60 + datetime.minute() as u16,
+        }
+    }
+}
+
+impl FromStr for TimeOfDay {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hours, minutes) = s.split_once(':').ok_or(())?;
+        if hours.len() != 2 || minutes.len() != 2 {
+            return Err(());
+        }
+        let hours: u16 = hours.parse().map_err(drop)?;
+        let minutes: u16 = minutes.parse().map_err(drop)?;
+        // `24:00` stands for the end of the day.
+        if minutes >= 60 || hours > 24 || (hours == 24 && minutes != 0) {
+            return Err(());
+        }
+
+        Ok(Self {
+            minutes: hours * 60 + minutes,
+        })
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.minutes / 60, self.minutes % 60)
+    }
+}
+
+impl Serialize for TimeOfDay {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeOfDay {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(|_| {
+            serde::de::Error::custom(format!(
+                "`{}` is not a time of the day in the `HH:MM` format",
+                s
+            ))
+        })
+    }
+}
+
+/// The weekly window, in UTC, during which a key can be used.
+///
+/// The window starts on each of the `days`, and ends on the following day when `end` is before
+/// `start`, e.g. from `22:00` to `06:00`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Schedule {
+    pub days: Vec<Day>,
+    pub start: TimeOfDay,
+    pub end: TimeOfDay,
+}
+
+impl Schedule {
+    pub fn contains(&self, datetime: OffsetDateTime) -> bool {
+        let datetime = datetime.to_offset(UtcOffset::UTC);
+        let time = TimeOfDay::of(datetime);
+        let today = Day::from(datetime.weekday());
+        let yesterday = Day::from(datetime.weekday().previous());
+
+        if self.start <= self.end {
+            self.days.contains(&today) && self.start <= time && time < self.end
+        } else {
+            (self.days.contains(&today) && self.start <= time)
+                || (self.days.contains(&yesterday) && time < self.end)
+        }
+    }
+}
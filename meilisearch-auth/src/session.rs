@@ -0,0 +1,83 @@
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use time::{Duration, OffsetDateTime};
+
+/// How long a dashboard session remains valid after the login.
+pub const SESSION_DURATION: Duration = Duration::hours(1);
+
+const SECRET_LENGTH: usize = 32;
+
+/// The content of a session token. The token only references the key the session was opened
+/// with, it never contains the key itself.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionClaims {
+    /// The id of the API key, `None` when the session was opened with the master key.
+    pub key_id: Option<String>,
+    pub exp: i64,
+}
+
+/// Signs and verifies the session tokens.
+///
+/// The secret is generated when the instance starts and is never persisted, the sessions don't
+/// survive a restart.
+#[derive(Clone)]
+pub(crate) struct SessionSigner {
+    secret: [u8; SECRET_LENGTH],
+}
+
+impl SessionSigner {
+    pub fn new() -> Self {
+        Self {
+            secret: rand::thread_rng().gen(),
+        }
+    }
+
+    /// Returns a token of the form `payload.signature`, both hex encoded.
+    pub fn sign(&self, claims: &SessionClaims) -> String {
+        let payload = hex_encode(&serde_json::to_vec(claims).unwrap_or_default());
+        let signature = hex_encode(&self.mac(payload.as_bytes()).finalize().into_bytes());
+        format!("{}.{}", payload, signature)
+    }
+
+    /// Returns the claims of the token if it was signed by this signer and is not expired.
+    pub fn verify(&self, token: &str) -> Option<SessionClaims> {
+        let (payload, signature) = token.split_once('.')?;
+        self.mac(payload.as_bytes())
+            .verify_slice(&hex_decode(signature)?)
+            .ok()?;
+
+        let claims: SessionClaims = serde_json::from_slice(&hex_decode(payload)?).ok()?;
+        (OffsetDateTime::now_utc().unix_timestamp() < claims.exp).then(|| claims)
+    }
+
+    fn mac(&self, payload: &[u8]) -> Hmac<Sha256> {
+        // HMAC accepts keys of any length.
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.secret).unwrap();
+        mac.update(payload);
+        mac
+    }
+}
+
+/// Compares two secrets in a time that only depends on their length, for the time of a refused
+/// login not to tell how much of the secret it guessed.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+pub(crate) fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -97,6 +97,7 @@ impl HeedAuthStore {
             } else {
                 // else we create a key for each index.
                 for index in key.indexes.iter() {
+                    let index = key.namespaced(index);
                     db.put(
                         &mut wtxn,
                         &(&id, &action, Some(index.as_bytes())),
@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, ToSocketAddrs};
+use std::str::from_utf8;
+
+use hmac::{Hmac, Mac};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use time::{Duration, OffsetDateTime};
+
+use crate::error::{AuthControllerError, Result};
+use crate::session::hex_encode;
+use crate::store::KeyId;
+use crate::{AuthController, Key};
+
+/// The number of indexes listed in a usage digest.
+const TOP_INDEXES: usize = 10;
+/// The number of distinct indexes counted per key, the requests to the other indexes are only
+/// counted in the totals.
+const MAX_TRACKED_INDEXES: usize = 1000;
+
+/// How often the usage digest of a key is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestFrequency {
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub fn period(&self) -> Duration {
+        match self {
+            DigestFrequency::Daily => Duration::days(1),
+            DigestFrequency::Weekly => Duration::weeks(1),
+        }
+    }
+}
+
+/// The callback receiving the usage digests of a key.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageWebhook {
+    pub url: String,
+    pub frequency: DigestFrequency,
+    /// The beginning of the period covered by the next digest.
+    #[serde(with = "time::serde::rfc3339")]
+    pub period_start: OffsetDateTime,
+}
+
+impl UsageWebhook {
+    fn is_due(&self, now: OffsetDateTime) -> bool {
+        self.period_start + self.frequency.period() <= now
+    }
+}
+
+/// The requests of a key to an index during the period of a digest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexUsage {
+    pub index_uid: String,
+    pub requests: u64,
+}
+
+/// The usage of a key during a period, sent to its usage webhook.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageDigest {
+    /// The first characters of the key, identifying it without revealing it.
+    pub key_prefix: String,
+    pub description: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub period_start: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub period_end: OffsetDateTime,
+    pub requests: u64,
+    pub errors: u64,
+    /// The share of the requests answered with an error, 0 without requests.
+    pub error_rate: f64,
+    /// The indexes the key sent the most requests to, from the most requested one.
+    pub top_indexes: Vec<IndexUsage>,
+}
+
+/// A digest ready to be sent to the webhook of its key.
+#[derive(Debug, Clone)]
+pub struct PendingDigest {
+    pub url: String,
+    pub body: Vec<u8>,
+    /// The HMAC-SHA256 of the body keyed with the key itself, in the `sha256=<hex>` format.
+    pub signature: String,
+}
+
+#[derive(Debug, Default)]
+struct KeyUsage {
+    requests: u64,
+    errors: u64,
+    indexes: HashMap<String, u64>,
+}
+
+/// Counts the requests of each API key since its last digest.
+///
+/// The counters are kept in memory, the requests of a period interrupted by a restart are
+/// missing from its digest.
+#[derive(Default)]
+pub(crate) struct UsageCounters {
+    keys: Mutex<HashMap<KeyId, KeyUsage>>,
+}
+
+impl UsageCounters {
+    fn record(&self, id: KeyId, index: Option<&str>, is_error: bool) {
+        let mut keys = self.keys.lock();
+        let usage = keys.entry(id).or_default();
+        usage.requests += 1;
+        if is_error {
+            usage.errors += 1;
+        }
+        if let Some(index) = index {
+            match usage.indexes.get_mut(index) {
+                Some(requests) => *requests += 1,
+                None if usage.indexes.len() < MAX_TRACKED_INDEXES => {
+                    usage.indexes.insert(index.to_string(), 1);
+                }
+                None => (),
+            }
+        }
+    }
+
+    fn take(&self, id: &KeyId) -> KeyUsage {
+        self.keys.lock().remove(id).unwrap_or_default()
+    }
+}
+
+impl AuthController {
+    /// Counts a request authenticated with an API key. The master key and the invalid keys are
+    /// not counted.
+    pub fn record_usage(&self, key: &str, index: Option<&str>, is_error: bool) {
+        if self.master_key.as_deref().map_or(true, |mk| mk == key) {
+            return;
+        }
+        if let (Some(id), Ok(true)) = (
+            self.store.get_key_id(key.as_bytes()),
+            self.is_key_valid(key.as_bytes()),
+        ) {
+            self.usage.record(id, index, is_error);
+        }
+    }
+
+    /// Registers the callback receiving the usage digests of the key. The first digest covers
+    /// the requests from now on.
+    pub fn set_usage_webhook(
+        &self,
+        key: impl AsRef<str>,
+        url: String,
+        frequency: DigestFrequency,
+    ) -> Result<Key> {
+        if !is_valid_webhook_url(&url) || targets_private_network(&url) {
+            return Err(AuthControllerError::InvalidApiKeyUsageWebhook(
+                Value::String(url),
+            ));
+        }
+
+        let mut key = self.get_key(key)?;
+        self.usage.take(&key.id);
+        key.usage_webhook = Some(UsageWebhook {
+            url,
+            frequency,
+            period_start: OffsetDateTime::now_utc(),
+        });
+        self.store.put_api_key(key)
+    }
+
+    pub fn delete_usage_webhook(&self, key: impl AsRef<str>) -> Result<Key> {
+        let mut key = self.get_key(key)?;
+        key.usage_webhook = None;
+        self.store.put_api_key(key)
+    }
+
+    /// Returns the digests of the keys whose period is over, signed, and starts their next
+    /// period. No digest can be signed without a master key.
+    pub fn take_due_usage_digests(&self, now: OffsetDateTime) -> Result<Vec<PendingDigest>> {
+        let mut digests = Vec::new();
+        for mut key in self.list_keys()? {
+            let (url, period_start) = match key.usage_webhook {
+                Some(ref webhook) if webhook.is_due(now) => {
+                    (webhook.url.clone(), webhook.period_start)
+                }
+                _ => continue,
+            };
+            let secret = match self.generate_key(from_utf8(&key.id)?) {
+                Some(secret) => secret,
+                None => continue,
+            };
+
+            let usage = self.usage.take(&key.id);
+            let digest = UsageDigest::new(&key, period_start, now, usage);
+            let body = serde_json::to_vec(&digest)?;
+            digests.push(PendingDigest {
+                url,
+                signature: sign_digest(&secret, &body),
+                body,
+            });
+
+            if let Some(ref mut webhook) = key.usage_webhook {
+                webhook.period_start = now;
+            }
+            self.store.put_api_key(key)?;
+        }
+
+        Ok(digests)
+    }
+}
+
+impl UsageDigest {
+    fn new(key: &Key, period_start: OffsetDateTime, now: OffsetDateTime, usage: KeyUsage) -> Self {
+        let mut top_indexes: Vec<_> = usage
+            .indexes
+            .into_iter()
+            .map(|(index_uid, requests)| IndexUsage {
+                index_uid,
+                requests,
+            })
+            .collect();
+        top_indexes.sort_unstable_by(|a, b| {
+            b.requests
+                .cmp(&a.requests)
+                .then_with(|| a.index_uid.cmp(&b.index_uid))
+        });
+        top_indexes.truncate(TOP_INDEXES);
+
+        let error_rate = match usage.requests {
+            0 => 0.0,
+            requests => usage.errors as f64 / requests as f64,
+        };
+
+        Self {
+            key_prefix: String::from_utf8_lossy(&key.id).into_owned(),
+            description: key.description.clone(),
+            period_start,
+            period_end: now,
+            requests: usage.requests,
+            errors: usage.errors,
+            error_rate,
+            top_indexes,
+        }
+    }
+}
+
+/// Signs the body of a digest with the key it describes, so that only the owner of the key can
+/// check it.
+pub fn sign_digest(key: &str, body: &[u8]) -> String {
+    // HMAC accepts keys of any length.
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).unwrap();
+    mac.update(body);
+    format!("sha256={}", hex_encode(&mac.finalize().into_bytes()))
+}
+
+fn is_valid_webhook_url(url: &str) -> bool {
+    webhook_host(url).is_some()
+}
+
+/// Returns the host and the port of an http or https URL. The URLs with credentials are refused,
+/// their host being easy to misread.
+pub fn webhook_host(url: &str) -> Option<(&str, u16)> {
+    let (rest, default_port) = match url.strip_prefix("https://") {
+        Some(rest) => (rest, 443),
+        None => (url.strip_prefix("http://")?, 80),
+    };
+    let authority = rest.split(&['/', '?', '#'][..]).next().unwrap_or_default();
+    if authority.is_empty() || authority.contains('@') || authority.contains(char::is_whitespace) {
+        return None;
+    }
+
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(ipv6) => {
+            let (host, rest) = ipv6.split_once(']')?;
+            match rest {
+                "" => (host, None),
+                rest => (host, Some(rest.strip_prefix(':')?)),
+            }
+        }
+        None => match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    let port = match port {
+        Some(port) => port.parse().ok()?,
+        None => default_port,
+    };
+
+    (!host.is_empty()).then(|| (host, port))
+}
+
+/// Whether an address can be reached from the internet. The webhooks can't target the loopback,
+/// private, link-local or otherwise reserved addresses, which would let a key holder reach the
+/// services of the network of the instance, e.g. a cloud metadata endpoint.
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                // the shared address space of the carrier-grade NATs, 100.64.0.0/10.
+                || (a == 100 && (b & 0xc0) == 64)
+                // the reserved addresses, 240.0.0.0/4.
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4() {
+            // the IPv4-mapped and IPv4-compatible addresses, `::1` included.
+            Some(ipv4) => is_public_address(IpAddr::V4(ipv4)),
+            None => {
+                let segment = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // the unique local addresses, fc00::/7.
+                    || (segment & 0xfe00) == 0xfc00
+                    // the link-local addresses, fe80::/10.
+                    || (segment & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Whether the host of the URL resolves to an address that isn't public. The hosts that can't be
+/// resolved are accepted, the delivery of the digests resolving them again anyway.
+fn targets_private_network(url: &str) -> bool {
+    match webhook_host(url).map(|host| host.to_socket_addrs()) {
+        Some(Ok(mut addresses)) => addresses.any(|address| !is_public_address(address.ip())),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn digest_lists_the_top_indexes() {
+        let counters = UsageCounters::default();
+        let id = *b"abcdefgh";
+        for _ in 0..3 {
+            counters.record(id, Some("movies"), false);
+        }
+        counters.record(id, Some("books"), true);
+        counters.record(id, None, true);
+
+        let key = Key::default_search();
+        let now = OffsetDateTime::now_utc();
+        let digest = UsageDigest::new(&key, now - Duration::days(1), now, counters.take(&id));
+        assert_eq!(digest.requests, 5);
+        assert_eq!(digest.errors, 2);
+        assert!((digest.error_rate - 0.4).abs() < f64::EPSILON);
+        assert_eq!(
+            digest.top_indexes,
+            vec![
+                IndexUsage {
+                    index_uid: "movies".to_string(),
+                    requests: 3
+                },
+                IndexUsage {
+                    index_uid: "books".to_string(),
+                    requests: 1
+                },
+            ]
+        );
+
+        // The counters are reset by the digest.
+        assert_eq!(counters.take(&id).requests, 0);
+    }
+
+    #[test]
+    fn webhook_urls() {
+        assert!(is_valid_webhook_url("https://example.com/digest"));
+        assert!(is_valid_webhook_url("http://localhost:8080"));
+        assert!(!is_valid_webhook_url("ftp://example.com"));
+        assert!(!is_valid_webhook_url("https://"));
+        assert!(!is_valid_webhook_url("example.com"));
+        assert!(!is_valid_webhook_url("https://user@example.com"));
+
+        assert_eq!(
+            webhook_host("https://example.com/a"),
+            Some(("example.com", 443))
+        );
+        assert_eq!(
+            webhook_host("http://example.com:8080?a"),
+            Some(("example.com", 8080))
+        );
+        assert_eq!(webhook_host("http://[::1]:8080/a"), Some(("::1", 8080)));
+        assert_eq!(webhook_host("http://[::1]"), Some(("::1", 80)));
+        assert_eq!(webhook_host("http://example.com:port"), None);
+    }
+
+    #[test]
+    fn webhooks_cannot_target_the_private_network() {
+        for url in [
+            "http://127.0.0.1:7700/keys",
+            "http://localhost:7700",
+            "http://[::1]/",
+            "http://[::ffff:127.0.0.1]/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://10.0.0.1",
+            "http://192.168.1.1",
+            "http://0.0.0.0",
+            "http://[fd00::1]",
+        ] {
+            assert!(targets_private_network(url), "{}", url);
+        }
+
+        assert!(!targets_private_network("https://93.184.216.34/usage"));
+        assert!(!targets_private_network(
+            "https://[2606:2800:220:1::]/usage"
+        ));
+    }
+}
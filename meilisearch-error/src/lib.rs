@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 #[cfg_attr(feature = "test-traits", derive(proptest_derive::Arbitrary))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ResponseError {
     #[serde(skip)]
     #[cfg_attr(
@@ -20,6 +21,24 @@ pub struct ResponseError {
     error_type: String,
     #[serde(rename = "link")]
     error_link: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "test-traits", proptest(value = "None"))]
+    details: Option<ErrorDetails>,
+}
+
+/// Describes the field of the payload that caused an error, so that clients can tell which one
+/// is wrong without parsing the message.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ErrorDetails {
+    /// The path of the field in the payload.
+    pub field: String,
+    /// A description of the values the field accepts.
+    pub expected: String,
+    /// The value that was sent, `null` when the field is missing.
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub value: serde_json::Value,
 }
 
 impl ResponseError {
@@ -30,6 +49,7 @@ impl ResponseError {
             error_code: code.err_code().error_name.to_string(),
             error_type: code.type_(),
             error_link: code.url(),
+            details: None,
         }
     }
 }
@@ -53,6 +73,7 @@ where
             error_code: other.error_name(),
             error_type: other.error_type(),
             error_link: other.error_url(),
+            details: other.details(),
         }
     }
 }
@@ -92,6 +113,11 @@ pub trait ErrorCode: std::error::Error {
     fn error_type(&self) -> String {
         self.error_code().type_()
     }
+
+    /// returns the details of the invalid field causing the error, if any
+    fn details(&self) -> Option<ErrorDetails> {
+        None
+    }
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -121,6 +147,10 @@ pub enum Code {
     IndexNotFound,
     InvalidIndexUid,
     InvalidMinWordLengthForTypo,
+    IndexTemplateNotFound,
+    InvalidIndexTemplate,
+    IndexInUse,
+    IndexNotInTrash,
 
     // invalid state error
     InvalidState,
@@ -133,6 +163,11 @@ pub enum Code {
 
     Filter,
     Sort,
+    InvalidSearchDistinct,
+    InvalidSearchLocales,
+    InvalidSearchRuleset,
+    InvalidSearchBoost,
+    UnauthorizedAttributesToRetrieve,
 
     BadParameter,
     BadRequest,
@@ -141,20 +176,40 @@ pub enum Code {
     Internal,
     InvalidGeoField,
     InvalidRankingRule,
+    InvalidLocalizedAttributes,
+    InvalidTokenizationSettings,
+    InvalidRulesets,
+    InvalidStopWordsPreset,
+    InvalidSynonymsFile,
     InvalidStore,
     InvalidToken,
     MissingAuthorizationHeader,
+    MissingMasterKey,
     NoSpaceLeftOnDevice,
+    DiskQuotaExceeded,
     DumpNotFound,
     TaskNotFound,
+    ReadSessionNotFound,
     PayloadTooLarge,
     RetrieveDocument,
     SearchDocuments,
+    TooManySearchRequests,
+    TooManyRequests,
+    RouteDisabled,
+    WritesSuspended,
+    TooManyReadSessions,
+    InvalidTimeseriesQuery,
     UnsupportedMediaType,
 
     DumpAlreadyInProgress,
     DumpProcessFailed,
 
+    MigrationNotFound,
+    InvalidMigrationSource,
+
+    ReadOnlyReplica,
+    ReplicationContentUnavailable,
+
     InvalidContentType,
     MissingContentType,
     MalformedPayload,
@@ -165,7 +220,13 @@ pub enum Code {
     InvalidApiKeyActions,
     InvalidApiKeyIndexes,
     InvalidApiKeyExpiresAt,
+    InvalidApiKeyActivatesAt,
+    InvalidApiKeySchedule,
     InvalidApiKeyDescription,
+    InvalidApiKeyRetrievableAttributes,
+    InvalidApiKeyDefaultSearchParams,
+    InvalidApiKeyNamespace,
+    InvalidApiKeyUsageWebhook,
 }
 
 impl Code {
@@ -183,6 +244,16 @@ impl Code {
             // thrown when requesting an unexisting index
             IndexNotFound => ErrCode::invalid("index_not_found", StatusCode::NOT_FOUND),
             InvalidIndexUid => ErrCode::invalid("invalid_index_uid", StatusCode::BAD_REQUEST),
+            IndexTemplateNotFound => {
+                ErrCode::invalid("index_template_not_found", StatusCode::NOT_FOUND)
+            }
+            InvalidIndexTemplate => {
+                ErrCode::invalid("invalid_index_template", StatusCode::BAD_REQUEST)
+            }
+            // thrown when an index can't be compacted because it is in use
+            IndexInUse => ErrCode::invalid("index_in_use", StatusCode::CONFLICT),
+            // thrown when restoring an index that wasn't deleted or whose retention elapsed
+            IndexNotInTrash => ErrCode::invalid("index_not_in_trash", StatusCode::NOT_FOUND),
 
             // invalid state error
             InvalidState => ErrCode::internal("invalid_state", StatusCode::INTERNAL_SERVER_ERROR),
@@ -196,6 +267,24 @@ impl Code {
             }
             // invalid ranking rule
             InvalidRankingRule => ErrCode::invalid("invalid_ranking_rule", StatusCode::BAD_REQUEST),
+            // invalid locale in the localized attributes
+            InvalidLocalizedAttributes => {
+                ErrCode::invalid("invalid_localized_attributes", StatusCode::BAD_REQUEST)
+            }
+            // invalid separator, non separator or dictionary tokens
+            InvalidTokenizationSettings => {
+                ErrCode::invalid("invalid_tokenization_settings", StatusCode::BAD_REQUEST)
+            }
+            // invalid name or traffic share in the rulesets
+            InvalidRulesets => ErrCode::invalid("invalid_rulesets", StatusCode::BAD_REQUEST),
+            // unknown language in the presets of the stop words
+            InvalidStopWordsPreset => {
+                ErrCode::invalid("invalid_stop_words_preset", StatusCode::BAD_REQUEST)
+            }
+            // malformed synonyms file on a synonyms import
+            InvalidSynonymsFile => {
+                ErrCode::invalid("invalid_synonyms_file", StatusCode::BAD_REQUEST)
+            }
 
             // invalid database
             InvalidStore => {
@@ -213,6 +302,25 @@ impl Code {
             Filter => ErrCode::invalid("invalid_filter", StatusCode::BAD_REQUEST),
             // error related to sorts
             Sort => ErrCode::invalid("invalid_sort", StatusCode::BAD_REQUEST),
+            // error related to the query-time distinct attribute
+            InvalidSearchDistinct => {
+                ErrCode::invalid("invalid_search_distinct", StatusCode::BAD_REQUEST)
+            }
+            // error related to the query-time locales
+            InvalidSearchLocales => {
+                ErrCode::invalid("invalid_search_locales", StatusCode::BAD_REQUEST)
+            }
+            // error related to the ruleset asked by the search
+            InvalidSearchRuleset => {
+                ErrCode::invalid("invalid_search_ruleset", StatusCode::BAD_REQUEST)
+            }
+            // error related to the boosts of the search
+            InvalidSearchBoost => ErrCode::invalid("invalid_search_boost", StatusCode::BAD_REQUEST),
+            // error related to the attributes the API key can retrieve
+            UnauthorizedAttributesToRetrieve => ErrCode::authentication(
+                "unauthorized_attributes_to_retrieve",
+                StatusCode::FORBIDDEN,
+            ),
 
             BadParameter => ErrCode::invalid("bad_parameter", StatusCode::BAD_REQUEST),
             BadRequest => ErrCode::invalid("bad_request", StatusCode::BAD_REQUEST),
@@ -227,16 +335,42 @@ impl Code {
             MissingAuthorizationHeader => {
                 ErrCode::authentication("missing_authorization_header", StatusCode::UNAUTHORIZED)
             }
+            MissingMasterKey => {
+                ErrCode::authentication("missing_master_key", StatusCode::UNAUTHORIZED)
+            }
             TaskNotFound => ErrCode::invalid("task_not_found", StatusCode::NOT_FOUND),
             DumpNotFound => ErrCode::invalid("dump_not_found", StatusCode::NOT_FOUND),
+            ReadSessionNotFound => {
+                ErrCode::invalid("read_session_not_found", StatusCode::NOT_FOUND)
+            }
             NoSpaceLeftOnDevice => {
                 ErrCode::internal("no_space_left_on_device", StatusCode::INTERNAL_SERVER_ERROR)
             }
+            DiskQuotaExceeded => {
+                ErrCode::internal("disk_quota_exceeded", StatusCode::INSUFFICIENT_STORAGE)
+            }
             PayloadTooLarge => ErrCode::invalid("payload_too_large", StatusCode::PAYLOAD_TOO_LARGE),
             RetrieveDocument => {
                 ErrCode::internal("unretrievable_document", StatusCode::BAD_REQUEST)
             }
             SearchDocuments => ErrCode::internal("search_error", StatusCode::BAD_REQUEST),
+            InvalidTimeseriesQuery => {
+                ErrCode::invalid("invalid_timeseries_query", StatusCode::BAD_REQUEST)
+            }
+            TooManySearchRequests => ErrCode::internal(
+                "too_many_search_requests",
+                StatusCode::SERVICE_UNAVAILABLE,
+            ),
+            TooManyRequests => {
+                ErrCode::invalid("too_many_requests", StatusCode::TOO_MANY_REQUESTS)
+            }
+            RouteDisabled => ErrCode::invalid("route_disabled", StatusCode::FORBIDDEN),
+            WritesSuspended => {
+                ErrCode::internal("writes_suspended", StatusCode::SERVICE_UNAVAILABLE)
+            }
+            TooManyReadSessions => {
+                ErrCode::internal("too_many_read_sessions", StatusCode::SERVICE_UNAVAILABLE)
+            }
             UnsupportedMediaType => {
                 ErrCode::invalid("unsupported_media_type", StatusCode::UNSUPPORTED_MEDIA_TYPE)
             }
@@ -248,6 +382,18 @@ impl Code {
             DumpProcessFailed => {
                 ErrCode::internal("dump_process_failed", StatusCode::INTERNAL_SERVER_ERROR)
             }
+
+            // error related to migrations
+            MigrationNotFound => ErrCode::invalid("migration_not_found", StatusCode::NOT_FOUND),
+            InvalidMigrationSource => {
+                ErrCode::invalid("invalid_migration_source", StatusCode::BAD_REQUEST)
+            }
+
+            // error related to replication
+            ReadOnlyReplica => ErrCode::invalid("read_only_replica", StatusCode::FORBIDDEN),
+            ReplicationContentUnavailable => {
+                ErrCode::invalid("replication_content_unavailable", StatusCode::GONE)
+            }
             MissingContentType => {
                 ErrCode::invalid("missing_content_type", StatusCode::UNSUPPORTED_MEDIA_TYPE)
             }
@@ -269,9 +415,29 @@ impl Code {
             InvalidApiKeyExpiresAt => {
                 ErrCode::invalid("invalid_api_key_expires_at", StatusCode::BAD_REQUEST)
             }
+            InvalidApiKeyActivatesAt => {
+                ErrCode::invalid("invalid_api_key_activates_at", StatusCode::BAD_REQUEST)
+            }
+            InvalidApiKeySchedule => {
+                ErrCode::invalid("invalid_api_key_schedule", StatusCode::BAD_REQUEST)
+            }
             InvalidApiKeyDescription => {
                 ErrCode::invalid("invalid_api_key_description", StatusCode::BAD_REQUEST)
             }
+            InvalidApiKeyRetrievableAttributes => ErrCode::invalid(
+                "invalid_api_key_retrievable_attributes",
+                StatusCode::BAD_REQUEST,
+            ),
+            InvalidApiKeyDefaultSearchParams => ErrCode::invalid(
+                "invalid_api_key_default_search_params",
+                StatusCode::BAD_REQUEST,
+            ),
+            InvalidApiKeyNamespace => {
+                ErrCode::invalid("invalid_api_key_namespace", StatusCode::BAD_REQUEST)
+            }
+            InvalidApiKeyUsageWebhook => {
+                ErrCode::invalid("invalid_api_key_usage_webhook", StatusCode::BAD_REQUEST)
+            }
             InvalidMinWordLengthForTypo => {
                 ErrCode::invalid("invalid_min_word_length_for_typo", StatusCode::BAD_REQUEST)
             }
@@ -7,6 +7,24 @@ fn main() {
 
     #[cfg(feature = "mini-dashboard")]
     mini_dashboard::setup_mini_dashboard().expect("Could not load the mini-dashboard assets");
+
+    #[cfg(feature = "grpc")]
+    grpc::compile_protos().expect("Could not compile the protobuf definitions");
+}
+
+#[cfg(feature = "grpc")]
+mod grpc {
+    pub fn compile_protos() -> Result<(), Box<dyn std::error::Error>> {
+        // Use the bundled compiler so the build doesn't depend on a system installation of protoc.
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+        println!("cargo:rerun-if-changed=proto/meilisearch.proto");
+
+        tonic_build::configure()
+            .build_client(false)
+            .compile(&["proto/meilisearch.proto"], &["proto"])?;
+
+        Ok(())
+    }
 }
 
 #[cfg(feature = "mini-dashboard")]
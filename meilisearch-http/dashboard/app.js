@@ -0,0 +1,298 @@
+"use strict";
+
+// The dashboard only talks to the public API. It logs in once with a key and is then
+// authenticated by a session cookie, the key itself is never kept by the browser.
+const app = document.getElementById("app");
+const logout = document.getElementById("logout");
+
+function escape(value) {
+  return String(value)
+    .replace(/&/g, "&amp;")
+    .replace(/</g, "&lt;")
+    .replace(/>/g, "&gt;")
+    .replace(/"/g, "&quot;");
+}
+
+async function api(method, path, body) {
+  const response = await fetch(path, {
+    method,
+    headers: { "Content-Type": "application/json" },
+    credentials: "same-origin",
+    body: body === undefined ? undefined : JSON.stringify(body),
+  });
+  if (response.status === 204) {
+    return null;
+  }
+
+  const content = await response.json();
+  // the session is missing or expired.
+  if (response.status === 401) {
+    location.hash = "#/login";
+  }
+  if (!response.ok) {
+    throw new Error(content.message || response.statusText);
+  }
+  return content;
+}
+
+function showError(error) {
+  app.insertAdjacentHTML(
+    "afterbegin",
+    `<p class="error">${escape(error.message)}</p>`
+  );
+}
+
+function renderLogin() {
+  logout.hidden = true;
+  app.innerHTML = `
+    <h2>Log in</h2>
+    <form id="login">
+      <label>Master key or API key with the <code>dashboard</code> action</label>
+      <input type="password" name="key" autocomplete="current-password" />
+      <button type="submit">Log in</button>
+    </form>`;
+
+  document.getElementById("login").addEventListener("submit", async (event) => {
+    event.preventDefault();
+    try {
+      await api("POST", "/dashboard/login", { key: event.target.key.value });
+      location.hash = "#/overview";
+    } catch (error) {
+      showError(error);
+    }
+  });
+}
+
+async function renderOverview() {
+  const overview = await api("GET", "/dashboard/api/overview");
+  const indexes = Object.entries(overview.stats.indexes)
+    .map(
+      ([uid, stats]) => `
+        <div class="card">
+          <h3><a href="#/indexes/${encodeURIComponent(uid)}">${escape(uid)}</a></h3>
+          <p>${stats.numberOfDocuments} documents</p>
+          <p>${stats.isIndexing ? "Indexing" : "Idle"}</p>
+        </div>`
+    )
+    .join("");
+
+  app.innerHTML = `
+    <h2>Overview</h2>
+    <p>Meilisearch v${escape(overview.pkgVersion)}, database size ${overview.stats.databaseSize} bytes.</p>
+    <div class="cards">${indexes}</div>`;
+}
+
+async function renderIndexes() {
+  const indexes = await api("GET", "/dashboard/api/indexes");
+  const rows = indexes
+    .map(
+      (index) => `
+        <tr>
+          <td><a href="#/indexes/${encodeURIComponent(index.uid)}">${escape(index.uid)}</a></td>
+          <td>${escape(index.primaryKey ?? "")}</td>
+          <td>${escape(index.updatedAt)}</td>
+        </tr>`
+    )
+    .join("");
+
+  app.innerHTML = `
+    <h2>Indexes</h2>
+    <form id="create-index">
+      <input name="uid" placeholder="Index uid" required />
+      <input name="primaryKey" placeholder="Primary key (optional)" />
+      <button type="submit">Create index</button>
+    </form>
+    <table>
+      <thead><tr><th>Uid</th><th>Primary key</th><th>Updated at</th></tr></thead>
+      <tbody>${rows}</tbody>
+    </table>`;
+
+  document.getElementById("create-index").addEventListener("submit", async (event) => {
+    event.preventDefault();
+    const form = event.target;
+    try {
+      await api("POST", "/dashboard/api/indexes", {
+        uid: form.uid.value,
+        primaryKey: form.primaryKey.value || null,
+      });
+      location.hash = "#/tasks";
+    } catch (error) {
+      showError(error);
+    }
+  });
+}
+
+async function renderIndex(uid) {
+  const path = `/dashboard/api/indexes/${encodeURIComponent(uid)}`;
+  const [documents, settings] = await Promise.all([
+    api("GET", `${path}/documents?limit=20`),
+    api("GET", `${path}/settings`),
+  ]);
+  const rows = documents
+    .map((document) => `<tr><td><pre>${escape(JSON.stringify(document, null, 2))}</pre></td></tr>`)
+    .join("");
+
+  app.innerHTML = `
+    <h2>${escape(uid)}</h2>
+    <h3>Settings</h3>
+    <form id="settings">
+      <textarea name="settings">${escape(JSON.stringify(settings, null, 2))}</textarea>
+      <button type="submit">Update settings</button>
+    </form>
+    <h3>Documents</h3>
+    <table><tbody>${rows}</tbody></table>`;
+
+  document.getElementById("settings").addEventListener("submit", async (event) => {
+    event.preventDefault();
+    try {
+      const settings = JSON.parse(event.target.settings.value);
+      await api("POST", `${path}/settings`, settings);
+      location.hash = "#/tasks";
+    } catch (error) {
+      showError(error);
+    }
+  });
+}
+
+// The stream of task changes of the tasks page, closed when leaving the page.
+let taskEvents = null;
+
+function taskRow(task) {
+  const status = task.status === "processing" ? `processing (${task.progress}%)` : task.status;
+  return `
+    <tr data-task="${task.uid}">
+      <td>${task.uid}</td>
+      <td>${escape(task.indexUid)}</td>
+      <td>${escape(task.type)}</td>
+      <td>${escape(status)}</td>
+      <td>${escape(task.error ? task.error.message : "")}</td>
+      <td>${escape(task.enqueuedAt)}</td>
+    </tr>`;
+}
+
+async function renderTasks() {
+  const tasks = await api("GET", "/dashboard/api/tasks");
+  const rows = tasks.results.map(taskRow).join("");
+
+  app.innerHTML = `
+    <h2>Tasks</h2>
+    <table>
+      <thead>
+        <tr><th>Uid</th><th>Index</th><th>Type</th><th>Status</th><th>Error</th><th>Enqueued at</th></tr>
+      </thead>
+      <tbody id="tasks">${rows}</tbody>
+    </table>`;
+
+  // the table is updated live instead of polling the tasks.
+  taskEvents = new EventSource("/dashboard/api/tasks/watch");
+  taskEvents.addEventListener("message", (event) => {
+    const task = JSON.parse(event.data);
+    const row = document.querySelector(`tr[data-task="${task.uid}"]`);
+    if (row) {
+      row.outerHTML = taskRow(task);
+    } else {
+      document.getElementById("tasks").insertAdjacentHTML("afterbegin", taskRow(task));
+    }
+  });
+}
+
+async function renderKeys() {
+  const keys = await api("GET", "/dashboard/api/keys");
+  const rows = keys.results
+    .map(
+      (key) => `
+        <tr>
+          <td>${escape(key.description ?? "")}</td>
+          <td><code>${escape(key.key)}</code></td>
+          <td>${escape(key.actions.join(", "))}</td>
+          <td>${escape(key.indexes.join(", "))}</td>
+          <td>${escape(key.expiresAt ?? "never")}</td>
+          <td><button class="secondary" data-key="${escape(key.key)}">Delete</button></td>
+        </tr>`
+    )
+    .join("");
+
+  app.innerHTML = `
+    <h2>Keys</h2>
+    <form id="create-key">
+      <input name="description" placeholder="Description" />
+      <input name="actions" placeholder="Actions, comma separated" required />
+      <input name="indexes" placeholder="Indexes, comma separated" required />
+      <input name="expiresAt" placeholder="Expiration date (RFC 3339, optional)" />
+      <button type="submit">Create key</button>
+    </form>
+    <table>
+      <thead>
+        <tr><th>Description</th><th>Key</th><th>Actions</th><th>Indexes</th><th>Expires at</th><th></th></tr>
+      </thead>
+      <tbody>${rows}</tbody>
+    </table>`;
+
+  const list = (value) => value.split(",").map((item) => item.trim()).filter(Boolean);
+
+  document.getElementById("create-key").addEventListener("submit", async (event) => {
+    event.preventDefault();
+    const form = event.target;
+    try {
+      await api("POST", "/dashboard/api/keys", {
+        description: form.description.value || null,
+        actions: list(form.actions.value),
+        indexes: list(form.indexes.value),
+        expiresAt: form.expiresAt.value || null,
+      });
+      render();
+    } catch (error) {
+      showError(error);
+    }
+  });
+
+  app.querySelectorAll("button[data-key]").forEach((button) =>
+    button.addEventListener("click", async () => {
+      if (!confirm("Delete this key? The clients using it will lose their access.")) {
+        return;
+      }
+      try {
+        await api("DELETE", `/dashboard/api/keys/${encodeURIComponent(button.dataset.key)}`);
+        render();
+      } catch (error) {
+        showError(error);
+      }
+    })
+  );
+}
+
+async function render() {
+  const [, page, argument] = location.hash.split("/");
+  if (taskEvents) {
+    taskEvents.close();
+    taskEvents = null;
+  }
+  if (page === "login") {
+    return renderLogin();
+  }
+
+  logout.hidden = false;
+  try {
+    switch (page) {
+      case "indexes":
+        return argument ? await renderIndex(decodeURIComponent(argument)) : await renderIndexes();
+      case "tasks":
+        return await renderTasks();
+      case "keys":
+        return await renderKeys();
+      default:
+        return await renderOverview();
+    }
+  } catch (error) {
+    app.innerHTML = "";
+    showError(error);
+  }
+}
+
+logout.addEventListener("click", async () => {
+  await api("POST", "/dashboard/logout");
+  location.hash = "#/login";
+});
+
+window.addEventListener("hashchange", render);
+render();
@@ -0,0 +1,413 @@
+//! Provisions and renews the TLS certificate of the instance with ACME, Let's Encrypt by default.
+//!
+//! The certificate is requested in the background once the server is started: until it is
+//! issued, the TLS handshakes fail. The control of the domain is proven either by answering the
+//! `tls-alpn-01` challenges on the TLS server itself, or the `http-01` challenges on a plain HTTP
+//! server. The account, the certificate and its private key are stored in the ACME directory, so
+//! that a restarted instance reuses them until they must be renewed.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::dev::Server;
+use actix_web::{web, App, HttpResponse, HttpServer};
+use anyhow::{anyhow, bail};
+use instant_acme::{
+    Account, AuthorizationStatus, ChallengeType, Identifier, NewAccount, NewOrder, OrderStatus,
+};
+use parking_lot::RwLock;
+use rcgen::{Certificate, CertificateParams, CustomExtension, DistinguishedName};
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::{any_supported_type, CertifiedKey};
+use rustls_pemfile::{certs, pkcs8_private_keys};
+use time::OffsetDateTime;
+
+use crate::Opt;
+
+pub const LETS_ENCRYPT_DIRECTORY: &str = "https://acme-v02.api.letsencrypt.org/directory";
+
+/// The ALPN protocol negotiated by the ACME servers validating a `tls-alpn-01` challenge.
+const ACME_TLS_ALPN: &[u8] = b"acme-tls/1";
+const HTTP_CHALLENGE_PATH: &str = "/.well-known/acme-challenge/{token}";
+/// The certificate is renewed when it expires in less than this duration.
+const RENEW_BEFORE: time::Duration = time::Duration::days(30);
+/// How long to wait before retrying when a certificate couldn't be issued.
+const RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+/// The maximum delay between two checks of the expiration of the certificate.
+const CHECK_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ArgEnum)]
+pub enum AcmeChallenge {
+    #[clap(name = "http-01")]
+    Http01,
+    #[clap(name = "tls-alpn-01")]
+    TlsAlpn01,
+}
+
+impl AcmeChallenge {
+    fn challenge_type(&self) -> ChallengeType {
+        match self {
+            AcmeChallenge::Http01 => ChallengeType::Http01,
+            AcmeChallenge::TlsAlpn01 => ChallengeType::TlsAlpn01,
+        }
+    }
+}
+
+/// Serves the current certificate, or the challenge certificate to the ACME servers validating
+/// a `tls-alpn-01` challenge.
+#[derive(Default)]
+pub struct AcmeResolver {
+    certificate: RwLock<Option<Arc<CertifiedKey>>>,
+    challenge: RwLock<Option<Arc<CertifiedKey>>>,
+}
+
+impl ResolvesServerCert for AcmeResolver {
+    fn resolve(&self, client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        let is_challenge = client_hello
+            .alpn()
+            .map_or(false, |mut protocols| protocols.any(|p| p == ACME_TLS_ALPN));
+        if is_challenge {
+            self.challenge.read().clone()
+        } else {
+            self.certificate.read().clone()
+        }
+    }
+}
+
+pub struct Acme {
+    domain: String,
+    email: Option<String>,
+    challenge: AcmeChallenge,
+    directory_url: String,
+    http_addr: String,
+    dir: PathBuf,
+    resolver: Arc<AcmeResolver>,
+    /// The key authorizations of the pending `http-01` challenges, by token.
+    http_challenges: RwLock<HashMap<String, String>>,
+    expires_at: RwLock<Option<OffsetDateTime>>,
+}
+
+impl Acme {
+    /// Returns the ACME configuration of the instance, when a domain is configured. The stored
+    /// certificate is loaded if there is one.
+    pub fn from_opt(opt: &Opt) -> anyhow::Result<Option<Self>> {
+        let domain = match opt.acme_domain {
+            Some(ref domain) => domain.clone(),
+            None => return Ok(None),
+        };
+        let dir = match opt.acme_dir {
+            Some(ref dir) => dir.clone(),
+            None => opt.db_path.with_file_name("acme"),
+        };
+        fs::create_dir_all(&dir)?;
+
+        let acme = Self {
+            domain,
+            email: opt.acme_email.clone(),
+            challenge: opt.acme_challenge,
+            directory_url: opt.acme_directory_url.clone(),
+            http_addr: opt.acme_http_addr.clone(),
+            dir,
+            resolver: Arc::default(),
+            http_challenges: RwLock::default(),
+            expires_at: RwLock::default(),
+        };
+
+        match (
+            fs::read(acme.certificate_path()),
+            fs::read(acme.private_key_path()),
+        ) {
+            (Ok(chain), Ok(key)) => acme.install(&chain, &key)?,
+            _ => log::info!("No certificate stored for `{}` yet.", acme.domain),
+        }
+
+        Ok(Some(acme))
+    }
+
+    pub fn server_config(&self) -> rustls::ServerConfig {
+        let mut config = rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_cert_resolver(self.resolver.clone());
+        // The HTTP protocols are added by the server.
+        config.alpn_protocols = vec![ACME_TLS_ALPN.to_vec()];
+        config
+    }
+
+    /// Returns the plain HTTP server answering the `http-01` challenges, when they are used.
+    pub fn challenge_server(self: &Arc<Self>) -> anyhow::Result<Option<Server>> {
+        if self.challenge != AcmeChallenge::Http01 {
+            return Ok(None);
+        }
+
+        let acme = web::Data::from(self.clone());
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(acme.clone())
+                .service(web::resource(HTTP_CHALLENGE_PATH).route(web::get().to(http_challenge)))
+        })
+        .disable_signals()
+        .bind(&self.http_addr)?
+        .run();
+
+        Ok(Some(server))
+    }
+
+    /// Issues the certificate when there is none, then renews it before it expires.
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            let delay = match self.renewal_delay() {
+                Some(delay) => delay,
+                None => match self.issue().await {
+                    Ok(()) => {
+                        log::info!("Issued a certificate for `{}`.", self.domain);
+                        continue;
+                    }
+                    Err(e) => {
+                        log::error!("Could not issue a certificate for `{}`: {}", self.domain, e);
+                        RETRY_DELAY
+                    }
+                },
+            };
+
+            tokio::time::sleep(delay.min(CHECK_INTERVAL)).await;
+        }
+    }
+
+    /// Returns how long to wait before renewing the certificate, or `None` if it must be issued
+    /// now.
+    fn renewal_delay(&self) -> Option<Duration> {
+        let renew_at = (*self.expires_at.read())? - RENEW_BEFORE;
+        let delay = renew_at - OffsetDateTime::now_utc();
+        delay.is_positive().then(|| delay.unsigned_abs())
+    }
+
+    async fn issue(&self) -> anyhow::Result<()> {
+        let account = self.account().await?;
+        let identifiers = [Identifier::Dns(self.domain.clone())];
+        let (mut order, state) = account
+            .new_order(&NewOrder {
+                identifiers: &identifiers,
+            })
+            .await?;
+
+        let authorizations = order.authorizations(&state.authorizations).await?;
+        let mut challenges = Vec::new();
+        for authorization in &authorizations {
+            match authorization.status {
+                AuthorizationStatus::Pending => (),
+                AuthorizationStatus::Valid => continue,
+                status => bail!("the authorization is {:?}", status),
+            }
+
+            let challenge = authorization
+                .challenges
+                .iter()
+                .find(|c| c.r#type == self.challenge.challenge_type())
+                .ok_or_else(|| anyhow!("the {:?} challenge is not offered", self.challenge))?;
+            let key_authorization = order.key_authorization(challenge);
+            match self.challenge {
+                AcmeChallenge::Http01 => {
+                    self.http_challenges.write().insert(
+                        challenge.token.clone(),
+                        key_authorization.as_str().to_string(),
+                    );
+                }
+                AcmeChallenge::TlsAlpn01 => {
+                    let certificate =
+                        challenge_certificate(&self.domain, key_authorization.digest().as_ref())?;
+                    *self.resolver.challenge.write() = Some(Arc::new(certificate));
+                }
+            }
+            challenges.push(challenge.url.clone());
+        }
+
+        for url in &challenges {
+            order.set_challenge_ready(url).await?;
+        }
+
+        let mut delay = Duration::from_millis(250);
+        let state = loop {
+            tokio::time::sleep(delay).await;
+            let state = order.state().await?;
+            match state.status {
+                OrderStatus::Ready | OrderStatus::Invalid | OrderStatus::Valid => break state,
+                _ if delay > Duration::from_secs(30) => bail!("the challenges timed out"),
+                _ => delay *= 2,
+            }
+        };
+        self.http_challenges.write().clear();
+        self.resolver.challenge.write().take();
+        if state.status == OrderStatus::Invalid {
+            bail!("the challenges failed");
+        }
+
+        let mut params = CertificateParams::new(vec![self.domain.clone()]);
+        params.distinguished_name = DistinguishedName::new();
+        let certificate = Certificate::from_params(params)?;
+        order
+            .finalize(&certificate.serialize_request_der()?, &state.finalize)
+            .await?;
+        let chain = loop {
+            match order.certificate().await? {
+                Some(chain) => break chain,
+                None => tokio::time::sleep(Duration::from_secs(1)).await,
+            }
+        };
+
+        let key = certificate.serialize_private_key_pem();
+        self.install(chain.as_bytes(), key.as_bytes())?;
+        write_private(&self.private_key_path(), key.as_bytes())?;
+        fs::write(self.certificate_path(), chain)?;
+
+        Ok(())
+    }
+
+    /// Returns the stored ACME account, or registers a new one.
+    async fn account(&self) -> anyhow::Result<Account> {
+        let path = self.dir.join("account.json");
+        match fs::read(&path) {
+            Ok(credentials) => Ok(Account::from_credentials(serde_json::from_slice(
+                &credentials,
+            )?)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let contact: Vec<_> = self.email.iter().map(|e| format!("mailto:{}", e)).collect();
+                let contact: Vec<_> = contact.iter().map(String::as_str).collect();
+                let account = Account::create(
+                    &NewAccount {
+                        contact: &contact,
+                        terms_of_service_agreed: true,
+                        only_return_existing: false,
+                    },
+                    &self.directory_url,
+                )
+                .await?;
+                write_private(&path, &serde_json::to_vec(&account.credentials())?)?;
+                Ok(account)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Serves the certificate chain and its private key, both PEM-encoded.
+    fn install(&self, chain: &[u8], key: &[u8]) -> anyhow::Result<()> {
+        let (certificate, expires_at) = certified_key(chain, key)?;
+        *self.resolver.certificate.write() = Some(Arc::new(certificate));
+        *self.expires_at.write() = Some(expires_at);
+        Ok(())
+    }
+
+    fn certificate_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.crt", self.domain))
+    }
+
+    fn private_key_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.key", self.domain))
+    }
+}
+
+async fn http_challenge(acme: web::Data<Acme>, token: web::Path<String>) -> HttpResponse {
+    match acme.http_challenges.read().get(token.as_str()) {
+        Some(key_authorization) => HttpResponse::Ok()
+            .content_type("application/octet-stream")
+            .body(key_authorization.clone()),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+/// Returns the self-signed certificate answering a `tls-alpn-01` challenge, the digest of the key
+/// authorization is stored in its `acmeIdentifier` extension.
+fn challenge_certificate(domain: &str, digest: &[u8]) -> anyhow::Result<CertifiedKey> {
+    let mut params = CertificateParams::new(vec![domain.to_string()]);
+    params.custom_extensions = vec![CustomExtension::new_acme_identifier(digest)];
+    let certificate = Certificate::from_params(params)?;
+    let key = rustls::PrivateKey(certificate.serialize_private_key_der());
+    let key = any_supported_type(&key).map_err(|_| anyhow!("unsupported private key"))?;
+
+    Ok(CertifiedKey::new(
+        vec![rustls::Certificate(certificate.serialize_der()?)],
+        key,
+    ))
+}
+
+/// Returns the certified key of a PEM-encoded certificate chain and its PKCS8 private key, along
+/// with the expiration date of the certificate.
+fn certified_key(chain: &[u8], key: &[u8]) -> anyhow::Result<(CertifiedKey, OffsetDateTime)> {
+    let chain: Vec<_> = certs(&mut &*chain)
+        .map_err(|_| anyhow!("cannot read the certificate"))?
+        .into_iter()
+        .map(rustls::Certificate)
+        .collect();
+    let first = chain
+        .first()
+        .ok_or_else(|| anyhow!("the certificate chain is empty"))?;
+    let (_, certificate) = x509_parser::parse_x509_certificate(&first.0)
+        .map_err(|e| anyhow!("cannot parse the certificate: {}", e))?;
+    let expires_at =
+        OffsetDateTime::from_unix_timestamp(certificate.validity().not_after.timestamp())?;
+
+    let key = pkcs8_private_keys(&mut &*key)
+        .map_err(|_| anyhow!("cannot read the private key"))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("the private key is missing"))?;
+    let key = any_supported_type(&rustls::PrivateKey(key))
+        .map_err(|_| anyhow!("unsupported private key"))?;
+
+    Ok((CertifiedKey::new(chain, key), expires_at))
+}
+
+/// Writes a file only readable by the current user.
+fn write_private(path: &Path, content: &[u8]) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(content)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn loads_certificates_and_their_expiration() {
+        let mut params = CertificateParams::new(vec!["localhost".to_string()]);
+        params.not_after = rcgen::date_time_ymd(2040, 1, 1);
+        let certificate = Certificate::from_params(params).unwrap();
+
+        let (_, expires_at) = certified_key(
+            certificate.serialize_pem().unwrap().as_bytes(),
+            certificate.serialize_private_key_pem().as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(expires_at.year(), 2040);
+
+        assert!(certified_key(b"", certificate.serialize_private_key_pem().as_bytes()).is_err());
+    }
+
+    #[test]
+    fn renews_certificates_before_they_expire() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let opt = Opt {
+            db_path: temp.path().join("data.ms"),
+            acme_domain: Some("localhost".to_string()),
+            ..clap::Parser::parse_from(None as Option<&str>)
+        };
+        let acme = Acme::from_opt(&opt).unwrap().unwrap();
+        assert!(temp.path().join("acme").exists());
+        assert_eq!(acme.renewal_delay(), None);
+
+        *acme.expires_at.write() = Some(OffsetDateTime::now_utc() + time::Duration::days(60));
+        let delay = acme.renewal_delay().unwrap();
+        assert!(delay > Duration::from_secs(29 * 24 * 60 * 60));
+        assert!(delay <= Duration::from_secs(30 * 24 * 60 * 60));
+
+        *acme.expires_at.write() = Some(OffsetDateTime::now_utc() + time::Duration::days(10));
+        assert_eq!(acme.renewal_delay(), None);
+    }
+}
@@ -8,10 +8,7 @@ use actix_web::http::header::USER_AGENT;
 use actix_web::HttpRequest;
 use http::header::CONTENT_TYPE;
 use meilisearch_auth::SearchRules;
-use meilisearch_lib::index::{
-    SearchQuery, SearchResult, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
-    DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG,
-};
+use meilisearch_lib::index::{SearchQuery, SearchResult, DEFAULT_SEARCH_LIMIT};
 use meilisearch_lib::index_controller::Stats;
 use meilisearch_lib::MeiliSearch;
 use once_cell::sync::Lazy;
@@ -365,6 +362,10 @@ pub struct SearchAggregator {
     crop_marker: bool,
     matches: bool,
     crop_length: bool,
+
+    // experiments
+    // the number of succeeded searches served with each ruleset
+    rulesets: HashMap<String, usize>,
 }
 
 impl SearchAggregator {
@@ -412,14 +413,14 @@ impl SearchAggregator {
             ret.max_terms_number = q.split_whitespace().count();
         }
 
-        ret.max_limit = query.limit;
+        ret.max_limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
         ret.max_offset = query.offset.unwrap_or_default();
 
-        ret.highlight_pre_tag = query.highlight_pre_tag != DEFAULT_HIGHLIGHT_PRE_TAG;
-        ret.highlight_post_tag = query.highlight_post_tag != DEFAULT_HIGHLIGHT_POST_TAG;
-        ret.crop_marker = query.crop_marker != DEFAULT_CROP_MARKER;
-        ret.crop_length = query.crop_length != DEFAULT_CROP_LENGTH;
-        ret.matches = query.matches;
+        ret.highlight_pre_tag = query.highlight_pre_tag.is_some();
+        ret.highlight_post_tag = query.highlight_post_tag.is_some();
+        ret.crop_marker = query.crop_marker.is_some();
+        ret.crop_length = query.crop_length.is_some();
+        ret.matches = query.matches.unwrap_or(false);
 
         ret
     }
@@ -427,6 +428,10 @@ impl SearchAggregator {
     pub fn succeed(&mut self, result: &SearchResult) {
         self.total_succeeded = self.total_succeeded.saturating_add(1);
         self.time_spent.push(result.processing_time_ms as usize);
+        if let Some(ref ruleset) = result.ruleset {
+            let served = self.rulesets.entry(ruleset.clone()).or_insert(0);
+            *served = served.saturating_add(1);
+        }
     }
 
     /// Aggregate one [SearchAggregator] into another.
@@ -474,6 +479,11 @@ impl SearchAggregator {
         self.crop_marker |= other.crop_marker;
         self.matches |= other.matches;
         self.crop_length |= other.crop_length;
+
+        for (ruleset, served) in other.rulesets.into_iter() {
+            let total = self.rulesets.entry(ruleset).or_insert(0);
+            *total = total.saturating_add(served);
+        }
     }
 
     pub fn into_event(self, user: &User, event_name: &str) -> Option<Track> {
@@ -518,6 +528,9 @@ impl SearchAggregator {
                     "matches": self.matches,
                     "crop_length": self.crop_length,
                 },
+                "experiments": {
+                    "served_by_ruleset": self.rulesets,
+                },
             });
 
             Some(Track {
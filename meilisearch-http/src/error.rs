@@ -12,6 +12,10 @@ pub enum MeilisearchHttpError {
         .1.iter().map(|s| format!("`{}`", s)).collect::<Vec<_>>().join(", ")
     )]
     InvalidContentType(String, Vec<String>),
+    #[error("Too many requests were sent from this IP address. Please retry in {0} seconds.")]
+    TooManyRequests(u64),
+    #[error("The `{0}` routes are disabled on this instance.")]
+    RouteDisabled(String),
 }
 
 impl ErrorCode for MeilisearchHttpError {
@@ -19,6 +23,8 @@ impl ErrorCode for MeilisearchHttpError {
         match self {
             MeilisearchHttpError::MissingContentType(_) => Code::MissingContentType,
             MeilisearchHttpError::InvalidContentType(_, _) => Code::InvalidContentType,
+            MeilisearchHttpError::TooManyRequests(_) => Code::TooManyRequests,
+            MeilisearchHttpError::RouteDisabled(_) => Code::RouteDisabled,
         }
     }
 }
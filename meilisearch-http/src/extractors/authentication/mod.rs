@@ -1,4 +1,4 @@
-mod error;
+pub mod error;
 
 use std::marker::PhantomData;
 use std::ops::Deref;
@@ -23,6 +23,28 @@ impl<P, D> GuardedData<P, D> {
         &self.filters
     }
 
+    /// Authenticates a token received elsewhere than in the `Authorization` header.
+    pub async fn from_token(
+        auth: AuthController,
+        token: String,
+        index: Option<String>,
+        data: D,
+    ) -> Result<Self, ResponseError>
+    where
+        P: Policy + 'static,
+    {
+        let missing = token.is_empty();
+        match Self::authenticate(auth, token, index).await? {
+            Some(filters) => Ok(Self {
+                data,
+                filters,
+                _marker: PhantomData,
+            }),
+            None if missing => Err(AuthenticationError::MissingAuthorizationHeader.into()),
+            None => Err(AuthenticationError::InvalidToken.into()),
+        }
+    }
+
     async fn auth_bearer(
         auth: AuthController,
         token: String,
@@ -147,7 +169,7 @@ pub mod policies {
     }
 
     /// Extracts the key prefix used to sign the payload from the payload, without performing any validation.
-    fn extract_key_prefix(token: &str) -> Option<String> {
+    pub(crate) fn extract_key_prefix(token: &str) -> Option<String> {
         let mut validation = tenant_token_validation();
         validation.insecure_disable_signature_validation();
         let dummy_key = DecodingKey::from_secret(b"secret");
@@ -230,6 +252,13 @@ pub mod policies {
                 )
                 .ok()?;
 
+                // The search rules of the keys bound to a namespace are relative to it.
+                let namespace = auth.get_key_namespace(&api_key_prefix).ok()?;
+                let index = match (index, namespace) {
+                    (Some(index), Some(ns)) => Some(index.strip_prefix(ns.as_str())?),
+                    (index, _) => index,
+                };
+
                 // Check index access if an index restriction is provided.
                 if let Some(index) = index {
                     if !data.claims.search_rules.is_index_authorized(index) {
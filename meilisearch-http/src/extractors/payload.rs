@@ -6,6 +6,8 @@ use actix_web::{dev, web, FromRequest, HttpRequest};
 use futures::future::{ready, Ready};
 use futures::Stream;
 
+use crate::Opt;
+
 pub struct Payload {
     payload: dev::Payload,
     limit: usize,
@@ -21,6 +23,32 @@ impl PayloadConfig {
     }
 }
 
+/// The maximum size of the payloads accepted by each group of routes.
+#[derive(Debug, Clone, Copy)]
+pub struct PayloadLimits {
+    /// The limit of the routes without a limit of their own.
+    pub default: usize,
+    pub documents: usize,
+    pub search: usize,
+    pub settings: usize,
+}
+
+impl PayloadLimits {
+    pub fn from_opt(opt: &Opt) -> Self {
+        let default = opt.http_payload_size_limit.get_bytes() as usize;
+        let limit = |route_limit: Option<byte_unit::Byte>| {
+            route_limit.map_or(default, |limit| limit.get_bytes() as usize)
+        };
+
+        Self {
+            default,
+            documents: limit(opt.http_payload_size_limit_documents),
+            search: limit(opt.http_payload_size_limit_search),
+            settings: limit(opt.http_payload_size_limit_settings),
+        }
+    }
+}
+
 impl Default for PayloadConfig {
     fn default() -> Self {
         Self { limit: 256 * 1024 }
@@ -0,0 +1,177 @@
+use std::io;
+
+use actix_web::error::PayloadError;
+use actix_web::web::Bytes;
+use meilisearch_lib::index_controller::{DocumentAdditionFormat, Update};
+use meilisearch_lib::milli::update::IndexDocumentsMethod;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tonic::{Request, Response, Status, Streaming};
+
+use super::proto::documents_server::Documents;
+use super::proto::{
+    DeleteDocumentsRequest, Document, DocumentsChunk, DocumentsFormat, GetDocumentRequest,
+    TaskSummary,
+};
+use super::{json_string, to_status, token, GrpcService};
+use crate::extractors::authentication::policies::*;
+use crate::routes::RouteGroup;
+use crate::task::SummarizedTaskView;
+
+impl From<SummarizedTaskView> for TaskSummary {
+    fn from(task: SummarizedTaskView) -> Self {
+        let task = json!(task);
+        TaskSummary {
+            uid: task["uid"].as_u64().unwrap_or_default(),
+            index_uid: json_string(&task["indexUid"]).unwrap_or_default(),
+            status: json_string(&task["status"]).unwrap_or_default(),
+            r#type: json_string(&task["type"]).unwrap_or_default(),
+            enqueued_at: json_string(&task["enqueuedAt"]).unwrap_or_default(),
+        }
+    }
+}
+
+/// Forwards the content of the chunks to the payload of the document addition, the chunks are
+/// not buffered so huge additions can be streamed.
+fn chunks_to_payload(
+    first: Vec<u8>,
+    mut chunks: Streaming<DocumentsChunk>,
+) -> tokio_stream::wrappers::ReceiverStream<Result<Bytes, PayloadError>> {
+    let (sender, receiver) = mpsc::channel(1);
+    tokio::spawn(async move {
+        if sender.send(Ok(Bytes::from(first))).await.is_err() {
+            return;
+        }
+        loop {
+            let chunk = match chunks.message().await {
+                Ok(Some(chunk)) => Ok(Bytes::from(chunk.content)),
+                Ok(None) => break,
+                Err(status) => Err(PayloadError::Io(io::Error::new(
+                    io::ErrorKind::Other,
+                    status.message().to_string(),
+                ))),
+            };
+            let failed = chunk.is_err();
+            if sender.send(chunk).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+    tokio_stream::wrappers::ReceiverStream::new(receiver)
+}
+
+#[tonic::async_trait]
+impl Documents for GrpcService {
+    async fn add_documents(
+        &self,
+        request: Request<Streaming<DocumentsChunk>>,
+    ) -> Result<Response<TaskSummary>, Status> {
+        self.check_route(RouteGroup::DocumentsWrite)?;
+        let token = token(&request);
+        let mut chunks = request.into_inner();
+        let first = chunks
+            .message()
+            .await?
+            .ok_or_else(|| Status::invalid_argument("The stream doesn't contain any chunk."))?;
+
+        let meilisearch = self
+            .guard::<ActionPolicy<{ actions::DOCUMENTS_ADD }>, _>(
+                token,
+                Some(first.index_uid.clone()),
+                self.meilisearch.clone(),
+            )
+            .await?;
+
+        let format = match first.format() {
+            DocumentsFormat::Json => DocumentAdditionFormat::Json,
+            DocumentsFormat::Ndjson => DocumentAdditionFormat::Ndjson,
+            DocumentsFormat::Csv => DocumentAdditionFormat::Csv,
+        };
+        let method = if first.update {
+            IndexDocumentsMethod::UpdateDocuments
+        } else {
+            IndexDocumentsMethod::ReplaceDocuments
+        };
+        let DocumentsChunk {
+            index_uid,
+            primary_key,
+            content,
+            ..
+        } = first;
+
+        let update = Update::DocumentAddition {
+            payload: Box::new(chunks_to_payload(content, chunks)),
+            primary_key,
+            method,
+            format,
+            allow_index_creation: meilisearch.filters().allow_index_creation,
+            payload_size: None,
+        };
+        let task: SummarizedTaskView = meilisearch
+            .register_update(index_uid, update)
+            .await
+            .map_err(to_status)?
+            .into();
+
+        Ok(Response::new(task.into()))
+    }
+
+    async fn get_document(
+        &self,
+        request: Request<GetDocumentRequest>,
+    ) -> Result<Response<Document>, Status> {
+        let token = token(&request);
+        let GetDocumentRequest {
+            index_uid,
+            document_id,
+        } = request.into_inner();
+        let meilisearch = self
+            .guard::<ActionPolicy<{ actions::DOCUMENTS_GET }>, _>(
+                token,
+                Some(index_uid.clone()),
+                self.meilisearch.clone(),
+            )
+            .await?;
+
+        let document = meilisearch
+            .document(index_uid, document_id, None as Option<Vec<String>>)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(Document {
+            json: serde_json::to_vec(&document).unwrap_or_default(),
+        }))
+    }
+
+    async fn delete_documents(
+        &self,
+        request: Request<DeleteDocumentsRequest>,
+    ) -> Result<Response<TaskSummary>, Status> {
+        self.check_route(RouteGroup::DocumentsWrite)?;
+        let token = token(&request);
+        let DeleteDocumentsRequest {
+            index_uid,
+            document_ids,
+        } = request.into_inner();
+        let meilisearch = self
+            .guard::<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, _>(
+                token,
+                Some(index_uid.clone()),
+                self.meilisearch.clone(),
+            )
+            .await?;
+
+        let update = if document_ids.is_empty() {
+            Update::ClearDocuments
+        } else {
+            Update::DeleteDocuments(document_ids)
+        };
+        let task: SummarizedTaskView = meilisearch
+            .register_update(index_uid, update)
+            .await
+            .map_err(to_status)?
+            .into();
+
+        Ok(Response::new(task.into()))
+    }
+}
@@ -0,0 +1,77 @@
+use std::str;
+
+use meilisearch_auth::{error::AuthControllerError, AuthController};
+use meilisearch_error::{Code, ResponseError};
+use serde_json::json;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tonic::{Request, Response, Status};
+
+use super::proto::keys_server::Keys;
+use super::proto::{GetKeyRequest, Key, ListKeysRequest, ListKeysResponse};
+use super::{json_string, to_status, token, GrpcService};
+use crate::extractors::authentication::policies::*;
+use crate::routes::RouteGroup;
+
+fn format_date(date: OffsetDateTime) -> String {
+    date.format(&Rfc3339).unwrap_or_default()
+}
+
+fn key_message(key: meilisearch_auth::Key, auth: &AuthController) -> Key {
+    let key_id = str::from_utf8(&key.id).unwrap_or_default();
+    Key {
+        description: key.description,
+        key: auth.generate_key(key_id).unwrap_or_default(),
+        actions: key
+            .actions
+            .iter()
+            .filter_map(|action| json_string(&json!(action)))
+            .collect(),
+        indexes: key.indexes,
+        expires_at: key.expires_at.map(format_date),
+        created_at: format_date(key.created_at),
+        updated_at: format_date(key.updated_at),
+    }
+}
+
+/// The key management is reserved to the master key, like the `/keys` routes.
+#[tonic::async_trait]
+impl Keys for GrpcService {
+    async fn list_keys(
+        &self,
+        request: Request<ListKeysRequest>,
+    ) -> Result<Response<ListKeysResponse>, Status> {
+        self.check_route(RouteGroup::Keys)?;
+        let auth = self
+            .guard::<MasterPolicy, _>(token(&request), None, self.auth.clone())
+            .await?;
+
+        let results = tokio::task::spawn_blocking(move || -> Result<_, AuthControllerError> {
+            let keys = auth.list_keys()?;
+            Ok(keys.into_iter().map(|k| key_message(k, &auth)).collect())
+        })
+        .await
+        .map_err(|e| to_status(ResponseError::from_msg(e.to_string(), Code::Internal)))?
+        .map_err(to_status)?;
+
+        Ok(Response::new(ListKeysResponse { results }))
+    }
+
+    async fn get_key(&self, request: Request<GetKeyRequest>) -> Result<Response<Key>, Status> {
+        self.check_route(RouteGroup::Keys)?;
+        let auth = self
+            .guard::<MasterPolicy, _>(token(&request), None, self.auth.clone())
+            .await?;
+        let key = request.into_inner().key;
+
+        let key = tokio::task::spawn_blocking(move || -> Result<_, AuthControllerError> {
+            let key = auth.get_key(key)?;
+            Ok(key_message(key, &auth))
+        })
+        .await
+        .map_err(|e| to_status(ResponseError::from_msg(e.to_string(), Code::Internal)))?
+        .map_err(to_status)?;
+
+        Ok(Response::new(key))
+    }
+}
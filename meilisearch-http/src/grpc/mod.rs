@@ -0,0 +1,158 @@
+//! The gRPC API, served alongside the HTTP API for the backends that prefer a binary protocol
+//! and streaming document ingestion.
+//!
+//! The services share the index controller and the auth controller of the HTTP server, the keys
+//! are sent in the `authorization` metadata and checked against the same policies.
+
+use std::net::SocketAddr;
+
+use actix_web::http::StatusCode;
+use meilisearch_auth::AuthController;
+use meilisearch_error::ResponseError;
+use meilisearch_lib::MeiliSearch;
+use serde_json::Value;
+use tonic::transport::Server;
+use tonic::{Request, Status};
+
+use crate::extractors::authentication::{GuardedData, Policy};
+use crate::routes::{RouteGroup, RouteLockdown};
+use proto::documents_server::DocumentsServer;
+use proto::keys_server::KeysServer;
+use proto::search_server::SearchServer;
+use proto::tasks_server::TasksServer;
+
+mod documents;
+mod keys;
+mod search;
+mod tasks;
+
+pub mod proto {
+    tonic::include_proto!("meilisearch.v1");
+}
+
+#[derive(Clone)]
+pub struct GrpcService {
+    meilisearch: MeiliSearch,
+    auth: AuthController,
+    lockdown: RouteLockdown,
+}
+
+impl GrpcService {
+    /// Authenticates the token of a request, taken from its metadata with [`token`].
+    async fn guard<P, D>(
+        &self,
+        token: String,
+        index: Option<String>,
+        data: D,
+    ) -> Result<GuardedData<P, D>, Status>
+    where
+        P: Policy + 'static,
+    {
+        GuardedData::from_token(self.auth.clone(), token, index, data)
+            .await
+            .map_err(to_status)
+    }
+
+    /// Refuses the requests of a route group disabled on the HTTP API.
+    fn check_route(&self, group: RouteGroup) -> Result<(), Status> {
+        self.lockdown.check(group).map_err(to_status)
+    }
+}
+
+/// Serves the gRPC API until the process stops.
+pub async fn serve(
+    addr: SocketAddr,
+    meilisearch: MeiliSearch,
+    auth: AuthController,
+    lockdown: RouteLockdown,
+) -> anyhow::Result<()> {
+    let service = GrpcService {
+        meilisearch,
+        auth,
+        lockdown,
+    };
+
+    Server::builder()
+        .add_service(SearchServer::new(service.clone()))
+        .add_service(DocumentsServer::new(service.clone()))
+        .add_service(TasksServer::new(service.clone()))
+        .add_service(KeysServer::new(service))
+        .serve(addr)
+        .await?;
+
+    Ok(())
+}
+
+/// Returns the key of the `authorization` metadata, like the `Authorization` header of the HTTP
+/// routes.
+fn token<T>(request: &Request<T>) -> String {
+    request
+        .metadata()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.strip_prefix("Bearer ").unwrap_or(value))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Converts an error to the closest gRPC status, the JSON of the error is kept in the details so
+/// the clients can read its `code` like with the HTTP API.
+pub fn to_status(error: impl Into<ResponseError>) -> Status {
+    let error = error.into();
+    let code = match actix_web::ResponseError::status_code(&error) {
+        StatusCode::BAD_REQUEST | StatusCode::UNSUPPORTED_MEDIA_TYPE => {
+            tonic::Code::InvalidArgument
+        }
+        StatusCode::UNAUTHORIZED => tonic::Code::Unauthenticated,
+        StatusCode::FORBIDDEN => tonic::Code::PermissionDenied,
+        StatusCode::NOT_FOUND => tonic::Code::NotFound,
+        StatusCode::CONFLICT => tonic::Code::AlreadyExists,
+        StatusCode::PAYLOAD_TOO_LARGE | StatusCode::TOO_MANY_REQUESTS => {
+            tonic::Code::ResourceExhausted
+        }
+        StatusCode::SERVICE_UNAVAILABLE => tonic::Code::Unavailable,
+        status if status.is_server_error() => tonic::Code::Internal,
+        _ => tonic::Code::Unknown,
+    };
+    let details = serde_json::to_vec(&error).unwrap_or_default();
+
+    Status::with_details(code, error.to_string(), details.into())
+}
+
+/// Returns the JSON of a value, or nothing for `null`.
+fn json_bytes(value: &Value) -> Vec<u8> {
+    if value.is_null() {
+        Vec::new()
+    } else {
+        serde_json::to_vec(value).unwrap_or_default()
+    }
+}
+
+fn json_string(value: &Value) -> Option<String> {
+    value.as_str().map(String::from)
+}
+
+#[cfg(test)]
+mod test {
+    use meilisearch_error::Code;
+
+    use super::*;
+
+    #[test]
+    fn errors_are_converted_to_statuses() {
+        let status = to_status(ResponseError::from_msg(
+            "Index `movies` not found.".to_string(),
+            Code::IndexNotFound,
+        ));
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        assert_eq!(status.message(), "Index `movies` not found.");
+        let details: Value = serde_json::from_slice(status.details()).unwrap();
+        assert_eq!(details["code"], "index_not_found");
+
+        let status = to_status(ResponseError::from_msg(
+            "The provided API key is invalid.".to_string(),
+            Code::InvalidToken,
+        ));
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    }
+}
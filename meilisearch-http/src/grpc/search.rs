@@ -0,0 +1,143 @@
+use meilisearch_lib::index::SearchQuery;
+use serde_json::{json, Value};
+use tonic::{Request, Response, Status};
+
+use super::proto::search_server::Search;
+use super::proto::{SearchRequest, SearchResponse};
+use super::{json_bytes, to_status, token, GrpcService};
+use crate::extractors::authentication::policies::*;
+use crate::routes::indexes::search::add_search_rules;
+
+/// Builds the query like the body of `POST /indexes/{index_uid}/search`, so the missing
+/// parameters take the same defaults.
+fn search_query(request: SearchRequest) -> Result<SearchQuery, Status> {
+    let mut query = json!({});
+    if let Some(q) = request.q {
+        query["q"] = json!(q);
+    }
+    if let Some(offset) = request.offset {
+        query["offset"] = json!(offset);
+    }
+    if let Some(limit) = request.limit {
+        query["limit"] = json!(limit);
+    }
+    if let Some(filter) = request.filter {
+        query["filter"] = if filter.trim_start().starts_with('[') {
+            serde_json::from_str(&filter)
+                .map_err(|e| Status::invalid_argument(format!("Invalid filter array: {}", e)))?
+        } else {
+            Value::String(filter)
+        };
+    }
+    if !request.sort.is_empty() {
+        query["sort"] = json!(request.sort);
+    }
+    if !request.attributes_to_retrieve.is_empty() {
+        query["attributesToRetrieve"] = json!(request.attributes_to_retrieve);
+    }
+    if !request.facets_distribution.is_empty() {
+        query["facetsDistribution"] = json!(request.facets_distribution);
+    }
+
+    serde_json::from_value(query).map_err(|e| Status::invalid_argument(e.to_string()))
+}
+
+#[tonic::async_trait]
+impl Search for GrpcService {
+    async fn search(
+        &self,
+        request: Request<SearchRequest>,
+    ) -> Result<Response<SearchResponse>, Status> {
+        let token = token(&request);
+        let request = request.into_inner();
+        let index_uid = request.index_uid.clone();
+        let meilisearch = self
+            .guard::<ActionPolicy<{ actions::SEARCH }>, _>(
+                token.clone(),
+                Some(index_uid.clone()),
+                self.meilisearch.clone(),
+            )
+            .await?;
+
+        let mut query = search_query(request)?;
+        // Tenant token search_rules.
+        if let Some(search_rules) = meilisearch
+            .filters()
+            .search_rules
+            .get_index_search_rules(&index_uid)
+        {
+            add_search_rules(&mut query, search_rules);
+        }
+
+        let result = meilisearch
+            .search(index_uid, query, &token)
+            .await
+            .map_err(to_status)?;
+
+        let hits = result
+            .hits
+            .iter()
+            .map(|hit| serde_json::to_vec(hit).unwrap_or_default())
+            .collect();
+        let facets_distribution = result
+            .facets_distribution
+            .as_ref()
+            .map_or_else(Vec::new, |facets| json_bytes(&json!(facets)));
+
+        Ok(Response::new(SearchResponse {
+            hits,
+            nb_hits: result.nb_hits,
+            exhaustive_nb_hits: result.exhaustive_nb_hits,
+            query: result.query,
+            limit: result.limit as u32,
+            offset: result.offset as u32,
+            processing_time_ms: result.processing_time_ms as u64,
+            facets_distribution,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn search_query_defaults() {
+        let query = search_query(SearchRequest {
+            index_uid: "movies".to_string(),
+            q: Some("carol".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(query.q.as_deref(), Some("carol"));
+        assert_eq!(query.limit, None);
+        assert!(query.filter.is_none());
+    }
+
+    #[test]
+    fn search_query_filters() {
+        let query = search_query(SearchRequest {
+            filter: Some("genre = drama".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(query.filter, Some(json!("genre = drama")));
+
+        let query = search_query(SearchRequest {
+            filter: Some(r#"["genre = drama", ["year = 2015", "year = 2016"]]"#.to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            query.filter,
+            Some(json!(["genre = drama", ["year = 2015", "year = 2016"]]))
+        );
+
+        let error = search_query(SearchRequest {
+            filter: Some("[genre".to_string()),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,46 @@
+use serde_json::json;
+use tonic::{Request, Response, Status};
+
+use super::proto::tasks_server::Tasks;
+use super::proto::{GetTaskRequest, Task};
+use super::{json_bytes, json_string, to_status, token, GrpcService};
+use crate::extractors::authentication::policies::*;
+use crate::routes::tasks::authorized_indexes_filter;
+use crate::task::TaskView;
+
+impl From<TaskView> for Task {
+    fn from(task: TaskView) -> Self {
+        let task = json!(task);
+        Task {
+            uid: task["uid"].as_u64().unwrap_or_default(),
+            index_uid: json_string(&task["indexUid"]).unwrap_or_default(),
+            status: json_string(&task["status"]).unwrap_or_default(),
+            r#type: json_string(&task["type"]).unwrap_or_default(),
+            details: json_bytes(&task["details"]),
+            error: json_bytes(&task["error"]),
+            duration: json_string(&task["duration"]),
+            enqueued_at: json_string(&task["enqueuedAt"]).unwrap_or_default(),
+            started_at: json_string(&task["startedAt"]),
+            finished_at: json_string(&task["finishedAt"]),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Tasks for GrpcService {
+    async fn get_task(&self, request: Request<GetTaskRequest>) -> Result<Response<Task>, Status> {
+        let token = token(&request);
+        let meilisearch = self
+            .guard::<ActionPolicy<{ actions::TASKS_GET }>, _>(token, None, self.meilisearch.clone())
+            .await?;
+
+        let filters = authorized_indexes_filter(meilisearch.filters());
+        let task: TaskView = meilisearch
+            .get_task(request.into_inner().uid, filters)
+            .await
+            .map_err(to_status)?
+            .into();
+
+        Ok(Response::new(task.into()))
+    }
+}
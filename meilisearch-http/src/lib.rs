@@ -1,10 +1,14 @@
 #![allow(rustdoc::private_intra_doc_links)]
 #[macro_use]
 pub mod error;
+#[cfg(feature = "acme")]
+pub mod acme;
 pub mod analytics;
 mod task;
 #[macro_use]
 pub mod extractors;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod helpers;
 pub mod option;
 pub mod routes;
@@ -21,8 +25,9 @@ pub use option::Opt;
 
 use actix_web::{web, HttpRequest};
 
-use extractors::payload::PayloadConfig;
+use extractors::payload::{PayloadConfig, PayloadLimits};
 use meilisearch_auth::AuthController;
+use meilisearch_lib::index_controller::write_breaker::WriteBreakerThresholds;
 use meilisearch_lib::MeiliSearch;
 
 pub static AUTOBATCHING_ENABLED: AtomicBool = AtomicBool::new(false);
@@ -47,7 +52,39 @@ pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<MeiliSearch> {
         // dump
         .set_ignore_missing_dump(opt.ignore_missing_dump)
         .set_ignore_dump_if_db_exists(opt.ignore_dump_if_db_exists)
-        .set_dump_dst(opt.dumps_dir.clone());
+        .set_dump_dst(opt.dumps_dir.clone())
+        // search
+        .set_search_queue_size(opt.search_queue_size)
+        .set_search_cache_size(opt.search_cache_size)
+        // replication
+        .set_replication_log_size(opt.replication_log_size);
+
+    if let Some(ref primary) = opt.replicate_from {
+        let master_key = opt.master_key.clone().ok_or_else(|| {
+            anyhow::anyhow!("A replica must share the master key of its primary.")
+        })?;
+        meilisearch.set_replicate_from(primary.clone(), master_key);
+    }
+
+    if let Some(max_disk_usage) = opt.max_disk_usage {
+        meilisearch.set_max_disk_usage(max_disk_usage.get_bytes() as u64);
+    }
+
+    if let Some(max_open_indexes) = opt.max_open_indexes {
+        meilisearch.set_max_open_indexes(max_open_indexes);
+    }
+
+    if let Some(map_size) = opt.max_open_indexes_map_size {
+        meilisearch.set_max_open_indexes_map_size(map_size.get_bytes() as usize);
+    }
+
+    if let Some(search_pool_size) = opt.search_pool_size {
+        meilisearch.set_search_pool_size(search_pool_size);
+    }
+
+    if let Some(timeout) = opt.search_timeout_ms {
+        meilisearch.set_search_timeout(Duration::from_millis(timeout));
+    }
 
     if let Some(ref path) = opt.import_snapshot {
         meilisearch.set_import_snapshot(path.clone());
@@ -61,6 +98,37 @@ pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<MeiliSearch> {
         meilisearch.set_schedule_snapshot();
     }
 
+    if let Some(threshold) = opt.slow_query_threshold_ms {
+        meilisearch
+            .set_slow_query_threshold(Duration::from_millis(threshold))
+            .set_slow_query_log_size(opt.slow_query_log_size);
+    }
+
+    if let Some(interval) = opt.index_rebuild_interval_sec {
+        meilisearch.set_index_rebuild_interval(Duration::from_secs(interval));
+    }
+
+    if opt.index_trash_retention_sec > 0 {
+        meilisearch.set_index_trash_retention(Duration::from_secs(opt.index_trash_retention_sec));
+    }
+
+    meilisearch.set_write_breaker(WriteBreakerThresholds {
+        max_enqueued_tasks: opt.write_breaker_max_enqueued_tasks,
+        min_free_disk: opt
+            .write_breaker_min_free_disk
+            .map(|size| size.get_bytes() as u64),
+    });
+
+    if let Some(ref dir) = opt.import_dir {
+        if opt.import_dir_interval_sec == 0 {
+            anyhow::bail!("The import directory scan interval must be at least 1 second.");
+        }
+        meilisearch.set_watched_import(
+            dir.clone(),
+            Duration::from_secs(opt.import_dir_interval_sec),
+        );
+    }
+
     meilisearch.build(
         opt.db_path.clone(),
         opt.indexer_options.clone(),
@@ -75,35 +143,40 @@ pub fn configure_data(
     opt: &Opt,
     analytics: Arc<dyn Analytics>,
 ) {
-    let http_payload_size_limit = opt.http_payload_size_limit.get_bytes() as usize;
+    let limits = PayloadLimits::from_opt(opt);
     config
         .app_data(data)
         .app_data(auth)
         .app_data(web::Data::from(analytics))
-        .app_data(
-            web::JsonConfig::default()
-                .content_type(|mime| mime == mime::APPLICATION_JSON)
-                .error_handler(|err, req: &HttpRequest| match err {
-                    JsonPayloadError::ContentType => match req.headers().get(CONTENT_TYPE) {
-                        Some(content_type) => MeilisearchHttpError::InvalidContentType(
-                            content_type.to_str().unwrap_or("unknown").to_string(),
-                            vec![mime::APPLICATION_JSON.to_string()],
-                        )
-                        .into(),
-                        None => MeilisearchHttpError::MissingContentType(vec![
-                            mime::APPLICATION_JSON.to_string(),
-                        ])
-                        .into(),
-                    },
-                    err => PayloadError::from(err).into(),
-                }),
-        )
-        .app_data(PayloadConfig::new(http_payload_size_limit))
+        .app_data(json_config(limits.default))
+        .app_data(PayloadConfig::new(limits.default))
         .app_data(
             web::QueryConfig::default().error_handler(|err, _req| PayloadError::from(err).into()),
         );
 }
 
+/// The configuration of the JSON payloads, accepting at most `limit` bytes. The routes with a
+/// limit of their own register it in their scope.
+pub fn json_config(limit: usize) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(limit)
+        .content_type(|mime| mime == mime::APPLICATION_JSON)
+        .error_handler(|err, req: &HttpRequest| match err {
+            JsonPayloadError::ContentType => match req.headers().get(CONTENT_TYPE) {
+                Some(content_type) => MeilisearchHttpError::InvalidContentType(
+                    content_type.to_str().unwrap_or("unknown").to_string(),
+                    vec![mime::APPLICATION_JSON.to_string()],
+                )
+                .into(),
+                None => MeilisearchHttpError::MissingContentType(vec![
+                    mime::APPLICATION_JSON.to_string()
+                ])
+                .into(),
+            },
+            err => PayloadError::from(err).into(),
+        })
+}
+
 #[cfg(feature = "mini-dashboard")]
 pub fn dashboard(config: &mut web::ServiceConfig, enable_frontend: bool) {
     use actix_web::HttpResponse;
@@ -150,13 +223,21 @@ macro_rules! create_app {
         use actix_web::{middleware, web};
         use meilisearch_error::ResponseError;
         use meilisearch_http::error::MeilisearchHttpError;
+        use meilisearch_http::extractors::payload::PayloadLimits;
         use meilisearch_http::routes;
         use meilisearch_http::{configure_data, dashboard};
 
         App::new()
             .configure(|s| configure_data(s, $data.clone(), $auth.clone(), &$opt, $analytics))
-            .configure(routes::configure)
+            .configure(|s| routes::configure(s, PayloadLimits::from_opt(&$opt)))
             .configure(|s| dashboard(s, $enable_frontend))
+            .wrap(routes::WriteBreaker)
+            .wrap(routes::RouteLockdown::new(&$opt))
+            .wrap(routes::ReadOnlyReplica)
+            .wrap(routes::Namespace)
+            .wrap(routes::DashboardSession)
+            .wrap(routes::RateLimit::new(&$opt))
+            .wrap(routes::UsageRecorder)
             .wrap(
                 Cors::default()
                     .send_wildcard()
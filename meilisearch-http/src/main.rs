@@ -4,6 +4,8 @@ use std::sync::Arc;
 use actix_web::HttpServer;
 use clap::Parser;
 use meilisearch_auth::AuthController;
+#[cfg(feature = "acme")]
+use meilisearch_http::acme::Acme;
 use meilisearch_http::analytics;
 use meilisearch_http::analytics::Analytics;
 use meilisearch_http::{create_app, setup_meilisearch, Opt};
@@ -48,6 +50,8 @@ async fn main() -> anyhow::Result<()> {
     let meilisearch = setup_meilisearch(&opt)?;
 
     let auth_controller = AuthController::new(&opt.db_path, &opt.master_key)?;
+    meilisearch.start_replication(auth_controller.clone());
+    meilisearch.start_usage_digests(auth_controller.clone());
 
     #[cfg(all(not(debug_assertions), feature = "analytics"))]
     let (analytics, user) = if !opt.no_analytics {
@@ -60,6 +64,18 @@ async fn main() -> anyhow::Result<()> {
 
     print_launch_resume(&opt, &user);
 
+    #[cfg(feature = "grpc")]
+    if let Some(ref grpc_addr) = opt.grpc_addr {
+        let grpc = meilisearch_http::grpc::serve(
+            grpc_addr.parse()?,
+            meilisearch.clone(),
+            auth_controller.clone(),
+            meilisearch_http::routes::RouteLockdown::new(&opt),
+        );
+        tokio::try_join!(run_http(meilisearch, auth_controller, opt, analytics), grpc)?;
+        return Ok(());
+    }
+
     run_http(meilisearch, auth_controller, opt, analytics).await?;
 
     Ok(())
@@ -85,6 +101,23 @@ async fn run_http(
     // Disable signals allows the server to terminate immediately when a user enter CTRL-C
     .disable_signals();
 
+    #[cfg(feature = "acme")]
+    if let Some(acme) = Acme::from_opt(&opt)? {
+        let acme = Arc::new(acme);
+        tokio::task::spawn_local(acme.clone().run());
+
+        let server = http_server
+            .bind_rustls(&opt.http_addr, acme.server_config())?
+            .run();
+        match acme.challenge_server()? {
+            Some(challenge_server) => {
+                tokio::try_join!(server, challenge_server)?;
+            }
+            None => server.await?,
+        }
+        return Ok(());
+    }
+
     if let Some(config) = opt.get_ssl_config()? {
         http_server
             .bind_rustls(opt.http_addr, config)?
@@ -115,6 +148,10 @@ pub fn print_launch_resume(opt: &Opt, user: &str) {
 
     eprintln!("Database path:\t\t{:?}", opt.db_path);
     eprintln!("Server listening on:\t\"http://{}\"", opt.http_addr);
+    #[cfg(feature = "grpc")]
+    if let Some(ref grpc_addr) = opt.grpc_addr {
+        eprintln!("gRPC listening on:\t\"{}\"", grpc_addr);
+    }
     eprintln!("Environment:\t\t{:?}", opt.env);
     eprintln!("Commit SHA:\t\t{:?}", commit_sha.to_string());
     eprintln!("Commit date:\t\t{:?}", commit_date.to_string());
@@ -152,6 +189,11 @@ Anonymous telemetry:\t\"Enabled\""
             If you need some protection in development mode, please export a key: export MEILI_MASTER_KEY=xxx");
     }
 
+    if !opt.disabled_routes.is_empty() {
+        let groups: Vec<_> = opt.disabled_routes.iter().map(|g| g.to_string()).collect();
+        eprintln!("Disabled routes:\t{}", groups.join(", "));
+    }
+
     eprintln!();
     eprintln!("Documentation:\t\thttps://docs.meilisearch.com");
     eprintln!("Source code:\t\thttps://github.com/meilisearch/meilisearch");
@@ -16,6 +16,11 @@ use rustls::{
 use rustls_pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
 use serde::Serialize;
 
+use crate::routes::{RateLimiter, RouteGroup};
+
+#[cfg(feature = "acme")]
+use crate::acme::{AcmeChallenge, LETS_ENCRYPT_DIRECTORY};
+
 const POSSIBLE_ENV: [&str; 2] = ["development", "production"];
 
 #[derive(Debug, Clone, Parser, Serialize)]
@@ -29,6 +34,12 @@ pub struct Opt {
     #[clap(long, env = "MEILI_HTTP_ADDR", default_value = "127.0.0.1:7700")]
     pub http_addr: String,
 
+    /// The address on which the gRPC server will listen.
+    /// The gRPC server is disabled if this option is not specified.
+    #[cfg(feature = "grpc")]
+    #[clap(long, env = "MEILI_GRPC_ADDR")]
+    pub grpc_addr: Option<String>,
+
     /// The master key allowing you to do everything on the server.
     #[serde(skip)]
     #[clap(long, env = "MEILI_MASTER_KEY")]
@@ -47,18 +58,57 @@ pub struct Opt {
     #[clap(long, env = "MEILI_NO_ANALYTICS")]
     pub no_analytics: bool,
 
-    /// The maximum size, in bytes, of the main lmdb database directory
+    /// The maximum size, in bytes, of the main lmdb database directory.
+    /// It is the size of the memory map of each index, the tasks making an index grow beyond it
+    /// fail with a `database_size_limit_reached` error.
     #[clap(long, env = "MEILI_MAX_INDEX_SIZE", default_value = "100 GiB")]
     pub max_index_size: Byte,
 
+    /// The maximum disk space, in bytes, used by the database. Once it is exceeded, the document
+    /// additions, settings updates and index creations fail with a `disk_quota_exceeded` error,
+    /// while the deletions are still processed to free some space. The index files don't shrink
+    /// when documents are deleted, the index must be rebuilt to give the space back. The document
+    /// payloads are refused as soon as their `Content-Length`, or the part received so far,
+    /// exceeds the space left. Unlimited by default.
+    #[clap(long, env = "MEILI_MAX_DISK_USAGE")]
+    pub max_disk_usage: Option<Byte>,
+
+    /// The maximum number of indexes kept open at the same time, each of them holding a memory
+    /// map and its own file descriptors. The indexes are opened on their first access and the
+    /// least recently used ones are closed once this budget is reached. Unlimited by default.
+    #[clap(long, env = "MEILI_MAX_OPEN_INDEXES")]
+    pub max_open_indexes: Option<usize>,
+
+    /// The maximum total size, in bytes, of the memory maps of the open indexes. It limits the
+    /// number of open indexes to this size divided by the `max-index-size`.
+    #[clap(long, env = "MEILI_MAX_OPEN_INDEXES_MAP_SIZE")]
+    pub max_open_indexes_map_size: Option<Byte>,
+
     /// The maximum size, in bytes, of the update lmdb database directory
     #[clap(long, env = "MEILI_MAX_TASK_DB_SIZE", default_value = "100 GiB")]
     pub max_task_db_size: Byte,
 
-    /// The maximum size, in bytes, of accepted JSON payloads
+    /// The maximum size, in bytes, of accepted payloads, for the routes without a limit of their
+    /// own.
     #[clap(long, env = "MEILI_HTTP_PAYLOAD_SIZE_LIMIT", default_value = "100 MB")]
     pub http_payload_size_limit: Byte,
 
+    /// The maximum size, in bytes, of the payloads sent to the documents routes. The NDJSON
+    /// documents are streamed to disk, so this limit can safely be much larger than the others.
+    /// It defaults to the `http-payload-size-limit`.
+    #[clap(long, env = "MEILI_HTTP_PAYLOAD_SIZE_LIMIT_DOCUMENTS")]
+    pub http_payload_size_limit_documents: Option<Byte>,
+
+    /// The maximum size, in bytes, of the search requests.
+    /// It defaults to the `http-payload-size-limit`.
+    #[clap(long, env = "MEILI_HTTP_PAYLOAD_SIZE_LIMIT_SEARCH")]
+    pub http_payload_size_limit_search: Option<Byte>,
+
+    /// The maximum size, in bytes, of the payloads sent to the settings routes.
+    /// It defaults to the `http-payload-size-limit`.
+    #[clap(long, env = "MEILI_HTTP_PAYLOAD_SIZE_LIMIT_SETTINGS")]
+    pub http_payload_size_limit_settings: Option<Byte>,
+
     /// Read server certificates from CERTFILE.
     /// This should contain PEM-format certificates
     /// in the right order (the first certificate should
@@ -100,6 +150,52 @@ pub struct Opt {
     #[clap(long, env = "MEILI_SSL_TICKETS")]
     pub ssl_tickets: bool,
 
+    /// Provisions and renews the TLS certificate of this domain automatically with ACME, instead
+    /// of reading it from `ssl-cert-path` and `ssl-key-path`. The instance must be reachable
+    /// through this domain, and built with the `acme` feature.
+    #[cfg(feature = "acme")]
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_ACME_DOMAIN", conflicts_with_all = &["ssl-cert-path", "ssl-key-path"])]
+    pub acme_domain: Option<String>,
+
+    /// The contact email of the ACME account, the expiration notices are sent to it.
+    #[cfg(feature = "acme")]
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_ACME_EMAIL")]
+    pub acme_email: Option<String>,
+
+    /// How the control of the domain is proven: the `tls-alpn-01` challenges are answered by the
+    /// TLS server itself, the `http-01` ones by a plain HTTP server listening on `acme-http-addr`.
+    #[cfg(feature = "acme")]
+    #[serde(skip)]
+    #[clap(
+        long,
+        env = "MEILI_ACME_CHALLENGE",
+        arg_enum,
+        default_value = "tls-alpn-01"
+    )]
+    pub acme_challenge: AcmeChallenge,
+
+    /// The address on which the `http-01` challenges are answered, the port 80 of the domain
+    /// must lead to it.
+    #[cfg(feature = "acme")]
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_ACME_HTTP_ADDR", default_value = "0.0.0.0:80")]
+    pub acme_http_addr: String,
+
+    /// The directory url of the ACME server, Let's Encrypt by default.
+    #[cfg(feature = "acme")]
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_ACME_DIRECTORY_URL", default_value = LETS_ENCRYPT_DIRECTORY)]
+    pub acme_directory_url: String,
+
+    /// Where the ACME account and the certificates are stored.
+    /// It defaults to an `acme` directory next to the database.
+    #[cfg(feature = "acme")]
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_ACME_DIR", parse(from_os_str))]
+    pub acme_dir: Option<PathBuf>,
+
     /// Defines the path of the snapshot file to import.
     /// This option will, by default, stop the process if a database already exist or if no snapshot exists at
     /// the given path. If this option is not specified no snapshot is imported.
@@ -146,6 +242,123 @@ pub struct Opt {
     #[clap(long, env = "MEILI_LOG_LEVEL", default_value = "info")]
     pub log_level: String,
 
+    /// Log every search taking longer than this threshold, in milliseconds.
+    /// The slow query log is disabled if this option is not specified.
+    #[clap(long, env = "MEILI_SLOW_QUERY_THRESHOLD_MS")]
+    pub slow_query_threshold_ms: Option<u64>,
+
+    /// The number of slow searches kept in memory and returned by the `/slow-queries` route.
+    #[clap(long, env = "MEILI_SLOW_QUERY_LOG_SIZE", default_value = "100")]
+    pub slow_query_log_size: usize,
+
+    /// The maximum number of searches processed concurrently.
+    /// It defaults to the number of available threads.
+    #[clap(long, env = "MEILI_SEARCH_POOL_SIZE")]
+    pub search_pool_size: Option<usize>,
+
+    /// The maximum number of searches waiting to be processed. The searches received
+    /// when the queue is full are rejected with a `503 Service Unavailable`.
+    #[clap(long, env = "MEILI_SEARCH_QUEUE_SIZE", default_value = "1000")]
+    pub search_queue_size: usize,
+
+    /// The time budget of the searches that don't set a `timeoutMs`, in milliseconds. The
+    /// searches taking longer return the hits ranked so far, flagged as `degraded`. The ranking
+    /// done by the engine can't be interrupted, only the stages before and after it are bounded.
+    #[clap(long, env = "MEILI_SEARCH_TIMEOUT_MS")]
+    pub search_timeout_ms: Option<u64>,
+
+    /// The number of search results kept in memory and reused by the identical searches, until
+    /// a task is committed to their index. The search cache is disabled when it is 0.
+    #[clap(long, env = "MEILI_SEARCH_CACHE_SIZE", default_value = "0")]
+    pub search_cache_size: usize,
+
+    /// Makes the instance a read-only replica of the primary instance at this url. The replica
+    /// registers the tasks of its primary and copies its API keys; it must share the master key
+    /// of the primary and start from an empty database or from a snapshot of the primary.
+    #[clap(long, env = "MEILI_REPLICATE_FROM")]
+    pub replicate_from: Option<String>,
+
+    /// The number of processed document additions whose documents are kept for the replicas.
+    /// A replica lagging further behind can't catch up with its primary anymore.
+    #[clap(long, env = "MEILI_REPLICATION_LOG_SIZE", default_value = "0")]
+    pub replication_log_size: usize,
+
+    /// Registers a rebuild of every index at this interval, in seconds. The scheduled rebuilds
+    /// only compact the index files to reclaim their free pages.
+    /// They are not scheduled on a replica.
+    #[clap(long, env = "MEILI_INDEX_REBUILD_INTERVAL_SEC")]
+    pub index_rebuild_interval_sec: Option<u64>,
+
+    /// Keeps the deleted indexes in a trash for this period, in seconds, during which they can be
+    /// restored with `POST /indexes/{index_uid}/restore`. Their files are deleted by a purge task
+    /// once it elapses. The trashed indexes are not kept by the snapshots and the dumps.
+    /// The indexes are deleted right away when it is 0, which is the default.
+    #[clap(long, env = "MEILI_INDEX_TRASH_RETENTION_SEC", default_value = "0")]
+    pub index_trash_retention_sec: u64,
+
+    /// Refuses the document writes with a `503 Service Unavailable` once this many tasks are
+    /// waiting to be processed, until the queue is back under 90% of it. The searches are still
+    /// served. Unlimited by default.
+    #[clap(long, env = "MEILI_WRITE_BREAKER_MAX_ENQUEUED_TASKS")]
+    pub write_breaker_max_enqueued_tasks: Option<usize>,
+
+    /// Refuses the document additions with a `503 Service Unavailable` while less than this
+    /// space, in bytes, is left on the disk of the database. The deletions are still accepted.
+    #[clap(long, env = "MEILI_WRITE_BREAKER_MIN_FREE_DISK")]
+    pub write_breaker_min_free_disk: Option<Byte>,
+
+    /// Imports the documents files dropped in the subdirectories of this directory, such as
+    /// `movies/2022-05.ndjson` or `movies/2022-05.csv`, in the index named after their
+    /// subdirectory. The imported files are moved to its `.processed` subdirectory and the files
+    /// that couldn't be imported to its `.failed` one. It is not watched on a replica.
+    #[clap(long, env = "MEILI_IMPORT_DIR")]
+    pub import_dir: Option<PathBuf>,
+
+    /// How often the import directory is scanned for new files, in seconds.
+    #[clap(long, env = "MEILI_IMPORT_DIR_INTERVAL_SEC", default_value = "10")]
+    pub import_dir_interval_sec: u64,
+
+    /// A comma separated list of route groups refused to every request with a `403 Forbidden`,
+    /// whatever its API key, such as `keys,dumps,settings.write`. It locks down the instances
+    /// running without a master key, whose routes are otherwise all open.
+    /// The groups are `keys`, `dumps`, `migrations`, `replication`, `dashboard`, `stats`,
+    /// `indexes.write`, `documents.write` and `settings.write`.
+    #[clap(long, env = "MEILI_DISABLED_ROUTES", use_value_delimiter = true)]
+    pub disabled_routes: Vec<RouteGroup>,
+
+    /// The maximum number of requests an IP address can send per rate limit window.
+    /// The requests exceeding it are refused with a `429 Too Many Requests`.
+    #[clap(long, env = "MEILI_RATE_LIMIT_GLOBAL")]
+    pub rate_limit_global: Option<u32>,
+
+    /// The maximum number of searches an IP address can send per rate limit window.
+    #[clap(long, env = "MEILI_RATE_LIMIT_SEARCH")]
+    pub rate_limit_search: Option<u32>,
+
+    /// The maximum number of document writes an IP address can send per rate limit window.
+    #[clap(long, env = "MEILI_RATE_LIMIT_DOCUMENTS")]
+    pub rate_limit_documents: Option<u32>,
+
+    /// The maximum number of requests to the `/keys` routes an IP address can send per rate
+    /// limit window.
+    #[clap(long, env = "MEILI_RATE_LIMIT_KEYS")]
+    pub rate_limit_keys: Option<u32>,
+
+    /// The duration of the rate limit window, in seconds.
+    #[clap(long, env = "MEILI_RATE_LIMIT_WINDOW_SEC", default_value = "60")]
+    pub rate_limit_window_sec: u64,
+
+    /// Identifies the clients by the `Forwarded` and `X-Forwarded-For` headers instead of the
+    /// address of the connection, which is used when they are missing. Only enable it behind a
+    /// proxy setting these headers.
+    #[clap(long, env = "MEILI_RATE_LIMIT_TRUST_PROXY")]
+    pub rate_limit_trust_proxy: bool,
+
+    /// The requests counted by the rate limits, shared by all the workers.
+    #[clap(skip)]
+    #[serde(skip)]
+    pub rate_limiter: RateLimiter,
+
     #[serde(flatten)]
     #[clap(flatten)]
     pub indexer_options: IndexerOpts,
@@ -1,38 +1,78 @@
 use std::str;
 
+use actix_web::http::header::AUTHORIZATION;
 use actix_web::{web, HttpRequest, HttpResponse};
 
-use meilisearch_auth::{error::AuthControllerError, Action, AuthController, Key};
+use meilisearch_auth::{
+    error::AuthControllerError, Action, AuthController, DigestFrequency, InvalidField, Key,
+    Schedule, UsageWebhook,
+};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::extractors::{
-    authentication::{policies::*, GuardedData},
+    authentication::{error::AuthenticationError, policies::*, GuardedData, Policy},
     sequential_extractor::SeqHandler,
 };
 use meilisearch_error::{Code, ResponseError};
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
+    configure_with_policy::<MasterPolicy>(cfg);
+    cfg.service(
+        web::resource("/{api_key}/usage-webhook")
+            .route(web::put().to(SeqHandler(put_usage_webhook)))
+            .route(web::delete().to(SeqHandler(delete_usage_webhook))),
+    );
+}
+
+/// Registers the key management routes, guarded by the policy `P`. This allows the dashboard to
+/// expose the same routes under its own scope.
+pub fn configure_with_policy<P: Policy + Send + 'static>(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::resource("")
-            .route(web::post().to(SeqHandler(create_api_key)))
-            .route(web::get().to(SeqHandler(list_api_keys))),
+            .route(web::post().to(SeqHandler(create_api_key::<P>)))
+            .route(web::get().to(SeqHandler(list_api_keys::<P>))),
     )
     .service(
         web::resource("/{api_key}")
-            .route(web::get().to(SeqHandler(get_api_key)))
-            .route(web::patch().to(SeqHandler(patch_api_key)))
-            .route(web::delete().to(SeqHandler(delete_api_key))),
+            .route(web::get().to(SeqHandler(get_api_key::<P>)))
+            .route(web::patch().to(SeqHandler(patch_api_key::<P>)))
+            .route(web::delete().to(SeqHandler(delete_api_key::<P>))),
+    )
+    .service(web::resource("/{api_key}/can").route(web::post().to(SeqHandler(can_api_key::<P>))))
+    .service(
+        web::resource("/{api_key}/permissions")
+            .route(web::get().to(SeqHandler(get_api_key_permissions::<P>))),
     );
 }
 
-pub async fn create_api_key(
-    auth_controller: GuardedData<MasterPolicy, AuthController>,
+#[utoipa::path(
+    post,
+    path = "/keys",
+    tag = "Keys",
+    params(DryRunQuery),
+    request_body = KeyCreation,
+    responses(
+        (status = 201, description = "The key is created.", body = KeyView),
+        (status = 200, description = "The validation of the key, on a dry run.", body = KeyValidationView),
+        (status = 400, description = "The key is invalid.", body = ResponseError),
+    ),
+    security(("apiKey" = []))
+)]
+pub async fn create_api_key<P: Policy + Send + 'static>(
+    auth_controller: GuardedData<P, AuthController>,
     body: web::Json<Value>,
+    params: web::Query<DryRunQuery>,
     _req: HttpRequest,
 ) -> Result<HttpResponse, ResponseError> {
     let v = body.into_inner();
+    if params.dry_run {
+        let errors = auth_controller.validate_key_creation(&v);
+        return Ok(HttpResponse::Ok().json(KeyValidationView::from(errors)));
+    }
+
     let res = tokio::task::spawn_blocking(move || -> Result<_, AuthControllerError> {
         let key = auth_controller.create_key(v)?;
         Ok(KeyView::from_key(key, &auth_controller))
@@ -43,8 +83,15 @@ pub async fn create_api_key(
     Ok(HttpResponse::Created().json(res))
 }
 
-pub async fn list_api_keys(
-    auth_controller: GuardedData<MasterPolicy, AuthController>,
+#[utoipa::path(
+    get,
+    path = "/keys",
+    tag = "Keys",
+    responses((status = 200, description = "All the keys.", body = KeyListView)),
+    security(("apiKey" = []))
+)]
+pub async fn list_api_keys<P: Policy + Send + 'static>(
+    auth_controller: GuardedData<P, AuthController>,
     _req: HttpRequest,
 ) -> Result<HttpResponse, ResponseError> {
     let res = tokio::task::spawn_blocking(move || -> Result<_, AuthControllerError> {
@@ -61,8 +108,19 @@ pub async fn list_api_keys(
     Ok(HttpResponse::Ok().json(KeyListView::from(res)))
 }
 
-pub async fn get_api_key(
-    auth_controller: GuardedData<MasterPolicy, AuthController>,
+#[utoipa::path(
+    get,
+    path = "/keys/{api_key}",
+    tag = "Keys",
+    params(AuthParam),
+    responses(
+        (status = 200, description = "The key.", body = KeyView),
+        (status = 404, description = "The key doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = []))
+)]
+pub async fn get_api_key<P: Policy + Send + 'static>(
+    auth_controller: GuardedData<P, AuthController>,
     path: web::Path<AuthParam>,
 ) -> Result<HttpResponse, ResponseError> {
     let api_key = path.into_inner().api_key;
@@ -76,13 +134,36 @@ pub async fn get_api_key(
     Ok(HttpResponse::Ok().json(res))
 }
 
-pub async fn patch_api_key(
-    auth_controller: GuardedData<MasterPolicy, AuthController>,
+#[utoipa::path(
+    patch,
+    path = "/keys/{api_key}",
+    tag = "Keys",
+    params(AuthParam, DryRunQuery),
+    request_body = KeyUpdate,
+    responses(
+        (status = 200, description = "The updated key, or its validation on a dry run.", body = KeyView),
+        (status = 400, description = "The update is invalid.", body = ResponseError),
+        (status = 404, description = "The key doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = []))
+)]
+pub async fn patch_api_key<P: Policy + Send + 'static>(
+    auth_controller: GuardedData<P, AuthController>,
     body: web::Json<Value>,
     path: web::Path<AuthParam>,
+    params: web::Query<DryRunQuery>,
 ) -> Result<HttpResponse, ResponseError> {
     let api_key = path.into_inner().api_key;
     let body = body.into_inner();
+    if params.dry_run {
+        let errors = tokio::task::spawn_blocking(move || {
+            auth_controller.validate_key_update(&api_key, &body)
+        })
+        .await
+        .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))??;
+        return Ok(HttpResponse::Ok().json(KeyValidationView::from(errors)));
+    }
+
     let res = tokio::task::spawn_blocking(move || -> Result<_, AuthControllerError> {
         let key = auth_controller.update_key(&api_key, body)?;
         Ok(KeyView::from_key(key, &auth_controller))
@@ -93,8 +174,19 @@ pub async fn patch_api_key(
     Ok(HttpResponse::Ok().json(res))
 }
 
-pub async fn delete_api_key(
-    auth_controller: GuardedData<MasterPolicy, AuthController>,
+#[utoipa::path(
+    delete,
+    path = "/keys/{api_key}",
+    tag = "Keys",
+    params(AuthParam),
+    responses(
+        (status = 204, description = "The key is deleted."),
+        (status = 404, description = "The key doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = []))
+)]
+pub async fn delete_api_key<P: Policy + Send + 'static>(
+    auth_controller: GuardedData<P, AuthController>,
     path: web::Path<AuthParam>,
 ) -> Result<HttpResponse, ResponseError> {
     let api_key = path.into_inner().api_key;
@@ -105,20 +197,250 @@ pub async fn delete_api_key(
     Ok(HttpResponse::NoContent().finish())
 }
 
-#[derive(Deserialize)]
+#[utoipa::path(
+    post,
+    path = "/keys/{api_key}/can",
+    tag = "Keys",
+    params(AuthParam),
+    request_body = PermissionQuery,
+    responses(
+        (status = 200, description = "Whether the key is authorized, and the rule authorizing it.", body = Object),
+        (status = 404, description = "The key doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = []))
+)]
+pub async fn can_api_key<P: Policy + Send + 'static>(
+    auth_controller: GuardedData<P, AuthController>,
+    body: web::Json<PermissionQuery>,
+    path: web::Path<AuthParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let api_key = path.into_inner().api_key;
+    let PermissionQuery { action, index } = body.into_inner();
+    let res = tokio::task::spawn_blocking(move || {
+        auth_controller.check_permission(&api_key, action, index.as_deref())
+    })
+    .await
+    .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))??;
+
+    Ok(HttpResponse::Ok().json(res))
+}
+
+#[utoipa::path(
+    get,
+    path = "/keys/{api_key}/permissions",
+    tag = "Keys",
+    params(AuthParam),
+    responses(
+        (status = 200, description = "The indexes of each action the key can perform.", body = Object),
+        (status = 404, description = "The key doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = []))
+)]
+pub async fn get_api_key_permissions<P: Policy + Send + 'static>(
+    auth_controller: GuardedData<P, AuthController>,
+    path: web::Path<AuthParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let api_key = path.into_inner().api_key;
+    let res = tokio::task::spawn_blocking(move || auth_controller.key_permissions(&api_key))
+        .await
+        .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))??;
+
+    Ok(HttpResponse::Ok().json(res))
+}
+
+#[utoipa::path(
+    put,
+    path = "/keys/{api_key}/usage-webhook",
+    tag = "Keys",
+    params(AuthParam),
+    request_body = UsageWebhookSettings,
+    responses(
+        (status = 200, description = "The webhook is registered.", body = Object),
+        (status = 400, description = "The URL of the webhook is invalid.", body = ResponseError),
+        (status = 404, description = "The key doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = []))
+)]
+pub async fn put_usage_webhook(
+    req: HttpRequest,
+    body: web::Json<UsageWebhookSettings>,
+    path: web::Path<AuthParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let api_key = path.into_inner().api_key;
+    let auth_controller = key_owner_auth(&req, &api_key)?;
+    let UsageWebhookSettings { url, frequency } = body.into_inner();
+    let res = tokio::task::spawn_blocking(move || -> Result<_, AuthControllerError> {
+        let key = auth_controller.set_usage_webhook(&api_key, url, frequency)?;
+        Ok(key.usage_webhook)
+    })
+    .await
+    .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))??;
+
+    Ok(HttpResponse::Ok().json(res))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/keys/{api_key}/usage-webhook",
+    tag = "Keys",
+    params(AuthParam),
+    responses(
+        (status = 204, description = "The webhook is removed."),
+        (status = 404, description = "The key doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = []))
+)]
+pub async fn delete_usage_webhook(
+    req: HttpRequest,
+    path: web::Path<AuthParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let api_key = path.into_inner().api_key;
+    let auth_controller = key_owner_auth(&req, &api_key)?;
+    tokio::task::spawn_blocking(move || auth_controller.delete_usage_webhook(&api_key))
+        .await
+        .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))??;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Returns the auth controller if the request is sent with the master key or with the key
+/// itself, the owner of a key can thus manage its usage webhook without the master key.
+fn key_owner_auth(req: &HttpRequest, api_key: &str) -> Result<AuthController, ResponseError> {
+    let auth = req
+        .app_data::<AuthController>()
+        .cloned()
+        .ok_or(AuthenticationError::IrretrievableState)?;
+    let master_key = match auth.get_master_key() {
+        Some(master_key) => master_key,
+        None => return Ok(auth),
+    };
+
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .ok_or(AuthenticationError::MissingAuthorizationHeader)?;
+    if token == master_key || (token == api_key && auth.is_key_valid(token.as_bytes())?) {
+        Ok(auth)
+    } else {
+        Err(AuthenticationError::InvalidToken.into())
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
 pub struct AuthParam {
+    /// The key itself.
     api_key: String,
 }
 
-#[derive(Debug, Serialize)]
+/// The body of a key creation, only documented since the handler validates the raw JSON.
+#[derive(Deserialize, ToSchema)]
+#[allow(dead_code)]
 #[serde(rename_all = "camelCase")]
-struct KeyView {
+pub(crate) struct KeyCreation {
+    description: Option<String>,
+    #[schema(example = json!(["search", "documents.add"]))]
+    actions: Vec<String>,
+    #[schema(example = json!(["products"]))]
+    indexes: Vec<String>,
+    /// The attributes the key can retrieve when searching, all of them when it is omitted.
+    #[schema(example = json!(["title", "price"]))]
+    retrievable_attributes: Option<Vec<String>>,
+    /// The parameters of the searches of the key that omit them, any search parameter but `q`.
+    #[schema(value_type = Object, example = json!({ "limit": 10, "attributesToHighlight": ["title"] }))]
+    default_search_params: Option<Map<String, Value>>,
+    /// A prefix added to the index uids of the requests of the key, which only sees the indexes of
+    /// its namespace. Only alphanumeric characters, `-` and `_`.
+    #[schema(example = "acme_")]
+    namespace: Option<String>,
+    /// An RFC 3339 date before which the key can't be used.
+    activates_at: Option<String>,
+    /// An RFC 3339 date, or `null` for a key that never expires.
+    expires_at: Option<String>,
+    /// The weekly window, in UTC, outside of which the key can't be used.
+    #[schema(value_type = Object, example = json!({ "days": ["monday", "friday"], "start": "08:00", "end": "20:00" }))]
+    schedule: Option<Schedule>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct DryRunQuery {
+    /// Only validates the payload, without creating or updating the key.
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// An action to check the authorization of a key against.
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct PermissionQuery {
+    #[schema(value_type = String, example = "documents.add")]
+    action: Action,
+    /// The index the action is performed on, any index when it is omitted.
+    #[schema(example = "products")]
+    index: Option<String>,
+}
+
+/// The callback receiving the signed usage digests of a key. The `X-Meilisearch-Signature`
+/// header of a digest is the HMAC-SHA256 of its body keyed with the key itself.
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct UsageWebhookSettings {
+    #[schema(example = "https://example.com/meilisearch-usage")]
+    url: String,
+    #[schema(value_type = String, example = "daily")]
+    frequency: DigestFrequency,
+}
+
+/// The body of a key update, the omitted fields are kept as they are.
+#[derive(Deserialize, ToSchema)]
+#[allow(dead_code)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KeyUpdate {
+    description: Option<String>,
+    actions: Option<Vec<String>>,
+    indexes: Option<Vec<String>>,
+    retrievable_attributes: Option<Vec<String>>,
+    #[schema(value_type = Object)]
+    default_search_params: Option<Map<String, Value>>,
+    namespace: Option<String>,
+    activates_at: Option<String>,
+    expires_at: Option<String>,
+    #[schema(value_type = Object)]
+    schedule: Option<Schedule>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KeyView {
     description: Option<String>,
     key: String,
+    #[schema(value_type = Vec<String>)]
     actions: Vec<Action>,
     indexes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retrievable_attributes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Object)]
+    default_search_params: Option<Map<String, Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace: Option<String>,
+    #[serde(
+        serialize_with = "time::serde::rfc3339::option::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    activates_at: Option<OffsetDateTime>,
     #[serde(serialize_with = "time::serde::rfc3339::option::serialize")]
     expires_at: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Object)]
+    schedule: Option<Schedule>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Object)]
+    usage_webhook: Option<UsageWebhook>,
     #[serde(serialize_with = "time::serde::rfc3339::serialize")]
     created_at: OffsetDateTime,
     #[serde(serialize_with = "time::serde::rfc3339::serialize")]
@@ -135,15 +457,54 @@ impl KeyView {
             key: generated_key,
             actions: key.actions,
             indexes: key.indexes,
+            retrievable_attributes: key.retrievable_attributes,
+            default_search_params: key.default_search_params,
+            namespace: key.namespace,
+            activates_at: key.activates_at,
             expires_at: key.expires_at,
+            schedule: key.schedule,
+            usage_webhook: key.usage_webhook,
             created_at: key.created_at,
             updated_at: key.updated_at,
         }
     }
 }
 
-#[derive(Debug, Serialize)]
-struct KeyListView {
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct InvalidFieldView {
+    #[schema(value_type = String)]
+    field: &'static str,
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    error: ResponseError,
+}
+
+/// The result of a dry run, listing the error of each invalid field.
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct KeyValidationView {
+    valid: bool,
+    errors: Vec<InvalidFieldView>,
+}
+
+impl From<Vec<InvalidField>> for KeyValidationView {
+    fn from(errors: Vec<InvalidField>) -> Self {
+        let errors: Vec<_> = errors
+            .into_iter()
+            .map(|InvalidField { field, error }| InvalidFieldView {
+                field,
+                error: error.into(),
+            })
+            .collect();
+
+        Self {
+            valid: errors.is_empty(),
+            errors,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct KeyListView {
     results: Vec<KeyView>,
 }
 
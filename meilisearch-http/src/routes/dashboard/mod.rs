@@ -0,0 +1,174 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_auth::AuthController;
+use meilisearch_error::{Code, ResponseError};
+use meilisearch_lib::MeiliSearch;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::extractors::authentication::error::AuthenticationError;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::api_key;
+
+mod session;
+
+pub use session::DashboardSession;
+
+/// The dashboard is a single page application embedded in the binary when it is built with the
+/// `dashboard` feature, the admin routes are always served. The assets are public, the
+/// application logs in with the master key or an API key holding the `dashboard` action, and then
+/// uses the API under `/dashboard/api` with the session cookie it receives, see
+/// `DashboardSession`. The overview is reserved to these keys, and
+/// the key management to the master key, since a key could otherwise create keys granting more
+/// than itself.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/login").route(web::post().to(login)))
+        .service(web::resource("/logout").route(web::post().to(logout)));
+
+    cfg.service(
+        web::scope("/api")
+            .service(web::resource("/overview").route(web::get().to(SeqHandler(get_overview))))
+            .service(web::scope("/keys").configure(api_key::configure_with_policy::<MasterPolicy>)),
+    );
+
+    #[cfg(feature = "dashboard")]
+    assets::configure(cfg);
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct LoginRequest {
+    key: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LoginResponse {
+    #[serde(with = "time::serde::rfc3339")]
+    expires_at: OffsetDateTime,
+}
+
+/// Exchanges the key for a session cookie, so the key doesn't need to be kept by the browser.
+#[utoipa::path(
+    post,
+    path = "/dashboard/login",
+    tag = "Dashboard",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "The session cookie is set.", body = LoginResponse),
+        (status = 403, description = "The key can't open a dashboard session.", body = ResponseError),
+        (status = 401, description = "No master key is set.", body = ResponseError),
+    )
+)]
+pub async fn login(
+    body: web::Json<LoginRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ResponseError> {
+    let auth = req
+        .app_data::<AuthController>()
+        .cloned()
+        .ok_or(AuthenticationError::IrretrievableState)?;
+    let key = body.into_inner().key;
+    let secure = is_https(&req);
+
+    let (token, expires_at) = tokio::task::spawn_blocking(move || auth.create_session(&key))
+        .await
+        .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))??;
+
+    Ok(HttpResponse::Ok()
+        .cookie(session::session_cookie(token, secure))
+        .json(LoginResponse { expires_at }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/dashboard/logout",
+    tag = "Dashboard",
+    responses((status = 204, description = "The session cookie is removed."))
+)]
+pub async fn logout(req: HttpRequest) -> HttpResponse {
+    let mut cookie = session::session_cookie(String::new(), is_https(&req));
+    cookie.make_removal();
+
+    HttpResponse::NoContent().cookie(cookie).finish()
+}
+
+/// Whether the dashboard is served over HTTPS, by Meilisearch or by a proxy in front of it.
+fn is_https(req: &HttpRequest) -> bool {
+    req.connection_info().scheme() == "https"
+}
+
+/// Everything the home page of the dashboard displays, in a single request.
+#[utoipa::path(
+    get,
+    path = "/dashboard/api/overview",
+    tag = "Dashboard",
+    responses((status = 200, description = "The version and the stats of the instance.", body = Object)),
+    security(("apiKey" = ["dashboard"]))
+)]
+pub async fn get_overview(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DASHBOARD }>, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    let search_rules = &meilisearch.filters().search_rules;
+    let stats = meilisearch.get_all_stats(search_rules).await?;
+
+    let response = json!({
+        "pkgVersion": env!("CARGO_PKG_VERSION"),
+        "stats": stats,
+    });
+
+    debug!("returns: {:?}", response);
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[cfg(feature = "dashboard")]
+mod assets {
+    use actix_web::{web, HttpResponse};
+    use include_dir::{include_dir, Dir};
+
+    static ASSETS: Dir = include_dir!("$CARGO_MANIFEST_DIR/dashboard");
+
+    pub fn configure(cfg: &mut web::ServiceConfig) {
+        cfg.service(web::resource("").route(web::get().to(index)))
+            .service(web::resource("/{path:.*}").route(web::get().to(asset)));
+    }
+
+    async fn index() -> HttpResponse {
+        serve("index.html")
+    }
+
+    /// The paths that don't match an asset are routes of the application, they are answered with
+    /// the application itself.
+    async fn asset(path: web::Path<String>) -> HttpResponse {
+        let path = path.into_inner();
+        if ASSETS.get_file(&path).is_some() {
+            serve(&path)
+        } else {
+            serve("index.html")
+        }
+    }
+
+    fn serve(path: &str) -> HttpResponse {
+        match ASSETS.get_file(path) {
+            Some(file) => HttpResponse::Ok()
+                .content_type(content_type(path))
+                .body(file.contents()),
+            None => HttpResponse::NotFound().finish(),
+        }
+    }
+
+    fn content_type(path: &str) -> mime::Mime {
+        match path.rsplit_once('.').map(|(_, extension)| extension) {
+            Some("html") => mime::TEXT_HTML_UTF_8,
+            Some("js") => mime::APPLICATION_JAVASCRIPT_UTF_8,
+            Some("css") => mime::TEXT_CSS_UTF_8,
+            Some("svg") => mime::IMAGE_SVG,
+            Some("json") => mime::APPLICATION_JSON,
+            Some("png") => mime::IMAGE_PNG,
+            _ => mime::APPLICATION_OCTET_STREAM,
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::pin::Pin;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use actix_web::cookie::{time::Duration, Cookie, SameSite};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderValue, AUTHORIZATION};
+use actix_web::http::uri::{PathAndQuery, Uri};
+use actix_web::HttpMessage;
+use futures::future::{ready, Ready};
+use futures::Future;
+use meilisearch_auth::{AuthController, SESSION_DURATION};
+
+pub const SESSION_COOKIE: &str = "meilisearch-dashboard-session";
+
+/// The routes the cookie of a session is sent to.
+const DASHBOARD_PATH: &str = "/dashboard";
+/// The routes a session authenticates.
+const DASHBOARD_API_PATH: &str = "/dashboard/api";
+
+/// The routes of the API the dashboard uses, that it reaches under `/dashboard/api`.
+const FORWARDED_ROUTES: &[&str] = &["/indexes", "/tasks"];
+
+/// Builds the cookie holding a session token. The cookie is not readable from the scripts of the
+/// page and is never sent along with requests initiated by other sites, nor over plain HTTP when
+/// the dashboard is served over HTTPS.
+pub fn session_cookie(token: String, secure: bool) -> Cookie<'static> {
+    Cookie::build(SESSION_COOKIE, token)
+        .path(DASHBOARD_PATH)
+        .http_only(true)
+        .secure(secure)
+        .same_site(SameSite::Strict)
+        .max_age(Duration::seconds(SESSION_DURATION.whole_seconds()))
+        .finish()
+}
+
+/// Authenticates the requests of the dashboard carrying a session cookie instead of an
+/// `Authorization` header, as if they were made with the key the session was opened with. Only the
+/// routes under `/dashboard/api` accept a session: the requests of the dashboard to the API, such as
+/// `/dashboard/api/indexes`, are forwarded to the API routes once authenticated, for the other
+/// middlewares to check them like any request of the key.
+pub struct DashboardSession;
+
+impl<S, B> Transform<S, ServiceRequest> for DashboardSession
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = DashboardSessionMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(DashboardSessionMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct DashboardSessionMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for DashboardSessionMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        if !is_session_route(req.path()) {
+            return Box::pin(service.call(req));
+        }
+
+        let session = match (
+            req.headers().contains_key(AUTHORIZATION),
+            req.cookie(SESSION_COOKIE),
+        ) {
+            (false, Some(cookie)) => req
+                .app_data::<AuthController>()
+                .cloned()
+                .map(|auth| (auth, cookie.value().to_string())),
+            _ => None,
+        };
+        forward_api_route(&mut req);
+
+        Box::pin(async move {
+            if let Some((auth, session)) = session {
+                // The session is checked against the keys stored in LMDB.
+                let key = tokio::task::spawn_blocking(move || auth.authenticate_session(&session))
+                    .await
+                    .ok()
+                    .flatten();
+                let value =
+                    key.and_then(|key| HeaderValue::from_str(&format!("Bearer {}", key)).ok());
+                if let Some(value) = value {
+                    req.headers_mut().insert(AUTHORIZATION, value);
+                }
+            }
+
+            service.call(req).await
+        })
+    }
+}
+
+fn is_session_route(path: &str) -> bool {
+    path.strip_prefix(DASHBOARD_API_PATH)
+        .map_or(false, |rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Returns the API route a route under `/dashboard/api` stands for, if it is one of the
+/// `FORWARDED_ROUTES`.
+fn api_route(path: &str) -> Option<&str> {
+    let route = path.strip_prefix(DASHBOARD_API_PATH)?;
+    FORWARDED_ROUTES
+        .iter()
+        .any(|prefix| {
+            route
+                .strip_prefix(prefix)
+                .map_or(false, |rest| rest.is_empty() || rest.starts_with('/'))
+        })
+        .then(|| route)
+}
+
+fn forward_api_route(req: &mut ServiceRequest) {
+    let path = match api_route(req.path()) {
+        Some(path) => path.to_string(),
+        None => return,
+    };
+    let path_and_query = match req.uri().query() {
+        Some(query) => format!("{}?{}", path, query),
+        None => path,
+    };
+    let mut parts = req.uri().clone().into_parts();
+    parts.path_and_query = PathAndQuery::from_str(&path_and_query).ok();
+    if let Ok(uri) = Uri::from_parts(parts) {
+        req.match_info_mut().get_mut().update(&uri);
+        req.head_mut().uri = uri;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn api_routes_are_forwarded() {
+        assert_eq!(api_route("/dashboard/api/indexes"), Some("/indexes"));
+        assert_eq!(
+            api_route("/dashboard/api/indexes/movies/documents"),
+            Some("/indexes/movies/documents")
+        );
+        assert_eq!(
+            api_route("/dashboard/api/tasks/watch"),
+            Some("/tasks/watch")
+        );
+        assert_eq!(api_route("/dashboard/api/overview"), None);
+        assert_eq!(api_route("/dashboard/api/keys"), None);
+        assert_eq!(api_route("/dashboard/api/indexesx"), None);
+        assert_eq!(api_route("/indexes"), None);
+
+        assert!(is_session_route("/dashboard/api/overview"));
+        assert!(!is_session_route("/dashboard/login"));
+        assert!(!is_session_route("/dashboard/apis"));
+        assert!(!is_session_route("/indexes"));
+    }
+}
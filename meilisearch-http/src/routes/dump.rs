@@ -4,6 +4,7 @@ use meilisearch_error::ResponseError;
 use meilisearch_lib::MeiliSearch;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use utoipa::IntoParams;
 
 use crate::analytics::Analytics;
 use crate::extractors::authentication::{policies::*, GuardedData};
@@ -16,6 +17,13 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         );
 }
 
+#[utoipa::path(
+    post,
+    path = "/dumps",
+    tag = "Dumps",
+    responses((status = 202, description = "The dump is being created.", body = Object)),
+    security(("apiKey" = ["dumps.create"]))
+)]
 pub async fn create_dump(
     meilisearch: GuardedData<ActionPolicy<{ actions::DUMPS_CREATE }>, MeiliSearch>,
     req: HttpRequest,
@@ -35,12 +43,24 @@ struct DumpStatusResponse {
     status: String,
 }
 
-#[derive(Deserialize)]
-struct DumpParam {
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+pub struct DumpParam {
     dump_uid: String,
 }
 
-async fn get_dump_status(
+#[utoipa::path(
+    get,
+    path = "/dumps/{dump_uid}/status",
+    tag = "Dumps",
+    params(DumpParam),
+    responses(
+        (status = 200, description = "The status of the dump.", body = Object),
+        (status = 404, description = "The dump doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["dumps.get"]))
+)]
+pub async fn get_dump_status(
     meilisearch: GuardedData<ActionPolicy<{ actions::DUMPS_GET }>, MeiliSearch>,
     path: web::Path<DumpParam>,
 ) -> Result<HttpResponse, ResponseError> {
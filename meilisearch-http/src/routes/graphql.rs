@@ -0,0 +1,374 @@
+//! A GraphQL endpoint searching the indexes, served when built with the `graphql` feature.
+//!
+//! The schema is derived at each request from the indexes the key can search: every index is a
+//! field of the `Query` type, taking the search parameters as arguments and returning the hits
+//! typed from the field distribution of the index. Since the documents are schemaless, their
+//! fields are values of the `JSON` scalar.
+//!
+//! ```graphql
+//! {
+//!   movies(q: "carol", filter: "genre = drama", sort: ["year:desc"], limit: 5) {
+//!     nbHits
+//!     hits { id title }
+//!   }
+//! }
+//! ```
+
+use std::collections::HashSet;
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use async_graphql::dynamic::{
+    Field, FieldFuture, FieldValue, InputValue, Object, ResolverContext, Scalar, Schema,
+    SchemaError, TypeRef,
+};
+use async_graphql::{ErrorExtensions, Request, Value as GraphQLValue};
+use log::debug;
+use meilisearch_auth::SearchRules;
+use meilisearch_error::{Code, ResponseError};
+use meilisearch_lib::index::{Document, SearchQuery, SearchResult};
+use meilisearch_lib::MeiliSearch;
+use serde_json::{json, Map, Value};
+
+use super::indexes::search::{add_search_rules, search_requester};
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+
+const JSON_SCALAR: &str = "JSON";
+
+/// The arguments of the search fields, with the search parameter they set.
+const SEARCH_ARGUMENTS: &[(&str, &str)] = &[
+    ("q", "q"),
+    ("filter", "filter"),
+    ("sort", "sort"),
+    ("facets", "facetsDistribution"),
+    ("limit", "limit"),
+    ("offset", "offset"),
+    ("page", "page"),
+    ("hitsPerPage", "hitsPerPage"),
+];
+
+/// The state available to the resolvers.
+struct SearchContext {
+    meilisearch: MeiliSearch,
+    search_rules: SearchRules,
+    requester: String,
+}
+
+pub async fn graphql(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
+    body: web::Json<Request>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let search_rules = meilisearch.filters().search_rules.clone();
+
+    let mut indexes = Vec::new();
+    for index in meilisearch.list_indexes().await? {
+        if search_rules.is_index_authorized(&index.uid) {
+            let stats = meilisearch.get_index_stats(index.uid.clone()).await?;
+            indexes.push((index.uid, stats.field_distribution.into_keys().collect()));
+        }
+    }
+
+    let schema = build_schema(&indexes)
+        .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))?;
+
+    let request = body.into_inner().data(SearchContext {
+        meilisearch: (*meilisearch).clone(),
+        search_rules,
+        requester: search_requester(&req).to_string(),
+    });
+    let response = schema.execute(request).await;
+
+    analytics.publish(
+        "GraphQL Query".to_string(),
+        json!({ "indexes": indexes.len(), "errors": response.errors.len() }),
+        Some(&req),
+    );
+
+    debug!("returns: {:?}", response);
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Makes a valid GraphQL name of an index uid or an attribute: the invalid characters are replaced
+/// by `_`, the names can't start with a digit or `__`.
+fn graphql_name(name: &str) -> String {
+    let mut name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    while name.starts_with("__") {
+        name.remove(0);
+    }
+    name
+}
+
+/// The prefix of the types of an index, unique among the indexes of the schema.
+fn type_prefix(field_name: &str, type_prefixes: &mut HashSet<String>) -> String {
+    let mut chars = field_name.chars();
+    let base: String = chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default();
+
+    let mut prefix = base.clone();
+    let mut count = 1;
+    while !type_prefixes.insert(prefix.clone()) {
+        count += 1;
+        prefix = format!("{}{}", base, count);
+    }
+    prefix
+}
+
+fn build_schema(indexes: &[(String, Vec<String>)]) -> Result<Schema, SchemaError> {
+    let mut schema = Schema::build("Query", None, None)
+        .register(Scalar::new(JSON_SCALAR).description("Any JSON value."));
+    let mut query = Object::new("Query");
+
+    let mut field_names = HashSet::new();
+    let mut type_prefixes = HashSet::new();
+    for (uid, attributes) in indexes {
+        let field_name = graphql_name(uid);
+        if !field_names.insert(field_name.clone()) {
+            debug!(
+                "index `{}` is not exposed, `{}` is already used",
+                uid, field_name
+            );
+            continue;
+        }
+        let prefix = type_prefix(&field_name, &mut type_prefixes);
+
+        let document_type = format!("{}Document", prefix);
+        let result_type = format!("{}SearchResult", prefix);
+        schema = schema
+            .register(document_object(&document_type, attributes))
+            .register(result_object(&result_type, &document_type));
+        query = query.field(search_field(uid, &field_name, &result_type));
+    }
+
+    if indexes.is_empty() {
+        // A GraphQL object must have at least one field.
+        query = query.field(Field::new(
+            "_empty",
+            TypeRef::named(TypeRef::BOOLEAN),
+            |_| FieldFuture::new(async { Ok(None::<FieldValue>) }),
+        ));
+    }
+
+    schema.register(query).finish()
+}
+
+fn document_object(name: &str, attributes: &[String]) -> Object {
+    let mut object = Object::new(name);
+    let mut field_names = HashSet::new();
+    for attribute in attributes {
+        let field_name = graphql_name(attribute);
+        if !field_names.insert(field_name.clone()) {
+            continue;
+        }
+
+        let attribute = attribute.clone();
+        object = object.field(Field::new(
+            field_name,
+            TypeRef::named(JSON_SCALAR),
+            move |ctx| {
+                let attribute = attribute.clone();
+                FieldFuture::new(async move {
+                    let document = ctx.parent_value.try_downcast_ref::<Document>()?;
+                    Ok(document
+                        .get(&attribute)
+                        .map(|value| FieldValue::value(graphql_value(value.clone()))))
+                })
+            },
+        ));
+    }
+
+    if field_names.is_empty() {
+        // The index doesn't contain any document yet.
+        object = object.field(Field::new(
+            "_empty",
+            TypeRef::named(TypeRef::BOOLEAN),
+            |_| FieldFuture::new(async { Ok(None::<FieldValue>) }),
+        ));
+    }
+
+    object
+}
+
+fn result_object(name: &str, document_type: &str) -> Object {
+    let integer = |result: &SearchResult, field: &str| match field {
+        "nbHits" => Some(result.nb_hits as usize),
+        "limit" => Some(result.limit),
+        "offset" => Some(result.offset),
+        "processingTimeMs" => Some(result.processing_time_ms as usize),
+        "page" => result.page,
+        "hitsPerPage" => result.hits_per_page,
+        "totalPages" => result.total_pages,
+        "totalHits" => result.total_hits.map(|total| total as usize),
+        _ => None,
+    };
+
+    let mut object = Object::new(name).field(Field::new(
+        "hits",
+        TypeRef::named_nn_list_nn(document_type),
+        |ctx| {
+            FieldFuture::new(async move {
+                let result = ctx.parent_value.try_downcast_ref::<SearchResult>()?;
+                Ok(Some(FieldValue::list(
+                    result
+                        .hits
+                        .iter()
+                        .map(|hit| FieldValue::borrowed_any(&hit.document)),
+                )))
+            })
+        },
+    ));
+
+    for (field, required) in [
+        ("nbHits", true),
+        ("limit", true),
+        ("offset", true),
+        ("processingTimeMs", true),
+        ("page", false),
+        ("hitsPerPage", false),
+        ("totalPages", false),
+        ("totalHits", false),
+    ] {
+        let type_ref = if required {
+            TypeRef::named_nn(TypeRef::INT)
+        } else {
+            TypeRef::named(TypeRef::INT)
+        };
+        object = object.field(Field::new(field, type_ref, move |ctx| {
+            FieldFuture::new(async move {
+                let result = ctx.parent_value.try_downcast_ref::<SearchResult>()?;
+                Ok(integer(result, field).map(|value| FieldValue::value(value as u64)))
+            })
+        }));
+    }
+
+    object
+        .field(Field::new(
+            "query",
+            TypeRef::named_nn(TypeRef::STRING),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let result = ctx.parent_value.try_downcast_ref::<SearchResult>()?;
+                    Ok(Some(FieldValue::value(result.query.clone())))
+                })
+            },
+        ))
+        .field(Field::new(
+            "facetsDistribution",
+            TypeRef::named(JSON_SCALAR),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let result = ctx.parent_value.try_downcast_ref::<SearchResult>()?;
+                    Ok(result
+                        .facets_distribution
+                        .as_ref()
+                        .map(|facets| FieldValue::value(graphql_value(json!(facets)))))
+                })
+            },
+        ))
+}
+
+fn search_field(uid: &str, field_name: &str, result_type: &str) -> Field {
+    let index_uid = uid.to_string();
+    let field = Field::new(field_name, TypeRef::named_nn(result_type), move |ctx| {
+        let uid = index_uid.clone();
+        FieldFuture::new(async move {
+            let context = ctx.data::<SearchContext>()?;
+            let mut query = search_query(&ctx)?;
+            // Tenant token search_rules.
+            if let Some(search_rules) = context.search_rules.get_index_search_rules(&uid) {
+                add_search_rules(&mut query, search_rules);
+            }
+
+            let result = context
+                .meilisearch
+                .search(uid, query, &context.requester)
+                .await
+                .map_err(|e| graphql_error(e.into()))?;
+
+            Ok(Some(FieldValue::owned_any(result)))
+        })
+    })
+    .description(format!("Searches the `{}` index.", uid));
+
+    field
+        .argument(InputValue::new("q", TypeRef::named(TypeRef::STRING)))
+        .argument(
+            InputValue::new("filter", TypeRef::named(JSON_SCALAR))
+                .description("A filter expression, or an array of filter expressions."),
+        )
+        .argument(InputValue::new(
+            "sort",
+            TypeRef::named_nn_list(TypeRef::STRING),
+        ))
+        .argument(
+            InputValue::new("facets", TypeRef::named_nn_list(TypeRef::STRING))
+                .description("The attributes whose facets distribution is returned."),
+        )
+        .argument(InputValue::new("limit", TypeRef::named(TypeRef::INT)))
+        .argument(InputValue::new("offset", TypeRef::named(TypeRef::INT)))
+        .argument(InputValue::new("page", TypeRef::named(TypeRef::INT)))
+        .argument(InputValue::new("hitsPerPage", TypeRef::named(TypeRef::INT)))
+}
+
+/// Builds the query like the body of `POST /indexes/{index_uid}/search`, so the missing
+/// arguments take the same defaults.
+fn search_query(ctx: &ResolverContext) -> async_graphql::Result<SearchQuery> {
+    let mut query = Map::new();
+    for (argument, parameter) in SEARCH_ARGUMENTS {
+        if let Some(value) = ctx.args.get(argument) {
+            if !value.is_null() {
+                query.insert(parameter.to_string(), value.as_value().clone().into_json()?);
+            }
+        }
+    }
+
+    serde_json::from_value(Value::Object(query))
+        .map_err(|e| graphql_error(ResponseError::from_msg(e.to_string(), Code::BadRequest)))
+}
+
+fn graphql_value(value: Value) -> GraphQLValue {
+    GraphQLValue::from_json(value).unwrap_or(GraphQLValue::Null)
+}
+
+/// The errors keep the `code` of the HTTP API in their extensions.
+fn graphql_error(error: ResponseError) -> async_graphql::Error {
+    let code = json!(error)["code"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    async_graphql::Error::new(error.to_string()).extend_with(|_, extensions| {
+        extensions.set("code", code.clone());
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn graphql_names() {
+        assert_eq!(graphql_name("movies"), "movies");
+        assert_eq!(graphql_name("movies-2022"), "movies_2022");
+        assert_eq!(graphql_name("2022_movies"), "_2022_movies");
+        assert_eq!(graphql_name("director.name"), "director_name");
+        assert_eq!(graphql_name("__typename"), "_typename");
+        assert_eq!(graphql_name(""), "_");
+    }
+
+    #[test]
+    fn type_prefixes_are_unique() {
+        let mut prefixes = HashSet::new();
+        assert_eq!(type_prefix("movies", &mut prefixes), "Movies");
+        assert_eq!(type_prefix("Movies", &mut prefixes), "Movies2");
+        assert_eq!(type_prefix("_2022_movies", &mut prefixes), "_2022_movies");
+    }
+}
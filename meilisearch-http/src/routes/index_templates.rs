@@ -0,0 +1,130 @@
+use actix_web::{web, HttpResponse};
+use log::debug;
+use meilisearch_error::ResponseError;
+use meilisearch_lib::index::{Settings, Unchecked};
+use meilisearch_lib::index_controller::IndexTemplate;
+use meilisearch_lib::MeiliSearch;
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::IntoParams;
+
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(SeqHandler(list_index_templates))))
+        .service(
+            web::resource("/{template_name}")
+                .route(web::get().to(SeqHandler(get_index_template)))
+                .route(web::put().to(SeqHandler(put_index_template)))
+                .route(web::delete().to(SeqHandler(delete_index_template))),
+        );
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct IndexTemplateRequest {
+    index_pattern: String,
+    #[serde(default)]
+    settings: Settings<Unchecked>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+pub struct IndexTemplateParam {
+    template_name: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/index-templates",
+    tag = "Index templates",
+    responses((status = 200, description = "All the index templates, ordered by name.", body = Object)),
+    security(("apiKey" = []))
+)]
+pub async fn list_index_templates(
+    meilisearch: GuardedData<MasterPolicy, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    let res = meilisearch.list_index_templates().await?;
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(json!({ "results": res })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/index-templates/{template_name}",
+    tag = "Index templates",
+    params(IndexTemplateParam),
+    responses(
+        (status = 200, description = "The index template.", body = Object),
+        (status = 404, description = "The index template doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = []))
+)]
+pub async fn get_index_template(
+    meilisearch: GuardedData<MasterPolicy, MeiliSearch>,
+    path: web::Path<IndexTemplateParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let res = meilisearch
+        .get_index_template(path.into_inner().template_name)
+        .await?;
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
+
+#[utoipa::path(
+    put,
+    path = "/index-templates/{template_name}",
+    tag = "Index templates",
+    params(IndexTemplateParam),
+    request_body(content = Object, description = "The pattern of the index uids, in which `*` matches any sequence of characters, and the settings applied to the matching indexes when they are created."),
+    responses(
+        (status = 200, description = "The created or replaced index template.", body = Object),
+        (status = 400, description = "The index template is invalid.", body = ResponseError),
+    ),
+    security(("apiKey" = []))
+)]
+pub async fn put_index_template(
+    meilisearch: GuardedData<MasterPolicy, MeiliSearch>,
+    path: web::Path<IndexTemplateParam>,
+    body: web::Json<IndexTemplateRequest>,
+) -> Result<HttpResponse, ResponseError> {
+    let IndexTemplateRequest {
+        index_pattern,
+        settings,
+    } = body.into_inner();
+    let template = IndexTemplate {
+        name: path.into_inner().template_name,
+        index_pattern,
+        settings,
+    };
+
+    let res = meilisearch.put_index_template(template).await?;
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/index-templates/{template_name}",
+    tag = "Index templates",
+    params(IndexTemplateParam),
+    responses(
+        (status = 204, description = "The index template is deleted, the indexes created with it keep their settings."),
+        (status = 404, description = "The index template doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = []))
+)]
+pub async fn delete_index_template(
+    meilisearch: GuardedData<MasterPolicy, MeiliSearch>,
+    path: web::Path<IndexTemplateParam>,
+) -> Result<HttpResponse, ResponseError> {
+    meilisearch
+        .delete_index_template(path.into_inner().template_name)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -1,5 +1,5 @@
 use actix_web::error::PayloadError;
-use actix_web::http::header::CONTENT_TYPE;
+use actix_web::http::header::{CONTENT_LENGTH, CONTENT_TYPE};
 use actix_web::web::Bytes;
 use actix_web::HttpMessage;
 use actix_web::{web, HttpRequest, HttpResponse};
@@ -15,6 +15,7 @@ use once_cell::sync::Lazy;
 use serde::Deserialize;
 use serde_json::Value;
 use tokio::sync::mpsc;
+use utoipa::IntoParams;
 
 use crate::analytics::Analytics;
 use crate::error::MeilisearchHttpError;
@@ -27,11 +28,19 @@ const DEFAULT_RETRIEVE_DOCUMENTS_OFFSET: usize = 0;
 const DEFAULT_RETRIEVE_DOCUMENTS_LIMIT: usize = 20;
 
 static ACCEPTED_CONTENT_TYPE: Lazy<Vec<String>> = Lazy::new(|| {
-    vec![
+    let mut accepted = vec![
         "application/json".to_string(),
         "application/x-ndjson".to_string(),
         "text/csv".to_string(),
-    ]
+    ];
+    if cfg!(feature = "xml") {
+        accepted.push("application/xml".to_string());
+        accepted.push("text/xml".to_string());
+    }
+    if cfg!(feature = "parquet") {
+        accepted.push("application/vnd.apache.parquet".to_string());
+    }
+    accepted
 });
 
 /// This is required because Payload is not Sync nor Send
@@ -86,6 +95,17 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
     );
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/documents/{document_id}",
+    tag = "Documents",
+    params(("index_uid" = String, Path, description = "The uid of the index."), ("document_id" = String, Path, description = "The id of the document.")),
+    responses(
+        (status = 200, description = "The document.", body = Object),
+        (status = 404, description = "The index or the document doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["documents.get"]))
+)]
 pub async fn get_document(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
     path: web::Path<DocumentParam>,
@@ -99,6 +119,14 @@ pub async fn get_document(
     Ok(HttpResponse::Ok().json(document))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/documents/{document_id}",
+    tag = "Documents",
+    params(("index_uid" = String, Path, description = "The uid of the index."), ("document_id" = String, Path, description = "The id of the document.")),
+    responses((status = 202, description = "The task is enqueued.", body = SummarizedTaskView)),
+    security(("apiKey" = ["documents.delete"]))
+)]
 pub async fn delete_document(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<DocumentParam>,
@@ -113,14 +141,29 @@ pub async fn delete_document(
     Ok(HttpResponse::Accepted().json(task))
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, IntoParams)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
 pub struct BrowseQuery {
     offset: Option<usize>,
     limit: Option<usize>,
     attributes_to_retrieve: Option<String>,
+    /// The token of a read session of the index, to read the documents as they were when it was
+    /// opened.
+    read_session: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/documents",
+    tag = "Documents",
+    params(("index_uid" = String, Path, description = "The uid of the index."), BrowseQuery),
+    responses(
+        (status = 200, description = "The documents.", body = Vec<Object>),
+        (status = 404, description = "The index or the read session doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["documents.get"]))
+)]
 pub async fn get_all_documents(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
     path: web::Path<String>,
@@ -138,24 +181,59 @@ pub async fn get_all_documents(
         Some(names)
     });
 
-    let documents = meilisearch
-        .documents(
-            path.into_inner(),
-            params.offset.unwrap_or(DEFAULT_RETRIEVE_DOCUMENTS_OFFSET),
-            params.limit.unwrap_or(DEFAULT_RETRIEVE_DOCUMENTS_LIMIT),
-            attributes_to_retrieve,
-        )
-        .await?;
+    let offset = params.offset.unwrap_or(DEFAULT_RETRIEVE_DOCUMENTS_OFFSET);
+    let limit = params.limit.unwrap_or(DEFAULT_RETRIEVE_DOCUMENTS_LIMIT);
+    let documents = match params.read_session.clone() {
+        Some(token) => {
+            meilisearch
+                .read_session_documents(
+                    path.into_inner(),
+                    token,
+                    offset,
+                    limit,
+                    attributes_to_retrieve,
+                )
+                .await?
+        }
+        None => {
+            meilisearch
+                .documents(path.into_inner(), offset, limit, attributes_to_retrieve)
+                .await?
+        }
+    };
     debug!("returns: {:?}", documents);
     Ok(HttpResponse::Ok().json(documents))
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, IntoParams)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
 pub struct UpdateDocumentsQuery {
     pub primary_key: Option<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/documents",
+    tag = "Documents",
+    params(("index_uid" = String, Path, description = "The uid of the index."), UpdateDocumentsQuery),
+    request_body(
+        description = "The documents, as JSON, NDJSON or CSV. XML and Parquet are accepted when \
+            built with the `xml` and `parquet` features.",
+        content(
+            (Vec<Object> = "application/json"),
+            (String = "application/x-ndjson"),
+            (String = "text/csv"),
+            (String = "application/xml"),
+            (String = "application/vnd.apache.parquet"),
+        )
+    ),
+    responses(
+        (status = 202, description = "The task is enqueued.", body = SummarizedTaskView),
+        (status = 400, description = "The payload is invalid.", body = ResponseError),
+    ),
+    security(("apiKey" = ["documents.add"]))
+)]
 pub async fn add_documents(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
     path: web::Path<String>,
@@ -176,7 +254,7 @@ pub async fn add_documents(
 
     let allow_index_creation = meilisearch.filters().allow_index_creation;
     let task = document_addition(
-        extract_mime_type(&req)?,
+        &req,
         meilisearch,
         index_uid,
         params.primary_key,
@@ -189,6 +267,28 @@ pub async fn add_documents(
     Ok(HttpResponse::Accepted().json(task))
 }
 
+#[utoipa::path(
+    put,
+    path = "/indexes/{index_uid}/documents",
+    tag = "Documents",
+    params(("index_uid" = String, Path, description = "The uid of the index."), UpdateDocumentsQuery),
+    request_body(
+        description = "The documents, as JSON, NDJSON or CSV. XML and Parquet are accepted when \
+            built with the `xml` and `parquet` features.",
+        content(
+            (Vec<Object> = "application/json"),
+            (String = "application/x-ndjson"),
+            (String = "text/csv"),
+            (String = "application/xml"),
+            (String = "application/vnd.apache.parquet"),
+        )
+    ),
+    responses(
+        (status = 202, description = "The task is enqueued.", body = SummarizedTaskView),
+        (status = 400, description = "The payload is invalid.", body = ResponseError),
+    ),
+    security(("apiKey" = ["documents.add"]))
+)]
 pub async fn update_documents(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
     path: web::Path<String>,
@@ -208,7 +308,7 @@ pub async fn update_documents(
 
     let allow_index_creation = meilisearch.filters().allow_index_creation;
     let task = document_addition(
-        extract_mime_type(&req)?,
+        &req,
         meilisearch,
         index_uid,
         params.into_inner().primary_key,
@@ -222,7 +322,7 @@ pub async fn update_documents(
 }
 
 async fn document_addition(
-    mime_type: Option<Mime>,
+    req: &HttpRequest,
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
     index_uid: String,
     primary_key: Option<String>,
@@ -230,6 +330,7 @@ async fn document_addition(
     method: IndexDocumentsMethod,
     allow_index_creation: bool,
 ) -> Result<SummarizedTaskView, ResponseError> {
+    let mime_type = extract_mime_type(req)?;
     let format = match mime_type
         .as_ref()
         .map(|m| (m.type_().as_str(), m.subtype().as_str()))
@@ -237,6 +338,10 @@ async fn document_addition(
         Some(("application", "json")) => DocumentAdditionFormat::Json,
         Some(("application", "x-ndjson")) => DocumentAdditionFormat::Ndjson,
         Some(("text", "csv")) => DocumentAdditionFormat::Csv,
+        #[cfg(feature = "xml")]
+        Some(("application" | "text", "xml")) => DocumentAdditionFormat::Xml,
+        #[cfg(feature = "parquet")]
+        Some(("application", "vnd.apache.parquet")) => DocumentAdditionFormat::Parquet,
         Some((type_, subtype)) => {
             return Err(MeilisearchHttpError::InvalidContentType(
                 format!("{}/{}", type_, subtype),
@@ -251,12 +356,18 @@ async fn document_addition(
         }
     };
 
+    // The payload is refused before it is read when its announced size exceeds the disk quota.
+    let payload_size = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|length| length.to_str().ok()?.parse().ok());
     let update = Update::DocumentAddition {
         payload: Box::new(payload_to_stream(body)),
         primary_key,
         method,
         format,
         allow_index_creation,
+        payload_size,
     };
 
     let task = meilisearch.register_update(index_uid, update).await?.into();
@@ -265,6 +376,15 @@ async fn document_addition(
     Ok(task)
 }
 
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/documents/delete-batch",
+    tag = "Documents",
+    params(("index_uid" = String, Path, description = "The uid of the index.")),
+    request_body(content = Vec<Object>, description = "The ids of the documents."),
+    responses((status = 202, description = "The task is enqueued.", body = SummarizedTaskView)),
+    security(("apiKey" = ["documents.delete"]))
+)]
 pub async fn delete_documents(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<String>,
@@ -290,6 +410,14 @@ pub async fn delete_documents(
     Ok(HttpResponse::Accepted().json(task))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/documents",
+    tag = "Documents",
+    params(("index_uid" = String, Path, description = "The uid of the index.")),
+    responses((status = 202, description = "The task is enqueued.", body = SummarizedTaskView)),
+    security(("apiKey" = ["documents.delete"]))
+)]
 pub async fn clear_all_documents(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<String>,
@@ -0,0 +1,78 @@
+use actix_web::http::header::CONTENT_DISPOSITION;
+use actix_web::{web, HttpResponse};
+use futures::StreamExt;
+use log::debug;
+use meilisearch_error::ResponseError;
+use meilisearch_lib::index::{ExportFormat, ExportQuery};
+use meilisearch_lib::MeiliSearch;
+use serde::Deserialize;
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(export_documents))));
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ExportRequest {
+    /// `ndjson` or `csv`, `ndjson` by default.
+    #[serde(default = "default_export_format")]
+    #[schema(value_type = String, example = "ndjson")]
+    format: ExportFormat,
+    /// Only exports the documents matching the filter, with the syntax of the search filters.
+    #[schema(value_type = Object)]
+    filter: Option<Value>,
+}
+
+fn default_export_format() -> ExportFormat {
+    ExportFormat::Ndjson
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/export",
+    tag = "Documents",
+    params(("index_uid" = String, Path, description = "The uid of the index.")),
+    request_body = ExportRequest,
+    responses(
+        (status = 200, description = "The documents, streamed as they are read from the index.",
+            body = String, content_type = "application/x-ndjson"),
+        (status = 400, description = "The filter is invalid.", body = ResponseError),
+        (status = 404, description = "The index doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["documents.export"]))
+)]
+pub async fn export_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_EXPORT }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<ExportRequest>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let uid = path.into_inner();
+    let ExportRequest { format, filter } = body.into_inner();
+    let query = ExportQuery { format, filter };
+
+    let chunks = meilisearch.export_documents(uid.clone(), query).await?;
+    let chunks = chunks.map(|chunk| {
+        chunk.map_err(|e| {
+            log::error!("error while exporting the documents: {}", e);
+            e
+        })
+    });
+
+    let extension = match format {
+        ExportFormat::Ndjson => "ndjson",
+        ExportFormat::Csv => "csv",
+    };
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.{}\"", uid, extension),
+        ))
+        .streaming(chunks))
+}
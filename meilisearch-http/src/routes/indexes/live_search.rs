@@ -0,0 +1,323 @@
+//! A WebSocket protocol for search-as-you-type interfaces.
+//!
+//! Once the session is opened, the client sends a search request as a text message at each
+//! keystroke, `{ "id": 12, "query": { "q": "hel" } }`, and only receives the results of the latest
+//! request once the user stops typing: `{ "id": 12, "result": { ... } }` or
+//! `{ "id": 12, "error": { ... } }`.
+
+use std::time::Duration;
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_ws::{CloseCode, CloseReason, Message, MessageStream, Session};
+use log::debug;
+use meilisearch_auth::AuthController;
+use meilisearch_error::{Code, ResponseError};
+use meilisearch_lib::index::{SearchQuery, SearchResult};
+use meilisearch_lib::MeiliSearch;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::time::{sleep_until, Instant};
+use utoipa::IntoParams;
+
+use super::search::add_search_rules;
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+
+type SearchGuard = GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>;
+
+/// The default time to wait for another request before running a search, in milliseconds.
+pub const DEFAULT_DEBOUNCE_MS: u64 = 50;
+/// The maximum debounce a client can ask for, in milliseconds.
+pub const MAX_DEBOUNCE_MS: u64 = 1000;
+/// The minimum time between two searches of a session, whatever the debounce.
+pub const MIN_SEARCH_INTERVAL: Duration = Duration::from_millis(50);
+/// A session without any message during this time is closed.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct LiveSearchParams {
+    /// The key or tenant token of the session, for the clients that can't send headers.
+    api_key: Option<String>,
+    #[serde(default = "default_debounce_ms")]
+    debounce_ms: u64,
+}
+
+fn default_debounce_ms() -> u64 {
+    DEFAULT_DEBOUNCE_MS
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct LiveSearchRequest {
+    /// Chosen by the client to match the results with its requests.
+    id: Option<u64>,
+    query: SearchQuery,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+enum LiveSearchResult {
+    Result(SearchResult),
+    Error(ResponseError),
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct LiveSearchResponse {
+    id: Option<u64>,
+    #[serde(flatten)]
+    result: LiveSearchResult,
+}
+
+/// Returns when the pending request must be run: after the debounce, and not before the rate
+/// limit allows another search.
+fn search_deadline(
+    received_at: Instant,
+    debounce: Duration,
+    last_search: Option<Instant>,
+) -> Instant {
+    let debounced = received_at + debounce;
+    match last_search {
+        Some(last_search) => debounced.max(last_search + MIN_SEARCH_INTERVAL),
+        None => debounced,
+    }
+}
+
+/// Returns the token of the session, taken from the `Authorization` header like the other routes,
+/// or from the `apiKey` parameter since the browsers can't set headers on WebSockets.
+fn session_token(req: &HttpRequest, params: &LiveSearchParams) -> String {
+    let header = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    header
+        .map(String::from)
+        .or_else(|| params.api_key.clone())
+        .unwrap_or_default()
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/search/live",
+    tag = "Search",
+    params(("index_uid" = String, Path, description = "The uid of the index."), LiveSearchParams),
+    responses(
+        (status = 101, description = "The WebSocket session is opened."),
+        (status = 400, description = "The request is not a WebSocket handshake or `debounceMs` is too high.", body = ResponseError),
+        (status = 404, description = "The index doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["search"]))
+)]
+pub async fn live_search(
+    index_uid: web::Path<String>,
+    params: web::Query<LiveSearchParams>,
+    req: HttpRequest,
+    body: web::Payload,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let index_uid = index_uid.into_inner();
+    let params = params.into_inner();
+    if params.debounce_ms > MAX_DEBOUNCE_MS {
+        return Err(ResponseError::from_msg(
+            format!("`debounceMs` must be at most {}.", MAX_DEBOUNCE_MS),
+            Code::BadRequest,
+        ));
+    }
+
+    let (auth, meilisearch) = match (
+        req.app_data::<AuthController>().cloned(),
+        req.app_data::<MeiliSearch>().cloned(),
+    ) {
+        (Some(auth), Some(meilisearch)) => (auth, meilisearch),
+        _ => {
+            return Err(ResponseError::from_msg(
+                "The application state is not available.".to_string(),
+                Code::Internal,
+            ))
+        }
+    };
+
+    let token = session_token(&req, &params);
+    let guard = SearchGuard::from_token(
+        auth.clone(),
+        token.clone(),
+        Some(index_uid.clone()),
+        meilisearch,
+    )
+    .await?;
+    // Fails early on an unexisting index rather than on the first search.
+    guard.get_index(index_uid.clone()).await?;
+
+    let (response, session, messages) = actix_ws::handle(&req, body)
+        .map_err(|e| ResponseError::from_msg(e.to_string(), Code::BadRequest))?;
+
+    analytics.publish(
+        "Live Search Session Opened".to_string(),
+        json!({ "debounce_ms": params.debounce_ms }),
+        Some(&req),
+    );
+
+    let session = LiveSearchSession {
+        meilisearch: (*guard).clone(),
+        auth,
+        token,
+        index_uid,
+        debounce: Duration::from_millis(params.debounce_ms),
+        session,
+    };
+    actix_web::rt::spawn(session.run(messages));
+
+    Ok(response)
+}
+
+struct LiveSearchSession {
+    meilisearch: MeiliSearch,
+    auth: AuthController,
+    token: String,
+    index_uid: String,
+    debounce: Duration,
+    session: Session,
+}
+
+impl LiveSearchSession {
+    async fn run(mut self, mut messages: MessageStream) {
+        let mut pending: Option<LiveSearchRequest> = None;
+        let mut deadline = Instant::now();
+        let mut last_search = None;
+        let mut last_activity = Instant::now();
+
+        let reason = loop {
+            tokio::select! {
+                message = messages.recv() => {
+                    last_activity = Instant::now();
+                    match message {
+                        Some(Ok(Message::Text(text))) => {
+                            match serde_json::from_str::<LiveSearchRequest>(&text) {
+                                Ok(request) => {
+                                    // Only the latest request is run, the previous ones are outdated.
+                                    pending = Some(request);
+                                    deadline =
+                                        search_deadline(last_activity, self.debounce, last_search);
+                                }
+                                Err(e) => {
+                                    let error = ResponseError::from_msg(
+                                        format!("Invalid search request: {}.", e),
+                                        Code::BadRequest,
+                                    );
+                                    if !self.send(None, LiveSearchResult::Error(error)).await {
+                                        break None;
+                                    }
+                                }
+                            }
+                        }
+                        Some(Ok(Message::Ping(bytes))) => {
+                            if self.session.pong(&bytes).await.is_err() {
+                                break None;
+                            }
+                        }
+                        Some(Ok(Message::Close(reason))) => break reason,
+                        Some(Ok(_)) => (),
+                        Some(Err(e)) => {
+                            debug!("live search session error: {}", e);
+                            break None;
+                        }
+                        None => break None,
+                    }
+                }
+                _ = sleep_until(deadline), if pending.is_some() => {
+                    let request = match pending.take() {
+                        Some(request) => request,
+                        None => continue,
+                    };
+                    last_search = Some(Instant::now());
+                    match self.search(request.query).await {
+                        Ok(result) => {
+                            if !self.send(request.id, result).await {
+                                break None;
+                            }
+                        }
+                        // The key of the session was deleted or expired.
+                        Err(e) => {
+                            self.send(request.id, LiveSearchResult::Error(e)).await;
+                            break Some(CloseReason::from(CloseCode::Policy));
+                        }
+                    }
+                }
+                _ = sleep_until(last_activity + IDLE_TIMEOUT) => {
+                    break Some(CloseReason::from(CloseCode::Normal));
+                }
+            }
+        };
+
+        let _ = self.session.close(reason).await;
+    }
+
+    /// Runs the search with the rules of the session. The token is authenticated again before each
+    /// search, so the session stops as soon as its key is deleted or expires.
+    async fn search(&self, mut query: SearchQuery) -> Result<LiveSearchResult, ResponseError> {
+        let guard = SearchGuard::from_token(
+            self.auth.clone(),
+            self.token.clone(),
+            Some(self.index_uid.clone()),
+            self.meilisearch.clone(),
+        )
+        .await?;
+
+        // Tenant token search_rules.
+        if let Some(search_rules) = guard
+            .filters()
+            .search_rules
+            .get_index_search_rules(&self.index_uid)
+        {
+            add_search_rules(&mut query, search_rules);
+        }
+
+        let result = match guard
+            .search(self.index_uid.clone(), query, &self.token)
+            .await
+        {
+            Ok(result) => LiveSearchResult::Result(result),
+            Err(e) => LiveSearchResult::Error(e.into()),
+        };
+
+        Ok(result)
+    }
+
+    /// Sends a response to the client, returns `false` if the session is closed.
+    async fn send(&mut self, id: Option<u64>, result: LiveSearchResult) -> bool {
+        let response = LiveSearchResponse { id, result };
+        match serde_json::to_string(&response) {
+            Ok(text) => self.session.text(text).await.is_ok(),
+            Err(e) => {
+                log::error!("error while serializing a live search response: {}", e);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deadline_waits_for_the_debounce_and_the_rate_limit() {
+        let now = Instant::now();
+        let debounce = Duration::from_millis(10);
+
+        assert_eq!(search_deadline(now, debounce, None), now + debounce);
+        assert_eq!(
+            search_deadline(now, debounce, Some(now)),
+            now + MIN_SEARCH_INTERVAL
+        );
+        let long_ago = now - Duration::from_secs(1);
+        assert_eq!(
+            search_deadline(now, debounce, Some(long_ago)),
+            now + debounce
+        );
+    }
+}
@@ -6,18 +6,26 @@ use meilisearch_lib::MeiliSearch;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use time::OffsetDateTime;
+use utoipa::ToSchema;
 
 use crate::analytics::Analytics;
+use crate::extractors::authentication::error::AuthenticationError;
 use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::payload::{PayloadConfig, PayloadLimits};
 use crate::extractors::sequential_extractor::SeqHandler;
+use crate::json_config;
 use crate::task::SummarizedTaskView;
 
 pub mod documents;
+pub mod export;
+pub mod live_search;
+pub mod read_sessions;
 pub mod search;
 pub mod settings;
+pub mod suggest;
 pub mod tasks;
 
-pub fn configure(cfg: &mut web::ServiceConfig) {
+pub fn configure(cfg: &mut web::ServiceConfig, limits: PayloadLimits) {
     cfg.service(
         web::resource("")
             .route(web::get().to(list_indexes))
@@ -29,38 +37,80 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
                 web::resource("")
                     .route(web::get().to(SeqHandler(get_index)))
                     .route(web::put().to(SeqHandler(update_index)))
+                    .route(web::patch().to(SeqHandler(rename_index)))
                     .route(web::delete().to(SeqHandler(delete_index))),
             )
             .service(web::resource("/stats").route(web::get().to(SeqHandler(get_index_stats))))
-            .service(web::scope("/documents").configure(documents::configure))
-            .service(web::scope("/search").configure(search::configure))
+            .service(web::resource("/rebuild").route(web::post().to(SeqHandler(rebuild_index))))
+            .service(web::resource("/restore").route(web::post().to(SeqHandler(restore_index))))
+            .service(
+                web::scope("/documents")
+                    .app_data(PayloadConfig::new(limits.documents))
+                    .app_data(json_config(limits.documents))
+                    .configure(documents::configure),
+            )
+            .service(web::scope("/read-sessions").configure(read_sessions::configure))
+            .service(
+                web::scope("/export")
+                    .app_data(json_config(limits.search))
+                    .configure(export::configure),
+            )
+            .service(
+                web::scope("/search")
+                    .app_data(json_config(limits.search))
+                    .configure(search::configure),
+            )
+            .service(web::scope("/suggest").configure(suggest::configure))
             .service(web::scope("/tasks").configure(tasks::configure))
-            .service(web::scope("/settings").configure(settings::configure)),
+            .service(
+                web::scope("/settings")
+                    .app_data(json_config(limits.settings))
+                    .configure(settings::configure),
+            ),
     );
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes",
+    tag = "Indexes",
+    responses((status = 200, description = "The indexes the key is authorized on.", body = Vec<Object>)),
+    security(("apiKey" = ["indexes.get"]))
+)]
 pub async fn list_indexes(
     data: GuardedData<ActionPolicy<{ actions::INDEXES_GET }>, MeiliSearch>,
 ) -> Result<HttpResponse, ResponseError> {
-    let search_rules = &data.filters().search_rules;
+    let filters = data.filters();
     let indexes: Vec<_> = data
         .list_indexes()
         .await?
         .into_iter()
-        .filter(|i| search_rules.is_index_authorized(&i.uid))
+        .filter(|i| filters.search_rules.is_index_authorized(&i.uid))
+        .filter(|i| filters.is_in_namespace(&i.uid))
         .collect();
 
     debug!("returns: {:?}", indexes);
     Ok(HttpResponse::Ok().json(indexes))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct IndexCreateRequest {
     uid: String,
     primary_key: Option<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/indexes",
+    tag = "Indexes",
+    request_body = IndexCreateRequest,
+    responses(
+        (status = 202, description = "The task is enqueued.", body = SummarizedTaskView),
+        (status = 400, description = "The index creation is invalid.", body = ResponseError),
+    ),
+    security(("apiKey" = ["indexes.create"]))
+)]
 pub async fn create_index(
     meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_CREATE }>, MeiliSearch>,
     body: web::Json<IndexCreateRequest>,
@@ -77,13 +127,14 @@ pub async fn create_index(
         Some(&req),
     );
 
+    let uid = meilisearch.filters().namespaced(uid);
     let update = Update::CreateIndex { primary_key };
     let task: SummarizedTaskView = meilisearch.register_update(uid, update).await?.into();
 
     Ok(HttpResponse::Accepted().json(task))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 #[allow(dead_code)]
 pub struct UpdateIndexRequest {
@@ -104,6 +155,17 @@ pub struct UpdateIndexResponse {
     primary_key: OffsetDateTime,
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}",
+    tag = "Indexes",
+    params(("index_uid" = String, Path, description = "The uid of the index.")),
+    responses(
+        (status = 200, description = "The index.", body = Object),
+        (status = 404, description = "The index doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["indexes.get"]))
+)]
 pub async fn get_index(
     meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_GET }>, MeiliSearch>,
     path: web::Path<String>,
@@ -113,6 +175,18 @@ pub async fn get_index(
     Ok(HttpResponse::Ok().json(meta))
 }
 
+#[utoipa::path(
+    put,
+    path = "/indexes/{index_uid}",
+    tag = "Indexes",
+    params(("index_uid" = String, Path, description = "The uid of the index.")),
+    request_body = UpdateIndexRequest,
+    responses(
+        (status = 202, description = "The task is enqueued.", body = SummarizedTaskView),
+        (status = 400, description = "The index update is invalid.", body = ResponseError),
+    ),
+    security(("apiKey" = ["indexes.update"]))
+)]
 pub async fn update_index(
     meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_UPDATE }>, MeiliSearch>,
     path: web::Path<String>,
@@ -141,6 +215,117 @@ pub async fn update_index(
     Ok(HttpResponse::Accepted().json(task))
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RenameIndexRequest {
+    new_uid: String,
+}
+
+/// Registers a task renaming the index. The documents and settings are kept as is, only the uid
+/// the index is accessed with changes. The task fails if the new uid is already taken.
+#[utoipa::path(
+    patch,
+    path = "/indexes/{index_uid}",
+    tag = "Indexes",
+    params(("index_uid" = String, Path, description = "The uid of the index.")),
+    request_body = RenameIndexRequest,
+    responses(
+        (status = 202, description = "The task is enqueued.", body = SummarizedTaskView),
+        (status = 400, description = "The new uid is invalid.", body = ResponseError),
+    ),
+    security(("apiKey" = ["indexes.update"]))
+)]
+pub async fn rename_index(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_UPDATE }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<RenameIndexRequest>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let RenameIndexRequest { new_uid } = body.into_inner();
+    let new_uid = meilisearch.filters().namespaced(new_uid);
+
+    // A key must not be able to move an index out of its reach, or onto an index it can't access.
+    if !meilisearch
+        .filters()
+        .search_rules
+        .is_index_authorized(&new_uid)
+    {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+
+    analytics.publish("Index Renamed".to_string(), json!({}), Some(&req));
+
+    let update = Update::RenameIndex { new_uid };
+    let task: SummarizedTaskView = meilisearch
+        .register_update(path.into_inner(), update)
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RebuildIndexRequest {
+    /// Reindexes the documents with the current settings before compacting the index, `true` by
+    /// default.
+    #[serde(default = "default_reindex")]
+    reindex: bool,
+}
+
+fn default_reindex() -> bool {
+    true
+}
+
+/// Registers a task rebuilding the index. The documents are reindexed with the current settings,
+/// then the index file is compacted to reclaim its free pages. The task reports the number of
+/// bytes reclaimed.
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/rebuild",
+    tag = "Indexes",
+    params(("index_uid" = String, Path, description = "The uid of the index.")),
+    request_body = RebuildIndexRequest,
+    responses((status = 202, description = "The task is enqueued.", body = SummarizedTaskView)),
+    security(("apiKey" = ["indexes.update"]))
+)]
+pub async fn rebuild_index(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_UPDATE }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<RebuildIndexRequest>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let RebuildIndexRequest { reindex } = body.into_inner();
+
+    analytics.publish(
+        "Index Rebuilt".to_string(),
+        json!({ "reindex": reindex }),
+        Some(&req),
+    );
+
+    let update = Update::RebuildIndex { reindex };
+    let task: SummarizedTaskView = meilisearch
+        .register_update(path.into_inner(), update)
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}",
+    tag = "Indexes",
+    params(("index_uid" = String, Path, description = "The uid of the index.")),
+    responses((status = 202, description = "The task is enqueued.", body = SummarizedTaskView)),
+    security(("apiKey" = ["indexes.delete"]))
+)]
 pub async fn delete_index(
     meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_DELETE }>, MeiliSearch>,
     path: web::Path<String>,
@@ -152,6 +337,40 @@ pub async fn delete_index(
     Ok(HttpResponse::Accepted().json(task))
 }
 
+/// Registers a task restoring a deleted index from the trash, with its documents and settings.
+/// An index can be restored until the retention period of the trash elapses, and only if no
+/// index was created under its uid in the meantime.
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/restore",
+    tag = "Indexes",
+    params(("index_uid" = String, Path, description = "The uid of the deleted index.")),
+    responses((status = 202, description = "The task is enqueued.", body = SummarizedTaskView)),
+    security(("apiKey" = ["indexes.delete"]))
+)]
+pub async fn restore_index(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_DELETE }>, MeiliSearch>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let uid = path.into_inner();
+    let update = Update::RestoreIndex;
+    let task: SummarizedTaskView = meilisearch.register_update(uid, update).await?.into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/stats",
+    tag = "Indexes",
+    params(("index_uid" = String, Path, description = "The uid of the index.")),
+    responses(
+        (status = 200, description = "The stats of the index, with its storage breakdown and the last task applied to it.", body = Object),
+        (status = 404, description = "The index doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["stats.get"]))
+)]
 pub async fn get_index_stats(
     meilisearch: GuardedData<ActionPolicy<{ actions::STATS_GET }>, MeiliSearch>,
     path: web::Path<String>,
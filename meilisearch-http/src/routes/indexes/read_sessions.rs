@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use actix_web::{web, HttpResponse};
+use log::debug;
+use meilisearch_error::ResponseError;
+use meilisearch_lib::MeiliSearch;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(open_read_session))))
+        .service(web::resource("/{token}").route(web::delete().to(SeqHandler(close_read_session))));
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct ReadSessionQuery {
+    /// The number of seconds after which the session expires if it isn't read, 60 by default and
+    /// at most 600.
+    ttl: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct ReadSessionParam {
+    index_uid: String,
+    token: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/read-sessions",
+    tag = "Documents",
+    params(("index_uid" = String, Path, description = "The uid of the index."), ReadSessionQuery),
+    responses(
+        (status = 201, description = "The session is open. Its `token` is passed as the \
+            `readSession` parameter of the documents route to read the documents as they were \
+            when it was opened.", body = Object),
+        (status = 404, description = "The index doesn't exist.", body = ResponseError),
+        (status = 503, description = "Too many read sessions are open.", body = ResponseError),
+    ),
+    security(("apiKey" = ["documents.get"]))
+)]
+pub async fn open_read_session(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Query<ReadSessionQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let ttl = params.ttl.map(Duration::from_secs);
+    let session = meilisearch
+        .open_read_session(path.into_inner(), ttl)
+        .await?;
+
+    debug!("returns: {:?}", session);
+    Ok(HttpResponse::Created().json(session))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/read-sessions/{token}",
+    tag = "Documents",
+    params(
+        ("index_uid" = String, Path, description = "The uid of the index."),
+        ("token" = String, Path, description = "The token of the read session."),
+    ),
+    responses(
+        (status = 204, description = "The session is closed."),
+        (status = 404, description = "The session doesn't exist or expired.", body = ResponseError),
+    ),
+    security(("apiKey" = ["documents.get"]))
+)]
+pub async fn close_read_session(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
+    path: web::Path<ReadSessionParam>,
+) -> Result<HttpResponse, ResponseError> {
+    meilisearch.close_read_session(&path.index_uid, &path.token)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
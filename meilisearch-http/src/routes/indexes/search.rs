@@ -1,49 +1,64 @@
+use std::collections::BTreeMap;
+
 use actix_web::{web, HttpRequest, HttpResponse};
 use log::debug;
 use meilisearch_auth::IndexSearchRules;
 use meilisearch_error::ResponseError;
-use meilisearch_lib::index::{
-    default_crop_length, default_crop_marker, default_highlight_post_tag,
-    default_highlight_pre_tag, SearchQuery, DEFAULT_SEARCH_LIMIT,
-};
+use meilisearch_lib::index::{MatchingStrategy, SearchQuery};
 use meilisearch_lib::MeiliSearch;
 use serde::Deserialize;
 use serde_json::Value;
+use utoipa::IntoParams;
 
 use crate::analytics::{Analytics, SearchAggregator};
 use crate::extractors::authentication::{policies::*, GuardedData};
 use crate::extractors::sequential_extractor::SeqHandler;
 
+use super::live_search::live_search;
+
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::resource("")
             .route(web::get().to(SeqHandler(search_with_url_query)))
             .route(web::post().to(SeqHandler(search_with_post))),
-    );
+    )
+    .service(web::resource("/live").route(web::get().to(SeqHandler(live_search))));
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, IntoParams)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
 pub struct SearchQueryGet {
     q: Option<String>,
     offset: Option<usize>,
     limit: Option<usize>,
+    page: Option<usize>,
+    hits_per_page: Option<usize>,
     attributes_to_retrieve: Option<String>,
     attributes_to_crop: Option<String>,
-    #[serde(default = "default_crop_length")]
-    crop_length: usize,
+    crop_length: Option<usize>,
     attributes_to_highlight: Option<String>,
     filter: Option<String>,
     sort: Option<String>,
-    #[serde(default = "Default::default")]
-    matches: bool,
+    matches: Option<bool>,
     facets_distribution: Option<String>,
-    #[serde(default = "default_highlight_pre_tag")]
-    highlight_pre_tag: String,
-    #[serde(default = "default_highlight_post_tag")]
-    highlight_post_tag: String,
-    #[serde(default = "default_crop_marker")]
-    crop_marker: String,
+    distinct: Option<String>,
+    #[param(value_type = Option<String>)]
+    matching_strategy: Option<MatchingStrategy>,
+    locales: Option<String>,
+    highlight_pre_tag: Option<String>,
+    highlight_post_tag: Option<String>,
+    crop_marker: Option<String>,
+    show_ranking_score: Option<bool>,
+    show_ranking_score_details: Option<bool>,
+    ruleset: Option<String>,
+    /// The boosted attributes along with their weight, as in `title:2,overview:0.5`.
+    boost: Option<String>,
+    boost_filter: Option<String>,
+    boost_filter_weight: Option<f64>,
+    /// The time budget of the search, in milliseconds, which bounds the stages around the
+    /// ranking but not the ranking itself.
+    timeout_ms: Option<u64>,
 }
 
 impl From<SearchQueryGet> for SearchQuery {
@@ -64,20 +79,23 @@ impl From<SearchQueryGet> for SearchQuery {
             .facets_distribution
             .map(|attrs| attrs.split(',').map(String::from).collect());
 
-        let filter = match other.filter {
-            Some(f) => match serde_json::from_str(&f) {
-                Ok(v) => Some(v),
-                _ => Some(Value::String(f)),
-            },
-            None => None,
-        };
+        let locales = other
+            .locales
+            .map(|locales| locales.split(',').map(String::from).collect());
+
+        let filter = other.filter.map(parse_filter_parameter);
+        let boost_filter = other.boost_filter.map(parse_filter_parameter);
+
+        let boost = other.boost.map(|boost| parse_boost_parameter(&boost));
 
         let sort = other.sort.map(|attr| fix_sort_query_parameters(&attr));
 
         Self {
             q: other.q,
             offset: other.offset,
-            limit: other.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+            limit: other.limit,
+            page: other.page,
+            hits_per_page: other.hits_per_page,
             attributes_to_retrieve,
             attributes_to_crop,
             crop_length: other.crop_length,
@@ -86,15 +104,56 @@ impl From<SearchQueryGet> for SearchQuery {
             sort,
             matches: other.matches,
             facets_distribution,
+            distinct: other.distinct,
+            matching_strategy: other.matching_strategy,
+            locales,
             highlight_pre_tag: other.highlight_pre_tag,
             highlight_post_tag: other.highlight_post_tag,
             crop_marker: other.crop_marker,
+            show_ranking_score: other.show_ranking_score,
+            show_ranking_score_details: other.show_ranking_score_details,
+            ruleset: other.ruleset,
+            boost,
+            boost_filter,
+            boost_filter_weight: other.boost_filter_weight,
+            timeout_ms: other.timeout_ms,
+            retrievable_attributes: None,
         }
     }
 }
 
+fn parse_filter_parameter(filter: String) -> Value {
+    match serde_json::from_str(&filter) {
+        Ok(v) => v,
+        _ => Value::String(filter),
+    }
+}
+
+/// Parses the boosted attributes of a search with url query parameters. The weights that aren't a
+/// number are kept as `NaN` to be refused along with the other invalid weights.
+fn parse_boost_parameter(boost: &str) -> BTreeMap<String, f64> {
+    boost
+        .split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| match entry.rsplit_once(':') {
+            Some((attribute, weight)) => (
+                attribute.trim().to_string(),
+                weight.trim().parse().unwrap_or(f64::NAN),
+            ),
+            None => (entry.trim().to_string(), f64::NAN),
+        })
+        .collect()
+}
+
 /// Incorporate search rules in search query
-fn add_search_rules(query: &mut SearchQuery, rules: IndexSearchRules) {
+pub(crate) fn add_search_rules(query: &mut SearchQuery, rules: IndexSearchRules) {
+    // The default parameters are validated when they are set on the key.
+    if let Some(params) = rules.default_search_params {
+        if let Ok(defaults) = serde_json::from_value(Value::Object(params)) {
+            query.merge_defaults(defaults);
+        }
+    }
+    query.retrievable_attributes = rules.retrievable_attributes;
     query.filter = match (query.filter.take(), rules.filter) {
         (None, rules_filter) => rules_filter,
         (filter, None) => filter,
@@ -113,6 +172,15 @@ fn add_search_rules(query: &mut SearchQuery, rules: IndexSearchRules) {
     }
 }
 
+/// Identifies the client performing a search by its API key or tenant token, so that the
+/// searches queued by the different clients are processed in turn.
+pub(crate) fn search_requester(req: &HttpRequest) -> &str {
+    req.headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+}
+
 // TODO: TAMO: split on :asc, and :desc, instead of doing some weird things
 
 /// Transform the sort query parameter into something that matches the post expected format.
@@ -139,6 +207,18 @@ fn fix_sort_query_parameters(sort_query: &str) -> Vec<String> {
     sort_parameters
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/search",
+    tag = "Search",
+    params(("index_uid" = String, Path, description = "The uid of the index."), SearchQueryGet),
+    responses(
+        (status = 200, description = "The search results.", body = Object),
+        (status = 400, description = "The search query is invalid.", body = ResponseError),
+        (status = 404, description = "The index doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["search"]))
+)]
 pub async fn search_with_url_query(
     meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
     path: web::Path<String>,
@@ -161,7 +241,8 @@ pub async fn search_with_url_query(
 
     let mut aggregate = SearchAggregator::from_query(&query, &req);
 
-    let search_result = meilisearch.search(index_uid, query).await;
+    let requester = search_requester(&req);
+    let search_result = meilisearch.search(index_uid, query, requester).await;
     if let Ok(ref search_result) = search_result {
         aggregate.succeed(search_result);
     }
@@ -177,6 +258,19 @@ pub async fn search_with_url_query(
     Ok(HttpResponse::Ok().json(search_result))
 }
 
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/search",
+    tag = "Search",
+    params(("index_uid" = String, Path, description = "The uid of the index.")),
+    request_body(content = Object, description = "The search query."),
+    responses(
+        (status = 200, description = "The search results.", body = Object),
+        (status = 400, description = "The search query is invalid.", body = ResponseError),
+        (status = 404, description = "The index doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["search"]))
+)]
 pub async fn search_with_post(
     meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
     path: web::Path<String>,
@@ -199,7 +293,8 @@ pub async fn search_with_post(
 
     let mut aggregate = SearchAggregator::from_query(&query, &req);
 
-    let search_result = meilisearch.search(index_uid, query).await;
+    let requester = search_requester(&req);
+    let search_result = meilisearch.search(index_uid, query, requester).await;
     if let Ok(ref search_result) = search_result {
         aggregate.succeed(search_result);
     }
@@ -252,4 +347,14 @@ mod test {
             ]
         );
     }
+    #[test]
+    fn test_parse_boost_parameter() {
+        let boost = parse_boost_parameter("title:2, overview:0.5");
+        assert_eq!(boost.get("title"), Some(&2.0));
+        assert_eq!(boost.get("overview"), Some(&0.5));
+
+        let boost = parse_boost_parameter("title:high,overview");
+        assert!(boost["title"].is_nan());
+        assert!(boost["overview"].is_nan());
+    }
 }
@@ -1,14 +1,24 @@
 use log::debug;
 
+use actix_web::error::JsonPayloadError;
 use actix_web::{web, HttpRequest, HttpResponse};
+use futures::StreamExt;
 use meilisearch_error::ResponseError;
+use meilisearch_lib::index::synonyms::{
+    self as synonyms_file, SynonymsFormat, SynonymsImportMode, SynonymsImportReport,
+};
 use meilisearch_lib::index::{Settings, Unchecked};
 use meilisearch_lib::index_controller::Update;
+use meilisearch_lib::milli::update::Setting;
 use meilisearch_lib::MeiliSearch;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use utoipa::IntoParams;
 
 use crate::analytics::Analytics;
+use crate::error::PayloadError;
 use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::payload::Payload;
 use crate::task::SummarizedTaskView;
 
 #[macro_export]
@@ -27,6 +37,9 @@ macro_rules! make_setting_route {
             use $crate::extractors::sequential_extractor::SeqHandler;
             use $crate::task::SummarizedTaskView;
 
+            pub const ROUTE: &str = $route;
+            pub const ATTRIBUTE: &str = $camelcase_attr;
+
             pub async fn delete(
                 meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
                 index_uid: web::Path<String>,
@@ -168,6 +181,69 @@ make_setting_route!(
     "typoTolerance"
 );
 
+make_setting_route!(
+    "/localized-attributes",
+    Vec<meilisearch_lib::index::updates::LocalizedAttributesRule>,
+    localized_attributes,
+    "localizedAttributes"
+);
+
+make_setting_route!(
+    "/separator-tokens",
+    std::collections::BTreeSet<String>,
+    separator_tokens,
+    "separatorTokens"
+);
+
+make_setting_route!(
+    "/non-separator-tokens",
+    std::collections::BTreeSet<String>,
+    non_separator_tokens,
+    "nonSeparatorTokens"
+);
+
+make_setting_route!(
+    "/dictionary",
+    std::collections::BTreeSet<String>,
+    dictionary,
+    "dictionary"
+);
+
+make_setting_route!(
+    "/formatting",
+    meilisearch_lib::index::updates::FormattingSettings,
+    formatting,
+    "formatting"
+);
+
+make_setting_route!(
+    "/faceting",
+    meilisearch_lib::index::updates::FacetingSettings,
+    faceting,
+    "faceting"
+);
+
+make_setting_route!(
+    "/version-field",
+    String,
+    version_field,
+    "versionField"
+);
+
+make_setting_route!(
+    "/document-compression",
+    meilisearch_lib::index::updates::DocumentCompression,
+    document_compression,
+    "documentCompression"
+);
+
+make_setting_route!(
+    "/rulesets",
+    std::collections::BTreeMap<String, meilisearch_lib::index::updates::Ruleset>,
+    rulesets,
+    "rulesets"
+);
+
 make_setting_route!(
     "/searchable-attributes",
     Vec<String>,
@@ -191,7 +267,7 @@ make_setting_route!(
 
 make_setting_route!(
     "/stop-words",
-    std::collections::BTreeSet<String>,
+    meilisearch_lib::index::updates::StopWords,
     stop_words,
     "stopWords"
 );
@@ -233,6 +309,10 @@ make_setting_route!(
 
 macro_rules! generate_configure {
     ($($mod:ident),*) => {
+        /// The route and the attribute of each setting, they are documented from this list since
+        /// their handlers are generated.
+        pub(crate) const SETTING_ROUTES: &[(&str, &str)] = &[$(($mod::ROUTE, $mod::ATTRIBUTE)),*];
+
         pub fn configure(cfg: &mut web::ServiceConfig) {
             use crate::extractors::sequential_extractor::SeqHandler;
             cfg.service(
@@ -240,7 +320,11 @@ macro_rules! generate_configure {
                 .route(web::post().to(SeqHandler(update_all)))
                 .route(web::get().to(SeqHandler(get_all)))
                 .route(web::delete().to(SeqHandler(delete_all))))
-                $(.service($mod::resources()))*;
+                $(.service($mod::resources()))*
+                .service(web::resource("/synonyms/export")
+                    .route(web::get().to(SeqHandler(export_synonyms))))
+                .service(web::resource("/synonyms/import")
+                    .route(web::post().to(SeqHandler(import_synonyms))));
         }
     };
 }
@@ -254,9 +338,30 @@ generate_configure!(
     stop_words,
     synonyms,
     ranking_rules,
-    typo_tolerance
+    typo_tolerance,
+    localized_attributes,
+    separator_tokens,
+    non_separator_tokens,
+    dictionary,
+    formatting,
+    faceting,
+    version_field,
+    document_compression,
+    rulesets
 );
 
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/settings",
+    tag = "Settings",
+    params(("index_uid" = String, Path, description = "The uid of the index.")),
+    request_body(content = Object, description = "The settings to update, the omitted ones are kept."),
+    responses(
+        (status = 202, description = "The task is enqueued.", body = SummarizedTaskView),
+        (status = 400, description = "The settings are invalid.", body = ResponseError),
+    ),
+    security(("apiKey" = ["settings.update"]))
+)]
 pub async fn update_all(
     meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
     index_uid: web::Path<String>,
@@ -302,6 +407,17 @@ pub async fn update_all(
     Ok(HttpResponse::Accepted().json(task))
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/settings",
+    tag = "Settings",
+    params(("index_uid" = String, Path, description = "The uid of the index.")),
+    responses(
+        (status = 200, description = "All the settings of the index.", body = Object),
+        (status = 404, description = "The index doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["settings.get"]))
+)]
 pub async fn get_all(
     data: GuardedData<ActionPolicy<{ actions::SETTINGS_GET }>, MeiliSearch>,
     index_uid: web::Path<String>,
@@ -311,6 +427,14 @@ pub async fn get_all(
     Ok(HttpResponse::Ok().json(settings))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/settings",
+    tag = "Settings",
+    params(("index_uid" = String, Path, description = "The uid of the index.")),
+    responses((status = 202, description = "The task is enqueued.", body = SummarizedTaskView)),
+    security(("apiKey" = ["settings.update"]))
+)]
 pub async fn delete_all(
     data: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
     index_uid: web::Path<String>,
@@ -331,3 +455,121 @@ pub async fn delete_all(
     debug!("returns: {:?}", task);
     Ok(HttpResponse::Accepted().json(task))
 }
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct SynonymsExportQuery {
+    /// `csv` or `solr`.
+    #[param(value_type = String)]
+    format: SynonymsFormat,
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/settings/synonyms/export",
+    tag = "Settings",
+    params(("index_uid" = String, Path, description = "The uid of the index."), SynonymsExportQuery),
+    responses(
+        (status = 200, description = "The synonyms file.", body = String, content_type = "text/plain"),
+        (status = 404, description = "The index doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["settings.get"]))
+)]
+pub async fn export_synonyms(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_GET }>, MeiliSearch>,
+    index_uid: web::Path<String>,
+    params: web::Query<SynonymsExportQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let settings = meilisearch.settings(index_uid.into_inner()).await?;
+    let synonyms = settings.synonyms.set().unwrap_or_default();
+    let content = synonyms_file::export_synonyms(params.format, &synonyms)?;
+
+    let content_type = match params.format {
+        SynonymsFormat::Csv => "text/csv",
+        SynonymsFormat::Solr => "text/plain",
+    };
+
+    Ok(HttpResponse::Ok().content_type(content_type).body(content))
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct SynonymsImportQuery {
+    /// `csv` or `solr`.
+    #[param(value_type = String)]
+    format: SynonymsFormat,
+    /// `merge` or `replace`.
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    mode: SynonymsImportMode,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SynonymsImportView {
+    #[serde(flatten)]
+    task: SummarizedTaskView,
+    #[serde(flatten)]
+    report: SynonymsImportReport,
+}
+
+/// Imports a synonyms file in the synonyms setting. When merging, the file is merged with the
+/// synonyms of the index at the time of the request.
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/settings/synonyms/import",
+    tag = "Settings",
+    params(("index_uid" = String, Path, description = "The uid of the index."), SynonymsImportQuery),
+    request_body(content = String, description = "A synonyms file in the format of the `format` parameter.", content_type = "text/plain"),
+    responses(
+        (status = 202, description = "The task is enqueued, with a report of the imported synonyms.", body = SummarizedTaskView),
+        (status = 400, description = "The synonyms file is invalid.", body = ResponseError),
+    ),
+    security(("apiKey" = ["settings.update"]))
+)]
+pub async fn import_synonyms(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
+    index_uid: web::Path<String>,
+    params: web::Query<SynonymsImportQuery>,
+    mut body: Payload,
+) -> Result<HttpResponse, ResponseError> {
+    let mut content = Vec::new();
+    while let Some(bytes) = body.next().await {
+        let bytes = bytes.map_err(|e| PayloadError::from(JsonPayloadError::Payload(e)))?;
+        content.extend_from_slice(&bytes);
+    }
+
+    let imported = synonyms_file::parse_synonyms(params.format, &content)?;
+
+    let index_uid = index_uid.into_inner();
+    let existing = match params.mode {
+        SynonymsImportMode::Merge => meilisearch
+            .settings(index_uid.clone())
+            .await?
+            .synonyms
+            .set()
+            .unwrap_or_default(),
+        SynonymsImportMode::Replace => Default::default(),
+    };
+
+    let (synonyms, report) = synonyms_file::import_synonyms(existing, imported, params.mode);
+
+    let settings = Settings {
+        synonyms: Setting::Set(synonyms),
+        ..Default::default()
+    };
+
+    let allow_index_creation = meilisearch.filters().allow_index_creation;
+    let update = Update::Settings {
+        settings,
+        is_deletion: false,
+        allow_index_creation,
+    };
+    let task: SummarizedTaskView = meilisearch.register_update(index_uid, update).await?.into();
+
+    let view = SynonymsImportView { task, report };
+    debug!("returns: {:?}", view);
+    Ok(HttpResponse::Accepted().json(view))
+}
@@ -0,0 +1,54 @@
+use actix_web::{web, HttpResponse};
+use log::debug;
+use meilisearch_error::ResponseError;
+use meilisearch_lib::index::{SuggestQuery, DEFAULT_SUGGEST_LIMIT};
+use meilisearch_lib::MeiliSearch;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(SeqHandler(suggest))));
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct SuggestParams {
+    /// The query to complete, its last word is completed with the words of the index.
+    q: String,
+    #[serde(default = "default_suggest_limit")]
+    limit: usize,
+}
+
+fn default_suggest_limit() -> usize {
+    DEFAULT_SUGGEST_LIMIT
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/suggest",
+    tag = "Search",
+    params(("index_uid" = String, Path, description = "The uid of the index."), SuggestParams),
+    responses(
+        (status = 200, description = "The completions of the query, the ones matching the most documents first.", body = Object),
+        (status = 404, description = "The index doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["suggest"]))
+)]
+pub async fn suggest(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SUGGEST }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Query<SuggestParams>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", params);
+    let SuggestParams { q, limit } = params.into_inner();
+    let query = SuggestQuery { q, limit };
+
+    let result = meilisearch.suggest(path.into_inner(), query).await?;
+
+    debug!("returns: {:?}", result);
+    Ok(HttpResponse::Ok().json(result))
+}
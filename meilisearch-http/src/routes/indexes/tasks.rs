@@ -5,6 +5,7 @@ use meilisearch_lib::MeiliSearch;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use time::OffsetDateTime;
+use utoipa::IntoParams;
 
 use crate::analytics::Analytics;
 use crate::extractors::authentication::{policies::*, GuardedData};
@@ -29,12 +30,26 @@ pub struct UpdateIndexResponse {
     primary_key: OffsetDateTime,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
 pub struct UpdateParam {
+    /// The uid of the index.
     index_uid: String,
+    /// The uid of the task.
     task_id: u64,
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/tasks/{task_id}",
+    tag = "Tasks",
+    params(UpdateParam),
+    responses(
+        (status = 200, description = "The task.", body = TaskView),
+        (status = 404, description = "The index or the task doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["tasks.get"]))
+)]
 pub async fn get_task_status(
     meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
     index_uid: web::Path<UpdateParam>,
@@ -55,6 +70,17 @@ pub async fn get_task_status(
     Ok(HttpResponse::Ok().json(task))
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/tasks",
+    tag = "Tasks",
+    params(("index_uid" = String, Path, description = "The uid of the index.")),
+    responses(
+        (status = 200, description = "The tasks of the index.", body = TaskListView),
+        (status = 404, description = "The index doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["tasks.get"]))
+)]
 pub async fn get_all_tasks_status(
     meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
     index_uid: web::Path<String>,
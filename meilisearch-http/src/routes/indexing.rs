@@ -0,0 +1,55 @@
+use actix_web::{web, HttpResponse};
+use log::debug;
+use meilisearch_error::ResponseError;
+use meilisearch_lib::tasks::IndexingSettingsUpdate;
+use meilisearch_lib::MeiliSearch;
+
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/indexing")
+            .route(web::get().to(SeqHandler(get_indexing_settings)))
+            .route(web::patch().to(SeqHandler(update_indexing_settings))),
+    );
+}
+
+#[utoipa::path(
+    get,
+    path = "/settings/indexing",
+    tag = "Indexing",
+    responses((status = 200, description = "The auto-batching parameters of the task scheduler.", body = Object)),
+    security(("apiKey" = []))
+)]
+pub async fn get_indexing_settings(
+    meilisearch: GuardedData<MasterPolicy, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    let settings = meilisearch.indexing_settings().await;
+
+    debug!("returns: {:?}", settings);
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/settings/indexing",
+    tag = "Indexing",
+    request_body(content = Object, description = "The parameters to update, `null` removes a limit."),
+    responses(
+        (status = 200, description = "The updated auto-batching parameters.", body = Object),
+        (status = 400, description = "The update is invalid.", body = ResponseError),
+    ),
+    security(("apiKey" = []))
+)]
+pub async fn update_indexing_settings(
+    meilisearch: GuardedData<MasterPolicy, MeiliSearch>,
+    body: web::Json<IndexingSettingsUpdate>,
+) -> Result<HttpResponse, ResponseError> {
+    let settings = meilisearch
+        .update_indexing_settings(body.into_inner())
+        .await;
+
+    debug!("returns: {:?}", settings);
+    Ok(HttpResponse::Ok().json(settings))
+}
@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::Method;
+use futures::future::{ready, Ready};
+use meilisearch_error::ResponseError;
+use serde::Serialize;
+
+use crate::error::MeilisearchHttpError;
+use crate::Opt;
+
+/// The groups of routes that can be disabled with the `disabled-routes` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum RouteGroup {
+    #[serde(rename = "keys")]
+    Keys,
+    #[serde(rename = "dumps")]
+    Dumps,
+    #[serde(rename = "migrations")]
+    Migrations,
+    #[serde(rename = "replication")]
+    Replication,
+    #[serde(rename = "dashboard")]
+    Dashboard,
+    #[serde(rename = "stats")]
+    Stats,
+    /// The creation, update, rename, rebuild, deletion and restoration of the indexes.
+    #[serde(rename = "indexes.write")]
+    IndexesWrite,
+    #[serde(rename = "documents.write")]
+    DocumentsWrite,
+    /// The updates of the settings of the indexes, of the indexing and of the index templates.
+    #[serde(rename = "settings.write")]
+    SettingsWrite,
+}
+
+impl RouteGroup {
+    const ALL: [RouteGroup; 9] = [
+        RouteGroup::Keys,
+        RouteGroup::Dumps,
+        RouteGroup::Migrations,
+        RouteGroup::Replication,
+        RouteGroup::Dashboard,
+        RouteGroup::Stats,
+        RouteGroup::IndexesWrite,
+        RouteGroup::DocumentsWrite,
+        RouteGroup::SettingsWrite,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            RouteGroup::Keys => "keys",
+            RouteGroup::Dumps => "dumps",
+            RouteGroup::Migrations => "migrations",
+            RouteGroup::Replication => "replication",
+            RouteGroup::Dashboard => "dashboard",
+            RouteGroup::Stats => "stats",
+            RouteGroup::IndexesWrite => "indexes.write",
+            RouteGroup::DocumentsWrite => "documents.write",
+            RouteGroup::SettingsWrite => "settings.write",
+        }
+    }
+
+    /// Returns the group of the route, if it belongs to one.
+    fn of_route(method: &Method, path: &str) -> Option<Self> {
+        let segments: Vec<_> = path.trim_start_matches('/').split('/').collect();
+        let is_write = !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+
+        match segments.as_slice() {
+            ["keys", ..] => Some(RouteGroup::Keys),
+            ["dumps", ..] => Some(RouteGroup::Dumps),
+            ["migrations", ..] => Some(RouteGroup::Migrations),
+            ["replication", ..] => Some(RouteGroup::Replication),
+            ["dashboard", ..] => Some(RouteGroup::Dashboard),
+            ["stats", ..] | ["slow-queries"] => Some(RouteGroup::Stats),
+            _ if !is_write => None,
+            ["settings", ..] | ["index-templates", ..] => Some(RouteGroup::SettingsWrite),
+            ["indexes"] | ["indexes", _] | ["indexes", _, "rebuild" | "restore"] => {
+                Some(RouteGroup::IndexesWrite)
+            }
+            ["indexes", _, "documents", ..] => Some(RouteGroup::DocumentsWrite),
+            ["indexes", _, "settings", ..] => Some(RouteGroup::SettingsWrite),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RouteGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for RouteGroup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RouteGroup::ALL
+            .iter()
+            .find(|group| group.name() == s.trim())
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = RouteGroup::ALL.iter().map(RouteGroup::name).collect();
+                format!(
+                    "`{}` is not a route group, expected one of {}.",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Refuses the requests to the disabled route groups with a `403 Forbidden`, whatever their
+/// key. The instances running without a master key can thus still close some of their routes.
+#[derive(Clone)]
+pub struct RouteLockdown {
+    disabled: Arc<HashSet<RouteGroup>>,
+}
+
+impl RouteLockdown {
+    pub fn new(opt: &Opt) -> Self {
+        Self {
+            disabled: Arc::new(opt.disabled_routes.iter().copied().collect()),
+        }
+    }
+
+    /// Returns an error if the group is disabled.
+    pub fn check(&self, group: RouteGroup) -> Result<(), MeilisearchHttpError> {
+        if self.disabled.contains(&group) {
+            Err(MeilisearchHttpError::RouteDisabled(group.to_string()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RouteLockdown
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = RouteLockdownMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RouteLockdownMiddleware {
+            service,
+            lockdown: self.clone(),
+        }))
+    }
+}
+
+pub struct RouteLockdownMiddleware<S> {
+    service: S,
+    lockdown: RouteLockdown,
+}
+
+impl<S, B> Service<ServiceRequest> for RouteLockdownMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let checked = match RouteGroup::of_route(req.method(), req.path()) {
+            Some(group) => self.lockdown.check(group),
+            None => Ok(()),
+        };
+        if let Err(error) = checked {
+            let error = ResponseError::from(error);
+            return Box::pin(ready(Err(error.into())));
+        }
+
+        Box::pin(self.service.call(req))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn routes_belong_to_their_group() {
+        let group = |method, path| RouteGroup::of_route(&method, path);
+
+        assert_eq!(group(Method::GET, "/keys/abcd"), Some(RouteGroup::Keys));
+        assert_eq!(group(Method::POST, "/dumps"), Some(RouteGroup::Dumps));
+        assert_eq!(group(Method::GET, "/stats"), Some(RouteGroup::Stats));
+        assert_eq!(group(Method::GET, "/slow-queries"), Some(RouteGroup::Stats));
+        assert_eq!(
+            group(Method::POST, "/indexes"),
+            Some(RouteGroup::IndexesWrite)
+        );
+        assert_eq!(
+            group(Method::DELETE, "/indexes/movies"),
+            Some(RouteGroup::IndexesWrite)
+        );
+        assert_eq!(
+            group(Method::POST, "/indexes/movies/restore"),
+            Some(RouteGroup::IndexesWrite)
+        );
+        assert_eq!(
+            group(Method::POST, "/indexes/movies/documents/delete-batch"),
+            Some(RouteGroup::DocumentsWrite)
+        );
+        assert_eq!(
+            group(Method::PATCH, "/indexes/movies/settings"),
+            Some(RouteGroup::SettingsWrite)
+        );
+        assert_eq!(
+            group(Method::PUT, "/settings/indexing"),
+            Some(RouteGroup::SettingsWrite)
+        );
+        // The reads and the searches don't belong to a write group.
+        assert_eq!(group(Method::GET, "/indexes/movies/settings"), None);
+        assert_eq!(group(Method::GET, "/indexes"), None);
+        assert_eq!(group(Method::POST, "/indexes/movies/search"), None);
+        assert_eq!(group(Method::GET, "/health"), None);
+    }
+
+    #[test]
+    fn parse_route_groups() {
+        assert_eq!("keys".parse(), Ok(RouteGroup::Keys));
+        assert_eq!(" settings.write".parse(), Ok(RouteGroup::SettingsWrite));
+        assert!("settings".parse::<RouteGroup>().is_err());
+    }
+}
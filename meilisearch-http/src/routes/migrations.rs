@@ -0,0 +1,142 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_error::ResponseError;
+use meilisearch_lib::index_controller::migration::error::MigrationError;
+use meilisearch_lib::index_controller::migration::{
+    Migration, MigrationSource, DEFAULT_MIGRATION_BATCH_SIZE,
+};
+use meilisearch_lib::MeiliSearch;
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::IntoParams;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::error::AuthenticationError;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("")
+            .route(web::post().to(SeqHandler(create_migration)))
+            .route(web::get().to(SeqHandler(list_migrations))),
+    )
+    .service(web::resource("/{migration_uid}").route(web::get().to(SeqHandler(get_migration))));
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MigrationRequest {
+    index_uid: String,
+    primary_key: Option<String>,
+    batch_size: Option<usize>,
+    source: MigrationSource,
+}
+
+#[utoipa::path(
+    post,
+    path = "/migrations",
+    tag = "Migrations",
+    request_body(content = Object, description = "The index to import the documents in and the Algolia or Elasticsearch source."),
+    responses(
+        (status = 202, description = "The migration is started.", body = Object),
+        (status = 400, description = "The source is invalid.", body = ResponseError),
+    ),
+    security(("apiKey" = ["migrations.create"]))
+)]
+pub async fn create_migration(
+    meilisearch: GuardedData<ActionPolicy<{ actions::MIGRATIONS_CREATE }>, MeiliSearch>,
+    body: web::Json<MigrationRequest>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let MigrationRequest {
+        index_uid,
+        primary_key,
+        batch_size,
+        source,
+    } = body.into_inner();
+
+    if !meilisearch
+        .filters()
+        .search_rules
+        .is_index_authorized(&index_uid)
+    {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+
+    let migration = Migration {
+        source,
+        index_uid,
+        primary_key,
+        batch_size: batch_size.unwrap_or(DEFAULT_MIGRATION_BATCH_SIZE),
+    };
+
+    let res = meilisearch.create_migration(migration)?;
+
+    analytics.publish(
+        "Migration Created".to_string(),
+        json!({ "source": res.source }),
+        Some(&req),
+    );
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Accepted().json(res))
+}
+
+#[utoipa::path(
+    get,
+    path = "/migrations",
+    tag = "Migrations",
+    responses((status = 200, description = "All the migrations, the most recent first.", body = Object)),
+    security(("apiKey" = ["migrations.get"]))
+)]
+pub async fn list_migrations(
+    meilisearch: GuardedData<ActionPolicy<{ actions::MIGRATIONS_GET }>, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    let search_rules = &meilisearch.filters().search_rules;
+    let res: Vec<_> = meilisearch
+        .list_migrations()
+        .into_iter()
+        .filter(|info| search_rules.is_index_authorized(&info.index_uid))
+        .collect();
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(json!({ "results": res })))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+pub struct MigrationParam {
+    migration_uid: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/migrations/{migration_uid}",
+    tag = "Migrations",
+    params(MigrationParam),
+    responses(
+        (status = 200, description = "The progress of the migration.", body = Object),
+        (status = 404, description = "The migration doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["migrations.get"]))
+)]
+pub async fn get_migration(
+    meilisearch: GuardedData<ActionPolicy<{ actions::MIGRATIONS_GET }>, MeiliSearch>,
+    path: web::Path<MigrationParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let res = meilisearch.migration_info(&path.migration_uid)?;
+
+    // The migrations of the indexes the key can't access are hidden, like their tasks.
+    if !meilisearch
+        .filters()
+        .search_rules
+        .is_index_authorized(&res.index_uid)
+    {
+        return Err(MigrationError::MigrationNotFound(path.migration_uid.clone()).into());
+    }
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
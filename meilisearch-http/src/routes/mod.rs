@@ -1,27 +1,64 @@
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
 use log::debug;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
 
-use meilisearch_error::ResponseError;
+use meilisearch_auth::AuthController;
+use meilisearch_error::{Code, ResponseError};
 use meilisearch_lib::index::{Settings, Unchecked};
+use meilisearch_lib::index_controller::health::Health;
 use meilisearch_lib::MeiliSearch;
 
 use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::payload::PayloadLimits;
 
 mod api_key;
+mod dashboard;
 mod dump;
+#[cfg(feature = "graphql")]
+mod graphql;
+mod index_templates;
 pub mod indexes;
-mod tasks;
+mod indexing;
+mod lockdown;
+mod migrations;
+mod namespace;
+mod openapi;
+mod rate_limit;
+mod replication;
+pub(crate) mod tasks;
+mod usage;
+mod write_breaker;
 
-pub fn configure(cfg: &mut web::ServiceConfig) {
+pub use dashboard::DashboardSession;
+pub use lockdown::{RouteGroup, RouteLockdown};
+pub use namespace::Namespace;
+pub use rate_limit::{RateLimit, RateLimiter};
+pub use replication::ReadOnlyReplica;
+pub use usage::UsageRecorder;
+pub use write_breaker::WriteBreaker;
+
+pub fn configure(cfg: &mut web::ServiceConfig, limits: PayloadLimits) {
     cfg.service(web::scope("/tasks").configure(tasks::configure))
         .service(web::resource("/health").route(web::get().to(get_health)))
         .service(web::scope("/keys").configure(api_key::configure))
         .service(web::scope("/dumps").configure(dump::configure))
+        .service(web::scope("/migrations").configure(migrations::configure))
+        .service(web::scope("/replication").configure(replication::configure))
+        .service(web::scope("/settings").configure(indexing::configure))
+        .service(web::scope("/index-templates").configure(index_templates::configure))
         .service(web::resource("/stats").route(web::get().to(get_stats)))
+        .service(web::resource("/stats/timeseries").route(web::get().to(get_timeseries)))
+        .service(web::resource("/stats/search-cache").route(web::get().to(get_search_cache_stats)))
+        .service(web::resource("/slow-queries").route(web::get().to(get_slow_queries)))
         .service(web::resource("/version").route(web::get().to(get_version)))
-        .service(web::scope("/indexes").configure(indexes::configure));
+        .service(web::resource("/openapi.json").route(web::get().to(openapi::get_openapi)))
+        .service(web::scope("/dashboard").configure(dashboard::configure))
+        .service(web::scope("/indexes").configure(|cfg| indexes::configure(cfg, limits)));
+
+    #[cfg(feature = "graphql")]
+    cfg.service(web::resource("/graphql").route(web::post().to(graphql::graphql)));
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -132,17 +169,113 @@ pub async fn running() -> HttpResponse {
     HttpResponse::Ok().json(serde_json::json!({ "status": "Meilisearch is running" }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/stats",
+    tag = "Stats",
+    responses((status = 200, description = "The stats of the instance and its indexes.", body = Object)),
+    security(("apiKey" = ["stats.get"]))
+)]
 async fn get_stats(
     meilisearch: GuardedData<ActionPolicy<{ actions::STATS_GET }>, MeiliSearch>,
 ) -> Result<HttpResponse, ResponseError> {
+    let filters = meilisearch.filters();
+    let mut response = meilisearch.get_all_stats(&filters.search_rules).await?;
+    response
+        .indexes
+        .retain(|uid, _| filters.is_in_namespace(uid));
+
+    debug!("returns: {:?}", response);
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+struct TimeseriesQuery {
+    /// The duration covered by the timeseries, in seconds.
+    #[serde(default = "default_timeseries_window")]
+    window: i64,
+    /// The duration aggregated by each point of the timeseries, in seconds.
+    #[serde(default = "default_timeseries_interval")]
+    interval: i64,
+}
+
+fn default_timeseries_window() -> i64 {
+    3600
+}
+
+fn default_timeseries_interval() -> i64 {
+    60
+}
+
+#[utoipa::path(
+    get,
+    path = "/stats/timeseries",
+    tag = "Stats",
+    params(TimeseriesQuery),
+    responses(
+        (status = 200, description = "The activity of the indexes over the window.", body = Object),
+        (status = 400, description = "The window or the interval is invalid.", body = ResponseError),
+    ),
+    security(("apiKey" = ["stats.get"]))
+)]
+async fn get_timeseries(
+    meilisearch: GuardedData<ActionPolicy<{ actions::STATS_GET }>, MeiliSearch>,
+    params: web::Query<TimeseriesQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let TimeseriesQuery { window, interval } = params.into_inner();
     let search_rules = &meilisearch.filters().search_rules;
-    let response = meilisearch.get_all_stats(search_rules).await?;
+    let indexes = meilisearch.timeseries(window, interval, search_rules)?;
+
+    let response = serde_json::json!({
+        "window": window,
+        "interval": interval,
+        "indexes": indexes,
+    });
 
     debug!("returns: {:?}", response);
     Ok(HttpResponse::Ok().json(response))
 }
 
-#[derive(Serialize)]
+#[utoipa::path(
+    get,
+    path = "/stats/search-cache",
+    tag = "Stats",
+    responses((status = 200, description = "The hit and miss counters of the search cache.", body = Object)),
+    security(("apiKey" = ["stats.get"]))
+)]
+async fn get_search_cache_stats(
+    meilisearch: GuardedData<ActionPolicy<{ actions::STATS_GET }>, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    let response = meilisearch.search_cache_stats();
+
+    debug!("returns: {:?}", response);
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/slow-queries",
+    tag = "Stats",
+    responses((status = 200, description = "The slowest recent searches.", body = Object)),
+    security(("apiKey" = ["stats.get"]))
+)]
+async fn get_slow_queries(
+    meilisearch: GuardedData<ActionPolicy<{ actions::STATS_GET }>, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    let search_rules = &meilisearch.filters().search_rules;
+    let response: Vec<_> = meilisearch
+        .slow_queries()
+        .into_iter()
+        .filter(|entry| search_rules.is_index_authorized(&entry.index_uid))
+        .collect();
+
+    debug!("returns: {:?}", response);
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "results": response })))
+}
+
+#[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct VersionResponse {
     commit_sha: String,
@@ -150,6 +283,13 @@ struct VersionResponse {
     pkg_version: String,
 }
 
+#[utoipa::path(
+    get,
+    path = "/version",
+    tag = "Version",
+    responses((status = 200, description = "The version of Meilisearch.", body = VersionResponse)),
+    security(("apiKey" = ["version"]))
+)]
 async fn get_version(
     _meilisearch: GuardedData<ActionPolicy<{ actions::VERSION }>, MeiliSearch>,
 ) -> HttpResponse {
@@ -169,6 +309,87 @@ struct KeysResponse {
     public: Option<String>,
 }
 
-pub async fn get_health() -> Result<HttpResponse, ResponseError> {
-    Ok(HttpResponse::Ok().json(serde_json::json!({ "status": "available" })))
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum HealthMode {
+    Live,
+    Ready,
+}
+
+impl Default for HealthMode {
+    fn default() -> Self {
+        Self::Live
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+struct HealthQuery {
+    /// `live` by default, `ready` also fails while the tasks registered before the instance
+    /// started are still being processed, for the readiness probes.
+    #[serde(default)]
+    #[param(value_type = Option<String>, example = "ready")]
+    mode: HealthMode,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HealthResponse {
+    status: &'static str,
+    #[serde(flatten)]
+    health: Health,
+    auth_store_accessible: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "Health",
+    params(HealthQuery),
+    responses(
+        (status = 200, description = "Meilisearch is available, along with the state of its subsystems.", body = Object),
+        (status = 503, description = "The database or the API keys can't be used, or the tasks are replayed in the `ready` mode.", body = Object),
+    )
+)]
+pub async fn get_health(
+    req: HttpRequest,
+    params: web::Query<HealthQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let (meilisearch, auth) = match (
+        req.app_data::<MeiliSearch>(),
+        req.app_data::<AuthController>(),
+    ) {
+        (Some(meilisearch), Some(auth)) => (meilisearch, auth),
+        _ => {
+            return Err(ResponseError::from_msg(
+                "The application state is not available.".to_string(),
+                Code::Internal,
+            ))
+        }
+    };
+
+    let health = meilisearch.health().await?;
+    let auth_store_accessible = auth.is_accessible();
+    let available = health.database_writable
+        && auth_store_accessible
+        && !(params.mode == HealthMode::Ready && health.task_queue.replaying);
+
+    let status = if available {
+        "available"
+    } else {
+        "unavailable"
+    };
+    let response = HealthResponse {
+        status,
+        health,
+        auth_store_accessible,
+    };
+    debug!("returns: {:?}", response);
+
+    if available {
+        Ok(HttpResponse::Ok().json(response))
+    } else {
+        Ok(HttpResponse::ServiceUnavailable().json(response))
+    }
 }
@@ -0,0 +1,250 @@
+use std::pin::Pin;
+use std::str::FromStr;
+
+use actix_web::body::{self, EitherBody, MessageBody};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::ErrorInternalServerError;
+use actix_web::http::header::{AUTHORIZATION, CONTENT_TYPE};
+use actix_web::http::uri::{PathAndQuery, Uri};
+use actix_web::http::StatusCode;
+use futures::future::{ready, Ready};
+use futures::Future;
+use meilisearch_auth::AuthController;
+use meilisearch_error::ResponseError;
+use serde_json::Value;
+
+use crate::extractors::authentication::error::AuthenticationError;
+use crate::extractors::authentication::policies::extract_key_prefix;
+
+/// Confines the keys bound to a namespace to the indexes of their namespace. The uids of the
+/// indexes in the paths of their requests are prefixed with the namespace, and removed from the
+/// index uids of the responses, so that the keys address their indexes as if they were alone on
+/// the instance. The routes listing the indexes or the tasks only return the ones of the
+/// namespace, and the routes managing the whole instance are refused.
+pub struct Namespace;
+
+impl<S, B> Transform<S, ServiceRequest> for Namespace
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = NamespaceMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(NamespaceMiddleware { service }))
+    }
+}
+
+pub struct NamespaceMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for NamespaceMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let namespace = match key_namespace(&req) {
+            Some(namespace) => namespace,
+            None => {
+                let response = self.service.call(req);
+                return Box::pin(async move { Ok(response.await?.map_into_left_body()) });
+            }
+        };
+
+        if !is_namespaced_route(req.path()) {
+            let error = ResponseError::from(AuthenticationError::InvalidToken);
+            return Box::pin(ready(Err(error.into())));
+        }
+
+        let returns_documents = returns_documents(req.path());
+        if let Some(path) = namespaced_path(req.path(), &namespace) {
+            let path_and_query = match req.uri().query() {
+                Some(query) => format!("{}?{}", path, query),
+                None => path,
+            };
+            let mut parts = req.uri().clone().into_parts();
+            parts.path_and_query = PathAndQuery::from_str(&path_and_query).ok();
+            if let Ok(uri) = Uri::from_parts(parts) {
+                req.match_info_mut().get_mut().update(&uri);
+                req.head_mut().uri = uri;
+            }
+        }
+
+        let response = self.service.call(req);
+        Box::pin(async move {
+            let response = response.await?;
+            let is_json = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .map_or(false, |content_type| content_type == "application/json");
+            // The documents are returned as they are, only the tasks of their routes are changed.
+            if !is_json || (returns_documents && response.status() != StatusCode::ACCEPTED) {
+                return Ok(response.map_into_left_body());
+            }
+
+            let (req, response) = response.into_parts();
+            let (response, body) = response.into_parts();
+            let bytes = body::to_bytes(body).await.map_err(|e| {
+                let error: Box<dyn std::error::Error> = e.into();
+                ErrorInternalServerError(error)
+            })?;
+            let body = match serde_json::from_slice::<Value>(&bytes) {
+                Ok(mut value) => {
+                    strip_namespace(&mut value, &namespace);
+                    value.to_string().into_bytes().into()
+                }
+                Err(_) => bytes,
+            };
+
+            let response = response.set_body(body).map_into_boxed_body();
+            Ok(ServiceResponse::new(req, response).map_into_right_body())
+        })
+    }
+}
+
+/// Returns the namespace of the API key or tenant token of the request, if it has one.
+fn key_namespace(req: &ServiceRequest) -> Option<String> {
+    let auth = req.app_data::<AuthController>()?;
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")?;
+    if auth
+        .get_master_key()
+        .map_or(true, |master_key| master_key == token)
+    {
+        return None;
+    }
+
+    let key = extract_key_prefix(token).unwrap_or_else(|| token.to_string());
+    auth.get_key_namespace(key).ok().flatten()
+}
+
+/// The routes of the indexes and tasks, along with the routes that don't depend on the indexes
+/// and the usage webhook of the key.
+fn is_namespaced_route(path: &str) -> bool {
+    let segments: Vec<_> = path.trim_start_matches('/').split('/').collect();
+    matches!(
+        segments.as_slice(),
+        ["indexes" | "tasks" | "health" | "version", ..] | ["stats"] | ["keys", _, "usage-webhook"]
+    )
+}
+
+/// Whether the route returns documents, which are never changed.
+fn returns_documents(path: &str) -> bool {
+    let sub_route = path.trim_start_matches('/').split('/').nth(2);
+    matches!(
+        sub_route,
+        Some("documents" | "search" | "suggest" | "export" | "live-search")
+    )
+}
+
+/// Prefixes the uid of the index of an index route with the namespace.
+fn namespaced_path(path: &str, namespace: &str) -> Option<String> {
+    let route = path.strip_prefix("/indexes/")?;
+    if route.is_empty() {
+        return None;
+    }
+
+    Some(format!("/indexes/{}{}", namespace, route))
+}
+
+/// Removes the namespace from the index uids of a response: the `uid` and `indexUid` fields of
+/// the returned object, of the returned objects or of their `results`, and the keys of their
+/// `indexes` object.
+pub(crate) fn strip_namespace(value: &mut Value, namespace: &str) {
+    strip_index_uids(value, namespace);
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                strip_index_uids(value, namespace);
+            }
+        }
+        Value::Object(object) => {
+            if let Some(Value::Array(results)) = object.get_mut("results") {
+                for value in results {
+                    strip_index_uids(value, namespace);
+                }
+            }
+            if let Some(Value::Object(indexes)) = object.get_mut("indexes") {
+                *indexes = std::mem::take(indexes)
+                    .into_iter()
+                    .map(|(uid, stats)| {
+                        let uid = uid.strip_prefix(namespace).map(String::from).unwrap_or(uid);
+                        (uid, stats)
+                    })
+                    .collect();
+            }
+        }
+        _ => (),
+    }
+}
+
+fn strip_index_uids(value: &mut Value, namespace: &str) {
+    for field in ["uid", "indexUid"] {
+        if let Some(Value::String(uid)) = value.get_mut(field) {
+            if let Some(stripped) = uid.strip_prefix(namespace) {
+                *uid = stripped.to_string();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn namespaced_keys_only_address_their_indexes() {
+        assert_eq!(
+            namespaced_path("/indexes/movies/search", "acme_").as_deref(),
+            Some("/indexes/acme_movies/search")
+        );
+        assert_eq!(namespaced_path("/indexes", "acme_"), None);
+        assert!(is_namespaced_route("/tasks/12"));
+        assert!(is_namespaced_route("/stats"));
+        assert!(!is_namespaced_route("/stats/timeseries"));
+        assert!(!is_namespaced_route("/keys"));
+        assert!(is_namespaced_route("/keys/abcd/usage-webhook"));
+        assert!(!is_namespaced_route("/dumps"));
+    }
+
+    #[test]
+    fn strip_namespace_from_responses() {
+        let mut tasks = json!({ "results": [{ "uid": 1, "indexUid": "acme_movies" }] });
+        strip_namespace(&mut tasks, "acme_");
+        assert_eq!(
+            tasks,
+            json!({ "results": [{ "uid": 1, "indexUid": "movies" }] })
+        );
+
+        let mut indexes = json!([{ "uid": "acme_movies" }, { "uid": "acme_books" }]);
+        strip_namespace(&mut indexes, "acme_");
+        assert_eq!(indexes, json!([{ "uid": "movies" }, { "uid": "books" }]));
+
+        let mut stats = json!({ "databaseSize": 1, "indexes": { "acme_movies": {} } });
+        strip_namespace(&mut stats, "acme_");
+        assert_eq!(
+            stats,
+            json!({ "databaseSize": 1, "indexes": { "movies": {} } })
+        );
+    }
+}
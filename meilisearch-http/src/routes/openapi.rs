@@ -0,0 +1,288 @@
+//! The OpenAPI specification of the HTTP routes, generated from the annotations of the handlers.
+
+use actix_web::HttpResponse;
+use meilisearch_error::ResponseError;
+use once_cell::sync::Lazy;
+use utoipa::openapi::path::{
+    HttpMethod, Operation, OperationBuilder, ParameterBuilder, ParameterIn,
+};
+use utoipa::openapi::request_body::RequestBodyBuilder;
+use utoipa::openapi::schema::{ObjectBuilder, Type};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme};
+use utoipa::openapi::{ContentBuilder, Ref, Required, ResponseBuilder};
+use utoipa::{Modify, OpenApi};
+
+use super::indexes::settings::SETTING_ROUTES;
+use super::*;
+use crate::task::{
+    SummarizedTaskView, TaskListView, TaskProgressView, TaskStatus, TaskType, TaskView,
+};
+
+/// The specification is the same for the whole life of the process.
+static SPECIFICATION: Lazy<String> = Lazy::new(|| {
+    ApiDoc::openapi()
+        .to_json()
+        .expect("the OpenAPI specification can always be serialized")
+});
+
+pub async fn get_openapi() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(SPECIFICATION.as_str())
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Meilisearch"),
+    paths(
+        get_health,
+        get_stats,
+        get_timeseries,
+        get_search_cache_stats,
+        get_slow_queries,
+        get_version,
+        tasks::get_tasks,
+        tasks::watch_tasks,
+        tasks::get_task,
+        tasks::watch_task,
+        api_key::create_api_key,
+        api_key::list_api_keys,
+        api_key::get_api_key,
+        api_key::patch_api_key,
+        api_key::delete_api_key,
+        api_key::can_api_key,
+        api_key::get_api_key_permissions,
+        api_key::put_usage_webhook,
+        api_key::delete_usage_webhook,
+        dump::create_dump,
+        dump::get_dump_status,
+        migrations::create_migration,
+        migrations::list_migrations,
+        migrations::get_migration,
+        replication::get_replication_tasks,
+        replication::get_replication_task_content,
+        replication::get_replication_keys,
+        indexing::get_indexing_settings,
+        indexing::update_indexing_settings,
+        index_templates::list_index_templates,
+        index_templates::get_index_template,
+        index_templates::put_index_template,
+        index_templates::delete_index_template,
+        dashboard::login,
+        dashboard::logout,
+        dashboard::get_overview,
+        indexes::list_indexes,
+        indexes::create_index,
+        indexes::get_index,
+        indexes::update_index,
+        indexes::rename_index,
+        indexes::rebuild_index,
+        indexes::delete_index,
+        indexes::restore_index,
+        indexes::get_index_stats,
+        indexes::documents::get_all_documents,
+        indexes::documents::add_documents,
+        indexes::documents::update_documents,
+        indexes::documents::clear_all_documents,
+        indexes::documents::delete_documents,
+        indexes::documents::get_document,
+        indexes::documents::delete_document,
+        indexes::export::export_documents,
+        indexes::read_sessions::open_read_session,
+        indexes::read_sessions::close_read_session,
+        indexes::search::search_with_url_query,
+        indexes::search::search_with_post,
+        indexes::live_search::live_search,
+        indexes::suggest::suggest,
+        indexes::tasks::get_all_tasks_status,
+        indexes::tasks::get_task_status,
+        indexes::settings::update_all,
+        indexes::settings::get_all,
+        indexes::settings::delete_all,
+        indexes::settings::export_synonyms,
+        indexes::settings::import_synonyms,
+    ),
+    components(schemas(
+        ResponseError,
+        api_key::KeyView,
+        api_key::KeyListView,
+        api_key::KeyCreation,
+        api_key::KeyUpdate,
+        api_key::PermissionQuery,
+        api_key::UsageWebhookSettings,
+        api_key::KeyValidationView,
+        api_key::InvalidFieldView,
+        dashboard::LoginRequest,
+        dashboard::LoginResponse,
+        indexes::IndexCreateRequest,
+        indexes::UpdateIndexRequest,
+        indexes::RenameIndexRequest,
+        indexes::RebuildIndexRequest,
+        indexes::export::ExportRequest,
+        VersionResponse,
+        TaskView,
+        TaskListView,
+        TaskProgressView,
+        SummarizedTaskView,
+        TaskType,
+        TaskStatus,
+    )),
+    modifiers(&ApiKeySecurity, &SettingRoutes, &AuthenticationErrors),
+    tags(
+        (name = "Indexes"),
+        (name = "Documents"),
+        (name = "Search"),
+        (name = "Settings"),
+        (name = "Tasks"),
+        (name = "Keys", description = "Reserved to the master key."),
+        (name = "Dumps"),
+        (name = "Migrations", description = "Imports an index from Algolia or Elasticsearch."),
+        (name = "Replication", description = "Followed by the read-only replicas, reserved to the master key."),
+        (name = "Indexing", description = "The auto-batching parameters of the task scheduler, reserved to the master key."),
+        (name = "Index templates", description = "The settings applied to the indexes created with a matching uid, reserved to the master key."),
+        (name = "Stats"),
+        (name = "Dashboard"),
+        (name = "Health"),
+        (name = "Version"),
+    )
+)]
+pub struct ApiDoc;
+
+/// The routes are authenticated by the master key or an API key sent as a bearer token.
+struct ApiKeySecurity;
+
+impl Modify for ApiKeySecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "apiKey",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some("The master key, an API key or a tenant token."))
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// The handlers of the settings are generated by `make_setting_route!`, their operations are
+/// documented from the list of the settings routes.
+struct SettingRoutes;
+
+impl Modify for SettingRoutes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for (route, attribute) in SETTING_ROUTES {
+            let path = format!("/indexes/{{index_uid}}/settings{}", route);
+            openapi.paths.add_path_operation(
+                &path,
+                vec![HttpMethod::Get],
+                setting_operation(attribute, "get", "settings.get")
+                    .description(Some(format!("Returns the `{}` setting.", attribute)))
+                    .response("200", json_response("The setting.", None))
+                    .build(),
+            );
+            openapi.paths.add_path_operation(
+                &path,
+                vec![HttpMethod::Post],
+                setting_operation(attribute, "update", "settings.update")
+                    .description(Some(format!("Updates the `{}` setting.", attribute)))
+                    .request_body(Some(
+                        RequestBodyBuilder::new()
+                            .description(Some("The new value, `null` resets the setting."))
+                            .content(
+                                "application/json",
+                                ContentBuilder::new()
+                                    .schema(Some(ObjectBuilder::new().build()))
+                                    .build(),
+                            )
+                            .build(),
+                    ))
+                    .response("202", enqueued_task_response())
+                    .build(),
+            );
+            openapi.paths.add_path_operation(
+                &path,
+                vec![HttpMethod::Delete],
+                setting_operation(attribute, "reset", "settings.update")
+                    .description(Some(format!("Resets the `{}` setting.", attribute)))
+                    .response("202", enqueued_task_response())
+                    .build(),
+            );
+        }
+    }
+}
+
+fn setting_operation(attribute: &str, verb: &str, action: &str) -> OperationBuilder {
+    OperationBuilder::new()
+        .tag("Settings")
+        .operation_id(Some(format!("{}_{}", verb, attribute)))
+        .parameter(
+            ParameterBuilder::new()
+                .name("index_uid")
+                .parameter_in(ParameterIn::Path)
+                .required(Required::True)
+                .description(Some("The uid of the index."))
+                .schema(Some(ObjectBuilder::new().schema_type(Type::String).build())),
+        )
+        .response(
+            "404",
+            json_response("The index doesn't exist.", Some("ResponseError")),
+        )
+        .security(SecurityRequirement::new("apiKey", [action]))
+}
+
+fn enqueued_task_response() -> utoipa::openapi::Response {
+    json_response("The task is enqueued.", Some("SummarizedTaskView"))
+}
+
+fn json_response(description: &str, schema: Option<&str>) -> utoipa::openapi::Response {
+    let content = match schema {
+        Some(schema) => ContentBuilder::new().schema(Some(Ref::from_schema_name(schema))),
+        None => ContentBuilder::new().schema(Some(ObjectBuilder::new().build())),
+    };
+
+    ResponseBuilder::new()
+        .description(description)
+        .content("application/json", content.build())
+        .build()
+}
+
+/// Every authenticated route answers with the same errors when the key is missing or invalid,
+/// they are added to the operations instead of being repeated in all the annotations.
+struct AuthenticationErrors;
+
+impl Modify for AuthenticationErrors {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for item in openapi.paths.paths.values_mut() {
+            let operations = [
+                &mut item.get,
+                &mut item.post,
+                &mut item.put,
+                &mut item.patch,
+                &mut item.delete,
+            ];
+            for operation in operations.into_iter().flatten() {
+                add_authentication_errors(operation);
+            }
+        }
+    }
+}
+
+fn add_authentication_errors(operation: &mut Operation) {
+    if operation.security.is_none() {
+        return;
+    }
+
+    let errors = [
+        ("401", "The `Authorization` header is missing."),
+        ("403", "The key is invalid or not authorized on this route."),
+    ];
+    for (status, description) in errors {
+        operation
+            .responses
+            .responses
+            .entry(status.to_string())
+            .or_insert_with(|| json_response(description, Some("ResponseError")).into());
+    }
+}
@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER};
+use actix_web::http::Method;
+use actix_web::HttpResponse;
+use futures::future::{ready, Ready};
+use meilisearch_error::ResponseError;
+use parking_lot::Mutex;
+
+use crate::error::MeilisearchHttpError;
+use crate::Opt;
+
+/// The windows are purged of the expired ones once there are this many.
+const PURGE_THRESHOLD: usize = 10_000;
+
+/// The budgets an IP address spends its requests from. All the requests are counted in the
+/// global budget, and some of them in the budget of their route too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Budget {
+    Global,
+    Search,
+    Documents,
+    Keys,
+}
+
+impl Budget {
+    /// Returns the budget of the route, besides the global one.
+    fn of_route(method: &Method, path: &str) -> Option<Self> {
+        if path == "/keys" || path.starts_with("/keys/") {
+            Some(Budget::Keys)
+        } else if path.ends_with("/search") || path == "/graphql" {
+            Some(Budget::Search)
+        } else if *method != Method::GET
+            && path.starts_with("/indexes/")
+            && path.split('/').nth(3) == Some("documents")
+        {
+            Some(Budget::Documents)
+        } else {
+            None
+        }
+    }
+}
+
+struct Window {
+    started_at: Instant,
+    requests: u32,
+}
+
+/// The requests counted in the current window of each IP address and budget. It is shared by
+/// all the workers of the HTTP server.
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+    windows: Arc<Mutex<HashMap<(IpAddr, Budget), Window>>>,
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimiter").finish_non_exhaustive()
+    }
+}
+
+/// What remains of a budget after a request.
+#[derive(Debug, PartialEq)]
+struct Usage {
+    limit: u32,
+    remaining: u32,
+    reset: Duration,
+}
+
+impl Usage {
+    fn insert_headers(&self, headers: &mut HeaderMap) {
+        let reset = reset_seconds(self.reset);
+        for (name, value) in [
+            ("ratelimit-limit", self.limit as u64),
+            ("ratelimit-remaining", self.remaining as u64),
+            ("ratelimit-reset", reset),
+        ] {
+            headers.insert(HeaderName::from_static(name), HeaderValue::from(value));
+        }
+    }
+}
+
+/// Parses the IP of an address given by the forwarded headers, or by the peer when there are none,
+/// that may have a port, e.g. `203.0.113.7:4321` or `[2001:db8::1]:4321`.
+fn parse_ip(addr: &str) -> Option<IpAddr> {
+    let addr = addr.trim();
+    if let Ok(ip) = addr.parse() {
+        return Some(ip);
+    }
+    if let Ok(socket) = addr.parse::<SocketAddr>() {
+        return Some(socket.ip());
+    }
+    addr.strip_prefix('[')
+        .and_then(|addr| addr.strip_suffix(']'))
+        .and_then(|addr| addr.parse().ok())
+}
+
+/// The seconds until the window is reset, rounded up so that a client waiting for them is never
+/// refused again.
+fn reset_seconds(reset: Duration) -> u64 {
+    reset.as_secs() + (reset.subsec_nanos() > 0) as u64
+}
+
+impl RateLimiter {
+    /// Counts a request in all the given budgets with their limit, only if none of them is
+    /// exhausted, a refused request not being counted at all.
+    fn hit(
+        &self,
+        budgets: &[((IpAddr, Budget), u32)],
+        window: Duration,
+        now: Instant,
+    ) -> Result<Vec<Usage>, Usage> {
+        let mut windows = self.windows.lock();
+        if windows.len() >= PURGE_THRESHOLD {
+            windows.retain(|_, w| now.duration_since(w.started_at) < window);
+        }
+
+        for &(key, limit) in budgets {
+            let current = windows.entry(key).or_insert(Window {
+                started_at: now,
+                requests: 0,
+            });
+            if now.duration_since(current.started_at) >= window {
+                current.started_at = now;
+                current.requests = 0;
+            }
+            if current.requests >= limit {
+                return Err(Usage {
+                    limit,
+                    remaining: 0,
+                    reset: window.saturating_sub(now.duration_since(current.started_at)),
+                });
+            }
+        }
+
+        let usages = budgets
+            .iter()
+            .filter_map(|&(key, limit)| {
+                let current = windows.get_mut(&key)?;
+                current.requests += 1;
+                Some(Usage {
+                    limit,
+                    remaining: limit - current.requests,
+                    reset: window.saturating_sub(now.duration_since(current.started_at)),
+                })
+            })
+            .collect();
+
+        Ok(usages)
+    }
+}
+
+/// Limits the number of requests each IP address can send per window, with a global budget and
+/// a budget for the search, the document writes and the key management routes.
+///
+/// The requests exceeding a budget are refused with a `429 Too Many Requests` and a
+/// `Retry-After` header, the others carry the `RateLimit-*` headers of their tightest budget.
+#[derive(Clone)]
+pub struct RateLimit {
+    global: Option<u32>,
+    search: Option<u32>,
+    documents: Option<u32>,
+    keys: Option<u32>,
+    window: Duration,
+    trust_proxy: bool,
+    limiter: RateLimiter,
+}
+
+impl RateLimit {
+    pub fn new(opt: &Opt) -> Self {
+        Self {
+            global: opt.rate_limit_global,
+            search: opt.rate_limit_search,
+            documents: opt.rate_limit_documents,
+            keys: opt.rate_limit_keys,
+            window: Duration::from_secs(opt.rate_limit_window_sec),
+            trust_proxy: opt.rate_limit_trust_proxy,
+            limiter: opt.rate_limiter.clone(),
+        }
+    }
+
+    fn limit(&self, budget: Budget) -> Option<u32> {
+        match budget {
+            Budget::Global => self.global,
+            Budget::Search => self.search,
+            Budget::Documents => self.documents,
+            Budget::Keys => self.keys,
+        }
+    }
+
+    /// Counts the request in its budgets, returns the tightest one or the exhausted one.
+    fn check(&self, ip: IpAddr, method: &Method, path: &str) -> Result<Option<Usage>, Usage> {
+        let budgets: Vec<_> = Some(Budget::Global)
+            .into_iter()
+            .chain(Budget::of_route(method, path))
+            .filter_map(|budget| Some(((ip, budget), self.limit(budget)?)))
+            .collect();
+
+        let usages = self.limiter.hit(&budgets, self.window, Instant::now())?;
+        Ok(usages.into_iter().min_by_key(|usage| usage.remaining))
+    }
+
+    fn is_enabled(&self) -> bool {
+        [self.global, self.search, self.documents, self.keys]
+            .iter()
+            .any(Option::is_some)
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service,
+            limits: self.clone(),
+        }))
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: S,
+    limits: RateLimit,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let forwarded_ip = if self.limits.trust_proxy {
+            req.connection_info()
+                .realip_remote_addr()
+                .and_then(parse_ip)
+        } else {
+            None
+        };
+        // A request the forwarded headers give no valid address for is limited by its peer.
+        let ip = forwarded_ip.or_else(|| req.peer_addr().map(|addr| addr.ip()));
+
+        let usage = match ip {
+            Some(ip) if self.limits.is_enabled() => self.limits.check(ip, req.method(), req.path()),
+            _ => Ok(None),
+        };
+
+        match usage {
+            Ok(usage) => {
+                let response = self.service.call(req);
+                Box::pin(async move {
+                    let mut response = response.await?;
+                    if let Some(usage) = usage {
+                        usage.insert_headers(response.headers_mut());
+                    }
+                    Ok(response.map_into_left_body())
+                })
+            }
+            Err(usage) => {
+                let retry_after = reset_seconds(usage.reset);
+                let error = ResponseError::from(MeilisearchHttpError::TooManyRequests(retry_after));
+                let mut response = HttpResponse::from_error(error);
+                usage.insert_headers(response.headers_mut());
+                response
+                    .headers_mut()
+                    .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+                Box::pin(ready(Ok(req.into_response(response).map_into_right_body())))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn routes_are_counted_in_their_budget() {
+        let budget = |method, path| Budget::of_route(&method, path);
+
+        assert_eq!(budget(Method::POST, "/keys"), Some(Budget::Keys));
+        assert_eq!(budget(Method::GET, "/keys/abcd"), Some(Budget::Keys));
+        assert_eq!(
+            budget(Method::GET, "/indexes/movies/search"),
+            Some(Budget::Search)
+        );
+        assert_eq!(
+            budget(Method::POST, "/indexes/movies/documents"),
+            Some(Budget::Documents)
+        );
+        assert_eq!(
+            budget(Method::POST, "/indexes/documents/documents/delete-batch"),
+            Some(Budget::Documents)
+        );
+        assert_eq!(budget(Method::GET, "/indexes/movies/documents"), None);
+        assert_eq!(budget(Method::POST, "/indexes/documents"), None);
+    }
+
+    #[test]
+    fn ips_are_parsed_without_their_port() {
+        let ip = |addr: &str| addr.parse::<IpAddr>().ok();
+
+        assert_eq!(parse_ip("203.0.113.7"), ip("203.0.113.7"));
+        assert_eq!(parse_ip("203.0.113.7:4321"), ip("203.0.113.7"));
+        assert_eq!(parse_ip("2001:db8::1"), ip("2001:db8::1"));
+        assert_eq!(parse_ip("[2001:db8::1]"), ip("2001:db8::1"));
+        assert_eq!(parse_ip("[2001:db8::1]:4321"), ip("2001:db8::1"));
+        assert_eq!(parse_ip("unknown"), None);
+    }
+
+    #[test]
+    fn budgets_are_reset_after_the_window() {
+        let limiter = RateLimiter::default();
+        let key = ("127.0.0.1".parse().unwrap(), Budget::Global);
+        let window = Duration::from_secs(60);
+        let now = Instant::now();
+
+        let usage = limiter.hit(&[(key, 2)], window, now).unwrap();
+        assert_eq!(usage[0].remaining, 1);
+        assert_eq!(usage[0].reset, window);
+        limiter.hit(&[(key, 2)], window, now).unwrap();
+
+        let later = now + Duration::from_secs(20);
+        let exhausted = limiter.hit(&[(key, 2)], window, later).unwrap_err();
+        assert_eq!(exhausted.reset, Duration::from_secs(40));
+
+        // the other addresses have their own budget.
+        let other = ("127.0.0.2".parse().unwrap(), Budget::Global);
+        assert!(limiter.hit(&[(other, 2)], window, later).is_ok());
+
+        let usage = limiter.hit(&[(key, 2)], window, now + window).unwrap();
+        assert_eq!(usage[0].remaining, 1);
+    }
+
+    #[test]
+    fn refused_requests_are_not_counted() {
+        let limiter = RateLimiter::default();
+        let ip = "127.0.0.1".parse().unwrap();
+        let (global, search) = ((ip, Budget::Global), (ip, Budget::Search));
+        let window = Duration::from_secs(60);
+        let now = Instant::now();
+
+        limiter
+            .hit(&[(global, 3), (search, 1)], window, now)
+            .unwrap();
+        let exhausted = limiter
+            .hit(&[(global, 3), (search, 1)], window, now)
+            .unwrap_err();
+        assert_eq!(exhausted.limit, 1);
+
+        // the refused search didn't spend a global request.
+        let usage = limiter.hit(&[(global, 3)], window, now).unwrap();
+        assert_eq!(usage[0].remaining, 1);
+    }
+}
@@ -0,0 +1,197 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::Method;
+use actix_web::{web, HttpResponse};
+use futures::future::{ready, Ready};
+use log::debug;
+use meilisearch_auth::AuthController;
+use meilisearch_error::ResponseError;
+use meilisearch_lib::index_controller::replication::error::ReplicationError;
+use meilisearch_lib::index_controller::replication::REPLICATION_BATCH_SIZE;
+use meilisearch_lib::tasks::task::TaskId;
+use meilisearch_lib::MeiliSearch;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/tasks").route(web::get().to(SeqHandler(get_replication_tasks))))
+        .service(
+            web::resource("/tasks/{task_uid}/content")
+                .route(web::get().to(SeqHandler(get_replication_task_content))),
+        )
+        .service(web::resource("/keys").route(web::get().to(SeqHandler(get_replication_keys))));
+}
+
+#[derive(Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct ReplicationTasksQuery {
+    /// The uid of the first returned task.
+    #[serde(default)]
+    from: TaskId,
+    /// The maximum number of returned tasks.
+    limit: Option<usize>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/replication/tasks",
+    tag = "Replication",
+    params(ReplicationTasksQuery),
+    responses((status = 200, description = "The tasks registered since `from`, the oldest first.", body = Object)),
+    security(("apiKey" = []))
+)]
+pub async fn get_replication_tasks(
+    meilisearch: GuardedData<MasterPolicy, MeiliSearch>,
+    params: web::Query<ReplicationTasksQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let ReplicationTasksQuery { from, limit } = params.into_inner();
+    let limit = limit.unwrap_or(REPLICATION_BATCH_SIZE);
+    let tasks = meilisearch.replication_tasks(from, limit).await?;
+
+    debug!("returns: {} tasks", tasks.len());
+    Ok(HttpResponse::Ok().json(tasks))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+pub struct TaskUidParam {
+    task_uid: TaskId,
+}
+
+#[utoipa::path(
+    get,
+    path = "/replication/tasks/{task_uid}/content",
+    tag = "Replication",
+    params(TaskUidParam),
+    responses(
+        (status = 200, description = "The documents of the document addition.", body = String, content_type = "application/x-ndjson"),
+        (status = 410, description = "The documents of the task were deleted.", body = ResponseError),
+    ),
+    security(("apiKey" = []))
+)]
+pub async fn get_replication_task_content(
+    meilisearch: GuardedData<MasterPolicy, MeiliSearch>,
+    path: web::Path<TaskUidParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let content = meilisearch.replication_content(path.task_uid).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .body(content))
+}
+
+#[utoipa::path(
+    get,
+    path = "/replication/keys",
+    tag = "Replication",
+    responses((status = 200, description = "All the keys, as stored by the primary.", body = Object)),
+    security(("apiKey" = []))
+)]
+pub async fn get_replication_keys(
+    auth_controller: GuardedData<MasterPolicy, AuthController>,
+) -> Result<HttpResponse, ResponseError> {
+    let keys = auth_controller.list_keys()?;
+
+    Ok(HttpResponse::Ok().json(keys))
+}
+
+/// Returns whether the request only reads data, and can thus be served by a replica.
+fn is_read_only(method: &Method, path: &str) -> bool {
+    match *method {
+        Method::GET | Method::HEAD | Method::OPTIONS => true,
+        Method::POST => {
+            let segments: Vec<_> = path.split('/').collect();
+            path.ends_with("/search")
+                || path.ends_with("/read-sessions")
+                || path == "/graphql"
+                || path == "/dashboard/login"
+                || path == "/dashboard/logout"
+                || matches!(segments[..], ["", "keys", _, "can"])
+                || matches!(segments[..], ["", "indexes", _, "export"])
+        }
+        Method::DELETE => path.contains("/read-sessions/"),
+        _ => false,
+    }
+}
+
+/// Refuses the requests writing data when the instance is a read-only replica, the writes must
+/// be sent to its primary.
+pub struct ReadOnlyReplica;
+
+impl<S, B> Transform<S, ServiceRequest> for ReadOnlyReplica
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = ReadOnlyReplicaMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ReadOnlyReplicaMiddleware { service }))
+    }
+}
+
+pub struct ReadOnlyReplicaMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for ReadOnlyReplicaMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let is_replica = req
+            .app_data::<MeiliSearch>()
+            .map_or(false, MeiliSearch::is_replica);
+        if is_replica && !is_read_only(req.method(), req.path()) {
+            let error = ResponseError::from(ReplicationError::ReadOnlyReplica);
+            return Box::pin(ready(Err(error.into())));
+        }
+
+        Box::pin(self.service.call(req))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn replicas_only_serve_reads() {
+        assert!(is_read_only(&Method::GET, "/indexes/movies/documents"));
+        assert!(is_read_only(&Method::POST, "/indexes/movies/search"));
+        assert!(is_read_only(&Method::POST, "/graphql"));
+        assert!(is_read_only(&Method::POST, "/dashboard/login"));
+        assert!(is_read_only(&Method::POST, "/indexes/movies/read-sessions"));
+        assert!(is_read_only(
+            &Method::DELETE,
+            "/indexes/movies/read-sessions/abcd"
+        ));
+        assert!(is_read_only(&Method::POST, "/keys/abcd/can"));
+        assert!(is_read_only(&Method::POST, "/indexes/movies/export"));
+        assert!(!is_read_only(&Method::POST, "/indexes/movies/documents"));
+        assert!(!is_read_only(&Method::POST, "/dashboard/logins"));
+        assert!(!is_read_only(&Method::POST, "/keys/abcd/usage-webhook"));
+        assert!(!is_read_only(&Method::DELETE, "/indexes/movies"));
+        assert!(!is_read_only(&Method::POST, "/keys"));
+        assert!(!is_read_only(&Method::PATCH, "/keys/abcd"));
+    }
+}
@@ -1,21 +1,66 @@
+use std::convert::Infallible;
+use std::time::Duration;
+
+use actix_web::http::header::CACHE_CONTROL;
+use actix_web::web::Bytes;
 use actix_web::{web, HttpRequest, HttpResponse};
+use async_stream::stream;
+use futures::{Stream, StreamExt};
+use meilisearch_auth::AuthFilter;
 use meilisearch_error::ResponseError;
 use meilisearch_lib::tasks::task::TaskId;
+use meilisearch_lib::tasks::watch::TaskProgress;
 use meilisearch_lib::tasks::TaskFilter;
 use meilisearch_lib::MeiliSearch;
+use serde::de::{value, DeserializeOwned, Error as _, IntoDeserializer};
+use serde::{Deserialize, Deserializer};
 use serde_json::json;
+use utoipa::IntoParams;
 
 use crate::analytics::Analytics;
 use crate::extractors::authentication::{policies::*, GuardedData};
 use crate::extractors::sequential_extractor::SeqHandler;
-use crate::task::{TaskListView, TaskView};
+use crate::task::{TaskListView, TaskProgressView, TaskStatus, TaskType, TaskView};
+
+/// How often a comment is sent on an idle watch stream, so the proxies don't close it.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(web::resource("").route(web::get().to(SeqHandler(get_tasks))))
-        .service(web::resource("/{task_id}").route(web::get().to(SeqHandler(get_task))));
+        .service(web::resource("/watch").route(web::get().to(SeqHandler(watch_tasks))))
+        .service(web::resource("/{task_id}").route(web::get().to(SeqHandler(get_task))))
+        .service(web::resource("/{task_id}/watch").route(web::get().to(SeqHandler(watch_task))));
+}
+
+/// Restricts the tasks to the indexes the key is authorized on, in its namespace.
+pub(crate) fn authorized_indexes_filter(auth_filter: &AuthFilter) -> Option<TaskFilter> {
+    let search_rules = &auth_filter.search_rules;
+    let mut filters = match search_rules.is_index_authorized("*") {
+        true if auth_filter.namespace.is_none() => return None,
+        true => TaskFilter::default(),
+        false => {
+            let mut filters = TaskFilter::default();
+            for (index, _policy) in search_rules.clone() {
+                filters.filter_index(index);
+            }
+            filters
+        }
+    };
+
+    if let Some(namespace) = auth_filter.namespace.clone() {
+        filters.filter_fn(move |task| task.index_uid.as_str().starts_with(&namespace));
+    }
+    Some(filters)
 }
 
-async fn get_tasks(
+#[utoipa::path(
+    get,
+    path = "/tasks",
+    tag = "Tasks",
+    responses((status = 200, description = "All the tasks.", body = TaskListView)),
+    security(("apiKey" = ["tasks.get"]))
+)]
+pub async fn get_tasks(
     meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
     req: HttpRequest,
     analytics: web::Data<dyn Analytics>,
@@ -26,16 +71,7 @@ async fn get_tasks(
         Some(&req),
     );
 
-    let search_rules = &meilisearch.filters().search_rules;
-    let filters = if search_rules.is_index_authorized("*") {
-        None
-    } else {
-        let mut filters = TaskFilter::default();
-        for (index, _policy) in search_rules.clone() {
-            filters.filter_index(index);
-        }
-        Some(filters)
-    };
+    let filters = authorized_indexes_filter(meilisearch.filters());
 
     let tasks: TaskListView = meilisearch
         .list_tasks(filters, None, None)
@@ -48,7 +84,18 @@ async fn get_tasks(
     Ok(HttpResponse::Ok().json(tasks))
 }
 
-async fn get_task(
+#[utoipa::path(
+    get,
+    path = "/tasks/{task_id}",
+    tag = "Tasks",
+    params(("task_id" = u32, Path, description = "The uid of the task.")),
+    responses(
+        (status = 200, description = "The task.", body = TaskView),
+        (status = 404, description = "The task doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["tasks.get"]))
+)]
+pub async fn get_task(
     meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
     task_id: web::Path<TaskId>,
     req: HttpRequest,
@@ -60,16 +107,7 @@ async fn get_task(
         Some(&req),
     );
 
-    let search_rules = &meilisearch.filters().search_rules;
-    let filters = if search_rules.is_index_authorized("*") {
-        None
-    } else {
-        let mut filters = TaskFilter::default();
-        for (index, _policy) in search_rules.clone() {
-            filters.filter_index(index);
-        }
-        Some(filters)
-    };
+    let filters = authorized_indexes_filter(meilisearch.filters());
 
     let task: TaskView = meilisearch
         .get_task(task_id.into_inner(), filters)
@@ -78,3 +116,162 @@ async fn get_task(
 
     Ok(HttpResponse::Ok().json(task))
 }
+
+/// Deserializes a comma separated list, such as `enqueued,processing`.
+fn deserialize_list<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|list| {
+            list.split(',')
+                .map(|item| {
+                    T::deserialize(IntoDeserializer::<value::Error>::into_deserializer(
+                        item.trim(),
+                    ))
+                    .map_err(D::Error::custom)
+                })
+                .collect()
+        })
+        .transpose()
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct WatchTasksQuery {
+    /// A comma separated list of index uids.
+    #[serde(default, deserialize_with = "deserialize_list")]
+    #[param(value_type = Option<String>)]
+    index_uid: Option<Vec<String>>,
+    /// A comma separated list of statuses, such as `enqueued,processing`.
+    #[serde(default, deserialize_with = "deserialize_list")]
+    #[param(value_type = Option<String>)]
+    status: Option<Vec<TaskStatus>>,
+    /// A comma separated list of task types.
+    #[serde(default, rename = "type", deserialize_with = "deserialize_list")]
+    #[param(value_type = Option<String>)]
+    task_type: Option<Vec<TaskType>>,
+}
+
+impl WatchTasksQuery {
+    fn matches(&self, task: &TaskView) -> bool {
+        self.index_uid
+            .as_ref()
+            .map_or(true, |uids| uids.iter().any(|uid| uid == task.index_uid()))
+            && self
+                .status
+                .as_ref()
+                .map_or(true, |statuses| statuses.contains(&task.status()))
+            && self
+                .task_type
+                .as_ref()
+                .map_or(true, |types| types.contains(&task.task_type()))
+    }
+}
+
+/// Streams the changes of the tasks as server-sent events, each event containing a task and its
+/// progress.
+fn task_events(
+    changes: impl Stream<Item = TaskProgress> + 'static,
+    query: Option<WatchTasksQuery>,
+) -> HttpResponse {
+    let body = stream! {
+        let mut changes = Box::pin(changes);
+        let mut keep_alive = tokio::time::interval(KEEP_ALIVE_INTERVAL);
+        // The first tick completes immediately.
+        keep_alive.tick().await;
+
+        loop {
+            let event = tokio::select! {
+                change = changes.next() => match change {
+                    Some(change) => {
+                        let view = TaskProgressView::from(change);
+                        if !query.as_ref().map_or(true, |query| query.matches(view.task())) {
+                            continue;
+                        }
+                        match serde_json::to_string(&view) {
+                            Ok(data) => format!("data: {}\n\n", data),
+                            Err(e) => {
+                                log::error!("error while serializing a task change: {}", e);
+                                continue;
+                            }
+                        }
+                    }
+                    None => break,
+                },
+                _ = keep_alive.tick() => String::from(": keep-alive\n\n"),
+            };
+
+            yield Ok::<_, Infallible>(Bytes::from(event));
+        }
+    };
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((CACHE_CONTROL, "no-cache"))
+        .streaming(body)
+}
+
+#[utoipa::path(
+    get,
+    path = "/tasks/watch",
+    tag = "Tasks",
+    params(WatchTasksQuery),
+    responses(
+        (status = 200, description = "A server-sent event stream of the task changes.",
+            body = TaskProgressView, content_type = "text/event-stream"),
+        (status = 400, description = "The filters are invalid.", body = ResponseError),
+    ),
+    security(("apiKey" = ["tasks.get"]))
+)]
+pub async fn watch_tasks(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
+    params: web::Query<WatchTasksQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish(
+        "Tasks Watched".to_string(),
+        json!({ "per_task_uid": false }),
+        Some(&req),
+    );
+
+    let filters = authorized_indexes_filter(meilisearch.filters());
+    let changes = meilisearch.watch_tasks(filters);
+
+    Ok(task_events(changes, Some(params.into_inner())))
+}
+
+#[utoipa::path(
+    get,
+    path = "/tasks/{task_id}/watch",
+    tag = "Tasks",
+    params(("task_id" = u32, Path, description = "The uid of the task.")),
+    responses(
+        (status = 200, description = "A server-sent event stream of the task changes, closed once it is finished.",
+            body = TaskProgressView, content_type = "text/event-stream"),
+        (status = 404, description = "The task doesn't exist.", body = ResponseError),
+    ),
+    security(("apiKey" = ["tasks.get"]))
+)]
+pub async fn watch_task(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
+    task_id: web::Path<TaskId>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish(
+        "Tasks Watched".to_string(),
+        json!({ "per_task_uid": true }),
+        Some(&req),
+    );
+
+    let filters = authorized_indexes_filter(meilisearch.filters());
+    let changes = meilisearch
+        .watch_task(task_id.into_inner(), filters)
+        .await?;
+
+    Ok(task_events(changes, None))
+}
@@ -0,0 +1,100 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::AUTHORIZATION;
+use futures::future::{ready, Ready};
+use meilisearch_auth::AuthController;
+
+/// Counts the requests of each API key, and the ones answered with an error, for the usage
+/// digests sent to the webhooks of the keys. The requests refused by the rate limit are counted
+/// as errors.
+pub struct UsageRecorder;
+
+impl<S, B> Transform<S, ServiceRequest> for UsageRecorder
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = UsageRecorderMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(UsageRecorderMiddleware { service }))
+    }
+}
+
+pub struct UsageRecorderMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for UsageRecorderMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let usage = match (req.app_data::<AuthController>(), bearer_token(&req)) {
+            (Some(auth), Some(key)) => Some((auth.clone(), key, index_uid(req.path()))),
+            _ => None,
+        };
+
+        let response = self.service.call(req);
+        Box::pin(async move {
+            let response = response.await;
+            if let Some((auth, key, index)) = usage {
+                let is_error = response
+                    .as_ref()
+                    .map_or(true, |response| !response.status().is_success());
+                auth.record_usage(&key, index.as_deref(), is_error);
+            }
+            response
+        })
+    }
+}
+
+fn bearer_token(req: &ServiceRequest) -> Option<String> {
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")?;
+    Some(token.to_string())
+}
+
+/// The uid of the index of an index route, as sent by the key.
+fn index_uid(path: &str) -> Option<String> {
+    let mut segments = path.trim_start_matches('/').split('/');
+    match (segments.next(), segments.next()) {
+        (Some("indexes"), Some(uid)) if !uid.is_empty() => Some(uid.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn usage_is_counted_per_index() {
+        assert_eq!(
+            index_uid("/indexes/movies/search").as_deref(),
+            Some("movies")
+        );
+        assert_eq!(index_uid("/indexes/movies").as_deref(), Some("movies"));
+        assert_eq!(index_uid("/indexes"), None);
+        assert_eq!(index_uid("/tasks"), None);
+    }
+}
@@ -0,0 +1,133 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderValue, RETRY_AFTER};
+use actix_web::http::Method;
+use actix_web::HttpResponse;
+use futures::future::{ready, Ready};
+use meilisearch_error::ResponseError;
+use meilisearch_lib::index_controller::write_breaker::WRITE_BREAKER_CHECK_INTERVAL;
+use meilisearch_lib::MeiliSearch;
+
+/// What a request does to the documents of an index.
+#[derive(Debug, PartialEq, Eq)]
+enum DocumentWrite {
+    Addition,
+    Deletion,
+}
+
+impl DocumentWrite {
+    fn of_route(method: &Method, path: &str) -> Option<Self> {
+        let mut segments = path.trim_start_matches('/').split('/');
+        if segments.next() != Some("indexes") || segments.nth(1) != Some("documents") {
+            return None;
+        }
+
+        match *method {
+            Method::GET | Method::HEAD | Method::OPTIONS => None,
+            Method::DELETE => Some(DocumentWrite::Deletion),
+            _ if segments.next() == Some("delete-batch") => Some(DocumentWrite::Deletion),
+            _ => Some(DocumentWrite::Addition),
+        }
+    }
+}
+
+/// Refuses the document writes while the write breaker is open, with a
+/// `503 Service Unavailable` and a `Retry-After` header. The other routes, and the searches in
+/// particular, are still served.
+pub struct WriteBreaker;
+
+impl<S, B> Transform<S, ServiceRequest> for WriteBreaker
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = WriteBreakerMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(WriteBreakerMiddleware { service }))
+    }
+}
+
+pub struct WriteBreakerMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for WriteBreakerMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let refused = match (
+            DocumentWrite::of_route(req.method(), req.path()),
+            req.app_data::<MeiliSearch>(),
+        ) {
+            (Some(write), Some(meilisearch)) => meilisearch
+                .check_document_write(write == DocumentWrite::Deletion)
+                .err(),
+            _ => None,
+        };
+
+        match refused {
+            None => {
+                let response = self.service.call(req);
+                Box::pin(async move { Ok(response.await?.map_into_left_body()) })
+            }
+            Some(error) => {
+                // The breaker is closed at the earliest at its next check.
+                let retry_after = WRITE_BREAKER_CHECK_INTERVAL.as_secs();
+                let mut response = HttpResponse::from_error(ResponseError::from(error));
+                response
+                    .headers_mut()
+                    .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+                Box::pin(ready(Ok(req.into_response(response).map_into_right_body())))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_the_document_writes_are_refused() {
+        let write = |method, path| DocumentWrite::of_route(&method, path);
+
+        assert_eq!(
+            write(Method::POST, "/indexes/movies/documents"),
+            Some(DocumentWrite::Addition)
+        );
+        assert_eq!(
+            write(Method::PUT, "/indexes/movies/documents"),
+            Some(DocumentWrite::Addition)
+        );
+        assert_eq!(
+            write(Method::DELETE, "/indexes/movies/documents/12"),
+            Some(DocumentWrite::Deletion)
+        );
+        assert_eq!(
+            write(Method::POST, "/indexes/movies/documents/delete-batch"),
+            Some(DocumentWrite::Deletion)
+        );
+        assert_eq!(write(Method::GET, "/indexes/movies/documents"), None);
+        assert_eq!(write(Method::POST, "/indexes/movies/search"), None);
+        assert_eq!(write(Method::POST, "/indexes/documents"), None);
+        assert_eq!(write(Method::POST, "/documents/movies/documents"), None);
+    }
+}
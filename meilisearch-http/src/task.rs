@@ -8,17 +8,23 @@ use meilisearch_lib::tasks::batch::BatchId;
 use meilisearch_lib::tasks::task::{
     DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskResult,
 };
-use serde::{Serialize, Serializer};
+use meilisearch_lib::tasks::watch::TaskProgress;
+use serde::{Deserialize, Serialize, Serializer};
 use time::{Duration, OffsetDateTime};
+use utoipa::ToSchema;
 
 use crate::AUTOBATCHING_ENABLED;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
-enum TaskType {
+pub enum TaskType {
     IndexCreation,
     IndexUpdate,
+    IndexRename,
+    IndexRebuild,
     IndexDeletion,
+    IndexRestore,
+    IndexPurge,
     DocumentAddition,
     DocumentPartial,
     DocumentDeletion,
@@ -43,14 +49,18 @@ impl From<TaskContent> for TaskType {
             TaskContent::IndexDeletion => TaskType::IndexDeletion,
             TaskContent::IndexCreation { .. } => TaskType::IndexCreation,
             TaskContent::IndexUpdate { .. } => TaskType::IndexUpdate,
+            TaskContent::IndexRename { .. } => TaskType::IndexRename,
+            TaskContent::IndexRebuild { .. } => TaskType::IndexRebuild,
+            TaskContent::IndexRestore => TaskType::IndexRestore,
+            TaskContent::IndexPurge { .. } => TaskType::IndexPurge,
             _ => unreachable!("unexpected task type"),
         }
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
-enum TaskStatus {
+pub enum TaskStatus {
     Enqueued,
     Processing,
     Succeeded,
@@ -65,6 +75,10 @@ enum TaskDetails {
     DocumentAddition {
         received_documents: usize,
         indexed_documents: Option<u64>,
+        /// The documents older than the stored ones, according to the `versionField` of the
+        /// index, only reported when some were skipped.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        skipped_documents: Option<u64>,
     },
     #[serde(rename_all = "camelCase")]
     Settings {
@@ -74,6 +88,15 @@ enum TaskDetails {
     #[serde(rename_all = "camelCase")]
     IndexInfo { primary_key: Option<String> },
     #[serde(rename_all = "camelCase")]
+    IndexRename { new_uid: String },
+    #[serde(rename_all = "camelCase")]
+    IndexRebuild {
+        reindex: bool,
+        reclaimed_bytes: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    IndexPurge { reclaimed_bytes: Option<u64> },
+    #[serde(rename_all = "camelCase")]
     DocumentDeletion {
         received_document_ids: usize,
         deleted_documents: Option<u64>,
@@ -133,19 +156,23 @@ fn serialize_duration<S: Serializer>(
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskView {
+    #[schema(value_type = u32)]
     uid: TaskId,
     index_uid: String,
     status: TaskStatus,
     #[serde(rename = "type")]
     task_type: TaskType,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
     details: Option<TaskDetails>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<ResponseError>,
+    /// An ISO 8601 duration, like `PT1.5S`.
     #[serde(serialize_with = "serialize_duration")]
+    #[schema(value_type = Option<String>)]
     duration: Option<Duration>,
     #[serde(serialize_with = "time::serde::rfc3339::serialize")]
     enqueued_at: OffsetDateTime,
@@ -154,6 +181,7 @@ pub struct TaskView {
     #[serde(serialize_with = "time::serde::rfc3339::option::serialize")]
     finished_at: Option<OffsetDateTime>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<u32>)]
     batch_uid: Option<Option<BatchId>>,
 }
 
@@ -175,6 +203,7 @@ impl From<Task> for TaskView {
                 let details = TaskDetails::DocumentAddition {
                     received_documents: documents_count,
                     indexed_documents: None,
+                    skipped_documents: None,
                 };
 
                 let task_type = match merge_strategy {
@@ -216,6 +245,26 @@ impl From<Task> for TaskView {
                 TaskType::IndexUpdate,
                 Some(TaskDetails::IndexInfo { primary_key }),
             ),
+            TaskContent::IndexRename { new_uid } => (
+                TaskType::IndexRename,
+                Some(TaskDetails::IndexRename {
+                    new_uid: new_uid.into_inner(),
+                }),
+            ),
+            TaskContent::IndexRebuild { reindex } => (
+                TaskType::IndexRebuild,
+                Some(TaskDetails::IndexRebuild {
+                    reindex,
+                    reclaimed_bytes: None,
+                }),
+            ),
+            TaskContent::IndexRestore => (TaskType::IndexRestore, None),
+            TaskContent::IndexPurge { .. } => (
+                TaskType::IndexPurge,
+                Some(TaskDetails::IndexPurge {
+                    reclaimed_bytes: None,
+                }),
+            ),
         };
 
         // An event always has at least one event: "Created"
@@ -228,14 +277,18 @@ impl From<Task> for TaskView {
                     (
                         TaskResult::DocumentAddition {
                             indexed_documents: num,
-                            ..
+                            skipped_documents: skipped,
                         },
                         Some(TaskDetails::DocumentAddition {
                             ref mut indexed_documents,
+                            ref mut skipped_documents,
                             ..
                         }),
                     ) => {
                         indexed_documents.replace(*num);
+                        if *skipped > 0 {
+                            skipped_documents.replace(*skipped);
+                        }
                     }
                     (
                         TaskResult::DocumentDeletion {
@@ -259,6 +312,27 @@ impl From<Task> for TaskView {
                     ) => {
                         deleted_documents.replace(*docs);
                     }
+                    (
+                        TaskResult::IndexRebuild {
+                            reclaimed_bytes: bytes,
+                        },
+                        Some(TaskDetails::IndexRebuild {
+                            ref mut reclaimed_bytes,
+                            ..
+                        }),
+                    ) => {
+                        reclaimed_bytes.replace(*bytes);
+                    }
+                    (
+                        TaskResult::IndexPurge {
+                            reclaimed_bytes: bytes,
+                        },
+                        Some(TaskDetails::IndexPurge {
+                            ref mut reclaimed_bytes,
+                        }),
+                    ) => {
+                        reclaimed_bytes.replace(*bytes);
+                    }
                     _ => (),
                 }
                 (TaskStatus::Succeeded, None, Some(*timestamp))
@@ -283,6 +357,12 @@ impl From<Task> for TaskView {
                     }) => {
                         indexed_documents.replace(0);
                     }
+                    Some(TaskDetails::IndexRebuild {
+                        ref mut reclaimed_bytes,
+                        ..
+                    }) => {
+                        reclaimed_bytes.replace(0);
+                    }
                     _ => (),
                 }
                 (TaskStatus::Failed, Some(error.clone()), Some(*timestamp))
@@ -327,7 +407,46 @@ impl From<Task> for TaskView {
     }
 }
 
-#[derive(Debug, Serialize)]
+impl TaskView {
+    pub fn index_uid(&self) -> &str {
+        &self.index_uid
+    }
+
+    pub fn status(&self) -> TaskStatus {
+        self.status
+    }
+
+    pub fn task_type(&self) -> TaskType {
+        self.task_type
+    }
+}
+
+/// The state of a task sent to the task watchers.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskProgressView {
+    #[serde(flatten)]
+    task: TaskView,
+    /// How much of the task is done, between 0 and 100.
+    progress: u8,
+}
+
+impl From<TaskProgress> for TaskProgressView {
+    fn from(other: TaskProgress) -> Self {
+        Self {
+            task: other.task.into(),
+            progress: other.percentage,
+        }
+    }
+}
+
+impl TaskProgressView {
+    pub fn task(&self) -> &TaskView {
+        &self.task
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TaskListView {
     results: Vec<TaskView>,
 }
@@ -338,9 +457,10 @@ impl From<Vec<TaskView>> for TaskListView {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SummarizedTaskView {
+    #[schema(value_type = u32)]
     uid: TaskId,
     index_uid: String,
     status: TaskStatus,
@@ -257,7 +257,12 @@ async fn error_add_api_key_missing_parameter() {
         "message": "`indexes` field is mandatory.",
         "code": "missing_parameter",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#missing_parameter"
+        "link": "https://docs.meilisearch.com/errors#missing_parameter",
+        "details": {
+            "field": "indexes",
+            "expected": "an array of index names",
+            "value": null
+        }
     });
 
     assert_eq!(response, expected_response);
@@ -275,7 +280,12 @@ async fn error_add_api_key_missing_parameter() {
         "message": "`actions` field is mandatory.",
         "code": "missing_parameter",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#missing_parameter"
+        "link": "https://docs.meilisearch.com/errors#missing_parameter",
+        "details": {
+            "field": "actions",
+            "expected": "an array of action names",
+            "value": null
+        }
     });
 
     assert_eq!(response, expected_response);
@@ -293,7 +303,12 @@ async fn error_add_api_key_missing_parameter() {
         "message": "`expiresAt` field is mandatory.",
         "code": "missing_parameter",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#missing_parameter"
+        "link": "https://docs.meilisearch.com/errors#missing_parameter",
+        "details": {
+            "field": "expiresAt",
+            "expected": "an RFC 3339 date or datetime in the future, or null",
+            "value": null
+        }
     });
 
     assert_eq!(response, expected_response);
@@ -319,7 +334,12 @@ async fn error_add_api_key_invalid_parameters_description() {
         "message": r#"`description` field value `{"name":"products"}` is invalid. It should be a string or specified as a null value."#,
         "code": "invalid_api_key_description",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_api_key_description"
+        "link": "https://docs.meilisearch.com/errors#invalid_api_key_description",
+        "details": {
+            "field": "description",
+            "expected": "a string, or null",
+            "value": {"name":"products"}
+        }
     });
 
     assert_eq!(response, expected_response);
@@ -345,7 +365,12 @@ async fn error_add_api_key_invalid_parameters_indexes() {
         "message": r#"`indexes` field value `{"name":"products"}` is invalid. It should be an array of string representing index names."#,
         "code": "invalid_api_key_indexes",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_api_key_indexes"
+        "link": "https://docs.meilisearch.com/errors#invalid_api_key_indexes",
+        "details": {
+            "field": "indexes",
+            "expected": "an array of index names",
+            "value": {"name":"products"}
+        }
     });
 
     assert_eq!(response, expected_response);
@@ -369,7 +394,12 @@ async fn error_add_api_key_invalid_parameters_actions() {
         "message": r#"`actions` field value `{"name":"products"}` is invalid. It should be an array of string representing action names."#,
         "code": "invalid_api_key_actions",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_api_key_actions"
+        "link": "https://docs.meilisearch.com/errors#invalid_api_key_actions",
+        "details": {
+            "field": "actions",
+            "expected": "an array of action names",
+            "value": {"name":"products"}
+        }
     });
 
     assert_eq!(response, expected_response);
@@ -389,7 +419,12 @@ async fn error_add_api_key_invalid_parameters_actions() {
         "message": r#"`actions` field value `["doc.add"]` is invalid. It should be an array of string representing action names."#,
         "code": "invalid_api_key_actions",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_api_key_actions"
+        "link": "https://docs.meilisearch.com/errors#invalid_api_key_actions",
+        "details": {
+            "field": "actions",
+            "expected": "an array of action names",
+            "value": ["doc.add"]
+        }
     });
 
     assert_eq!(response, expected_response);
@@ -415,7 +450,12 @@ async fn error_add_api_key_invalid_parameters_expires_at() {
         "message": r#"`expiresAt` field value `{"name":"products"}` is invalid. It should follow the RFC 3339 format to represents a date or datetime in the future or specified as a null value. e.g. 'YYYY-MM-DD' or 'YYYY-MM-DD HH:MM:SS'."#,
         "code": "invalid_api_key_expires_at",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_api_key_expires_at"
+        "link": "https://docs.meilisearch.com/errors#invalid_api_key_expires_at",
+        "details": {
+            "field": "expiresAt",
+            "expected": "an RFC 3339 date or datetime in the future, or null",
+            "value": {"name":"products"}
+        }
     });
 
     assert_eq!(response, expected_response);
@@ -441,7 +481,12 @@ async fn error_add_api_key_invalid_parameters_expires_at_in_the_past() {
         "message": r#"`expiresAt` field value `"2010-11-13T00:00:00Z"` is invalid. It should follow the RFC 3339 format to represents a date or datetime in the future or specified as a null value. e.g. 'YYYY-MM-DD' or 'YYYY-MM-DD HH:MM:SS'."#,
         "code": "invalid_api_key_expires_at",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_api_key_expires_at"
+        "link": "https://docs.meilisearch.com/errors#invalid_api_key_expires_at",
+        "details": {
+            "field": "expiresAt",
+            "expected": "an RFC 3339 date or datetime in the future, or null",
+            "value": "2010-11-13T00:00:00Z"
+        }
     });
 
     assert_eq!(response, expected_response);
@@ -1216,7 +1261,12 @@ async fn error_patch_api_key_indexes_invalid_parameters() {
         "message": "`description` field value `13` is invalid. It should be a string or specified as a null value.",
         "code": "invalid_api_key_description",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_api_key_description"
+        "link": "https://docs.meilisearch.com/errors#invalid_api_key_description",
+        "details": {
+            "field": "description",
+            "expected": "a string, or null",
+            "value": 13
+        }
     });
 
     assert_eq!(response, expected_response);
@@ -1233,7 +1283,12 @@ async fn error_patch_api_key_indexes_invalid_parameters() {
         "message": "`indexes` field value `13` is invalid. It should be an array of string representing index names.",
         "code": "invalid_api_key_indexes",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_api_key_indexes"
+        "link": "https://docs.meilisearch.com/errors#invalid_api_key_indexes",
+        "details": {
+            "field": "indexes",
+            "expected": "an array of index names",
+            "value": 13
+        }
     });
 
     assert_eq!(response, expected_response);
@@ -1249,7 +1304,12 @@ async fn error_patch_api_key_indexes_invalid_parameters() {
         "message": "`actions` field value `13` is invalid. It should be an array of string representing action names.",
         "code": "invalid_api_key_actions",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_api_key_actions"
+        "link": "https://docs.meilisearch.com/errors#invalid_api_key_actions",
+        "details": {
+            "field": "actions",
+            "expected": "an array of action names",
+            "value": 13
+        }
     });
 
     assert_eq!(response, expected_response);
@@ -1265,7 +1325,12 @@ async fn error_patch_api_key_indexes_invalid_parameters() {
         "message": "`expiresAt` field value `13` is invalid. It should follow the RFC 3339 format to represents a date or datetime in the future or specified as a null value. e.g. 'YYYY-MM-DD' or 'YYYY-MM-DD HH:MM:SS'.",
         "code": "invalid_api_key_expires_at",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_api_key_expires_at"
+        "link": "https://docs.meilisearch.com/errors#invalid_api_key_expires_at",
+        "details": {
+            "field": "expiresAt",
+            "expected": "an RFC 3339 date or datetime in the future, or null",
+            "value": 13
+        }
     });
 
     assert_eq!(response, expected_response);
@@ -1333,3 +1398,425 @@ async fn error_access_api_key_routes_no_master_key_set() {
     assert_eq!(response, expected_response);
     assert_eq!(code, expected_code);
 }
+
+#[actix_rt::test]
+async fn can_api_key() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["products", "sales"],
+        "actions": ["search", "documents.add"],
+        "expiresAt": "2050-11-13T00:00:00Z"
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 201, "{}", response);
+    let key = response["key"].as_str().unwrap();
+
+    let (response, code) = server
+        .can_api_key(key, json!({ "action": "documents.add", "index": "sales" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "authorized": true,
+            "matchingRule": { "action": "documents.add", "index": "sales" },
+            "expired": false,
+            "active": true,
+        })
+    );
+
+    let (response, code) = server
+        .can_api_key(key, json!({ "action": "documents.add", "index": "movies" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({ "authorized": false, "matchingRule": null, "expired": false, "active": true })
+    );
+
+    let (response, code) = server
+        .can_api_key(
+            key,
+            json!({ "action": "settings.update", "index": "sales" }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["authorized"], false);
+
+    // the wildcards are reported as the matching rule.
+    let content = json!({
+        "indexes": ["*"],
+        "actions": ["*"],
+        "expiresAt": null
+    });
+    let (response, _) = server.add_api_key(content).await;
+    let admin = response["key"].as_str().unwrap();
+
+    let (response, code) = server
+        .can_api_key(
+            admin,
+            json!({ "action": "settings.update", "index": "sales" }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["matchingRule"],
+        json!({ "action": "*", "index": "*" })
+    );
+
+    let (response, code) = server
+        .can_api_key("unknown", json!({ "action": "search" }))
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "api_key_not_found");
+}
+
+#[actix_rt::test]
+async fn get_api_key_permissions() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["search", "documents.get"],
+        "expiresAt": null
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 201, "{}", response);
+    let key = response["key"].as_str().unwrap();
+
+    let (response, code) = server.get_api_key_permissions(key).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "expired": false,
+            "active": true,
+            "results": [
+                { "action": "search", "indexes": ["products"] },
+                { "action": "documents.get", "indexes": ["products"] },
+            ]
+        })
+    );
+
+    // the `*` action is expanded in all the actions.
+    let content = json!({
+        "indexes": ["*"],
+        "actions": ["*"],
+        "expiresAt": null
+    });
+    let (response, _) = server.add_api_key(content).await;
+    let admin = response["key"].as_str().unwrap();
+
+    let (response, code) = server.get_api_key_permissions(admin).await;
+    assert_eq!(code, 200, "{}", response);
+    let results = response["results"].as_array().unwrap();
+    assert!(results.iter().all(|p| p["indexes"] == json!(["*"])));
+    assert!(results.iter().any(|p| p["action"] == "settings.update"));
+    assert!(results.iter().all(|p| p["action"] != "*"));
+}
+
+#[actix_rt::test]
+async fn dry_run_api_key_creation() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["search"],
+        "expiresAt": "2050-11-13T00:00:00Z"
+    });
+    let (response, code) = server.service.post("/keys?dryRun=true", content).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "valid": true, "errors": [] }));
+
+    // nothing was created.
+    let (response, _) = server.list_api_keys().await;
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+
+    let content = json!({
+        "description": 13,
+        "actions": ["doc.add"],
+        "expiresAt": "2010-11-13T00:00:00Z"
+    });
+    let (response, code) = server.service.post("/keys?dryRun=true", content).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["valid"], false);
+    let errors = response["errors"].as_array().unwrap();
+    let fields: Vec<_> = errors
+        .iter()
+        .map(|e| e["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["description", "actions", "indexes", "expiresAt"]);
+    assert_eq!(errors[1]["code"], "invalid_api_key_actions");
+    assert_eq!(errors[1]["details"]["value"], json!(["doc.add"]));
+    assert_eq!(errors[2]["code"], "missing_parameter");
+    assert_eq!(errors[3]["code"], "invalid_api_key_expires_at");
+}
+
+#[actix_rt::test]
+async fn dry_run_api_key_update() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["search"],
+        "expiresAt": null
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 201, "{}", response);
+    let key = response["key"].as_str().unwrap();
+
+    let url = format!("/keys/{}?dryRun=true", key);
+    let (response, code) = server
+        .service
+        .patch(&url, json!({ "indexes": ["sales"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "valid": true, "errors": [] }));
+
+    let (response, _) = server.get_api_key(key).await;
+    assert_eq!(response["indexes"], json!(["products"]));
+
+    let (response, code) = server
+        .service
+        .patch(&url, json!({ "indexes": "sales" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["valid"], false);
+    assert_eq!(response["errors"][0]["field"], "indexes");
+    assert_eq!(response["errors"][0]["code"], "invalid_api_key_indexes");
+
+    let (response, code) = server
+        .service
+        .patch("/keys/unknown?dryRun=true", json!({}))
+        .await;
+    assert_eq!(code, 404, "{}", response);
+}
+
+#[actix_rt::test]
+async fn search_with_retrievable_attributes() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+    let index = server.index("products");
+    let documents = json!([
+        { "id": 1, "title": "Mug", "email": "sales@example.com", "costPrice": 3 },
+        { "id": 2, "title": "Bowl", "email": "sales@example.com", "costPrice": 5 },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["search"],
+        "retrievableAttributes": ["id", "title"],
+        "expiresAt": null
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 201, "{}", response);
+    assert_eq!(response["retrievableAttributes"], json!(["id", "title"]));
+    let key = response["key"].as_str().unwrap().to_string();
+
+    server.use_api_key(&key);
+    let index = server.index("products");
+    index
+        .search(json!({ "q": "mug" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([{ "id": 1, "title": "Mug" }]));
+        })
+        .await;
+
+    index
+        .search(
+            json!({ "attributesToRetrieve": ["title", "email"] }),
+            |response, code| {
+                assert_eq!(code, 403, "{}", response);
+                assert_eq!(response["code"], "unauthorized_attributes_to_retrieve");
+            },
+        )
+        .await;
+
+    // the master key retrieves all the attributes.
+    server.use_api_key("MASTER_KEY");
+    let index = server.index("products");
+    index
+        .search(json!({ "q": "mug" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"][0]["costPrice"], 3);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn facets_distribution_with_retrievable_attributes() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+    let index = server.index("products");
+    let documents = json!([
+        { "id": 1, "title": "Mug", "email": "sales@example.com" },
+        { "id": 2, "title": "Bowl", "email": "sales@example.com" },
+    ]);
+    index.add_documents(documents, None).await;
+    index
+        .update_settings(json!({ "filterableAttributes": ["title", "email"] }))
+        .await;
+    index.wait_task(1).await;
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["search"],
+        "retrievableAttributes": ["id", "title"],
+        "expiresAt": null
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 201, "{}", response);
+    let key = response["key"].as_str().unwrap().to_string();
+
+    server.use_api_key(&key);
+    let index = server.index("products");
+    index
+        .search(
+            json!({ "facetsDistribution": ["email"] }),
+            |response, code| {
+                assert_eq!(code, 403, "{}", response);
+                assert_eq!(response["code"], "unauthorized_attributes_to_retrieve");
+            },
+        )
+        .await;
+
+    index
+        .search(json!({ "facetsDistribution": ["*"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            let dist = response["facetsDistribution"].as_object().unwrap();
+            assert_eq!(dist.keys().collect::<Vec<_>>(), vec!["title"]);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn error_add_api_key_invalid_parameters_retrievable_attributes() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["search"],
+        "retrievableAttributes": "title",
+        "expiresAt": null
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_api_key_retrievable_attributes");
+}
+
+#[actix_rt::test]
+async fn search_with_default_search_params() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+    let index = server.index("products");
+    index
+        .update_settings(json!({ "sortableAttributes": ["price"] }))
+        .await;
+    let documents = json!([
+        { "id": 1, "title": "Red mug", "price": 8 },
+        { "id": 2, "title": "Blue mug", "price": 5 },
+        { "id": 3, "title": "Green mug", "price": 12 },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["search"],
+        "defaultSearchParams": {
+            "limit": 2,
+            "sort": ["price:asc"],
+            "attributesToHighlight": ["title"],
+            "showRankingScore": true,
+        },
+        "expiresAt": null
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 201, "{}", response);
+    assert_eq!(response["defaultSearchParams"]["limit"], 2);
+    let key = response["key"].as_str().unwrap().to_string();
+
+    server.use_api_key(&key);
+    let index = server.index("products");
+    index
+        .search(json!({ "q": "mug" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["limit"], 2);
+            assert_eq!(response["hits"][0]["id"], 2);
+            assert_eq!(response["hits"][1]["id"], 1);
+            assert_eq!(
+                response["hits"][0]["_formatted"]["title"],
+                "Blue <em>mug</em>"
+            );
+            assert!(response["hits"][0]["_rankingScore"].is_number());
+        })
+        .await;
+
+    // the parameters sent with their default value are kept too.
+    index
+        .search(
+            json!({ "q": "mug", "limit": 20, "showRankingScore": false }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["limit"], 20);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 3);
+                assert!(response["hits"][0].get("_rankingScore").is_none());
+            },
+        )
+        .await;
+
+    // the parameters of the query take precedence over the defaults of the key.
+    index
+        .search(
+            json!({ "q": "mug", "limit": 1, "sort": ["price:desc"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+                assert_eq!(response["hits"][0]["id"], 3);
+            },
+        )
+        .await;
+
+    // the master key searches with the defaults of the engine.
+    server.use_api_key("MASTER_KEY");
+    let index = server.index("products");
+    index
+        .search(json!({ "q": "mug" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["limit"], 20);
+            assert!(response["hits"][0].get("_formatted").is_none());
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn error_add_api_key_invalid_parameters_default_search_params() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    for params in [
+        json!(["limit"]),
+        json!({ "q": "mug" }),
+        json!({ "limit": "ten" }),
+        json!({ "matchingStrategy": "first" }),
+    ] {
+        let content = json!({
+            "indexes": ["products"],
+            "actions": ["search"],
+            "defaultSearchParams": params,
+            "expiresAt": null
+        });
+        let (response, code) = server.add_api_key(content).await;
+        assert_eq!(code, 400, "{}", response);
+        assert_eq!(response["code"], "invalid_api_key_default_search_params");
+        assert_eq!(response["details"]["field"], "defaultSearchParams");
+    }
+}
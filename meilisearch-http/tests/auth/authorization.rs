@@ -11,42 +11,67 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
         hashmap! {
             ("POST",    "/indexes/products/search") =>                         hashset!{"search", "*"},
             ("GET",     "/indexes/products/search") =>                         hashset!{"search", "*"},
+            ("GET",     "/indexes/products/search/live") =>                    hashset!{"search", "*"},
+            ("GET",     "/indexes/products/suggest?q=a") =>                    hashset!{"suggest", "*"},
+            ("POST",    "/graphql") =>                                         hashset!{"search", "*"},
             ("POST",    "/indexes/products/documents") =>                      hashset!{"documents.add", "*"},
             ("GET",     "/indexes/products/documents") =>                      hashset!{"documents.get", "*"},
             ("GET",     "/indexes/products/documents/0") =>                    hashset!{"documents.get", "*"},
             ("DELETE",  "/indexes/products/documents/0") =>                    hashset!{"documents.delete", "*"},
+            ("POST",    "/indexes/products/export") =>                         hashset!{"documents.export", "*"},
             ("GET",     "/tasks") =>                                           hashset!{"tasks.get", "*"},
+            ("GET",     "/tasks/0/watch") =>                                   hashset!{"tasks.get", "*"},
             ("GET",     "/indexes/products/tasks") =>                          hashset!{"tasks.get", "*"},
             ("GET",     "/indexes/products/tasks/0") =>                        hashset!{"tasks.get", "*"},
             ("PUT",     "/indexes/products/") =>                               hashset!{"indexes.update", "*"},
+            ("PATCH",   "/indexes/products/") =>                               hashset!{"indexes.update", "*"},
+            ("POST",    "/indexes/products/rebuild") =>                        hashset!{"indexes.update", "*"},
             ("GET",     "/indexes/products/") =>                               hashset!{"indexes.get", "*"},
             ("DELETE",  "/indexes/products/") =>                               hashset!{"indexes.delete", "*"},
+            ("POST",    "/indexes/products/restore") =>                        hashset!{"indexes.delete", "*"},
             ("POST",    "/indexes") =>                                         hashset!{"indexes.create", "*"},
             ("GET",     "/indexes") =>                                         hashset!{"indexes.get", "*"},
             ("GET",     "/indexes/products/settings") =>                       hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/dictionary") =>            hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/displayed-attributes") =>  hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/distinct-attribute") =>    hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/filterable-attributes") => hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/localized-attributes") =>  hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/non-separator-tokens") =>  hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/ranking-rules") =>         hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/searchable-attributes") => hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/separator-tokens") =>      hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/sortable-attributes") =>   hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/stop-words") =>            hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/synonyms") =>              hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/synonyms/export?format=csv") => hashset!{"settings.get", "*"},
             ("DELETE",  "/indexes/products/settings") =>                       hashset!{"settings.update", "*"},
             ("POST",    "/indexes/products/settings") =>                       hashset!{"settings.update", "*"},
+            ("POST",    "/indexes/products/settings/dictionary") =>            hashset!{"settings.update", "*"},
             ("POST",    "/indexes/products/settings/displayed-attributes") =>  hashset!{"settings.update", "*"},
             ("POST",    "/indexes/products/settings/distinct-attribute") =>    hashset!{"settings.update", "*"},
             ("POST",    "/indexes/products/settings/filterable-attributes") => hashset!{"settings.update", "*"},
+            ("POST",    "/indexes/products/settings/localized-attributes") =>  hashset!{"settings.update", "*"},
+            ("POST",    "/indexes/products/settings/non-separator-tokens") =>  hashset!{"settings.update", "*"},
             ("POST",    "/indexes/products/settings/ranking-rules") =>         hashset!{"settings.update", "*"},
             ("POST",    "/indexes/products/settings/searchable-attributes") => hashset!{"settings.update", "*"},
+            ("POST",    "/indexes/products/settings/separator-tokens") =>      hashset!{"settings.update", "*"},
             ("POST",    "/indexes/products/settings/sortable-attributes") =>   hashset!{"settings.update", "*"},
             ("POST",    "/indexes/products/settings/stop-words") =>            hashset!{"settings.update", "*"},
             ("POST",    "/indexes/products/settings/synonyms") =>              hashset!{"settings.update", "*"},
+            ("POST",    "/indexes/products/settings/synonyms/import?format=csv") => hashset!{"settings.update", "*"},
             ("GET",     "/indexes/products/stats") =>                          hashset!{"stats.get", "*"},
             ("GET",     "/stats") =>                                           hashset!{"stats.get", "*"},
+            ("GET",     "/slow-queries") =>                                    hashset!{"stats.get", "*"},
+            ("GET",     "/stats/timeseries") =>                                hashset!{"stats.get", "*"},
+            ("GET",     "/stats/search-cache") =>                              hashset!{"stats.get", "*"},
             ("POST",    "/dumps") =>                                           hashset!{"dumps.create", "*"},
             ("GET",     "/dumps/0/status") =>                                  hashset!{"dumps.get", "*"},
+            ("POST",    "/migrations") =>                                      hashset!{"migrations.create", "*"},
+            ("GET",     "/migrations") =>                                      hashset!{"migrations.get", "*"},
+            ("GET",     "/migrations/unknown") =>                              hashset!{"migrations.get", "*"},
             ("GET",     "/version") =>                                         hashset!{"version", "*"},
+            ("GET",     "/dashboard/api/overview") =>                          hashset!{"dashboard", "*"},
         }
     });
 
@@ -98,6 +123,81 @@ async fn error_access_expired_key() {
     }
 }
 
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn error_access_inactive_key() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let not_yet_active = json!({
+        "indexes": ["products"],
+        "actions": ALL_ACTIONS.clone(),
+        "activatesAt": (OffsetDateTime::now_utc() + Duration::days(1)).format(&Rfc3339).unwrap(),
+        "expiresAt": null,
+    });
+    // a schedule without any day never lets the key be used.
+    let out_of_schedule = json!({
+        "indexes": ["products"],
+        "actions": ALL_ACTIONS.clone(),
+        "expiresAt": null,
+        "schedule": { "days": [], "start": "00:00", "end": "24:00" },
+    });
+
+    for content in [not_yet_active, out_of_schedule] {
+        server.use_api_key("MASTER_KEY");
+        let (response, code) = server.add_api_key(content).await;
+        assert_eq!(code, 201, "{}", response);
+        let key = response["key"].as_str().unwrap().to_string();
+        server.use_api_key(&key);
+
+        for (method, route) in AUTHORIZATIONS.keys() {
+            let (response, code) = server.dummy_request(method, route).await;
+
+            assert_eq!(response, INVALID_RESPONSE.clone());
+            assert_eq!(code, 403);
+        }
+    }
+}
+
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn access_key_within_its_schedule() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["documents.get"],
+        "activatesAt": (OffsetDateTime::now_utc() - Duration::days(1)).format(&Rfc3339).unwrap(),
+        "expiresAt": null,
+        "schedule": {
+            "days": ["monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday"],
+            "start": "00:00",
+            "end": "24:00",
+        },
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 201, "{}", response);
+    assert_eq!(response["schedule"]["end"], "24:00");
+    let key = response["key"].as_str().unwrap().to_string();
+
+    server.use_api_key(&key);
+    let (response, code) = server
+        .dummy_request("GET", "/indexes/products/documents")
+        .await;
+    assert_ne!(code, 403, "{}", response);
+
+    server.use_api_key("MASTER_KEY");
+    let (response, code) = server.get_api_key_permissions(&key).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["active"], true);
+
+    let content = json!({ "schedule": { "days": ["monday"], "start": "8:00", "end": "20:00" } });
+    let (response, code) = server.patch_api_key(&key, content).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_api_key_schedule");
+}
+
 #[actix_rt::test]
 #[cfg_attr(target_os = "windows", ignore)]
 async fn error_access_unauthorized_index() {
@@ -446,6 +546,36 @@ async fn list_authorized_tasks_restricted_index() {
     assert!(!response.iter().any(|task| task["indexUid"] == "test"));
 }
 
+#[actix_rt::test]
+async fn watch_authorized_tasks_restricted_index() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    // create index `test`
+    let index = server.index("test");
+    let (_, code) = index.create(Some("id")).await;
+    assert_eq!(code, 202);
+    index.wait_task(0).await;
+
+    // create key with access on `products` index only.
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["tasks.get"],
+        "expiresAt": (OffsetDateTime::now_utc() + Duration::hours(1)).format(&Rfc3339).unwrap(),
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 201);
+
+    // use created key.
+    let key = response["key"].as_str().unwrap();
+    server.use_api_key(&key);
+
+    // key should not have access on `test` index.
+    let (response, code) = server.service.watch("/tasks/0/watch", usize::MAX).await;
+    assert_eq!(code, 404);
+    assert_eq!(response[0]["code"], "task_not_found");
+}
+
 #[actix_rt::test]
 async fn list_authorized_tasks_no_index_restriction() {
     let mut server = Server::new_auth().await;
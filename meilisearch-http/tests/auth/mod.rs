@@ -1,7 +1,9 @@
 mod api_keys;
 mod authorization;
+mod namespace;
 mod payload;
 mod tenant_token;
+mod usage_webhook;
 
 use crate::common::Server;
 use actix_web::http::StatusCode;
@@ -38,6 +40,16 @@ impl Server {
         self.service.delete(url).await
     }
 
+    pub async fn can_api_key(&self, key: impl AsRef<str>, content: Value) -> (Value, StatusCode) {
+        let url = format!("/keys/{}/can", key.as_ref());
+        self.service.post(url, content).await
+    }
+
+    pub async fn get_api_key_permissions(&self, key: impl AsRef<str>) -> (Value, StatusCode) {
+        let url = format!("/keys/{}/permissions", key.as_ref());
+        self.service.get(url).await
+    }
+
     pub async fn dummy_request(
         &self,
         method: impl AsRef<str>,
@@ -0,0 +1,151 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+async fn server_with_namespaced_key() -> (Server, String) {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+    let content = json!({
+        "namespace": "acme_",
+        "indexes": ["*"],
+        "actions": ["*"],
+        "expiresAt": null,
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 201, "{}", response);
+    assert_eq!(response["namespace"], "acme_");
+    let key = response["key"].as_str().unwrap().to_string();
+
+    let (_, code) = server.index("other").create(None).await;
+    assert_eq!(code, 202);
+    server.index("other").wait_task(0).await;
+
+    (server, key)
+}
+
+#[actix_rt::test]
+async fn namespaced_key_creates_indexes_in_its_namespace() {
+    let (mut server, key) = server_with_namespaced_key().await;
+
+    server.use_api_key(&key);
+    let index = server.index("products");
+    let (response, code) = index.create(None).await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["indexUid"], "products");
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["indexUid"], "products");
+
+    let (response, code) = index.get().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["uid"], "products");
+
+    server.use_api_key("MASTER_KEY");
+    let (response, code) = server.index("acme_products").get().await;
+    assert_eq!(code, 200, "{}", response);
+    let (response, code) = server.index("products").get().await;
+    assert_eq!(code, 404, "{}", response);
+}
+
+#[actix_rt::test]
+async fn namespaced_key_only_sees_its_namespace() {
+    let (mut server, key) = server_with_namespaced_key().await;
+    server.index("acme_products").create(None).await;
+    server.index("acme_products").wait_task(1).await;
+
+    server.use_api_key(&key);
+    let (response, code) = server.list_indexes().await;
+    assert_eq!(code, 200, "{}", response);
+    let uids: Vec<_> = response
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|index| index["uid"].clone())
+        .collect();
+    assert_eq!(uids, vec![json!("products")]);
+
+    let (response, code) = server.service.get("/tasks").await;
+    assert_eq!(code, 200, "{}", response);
+    let tasks = response["results"].as_array().unwrap();
+    assert_eq!(tasks.len(), 1, "{}", response);
+    assert_eq!(tasks[0]["indexUid"], "products");
+
+    // The other indexes don't exist for the key.
+    let (response, code) = server.index("other").get().await;
+    assert_eq!(code, 404, "{}", response);
+    let (response, code) = server.service.get("/tasks/0").await;
+    assert_eq!(code, 404, "{}", response);
+
+    // The routes managing the whole instance are refused.
+    let (response, code) = server.service.get("/keys").await;
+    assert_eq!(code, 403, "{}", response);
+    let (response, code) = server.service.post("/dumps", json!({})).await;
+    assert_eq!(code, 403, "{}", response);
+}
+
+#[actix_rt::test]
+async fn error_add_api_key_invalid_namespace() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "namespace": "acme/",
+        "indexes": ["*"],
+        "actions": ["*"],
+        "expiresAt": null,
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_api_key_namespace");
+}
+
+#[actix_rt::test]
+async fn error_add_api_key_overlapping_namespace() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    for namespace in ["acme", "acme_eu_", "_"] {
+        let content = json!({
+            "namespace": namespace,
+            "indexes": ["*"],
+            "actions": ["*"],
+            "expiresAt": null,
+        });
+        let (response, code) = server.add_api_key(content).await;
+        assert_eq!(code, 400, "{}: {}", namespace, response);
+        assert_eq!(response["code"], "invalid_api_key_namespace");
+    }
+}
+
+#[actix_rt::test]
+async fn similar_namespaces_are_isolated() {
+    let (mut server, acme) = server_with_namespaced_key().await;
+    let content = json!({
+        "namespace": "acme-eu_",
+        "indexes": ["*"],
+        "actions": ["*"],
+        "expiresAt": null,
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 201, "{}", response);
+    let acme_eu = response["key"].as_str().unwrap().to_string();
+
+    server.use_api_key(&acme_eu);
+    let (response, code) = server.index("orders").create(None).await;
+    assert_eq!(code, 202, "{}", response);
+    server.index("orders").wait_task(1).await;
+
+    server.use_api_key(&acme);
+    let (response, code) = server.list_indexes().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!([]));
+    let (response, code) = server.index("orders").get().await;
+    assert_eq!(code, 404, "{}", response);
+    let (response, code) = server.index("-eu_orders").get().await;
+    assert_eq!(code, 404, "{}", response);
+
+    server.use_api_key(&acme_eu);
+    let (response, code) = server.index("orders").get().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["uid"], "orders");
+}
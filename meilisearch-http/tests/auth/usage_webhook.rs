@@ -0,0 +1,83 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+async fn server_with_keys() -> (Server, String, String) {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+    let content = json!({
+        "indexes": ["*"],
+        "actions": ["search"],
+        "expiresAt": null,
+    });
+    let (response, code) = server.add_api_key(content.clone()).await;
+    assert_eq!(code, 201, "{}", response);
+    let key = response["key"].as_str().unwrap().to_string();
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 201, "{}", response);
+    let other_key = response["key"].as_str().unwrap().to_string();
+
+    (server, key, other_key)
+}
+
+#[actix_rt::test]
+async fn key_registers_its_own_usage_webhook() {
+    let (mut server, key, _) = server_with_keys().await;
+
+    server.use_api_key(&key);
+    let url = format!("/keys/{}/usage-webhook", key);
+    let webhook = json!({ "url": "https://example.com/usage", "frequency": "weekly" });
+    let (response, code) = server.service.put(&url, webhook).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["url"], "https://example.com/usage");
+    assert_eq!(response["frequency"], "weekly");
+    assert!(response["periodStart"].is_string());
+
+    server.use_api_key("MASTER_KEY");
+    let (response, code) = server.get_api_key(&key).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["usageWebhook"]["url"], "https://example.com/usage");
+
+    server.use_api_key(&key);
+    let (response, code) = server.service.delete(&url).await;
+    assert_eq!(code, 204, "{}", response);
+
+    server.use_api_key("MASTER_KEY");
+    let (response, _) = server.get_api_key(&key).await;
+    assert!(response.get("usageWebhook").is_none(), "{}", response);
+}
+
+#[actix_rt::test]
+async fn key_cannot_register_the_webhook_of_another_key() {
+    let (mut server, key, other_key) = server_with_keys().await;
+
+    server.use_api_key(&other_key);
+    let url = format!("/keys/{}/usage-webhook", key);
+    let webhook = json!({ "url": "https://example.com/usage", "frequency": "daily" });
+    let (response, code) = server.service.put(&url, webhook.clone()).await;
+    assert_eq!(code, 403, "{}", response);
+    assert_eq!(response["code"], "invalid_api_key");
+
+    server.use_api_key("MASTER_KEY");
+    let (response, code) = server.service.put(&url, webhook).await;
+    assert_eq!(code, 200, "{}", response);
+}
+
+#[actix_rt::test]
+async fn invalid_usage_webhook_url() {
+    let (mut server, key, _) = server_with_keys().await;
+
+    server.use_api_key(&key);
+    let url = format!("/keys/{}/usage-webhook", key);
+    for webhook_url in [
+        "ftp://example.com",
+        "http://localhost:7700/keys",
+        "http://169.254.169.254/latest/meta-data",
+        "http://[::1]:7700",
+    ] {
+        let webhook = json!({ "url": webhook_url, "frequency": "daily" });
+        let (response, code) = server.service.put(&url, webhook).await;
+        assert_eq!(code, 400, "{}", response);
+        assert_eq!(response["code"], "invalid_api_key_usage_webhook");
+    }
+}
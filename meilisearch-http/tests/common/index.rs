@@ -68,6 +68,25 @@ impl Index<'_> {
         self.service.put(url, body).await
     }
 
+    pub async fn rename(&self, new_uid: &str) -> (Value, StatusCode) {
+        let body = json!({
+            "newUid": new_uid,
+        });
+        let url = format!("/indexes/{}", encode(self.uid.as_ref()));
+
+        self.service.patch(url, body).await
+    }
+
+    pub async fn rebuild(&self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/rebuild", encode(self.uid.as_ref()));
+        self.service.post(url, body).await
+    }
+
+    pub async fn restore(&self) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/restore", encode(self.uid.as_ref()));
+        self.service.post(url, Value::Null).await
+    }
+
     pub async fn delete(&self) -> (Value, StatusCode) {
         let url = format!("/indexes/{}", encode(self.uid.as_ref()));
         self.service.delete(url).await
@@ -231,7 +250,7 @@ impl Index<'_> {
         self.service.get(url).await
     }
 
-    make_settings_test_routes!(distinct_attribute);
+    make_settings_test_routes!(distinct_attribute, dictionary);
 }
 
 pub struct GetDocumentOptions;
@@ -119,6 +119,14 @@ impl Server {
         self.service.get("/tasks").await
     }
 
+    pub async fn slow_queries(&self) -> (Value, StatusCode) {
+        self.service.get("/slow-queries").await
+    }
+
+    pub async fn search_cache_stats(&self) -> (Value, StatusCode) {
+        self.service.get("/stats/search-cache").await
+    }
+
     pub async fn get_dump_status(&self, uid: &str) -> (Value, StatusCode) {
         self.service.get(format!("/dumps/{}/status", uid)).await
     }
@@ -1,4 +1,7 @@
-use actix_web::{http::StatusCode, test};
+use actix_web::body::MessageBody;
+use actix_web::http::{header::HeaderMap, StatusCode};
+use actix_web::test;
+use futures::future::poll_fn;
 use meilisearch_auth::AuthController;
 use meilisearch_lib::MeiliSearch;
 use serde_json::Value;
@@ -36,6 +39,30 @@ impl Service {
         (response, status_code)
     }
 
+    /// Send a test post request with a JSON body, and return the response body as text.
+    pub async fn post_text(&self, url: impl AsRef<str>, body: Value) -> (String, StatusCode) {
+        let app = test::init_service(create_app!(
+            &self.meilisearch,
+            &self.auth,
+            true,
+            &self.options,
+            analytics::MockAnalytics::new(&self.options).0
+        ))
+        .await;
+
+        let mut req = test::TestRequest::post().uri(url.as_ref()).set_json(&body);
+        if let Some(api_key) = &self.api_key {
+            req = req.insert_header(("Authorization", ["Bearer ", api_key].concat()));
+        }
+        let req = req.to_request();
+        let res = test::call_service(&app, req).await;
+        let status_code = res.status();
+
+        let body = test::read_body(res).await;
+        let response = String::from_utf8_lossy(&body).into_owned();
+        (response, status_code)
+    }
+
     /// Send a test post request from a text body, with a `content-type:application/json` header.
     pub async fn post_str(
         &self,
@@ -90,6 +117,94 @@ impl Service {
         (response, status_code)
     }
 
+    /// Send a test get request, and return the body as text instead of parsing it as json.
+    pub async fn get_text(&self, url: impl AsRef<str>) -> (String, StatusCode) {
+        let app = test::init_service(create_app!(
+            &self.meilisearch,
+            &self.auth,
+            true,
+            &self.options,
+            analytics::MockAnalytics::new(&self.options).0
+        ))
+        .await;
+
+        let mut req = test::TestRequest::get().uri(url.as_ref());
+        if let Some(api_key) = &self.api_key {
+            req = req.insert_header(("Authorization", ["Bearer ", api_key].concat()));
+        }
+        let req = req.to_request();
+        let res = test::call_service(&app, req).await;
+        let status_code = res.status();
+
+        let body = test::read_body(res).await;
+        let response = String::from_utf8_lossy(&body).into_owned();
+        (response, status_code)
+    }
+
+    /// Send a request built by the caller, without adding the api key, and return the response
+    /// headers along with the body.
+    pub async fn request(&self, req: test::TestRequest) -> (Value, StatusCode, HeaderMap) {
+        let app = test::init_service(create_app!(
+            &self.meilisearch,
+            &self.auth,
+            true,
+            &self.options,
+            analytics::MockAnalytics::new(&self.options).0
+        ))
+        .await;
+
+        let res = test::call_service(&app, req.to_request()).await;
+        let status_code = res.status();
+        let headers = res.headers().clone();
+
+        let body = test::read_body(res).await;
+        let response = serde_json::from_slice(&body).unwrap_or_default();
+        (response, status_code, headers)
+    }
+
+    /// Reads the first `count` server-sent events of a stream, or less if the stream ends before.
+    /// The body is returned as the only event when the request fails.
+    pub async fn watch(&self, url: impl AsRef<str>, count: usize) -> (Vec<Value>, StatusCode) {
+        let app = test::init_service(create_app!(
+            &self.meilisearch,
+            &self.auth,
+            true,
+            &self.options,
+            analytics::MockAnalytics::new(&self.options).0
+        ))
+        .await;
+
+        let mut req = test::TestRequest::get().uri(url.as_ref());
+        if let Some(api_key) = &self.api_key {
+            req = req.insert_header(("Authorization", ["Bearer ", api_key].concat()));
+        }
+        let req = req.to_request();
+        let res = test::call_service(&app, req).await;
+        let status_code = res.status();
+
+        if !status_code.is_success() {
+            let body = test::read_body(res).await;
+            let response = serde_json::from_slice(&body).unwrap_or_default();
+            return (vec![response], status_code);
+        }
+
+        let mut body = Box::pin(res.into_body());
+        let mut events = Vec::new();
+        while events.len() < count {
+            let chunk = match poll_fn(|cx| body.as_mut().poll_next(cx)).await {
+                Some(Ok(chunk)) => chunk,
+                _ => break,
+            };
+            let chunk = String::from_utf8(chunk.to_vec()).unwrap();
+            // The comments are only sent to keep the connection alive.
+            if let Some(data) = chunk.strip_prefix("data: ") {
+                events.push(serde_json::from_str(data.trim_end()).unwrap());
+            }
+        }
+
+        (events, status_code)
+    }
+
     pub async fn put(&self, url: impl AsRef<str>, body: Value) -> (Value, StatusCode) {
         let app = test::init_service(create_app!(
             &self.meilisearch,
@@ -0,0 +1,108 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[cfg(feature = "dashboard")]
+#[actix_rt::test]
+async fn serves_the_application() {
+    let server = Server::new().await;
+
+    let (body, code) = server.service.get_text("/dashboard").await;
+    assert_eq!(code, 200);
+    assert!(body.contains("<html"));
+
+    let (_, code) = server.service.get_text("/dashboard/app.js").await;
+    assert_eq!(code, 200);
+
+    // the routes of the application are answered with the application.
+    let (body, code) = server.service.get_text("/dashboard/keys").await;
+    assert_eq!(code, 200);
+    assert!(body.contains("<html"));
+}
+
+#[actix_rt::test]
+async fn admin_routes_require_the_dashboard_action() {
+    let mut server = Server::new_auth().await;
+
+    let (_, code) = server.service.get("/dashboard/api/overview").await;
+    assert_eq!(code, 401);
+
+    server.use_api_key("MASTER_KEY");
+    let (response, code) = server.service.get("/dashboard/api/overview").await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response["pkgVersion"].is_string());
+    assert!(response["stats"]["indexes"].is_object());
+
+    let (response, _) = server
+        .add_api_key(json!({ "indexes": ["*"], "actions": ["search"], "expiresAt": null }))
+        .await;
+    let search_key = response["key"].as_str().unwrap().to_string();
+    let (response, _) = server
+        .add_api_key(json!({ "indexes": ["*"], "actions": ["dashboard"], "expiresAt": null }))
+        .await;
+    let dashboard_key = response["key"].as_str().unwrap().to_string();
+
+    server.use_api_key(&search_key);
+    let (_, code) = server.service.get("/dashboard/api/overview").await;
+    assert_eq!(code, 403);
+    let (_, code) = server.service.get("/dashboard/api/keys").await;
+    assert_eq!(code, 403);
+
+    server.use_api_key(&dashboard_key);
+    let (_, code) = server.service.get("/dashboard/api/overview").await;
+    assert_eq!(code, 200);
+}
+
+#[actix_rt::test]
+async fn keys_are_managed_with_the_master_key() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let (response, _) = server
+        .add_api_key(
+            json!({ "indexes": ["products"], "actions": ["dashboard"], "expiresAt": null }),
+        )
+        .await;
+    let dashboard_key = response["key"].as_str().unwrap().to_string();
+
+    // A key holding the `dashboard` action can't create keys, they could grant more than it.
+    server.use_api_key(&dashboard_key);
+    let content = json!({ "indexes": ["*"], "actions": ["*"], "expiresAt": null });
+    let (_, code) = server.service.post("/dashboard/api/keys", content).await;
+    assert_eq!(code, 403);
+    let (_, code) = server.service.get("/dashboard/api/keys").await;
+    assert_eq!(code, 403);
+
+    server.use_api_key("MASTER_KEY");
+    let content = json!({
+        "description": "search key",
+        "indexes": ["products"],
+        "actions": ["search"],
+        "expiresAt": null,
+    });
+    let (response, code) = server.service.post("/dashboard/api/keys", content).await;
+    assert_eq!(code, 201, "{}", response);
+    let key = response["key"].as_str().unwrap().to_string();
+
+    let (response, code) = server.service.get("/dashboard/api/keys").await;
+    assert_eq!(code, 200);
+    assert!(response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|k| k["key"] == key));
+
+    let url = format!("/dashboard/api/keys/{}", key);
+    let (response, code) = server
+        .service
+        .patch(&url, json!({ "description": "products search key" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["description"], "products search key");
+
+    let (_, code) = server.service.delete(&url).await;
+    assert_eq!(code, 204);
+
+    let (_, code) = server.service.get(&url).await;
+    assert_eq!(code, 404);
+}
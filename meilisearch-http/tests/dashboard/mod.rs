@@ -1,3 +1,6 @@
+mod embedded;
+mod session;
+
 use crate::common::Server;
 
 #[actix_rt::test]
@@ -0,0 +1,159 @@
+use actix_web::http::header::{HeaderMap, COOKIE, SET_COOKIE};
+use actix_web::test::TestRequest;
+use serde_json::json;
+
+use crate::common::Server;
+
+/// Returns the `name=value` part of the session cookie set by the response.
+fn session_cookie(headers: &HeaderMap) -> String {
+    let cookie = headers
+        .get(SET_COOKIE)
+        .expect("missing session cookie")
+        .to_str()
+        .unwrap();
+    cookie.split(';').next().unwrap().to_string()
+}
+
+async fn login(server: &Server, key: &str) -> String {
+    let req = TestRequest::post()
+        .uri("/dashboard/login")
+        .set_json(&json!({ "key": key }));
+    let (response, code, headers) = server.service.request(req).await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response["expiresAt"].is_string());
+
+    session_cookie(&headers)
+}
+
+async fn get_overview(server: &Server, cookie: &str) -> u16 {
+    let req = TestRequest::get()
+        .uri("/dashboard/api/overview")
+        .insert_header((COOKIE, cookie));
+    let (_, code, _) = server.service.request(req).await;
+    code.as_u16()
+}
+
+#[actix_rt::test]
+async fn login_with_master_key() {
+    let server = Server::new_auth().await;
+
+    let req = TestRequest::post()
+        .uri("/dashboard/login")
+        .set_json(&json!({ "key": "MASTER_KEY" }));
+    let (_, code, headers) = server.service.request(req).await;
+    assert_eq!(code, 200);
+
+    let set_cookie = headers.get(SET_COOKIE).unwrap().to_str().unwrap();
+    assert!(set_cookie.starts_with("meilisearch-dashboard-session="));
+    assert!(set_cookie.contains("HttpOnly"));
+    assert!(set_cookie.contains("SameSite=Strict"));
+    assert!(set_cookie.contains("Path=/dashboard"));
+    assert!(!set_cookie.contains("MASTER_KEY"));
+
+    let cookie = session_cookie(&headers);
+    assert_eq!(get_overview(&server, &cookie).await, 200);
+
+    // the session authenticates the API the dashboard uses under its own routes only.
+    let req = TestRequest::get()
+        .uri("/dashboard/api/indexes")
+        .insert_header((COOKIE, cookie.as_str()));
+    let (_, code, _) = server.service.request(req).await;
+    assert_eq!(code, 200);
+
+    let req = TestRequest::get()
+        .uri("/indexes")
+        .insert_header((COOKIE, cookie.as_str()));
+    let (_, code, _) = server.service.request(req).await;
+    assert_eq!(code, 401);
+}
+
+#[actix_rt::test]
+async fn session_cookie_is_secure_over_https() {
+    let server = Server::new_auth().await;
+
+    let req = TestRequest::post()
+        .uri("/dashboard/login")
+        .insert_header(("X-Forwarded-Proto", "https"))
+        .set_json(&json!({ "key": "MASTER_KEY" }));
+    let (_, code, headers) = server.service.request(req).await;
+    assert_eq!(code, 200);
+    let set_cookie = headers.get(SET_COOKIE).unwrap().to_str().unwrap();
+    assert!(set_cookie.contains("Secure"));
+
+    let req = TestRequest::post()
+        .uri("/dashboard/login")
+        .set_json(&json!({ "key": "MASTER_KEY" }));
+    let (_, _, headers) = server.service.request(req).await;
+    let set_cookie = headers.get(SET_COOKIE).unwrap().to_str().unwrap();
+    assert!(!set_cookie.contains("Secure"));
+}
+
+#[actix_rt::test]
+async fn login_without_master_key_is_refused() {
+    let server = Server::new().await;
+
+    let req = TestRequest::post()
+        .uri("/dashboard/login")
+        .set_json(&json!({ "key": "any key" }));
+    let (response, code, headers) = server.service.request(req).await;
+    assert_eq!(code, 401, "{}", response);
+    assert_eq!(response["code"], "missing_master_key");
+    assert!(headers.get(SET_COOKIE).is_none());
+}
+
+#[actix_rt::test]
+async fn requests_without_session_are_rejected() {
+    let server = Server::new_auth().await;
+
+    let req = TestRequest::get().uri("/dashboard/api/overview");
+    let (_, code, _) = server.service.request(req).await;
+    assert_eq!(code, 401);
+
+    let cookie = login(&server, "MASTER_KEY").await;
+    let tampered = format!("{}0", cookie);
+    assert_eq!(get_overview(&server, &tampered).await, 401);
+}
+
+#[actix_rt::test]
+async fn login_with_dashboard_key() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let (response, _) = server
+        .add_api_key(json!({ "indexes": ["*"], "actions": ["search"], "expiresAt": null }))
+        .await;
+    let search_key = response["key"].as_str().unwrap().to_string();
+    let (response, _) = server
+        .add_api_key(json!({ "indexes": ["*"], "actions": ["dashboard"], "expiresAt": null }))
+        .await;
+    let dashboard_key = response["key"].as_str().unwrap().to_string();
+
+    let req = TestRequest::post()
+        .uri("/dashboard/login")
+        .set_json(&json!({ "key": search_key }));
+    let (response, code, headers) = server.service.request(req).await;
+    assert_eq!(code, 403);
+    assert_eq!(response["code"], "invalid_api_key");
+    assert!(headers.get(SET_COOKIE).is_none());
+
+    let cookie = login(&server, &dashboard_key).await;
+    assert_eq!(get_overview(&server, &cookie).await, 200);
+
+    // the session ends with its key.
+    let (_, code) = server.delete_api_key(&dashboard_key).await;
+    assert_eq!(code, 204);
+    assert_eq!(get_overview(&server, &cookie).await, 401);
+}
+
+#[actix_rt::test]
+async fn logout_removes_the_session_cookie() {
+    let server = Server::new_auth().await;
+
+    let req = TestRequest::post().uri("/dashboard/logout");
+    let (_, code, headers) = server.service.request(req).await;
+    assert_eq!(code, 204);
+
+    let set_cookie = headers.get(SET_COOKIE).unwrap().to_str().unwrap();
+    assert!(set_cookie.starts_with("meilisearch-dashboard-session=;"));
+    assert!(set_cookie.contains("Max-Age=0"));
+}
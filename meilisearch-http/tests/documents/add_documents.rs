@@ -1,6 +1,8 @@
+use crate::common::server::default_settings;
 use crate::common::{GetAllDocumentsOptions, Server};
 use actix_web::test;
-use meilisearch_http::{analytics, create_app};
+use byte_unit::{Byte, ByteUnit};
+use meilisearch_http::{analytics, create_app, Opt};
 use serde_json::{json, Value};
 use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
@@ -1048,6 +1050,53 @@ async fn error_add_documents_payload_size() {
     assert_eq!(code, 413);
 }
 
+#[actix_rt::test]
+async fn add_documents_payload_size_limit_of_the_route() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt {
+        http_payload_size_limit: Byte::from_bytes(1024),
+        http_payload_size_limit_documents: Some(Byte::from_unit(1.0, ByteUnit::MiB).unwrap()),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+
+    // the documents are streamed in several chunks, way over the default limit.
+    let documents: String = (0..2000)
+        .map(|id| format!("{{\"id\": {}, \"content\": \"Bouvier Bernois\"}}\n", id))
+        .collect();
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents")
+        .set_payload(documents)
+        .insert_header(("content-type", "application/x-ndjson"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 202, "{}", response);
+
+    let index = server.index("dog");
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 2000);
+
+    // the other routes are still limited.
+    let stop_words: Vec<_> = (0..200).map(|i| format!("word{}", i)).collect();
+    let (response, code) = index
+        .update_settings(json!({ "stopWords": stop_words }))
+        .await;
+    assert_eq!(code, 413, "{}", response);
+    assert_eq!(response["code"], "payload_too_large");
+}
+
 #[actix_rt::test]
 async fn error_primary_key_inference() {
     let server = Server::new().await;
@@ -1098,3 +1147,149 @@ async fn add_documents_with_primary_key_twice() {
     let (response, _code) = index.get_task(1).await;
     assert_eq!(response["status"], "succeeded");
 }
+
+#[actix_rt::test]
+async fn disk_quota_exceeded() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt {
+        max_disk_usage: Some(Byte::from_bytes(1)),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await;
+    let index = server.index("test");
+
+    let (response, code) = index
+        .add_documents(json!([{ "id": 1, "content": "foo" }]), None)
+        .await;
+    assert_eq!(code, 507, "{}", response);
+    assert_eq!(response["code"], "disk_quota_exceeded");
+    assert_eq!(response["type"], "internal");
+
+    // the tasks that would make the database grow fail once they are processed.
+    let (response, code) = index.create(None).await;
+    assert_eq!(code, 202, "{}", response);
+    let response = index.wait_task(response["uid"].as_u64().unwrap()).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "disk_quota_exceeded");
+}
+
+#[actix_rt::test]
+async fn disk_quota_exceeded_by_the_announced_payload_size() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt {
+        max_disk_usage: Some(Byte::from_unit(1., ByteUnit::GB).unwrap()),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await;
+
+    // the payload is refused from its `Content-Length`, before it is read.
+    let req = test::TestRequest::post()
+        .uri("/indexes/test/documents")
+        .set_payload(json!([{ "id": 1, "content": "foo" }]).to_string())
+        .insert_header(("content-type", "application/json"))
+        .insert_header(("content-length", "10000000000"));
+    let (response, code, _) = server.service.request(req).await;
+    assert_eq!(code, 507, "{}", response);
+    assert_eq!(response["code"], "disk_quota_exceeded");
+
+    let (response, code) = server
+        .index("test")
+        .add_documents(json!([{ "id": 1 }]), None)
+        .await;
+    assert_eq!(code, 202, "{}", response);
+}
+
+#[actix_rt::test]
+async fn add_documents_with_version_field() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "versionField": "version" }))
+        .await;
+    index.wait_task(0).await;
+
+    let documents = json!([
+        { "id": 1, "title": "newer", "version": 2 },
+        { "id": 2, "title": "older", "version": 1 },
+    ]);
+    index.add_documents(documents, Some("id")).await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded");
+    assert!(response["details"].get("skippedDocuments").is_none());
+
+    // the documents older than the stored ones are skipped, within an addition too.
+    let documents = json!([
+        { "id": 1, "title": "outdated", "version": 1 },
+        { "id": 2, "title": "updated", "version": 3 },
+        { "id": 2, "title": "reordered", "version": 2 },
+        { "id": 3, "title": "unversioned" },
+    ]);
+    index.add_documents(documents, None).await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(response["details"]["receivedDocuments"], 4);
+    assert_eq!(response["details"]["skippedDocuments"], 2);
+
+    let (response, _) = index.get_document(1, None).await;
+    assert_eq!(response["title"], "newer");
+    let (response, _) = index.get_document(2, None).await;
+    assert_eq!(response["title"], "updated");
+    let (response, _) = index.get_document(3, None).await;
+    assert_eq!(response["title"], "unversioned");
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200);
+    assert_eq!(response["versionField"], "version");
+}
+
+#[cfg(feature = "xml")]
+#[actix_rt::test]
+async fn add_xml_documents() {
+    let documents = r#"<?xml version="1.0"?>
+        <dogs>
+            <dog id="1"><name>Bouvier Bernois</name><color>black</color><color>white</color></dog>
+            <dog id="2"><name>Jack Russell &amp; co</name></dog>
+        </dogs>"#;
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents")
+        .set_payload(documents.to_string())
+        .insert_header(("content-type", "application/xml"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 202);
+
+    let index = server.index("dog");
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 2);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "id": "1", "name": "Bouvier Bernois", "color": ["black", "white"] })
+    );
+
+    // the documents are parsed as the payload is received, an unclosed element is malformed.
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents")
+        .set_payload("<dogs><dog id=\"3\"><name>Akita</dog></dogs>")
+        .insert_header(("content-type", "text/xml"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 400, "{}", response);
+    assert_eq!(response["code"], "malformed_payload");
+}
@@ -0,0 +1,100 @@
+use serde_json::json;
+
+use crate::common::{GetAllDocumentsOptions, Server};
+
+#[actix_rt::test]
+async fn documents_are_compressed() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "documentCompression": "zstd" }))
+        .await;
+    index.wait_task(0).await;
+
+    let documents: Vec<_> = (0..20)
+        .map(
+            |id| json!({ "id": id, "title": format!("The story number {} of the collection", id) }),
+        )
+        .collect();
+    index.add_documents(json!(documents), None).await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.stats().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["numberOfDocuments"], 20);
+    assert_eq!(response["documentCompression"]["compressedDocuments"], 20);
+    assert!(
+        response["documentCompression"]["uncompressedBytes"]
+            .as_u64()
+            .unwrap()
+            > 0
+    );
+
+    let (response, code) = index.get_document(3, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({ "id": 3, "title": "The story number 3 of the collection" })
+    );
+
+    index
+        .search(json!({ "q": "story", "limit": 2 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+            assert!(response["hits"][0]["title"].is_string());
+        })
+        .await;
+
+    // the partial updates and deletions read the compressed documents.
+    index
+        .update_documents(json!([{ "id": 3, "author": "Jane" }]), None)
+        .await;
+    index.wait_task(2).await;
+    index.delete_document(4).await;
+    index.wait_task(3).await;
+
+    let (response, code) = index.get_document(3, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({ "id": 3, "title": "The story number 3 of the collection", "author": "Jane" })
+    );
+    let (response, code) = index.get_document(4, None).await;
+    assert_eq!(code, 404, "{}", response);
+
+    let (response, _) = index.stats().await;
+    assert_eq!(response["documentCompression"]["compressedDocuments"], 19);
+}
+
+#[actix_rt::test]
+async fn disable_document_compression() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "documentCompression": "zstd" }))
+        .await;
+    index.wait_task(0).await;
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(1).await;
+
+    index
+        .update_settings(json!({ "documentCompression": "none" }))
+        .await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.stats().await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response.get("documentCompression").is_none());
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!([{ "id": 1, "title": "Carol" }]));
+}
@@ -0,0 +1,161 @@
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+async fn server_with_documents() -> Server {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["genre"] }))
+        .await;
+    index.wait_task(0).await;
+
+    let documents = json!([
+        { "genre": "horror", "id": 1, "title": "Carrie" },
+        { "genre": "comedy", "id": 2, "title": "Airplane!, the movie" },
+        { "genre": "horror", "id": 3, "title": "It", "tags": ["clown", "novel"] },
+    ]);
+    index.add_documents(documents, None).await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    server
+}
+
+fn ndjson(response: &str) -> Vec<Value> {
+    response
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn export_all_documents_as_ndjson() {
+    let server = server_with_documents().await;
+
+    let (response, code) = server
+        .service
+        .post_text("/indexes/test/export", json!({}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        ndjson(&response),
+        vec![
+            json!({ "genre": "horror", "id": 1, "title": "Carrie" }),
+            json!({ "genre": "comedy", "id": 2, "title": "Airplane!, the movie" }),
+            json!({ "genre": "horror", "id": 3, "title": "It", "tags": ["clown", "novel"] }),
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn export_filtered_documents() {
+    let server = server_with_documents().await;
+
+    let (response, code) = server
+        .service
+        .post_text(
+            "/indexes/test/export",
+            json!({ "format": "ndjson", "filter": "genre = horror" }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let ids: Vec<_> = ndjson(&response).iter().map(|d| d["id"].clone()).collect();
+    assert_eq!(ids, vec![json!(1), json!(3)]);
+
+    let (response, code) = server
+        .service
+        .post_text(
+            "/indexes/test/export",
+            json!({ "filter": ["genre = comedy", "genre EXISTS"] }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let ids: Vec<_> = ndjson(&response).iter().map(|d| d["id"].clone()).collect();
+    assert_eq!(ids, vec![json!(2)]);
+
+    let (response, code) = server
+        .service
+        .post_text(
+            "/indexes/test/export",
+            json!({ "filter": "genre = western" }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, "");
+}
+
+#[actix_rt::test]
+async fn export_documents_as_csv() {
+    let server = server_with_documents().await;
+
+    let (response, code) = server
+        .service
+        .post_text("/indexes/test/export", json!({ "format": "csv" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let mut lines = response.lines();
+    let header: Vec<_> = lines.next().unwrap().split(',').collect();
+    assert_eq!(header, vec!["genre", "id", "title", "tags"]);
+    assert_eq!(
+        lines.collect::<Vec<_>>(),
+        vec![
+            "horror,1,Carrie,",
+            "comedy,2,\"Airplane!, the movie\",",
+            "horror,3,It,\"[\"\"clown\"\",\"\"novel\"\"]\"",
+        ]
+    );
+
+    // The header is written even when no documents match.
+    let (response, code) = server
+        .service
+        .post_text(
+            "/indexes/test/export",
+            json!({ "format": "csv", "filter": "genre = western" }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, "genre,id,title,tags\n");
+}
+
+#[actix_rt::test]
+async fn export_only_the_displayed_attributes() {
+    let server = server_with_documents().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "displayedAttributes": ["id", "title"] }))
+        .await;
+    index.wait_task(2).await;
+
+    let (response, code) = server
+        .service
+        .post_text("/indexes/test/export", json!({}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ndjson(&response)[0], json!({ "id": 1, "title": "Carrie" }));
+}
+
+#[actix_rt::test]
+async fn error_export_documents() {
+    let server = server_with_documents().await;
+
+    let (response, code) = server
+        .service
+        .post("/indexes/test/export", json!({ "filter": "title = It" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_filter");
+
+    let (response, code) = server
+        .service
+        .post("/indexes/test/export", json!({ "format": "xml" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+
+    let (response, code) = server
+        .service
+        .post("/indexes/unknown/export", json!({}))
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_not_found");
+}
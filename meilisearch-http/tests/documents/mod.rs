@@ -1,3 +1,6 @@
 mod add_documents;
+mod compression;
 mod delete_documents;
+mod export;
 mod get_documents;
+mod read_sessions;
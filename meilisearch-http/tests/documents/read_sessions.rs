@@ -0,0 +1,139 @@
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+fn ids(response: &Value) -> Vec<u64> {
+    response
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|document| document["id"].as_u64().unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn read_session_pages_ignore_the_later_updates() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1 }, { "id": 2 }, { "id": 3 }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let (session, code) = server
+        .service
+        .post("/indexes/test/read-sessions", json!({}))
+        .await;
+    assert_eq!(code, 201, "{}", session);
+    assert_eq!(session["indexUid"], "test");
+    assert_eq!(session["numberOfDocuments"], 3);
+    assert_eq!(session["ttl"], 60);
+    let token = session["token"].as_str().unwrap();
+
+    let (response, code) = server
+        .service
+        .get(format!(
+            "/indexes/test/documents?limit=2&readSession={}",
+            token
+        ))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), vec![1, 2]);
+
+    // The documents deleted and added after the session opened are not seen by its pages.
+    index.delete_document(1).await;
+    index.add_documents(json!([{ "id": 4 }]), None).await;
+    index.wait_task(2).await;
+
+    let (response, code) = server
+        .service
+        .get(format!(
+            "/indexes/test/documents?offset=2&limit=2&readSession={}",
+            token
+        ))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), vec![3]);
+
+    let (response, _) = index.get_all_documents(Default::default()).await;
+    assert_eq!(ids(&response), vec![2, 3, 4]);
+
+    let (_, code) = server
+        .service
+        .delete(format!("/indexes/test/read-sessions/{}", token))
+        .await;
+    assert_eq!(code, 204);
+
+    let (response, code) = server
+        .service
+        .get(format!("/indexes/test/documents?readSession={}", token))
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "read_session_not_found");
+}
+
+#[actix_rt::test]
+async fn read_session_belongs_to_its_index() {
+    let server = Server::new().await;
+    server.index("test").create(None).await;
+    server.index("other").create(None).await;
+    server.index("other").wait_task(1).await;
+
+    let (session, code) = server
+        .service
+        .post("/indexes/test/read-sessions", json!({}))
+        .await;
+    assert_eq!(code, 201, "{}", session);
+
+    let (response, code) = server
+        .service
+        .get(format!(
+            "/indexes/other/documents?readSession={}",
+            session["token"].as_str().unwrap()
+        ))
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "read_session_not_found");
+}
+
+#[actix_rt::test]
+async fn error_read_session_ttl() {
+    let server = Server::new().await;
+    server.index("test").create(None).await;
+    server.index("test").wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .post("/indexes/test/read-sessions?ttl=3600", json!({}))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn rebuild_closes_the_read_sessions() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    index.wait_task(0).await;
+
+    let (session, code) = server
+        .service
+        .post("/indexes/test/read-sessions?ttl=600", json!({}))
+        .await;
+    assert_eq!(code, 201, "{}", session);
+    let token = session["token"].as_str().unwrap();
+
+    // The session would keep the index open past the time a compaction waits for it.
+    let (_, code) = index.rebuild(json!({})).await;
+    assert_eq!(code, 202);
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = server
+        .service
+        .get(format!("/indexes/test/documents?readSession={}", token))
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "read_session_not_found");
+}
@@ -0,0 +1,108 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+async fn server_with_movies() -> Server {
+    let server = Server::new().await;
+    let index = server.index("movies");
+    index
+        .update_settings(
+            json!({ "filterableAttributes": ["genre"], "sortableAttributes": ["year"] }),
+        )
+        .await;
+    let (response, _) = index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "Carol", "genre": "drama", "year": 2015 },
+                { "id": 2, "title": "Alien", "genre": "sci-fi", "year": 1979 },
+                { "id": 3, "title": "Moonlight", "genre": "drama", "year": 2016 },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(response["uid"].as_u64().unwrap()).await;
+    server
+}
+
+#[actix_rt::test]
+async fn graphql_search() {
+    let server = server_with_movies().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/graphql",
+            json!({
+                "query": r#"{
+                    movies(filter: "genre = drama", sort: ["year:desc"]) {
+                        nbHits
+                        limit
+                        hits { id title }
+                    }
+                }"#
+            }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response.get("errors").is_none(), "{}", response);
+    assert_eq!(
+        response["data"],
+        json!({
+            "movies": {
+                "nbHits": 2,
+                "limit": 20,
+                "hits": [
+                    { "id": 3, "title": "Moonlight" },
+                    { "id": 1, "title": "Carol" },
+                ]
+            }
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn graphql_schema_is_derived_from_the_indexes() {
+    let server = server_with_movies().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/graphql",
+            json!({ "query": r#"{ __type(name: "MoviesDocument") { fields { name } } }"# }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let mut fields: Vec<_> = response["data"]["__type"]["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| field["name"].as_str().unwrap().to_string())
+        .collect();
+    fields.sort();
+    assert_eq!(fields, ["genre", "id", "title", "year"]);
+}
+
+#[actix_rt::test]
+async fn graphql_search_errors() {
+    let server = server_with_movies().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/graphql",
+            json!({ "query": r#"{ movies(filter: "title = Carol") { nbHits } }"# }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["errors"][0]["extensions"]["code"],
+        "invalid_filter"
+    );
+
+    let (response, code) = server
+        .service
+        .post("/graphql", json!({ "query": "{ series { nbHits } }" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response["errors"].as_array().is_some(), "{}", response);
+}
@@ -1,5 +1,9 @@
 mod create_index;
 mod delete_index;
 mod get_index;
+mod rebuild_index;
+mod rename_index;
+mod restore_index;
 mod stats;
+mod templates;
 mod update_index;
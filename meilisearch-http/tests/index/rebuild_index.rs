@@ -0,0 +1,74 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn rebuild_index() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(
+            json!([{ "id": 1, "title": "Carol" }, { "id": 2, "title": "Kefir" }]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+    index
+        .update_settings(json!({ "searchableAttributes": ["title"] }))
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.rebuild(json!({})).await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["type"], "indexRebuild");
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["reindex"], true);
+    assert!(
+        response["details"]["reclaimedBytes"].is_u64(),
+        "{}",
+        response
+    );
+
+    index
+        .search(json!({ "q": "kefir" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([{ "id": 2, "title": "Kefir" }]));
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn compact_index_without_reindexing() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let (_, code) = index.rebuild(json!({ "reindex": false })).await;
+    assert_eq!(code, 202);
+
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["reindex"], false);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(response, json!({ "id": 1, "title": "Carol" }));
+}
+
+#[actix_rt::test]
+async fn rebuild_unexisting_index() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (_, code) = index.rebuild(json!({})).await;
+    assert_eq!(code, 202);
+
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["details"]["reclaimedBytes"], 0);
+    assert_eq!(response["error"]["code"], "index_not_found");
+}
@@ -0,0 +1,77 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn rename_index() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.rename("renamed").await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["type"], "indexRename");
+
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["newUid"], "renamed");
+
+    let (_, code) = index.get().await;
+    assert_eq!(code, 404);
+
+    let renamed = server.index("renamed");
+    let (response, code) = renamed.get().await;
+    assert_eq!(code, 200);
+    assert_eq!(response["uid"], "renamed");
+    assert_eq!(response["primaryKey"], "id");
+
+    let (response, code) = renamed.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(response, json!({ "id": 1, "title": "Carol" }));
+}
+
+#[actix_rt::test]
+async fn rename_to_existing_index() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    server.index("other").create(None).await;
+    index.wait_task(1).await;
+
+    let (_, code) = index.rename("other").await;
+    assert_eq!(code, 202);
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "index_already_exists");
+
+    let (_, code) = index.get().await;
+    assert_eq!(code, 200);
+}
+
+#[actix_rt::test]
+async fn rename_unexisting_index() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (_, code) = index.rename("renamed").await;
+    assert_eq!(code, 202);
+
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "index_not_found");
+}
+
+#[actix_rt::test]
+async fn rename_with_invalid_uid() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.rename("in valid").await;
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "invalid_index_uid");
+}
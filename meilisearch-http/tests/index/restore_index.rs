@@ -0,0 +1,132 @@
+use std::time::Duration;
+
+use meilisearch_http::Opt;
+use serde_json::json;
+use tempfile::TempDir;
+use tokio::time::sleep;
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+/// The deleted indexes are only kept in the trash when a retention period is set.
+async fn server_with_trash(dir: &TempDir) -> Server {
+    let options = Opt {
+        index_trash_retention_sec: 86400,
+        ..default_settings(dir.path())
+    };
+    Server::new_with_options(options).await
+}
+
+#[actix_rt::test]
+async fn restore_a_deleted_index() {
+    let temp = TempDir::new().unwrap();
+    let server = server_with_trash(&temp).await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(0).await;
+    index
+        .update_settings(json!({ "searchableAttributes": ["title"] }))
+        .await;
+    index.wait_task(1).await;
+
+    index.delete().await;
+    index.wait_task(2).await;
+    assert_eq!(index.get().await.1, 404);
+    let (response, code) = server.list_indexes().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!([]));
+
+    let (response, code) = index.restore().await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["type"], "indexRestore");
+    let response = index.wait_task(3).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "id": 1, "title": "Carol" }));
+    let (response, _) = index.settings().await;
+    assert_eq!(response["searchableAttributes"], json!(["title"]));
+}
+
+#[actix_rt::test]
+async fn restore_an_index_whose_uid_is_taken() {
+    let temp = TempDir::new().unwrap();
+    let server = server_with_trash(&temp).await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+    index.delete().await;
+    index.wait_task(1).await;
+    index.create(None).await;
+    index.wait_task(2).await;
+
+    index.restore().await;
+    let response = index.wait_task(3).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_already_exists");
+}
+
+#[actix_rt::test]
+async fn restore_an_index_that_was_not_deleted() {
+    let temp = TempDir::new().unwrap();
+    let server = server_with_trash(&temp).await;
+    let index = server.index("test");
+
+    index.restore().await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_not_in_trash");
+}
+
+#[actix_rt::test]
+async fn deleted_indexes_are_not_kept_by_default() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+    index.delete().await;
+    index.wait_task(1).await;
+
+    index.restore().await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_not_in_trash");
+}
+
+#[actix_rt::test]
+async fn trashed_indexes_are_purged_after_the_retention_period() {
+    let temp = TempDir::new().unwrap();
+    let options = Opt {
+        index_trash_retention_sec: 1,
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(0).await;
+    index.delete().await;
+    index.wait_task(1).await;
+
+    sleep(Duration::from_secs(3)).await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["type"], "indexPurge", "{}", response);
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert!(
+        response["details"]["reclaimedBytes"].as_u64().unwrap() > 0,
+        "{}",
+        response
+    );
+
+    index.restore().await;
+    let response = index.wait_task(3).await;
+    assert_eq!(
+        response["error"]["code"], "index_not_in_trash",
+        "{}",
+        response
+    );
+}
@@ -49,6 +49,59 @@ async fn stats() {
     assert_eq!(response["fieldDistribution"]["age"], 1);
 }
 
+#[actix_rt::test]
+async fn stats_storage_breakdown() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["genre", "year"] }))
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.stats().await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response["storage"]["sizeOnDisk"].as_u64().unwrap() > 0);
+    assert_eq!(response["storage"]["numberOfWords"], 0);
+    assert_eq!(response["storage"]["averageDocumentSize"], 0);
+    assert_eq!(response["lastAppliedTask"]["uid"], 0);
+
+    let documents = json!([
+        { "id": 1, "title": "Carol", "genre": "drama", "year": 2015 },
+        { "id": 2, "title": "Wonder Woman", "genre": "action", "year": 2017 },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+    // a failed task isn't applied to the index.
+    index.add_documents(json!([{ "id": "@@" }]), None).await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+
+    let (response, code) = index.stats().await;
+    assert_eq!(code, 200, "{}", response);
+    let storage = &response["storage"];
+    assert!(storage["numberOfWords"].as_u64().unwrap() > 0);
+    assert!(storage["averageDocumentSize"].as_u64().unwrap() > 0);
+    for database in [
+        "facetIdF64Docids",
+        "facetIdStringDocids",
+        "fieldIdDocidFacetF64s",
+        "fieldIdDocidFacetStrings",
+    ] {
+        assert!(
+            storage["facetDatabases"][database].as_u64().unwrap() > 0,
+            "{}",
+            database
+        );
+    }
+    assert_eq!(response["lastAppliedTask"]["uid"], 1);
+    assert!(response["lastAppliedTask"]["finishedAt"].is_string());
+
+    // The storage breakdown is only computed for the stats of a single index.
+    let (response, code) = server.stats().await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response["indexes"]["test"].get("storage").is_none());
+}
+
 #[actix_rt::test]
 async fn error_get_stats_unexisting_index() {
     let server = Server::new().await;
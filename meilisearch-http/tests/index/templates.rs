@@ -0,0 +1,180 @@
+use meilisearch_http::Opt;
+use serde_json::json;
+use tempfile::TempDir;
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+async fn put_logs_template(server: &Server) {
+    let (response, code) = server
+        .service
+        .put(
+            "/index-templates/logs",
+            json!({
+                "indexPattern": "logs-*",
+                "settings": {
+                    "searchableAttributes": ["message"],
+                    "filterableAttributes": ["level"],
+                    "rankingRules": ["words", "exactness"],
+                    "typoTolerance": { "enabled": false },
+                },
+            }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+}
+
+#[actix_rt::test]
+async fn create_get_and_delete_index_template() {
+    let server = Server::new().await;
+    put_logs_template(&server).await;
+
+    let (response, code) = server.service.get("/index-templates/logs").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["name"], "logs");
+    assert_eq!(response["indexPattern"], "logs-*");
+    assert_eq!(
+        response["settings"]["filterableAttributes"],
+        json!(["level"])
+    );
+
+    let (response, code) = server.service.get("/index-templates").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+
+    let (response, code) = server.service.delete("/index-templates/logs").await;
+    assert_eq!(code, 204, "{}", response);
+
+    let (response, code) = server.service.get("/index-templates/logs").await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_template_not_found");
+
+    let (response, code) = server.service.get("/index-templates").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"], json!([]));
+}
+
+#[actix_rt::test]
+async fn template_is_applied_to_the_matching_new_indexes() {
+    let server = Server::new().await;
+    put_logs_template(&server).await;
+
+    let index = server.index("logs-2022");
+    index.create(None).await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["searchableAttributes"], json!(["message"]));
+    assert_eq!(response["filterableAttributes"], json!(["level"]));
+    assert_eq!(response["rankingRules"], json!(["words", "exactness"]));
+    assert_eq!(response["typoTolerance"]["enabled"], false);
+
+    // The indexes created by a document addition get the template too.
+    let index = server.index("logs-2023");
+    index
+        .add_documents(json!([{ "id": 1, "level": "error" }]), None)
+        .await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["filterableAttributes"], json!(["level"]));
+
+    let index = server.index("movies");
+    index.create(None).await;
+    index.wait_task(2).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["searchableAttributes"], json!(["*"]));
+    assert_eq!(response["filterableAttributes"], json!([]));
+}
+
+#[actix_rt::test]
+async fn most_specific_template_is_applied() {
+    let server = Server::new().await;
+    put_logs_template(&server).await;
+    server
+        .service
+        .put(
+            "/index-templates/app-logs",
+            json!({
+                "indexPattern": "logs-app-*",
+                "settings": { "filterableAttributes": ["service"] },
+            }),
+        )
+        .await;
+
+    let index = server.index("logs-app-1");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["filterableAttributes"], json!(["service"]));
+    assert_eq!(response["searchableAttributes"], json!(["*"]));
+}
+
+#[actix_rt::test]
+async fn template_is_not_applied_to_the_existing_indexes() {
+    let server = Server::new().await;
+    let index = server.index("logs-2022");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    put_logs_template(&server).await;
+    index
+        .update_settings(json!({ "displayedAttributes": ["message"] }))
+        .await;
+    index.wait_task(1).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["displayedAttributes"], json!(["message"]));
+    assert_eq!(response["filterableAttributes"], json!([]));
+}
+
+#[actix_rt::test]
+async fn invalid_index_template() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .put("/index-templates/logs", json!({ "indexPattern": "logs/*" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_index_template");
+
+    let (response, code) = server
+        .service
+        .put(
+            "/index-templates/logs",
+            json!({ "indexPattern": "logs-*", "settings": { "unknownSetting": true } }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "malformed_payload");
+
+    let (response, code) = server.service.delete("/index-templates/logs").await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_template_not_found");
+}
+
+#[actix_rt::test]
+async fn index_templates_are_reserved_to_the_master_key() {
+    let temp = TempDir::new().unwrap();
+    let options = Opt {
+        master_key: Some("MASTER_KEY".to_string()),
+        ..default_settings(temp.path())
+    };
+    let mut server = Server::new_with_options(options).await;
+    server.use_api_key("not-the-master-key");
+
+    let (response, code) = server.service.get("/index-templates").await;
+    assert_eq!(code, 403, "{}", response);
+
+    let (response, code) = server
+        .service
+        .put("/index-templates/logs", json!({ "indexPattern": "logs-*" }))
+        .await;
+    assert_eq!(code, 403, "{}", response);
+}
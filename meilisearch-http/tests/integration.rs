@@ -2,12 +2,21 @@ mod auth;
 mod common;
 mod dashboard;
 mod documents;
+#[cfg(feature = "graphql")]
+mod graphql;
 mod index;
+mod lockdown;
+mod migrations;
+mod openapi;
+mod rate_limit;
+mod replication;
 mod search;
 mod settings;
 mod snapshot;
 mod stats;
 mod tasks;
+mod watched_import;
+mod write_breaker;
 
 // Tests are isolated by features in different modules to allow better readability, test
 // targetability, and improved incremental compilation times.
@@ -0,0 +1,58 @@
+use meilisearch_http::routes::RouteGroup;
+use meilisearch_http::Opt;
+use serde_json::json;
+use tempfile::TempDir;
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn disabled_routes_are_refused_without_master_key() {
+    let temp = TempDir::new().unwrap();
+    let options = Opt {
+        disabled_routes: vec![
+            RouteGroup::Keys,
+            RouteGroup::Dumps,
+            RouteGroup::SettingsWrite,
+        ],
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await;
+    let index = server.index("movies");
+    let (_, code) = index.create(None).await;
+    assert_eq!(code, 202);
+    index.wait_task(0).await;
+
+    let (response, code) = server.service.get("/keys").await;
+    assert_eq!(code, 403, "{}", response);
+    assert_eq!(response["code"], "route_disabled");
+    assert_eq!(
+        response["message"],
+        "The `keys` routes are disabled on this instance."
+    );
+
+    let (response, code) = server.service.post("/dumps", json!(null)).await;
+    assert_eq!(code, 403, "{}", response);
+
+    let (response, code) = index
+        .update_settings(json!({ "searchableAttributes": ["title"] }))
+        .await;
+    assert_eq!(code, 403, "{}", response);
+    assert_eq!(response["code"], "route_disabled");
+
+    // The other routes, and the reads of the settings, are still served.
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    let (response, code) = index.add_documents(json!([{ "id": 1 }]), None).await;
+    assert_eq!(code, 202, "{}", response);
+    let (response, code) = index.search_post(json!({ "q": "" })).await;
+    assert_eq!(code, 200, "{}", response);
+}
+
+#[actix_rt::test]
+async fn no_route_is_disabled_by_default() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/keys").await;
+    assert_eq!(code, 200, "{}", response);
+}
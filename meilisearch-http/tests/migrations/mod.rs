@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use actix_web::{web, App, HttpResponse, HttpServer};
+use serde_json::{json, Value};
+
+use crate::common::{GetAllDocumentsOptions, Server};
+
+#[actix_rt::test]
+async fn create_migration_with_invalid_source() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/migrations",
+            json!({
+                "indexUid": "movies",
+                "source": { "type": "elasticsearch", "url": "not a url", "index": "movies" }
+            }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_migration_source");
+
+    let (response, code) = server
+        .service
+        .post(
+            "/migrations",
+            json!({
+                "indexUid": "movies",
+                "batchSize": 0,
+                "source": { "type": "algolia", "appId": "APP", "apiKey": "key", "index": "movies" }
+            }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_migration_source");
+}
+
+#[actix_rt::test]
+async fn create_migration_with_invalid_index_uid() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/migrations",
+            json!({
+                "indexUid": "movies and series",
+                "source": { "type": "algolia", "appId": "APP", "apiKey": "key", "index": "movies" }
+            }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_index_uid");
+}
+
+#[actix_rt::test]
+async fn get_unknown_migration() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/migrations/unknown").await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "migration_not_found");
+
+    let (response, code) = server.service.get("/migrations").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"], json!([]));
+}
+
+/// Answers like an Elasticsearch cluster holding a `movies` index with two documents.
+fn elasticsearch_mock(cfg: &mut web::ServiceConfig) {
+    cfg.route(
+        "/movies/_mapping",
+        web::get().to(|| async {
+            HttpResponse::Ok().json(json!({
+                "movies": {
+                    "mappings": {
+                        "properties": {
+                            "title": { "type": "text" },
+                            "genre": { "type": "keyword" },
+                        }
+                    }
+                }
+            }))
+        }),
+    )
+    .route(
+        "/movies/_settings",
+        web::get().to(|| async {
+            HttpResponse::Ok().json(json!({ "movies": { "settings": { "index": {} } } }))
+        }),
+    )
+    .route(
+        "/movies/_search",
+        web::post().to(|| async {
+            HttpResponse::Ok().json(json!({
+                "_scroll_id": "scroll",
+                "hits": {
+                    "total": { "value": 2, "relation": "eq" },
+                    "hits": [
+                        { "_id": "1", "_source": { "title": "Carol", "genre": "drama" } },
+                        { "_id": "2", "_source": { "title": "Alien", "genre": "sci-fi" } },
+                    ]
+                }
+            }))
+        }),
+    )
+    .route(
+        "/_search/scroll",
+        web::post().to(|| async {
+            HttpResponse::Ok().json(json!({
+                "_scroll_id": "scroll",
+                "hits": { "total": { "value": 2, "relation": "eq" }, "hits": [] }
+            }))
+        }),
+    )
+    .route(
+        "/_search/scroll",
+        web::delete().to(|| async { HttpResponse::Ok().json(json!({ "succeeded": true })) }),
+    );
+}
+
+#[actix_rt::test]
+async fn migrate_from_elasticsearch() {
+    let mock = HttpServer::new(|| App::new().configure(elasticsearch_mock))
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+    let address = mock.addrs()[0];
+    actix_rt::spawn(mock.run());
+
+    let server = Server::new().await;
+    let (response, code) = server
+        .service
+        .post(
+            "/migrations",
+            json!({
+                "indexUid": "movies",
+                "source": {
+                    "type": "elasticsearch",
+                    "url": format!("http://{}", address),
+                    "index": "movies"
+                }
+            }),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["status"], "in_progress");
+    assert_eq!(response["source"], "elasticsearch");
+
+    let url = format!("/migrations/{}", response["uid"].as_str().unwrap());
+    let mut migration = Value::Null;
+    for _ in 0..50 {
+        let (response, code) = server.service.get(&url).await;
+        assert_eq!(code, 200, "{}", response);
+        if response["status"] != "in_progress" {
+            migration = response;
+            break;
+        }
+        actix_rt::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(migration["status"], "done", "{}", migration);
+    assert_eq!(migration["documentsFetched"], 2);
+    assert_eq!(migration["totalDocuments"], 2);
+
+    let task_uids = migration["taskUids"].as_array().unwrap();
+    assert_eq!(task_uids.len(), 2);
+    let index = server.index("movies");
+    index.wait_task(task_uids[1].as_u64().unwrap()).await;
+
+    let (settings, _) = index.settings().await;
+    assert_eq!(settings["searchableAttributes"], json!(["title"]));
+    assert_eq!(settings["filterableAttributes"], json!(["genre"]));
+
+    let (documents, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(documents.as_array().unwrap().len(), 2);
+    assert_eq!(index.get().await.0["primaryKey"], "id");
+}
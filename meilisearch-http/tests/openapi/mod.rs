@@ -0,0 +1,71 @@
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn get_openapi_specification() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/openapi.json").await;
+    assert_eq!(code, 200);
+    assert!(response["openapi"].as_str().unwrap().starts_with("3.1"));
+    assert_eq!(response["info"]["title"], "Meilisearch");
+
+    let paths = &response["paths"];
+    for path in [
+        "/keys",
+        "/keys/{api_key}",
+        "/tasks/{task_id}",
+        "/indexes/{index_uid}/documents",
+        "/indexes/{index_uid}/search",
+        "/indexes/{index_uid}/settings",
+    ] {
+        assert!(paths.get(path).is_some(), "missing path {}", path);
+    }
+}
+
+#[actix_rt::test]
+async fn openapi_documents_key_routes_and_errors() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/openapi.json").await;
+    assert_eq!(code, 200);
+
+    let schemas = &response["components"]["schemas"];
+    assert!(schemas.get("ResponseError").is_some());
+    assert!(schemas.get("KeyView").is_some());
+    assert!(response["components"]["securitySchemes"]
+        .get("apiKey")
+        .is_some());
+
+    let create_key = &response["paths"]["/keys"]["post"];
+    assert_eq!(
+        create_key["responses"]["201"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/KeyView"
+    );
+    for status in ["400", "401", "403"] {
+        assert_eq!(
+            create_key["responses"][status]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/ResponseError"
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn openapi_documents_generated_setting_routes() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/openapi.json").await;
+    assert_eq!(code, 200);
+
+    let route = &response["paths"]["/indexes/{index_uid}/settings/ranking-rules"];
+    assert_eq!(route["get"]["operationId"], "get_rankingRules");
+    assert_eq!(route["post"]["operationId"], "update_rankingRules");
+    assert_eq!(route["delete"]["operationId"], "reset_rankingRules");
+}
+
+#[actix_rt::test]
+async fn openapi_is_public() {
+    let server = Server::new_auth().await;
+
+    let (_response, code) = server.service.get("/openapi.json").await;
+    assert_eq!(code, 200);
+}
@@ -0,0 +1,82 @@
+use actix_web::http::header::HeaderMap;
+use actix_web::test;
+use meilisearch_http::{analytics, create_app, Opt};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+async fn search(server: &Server, ip: &str) -> (Value, u16, HeaderMap) {
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/indexes/movies/search")
+        .peer_addr(format!("{}:4242", ip).parse().unwrap())
+        .set_json(&json!({ "q": "carol" }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status().as_u16();
+    let headers = res.headers().clone();
+
+    let body = test::read_body(res).await;
+    let response = serde_json::from_slice(&body).unwrap_or_default();
+    (response, status_code, headers)
+}
+
+#[actix_rt::test]
+async fn searches_exceeding_the_rate_limit_are_refused() {
+    let temp = TempDir::new().unwrap();
+    let options = Opt {
+        rate_limit_search: Some(2),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await;
+    server.index("movies").create(None).await;
+    server.index("movies").wait_task(0).await;
+
+    let (response, code, headers) = search(&server, "10.0.0.1").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(headers["ratelimit-limit"], "2");
+    assert_eq!(headers["ratelimit-remaining"], "1");
+    assert_eq!(headers["ratelimit-reset"], "60");
+
+    let (_, code, headers) = search(&server, "10.0.0.1").await;
+    assert_eq!(code, 200);
+    assert_eq!(headers["ratelimit-remaining"], "0");
+
+    let (response, code, headers) = search(&server, "10.0.0.1").await;
+    assert_eq!(code, 429, "{}", response);
+    assert_eq!(response["code"], "too_many_requests");
+    assert_eq!(response["type"], "invalid_request");
+    assert_eq!(headers["retry-after"], "60");
+    assert_eq!(headers["ratelimit-remaining"], "0");
+
+    // another address has its own budget.
+    let (_, code, _) = search(&server, "10.0.0.2").await;
+    assert_eq!(code, 200);
+
+    // the other routes aren't limited by the search budget.
+    let (_, code) = server.service.get("/indexes/movies").await;
+    assert_eq!(code, 200);
+}
+
+#[actix_rt::test]
+async fn no_rate_limit_by_default() {
+    let server = Server::new().await;
+    server.index("movies").create(None).await;
+    server.index("movies").wait_task(0).await;
+
+    for _ in 0..5 {
+        let (response, code, headers) = search(&server, "10.0.0.1").await;
+        assert_eq!(code, 200, "{}", response);
+        assert!(headers.get("ratelimit-limit").is_none());
+    }
+}
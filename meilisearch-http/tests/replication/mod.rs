@@ -0,0 +1,114 @@
+use meilisearch_http::{setup_meilisearch, Opt};
+use serde_json::json;
+use tempfile::TempDir;
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+async fn primary(temp: &TempDir, replication_log_size: usize) -> Server {
+    let options = Opt {
+        master_key: Some("MASTER_KEY".to_string()),
+        replication_log_size,
+        ..default_settings(temp.path())
+    };
+    let mut server = Server::new_with_options(options).await;
+    server.use_api_key("MASTER_KEY");
+
+    let index = server.index("movies");
+    let (response, code) = index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(0).await;
+    server
+}
+
+#[actix_rt::test]
+async fn primary_serves_its_tasks_and_their_documents() {
+    let temp = TempDir::new().unwrap();
+    let server = primary(&temp, 10).await;
+
+    let (response, code) = server.service.get("/replication/tasks?from=0").await;
+    assert_eq!(code, 200, "{}", response);
+    let tasks = response.as_array().unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0]["uid"], 0);
+    assert_eq!(tasks[0]["indexUid"], "movies");
+
+    let (response, code) = server.service.get("/replication/tasks?from=1").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!([]));
+
+    // The documents of the processed task are kept for the replicas.
+    let (response, code) = server.service.get("/replication/tasks/0/content").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "id": 1, "title": "Carol" }));
+}
+
+#[actix_rt::test]
+async fn documents_are_not_kept_without_replication_log() {
+    let temp = TempDir::new().unwrap();
+    let server = primary(&temp, 0).await;
+
+    let (response, code) = server.service.get("/replication/tasks/0/content").await;
+    assert_eq!(code, 410, "{}", response);
+    assert_eq!(response["code"], "replication_content_unavailable");
+}
+
+#[actix_rt::test]
+async fn replication_routes_are_reserved_to_the_master_key() {
+    let temp = TempDir::new().unwrap();
+    let mut server = primary(&temp, 0).await;
+    server.use_api_key("not-the-master-key");
+
+    for route in ["/replication/tasks", "/replication/keys"] {
+        let (response, code) = server.service.get(route).await;
+        assert_eq!(code, 403, "{}", response);
+    }
+}
+
+#[actix_rt::test]
+async fn replicas_require_a_master_key() {
+    let temp = TempDir::new().unwrap();
+    let options = Opt {
+        replicate_from: Some("http://127.0.0.1:7700".to_string()),
+        ..default_settings(temp.path())
+    };
+
+    assert!(setup_meilisearch(&options).is_err());
+}
+
+#[actix_rt::test]
+async fn replicas_refuse_writes() {
+    let temp = TempDir::new().unwrap();
+    let options = Opt {
+        master_key: Some("MASTER_KEY".to_string()),
+        // The replica isn't started, its primary is never contacted.
+        replicate_from: Some("http://127.0.0.1:7700".to_string()),
+        ..default_settings(temp.path())
+    };
+    let mut server = Server::new_with_options(options).await;
+    server.use_api_key("MASTER_KEY");
+
+    let index = server.index("movies");
+    let (response, code) = index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    assert_eq!(code, 403, "{}", response);
+    assert_eq!(response["code"], "read_only_replica");
+
+    let (response, code) = server
+        .service
+        .post(
+            "/keys",
+            json!({ "actions": ["search"], "indexes": ["*"], "expiresAt": null }),
+        )
+        .await;
+    assert_eq!(code, 403, "{}", response);
+    assert_eq!(response["code"], "read_only_replica");
+
+    // The searches are still served.
+    let (response, code) = index.search_post(json!({ "q": "carol" })).await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_not_found");
+}
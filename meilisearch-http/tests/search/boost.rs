@@ -0,0 +1,101 @@
+use serde_json::{json, Value};
+
+use crate::common::index::Index;
+use crate::common::Server;
+
+async fn index_with_documents(server: &Server) -> Index<'_> {
+    let index = server.index("test");
+    index
+        .update_settings(json!({
+            "searchableAttributes": ["title", "description"],
+            "filterableAttributes": ["color"],
+        }))
+        .await;
+    index.wait_task(0).await;
+
+    let documents = json!([
+        { "id": 1, "title": "red shoes", "description": "comfortable", "color": "red" },
+        { "id": 2, "title": "blue shoes", "description": "comfortable", "color": "blue" },
+        { "id": 3, "title": "shoes for kids", "description": "red and comfortable", "color": "red" },
+    ]);
+    index.add_documents(documents, None).await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    index
+}
+
+fn ids(response: &Value) -> Vec<u64> {
+    response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn boost_filter_moves_the_matching_documents_up() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server).await;
+
+    index
+        .search(
+            json!({
+                "q": "shoes",
+                "boostFilter": "color = blue",
+                "boostFilterWeight": 10,
+                "showRankingScoreDetails": true,
+            }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response)[0], 2, "{}", response);
+                assert_eq!(
+                    response["hits"][0]["_rankingScoreDetails"]["boost"]["factor"],
+                    json!(10.0)
+                );
+                assert_eq!(response["nbHits"], 3);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn boosted_attribute_moves_the_matching_documents_up() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server).await;
+
+    let (response, code) = index
+        .search_post(json!({ "q": "red", "boost": { "description": 10 } }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), vec![3, 1], "{}", response);
+}
+
+#[actix_rt::test]
+async fn invalid_boost() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server).await;
+
+    index
+        .search(
+            json!({ "q": "shoes", "boost": { "color": 2 } }),
+            |response, code| {
+                assert_eq!(code, 400, "{}", response);
+                assert_eq!(response["code"], "invalid_search_boost");
+            },
+        )
+        .await;
+
+    let (response, code) = index
+        .search_post(json!({ "q": "shoes", "boost": { "title": -1 } }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_search_boost");
+
+    let (response, code) = index
+        .search_get(json!({ "q": "shoes", "boost": "title:high" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_search_boost");
+}
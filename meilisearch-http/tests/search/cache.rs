@@ -0,0 +1,87 @@
+use meilisearch_http::Opt;
+use serde_json::json;
+use tempfile::TempDir;
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+use super::DOCUMENTS;
+
+async fn server_with_cache(temp: &TempDir) -> Server {
+    let options = Opt {
+        search_cache_size: 10,
+        ..default_settings(temp.path())
+    };
+    Server::new_with_options(options).await
+}
+
+#[actix_rt::test]
+async fn identical_searches_are_cached() {
+    let temp = TempDir::new().unwrap();
+    let server = server_with_cache(&temp).await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let (first, code) = index.search_post(json!({ "q": "glass" })).await;
+    assert_eq!(code, 200, "{}", first);
+    let (second, code) = index.search_post(json!({ "q": "glass" })).await;
+    assert_eq!(code, 200, "{}", second);
+    assert_eq!(first["hits"], second["hits"]);
+
+    let (response, code) = server.search_cache_stats().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["capacity"], 10);
+    assert_eq!(response["entries"], 1);
+    assert_eq!(response["hits"], 1);
+    assert_eq!(response["misses"], 1);
+}
+
+#[actix_rt::test]
+async fn committed_tasks_invalidate_the_cache() {
+    let temp = TempDir::new().unwrap();
+    let server = server_with_cache(&temp).await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let (response, _) = index.search_post(json!({ "q": "glass" })).await;
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+
+    index
+        .add_documents(json!([{ "id": "1", "title": "Glass Onion" }]), None)
+        .await;
+    index.wait_task(1).await;
+
+    let (response, _) = index.search_post(json!({ "q": "glass" })).await;
+    assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+
+    let (response, _) = server.search_cache_stats().await;
+    assert_eq!(response["hits"], 0);
+    assert_eq!(response["misses"], 2);
+    assert!(response["invalidations"].as_u64().unwrap() >= 1);
+}
+
+#[actix_rt::test]
+async fn search_cache_disabled_by_default() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index.search_post(json!({ "q": "glass" })).await;
+    index.search_post(json!({ "q": "glass" })).await;
+
+    let (response, code) = server.search_cache_stats().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({ "capacity": 0, "entries": 0, "hits": 0, "misses": 0, "invalidations": 0 })
+    );
+}
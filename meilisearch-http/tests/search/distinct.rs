@@ -0,0 +1,79 @@
+use crate::common::Server;
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+pub(self) static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "name": "red shirt", "productId": 1, "color": "red" },
+        { "id": 2, "name": "blue shirt", "productId": 1, "color": "blue" },
+        { "id": 3, "name": "red pants", "productId": 2, "color": "red" },
+        { "id": 4, "name": "blue pants", "productId": 2, "color": "blue" },
+        { "id": 5, "name": "red hat", "productId": 3, "color": "red" },
+    ])
+});
+
+#[actix_rt::test]
+async fn search_with_distinct() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["productId", "color"]}))
+        .await;
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(json!({"distinct": "productId"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 3);
+            assert_eq!(response["nbHits"], 3);
+        })
+        .await;
+
+    index
+        .search(json!({"distinct": "color"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+        })
+        .await;
+
+    index
+        .search(
+            json!({"distinct": "productId", "offset": 1, "limit": 1}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+                assert_eq!(response["nbHits"], 3);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_non_filterable_distinct() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["color"]}))
+        .await;
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let expected_response = json!({
+        "message": "Attribute `productId` is not filterable and thus, cannot be used as distinct attribute. Available filterable attributes are: `color`.",
+        "code": "invalid_search_distinct",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#invalid_search_distinct"
+    });
+
+    index
+        .search(json!({"distinct": "productId"}), |response, code| {
+            assert_eq!(code, 400, "{}", response);
+            assert_eq!(response, expected_response);
+        })
+        .await;
+}
@@ -0,0 +1,185 @@
+use crate::common::Server;
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+pub(self) static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "color": "red", "tags": ["sale"], "size": 1 },
+        { "id": 2, "color": "blue", "tags": [], "size": 2 },
+        { "id": 3, "color": null, "size": 3 },
+        { "id": 4, "tags": "", "size": 4 },
+    ])
+});
+
+async fn setup(server: &Server) {
+    let index = server.index("test");
+    index
+        .update_settings(json!({"filterableAttributes": ["color", "tags", "size"]}))
+        .await;
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+}
+
+fn ids(response: &Value) -> Vec<u64> {
+    let mut ids: Vec<_> = response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+#[actix_rt::test]
+async fn filter_in() {
+    let server = Server::new().await;
+    setup(&server).await;
+    let index = server.index("test");
+
+    index
+        .search(
+            json!({"filter": "color IN [red, blue]"}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), [1, 2]);
+            },
+        )
+        .await;
+
+    index
+        .search(json!({"filter": "color NOT IN [red]"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(ids(&response), [2, 3, 4]);
+        })
+        .await;
+
+    index
+        .search(
+            json!({"filter": ["size > 1", ["color IN [red, blue]", "size = 4"]]}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), [2, 4]);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn filter_existence() {
+    let server = Server::new().await;
+    setup(&server).await;
+    let index = server.index("test");
+
+    for (filter, expected) in [
+        ("color EXISTS", vec![1, 2, 3]),
+        ("color NOT EXISTS", vec![4]),
+        ("color IS NULL", vec![3]),
+        ("color IS NOT NULL", vec![1, 2, 4]),
+        ("tags IS EMPTY", vec![2, 4]),
+        ("NOT tags IS EMPTY", vec![1, 3]),
+        ("tags IS EMPTY AND size > 2", vec![4]),
+        ("color IS NULL OR size = 4", vec![3, 4]),
+        ("NOT (color EXISTS AND tags IS EMPTY)", vec![1, 3, 4]),
+        ("size < 3 AND (color NOT EXISTS OR tags IS EMPTY)", vec![2]),
+    ] {
+        index
+            .search(json!({ "filter": filter }), |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), expected, "{}", filter);
+                assert_eq!(response["nbHits"], expected.len(), "{}", filter);
+            })
+            .await;
+    }
+
+    index
+        .search(
+            json!({"filter": ["color EXISTS", "size >= 2"]}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), [2, 3]);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({"filter": [["color IS NULL", "size = 1"], "size < 4"]}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), [1, 3]);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn filter_existence_after_updates() {
+    let server = Server::new().await;
+    setup(&server).await;
+    let index = server.index("test");
+
+    index
+        .update_documents(json!([{ "id": 4, "color": "green" }]), None)
+        .await;
+    index.wait_task(2).await;
+    index.delete_document(1).await;
+    index.wait_task(3).await;
+
+    index
+        .search(json!({"filter": "color EXISTS"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(ids(&response), [2, 3, 4]);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn filter_operators_errors() {
+    let server = Server::new().await;
+    setup(&server).await;
+    let index = server.index("test");
+
+    let expected_response = json!({
+        "message": "Invalid syntax for the filter parameter: `expected a condition of the form field EXISTS, field IS NULL or field IS EMPTY, found: color EXISTS size`.",
+        "code": "invalid_filter",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#invalid_filter"
+    });
+    index
+        .search(
+            json!({"filter": "color EXISTS OR color EXISTS size"}),
+            |response, code| {
+                assert_eq!(response, expected_response);
+                assert_eq!(code, 400);
+            },
+        )
+        .await;
+
+    let expected_response = json!({
+        "message": "Invalid syntax for the filter parameter: `expected a non-empty list of values between brackets after IN, found: color IN []`.",
+        "code": "invalid_filter",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#invalid_filter"
+    });
+    index
+        .search(json!({"filter": "color IN []"}), |response, code| {
+            assert_eq!(response, expected_response);
+            assert_eq!(code, 400);
+        })
+        .await;
+
+    let expected_response = json!({
+        "message": "Attribute `id` is not filterable. Available filterable attributes are: `color, size, tags`.",
+        "code": "invalid_filter",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#invalid_filter"
+    });
+    index
+        .search(json!({"filter": "id EXISTS"}), |response, code| {
+            assert_eq!(response, expected_response);
+            assert_eq!(code, 400);
+        })
+        .await;
+}
@@ -374,3 +374,70 @@ async fn displayedattr_2_smol() {
         })
     );
 }
+
+#[actix_rt::test]
+async fn formatting_defaults_of_the_index() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({
+            "displayedAttributes": ["id", "cattos"],
+            "formatting": { "highlightPreTag": "<mark>", "highlightPostTag": "</mark>" },
+        }))
+        .await;
+
+    let documents = NESTED_DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .search_post(json!({ "q": "pesti", "attributesToHighlight": ["cattos"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"][0]["_formatted"]["cattos"],
+        "<mark>pesti</mark>"
+    );
+
+    // the parameters of the search override the ones of the index.
+    let (response, code) = index
+        .search_post(json!({
+            "q": "pesti",
+            "attributesToHighlight": ["cattos"],
+            "highlightPreTag": "<b>",
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"][0]["_formatted"]["cattos"],
+        "<b>pesti</mark>"
+    );
+
+    // the parameters are updated one by one.
+    index
+        .update_settings(json!({ "formatting": { "highlightPostTag": null, "cropMarker": "[…]" } }))
+        .await;
+    index.wait_task(2).await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["formatting"],
+        json!({
+            "highlightPreTag": "<mark>",
+            "highlightPostTag": "</em>",
+            "cropLength": 10,
+            "cropMarker": "[…]",
+        })
+    );
+
+    let (response, code) = index
+        .search_post(json!({ "q": "pesti", "attributesToHighlight": ["cattos"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"][0]["_formatted"]["cattos"],
+        "<mark>pesti</em>"
+    );
+}
@@ -0,0 +1,110 @@
+use actix_web::test::TestRequest;
+use serde_json::json;
+
+use crate::common::Server;
+
+/// Returns a request opening a WebSocket session on `uri`.
+fn handshake(uri: &str) -> TestRequest {
+    TestRequest::get()
+        .uri(uri)
+        .insert_header(("Upgrade", "websocket"))
+        .insert_header(("Connection", "Upgrade"))
+        .insert_header(("Sec-WebSocket-Version", "13"))
+        .insert_header(("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ=="))
+}
+
+#[actix_rt::test]
+async fn open_live_search_session() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (_, code, headers) = server
+        .service
+        .request(handshake("/indexes/test/search/live"))
+        .await;
+    assert_eq!(code, 101);
+    assert_eq!(headers.get("Upgrade").unwrap(), "websocket");
+}
+
+#[actix_rt::test]
+async fn error_live_search_without_handshake() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server.service.get("/indexes/test/search/live").await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn error_live_search_unexisting_index() {
+    let server = Server::new().await;
+
+    let (response, code, _) = server
+        .service
+        .request(handshake("/indexes/test/search/live"))
+        .await;
+    assert_eq!(code, 404);
+    assert_eq!(response["code"], "index_not_found");
+}
+
+#[actix_rt::test]
+async fn error_live_search_invalid_debounce() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code, _) = server
+        .service
+        .request(handshake("/indexes/test/search/live?debounceMs=5000"))
+        .await;
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn live_search_session_key() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, _) = server
+        .add_api_key(json!({ "indexes": ["test"], "actions": ["search"], "expiresAt": null }))
+        .await;
+    let search_key = response["key"].as_str().unwrap().to_string();
+    let (response, _) = server
+        .add_api_key(
+            json!({ "indexes": ["test"], "actions": ["documents.get"], "expiresAt": null }),
+        )
+        .await;
+    let documents_key = response["key"].as_str().unwrap().to_string();
+
+    let (response, code, _) = server
+        .service
+        .request(handshake("/indexes/test/search/live"))
+        .await;
+    assert_eq!(code, 401);
+    assert_eq!(response["code"], "missing_authorization_header");
+
+    // the browsers can't send headers on WebSockets, the key is accepted as a parameter.
+    let uri = format!("/indexes/test/search/live?apiKey={}", search_key);
+    let (_, code, _) = server.service.request(handshake(&uri)).await;
+    assert_eq!(code, 101);
+
+    let uri = format!("/indexes/test/search/live?apiKey={}", documents_key);
+    let (response, code, _) = server.service.request(handshake(&uri)).await;
+    assert_eq!(code, 403);
+    assert_eq!(response["code"], "invalid_api_key");
+
+    let req = handshake("/indexes/test/search/live")
+        .insert_header(("Authorization", format!("Bearer {}", search_key)));
+    let (_, code, _) = server.service.request(req).await;
+    assert_eq!(code, 101);
+}
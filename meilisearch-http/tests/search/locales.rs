@@ -0,0 +1,97 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn search_with_locales() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "the quick brown fox" },
+                { "id": 2, "title": "le renard brun rapide" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .search_post(json!({ "q": "fox", "locales": ["eng"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+    assert_eq!(response["hits"][0]["id"], 1);
+
+    let (response, code) = index
+        .search_get(json!({ "q": "renard", "locales": "eng,fra" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][0]["id"], 2);
+}
+
+#[actix_rt::test]
+async fn search_with_invalid_locale() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .search_post(json!({ "q": "fox", "locales": ["english"] }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_search_locales");
+}
+
+#[actix_rt::test]
+async fn search_transliterated_german_words() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({
+            "localizedAttributes": [{ "attributePatterns": ["name"], "locales": ["deu"] }]
+        }))
+        .await;
+    index.wait_task(0).await;
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "name": "Herr Müller" },
+                { "id": 2, "name": "Frau Schmidt" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.search_post(json!({ "q": "mueller" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+    assert_eq!(response["hits"][0]["name"], "Herr Müller");
+}
+
+#[actix_rt::test]
+async fn localized_attributes_reindex_the_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "title": "東京タワー" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    index
+        .update_settings(json!({
+            "localizedAttributes": [{ "attributePatterns": ["title"], "locales": ["jpn"] }]
+        }))
+        .await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index
+        .search_post(json!({ "q": "タワー", "locales": ["jpn"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+    assert_eq!(response["hits"][0]["title"], "東京タワー");
+}
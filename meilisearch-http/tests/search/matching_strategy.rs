@@ -0,0 +1,119 @@
+use crate::common::Server;
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+pub(self) static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "title": "the quick brown fox" },
+        { "id": 2, "title": "the quick rabbit" },
+        { "id": 3, "title": "the lazy dog" },
+        { "id": 4, "title": "the brown bear" },
+    ])
+});
+
+async fn setup(server: &Server) {
+    let index = server.index("test");
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+}
+
+#[actix_rt::test]
+async fn matching_strategy_all() {
+    let server = Server::new().await;
+    setup(&server).await;
+    let index = server.index("test");
+
+    index
+        .search(
+            json!({"q": "quick fox", "matchingStrategy": "all"}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+                assert_eq!(response["hits"][0]["id"], 1);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn matching_strategy_last() {
+    let server = Server::new().await;
+    setup(&server).await;
+    let index = server.index("test");
+
+    index
+        .search(
+            json!({"q": "quick fox", "matchingStrategy": "last"}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                // `fox` is dropped, the documents matching `quick` are returned.
+                assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn matching_strategy_frequency() {
+    let server = Server::new().await;
+    setup(&server).await;
+    let index = server.index("test");
+
+    index
+        .search(
+            json!({"q": "the dog", "matchingStrategy": "frequency"}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                // `the` is the most frequent word, it is dropped before `dog`.
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+                assert_eq!(response["hits"][0]["id"], 3);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn matching_strategy_frequency_keeps_the_query_order() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    let documents = json!([
+        { "id": 1, "title": "brown fox" },
+        { "id": 2, "title": "fox brown" },
+        { "id": 3, "title": "brown bear" },
+        { "id": 4, "title": "brown cat" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(
+            json!({"q": "brown fox", "matchingStrategy": "frequency"}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                // `brown` is the most frequent word, but the proximity is still computed in the
+                // order of the query.
+                let ids: Vec<_> = response["hits"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|hit| hit["id"].clone())
+                    .collect();
+                assert_eq!(ids, [json!(1), json!(2)]);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn invalid_matching_strategy() {
+    let server = Server::new().await;
+    setup(&server).await;
+    let index = server.index("test");
+
+    let (response, code) = index
+        .search_post(json!({"q": "the dog", "matchingStrategy": "first"}))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
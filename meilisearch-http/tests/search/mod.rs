@@ -1,8 +1,21 @@
 // This modules contains all the test concerning search. Each particular feture of the search
 // should be tested in its own module to isolate tests and keep the tests readable.
 
+mod boost;
+mod cache;
+mod distinct;
 mod errors;
+mod filter_operators;
 mod formatted;
+mod live;
+mod locales;
+mod matching_strategy;
+mod negation;
+mod pagination;
+mod rulesets;
+mod slow_queries;
+mod suggest;
+mod timeout;
 
 use crate::common::Server;
 use once_cell::sync::Lazy;
@@ -606,3 +619,121 @@ async fn search_is_hard_limited() {
         )
         .await;
 }
+
+#[actix_rt::test]
+async fn search_returns_queue_wait_time() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"q": "glass"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response["queueWaitMs"].is_u64());
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_ranking_score() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(
+            json!({"q": "captain marvel", "showRankingScore": true}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                let hit = &response["hits"][0];
+                assert_eq!(hit["title"], "Captain Marvel");
+                assert_eq!(hit["_rankingScore"], 1.0);
+                assert!(hit.get("_rankingScoreDetails").is_none());
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({"q": "captain marvel", "showRankingScoreDetails": true}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                let hit = &response["hits"][0];
+                assert!(hit.get("_rankingScore").is_none());
+                let details = &hit["_rankingScoreDetails"];
+                assert_eq!(details["words"]["order"], 0);
+                assert_eq!(details["words"]["matchingWords"], 2);
+                assert_eq!(details["words"]["maxMatchingWords"], 2);
+                assert_eq!(details["typo"]["typoCount"], 0);
+            },
+        )
+        .await;
+
+    // without the parameters, no score is returned
+    index
+        .search(json!({"q": "captain marvel"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response["hits"][0].get("_rankingScore").is_none());
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_facet_distribution_with_faceting_settings() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["genre", "color"],
+            "faceting": {
+                "maxValuesPerFacet": 2,
+                "sortFacetValuesBy": { "*": "count", "color": "alpha" }
+            }
+        }))
+        .await;
+
+    let documents = json!([
+        { "id": 1, "genre": "action", "color": "yellow" },
+        { "id": 2, "genre": "drama", "color": "red" },
+        { "id": 3, "genre": "drama", "color": "blue" },
+        { "id": 4, "genre": "horror", "color": "blue" },
+        { "id": 5, "genre": "horror", "color": "green" },
+        { "id": 6, "genre": "horror", "color": "green" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(
+            json!({ "facetsDistribution": ["genre", "color"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                let dist = response["facetsDistribution"].as_object().unwrap();
+
+                let genres: Vec<_> = dist["genre"].as_object().unwrap().keys().collect();
+                assert_eq!(genres, ["horror", "drama"]);
+                assert_eq!(dist["genre"]["horror"], 3);
+
+                let colors: Vec<_> = dist["color"].as_object().unwrap().keys().collect();
+                assert_eq!(colors, ["blue", "green"]);
+            },
+        )
+        .await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["faceting"],
+        json!({
+            "maxValuesPerFacet": 2,
+            "sortFacetValuesBy": { "*": "count", "color": "alpha" }
+        })
+    );
+}
@@ -0,0 +1,108 @@
+use crate::common::Server;
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+pub(self) static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "title": "blue cheese pie" },
+        { "id": 2, "title": "apple pie" },
+        { "id": 3, "title": "cheese and blue berries pie" },
+        { "id": 4, "title": "t-shirt" },
+    ])
+});
+
+async fn setup(server: &Server) {
+    let index = server.index("test");
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+}
+
+fn ids(response: &Value) -> Vec<u64> {
+    let mut ids: Vec<_> = response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+#[actix_rt::test]
+async fn negated_word() {
+    let server = Server::new().await;
+    setup(&server).await;
+    let index = server.index("test");
+
+    index
+        .search(json!({"q": "pie -cheese"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(ids(&response), [2]);
+            assert_eq!(response["nbHits"], 1);
+        })
+        .await;
+
+    // a query made of negated terms only excludes documents from all the documents.
+    index
+        .search(json!({"q": "-pie"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(ids(&response), [4]);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn negated_phrase() {
+    let server = Server::new().await;
+    setup(&server).await;
+    let index = server.index("test");
+
+    index
+        .search(json!({"q": "pie -\"blue cheese\""}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(ids(&response), [2, 3]);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn hyphenated_words_are_not_negated() {
+    let server = Server::new().await;
+    setup(&server).await;
+    let index = server.index("test");
+
+    index
+        .search(json!({"q": "t-shirt"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(ids(&response), [4]);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn negated_documents_ranked_first() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({"sortableAttributes": ["rank"]}))
+        .await;
+    let mut documents: Vec<_> = (0..1100)
+        .map(|id| json!({ "id": id, "title": "cheese pie", "rank": id }))
+        .collect();
+    documents.push(json!({ "id": 2000, "title": "apple pie", "rank": 2000 }));
+    index.add_documents(json!(documents), None).await;
+    index.wait_task(1).await;
+
+    // The excluded documents fill more than the hits the engine returns at once.
+    index
+        .search(
+            json!({"q": "pie -cheese", "sort": ["rank:asc"]}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), [2000]);
+                assert_eq!(response["nbHits"], 1);
+            },
+        )
+        .await;
+}
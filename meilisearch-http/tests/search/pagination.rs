@@ -0,0 +1,114 @@
+use crate::common::Server;
+use serde_json::json;
+
+use super::DOCUMENTS;
+
+#[actix_rt::test]
+async fn search_with_page_and_hits_per_page() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"page": 2, "hitsPerPage": 2}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+            assert_eq!(response["page"], 2);
+            assert_eq!(response["hitsPerPage"], 2);
+            assert_eq!(response["offset"], 2);
+            assert_eq!(response["limit"], 2);
+            assert_eq!(response["totalHits"], 5);
+            assert_eq!(response["totalPages"], 3);
+        })
+        .await;
+
+    index
+        .search(json!({"page": 3, "hitsPerPage": 2}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_page_out_of_range() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"page": 0}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([]));
+            assert_eq!(response["totalHits"], 5);
+            assert_eq!(response["totalPages"], 1);
+        })
+        .await;
+
+    index
+        .search(json!({"page": 10, "hitsPerPage": 2}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([]));
+            assert_eq!(response["totalPages"], 3);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn pagination_is_hard_limited() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents: Vec<_> = (0..1200)
+        .map(|i| json!({ "id": i, "content": "number" }))
+        .collect();
+    index.add_documents(documents.into(), None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"page": 6, "hitsPerPage": 200}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            // pages beyond the hard limit can't be reached.
+            assert_eq!(response["hits"], json!([]));
+            assert_eq!(response["nbHits"], 1200);
+            assert_eq!(response["totalHits"], 1000);
+            assert_eq!(response["totalPages"], 5);
+            assert_eq!(response["offset"], 1000);
+            assert_eq!(response["limit"], 0);
+        })
+        .await;
+
+    index
+        .search(json!({"offset": 800, "limit": 400}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            // the limit is clamped to the hard limit.
+            assert_eq!(response["hits"].as_array().unwrap().len(), 200);
+            assert_eq!(response["offset"], 800);
+            assert_eq!(response["limit"], 200);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn offset_and_limit_do_not_return_pagination() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"offset": 1, "limit": 2}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response.get("totalHits").is_none());
+            assert!(response.get("totalPages").is_none());
+        })
+        .await;
+}
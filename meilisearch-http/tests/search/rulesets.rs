@@ -0,0 +1,174 @@
+use serde_json::{json, Value};
+
+use crate::common::index::Index;
+use crate::common::Server;
+
+async fn index_with_rulesets<'a>(server: &'a Server, rulesets: Value) -> Index<'a> {
+    let index = server.index("test");
+    index
+        .update_settings(json!({
+            "sortableAttributes": ["price"],
+            "filterableAttributes": ["color"],
+            "rulesets": rulesets,
+        }))
+        .await;
+    index.wait_task(0).await;
+
+    let documents = json!([
+        { "id": 1, "title": "red shoes", "price": 30, "color": "red" },
+        { "id": 2, "title": "blue shoes", "price": 10, "color": "blue" },
+        { "id": 3, "title": "red shoes for kids", "price": 20, "color": "red" },
+    ]);
+    index.add_documents(documents, None).await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    index
+}
+
+fn ids(response: &Value) -> Vec<u64> {
+    response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn search_with_ruleset() {
+    let server = Server::new().await;
+    let index = index_with_rulesets(
+        &server,
+        json!({
+            "cheapest": { "sort": ["price:asc"] },
+            "by-color": { "distinct": "color" },
+        }),
+    )
+    .await;
+
+    index
+        .search(
+            json!({ "q": "shoes", "ruleset": "cheapest" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["ruleset"], "cheapest");
+                assert_eq!(ids(&response), vec![2, 3, 1]);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({ "q": "shoes", "ruleset": "by-color" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["ruleset"], "by-color");
+                assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+            },
+        )
+        .await;
+
+    // The rulesets without traffic only serve the searches asking for them.
+    index
+        .search(json!({ "q": "shoes" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response.get("ruleset").is_none(), "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 3);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn ruleset_serving_all_the_traffic() {
+    let server = Server::new().await;
+    let index = index_with_rulesets(
+        &server,
+        json!({ "cheapest": { "traffic": 100, "sort": ["price:asc"] } }),
+    )
+    .await;
+
+    for _ in 0..5 {
+        index
+            .search(json!({ "q": "shoes" }), |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["ruleset"], "cheapest");
+                assert_eq!(ids(&response), vec![2, 3, 1]);
+            })
+            .await;
+    }
+}
+
+#[actix_rt::test]
+async fn ruleset_fills_the_omitted_query_parameters() {
+    let server = Server::new().await;
+    let index = index_with_rulesets(
+        &server,
+        json!({ "cheapest": { "sort": ["price:asc"], "matchingStrategy": "all" } }),
+    )
+    .await;
+
+    index
+        .search(
+            json!({ "q": "red shoes kids", "ruleset": "cheapest" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![3]);
+            },
+        )
+        .await;
+
+    // The explicit parameters of the query are kept.
+    index
+        .search(
+            json!({ "q": "shoes", "sort": ["price:desc"], "ruleset": "cheapest" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![1, 3, 2]);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn error_search_unexisting_ruleset() {
+    let server = Server::new().await;
+    let index = index_with_rulesets(&server, json!({})).await;
+
+    index
+        .search(
+            json!({ "q": "shoes", "ruleset": "cheapest" }),
+            |response, code| {
+                assert_eq!(code, 400, "{}", response);
+                assert_eq!(response["code"], "invalid_search_ruleset");
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn error_invalid_rulesets() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({
+            "rulesets": { "a": { "traffic": 60 }, "b": { "traffic": 50 } },
+        }))
+        .await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "invalid_rulesets");
+
+    index
+        .update_settings(json!({ "rulesets": { "a/b": { "traffic": 10 } } }))
+        .await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "invalid_rulesets");
+
+    let (response, code) = index
+        .update_settings(json!({ "rulesets": { "a": { "rankingRules": ["words"] } } }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+}
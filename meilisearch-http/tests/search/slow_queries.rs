@@ -0,0 +1,53 @@
+use meilisearch_http::Opt;
+use serde_json::json;
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+use super::DOCUMENTS;
+
+#[actix_rt::test]
+async fn slow_queries_are_recorded() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt {
+        slow_query_threshold_ms: Some(0),
+        slow_query_log_size: 1,
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index.search_post(json!({ "q": "glass" })).await;
+    index.search_post(json!({ "q": "captain" })).await;
+
+    let (response, code) = server.slow_queries().await;
+    assert_eq!(code, 200, "{}", response);
+
+    let results = response["results"].as_array().unwrap();
+    // only the most recent query is kept
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["indexUid"], "test");
+    assert_eq!(results[0]["query"]["q"], "captain");
+    assert!(results[0]["processingTimeMs"].is_number());
+    assert!(results[0]["timings"]["rankingMs"].is_number());
+}
+
+#[actix_rt::test]
+async fn slow_query_log_disabled_by_default() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index.search_post(json!({ "q": "glass" })).await;
+
+    let (response, code) = server.slow_queries().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"], json!([]));
+}
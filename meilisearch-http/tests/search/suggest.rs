@@ -0,0 +1,93 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+async fn server_with_documents() -> Server {
+    let server = Server::new().await;
+    let index = server.index("test");
+    let documents = json!([
+        { "id": 1, "title": "harry potter" },
+        { "id": 2, "title": "harry potter and the prisoner" },
+        { "id": 3, "title": "hardcover edition" },
+        { "id": 4, "title": "hare and tortoise" },
+        { "id": 5, "title": "hare in the wild" },
+        { "id": 6, "title": "harp music" },
+    ]);
+    index.add_documents(documents, None).await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    server
+}
+
+#[actix_rt::test]
+async fn suggest_completes_the_last_word() {
+    let server = server_with_documents().await;
+
+    let (response, code) = server.service.get("/indexes/test/suggest?q=har").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["query"], "har");
+    assert_eq!(
+        response["suggestions"],
+        json!([
+            { "suggestion": "hare", "count": 2 },
+            { "suggestion": "harry", "count": 2 },
+            { "suggestion": "hardcover", "count": 1 },
+            { "suggestion": "harp", "count": 1 },
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn suggest_counts_the_documents_with_the_previous_words() {
+    let server = server_with_documents().await;
+
+    let (response, code) = server
+        .service
+        .get("/indexes/test/suggest?q=harry%20p")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["suggestions"],
+        json!([
+            { "suggestion": "harry potter", "count": 2 },
+            { "suggestion": "harry prisoner", "count": 1 },
+        ])
+    );
+
+    let (response, code) = server.service.get("/indexes/test/suggest?q=hare%20p").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["suggestions"], json!([]));
+}
+
+#[actix_rt::test]
+async fn suggest_limit() {
+    let server = server_with_documents().await;
+
+    let (response, code) = server
+        .service
+        .get("/indexes/test/suggest?q=har&limit=1")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["suggestions"],
+        json!([{ "suggestion": "hare", "count": 2 }])
+    );
+}
+
+#[actix_rt::test]
+async fn suggest_empty_query() {
+    let server = server_with_documents().await;
+
+    let (response, code) = server.service.get("/indexes/test/suggest?q=").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["suggestions"], json!([]));
+}
+
+#[actix_rt::test]
+async fn suggest_unexisting_index() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/indexes/test/suggest?q=har").await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_not_found");
+}
@@ -0,0 +1,76 @@
+use meilisearch_http::Opt;
+use serde_json::json;
+use tempfile::TempDir;
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+use super::DOCUMENTS;
+
+#[actix_rt::test]
+async fn search_without_timeout_is_not_degraded() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .search_post(json!({ "q": "glass", "attributesToHighlight": ["title"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response.get("degraded").is_none(), "{}", response);
+    assert_eq!(response["hits"][0]["_formatted"]["title"], "<em>Glass</em>");
+}
+
+#[actix_rt::test]
+async fn search_past_its_timeout_returns_the_unformatted_hits() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "glass",
+            "attributesToHighlight": ["title"],
+            "timeoutMs": 0,
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["degraded"], true);
+    assert_eq!(response["hits"][0]["title"], "Glass");
+    assert!(
+        response["hits"][0].get("_formatted").is_none(),
+        "{}",
+        response
+    );
+}
+
+#[actix_rt::test]
+async fn search_timeout_of_the_query_overrides_the_default_one() {
+    let temp = TempDir::new().unwrap();
+    let options = Opt {
+        search_timeout_ms: Some(0),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.search_post(json!({ "q": "glass" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["degraded"], true);
+
+    let (response, code) = index
+        .search_post(json!({ "q": "glass", "timeoutMs": 60000 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response.get("degraded").is_none(), "{}", response);
+}
@@ -24,6 +24,29 @@ static DEFAULT_SETTINGS_VALUES: Lazy<HashMap<&'static str, Value>> = Lazy::new(|
     );
     map.insert("stop_words", json!([]));
     map.insert("synonyms", json!({}));
+    map.insert("localized_attributes", json!(Value::Null));
+    map.insert("separator_tokens", json!([]));
+    map.insert("non_separator_tokens", json!([]));
+    map.insert("dictionary", json!([]));
+    map.insert(
+        "formatting",
+        json!({
+            "highlightPreTag": "<em>",
+            "highlightPostTag": "</em>",
+            "cropLength": 10,
+            "cropMarker": "…"
+        }),
+    );
+    map.insert(
+        "faceting",
+        json!({
+            "maxValuesPerFacet": 100,
+            "sortFacetValuesBy": { "*": "alpha" }
+        }),
+    );
+    map.insert("version_field", json!(Value::Null));
+    map.insert("document_compression", json!("none"));
+    map.insert("rulesets", json!({}));
     map
 });
 
@@ -43,7 +66,7 @@ async fn get_settings() {
     let (response, code) = index.settings().await;
     assert_eq!(code, 200);
     let settings = response.as_object().unwrap();
-    assert_eq!(settings.keys().len(), 9);
+    assert_eq!(settings.keys().len(), 18);
     assert_eq!(settings["displayedAttributes"], json!(["*"]));
     assert_eq!(settings["searchableAttributes"], json!(["*"]));
     assert_eq!(settings["filterableAttributes"], json!([]));
@@ -61,6 +84,15 @@ async fn get_settings() {
         ])
     );
     assert_eq!(settings["stopWords"], json!([]));
+    assert_eq!(settings["localizedAttributes"], json!(null));
+    assert_eq!(settings["separatorTokens"], json!([]));
+    assert_eq!(settings["nonSeparatorTokens"], json!([]));
+    assert_eq!(settings["dictionary"], json!([]));
+    assert_eq!(settings["formatting"]["cropMarker"], json!("…"));
+    assert_eq!(settings["faceting"]["maxValuesPerFacet"], json!(100));
+    assert_eq!(settings["versionField"], json!(null));
+    assert_eq!(settings["documentCompression"], json!("none"));
+    assert_eq!(settings["rulesets"], json!({}));
 }
 
 #[actix_rt::test]
@@ -264,7 +296,16 @@ test_setting_routes!(
     distinct_attribute,
     stop_words,
     ranking_rules,
-    synonyms
+    synonyms,
+    localized_attributes,
+    separator_tokens,
+    non_separator_tokens,
+    dictionary,
+    formatting,
+    faceting,
+    version_field,
+    document_compression,
+    rulesets
 );
 
 #[actix_rt::test]
@@ -0,0 +1,108 @@
+use meilisearch_http::Opt;
+use serde_json::json;
+use tempfile::TempDir;
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn get_default_indexing_settings() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/settings/indexing").await;
+    assert_eq!(code, 200, "{}", response);
+    // The auto-batching is disabled by default.
+    assert_eq!(
+        response,
+        json!({
+            "maxBatchSize": 1,
+            "maxDocumentsPerBatch": null,
+            "maxPayloadBytes": null,
+            "maxWaitTimeMs": 0,
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn update_indexing_settings() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .patch(
+            "/settings/indexing",
+            json!({ "maxBatchSize": 10, "maxPayloadBytes": 1048576, "maxWaitTimeMs": 50 }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "maxBatchSize": 10,
+            "maxDocumentsPerBatch": null,
+            "maxPayloadBytes": 1048576,
+            "maxWaitTimeMs": 50,
+        })
+    );
+
+    // The omitted parameters are kept and the null ones are reset.
+    let (response, code) = server
+        .service
+        .patch(
+            "/settings/indexing",
+            json!({ "maxBatchSize": null, "maxDocumentsPerBatch": 1000 }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "maxBatchSize": null,
+            "maxDocumentsPerBatch": 1000,
+            "maxPayloadBytes": 1048576,
+            "maxWaitTimeMs": 50,
+        })
+    );
+
+    let (response, code) = server.service.get("/settings/indexing").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["maxDocumentsPerBatch"], 1000);
+
+    // The tasks are still processed with the new parameters.
+    let index = server.index("test");
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+}
+
+#[actix_rt::test]
+async fn update_indexing_settings_with_unknown_parameter() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .patch("/settings/indexing", json!({ "maxBatchBytes": 10 }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "malformed_payload");
+}
+
+#[actix_rt::test]
+async fn indexing_settings_are_reserved_to_the_master_key() {
+    let temp = TempDir::new().unwrap();
+    let options = Opt {
+        master_key: Some("MASTER_KEY".to_string()),
+        ..default_settings(temp.path())
+    };
+    let mut server = Server::new_with_options(options).await;
+    server.use_api_key("not-the-master-key");
+
+    let (response, code) = server.service.get("/settings/indexing").await;
+    assert_eq!(code, 403, "{}", response);
+
+    let (response, code) = server
+        .service
+        .patch("/settings/indexing", json!({ "maxBatchSize": 10 }))
+        .await;
+    assert_eq!(code, 403, "{}", response);
+}
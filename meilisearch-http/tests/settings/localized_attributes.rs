@@ -0,0 +1,85 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn set_and_reset_localized_attributes() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let rules = json!([
+        { "attributePatterns": ["title_ja", "*_jpn"], "locales": ["jpn"] },
+        { "attributePatterns": ["title"], "locales": ["eng", "fra"] },
+    ]);
+
+    index
+        .update_settings(json!({ "localizedAttributes": rules }))
+        .await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200);
+    assert_eq!(response["localizedAttributes"], rules);
+
+    index
+        .update_settings(json!({ "localizedAttributes": null }))
+        .await;
+    index.wait_task(1).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["localizedAttributes"], json!(null));
+}
+
+#[actix_rt::test]
+async fn localized_attributes_are_reset_with_all_settings() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({
+            "localizedAttributes": [{ "attributePatterns": ["*"], "locales": ["cmn"] }]
+        }))
+        .await;
+    index.wait_task(0).await;
+
+    index.delete_settings().await;
+    index.wait_task(1).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["localizedAttributes"], json!(null));
+}
+
+#[actix_rt::test]
+async fn error_set_invalid_locale() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({
+            "localizedAttributes": [{ "attributePatterns": ["title"], "locales": ["klingon"] }]
+        }))
+        .await;
+    let response = index.wait_task(0).await;
+
+    assert_eq!(response["status"], "failed");
+    let expected_error = json!({
+        "message": "Invalid locale `klingon` in `localizedAttributes`. Locales must be ISO 639-3 language codes supported by the tokenizer, such as `eng`, `fra`, `cmn` or `jpn`.",
+        "code": "invalid_localized_attributes",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#invalid_localized_attributes"
+    });
+    assert_eq!(response["error"], expected_error);
+}
+
+#[actix_rt::test]
+async fn error_set_localized_attributes_unknown_field() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (_response, code) = index
+        .update_settings(json!({
+            "localizedAttributes": [{ "attributes": ["title"], "locales": ["eng"] }]
+        }))
+        .await;
+    assert_eq!(code, 400);
+}
@@ -1,2 +1,7 @@
 mod distinct;
 mod get_settings;
+mod indexing;
+mod localized_attributes;
+mod stop_words;
+mod tokenization;
+mod synonyms_files;
@@ -0,0 +1,85 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn set_stop_words_presets() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "stopWords": { "preset": "fr", "words": ["voici"] } }))
+        .await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response["stopWords"],
+        json!({ "preset": ["fr"], "words": ["voici"] })
+    );
+
+    index
+        .update_settings(json!({ "stopWords": { "preset": ["en", "fr"] } }))
+        .await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, _) = index.settings().await;
+    assert_eq!(
+        response["stopWords"],
+        json!({ "preset": ["en", "fr"], "words": [] })
+    );
+
+    // A list of words replaces the presets.
+    index.update_settings(json!({ "stopWords": ["the"] })).await;
+    index.wait_task(2).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["stopWords"], json!(["the"]));
+}
+
+#[actix_rt::test]
+async fn stop_words_preset_words_are_ignored() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "the cat" },
+                { "id": 2, "title": "the dog" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+
+    index
+        .update_settings(json!({ "stopWords": { "preset": "en" } }))
+        .await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    index
+        .search(json!({ "q": "the cat" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+            assert_eq!(response["hits"][0]["id"], 1);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn error_unknown_stop_words_preset() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "stopWords": { "preset": "xx" } }))
+        .await;
+    let response = index.wait_task(0).await;
+
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "invalid_stop_words_preset");
+}
@@ -0,0 +1,131 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn import_csv_synonyms() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .post_str(
+            "/indexes/test/settings/synonyms/import?format=csv",
+            "tv,television,telly\nlaptop,notebook\n",
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["importedWords"], 2);
+    assert_eq!(response["conflicts"], json!([]));
+    index.wait_task(1).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(
+        response["synonyms"],
+        json!({ "laptop": ["notebook"], "tv": ["television", "telly"] })
+    );
+}
+
+#[actix_rt::test]
+async fn merge_solr_synonyms_reports_conflicts() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "synonyms": { "tv": ["television"] } }))
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .post_str(
+            "/indexes/test/settings/synonyms/import?format=solr&mode=merge",
+            "# electronics\ntv => telly\nlaptop, notebook\n",
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(
+        response["conflicts"],
+        json!([{ "word": "tv", "existing": ["television"], "imported": ["telly"] }])
+    );
+    index.wait_task(1).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(
+        response["synonyms"],
+        json!({
+            "laptop": ["notebook"],
+            "notebook": ["laptop"],
+            "tv": ["television", "telly"],
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn replace_synonyms() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "synonyms": { "tv": ["television"] } }))
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .post_str(
+            "/indexes/test/settings/synonyms/import?format=csv&mode=replace",
+            "laptop,notebook\n",
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(1).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["synonyms"], json!({ "laptop": ["notebook"] }));
+}
+
+#[actix_rt::test]
+async fn export_synonyms() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "synonyms": { "tv": ["television", "telly"] } }))
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .get_text("/indexes/test/settings/synonyms/export?format=csv")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, "tv,television,telly\n");
+
+    let (response, code) = server
+        .service
+        .get_text("/indexes/test/settings/synonyms/export?format=solr")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, "tv => television, telly\n");
+}
+
+#[actix_rt::test]
+async fn error_import_invalid_synonyms_file() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .post_str(
+            "/indexes/test/settings/synonyms/import?format=solr",
+            "tv, television\n=> telly\n",
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_synonyms_file");
+    assert_eq!(
+        response["message"],
+        "Invalid synonyms file at line 2: both sides of `=>` must contain a word."
+    );
+}
@@ -0,0 +1,135 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn set_and_reset_tokenization_settings() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({
+            "separatorTokens": ["|", "&"],
+            "nonSeparatorTokens": ["@", "#"],
+            "dictionary": ["C++", "e-mail"],
+        }))
+        .await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200);
+    assert_eq!(response["separatorTokens"], json!(["&", "|"]));
+    assert_eq!(response["nonSeparatorTokens"], json!(["#", "@"]));
+    assert_eq!(response["dictionary"], json!(["C++", "e-mail"]));
+
+    index.delete_settings().await;
+    index.wait_task(1).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["separatorTokens"], json!([]));
+    assert_eq!(response["nonSeparatorTokens"], json!([]));
+    assert_eq!(response["dictionary"], json!([]));
+}
+
+#[actix_rt::test]
+async fn dictionary_words_are_matched_whole() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index.update_dictionary(json!(["C++"])).await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, _) = index.get_dictionary().await;
+    assert_eq!(response, json!(["C++"]));
+
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "learning C++" },
+                { "id": 2, "title": "learning C" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    index
+        .search(json!({ "q": "C++" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(
+                response["hits"],
+                json!([{ "id": 1, "title": "learning C++" }])
+            );
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn update_non_separator_tokens_reindexes_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "e-mail" },
+                { "id": 2, "title": "mail" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+
+    let (response, _) = index.search_post(json!({ "q": "mail" })).await;
+    assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+
+    index
+        .update_settings(json!({ "nonSeparatorTokens": ["-"] }))
+        .await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    index
+        .search(json!({ "q": "mail" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([{ "id": 2, "title": "mail" }]));
+        })
+        .await;
+    index
+        .search(json!({ "q": "e-mail" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([{ "id": 1, "title": "e-mail" }]));
+        })
+        .await;
+
+    // the stored documents are the original ones, and are merged with the updates.
+    index
+        .update_documents(json!([{ "id": 1, "note": "read" }]), None)
+        .await;
+    index.wait_task(2).await;
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "id": 1, "title": "e-mail", "note": "read" })
+    );
+
+    let (response, _) = index.search_post(json!({ "q": "e-mail" })).await;
+    assert_eq!(response["hits"][0]["id"], 1);
+}
+
+#[actix_rt::test]
+async fn error_set_empty_token() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "separatorTokens": [""] }))
+        .await;
+    let response = index.wait_task(0).await;
+
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "invalid_tokenization_settings");
+}
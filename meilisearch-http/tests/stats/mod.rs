@@ -1,5 +1,8 @@
+use std::time::Duration;
+
 use serde_json::json;
 use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use tokio::time::sleep;
 
 use crate::common::Server;
 
@@ -23,6 +26,50 @@ async fn test_healthyness() {
     assert_eq!(response["status"], "available");
 }
 
+#[actix_rt::test]
+async fn health_details() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server.service.get("/health").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["databaseWritable"], true);
+    assert_eq!(response["authStoreAccessible"], true);
+    assert_eq!(
+        response["taskQueue"],
+        json!({
+            "enqueuedTasks": 0,
+            "processingTasks": 0,
+            "oldestEnqueuedAt": null,
+            "lagSeconds": 0,
+            "replaying": false,
+        })
+    );
+    assert_eq!(response["lastDumpAt"], json!(null));
+
+    let (response, code) = server.service.post("/dumps", json!(null)).await;
+    assert_eq!(code, 202, "{}", response);
+    let dump_uid = response["uid"].as_str().unwrap();
+    for _ in 0..10 {
+        let (response, _) = server.get_dump_status(dump_uid).await;
+        if response["status"] != "in_progress" {
+            break;
+        }
+        sleep(Duration::from_secs(1)).await;
+    }
+    let (response, _) = server.service.get("/health").await;
+    assert!(response["lastDumpAt"].is_string(), "{}", response);
+
+    let (response, code) = server.service.get("/health?mode=ready").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["status"], "available");
+
+    let (response, code) = server.service.get("/health?mode=unknown").await;
+    assert_eq!(code, 400, "{}", response);
+}
+
 #[actix_rt::test]
 async fn stats() {
     let server = Server::new().await;
@@ -73,3 +120,59 @@ async fn stats() {
     assert_eq!(response["indexes"]["test"]["fieldDistribution"]["name"], 1);
     assert_eq!(response["indexes"]["test"]["fieldDistribution"]["age"], 1);
 }
+
+#[actix_rt::test]
+async fn timeseries() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1 }, { "id": 2 }]), Some("id"))
+        .await;
+    index.wait_task(0).await;
+
+    index.search_post(json!({ "q": "" })).await;
+    index.search_post(json!({ "q": "" })).await;
+
+    // the indexing activity is recorded right after the task is marked as processed.
+    let mut points = Vec::new();
+    for _ in 0..10 {
+        let (response, code) = server
+            .service
+            .get("/stats/timeseries?window=600&interval=300")
+            .await;
+        assert_eq!(code, 200, "{}", response);
+        assert_eq!(response["window"], 600);
+        assert_eq!(response["interval"], 300);
+
+        points = response["indexes"]["test"].as_array().unwrap().clone();
+        if points.last().unwrap()["numberOfDocuments"] == 2 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+
+    assert_eq!(points.len(), 2);
+    let sum = |field: &str| {
+        points
+            .iter()
+            .map(|p| p[field].as_u64().unwrap())
+            .sum::<u64>()
+    };
+    assert_eq!(sum("searches"), 2);
+    assert_eq!(sum("indexedDocuments"), 2);
+    assert_eq!(points[1]["numberOfDocuments"], 2);
+    assert!(points[1]["start"].is_string());
+}
+
+#[actix_rt::test]
+async fn timeseries_invalid_interval() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/stats/timeseries?interval=90").await;
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "invalid_timeseries_query");
+
+    let (response, code) = server.service.get("/stats/timeseries?window=3600000").await;
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "invalid_timeseries_query");
+}
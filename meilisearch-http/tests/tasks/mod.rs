@@ -133,3 +133,84 @@ async fn test_summarized_task_view() {
     let (response, _) = index.delete().await;
     assert_valid_summarized_task!(response, "indexDeletion", "test");
 }
+
+#[actix_rt::test]
+async fn watch_finished_task() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (events, code) = server.service.watch("/tasks/0/watch", usize::MAX).await;
+    assert_eq!(code, 200);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["uid"], 0);
+    assert_eq!(events[0]["type"], "indexCreation");
+    assert_eq!(events[0]["status"], "succeeded");
+    assert_eq!(events[0]["progress"], 100);
+}
+
+#[actix_rt::test]
+async fn watch_task_until_finished() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(
+            serde_json::from_str(include_str!("../assets/test_set.json")).unwrap(),
+            None,
+        )
+        .await;
+
+    // The stream ends once the task is finished.
+    let (events, code) = server.service.watch("/tasks/0/watch", usize::MAX).await;
+    assert_eq!(code, 200);
+
+    let progress: Vec<_> = events
+        .iter()
+        .map(|event| event["progress"].as_u64().unwrap())
+        .collect();
+    assert!(progress.windows(2).all(|w| w[0] <= w[1]), "{:?}", progress);
+
+    let last = events.last().unwrap();
+    assert_eq!(last["status"], "succeeded");
+    assert_eq!(last["progress"], 100);
+    assert!(last["details"]["indexedDocuments"].as_u64().unwrap() > 0);
+}
+
+#[actix_rt::test]
+async fn error_watch_unexisting_task() {
+    let server = Server::new().await;
+
+    let (events, code) = server.service.watch("/tasks/1/watch", usize::MAX).await;
+    assert_eq!(code, 404);
+    assert_eq!(events[0]["code"], "task_not_found");
+}
+
+#[actix_rt::test]
+async fn watch_tasks_with_filters() {
+    let server = Server::new().await;
+
+    let watch = server
+        .service
+        .watch("/tasks/watch?indexUid=test&status=succeeded", 1);
+    let create = async {
+        server.index("other").create(None).await;
+        server.index("test").create(None).await;
+    };
+    let ((events, code), ()) = futures::join!(watch, create);
+
+    assert_eq!(code, 200);
+    assert_eq!(events[0]["indexUid"], "test");
+    assert_eq!(events[0]["type"], "indexCreation");
+    assert_eq!(events[0]["status"], "succeeded");
+    assert_eq!(events[0]["progress"], 100);
+}
+
+#[actix_rt::test]
+async fn error_watch_tasks_invalid_filter() {
+    let server = Server::new().await;
+
+    let (events, code) = server.service.watch("/tasks/watch?status=done", 1).await;
+    assert_eq!(code, 400);
+    assert_eq!(events[0]["code"], "bad_request");
+}
@@ -0,0 +1,62 @@
+use std::fs;
+use std::time::Duration;
+
+use meilisearch_http::Opt;
+use serde_json::json;
+use tempfile::TempDir;
+use tokio::time::sleep;
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn dropped_files_are_imported_in_the_index_of_their_directory() {
+    let temp = TempDir::new().unwrap();
+    let import_dir = TempDir::new().unwrap();
+    let movies = import_dir.path().join("movies");
+    fs::create_dir(&movies).unwrap();
+    fs::write(
+        movies.join("a.ndjson"),
+        "{\"id\":1,\"title\":\"Carol\"}\n{\"id\":2,\"title\":\"Wonder Woman\"}\n",
+    )
+    .unwrap();
+    fs::write(movies.join("b.csv"), "id,title\n3,Mad Max\n").unwrap();
+    fs::write(movies.join("c.ndjson"), "{\"id\":4,").unwrap();
+    // The files are imported once they haven't been modified for a second.
+    sleep(Duration::from_millis(1100)).await;
+
+    let options = Opt {
+        import_dir: Some(import_dir.path().to_path_buf()),
+        import_dir_interval_sec: 1,
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await;
+    sleep(Duration::from_secs(1)).await;
+
+    let index = server.index("movies");
+    index.wait_task(1).await;
+    let (response, code) = index.get_all_documents(Default::default()).await;
+    assert_eq!(code, 200, "{}", response);
+    let titles: Vec<_> = response
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|document| document["title"].clone())
+        .collect();
+    assert_eq!(
+        titles,
+        [json!("Carol"), json!("Wonder Woman"), json!("Mad Max")]
+    );
+
+    assert!(fs::read_dir(&movies).unwrap().next().is_none());
+    let processed = import_dir.path().join(".processed/movies");
+    assert!(processed.join("a.ndjson").exists());
+    assert!(processed.join("b.csv").exists());
+    assert!(import_dir.path().join(".failed/movies/c.ndjson").exists());
+
+    // The imported files are not imported again.
+    sleep(Duration::from_secs(2)).await;
+    let (response, code) = server.service.get("/tasks").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+}
@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use actix_web::http::header::HeaderMap;
+use actix_web::test;
+use byte_unit::{Byte, ByteUnit};
+use meilisearch_http::{analytics, create_app, Opt};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use tokio::time::sleep;
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+async fn request(server: &Server, req: test::TestRequest) -> (Value, u16, HeaderMap) {
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+
+    let res = test::call_service(&app, req.to_request()).await;
+    let status_code = res.status().as_u16();
+    let headers = res.headers().clone();
+
+    let body = test::read_body(res).await;
+    let response = serde_json::from_slice(&body).unwrap_or_default();
+    (response, status_code, headers)
+}
+
+#[actix_rt::test]
+async fn document_additions_are_refused_when_the_disk_is_full() {
+    let temp = TempDir::new().unwrap();
+    let options = Opt {
+        // No disk has that much free space, the breaker is opened on its first check.
+        write_breaker_min_free_disk: Some(Byte::from_unit(1024.0, ByteUnit::PB).unwrap()),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await;
+    let index = server.index("movies");
+    index.create(None).await;
+    index.wait_task(0).await;
+    sleep(Duration::from_secs(1)).await;
+
+    let (response, code) = server.service.get("/health").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["writeBreaker"]["open"], true, "{}", response);
+    assert_eq!(response["writeBreaker"]["reason"], "disk");
+
+    let req = test::TestRequest::post()
+        .uri("/indexes/movies/documents")
+        .set_json(&json!([{ "id": 1, "title": "Carol" }]));
+    let (response, code, headers) = request(&server, req).await;
+    assert_eq!(code, 503, "{}", response);
+    assert_eq!(response["code"], "writes_suspended");
+    assert_eq!(headers["retry-after"], "5");
+
+    // The searches are still served and the deletions still accepted to free some space.
+    let req = test::TestRequest::post()
+        .uri("/indexes/movies/search")
+        .set_json(&json!({ "q": "carol" }));
+    let (response, code, _) = request(&server, req).await;
+    assert_eq!(code, 200, "{}", response);
+
+    let req = test::TestRequest::delete().uri("/indexes/movies/documents");
+    let (response, code, _) = request(&server, req).await;
+    assert_eq!(code, 202, "{}", response);
+}
+
+#[actix_rt::test]
+async fn write_breaker_is_closed_by_default() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/health").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["writeBreaker"],
+        json!({ "open": false, "enqueuedTasks": 0 })
+    );
+}
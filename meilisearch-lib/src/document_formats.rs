@@ -4,6 +4,8 @@ use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Seek, Write};
 
 use meilisearch_error::{internal_error, Code, ErrorCode};
 use milli::documents::DocumentBatchBuilder;
+#[cfg(feature = "xml")]
+use serde_json::{Map, Value};
 
 type Result<T> = std::result::Result<T, DocumentFormatError>;
 
@@ -12,6 +14,10 @@ pub enum PayloadType {
     Ndjson,
     Json,
     Csv,
+    #[cfg(feature = "xml")]
+    Xml,
+    #[cfg(feature = "parquet")]
+    Parquet,
 }
 
 impl fmt::Display for PayloadType {
@@ -20,6 +26,10 @@ impl fmt::Display for PayloadType {
             PayloadType::Ndjson => write!(f, "ndjson"),
             PayloadType::Json => write!(f, "json"),
             PayloadType::Csv => write!(f, "csv"),
+            #[cfg(feature = "xml")]
+            PayloadType::Xml => write!(f, "xml"),
+            #[cfg(feature = "parquet")]
+            PayloadType::Parquet => write!(f, "parquet"),
         }
     }
 }
@@ -78,6 +88,29 @@ impl ErrorCode for DocumentFormatError {
 
 internal_error!(DocumentFormatError: io::Error);
 
+#[cfg(feature = "xml")]
+impl From<quick_xml::Error> for DocumentFormatError {
+    fn from(error: quick_xml::Error) -> Self {
+        match error {
+            quick_xml::Error::Io(e) => Self::Internal(Box::new(e)),
+            e => Self::MalformedPayload(
+                Box::new(milli::documents::Error::Custom(e.to_string())),
+                PayloadType::Xml,
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<parquet::errors::ParquetError> for DocumentFormatError {
+    fn from(error: parquet::errors::ParquetError) -> Self {
+        Self::MalformedPayload(
+            Box::new(milli::documents::Error::Custom(error.to_string())),
+            PayloadType::Parquet,
+        )
+    }
+}
+
 /// reads csv from input and write an obkv batch to writer.
 pub fn read_csv(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
     let writer = BufWriter::new(writer);
@@ -126,3 +159,194 @@ pub fn read_json(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
 
     Ok(count)
 }
+
+/// reads xml from input and write an obkv batch to writer.
+///
+/// The children of the root element are the documents, their attributes and child elements being
+/// their fields. The elements with attributes or children are objects, the repeated elements are
+/// arrays, and the text of the other elements are strings.
+#[cfg(feature = "xml")]
+pub fn read_xml(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_reader(BufReader::new(input));
+    reader.trim_text(true);
+    let writer = BufWriter::new(writer);
+    let mut builder = DocumentBatchBuilder::new(writer).map_err(|e| (PayloadType::Xml, e))?;
+
+    // The open elements, starting with the root element.
+    let mut elements: Vec<XmlElement> = Vec::new();
+    let mut buf = Vec::new();
+    loop {
+        let closed = match reader.read_event(&mut buf)? {
+            Event::Start(start) => {
+                elements.push(XmlElement::new(&reader, &start)?);
+                None
+            }
+            Event::Empty(start) => Some(XmlElement::new(&reader, &start)?),
+            Event::End(_) => elements.pop(),
+            Event::Text(text) => {
+                if let Some(element) = elements.last_mut() {
+                    element.text.push_str(&text.unescape_and_decode(&reader)?);
+                }
+                None
+            }
+            Event::CData(text) => {
+                if let Some(element) = elements.last_mut() {
+                    element.text.push_str(reader.decode(&text)?);
+                }
+                None
+            }
+            Event::Eof => break,
+            _ => None,
+        };
+
+        if let Some(element) = closed {
+            match elements.last_mut() {
+                // The root element is closed.
+                None => (),
+                Some(_) if elements.len() == 1 => {
+                    let document = Value::Object(element.fields).to_string();
+                    builder
+                        .extend_from_json(Cursor::new(document.as_bytes()))
+                        .map_err(|e| (PayloadType::Xml, e))?;
+                }
+                Some(parent) => parent.insert(element.name, element.into_value()),
+            }
+        }
+        buf.clear();
+    }
+
+    let count = builder.finish().map_err(|e| (PayloadType::Xml, e))?;
+
+    Ok(count)
+}
+
+#[cfg(feature = "xml")]
+struct XmlElement {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
+#[cfg(feature = "xml")]
+impl XmlElement {
+    fn new<B: BufRead>(
+        reader: &quick_xml::Reader<B>,
+        start: &quick_xml::events::BytesStart,
+    ) -> Result<Self> {
+        let mut element = XmlElement {
+            name: reader.decode(start.name())?.to_string(),
+            fields: Map::new(),
+            text: String::new(),
+        };
+        for attribute in start.attributes() {
+            let attribute = attribute?;
+            let name = reader.decode(attribute.key)?.to_string();
+            let value = attribute.unescape_and_decode_value(reader)?;
+            element.insert(name, Value::String(value));
+        }
+        Ok(element)
+    }
+
+    /// Adds a field to the element, the values of a repeated field being gathered in an array.
+    fn insert(&mut self, name: String, value: Value) {
+        match self.fields.get_mut(&name) {
+            Some(Value::Array(values)) => values.push(value),
+            Some(previous) => *previous = Value::Array(vec![previous.take(), value]),
+            None => {
+                self.fields.insert(name, value);
+            }
+        }
+    }
+
+    /// The text of the elements with attributes or children is ignored.
+    fn into_value(self) -> Value {
+        if !self.fields.is_empty() {
+            Value::Object(self.fields)
+        } else if !self.text.is_empty() {
+            Value::String(self.text)
+        } else {
+            Value::Null
+        }
+    }
+}
+
+/// reads parquet from input and write an obkv batch to writer.
+#[cfg(feature = "parquet")]
+pub fn read_parquet(mut input: impl Read, writer: impl Write + Seek) -> Result<usize> {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    // The metadata of a parquet file are at its end, so the payload is written to a temporary
+    // file before its rows are read.
+    let mut file = tempfile::tempfile()?;
+    io::copy(&mut input, &mut file)?;
+    let reader = SerializedFileReader::new(file)?;
+
+    let writer = BufWriter::new(writer);
+    let mut builder = DocumentBatchBuilder::new(writer).map_err(|e| (PayloadType::Parquet, e))?;
+    for row in reader.get_row_iter(None)? {
+        let document = row.to_json_value().to_string();
+        builder
+            .extend_from_json(Cursor::new(document.as_bytes()))
+            .map_err(|e| (PayloadType::Parquet, e))?;
+    }
+
+    let count = builder.finish().map_err(|e| (PayloadType::Parquet, e))?;
+
+    Ok(count)
+}
+
+#[cfg(all(test, feature = "xml"))]
+mod test {
+    use milli::documents::DocumentBatchReader;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn read_xml_documents() {
+        let xml = r#"<?xml version="1.0"?>
+            <movies>
+                <movie id="1">
+                    <title>Carol &amp; Therese</title>
+                    <genre>drama</genre>
+                    <genre>romance</genre>
+                    <director><name>Todd Haynes</name></director>
+                </movie>
+                <movie id="2"><title><![CDATA[<Kefir>]]></title><tagline/></movie>
+            </movies>"#;
+
+        let mut batch = Cursor::new(Vec::new());
+        let count = read_xml(xml.as_bytes(), &mut batch).unwrap();
+        assert_eq!(count, 2);
+
+        batch.set_position(0);
+        let mut reader = DocumentBatchReader::from_reader(batch).unwrap();
+        let mut documents = Vec::new();
+        while let Some((index, document)) = reader.next_document_with_index().unwrap() {
+            let document: Map<_, _> = document
+                .iter()
+                .map(|(id, value)| {
+                    let name = index.name(id).unwrap().to_string();
+                    (name, serde_json::from_slice(value).unwrap())
+                })
+                .collect();
+            documents.push(Value::Object(document));
+        }
+
+        assert_eq!(
+            documents[0],
+            json!({
+                "id": "1",
+                "title": "Carol & Therese",
+                "genre": ["drama", "romance"],
+                "director": { "name": "Todd Haynes" },
+            })
+        );
+        assert_eq!(
+            documents[1],
+            json!({ "id": "2", "title": "<Kefir>", "tagline": null })
+        );
+    }
+}
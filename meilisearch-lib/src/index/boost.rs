@@ -0,0 +1,167 @@
+//! The query-time boosts of a search. The hits ranked by the ranking rules of the index are
+//! ranked again by their relevancy score multiplied by their boosts: the weights of the boosted
+//! attributes containing a query word, and the weight of the boost filter for the documents
+//! matching it.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+use milli::heed::RoTxn;
+use milli::tokenizer::{Analyzer, AnalyzerConfig};
+use milli::{obkv_to_json, DocumentId};
+use obkv::KvReaderU16;
+use roaring::RoaringBitmap;
+use serde_json::Value;
+
+use super::compression;
+use super::error::{IndexError, Result};
+use super::filter::split_existence_conditions;
+use super::index::Index;
+use super::ranking_score::ScoreComputer;
+use super::search::{parse_filter, Deadline};
+
+/// The weight of the documents matching the boost filter of a search that doesn't set one.
+pub const DEFAULT_BOOST_FILTER_WEIGHT: f64 = 2.0;
+
+/// The validated boosts of a search.
+pub(super) struct SearchBoost {
+    attributes: Vec<(String, f64)>,
+    /// The documents matching the boost filter, along with their weight.
+    boosted: Option<(RoaringBitmap, f64)>,
+}
+
+impl Index {
+    /// Validates the boosts of a search and evaluates its boost filter. Returns `None` when the
+    /// search doesn't boost anything.
+    pub(super) fn search_boost(
+        &self,
+        rtxn: &RoTxn,
+        attributes: Option<&BTreeMap<String, f64>>,
+        filter: Option<&Value>,
+        filter_weight: Option<f64>,
+    ) -> Result<Option<SearchBoost>> {
+        if attributes.is_none() && filter.is_none() {
+            return Ok(None);
+        }
+
+        let searchable = self.searchable_fields(rtxn)?;
+        let mut boosted_attributes = Vec::new();
+        for (attribute, &weight) in attributes.into_iter().flatten() {
+            if let Some(ref searchable) = searchable {
+                if !searchable.contains(&attribute.as_str()) {
+                    return Err(IndexError::InvalidSearchBoost(format!(
+                        "Attribute `{}` is not searchable and thus, cannot be boosted. Available searchable attributes are: `{}`.",
+                        attribute,
+                        searchable.join(", ")
+                    )));
+                }
+            }
+            check_weight(attribute, weight)?;
+            boosted_attributes.push((attribute.clone(), weight));
+        }
+
+        let boosted = match filter {
+            Some(filter) => {
+                let weight = filter_weight.unwrap_or(DEFAULT_BOOST_FILTER_WEIGHT);
+                check_weight("boostFilterWeight", weight)?;
+                Some((self.filtered_documents(rtxn, filter)?, weight))
+            }
+            None => None,
+        };
+
+        Ok(Some(SearchBoost {
+            attributes: boosted_attributes,
+            boosted,
+        }))
+    }
+
+    /// Ranks the hits by their boosted relevancy score, the hits of the same score keep the order
+    /// of the ranking rules. Returns the hits along with the boost factor of each of them, or
+    /// `None` if the deadline elapsed before all the hits were scored.
+    pub(super) fn boost_documents(
+        &self,
+        rtxn: &RoTxn,
+        documents_ids: &[DocumentId],
+        boost: &SearchBoost,
+        query: Option<&str>,
+        deadline: &Deadline,
+    ) -> Result<Option<(Vec<DocumentId>, HashMap<DocumentId, f64>)>> {
+        let stop_words = fst::Set::default();
+        let mut config = AnalyzerConfig::default();
+        config.stop_words(&stop_words);
+        let analyzer = Analyzer::new(config);
+
+        let criteria = self.criteria(rtxn)?;
+        let searchable_attributes = self.searchable_attribute_names(rtxn)?;
+        let computer = ScoreComputer::new(query, &analyzer, &criteria, &searchable_attributes, &[]);
+
+        let fields_ids_map = self.fields_ids_map(rtxn)?;
+        let all_ids: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+
+        let mut scored = Vec::with_capacity(documents_ids.len());
+        let mut factors = HashMap::new();
+        for (id, document) in compression::documents(self, rtxn, documents_ids.iter().copied())? {
+            if deadline.is_elapsed() {
+                return Ok(None);
+            }
+
+            let document = obkv_to_json(&all_ids, &fields_ids_map, KvReaderU16::new(&document))?;
+            let mut factor = computer.attribute_boost(&document, &analyzer, &boost.attributes);
+            if let Some((ref boosted, weight)) = boost.boosted {
+                if boosted.contains(id) {
+                    factor *= weight;
+                }
+            }
+
+            let score = computer.compute(&document, &document, &analyzer).score;
+            scored.push((id, score * factor));
+            factors.insert(id, factor);
+        }
+
+        // the sort is stable, the hits of the same score keep their ranking order.
+        scored.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+        Ok(Some((
+            scored.into_iter().map(|(id, _)| id).collect(),
+            factors,
+        )))
+    }
+
+    /// The searchable attributes of the index, all of its attributes by default.
+    pub(super) fn searchable_attribute_names(&self, rtxn: &RoTxn) -> Result<Vec<String>> {
+        Ok(match self.searchable_fields(rtxn)? {
+            Some(fields) => fields.into_iter().map(String::from).collect(),
+            None => self
+                .fields_ids_map(rtxn)?
+                .iter()
+                .map(|(_, name)| name.to_string())
+                .collect(),
+        })
+    }
+
+    /// Returns the documents matching a filter, with its existence conditions.
+    fn filtered_documents(&self, rtxn: &RoTxn, filter: &Value) -> Result<RoaringBitmap> {
+        let filter = split_existence_conditions(filter)?;
+        let mut documents = match self.existence_documents(rtxn, &filter.conditions)? {
+            Some(documents) => documents,
+            None => self.documents_ids(rtxn)?,
+        };
+        if let Some(ref filter) = filter.filter {
+            if let Some(facets) = parse_filter(filter)? {
+                documents &= facets.evaluate(rtxn, self)?;
+            }
+        }
+
+        Ok(documents)
+    }
+}
+
+fn check_weight(name: &str, weight: f64) -> Result<()> {
+    if weight.is_finite() && weight > 0.0 {
+        Ok(())
+    } else {
+        Err(IndexError::InvalidSearchBoost(format!(
+            "The weight of `{}` is invalid. It should be a positive number, got `{}`.",
+            name, weight
+        )))
+    }
+}
@@ -0,0 +1,357 @@
+//! The opt-in zstd compression of the stored documents. milli is not aware of it: the documents
+//! it writes are compressed at the end of each update, and the ones it reads during an update are
+//! decompressed beforehand. A compressed document is an obkv with a single entry, under a field id
+//! milli never assigns, holding the size of the original obkv followed by the original obkv
+//! compressed with the dictionary of the index.
+
+use std::borrow::Cow;
+use std::fs::File;
+
+use milli::documents::DocumentBatchReader;
+use milli::heed::types::{ByteSlice, Str};
+use milli::heed::{RoTxn, RwTxn};
+use milli::{DocumentId, FieldId, BEU32};
+use obkv::{KvReaderU16, KvWriterU16};
+use roaring::RoaringBitmap;
+use serde::Serialize;
+use serde_json::Value;
+use zstd::bulk::{Compressor, Decompressor};
+
+use super::custom_settings;
+use super::error::Result;
+use super::updates::DocumentCompression;
+
+/// milli assigns the field ids in increasing order from 0, and never reaches this one.
+const COMPRESSED_FIELD_ID: FieldId = FieldId::MAX;
+const COMPRESSION_LEVEL: i32 = 3;
+/// The dictionary is trained on the first compressed documents of the index.
+const DICTIONARY_SAMPLES: usize = 1000;
+const DICTIONARY_MAX_SIZE: usize = 110 * 1024;
+
+mod main_key {
+    pub const DICTIONARY: &str = "meilisearch-compression-dictionary";
+    pub const COMPRESSED_DOCUMENTS: &str = "meilisearch-compressed-documents";
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentCompressionStats {
+    pub compressed_documents: u64,
+    /// The size of the stored documents, compressed or not.
+    pub stored_bytes: u64,
+    /// The size the stored documents would have without compression.
+    pub uncompressed_bytes: u64,
+    pub dictionary_bytes: u64,
+}
+
+/// Returns the original obkv of the stored documents.
+pub struct DocumentDecoder {
+    decompressor: Decompressor<'static>,
+}
+
+impl DocumentDecoder {
+    pub fn new(index: &milli::Index, rtxn: &RoTxn) -> Result<Self> {
+        let dictionary = index
+            .main
+            .get::<_, Str, ByteSlice>(rtxn, main_key::DICTIONARY)?
+            .unwrap_or_default();
+
+        Ok(Self {
+            decompressor: Decompressor::with_dictionary(dictionary)?,
+        })
+    }
+
+    /// Decompresses a stored document, the documents that are not compressed being returned as
+    /// they are.
+    pub fn decode<'a>(&mut self, bytes: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+        match compressed_content(bytes) {
+            Some(content) if content.len() >= 4 => {
+                let (size, data) = content.split_at(4);
+                let size = u32::from_be_bytes([size[0], size[1], size[2], size[3]]) as usize;
+                Ok(Cow::Owned(self.decompressor.decompress(data, size)?))
+            }
+            _ => Ok(Cow::Borrowed(bytes)),
+        }
+    }
+}
+
+fn compressed_content(bytes: &[u8]) -> Option<&[u8]> {
+    let mut entries = KvReaderU16::new(bytes).iter();
+    match (entries.next(), entries.next()) {
+        (Some((COMPRESSED_FIELD_ID, content)), None) => Some(content),
+        _ => None,
+    }
+}
+
+fn compress_document(compressor: &mut Compressor, bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut content = (bytes.len() as u32).to_be_bytes().to_vec();
+    content.extend_from_slice(&compressor.compress(bytes)?);
+
+    let mut writer = KvWriterU16::memory();
+    writer.insert(COMPRESSED_FIELD_ID, &content)?;
+    Ok(writer.into_inner()?)
+}
+
+/// Returns the obkv of the documents with the given ids, the unknown ids being ignored.
+pub fn documents(
+    index: &milli::Index,
+    rtxn: &RoTxn,
+    ids: impl IntoIterator<Item = DocumentId>,
+) -> Result<Vec<(DocumentId, Vec<u8>)>> {
+    let mut decoder = DocumentDecoder::new(index, rtxn)?;
+    let stored = index.documents.remap_data_type::<ByteSlice>();
+
+    let mut documents = Vec::new();
+    for id in ids {
+        if let Some(bytes) = stored.get(rtxn, &BEU32::new(id))? {
+            documents.push((id, decoder.decode(bytes)?.into_owned()));
+        }
+    }
+
+    Ok(documents)
+}
+
+/// Returns the obkv of all the documents, in the order of their ids.
+pub fn all_documents<'t>(
+    index: &milli::Index,
+    rtxn: &'t RoTxn,
+) -> Result<impl Iterator<Item = Result<(DocumentId, Vec<u8>)>> + 't> {
+    let mut decoder = DocumentDecoder::new(index, rtxn)?;
+    let stored = index.documents.remap_data_type::<ByteSlice>().iter(rtxn)?;
+
+    Ok(stored.map(move |entry| {
+        let (id, bytes) = entry?;
+        Ok((id.get(), decoder.decode(bytes)?.into_owned()))
+    }))
+}
+
+fn compressed_documents(index: &milli::Index, rtxn: &RoTxn) -> Result<RoaringBitmap> {
+    match index
+        .main
+        .get::<_, Str, ByteSlice>(rtxn, main_key::COMPRESSED_DOCUMENTS)?
+    {
+        Some(bytes) => Ok(RoaringBitmap::deserialize_from(bytes)?),
+        None => Ok(RoaringBitmap::new()),
+    }
+}
+
+fn put_compressed_documents(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    docids: &RoaringBitmap,
+) -> Result<()> {
+    let mut bytes = Vec::new();
+    docids.serialize_into(&mut bytes)?;
+    index
+        .main
+        .put::<_, Str, ByteSlice>(txn, main_key::COMPRESSED_DOCUMENTS, &bytes)?;
+
+    Ok(())
+}
+
+pub fn has_compressed_documents(index: &milli::Index, rtxn: &RoTxn) -> Result<bool> {
+    Ok(!compressed_documents(index, rtxn)?.is_empty())
+}
+
+/// Returns the ids of the stored documents replaced or updated by the documents of the batch.
+pub fn replaced_documents(
+    index: &milli::Index,
+    rtxn: &RoTxn,
+    mut reader: DocumentBatchReader<File>,
+) -> Result<RoaringBitmap> {
+    let mut docids = RoaringBitmap::new();
+    let primary_key = match index.primary_key(rtxn)? {
+        Some(primary_key) => primary_key.to_string(),
+        None => return Ok(docids),
+    };
+    let external_ids = index.external_documents_ids(rtxn)?;
+
+    while let Some((fields, obkv)) = reader.next_document_with_index()? {
+        let content = obkv.iter().find(|(field_id, _)| {
+            fields
+                .name(*field_id)
+                .map_or(false, |name| name == primary_key.as_str())
+        });
+        let id = match content {
+            Some((_, content)) => match serde_json::from_slice(content)? {
+                Value::String(id) => id,
+                Value::Number(id) => id.to_string(),
+                // the invalid ids are reported by milli.
+                _ => continue,
+            },
+            None => continue,
+        };
+        if let Some(docid) = external_ids.get(id.as_bytes()) {
+            docids.insert(docid);
+        }
+    }
+
+    Ok(docids)
+}
+
+/// Decompresses the given documents in place, for milli to be able to read them during an
+/// update.
+pub fn decompress_documents(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    docids: &RoaringBitmap,
+) -> Result<()> {
+    let mut compressed = compressed_documents(index, txn)?;
+    let docids = docids & &compressed;
+    if docids.is_empty() {
+        return Ok(());
+    }
+
+    let mut decoder = DocumentDecoder::new(index, txn)?;
+    let stored = index.documents.remap_data_type::<ByteSlice>();
+    for id in &docids {
+        let key = BEU32::new(id);
+        let bytes = match stored.get(txn, &key)? {
+            Some(bytes) => decoder.decode(bytes)?.into_owned(),
+            None => continue,
+        };
+        stored.put(txn, &key, &bytes)?;
+    }
+
+    compressed -= docids;
+    put_compressed_documents(index, txn, &compressed)
+}
+
+/// Compresses the documents written by milli during the update when the index compresses its
+/// documents, or decompresses all of them when the compression was disabled.
+pub fn sync_documents(index: &milli::Index, txn: &mut RwTxn) -> Result<()> {
+    let documents_ids = index.documents_ids(txn)?;
+    let compressed = compressed_documents(index, txn)? & &documents_ids;
+
+    if custom_settings::document_compression(index, txn)? == DocumentCompression::None {
+        decompress_documents(index, txn, &compressed)?;
+        index.main.delete::<_, Str>(txn, main_key::DICTIONARY)?;
+        index
+            .main
+            .delete::<_, Str>(txn, main_key::COMPRESSED_DOCUMENTS)?;
+        return Ok(());
+    }
+
+    let uncompressed = documents_ids - &compressed;
+    if uncompressed.is_empty() {
+        return put_compressed_documents(index, txn, &compressed);
+    }
+
+    // The dictionary can only be replaced when no document is compressed with it.
+    if compressed.is_empty() {
+        let dictionary = train_dictionary(index, txn, &uncompressed)?;
+        index
+            .main
+            .put::<_, Str, ByteSlice>(txn, main_key::DICTIONARY, &dictionary)?;
+    }
+
+    let dictionary = index
+        .main
+        .get::<_, Str, ByteSlice>(txn, main_key::DICTIONARY)?
+        .unwrap_or_default()
+        .to_vec();
+    let mut compressor = Compressor::with_dictionary(COMPRESSION_LEVEL, &dictionary)?;
+    let stored = index.documents.remap_data_type::<ByteSlice>();
+    for id in &uncompressed {
+        let key = BEU32::new(id);
+        let bytes = match stored.get(txn, &key)? {
+            Some(bytes) if compressed_content(bytes).is_none() => bytes.to_vec(),
+            _ => continue,
+        };
+        let document = compress_document(&mut compressor, &bytes)?;
+        stored.put(txn, &key, &document)?;
+    }
+
+    put_compressed_documents(index, txn, &(compressed | uncompressed))
+}
+
+fn train_dictionary(index: &milli::Index, rtxn: &RoTxn, docids: &RoaringBitmap) -> Result<Vec<u8>> {
+    let stored = index.documents.remap_data_type::<ByteSlice>();
+    let mut samples = Vec::new();
+    for id in docids.iter().take(DICTIONARY_SAMPLES) {
+        if let Some(bytes) = stored.get(rtxn, &BEU32::new(id))? {
+            samples.push(bytes.to_vec());
+        }
+    }
+
+    // zstd can't train a dictionary on too few documents, they are then compressed without one.
+    Ok(zstd::dict::from_samples(&samples, DICTIONARY_MAX_SIZE).unwrap_or_default())
+}
+
+/// Returns the sizes of the stored documents when the index compresses its documents.
+pub fn stats(index: &milli::Index, rtxn: &RoTxn) -> Result<Option<DocumentCompressionStats>> {
+    if custom_settings::document_compression(index, rtxn)? == DocumentCompression::None {
+        return Ok(None);
+    }
+
+    let mut stats = DocumentCompressionStats {
+        compressed_documents: compressed_documents(index, rtxn)?.len(),
+        stored_bytes: 0,
+        uncompressed_bytes: 0,
+        dictionary_bytes: index
+            .main
+            .get::<_, Str, ByteSlice>(rtxn, main_key::DICTIONARY)?
+            .map_or(0, |dictionary| dictionary.len() as u64),
+    };
+    for entry in index.documents.remap_data_type::<ByteSlice>().iter(rtxn)? {
+        let (_, bytes) = entry?;
+        stats.stored_bytes += bytes.len() as u64;
+        stats.uncompressed_bytes += match compressed_content(bytes) {
+            Some(content) if content.len() >= 4 => {
+                u32::from_be_bytes([content[0], content[1], content[2], content[3]]) as u64
+            }
+            _ => bytes.len() as u64,
+        };
+    }
+
+    Ok(Some(stats))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn document(fields: &[(FieldId, &str)]) -> Vec<u8> {
+        let mut writer = KvWriterU16::memory();
+        for (field_id, value) in fields {
+            writer.insert(*field_id, value.as_bytes()).unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    fn compressed_documents_are_decoded() {
+        let original = document(&[
+            (0, "1"),
+            (1, r#""a long and repeated text, repeated text""#),
+        ]);
+
+        let mut compressor = Compressor::with_dictionary(COMPRESSION_LEVEL, &[]).unwrap();
+        let compressed = compress_document(&mut compressor, &original).unwrap();
+        assert!(compressed_content(&compressed).is_some());
+
+        let mut decoder = DocumentDecoder {
+            decompressor: Decompressor::with_dictionary(&[]).unwrap(),
+        };
+        assert_eq!(decoder.decode(&compressed).unwrap().as_ref(), original);
+    }
+
+    #[test]
+    fn uncompressed_documents_are_kept() {
+        let mut decoder = DocumentDecoder {
+            decompressor: Decompressor::with_dictionary(&[]).unwrap(),
+        };
+
+        for original in [
+            document(&[(0, "1"), (1, "2")]),
+            document(&[(0, "1")]),
+            document(&[]),
+        ] {
+            assert!(compressed_content(&original).is_none());
+            assert!(matches!(
+                decoder.decode(&original).unwrap(),
+                Cow::Borrowed(bytes) if bytes == original
+            ));
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Settings that are not handled by milli are stored next to the milli settings, in the main
+//! database of the index, under keys of their own.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use milli::heed::types::{SerdeJson, Str};
+use milli::heed::{RoTxn, RwTxn};
+use milli::update::Setting;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::error::{IndexError, Result};
+use super::stop_words::{preset_languages, preset_words};
+use super::updates::{
+    DocumentCompression, FacetingSettings, FormattingSettings, LocalizedAttributesRule, Ruleset,
+    Settings, StopWords, StopWordsPresets,
+};
+use super::Checked;
+
+mod main_key {
+    pub const LOCALIZED_ATTRIBUTES: &str = "meilisearch-localized-attributes";
+    pub const SEPARATOR_TOKENS: &str = "meilisearch-separator-tokens";
+    pub const NON_SEPARATOR_TOKENS: &str = "meilisearch-non-separator-tokens";
+    pub const DICTIONARY: &str = "meilisearch-dictionary";
+    pub const FORMATTING: &str = "meilisearch-formatting";
+    pub const FACETING: &str = "meilisearch-faceting";
+    pub const VERSION_FIELD: &str = "meilisearch-version-field";
+    pub const DOCUMENT_COMPRESSION: &str = "meilisearch-document-compression";
+    pub const RULESETS: &str = "meilisearch-rulesets";
+    pub const STOP_WORDS_PRESETS: &str = "meilisearch-stop-words-presets";
+}
+
+/// The locales that can be assigned to attributes and queries, as ISO 639-3 codes. These are the
+/// languages the tokenizer is able to recognize.
+pub const SUPPORTED_LOCALES: &[&str] = &[
+    "afr", "aka", "amh", "ara", "aze", "bel", "ben", "bul", "cat", "ces", "cmn", "dan", "deu",
+    "ell", "eng", "epo", "est", "fin", "fra", "guj", "heb", "hin", "hrv", "hun", "hye", "ind",
+    "ita", "jav", "jpn", "kan", "kat", "khm", "kor", "lat", "lav", "lit", "mal", "mar", "mkd",
+    "mya", "nep", "nld", "nob", "ori", "pan", "pes", "pol", "por", "ron", "rus", "sin", "slk",
+    "slv", "sna", "spa", "srp", "swe", "tam", "tel", "tgl", "tha", "tuk", "tur", "ukr", "urd",
+    "uzb", "vie", "yid", "zul",
+];
+
+/// Returns the first locale that is not supported, if any.
+pub fn find_unsupported_locale<'a>(
+    locales: impl IntoIterator<Item = &'a String>,
+) -> Option<&'a String> {
+    locales
+        .into_iter()
+        .find(|locale| !SUPPORTED_LOCALES.contains(&locale.as_str()))
+}
+
+pub fn localized_attributes(
+    index: &milli::Index,
+    txn: &RoTxn,
+) -> Result<Option<Vec<LocalizedAttributesRule>>> {
+    get(index, txn, main_key::LOCALIZED_ATTRIBUTES)
+}
+
+pub fn separator_tokens(index: &milli::Index, txn: &RoTxn) -> Result<Option<BTreeSet<String>>> {
+    get(index, txn, main_key::SEPARATOR_TOKENS)
+}
+
+pub fn non_separator_tokens(
+    index: &milli::Index,
+    txn: &RoTxn,
+) -> Result<Option<BTreeSet<String>>> {
+    get(index, txn, main_key::NON_SEPARATOR_TOKENS)
+}
+
+pub fn dictionary(index: &milli::Index, txn: &RoTxn) -> Result<Option<BTreeSet<String>>> {
+    get(index, txn, main_key::DICTIONARY)
+}
+
+/// The formatting parameters set on the index, the others have their default value.
+pub fn formatting(index: &milli::Index, txn: &RoTxn) -> Result<FormattingSettings> {
+    Ok(get(index, txn, main_key::FORMATTING)?.unwrap_or_default())
+}
+
+/// The faceting parameters set on the index, the others have their default value.
+pub fn faceting(index: &milli::Index, txn: &RoTxn) -> Result<FacetingSettings> {
+    Ok(get(index, txn, main_key::FACETING)?.unwrap_or_default())
+}
+
+pub fn version_field(index: &milli::Index, txn: &RoTxn) -> Result<Option<String>> {
+    get(index, txn, main_key::VERSION_FIELD)
+}
+
+pub fn document_compression(index: &milli::Index, txn: &RoTxn) -> Result<DocumentCompression> {
+    Ok(get(index, txn, main_key::DOCUMENT_COMPRESSION)?.unwrap_or_default())
+}
+
+pub fn rulesets(index: &milli::Index, txn: &RoTxn) -> Result<BTreeMap<String, Ruleset>> {
+    Ok(get(index, txn, main_key::RULESETS)?.unwrap_or_default())
+}
+
+/// The presets of the stop words, milli only knowing the words they expand to.
+pub fn stop_words_presets(index: &milli::Index, txn: &RoTxn) -> Result<Option<StopWordsPresets>> {
+    get(index, txn, main_key::STOP_WORDS_PRESETS)
+}
+
+/// Whether the settings change the way the documents are segmented, in which case they must be
+/// reindexed.
+pub fn tokenization_updated(settings: &Settings<Checked>) -> bool {
+    !(settings.localized_attributes.is_not_set()
+        && settings.separator_tokens.is_not_set()
+        && settings.non_separator_tokens.is_not_set()
+        && settings.dictionary.is_not_set())
+}
+
+/// Check the custom settings, that are not validated by milli when it applies the settings.
+pub fn check_custom_settings(settings: &Settings<Checked>) -> Result<()> {
+    if let Setting::Set(ref rules) = settings.localized_attributes {
+        let locales = rules.iter().flat_map(|rule| rule.locales.iter());
+        if let Some(locale) = find_unsupported_locale(locales) {
+            return Err(IndexError::InvalidLocalizedAttributes(locale.clone()));
+        }
+    }
+
+    let tokens = [
+        &settings.separator_tokens,
+        &settings.non_separator_tokens,
+        &settings.dictionary,
+    ];
+    let empty_token = tokens
+        .iter()
+        .filter_map(|setting| setting.as_ref().set())
+        .any(|tokens| tokens.iter().any(|token| token.is_empty()));
+    if empty_token {
+        return Err(IndexError::EmptyTokenizationToken);
+    }
+
+    if let Setting::Set(StopWords::Presets(ref presets)) = settings.stop_words {
+        if let Some(language) = presets
+            .preset
+            .iter()
+            .find(|language| preset_words(language).is_none())
+        {
+            let languages: Vec<_> = preset_languages().collect();
+            return Err(IndexError::InvalidStopWordsPreset(format!(
+                "There is no stop words preset for `{}`. Available presets are: `{}`.",
+                language,
+                languages.join(", ")
+            )));
+        }
+    }
+
+    if let Setting::Set(ref rulesets) = settings.rulesets {
+        let invalid_name = rulesets.keys().find(|name| {
+            name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        });
+        if let Some(name) = invalid_name {
+            return Err(IndexError::InvalidRulesets(format!(
+                "`{}` is not a valid ruleset name. A ruleset name can only contain alphanumeric characters, hyphens (-) and underscores (_).",
+                name
+            )));
+        }
+
+        let traffic: u32 = rulesets
+            .values()
+            .map(|ruleset| u32::from(ruleset.traffic))
+            .sum();
+        if traffic > 100 {
+            return Err(IndexError::InvalidRulesets(format!(
+                "The rulesets are served to {}% of the traffic, which can't exceed 100%.",
+                traffic
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Persist the custom settings in the transaction, this must be called along with the milli
+/// settings update.
+pub fn apply_custom_settings(
+    settings: &Settings<Checked>,
+    index: &milli::Index,
+    txn: &mut RwTxn,
+) -> Result<()> {
+    apply(
+        index,
+        txn,
+        main_key::LOCALIZED_ATTRIBUTES,
+        &settings.localized_attributes,
+    )?;
+    apply(
+        index,
+        txn,
+        main_key::SEPARATOR_TOKENS,
+        &settings.separator_tokens,
+    )?;
+    apply(
+        index,
+        txn,
+        main_key::NON_SEPARATOR_TOKENS,
+        &settings.non_separator_tokens,
+    )?;
+    apply(index, txn, main_key::DICTIONARY, &settings.dictionary)?;
+
+    // the formatting parameters are updated one by one, like the typo tolerance.
+    match settings.formatting {
+        Setting::Set(ref update) => {
+            let mut current = formatting(index, txn)?;
+            current.merge(update);
+            apply(index, txn, main_key::FORMATTING, &Setting::Set(current))?;
+        }
+        ref otherwise => apply(index, txn, main_key::FORMATTING, otherwise)?,
+    }
+
+    match settings.faceting {
+        Setting::Set(ref update) => {
+            let mut current = faceting(index, txn)?;
+            current.merge(update);
+            apply(index, txn, main_key::FACETING, &Setting::Set(current))?;
+        }
+        ref otherwise => apply(index, txn, main_key::FACETING, otherwise)?,
+    }
+
+    apply(index, txn, main_key::VERSION_FIELD, &settings.version_field)?;
+    apply(
+        index,
+        txn,
+        main_key::DOCUMENT_COMPRESSION,
+        &settings.document_compression,
+    )?;
+    apply(index, txn, main_key::RULESETS, &settings.rulesets)?;
+
+    // the stop words set as a list of words drop the presets.
+    let presets = match settings.stop_words {
+        Setting::Set(StopWords::Presets(ref presets)) => Setting::Set(presets),
+        Setting::Set(StopWords::Words(_)) | Setting::Reset => Setting::Reset,
+        Setting::NotSet => Setting::NotSet,
+    };
+    apply(index, txn, main_key::STOP_WORDS_PRESETS, &presets)?;
+
+    Ok(())
+}
+
+fn get<T: DeserializeOwned>(index: &milli::Index, txn: &RoTxn, key: &str) -> Result<Option<T>> {
+    Ok(index.main.get::<_, Str, SerdeJson<T>>(txn, key)?)
+}
+
+fn apply<T: Serialize>(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    key: &str,
+    setting: &Setting<T>,
+) -> Result<()> {
+    match setting {
+        Setting::Set(value) => index.main.put::<_, Str, SerdeJson<T>>(txn, key, value)?,
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, key)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    Ok(())
+}
@@ -6,13 +6,17 @@ use anyhow::Context;
 use indexmap::IndexMap;
 use milli::documents::DocumentBatchReader;
 use milli::heed::{EnvOpenOptions, RoTxn};
-use milli::update::{IndexDocumentsConfig, IndexerConfig};
+use milli::update::{IndexDocumentsConfig, IndexDocumentsMethod, IndexerConfig};
+use obkv::KvReaderU16;
 use serde::{Deserialize, Serialize};
 
 use crate::document_formats::read_ndjson;
 use crate::index::updates::apply_settings_to_builder;
 
+use super::compression;
+use super::custom_settings::apply_custom_settings;
 use super::error::Result;
+use super::tokenization::{DocumentRewriter, OriginalDocuments};
 use super::{index::Index, Settings, Unchecked};
 
 #[derive(Serialize, Deserialize)]
@@ -42,15 +46,15 @@ impl Index {
         let document_file_path = path.as_ref().join(DATA_FILE_NAME);
         let mut document_file = File::create(&document_file_path)?;
 
-        let documents = self.all_documents(txn)?;
+        let documents = compression::all_documents(self, txn)?;
         let fields_ids_map = self.fields_ids_map(txn)?;
 
         // dump documents
         let mut json_map = IndexMap::new();
         for document in documents {
-            let (_, reader) = document?;
+            let (_, document) = document?;
 
-            for (fid, bytes) in reader.iter() {
+            for (fid, bytes) in KvReaderU16::new(&document).iter() {
                 if let Some(name) = fields_ids_map.name(fid) {
                     json_map.insert(name, serde_json::from_slice::<serde_json::Value>(bytes)?);
                 }
@@ -120,6 +124,8 @@ impl Index {
 
         builder.execute(|_| ())?;
 
+        apply_custom_settings(&settings, &index, &mut txn)?;
+
         let document_file_path = src.as_ref().join(DATA_FILE_NAME);
         let reader = BufReader::new(File::open(&document_file_path)?);
 
@@ -136,6 +142,15 @@ impl Index {
             tmp_doc_file.seek(SeekFrom::Start(0))?;
 
             let documents_reader = DocumentBatchReader::from_reader(tmp_doc_file)?;
+            let rewriter = DocumentRewriter::new(&index, &txn, None)?;
+            let (documents_reader, original_reader) = match rewriter {
+                Some(ref rewriter) => {
+                    let (rewritten, original) =
+                        rewriter.rewrite_batch(documents_reader, indexer_config)?;
+                    (rewritten, Some(original))
+                }
+                None => (documents_reader, None),
+            };
 
             //If the document file is empty, we don't perform the document addition, to prevent
             //a primary key error to be thrown.
@@ -149,8 +164,20 @@ impl Index {
             )?;
             builder.add_documents(documents_reader)?;
             builder.execute()?;
+
+            if let (Some(rewriter), Some(original_reader)) = (rewriter, original_reader) {
+                rewriter.restore_documents(
+                    &index,
+                    &mut txn,
+                    IndexDocumentsMethod::ReplaceDocuments,
+                    vec![original_reader],
+                    OriginalDocuments::new()?,
+                )?;
+            }
         }
 
+        compression::sync_documents(&index, &mut txn)?;
+
         txn.commit()?;
         index.prepare_for_closing().wait();
 
@@ -13,6 +13,26 @@ pub enum IndexError {
     Internal(Box<dyn Error + Send + Sync + 'static>),
     #[error("Document `{0}` not found.")]
     DocumentNotFound(String),
+    #[error("Attribute `{0}` is not filterable and thus, cannot be used as distinct attribute. Available filterable attributes are: `{}`.", .1.join(", "))]
+    InvalidDistinctAttribute(String, Vec<String>),
+    #[error("Invalid locale `{0}` in `localizedAttributes`. Locales must be ISO 639-3 language codes supported by the tokenizer, such as `eng`, `fra`, `cmn` or `jpn`.")]
+    InvalidLocalizedAttributes(String),
+    #[error("Invalid locale `{0}` in `locales`. Locales must be ISO 639-3 language codes supported by the tokenizer, such as `eng`, `fra`, `cmn` or `jpn`.")]
+    InvalidSearchLocales(String),
+    #[error("The API key is not allowed to retrieve the attribute `{0}`.")]
+    UnauthorizedAttributeToRetrieve(String),
+    #[error("The tokens of `separatorTokens`, `nonSeparatorTokens` and `dictionary` can't be empty strings.")]
+    EmptyTokenizationToken,
+    #[error("{0}")]
+    InvalidRulesets(String),
+    #[error("{0}")]
+    InvalidStopWordsPreset(String),
+    #[error(
+        "Ruleset `{0}` not found. The rulesets of an index are defined by its `rulesets` setting."
+    )]
+    InvalidSearchRuleset(String),
+    #[error("{0}")]
+    InvalidSearchBoost(String),
     #[error("{0}")]
     Facet(#[from] FacetError),
     #[error("{0}")]
@@ -25,7 +45,8 @@ internal_error!(
     fst::Error,
     serde_json::Error,
     update_file_store::UpdateFileStoreError,
-    milli::documents::Error
+    milli::documents::Error,
+    csv::Error
 );
 
 impl ErrorCode for IndexError {
@@ -33,6 +54,17 @@ impl ErrorCode for IndexError {
         match self {
             IndexError::Internal(_) => Code::Internal,
             IndexError::DocumentNotFound(_) => Code::DocumentNotFound,
+            IndexError::InvalidDistinctAttribute(_, _) => Code::InvalidSearchDistinct,
+            IndexError::InvalidLocalizedAttributes(_) => Code::InvalidLocalizedAttributes,
+            IndexError::InvalidSearchLocales(_) => Code::InvalidSearchLocales,
+            IndexError::UnauthorizedAttributeToRetrieve(_) => {
+                Code::UnauthorizedAttributesToRetrieve
+            }
+            IndexError::EmptyTokenizationToken => Code::InvalidTokenizationSettings,
+            IndexError::InvalidRulesets(_) => Code::InvalidRulesets,
+            IndexError::InvalidStopWordsPreset(_) => Code::InvalidStopWordsPreset,
+            IndexError::InvalidSearchRuleset(_) => Code::InvalidSearchRuleset,
+            IndexError::InvalidSearchBoost(_) => Code::InvalidSearchBoost,
             IndexError::Facet(e) => e.error_code(),
             IndexError::Milli(e) => MilliError(e).error_code(),
         }
@@ -43,12 +75,21 @@ impl ErrorCode for IndexError {
 pub enum FacetError {
     #[error("Invalid syntax for the filter parameter: `expected {}, found: {1}`.", .0.join(", "))]
     InvalidExpression(&'static [&'static str], Value),
+    #[error("Invalid syntax for the filter parameter: `expected a non-empty list of values between brackets after IN, found: {0}`.")]
+    InvalidInExpression(String),
+    #[error("Invalid syntax for the filter parameter: `expected a condition of the form field EXISTS, field IS NULL or field IS EMPTY, found: {0}`.")]
+    InvalidExistenceCondition(String),
+    #[error("Attribute `{0}` is not filterable. Available filterable attributes are: `{}`.", .1.join(", "))]
+    AttributeNotFilterable(String, Vec<String>),
 }
 
 impl ErrorCode for FacetError {
     fn error_code(&self) -> Code {
         match self {
-            FacetError::InvalidExpression(_, _) => Code::Filter,
+            FacetError::InvalidExpression(_, _)
+            | FacetError::InvalidInExpression(_)
+            | FacetError::InvalidExistenceCondition(_)
+            | FacetError::AttributeNotFilterable(_, _) => Code::Filter,
         }
     }
 }
@@ -0,0 +1,163 @@
+//! Exports of the documents of an index. The matching documents are read from a single read
+//! transaction and serialized in chunks, handed to the caller one at a time so that the whole
+//! export never has to be held in memory.
+
+use milli::obkv_to_json;
+use obkv::KvReaderU16;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::compression;
+use super::error::Result;
+use super::filter::split_existence_conditions;
+use super::index::Index;
+use super::search::parse_filter;
+
+/// The number of documents serialized in each chunk of an export.
+const EXPORT_CHUNK_SIZE: usize = 1000;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    /// One JSON document per line.
+    Ndjson,
+    /// A header with the displayed attributes, followed by one line per document. The arrays and
+    /// objects are written as JSON strings.
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Csv => "text/csv",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportQuery {
+    pub format: ExportFormat,
+    pub filter: Option<Value>,
+}
+
+impl Index {
+    /// Serializes the documents matching the filter of the query, all the documents without
+    /// filter, in the order of their internal ids. Each chunk is passed to `write`, which returns
+    /// `false` to stop the export early. The filter is checked before the first chunk is written.
+    pub fn export(&self, query: ExportQuery, mut write: impl FnMut(Vec<u8>) -> bool) -> Result<()> {
+        let rtxn = self.read_txn()?;
+
+        let mut documents_ids = self.documents_ids(&rtxn)?;
+        if let Some(ref filter) = query.filter {
+            let filter = split_existence_conditions(filter)?;
+            if let Some(allowed) = self.existence_documents(&rtxn, &filter.conditions)? {
+                documents_ids &= allowed;
+            }
+            if let Some(ref filter) = filter.filter {
+                if let Some(facets) = parse_filter(filter)? {
+                    documents_ids &= facets.evaluate(&rtxn, self)?;
+                }
+            }
+        }
+
+        let fields_ids_map = self.fields_ids_map(&rtxn)?;
+        let displayed: Vec<_> = match self.displayed_fields_ids(&rtxn)? {
+            Some(ids) => ids,
+            None => fields_ids_map.iter().map(|(id, _)| id).collect(),
+        };
+
+        let mut csv_header = (query.format == ExportFormat::Csv).then(|| {
+            displayed
+                .iter()
+                .filter_map(|id| fields_ids_map.name(*id))
+                .collect::<Vec<_>>()
+        });
+
+        let mut documents_ids = documents_ids.into_iter().peekable();
+        while documents_ids.peek().is_some() {
+            let ids = documents_ids.by_ref().take(EXPORT_CHUNK_SIZE);
+            let documents = compression::documents(self, &rtxn, ids)?;
+            let mut chunk = Vec::new();
+            match query.format {
+                ExportFormat::Ndjson => {
+                    for (_, document) in documents {
+                        let object =
+                            obkv_to_json(&displayed, &fields_ids_map, KvReaderU16::new(&document))?;
+                        serde_json::to_writer(&mut chunk, &object)?;
+                        chunk.push(b'\n');
+                    }
+                }
+                ExportFormat::Csv => {
+                    let mut writer = csv::Writer::from_writer(chunk);
+                    if let Some(header) = csv_header.take() {
+                        writer.write_record(&header)?;
+                    }
+                    for (_, document) in documents {
+                        let object =
+                            obkv_to_json(&displayed, &fields_ids_map, KvReaderU16::new(&document))?;
+                        writer.write_record(csv_record(&displayed, &fields_ids_map, &object))?;
+                    }
+                    chunk = writer.into_inner().map_err(|e| e.into_error())?;
+                }
+            }
+
+            if !write(chunk) {
+                return Ok(());
+            }
+        }
+
+        // The header of an export without documents is still written.
+        if let Some(header) = csv_header {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer.write_record(&header)?;
+            write(writer.into_inner().map_err(|e| e.into_error())?);
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the cells of a document in the order of the header, the missing attributes and the
+/// `null` values being empty cells.
+fn csv_record(
+    displayed: &[milli::FieldId],
+    fields_ids_map: &milli::FieldsIdsMap,
+    document: &Map<String, Value>,
+) -> Vec<String> {
+    displayed
+        .iter()
+        .filter_map(|id| fields_ids_map.name(*id))
+        .map(|name| match document.get(name) {
+            None | Some(Value::Null) => String::new(),
+            Some(Value::String(s)) => s.clone(),
+            Some(value) => value.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn csv_record_follows_the_header() {
+        let mut fields_ids_map = milli::FieldsIdsMap::new();
+        let id = fields_ids_map.insert("id").unwrap();
+        let title = fields_ids_map.insert("title").unwrap();
+        let tags = fields_ids_map.insert("tags").unwrap();
+        let missing = fields_ids_map.insert("missing").unwrap();
+
+        let document = serde_json::json!({
+            "tags": ["a", "b"],
+            "title": "hello, world",
+            "id": 1,
+        });
+        let record = csv_record(
+            &[id, title, tags, missing],
+            &fields_ids_map,
+            document.as_object().unwrap(),
+        );
+        assert_eq!(record, vec!["1", "hello, world", r#"["a","b"]"#, ""]);
+    }
+}
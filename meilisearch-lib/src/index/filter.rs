@@ -0,0 +1,845 @@
+//! The filter operators unknown to milli. The `IN` conditions are rewritten into the equivalent
+//! `OR` chains before the filter is parsed by milli, while the `EXISTS`, `IS NULL` and `IS EMPTY`
+//! conditions are evaluated on the documents ids stored for each filterable attribute, and
+//! applied on top of the results of the engine.
+
+use std::collections::HashSet;
+
+use milli::heed::types::{ByteSlice, DecodeIgnore, Str};
+use milli::heed::{RoTxn, RwTxn};
+use milli::{obkv_to_json, DocumentId, FieldsIdsMap, Filter};
+use obkv::KvReaderU16;
+use rayon::prelude::*;
+use roaring::RoaringBitmap;
+use serde_json::{Map, Value};
+
+use super::compression;
+use super::error::{FacetError, Result};
+use super::index::Index;
+
+/// The documents ids of the existence operators are stored in the main database of the index,
+/// under a key made of this prefix, the operator and the attribute.
+const EXISTENCE_KEY_PREFIX: &str = "meilisearch-existence-";
+
+/// The number of documents read at once when the documents of the existence operators are updated.
+/// The documents of a chunk are then matched in parallel, on the indexing thread pool.
+const EXISTENCE_CHUNK_SIZE: usize = 1000;
+
+/// The words that can't be attribute names in a filter, unless they are quoted.
+const KEYWORDS: &[&str] = &[
+    "AND", "OR", "NOT", "TO", "IN", "EXISTS", "IS", "NULL", "EMPTY",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Existence {
+    /// The attribute is in the document, whatever its value.
+    Exists,
+    /// The value of the attribute is `null`.
+    Null,
+    /// The value of the attribute is an empty string, array or object.
+    Empty,
+}
+
+impl Existence {
+    const ALL: [Existence; 3] = [Existence::Exists, Existence::Null, Existence::Empty];
+
+    fn name(self) -> &'static str {
+        match self {
+            Existence::Exists => "exists",
+            Existence::Null => "null",
+            Existence::Empty => "empty",
+        }
+    }
+
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            Existence::Exists => true,
+            Existence::Null => value.is_null(),
+            Existence::Empty => match value {
+                Value::String(s) => s.is_empty(),
+                Value::Array(values) => values.is_empty(),
+                Value::Object(object) => object.is_empty(),
+                _ => false,
+            },
+        }
+    }
+}
+
+/// A `field EXISTS`, `field IS NULL` or `field IS EMPTY` condition, possibly negated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExistenceCondition {
+    pub attribute: String,
+    pub existence: Existence,
+    pub negated: bool,
+}
+
+/// A condition of the filter containing existence conditions, which can be combined with the
+/// rest of the filter in any way. The parts without existence conditions are evaluated by milli.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExistenceExpression {
+    Condition(ExistenceCondition),
+    Milli(String),
+    And(Vec<ExistenceExpression>),
+    Or(Vec<ExistenceExpression>),
+    Not(Box<ExistenceExpression>),
+}
+
+/// A filter split into the part milli is able to parse, and the top-level conditions containing
+/// existence conditions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SplitFilter {
+    pub filter: Option<Value>,
+    pub conditions: Vec<ExistenceExpression>,
+}
+
+/// Rewrites the `IN` conditions of a filter, and extracts its top-level conditions containing
+/// existence conditions, either in the expressions or as elements of the array notation. The
+/// other conditions are left to milli.
+pub fn split_existence_conditions(filter: &Value) -> Result<SplitFilter> {
+    let mut conditions = Vec::new();
+    let filter = match filter {
+        Value::String(expr) => {
+            let rest = split_expression(expr, &mut conditions)?;
+            (conditions.is_empty() || !rest.is_empty()).then(|| Value::String(rest))
+        }
+        Value::Array(values) => {
+            let mut ands = Vec::new();
+            for value in values {
+                match value {
+                    Value::String(expr) => {
+                        let extracted = conditions.len();
+                        let rest = split_expression(expr, &mut conditions)?;
+                        if conditions.len() == extracted || !rest.is_empty() {
+                            ands.push(Value::String(rest));
+                        }
+                    }
+                    Value::Array(ors) => {
+                        let ors = ors
+                            .iter()
+                            .map(|value| match value {
+                                Value::String(expr) => Ok(Value::String(rewrite_in(expr)?)),
+                                value => Ok(value.clone()),
+                            })
+                            .collect::<Result<Vec<_>>>()?;
+                        let has_existence = ors
+                            .iter()
+                            .any(|value| value.as_str().map_or(false, contains_existence_operator));
+                        if has_existence {
+                            let ors = ors
+                                .iter()
+                                .map(|value| match value {
+                                    Value::String(expr) => parse_expression(expr),
+                                    value => Err(FacetError::InvalidExpression(
+                                        &["String"],
+                                        value.clone(),
+                                    )
+                                    .into()),
+                                })
+                                .collect::<Result<_>>()?;
+                            conditions.push(ExistenceExpression::Or(ors));
+                        } else {
+                            ands.push(Value::Array(ors));
+                        }
+                    }
+                    value => ands.push(value.clone()),
+                }
+            }
+            Some(Value::Array(ands))
+        }
+        value => Some(value.clone()),
+    };
+
+    Ok(SplitFilter { filter, conditions })
+}
+
+/// Rewrites the `IN` conditions of the expression, moves its top-level conditions containing
+/// existence conditions to `conditions`, and returns the rest of the expression.
+fn split_expression(expr: &str, conditions: &mut Vec<ExistenceExpression>) -> Result<String> {
+    let expr = rewrite_in(expr)?;
+    let mut rest = Vec::new();
+    for condition in split_conjunction(&expr) {
+        if contains_existence_operator(condition) {
+            conditions.push(parse_expression(condition)?);
+        } else {
+            rest.push(condition.trim());
+        }
+    }
+
+    Ok(rest.join(" AND "))
+}
+
+/// Parses an expression whose `IN` conditions are already rewritten. As in milli, `NOT` binds
+/// tighter than `AND`, which binds tighter than `OR`.
+fn parse_expression(expr: &str) -> Result<ExistenceExpression> {
+    let expr = expr.trim();
+    if !contains_existence_operator(expr) {
+        return Ok(ExistenceExpression::Milli(expr.to_string()));
+    }
+
+    let disjunction = split_top_level(expr, "OR");
+    if disjunction.len() > 1 {
+        let expressions = disjunction.into_iter().map(parse_expression);
+        return Ok(ExistenceExpression::Or(expressions.collect::<Result<_>>()?));
+    }
+    let conjunction = split_top_level(expr, "AND");
+    if conjunction.len() > 1 {
+        let expressions = conjunction.into_iter().map(parse_expression);
+        return Ok(ExistenceExpression::And(
+            expressions.collect::<Result<_>>()?,
+        ));
+    }
+    if let Some(condition) = parse_existence(expr) {
+        return Ok(ExistenceExpression::Condition(condition));
+    }
+
+    let tokens = tokenize(expr);
+    match (tokens.first(), tokens.last()) {
+        (Some(first), _) if first.is("NOT") => {
+            let expression = parse_expression(&expr[first.end()..])?;
+            Ok(ExistenceExpression::Not(Box::new(expression)))
+        }
+        (Some(first), Some(last)) if is_parenthesized(&tokens) => {
+            parse_expression(&expr[first.end()..last.start])
+        }
+        _ => Err(FacetError::InvalidExistenceCondition(expr.to_string()).into()),
+    }
+}
+
+/// Whether the tokens are a single group between parentheses.
+fn is_parenthesized(tokens: &[Token]) -> bool {
+    if !tokens.first().map_or(false, |t| t.is("(")) {
+        return false;
+    }
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        if token.is("(") {
+            depth += 1;
+        } else if token.is(")") {
+            depth -= 1;
+            if depth == 0 {
+                return i == tokens.len() - 1;
+            }
+        }
+    }
+    false
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Word,
+    Quoted,
+    Symbol,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: Kind,
+    text: &'a str,
+    start: usize,
+}
+
+impl Token<'_> {
+    fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    fn is(&self, text: &str) -> bool {
+        self.kind != Kind::Quoted && self.text == text
+    }
+
+    fn is_value(&self) -> bool {
+        self.kind != Kind::Symbol
+    }
+
+    fn is_attribute(&self) -> bool {
+        self.kind == Kind::Quoted || (self.kind == Kind::Word && !KEYWORDS.contains(&self.text))
+    }
+
+    fn unquoted(&self) -> &str {
+        match self.kind {
+            Kind::Quoted => {
+                let quote = &self.text[..1];
+                let text = &self.text[1..];
+                text.strip_suffix(quote).unwrap_or(text)
+            }
+            _ => self.text,
+        }
+    }
+}
+
+fn is_special(c: char) -> bool {
+    c.is_whitespace() || "()[],'\"=!<>".contains(c)
+}
+
+fn find_from(expr: &str, start: usize, pattern: impl Fn(char) -> bool) -> usize {
+    expr[start..]
+        .find(pattern)
+        .map_or(expr.len(), |i| start + i)
+}
+
+/// Splits an expression into words, quoted strings and symbols, as milli does.
+fn tokenize(expr: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while let Some(c) = expr[pos..].chars().next() {
+        let start = pos;
+        let (kind, end) = match c {
+            c if c.is_whitespace() => {
+                pos += c.len_utf8();
+                continue;
+            }
+            // An unclosed quote runs to the end of the expression, milli reports the error.
+            '\'' | '"' => {
+                let end = find_from(expr, start + 1, |next| next == c);
+                (Kind::Quoted, (end + 1).min(expr.len()))
+            }
+            '(' | ')' | '[' | ']' | ',' => (Kind::Symbol, start + 1),
+            '=' | '!' | '<' | '>' => (
+                Kind::Symbol,
+                find_from(expr, start, |c| !"=!<>".contains(c)),
+            ),
+            _ => (Kind::Word, find_from(expr, start, is_special)),
+        };
+        tokens.push(Token {
+            kind,
+            text: &expr[start..end],
+            start,
+        });
+        pos = end;
+    }
+    tokens
+}
+
+/// Rewrites the `field IN [a, b]` conditions of an expression into `(field = a OR field = b)`,
+/// and the `field NOT IN [a, b]` ones into `(field != a AND field != b)`.
+fn rewrite_in(expr: &str) -> Result<String> {
+    let tokens = tokenize(expr);
+    let invalid = || FacetError::InvalidInExpression(expr.to_string());
+
+    let mut rewritten = String::new();
+    let mut copied = 0;
+    let mut i = 1;
+    while i < tokens.len() {
+        let negated = tokens[i].is("NOT") && tokens.get(i + 1).map_or(false, |t| t.is("IN"));
+        let keyword = if negated { i + 1 } else { i };
+        if !tokens[keyword].is("IN") || !tokens[i - 1].is_attribute() {
+            i += 1;
+            continue;
+        }
+
+        if !tokens.get(keyword + 1).map_or(false, |t| t.is("[")) {
+            return Err(invalid().into());
+        }
+        let mut values = Vec::new();
+        let mut j = keyword + 2;
+        let end = loop {
+            match (tokens.get(j), tokens.get(j + 1)) {
+                (Some(value), Some(next)) if value.is_value() && (next.is(",") || next.is("]")) => {
+                    values.push(value.text);
+                    j += 2;
+                    if next.is("]") {
+                        break next.end();
+                    }
+                }
+                _ => return Err(invalid().into()),
+            }
+        };
+
+        let field = tokens[i - 1];
+        let (operator, separator) = if negated {
+            ("!=", " AND ")
+        } else {
+            ("=", " OR ")
+        };
+        let conditions: Vec<_> = values
+            .iter()
+            .map(|value| format!("{} {} {}", field.text, operator, value))
+            .collect();
+
+        rewritten.push_str(&expr[copied..field.start]);
+        rewritten.push('(');
+        rewritten.push_str(&conditions.join(separator));
+        rewritten.push(')');
+        copied = end;
+        i = j + 1;
+    }
+    rewritten.push_str(&expr[copied..]);
+
+    Ok(rewritten)
+}
+
+/// Splits an expression on its top-level `AND`s. Since `AND` has precedence over `OR`, an
+/// expression with a top-level `OR` is a single condition.
+fn split_conjunction(expr: &str) -> Vec<&str> {
+    if split_top_level(expr, "OR").len() > 1 {
+        vec![expr]
+    } else {
+        split_top_level(expr, "AND")
+    }
+}
+
+/// Splits an expression on the given keyword, outside of the parentheses and brackets.
+fn split_top_level<'a>(expr: &'a str, keyword: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for token in tokenize(expr) {
+        if token.is("(") || token.is("[") {
+            depth += 1;
+        } else if token.is(")") || token.is("]") {
+            depth -= 1;
+        } else if depth == 0 && token.is(keyword) {
+            parts.push(&expr[start..token.start]);
+            start = token.end();
+        }
+    }
+    parts.push(&expr[start..]);
+    parts
+}
+
+/// Parses `[NOT] field [NOT] EXISTS`, `[NOT] field IS [NOT] NULL` and
+/// `[NOT] field IS [NOT] EMPTY` conditions.
+fn parse_existence(condition: &str) -> Option<ExistenceCondition> {
+    let tokens = tokenize(condition);
+    let (negated, tokens) = match tokens.split_first() {
+        Some((first, rest)) if first.is("NOT") => (true, rest),
+        _ => (false, &tokens[..]),
+    };
+    let (field, operator) = tokens.split_first()?;
+    if !field.is_attribute() || operator.iter().any(|t| t.kind != Kind::Word) {
+        return None;
+    }
+
+    let operator: Vec<_> = operator.iter().map(|t| t.text).collect();
+    let (existence, not) = match operator.as_slice() {
+        ["EXISTS"] => (Existence::Exists, false),
+        ["NOT", "EXISTS"] => (Existence::Exists, true),
+        ["IS", "NULL"] => (Existence::Null, false),
+        ["IS", "NOT", "NULL"] => (Existence::Null, true),
+        ["IS", "EMPTY"] => (Existence::Empty, false),
+        ["IS", "NOT", "EMPTY"] => (Existence::Empty, true),
+        _ => return None,
+    };
+
+    Some(ExistenceCondition {
+        attribute: field.unquoted().to_string(),
+        existence,
+        negated: negated != not,
+    })
+}
+
+fn contains_existence_operator(expr: &str) -> bool {
+    let tokens = tokenize(expr);
+    tokens.iter().enumerate().any(|(i, token)| {
+        token.is("EXISTS")
+            || (token.is("IS")
+                && tokens
+                    .get(i + 1)
+                    .map_or(false, |t| t.is("NULL") || t.is("EMPTY") || t.is("NOT")))
+    })
+}
+
+fn existence_key(existence: Existence, attribute: &str) -> String {
+    format!("{}{}-{}", EXISTENCE_KEY_PREFIX, existence.name(), attribute)
+}
+
+/// Collects the values of the attribute in the document. The attribute can be a nested field,
+/// whose values are looked up through the objects and arrays of objects of the document.
+fn values_at<'a>(document: &'a Map<String, Value>, attribute: &str, values: &mut Vec<&'a Value>) {
+    for (key, value) in document {
+        if key == attribute {
+            values.push(value);
+        } else if let Some(nested) = attribute
+            .strip_prefix(key.as_str())
+            .and_then(|rest| rest.strip_prefix('.'))
+        {
+            match value {
+                Value::Object(object) => values_at(object, nested, values),
+                Value::Array(array) => array
+                    .iter()
+                    .filter_map(Value::as_object)
+                    .for_each(|object| values_at(object, nested, values)),
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Adds the document to the documents of the existence operators it matches, for each filterable
+/// attribute.
+fn insert_existence_docids(
+    filterable: &[String],
+    docids: &mut [[RoaringBitmap; 3]],
+    id: DocumentId,
+    document: &Map<String, Value>,
+) {
+    for (attribute, attribute_docids) in filterable.iter().zip(docids) {
+        let mut values = Vec::new();
+        values_at(document, attribute, &mut values);
+        for (existence, docids) in Existence::ALL.iter().zip(attribute_docids) {
+            if values.iter().any(|value| existence.matches(value)) {
+                docids.insert(id);
+            }
+        }
+    }
+}
+
+/// Adds the documents of each existence operator of `other` to `docids`.
+fn merge_existence_docids(docids: &mut [[RoaringBitmap; 3]], other: Vec<[RoaringBitmap; 3]>) {
+    for (attribute_docids, other) in docids.iter_mut().zip(other) {
+        for (docids, other) in attribute_docids.iter_mut().zip(other) {
+            *docids |= other;
+        }
+    }
+}
+
+impl Index {
+    /// Returns the documents matching all the existence expressions, or `None` when there are no
+    /// expressions.
+    pub(super) fn existence_documents(
+        &self,
+        rtxn: &RoTxn,
+        conditions: &[ExistenceExpression],
+    ) -> Result<Option<RoaringBitmap>> {
+        if conditions.is_empty() {
+            return Ok(None);
+        }
+
+        let filterable = self.filterable_fields(rtxn)?;
+        let all = self.documents_ids(rtxn)?;
+        let mut documents = all.clone();
+        for expression in conditions {
+            documents &= self.evaluate_existence(rtxn, expression, &filterable, &all)?;
+        }
+
+        Ok(Some(documents))
+    }
+
+    fn evaluate_existence(
+        &self,
+        rtxn: &RoTxn,
+        expression: &ExistenceExpression,
+        filterable: &HashSet<String>,
+        all: &RoaringBitmap,
+    ) -> Result<RoaringBitmap> {
+        let documents = match expression {
+            ExistenceExpression::Condition(condition) => {
+                if !filterable.contains(&condition.attribute) {
+                    let mut filterable: Vec<_> = filterable.iter().cloned().collect();
+                    filterable.sort_unstable();
+                    return Err(FacetError::AttributeNotFilterable(
+                        condition.attribute.clone(),
+                        filterable,
+                    )
+                    .into());
+                }
+
+                let docids =
+                    self.existence_docids(rtxn, condition.existence, &condition.attribute)?;
+                if condition.negated {
+                    all - docids
+                } else {
+                    docids
+                }
+            }
+            ExistenceExpression::Milli(expr) => match Filter::from_str(expr)? {
+                Some(filter) => filter.evaluate(rtxn, self)?,
+                None => all.clone(),
+            },
+            ExistenceExpression::And(expressions) => {
+                let mut documents = all.clone();
+                for expression in expressions {
+                    documents &= self.evaluate_existence(rtxn, expression, filterable, all)?;
+                }
+                documents
+            }
+            ExistenceExpression::Or(expressions) => {
+                let mut documents = RoaringBitmap::new();
+                for expression in expressions {
+                    documents |= self.evaluate_existence(rtxn, expression, filterable, all)?;
+                }
+                documents
+            }
+            ExistenceExpression::Not(expression) => {
+                all - self.evaluate_existence(rtxn, expression, filterable, all)?
+            }
+        };
+
+        Ok(documents)
+    }
+
+    fn existence_docids(
+        &self,
+        rtxn: &RoTxn,
+        existence: Existence,
+        attribute: &str,
+    ) -> Result<RoaringBitmap> {
+        let key = existence_key(existence, attribute);
+        match self.main.get::<_, Str, ByteSlice>(rtxn, &key)? {
+            Some(bytes) => Ok(RoaringBitmap::deserialize_from(bytes)?),
+            None => Ok(RoaringBitmap::new()),
+        }
+    }
+
+    /// Returns the documents of each existence operator matched by the documents of a chunk. The
+    /// documents are converted and matched on the indexing thread pool, see `IndexerOpts`.
+    fn chunk_existence_docids(
+        &self,
+        filterable: &[String],
+        fields_ids_map: &FieldsIdsMap,
+        documents: &[(DocumentId, Vec<u8>)],
+    ) -> Result<Vec<[RoaringBitmap; 3]>> {
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let empty = || vec![<[RoaringBitmap; 3]>::default(); filterable.len()];
+        let scan = || {
+            documents
+                .par_iter()
+                .try_fold(empty, |mut docids, (id, document)| -> Result<_> {
+                    let obkv = KvReaderU16::new(document);
+                    let document = obkv_to_json(&all_fields, fields_ids_map, obkv)?;
+                    insert_existence_docids(filterable, &mut docids, *id, &document);
+                    Ok(docids)
+                })
+                .try_reduce(empty, |mut docids, other| {
+                    merge_existence_docids(&mut docids, other);
+                    Ok(docids)
+                })
+        };
+
+        match self.indexer_config.thread_pool {
+            Some(ref thread_pool) => thread_pool.install(scan),
+            None => scan(),
+        }
+    }
+
+    fn put_existence_docids(
+        &self,
+        txn: &mut RwTxn,
+        filterable: &[String],
+        docids: Vec<[RoaringBitmap; 3]>,
+    ) -> Result<()> {
+        for (attribute, attribute_docids) in filterable.iter().zip(docids) {
+            for (existence, docids) in Existence::ALL.iter().zip(attribute_docids) {
+                let mut bytes = Vec::new();
+                docids.serialize_into(&mut bytes)?;
+                let key = existence_key(*existence, attribute);
+                self.main.put::<_, Str, ByteSlice>(txn, &key, &bytes)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Computes the documents of each existence operator for the filterable attributes, by
+    /// scanning all the documents. It is only needed when the filterable attributes change, the
+    /// updates of the documents are handled by `update_existence_docids_of`.
+    pub(super) fn update_existence_docids(&self, txn: &mut RwTxn) -> Result<()> {
+        let stale_keys = {
+            let rtxn: &RoTxn = txn;
+            self.main
+                .prefix_iter::<_, Str, DecodeIgnore>(rtxn, EXISTENCE_KEY_PREFIX)?
+                .map(|entry| entry.map(|(key, _)| key.to_string()))
+                .collect::<milli::heed::Result<Vec<_>>>()?
+        };
+        for key in stale_keys {
+            self.main.delete::<_, Str>(txn, &key)?;
+        }
+
+        let filterable: Vec<_> = self.filterable_fields(txn)?.into_iter().collect();
+        if filterable.is_empty() {
+            return Ok(());
+        }
+
+        let mut docids = vec![<[RoaringBitmap; 3]>::default(); filterable.len()];
+        {
+            let rtxn: &RoTxn = txn;
+            let fields_ids_map = self.fields_ids_map(rtxn)?;
+            let mut entries = compression::all_documents(self, rtxn)?.peekable();
+            while entries.peek().is_some() {
+                let chunk = entries
+                    .by_ref()
+                    .take(EXISTENCE_CHUNK_SIZE)
+                    .collect::<Result<Vec<_>>>()?;
+                let chunk_docids =
+                    self.chunk_existence_docids(&filterable, &fields_ids_map, &chunk)?;
+                merge_existence_docids(&mut docids, chunk_docids);
+            }
+        }
+
+        self.put_existence_docids(txn, &filterable, docids)
+    }
+
+    /// Updates the documents of each existence operator for the documents added, updated or
+    /// deleted by an update, since milli doesn't keep track of them. Only these documents are
+    /// read again.
+    pub(super) fn update_existence_docids_of(
+        &self,
+        txn: &mut RwTxn,
+        updated: &RoaringBitmap,
+    ) -> Result<()> {
+        let filterable: Vec<_> = self.filterable_fields(txn)?.into_iter().collect();
+        if filterable.is_empty() || updated.is_empty() {
+            return Ok(());
+        }
+
+        let mut docids = Vec::with_capacity(filterable.len());
+        for attribute in &filterable {
+            let mut attribute_docids = <[RoaringBitmap; 3]>::default();
+            for (existence, docids) in Existence::ALL.iter().zip(&mut attribute_docids) {
+                *docids = self.existence_docids(txn, *existence, attribute)? - updated;
+            }
+            docids.push(attribute_docids);
+        }
+
+        {
+            let rtxn: &RoTxn = txn;
+            let fields_ids_map = self.fields_ids_map(rtxn)?;
+            // The deleted documents are not found, and stay out of the documents ids.
+            let mut ids = updated.iter().peekable();
+            while ids.peek().is_some() {
+                let chunk = ids.by_ref().take(EXISTENCE_CHUNK_SIZE);
+                let chunk = compression::documents(self, rtxn, chunk)?;
+                let chunk_docids =
+                    self.chunk_existence_docids(&filterable, &fields_ids_map, &chunk)?;
+                merge_existence_docids(&mut docids, chunk_docids);
+            }
+        }
+
+        self.put_existence_docids(txn, &filterable, docids)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn rewrite_in_conditions() {
+        assert_eq!(
+            rewrite_in("color IN [red, 'dark blue'] AND size > 2").unwrap(),
+            "(color = red OR color = 'dark blue') AND size > 2"
+        );
+        assert_eq!(
+            rewrite_in("NOT (color NOT IN [red,blue])").unwrap(),
+            "NOT ((color != red AND color != blue))"
+        );
+        // the quoted keywords are values.
+        assert_eq!(
+            rewrite_in("color = 'IN' OR in = 2").unwrap(),
+            "color = 'IN' OR in = 2"
+        );
+
+        assert!(rewrite_in("color IN red").is_err());
+        assert!(rewrite_in("color IN []").is_err());
+        assert!(rewrite_in("color IN [red, blue").is_err());
+    }
+
+    #[test]
+    fn split_existence_conditions_of_an_expression() {
+        let split = split_existence_conditions(&json!(
+            "color EXISTS AND size > 2 AND NOT 'release date' IS NULL AND tags IS NOT EMPTY"
+        ))
+        .unwrap();
+        assert_eq!(split.filter, Some(json!("size > 2")));
+        assert_eq!(
+            split.conditions,
+            [
+                ExistenceCondition {
+                    attribute: "color".to_string(),
+                    existence: Existence::Exists,
+                    negated: false,
+                },
+                ExistenceCondition {
+                    attribute: "release date".to_string(),
+                    existence: Existence::Null,
+                    negated: true,
+                },
+                ExistenceCondition {
+                    attribute: "tags".to_string(),
+                    existence: Existence::Empty,
+                    negated: true,
+                },
+            ]
+        );
+
+        let split = split_existence_conditions(&json!("color NOT EXISTS")).unwrap();
+        assert_eq!(split.filter, None);
+        assert!(split.conditions[0].negated);
+
+        // the filters without existence conditions are left untouched.
+        let split = split_existence_conditions(&json!("color = red OR size > 2")).unwrap();
+        assert_eq!(split.filter, Some(json!("color = red OR size > 2")));
+        assert!(split.conditions.is_empty());
+    }
+
+    #[test]
+    fn split_existence_conditions_of_an_array() {
+        let split =
+            split_existence_conditions(&json!(["color EXISTS", ["size IN [1, 2]", "size > 5"]]))
+                .unwrap();
+        assert_eq!(
+            split.filter,
+            Some(json!([["(size = 1 OR size = 2)", "size > 5"]]))
+        );
+        assert_eq!(split.conditions.len(), 1);
+    }
+
+    #[test]
+    fn nested_existence_conditions() {
+        let split = split_existence_conditions(&json!(
+            "size > 2 AND (color EXISTS OR NOT (tags IS EMPTY AND size = 3))"
+        ))
+        .unwrap();
+        assert_eq!(split.filter, Some(json!("size > 2")));
+        assert_eq!(
+            split.conditions,
+            [ExistenceExpression::Or(vec![
+                ExistenceExpression::Condition(ExistenceCondition {
+                    attribute: "color".to_string(),
+                    existence: Existence::Exists,
+                    negated: false,
+                }),
+                ExistenceExpression::Not(Box::new(ExistenceExpression::And(vec![
+                    ExistenceExpression::Condition(ExistenceCondition {
+                        attribute: "tags".to_string(),
+                        existence: Existence::Empty,
+                        negated: false,
+                    }),
+                    ExistenceExpression::Milli("size = 3".to_string()),
+                ]))),
+            ])]
+        );
+
+        let split = split_existence_conditions(&json!([["color IS NULL", "size > 2"]])).unwrap();
+        assert_eq!(split.filter, Some(json!([])));
+        assert!(
+            matches!(split.conditions[..], [ExistenceExpression::Or(ref ors)] if ors.len() == 2)
+        );
+
+        assert!(split_existence_conditions(&json!("color EXISTS size")).is_err());
+        assert!(split_existence_conditions(&json!("(color IS NULL")).is_err());
+    }
+
+    #[test]
+    fn values_of_nested_attributes() {
+        let document = json!({
+            "doggos": [{ "name": "bobby", "age": null }, { "name": "buddy" }],
+            "info.color": "",
+        });
+        let document = document.as_object().unwrap();
+
+        let mut values = Vec::new();
+        values_at(document, "doggos.age", &mut values);
+        assert_eq!(values, [&Value::Null]);
+
+        let mut values = Vec::new();
+        values_at(document, "info.color", &mut values);
+        assert_eq!(values, [&json!("")]);
+
+        let mut values = Vec::new();
+        values_at(document, "doggos.color", &mut values);
+        assert!(values.is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fs::create_dir_all;
 use std::marker::PhantomData;
 use std::ops::Deref;
@@ -6,9 +6,11 @@ use std::path::Path;
 use std::sync::Arc;
 
 use fst::IntoStreamer;
-use milli::heed::{EnvOpenOptions, RoTxn};
+use milli::heed::types::ByteSlice;
+use milli::heed::{Database, EnvOpenOptions, RoTxn};
 use milli::update::{IndexerConfig, Setting};
 use milli::{obkv_to_json, FieldDistribution, FieldId};
+use obkv::KvReaderU16;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use time::OffsetDateTime;
@@ -16,9 +18,13 @@ use uuid::Uuid;
 
 use crate::EnvSizer;
 
+use super::compression::{self, DocumentCompressionStats, DocumentDecoder};
+use super::custom_settings;
 use super::error::IndexError;
 use super::error::Result;
-use super::updates::{MinWordSizeTyposSetting, TypoSettings};
+use super::updates::{
+    FacetingSettings, FormattingSettings, MinWordSizeTyposSetting, StopWords, TypoSettings,
+};
 use super::{Checked, Settings};
 
 pub type Document = Map<String, Value>;
@@ -62,6 +68,35 @@ pub struct IndexStats {
     /// later set to either true or false, we we retrieve the information from the `UpdateStore`
     pub is_indexing: Option<bool>,
     pub field_distribution: FieldDistribution,
+    /// The sizes of the stored documents, when the index compresses them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_compression: Option<DocumentCompressionStats>,
+    /// The storage breakdown of the index, only computed for the stats of a single index.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<IndexStorageStats>,
+    /// The last task successfully applied to the index, set along with the storage breakdown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_applied_task: Option<AppliedTask>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexStorageStats {
+    /// The size of the LMDB environment of the index on disk, in bytes.
+    pub size_on_disk: u64,
+    pub number_of_words: u64,
+    /// The size of the keys and values of each facet database, in bytes.
+    pub facet_databases: BTreeMap<&'static str, u64>,
+    /// The average size of the stored documents, in bytes.
+    pub average_document_size: u64,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedTask {
+    pub uid: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub finished_at: OffsetDateTime,
 }
 
 #[derive(Clone, derivative::Derivative)]
@@ -106,6 +141,14 @@ impl Index {
         self.inner.as_ref().clone().prepare_for_closing();
     }
 
+    /// Close the underlying index, and wait for its environment to be closed. It must be the
+    /// last handle on the index.
+    pub fn close_and_wait(self) {
+        let closing = self.inner.as_ref().clone().prepare_for_closing();
+        drop(self);
+        closing.wait();
+    }
+
     pub fn stats(&self) -> Result<IndexStats> {
         let rtxn = self.read_txn()?;
 
@@ -114,6 +157,50 @@ impl Index {
             number_of_documents: self.number_of_documents(&rtxn)?,
             is_indexing: None,
             field_distribution: self.field_distribution(&rtxn)?,
+            document_compression: compression::stats(self, &rtxn)?,
+            storage: None,
+            last_applied_task: None,
+        })
+    }
+
+    /// Computes the storage breakdown of the index, the facet and documents databases are
+    /// entirely read to measure them.
+    pub fn storage_stats(&self) -> Result<IndexStorageStats> {
+        let rtxn = self.read_txn()?;
+
+        let facet_databases = [
+            (
+                "facetIdF64Docids",
+                database_size(self.facet_id_f64_docids, &rtxn)?,
+            ),
+            (
+                "facetIdStringDocids",
+                database_size(self.facet_id_string_docids, &rtxn)?,
+            ),
+            (
+                "fieldIdDocidFacetF64s",
+                database_size(self.field_id_docid_facet_f64s, &rtxn)?,
+            ),
+            (
+                "fieldIdDocidFacetStrings",
+                database_size(self.field_id_docid_facet_strings, &rtxn)?,
+            ),
+        ];
+
+        let mut documents_size = 0;
+        for entry in self.documents.remap_data_type::<ByteSlice>().iter(&rtxn)? {
+            let (_, document) = entry?;
+            documents_size += document.len() as u64;
+        }
+        let average_document_size = documents_size
+            .checked_div(self.number_of_documents(&rtxn)?)
+            .unwrap_or_default();
+
+        Ok(IndexStorageStats {
+            size_on_disk: self.size(),
+            number_of_words: self.words_fst(&rtxn)?.len() as u64,
+            facet_databases: facet_databases.into_iter().collect(),
+            average_document_size,
         })
     }
 
@@ -148,13 +235,17 @@ impl Index {
             .map(|c| c.to_string())
             .collect();
 
-        let stop_words = self
-            .stop_words(txn)?
-            .map(|stop_words| -> Result<BTreeSet<_>> {
-                Ok(stop_words.stream().into_strs()?.into_iter().collect())
-            })
-            .transpose()?
-            .unwrap_or_default();
+        let stop_words = match custom_settings::stop_words_presets(self, txn)? {
+            Some(presets) => StopWords::Presets(presets),
+            None => StopWords::Words(
+                self.stop_words(txn)?
+                    .map(|stop_words| -> Result<BTreeSet<_>> {
+                        Ok(stop_words.stream().into_strs()?.into_iter().collect())
+                    })
+                    .transpose()?
+                    .unwrap_or_default(),
+            ),
+        };
         let distinct_field = self.distinct_field(txn)?.map(String::from);
 
         // in milli each word in the synonyms map were split on their separator. Since we lost
@@ -195,6 +286,27 @@ impl Index {
             disable_on_attributes: Setting::Set(disabled_attributes),
         };
 
+        let localized_attributes = custom_settings::localized_attributes(self, txn)?;
+        let separator_tokens = custom_settings::separator_tokens(self, txn)?.unwrap_or_default();
+        let non_separator_tokens =
+            custom_settings::non_separator_tokens(self, txn)?.unwrap_or_default();
+        let dictionary = custom_settings::dictionary(self, txn)?.unwrap_or_default();
+        let formatting = custom_settings::formatting(self, txn)?;
+        let formatting = FormattingSettings {
+            highlight_pre_tag: Setting::Set(formatting.highlight_pre_tag()),
+            highlight_post_tag: Setting::Set(formatting.highlight_post_tag()),
+            crop_length: Setting::Set(formatting.crop_length()),
+            crop_marker: Setting::Set(formatting.crop_marker()),
+        };
+        let faceting = custom_settings::faceting(self, txn)?;
+        let faceting = FacetingSettings {
+            max_values_per_facet: Setting::Set(faceting.max_values_per_facet()),
+            sort_facet_values_by: Setting::Set(faceting.sort_facet_values_by()),
+        };
+        let version_field = custom_settings::version_field(self, txn)?;
+        let document_compression = custom_settings::document_compression(self, txn)?;
+        let rulesets = custom_settings::rulesets(self, txn)?;
+
         Ok(Settings {
             displayed_attributes: match displayed_attributes {
                 Some(attrs) => Setting::Set(attrs),
@@ -214,6 +326,21 @@ impl Index {
             },
             synonyms: Setting::Set(synonyms),
             typo_tolerance: Setting::Set(typo_tolerance),
+            localized_attributes: match localized_attributes {
+                Some(rules) => Setting::Set(rules),
+                None => Setting::Reset,
+            },
+            separator_tokens: Setting::Set(separator_tokens),
+            non_separator_tokens: Setting::Set(non_separator_tokens),
+            dictionary: Setting::Set(dictionary),
+            formatting: Setting::Set(formatting),
+            faceting: Setting::Set(faceting),
+            version_field: match version_field {
+                Some(field) => Setting::Set(field),
+                None => Setting::Reset,
+            },
+            document_compression: Setting::Set(document_compression),
+            rulesets: Setting::Set(rulesets),
             _kind: PhantomData,
         })
     }
@@ -225,18 +352,39 @@ impl Index {
         attributes_to_retrieve: Option<Vec<S>>,
     ) -> Result<Vec<Map<String, Value>>> {
         let txn = self.read_txn()?;
+        self.documents_page(&txn, offset, limit, attributes_to_retrieve)
+    }
 
-        let fields_ids_map = self.fields_ids_map(&txn)?;
+    /// Reads a page of the documents, in the order of their internal ids, from the transaction.
+    pub(super) fn documents_page<S: AsRef<str>>(
+        &self,
+        txn: &RoTxn,
+        offset: usize,
+        limit: usize,
+        attributes_to_retrieve: Option<Vec<S>>,
+    ) -> Result<Vec<Map<String, Value>>> {
+        let fields_ids_map = self.fields_ids_map(txn)?;
         let fields_to_display =
-            self.fields_to_display(&txn, &attributes_to_retrieve, &fields_ids_map)?;
+            self.fields_to_display(txn, &attributes_to_retrieve, &fields_ids_map)?;
 
-        let iter = self.documents.range(&txn, &(..))?.skip(offset).take(limit);
+        let mut decoder = DocumentDecoder::new(self, txn)?;
+        let iter = self
+            .documents
+            .remap_data_type::<ByteSlice>()
+            .range(txn, &(..))?
+            .skip(offset)
+            .take(limit);
 
         let mut documents = Vec::new();
 
         for entry in iter {
-            let (_id, obkv) = entry?;
-            let object = obkv_to_json(&fields_to_display, &fields_ids_map, obkv)?;
+            let (_id, bytes) = entry?;
+            let document = decoder.decode(bytes)?;
+            let object = obkv_to_json(
+                &fields_to_display,
+                &fields_ids_map,
+                KvReaderU16::new(&document),
+            )?;
             documents.push(object);
         }
 
@@ -260,14 +408,17 @@ impl Index {
             .get(doc_id.as_bytes())
             .ok_or_else(|| IndexError::DocumentNotFound(doc_id.clone()))?;
 
-        let document = self
-            .documents(&txn, std::iter::once(internal_id))?
+        let document = compression::documents(self, &txn, std::iter::once(internal_id))?
             .into_iter()
             .next()
             .map(|(_, d)| d)
             .ok_or(IndexError::DocumentNotFound(doc_id))?;
 
-        let document = obkv_to_json(&fields_to_display, &fields_ids_map, document)?;
+        let document = obkv_to_json(
+            &fields_to_display,
+            &fields_ids_map,
+            KvReaderU16::new(&document),
+        )?;
 
         Ok(document)
     }
@@ -299,6 +450,21 @@ impl Index {
         Ok(displayed_fields_ids)
     }
 
+    /// Writes a compacted copy of the index file to `path`, without its free pages.
+    pub fn compact_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let _txn = self.write_txn()?;
+        self.inner
+            .env
+            .copy_to_path(path, milli::heed::CompactionOption::Enabled)?;
+        Ok(())
+    }
+
+    /// The number of handles on the index, its environment is only closed once the last one is
+    /// dropped.
+    pub fn handle_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
     pub fn snapshot(&self, path: impl AsRef<Path>) -> Result<()> {
         let mut dst = path.as_ref().join(format!("indexes/{}/", self.uuid));
         create_dir_all(&dst)?;
@@ -324,3 +490,13 @@ impl Drop for Index {
         }
     }
 }
+
+/// Returns the size of the keys and values of `database`.
+fn database_size<K, V>(database: Database<K, V>, txn: &RoTxn) -> Result<u64> {
+    let mut size = 0;
+    for entry in database.remap_types::<ByteSlice, ByteSlice>().iter(txn)? {
+        let (key, value) = entry?;
+        size += (key.len() + value.len()) as u64;
+    }
+    Ok(size)
+}
@@ -0,0 +1,236 @@
+//! The `localizedAttributes` of the index and the `locales` of the queries. The tokenizer of milli
+//! detects the script and language of each text by itself and can't be given language hints, so
+//! the texts of the localized attributes are rewritten for it instead, along with the tokenization
+//! settings, and the queries are rewritten with the same locales.
+//!
+//! - The German, Danish and Norwegian words written with umlauts or `ß` are followed by their
+//!   transliterated spelling, e.g. `Müller` is indexed along with `mueller`, the tokenizer only
+//!   removing the diacritics and indexing `muller`.
+//! - The Japanese texts are segmented between the runs of Kanji, Hiragana and Katakana, the
+//!   tokenizer otherwise segmenting the Japanese texts as if they were Chinese.
+//!
+//! The other supported locales are tokenized as usual. The queries are only segmented, a query
+//! typed with umlauts matching the documents without the transliterated words anyway.
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+use super::updates::LocalizedAttributesRule;
+
+/// The locales of the attributes, according to the first rule matching them.
+pub struct LocaleRules {
+    rules: Vec<LocalizedAttributesRule>,
+}
+
+impl LocaleRules {
+    /// Returns `None` when no rule assigns a locale rewriting the texts.
+    pub fn new(rules: Vec<LocalizedAttributesRule>) -> Option<Self> {
+        if rules
+            .iter()
+            .flat_map(|rule| rule.locales.iter())
+            .any(|locale| rewrites(locale))
+        {
+            Some(Self { rules })
+        } else {
+            None
+        }
+    }
+
+    /// The locales of an attribute, `None` if it matches no pattern.
+    pub fn locales(&self, name: &str) -> Option<&[String]> {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.attribute_patterns
+                    .iter()
+                    .any(|pattern| matches_pattern(pattern, name))
+            })
+            .map(|rule| rule.locales.as_slice())
+    }
+
+    /// The locales of all the rules, the queries without locales being rewritten for all of them.
+    pub fn all_locales(&self) -> Vec<String> {
+        let locales: BTreeSet<_> = self
+            .rules
+            .iter()
+            .flat_map(|rule| rule.locales.iter().cloned())
+            .collect();
+        locales.into_iter().collect()
+    }
+}
+
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    match (pattern.strip_prefix('*'), pattern.strip_suffix('*')) {
+        _ if pattern == "*" => true,
+        (Some(suffix), _) => name.ends_with(suffix),
+        (None, Some(prefix)) => name.starts_with(prefix),
+        (None, None) => pattern == name,
+    }
+}
+
+fn rewrites(locale: &str) -> bool {
+    locale == "jpn" || transliterations(locale).is_some()
+}
+
+/// The letters written differently when they can't be typed, by locale.
+fn transliterations(locale: &str) -> Option<&'static [(char, &'static str)]> {
+    match locale {
+        "deu" => Some(&[('ä', "ae"), ('ö', "oe"), ('ü', "ue"), ('ß', "ss")]),
+        "dan" | "nob" => Some(&[('æ', "ae"), ('ø', "oe"), ('å', "aa")]),
+        _ => None,
+    }
+}
+
+/// Rewrites a query for the given locales.
+pub fn localize_query<'a>(query: &'a str, locales: &[String]) -> Cow<'a, str> {
+    if locales.iter().any(|locale| locale == "jpn") {
+        segment_japanese(query)
+    } else {
+        Cow::Borrowed(query)
+    }
+}
+
+/// Rewrites a text of a document for the given locales, the texts no locale applies to being
+/// returned as they are.
+pub fn localize<'a>(text: &'a str, locales: &[String]) -> Cow<'a, str> {
+    let mut text = localize_query(text, locales);
+
+    let letters: Vec<_> = locales
+        .iter()
+        .filter_map(|locale| transliterations(locale))
+        .flatten()
+        .collect();
+    if !letters.is_empty() {
+        if let Cow::Owned(transliterated) = transliterate(&text, &letters) {
+            text = Cow::Owned(transliterated);
+        }
+    }
+
+    text
+}
+
+/// Appends the transliterated spelling of the words containing one of the letters.
+fn transliterate<'a>(text: &'a str, letters: &[&(char, &str)]) -> Cow<'a, str> {
+    let mut variants = String::new();
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        let word = word.to_lowercase();
+        if !word.chars().any(|c| letters.iter().any(|(l, _)| *l == c)) {
+            continue;
+        }
+        variants.push(' ');
+        for c in word.chars() {
+            match letters.iter().find(|(l, _)| *l == c) {
+                Some((_, replacement)) => variants.push_str(replacement),
+                None => variants.push(c),
+            }
+        }
+    }
+
+    if variants.is_empty() {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(format!("{}{}", text, variants))
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum JapaneseScript {
+    Kanji,
+    Hiragana,
+    Katakana,
+}
+
+fn japanese_script(c: char) -> Option<JapaneseScript> {
+    match c {
+        '\u{3041}'..='\u{309f}' => Some(JapaneseScript::Hiragana),
+        // the prolonged sound mark `ー` belongs to the katakana words.
+        '\u{30a0}'..='\u{30ff}' | '\u{31f0}'..='\u{31ff}' => Some(JapaneseScript::Katakana),
+        '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}' | '々' => Some(JapaneseScript::Kanji),
+        _ => None,
+    }
+}
+
+/// Inserts a space between the runs of different scripts, e.g. `東京のタワー` becomes
+/// `東京 の タワー`.
+fn segment_japanese(text: &str) -> Cow<str> {
+    let mut segmented = String::with_capacity(text.len());
+    let mut previous = None;
+    let mut changed = false;
+    for c in text.chars() {
+        let script = japanese_script(c);
+        if let (Some(previous), Some(script)) = (previous, script) {
+            if previous != script {
+                segmented.push(' ');
+                changed = true;
+            }
+        }
+        segmented.push(c);
+        previous = script;
+    }
+
+    if changed {
+        Cow::Owned(segmented)
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn locales(locales: &[&str]) -> Vec<String> {
+        locales.iter().map(|locale| locale.to_string()).collect()
+    }
+
+    #[test]
+    fn german_words_are_transliterated() {
+        assert_eq!(
+            localize("Herr Müller aß", &locales(&["deu"])),
+            "Herr Müller aß mueller ass"
+        );
+        assert!(matches!(
+            localize("Herr Müller", &locales(&["fra"])),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn japanese_scripts_are_segmented() {
+        assert_eq!(
+            localize("東京のタワーに行く", &locales(&["jpn"])),
+            "東京 の タワー に 行 く"
+        );
+        assert_eq!(
+            localize_query("東京タワー", &locales(&["deu", "jpn"])),
+            "東京 タワー"
+        );
+        assert!(matches!(
+            localize("東京のタワー", &locales(&["zho"])),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn attributes_match_the_first_rule() {
+        let rule = |patterns: &[&str], locales: &[&str]| LocalizedAttributesRule {
+            attribute_patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            locales: locales.iter().map(|l| l.to_string()).collect(),
+        };
+        let rules = LocaleRules::new(vec![
+            rule(&["title_de", "*_ja"], &["deu"]),
+            rule(&["desc_*"], &["jpn"]),
+            rule(&["*"], &["eng"]),
+        ])
+        .unwrap();
+
+        assert_eq!(rules.locales("title_de"), Some(&locales(&["deu"])[..]));
+        assert_eq!(rules.locales("desc_ja"), Some(&locales(&["deu"])[..]));
+        assert_eq!(rules.locales("desc_en"), Some(&locales(&["jpn"])[..]));
+        assert_eq!(rules.locales("title"), Some(&locales(&["eng"])[..]));
+        assert_eq!(rules.all_locales(), locales(&["deu", "eng", "jpn"]));
+    }
+}
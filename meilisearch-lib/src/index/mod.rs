@@ -1,19 +1,39 @@
+pub use export::{ExportFormat, ExportQuery};
 pub use search::{
-    default_crop_length, default_crop_marker, default_highlight_post_tag,
-    default_highlight_pre_tag, SearchQuery, SearchResult, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
-    DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
+    MatchingStrategy, SearchQuery, SearchResult, SearchTimings, DEFAULT_CROP_LENGTH,
+    DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG,
+    DEFAULT_SEARCH_LIMIT,
+};
+pub use suggest::{SuggestQuery, SuggestResult, Suggestion, DEFAULT_SUGGEST_LIMIT};
+pub use updates::{
+    apply_settings_to_builder, Checked, DocumentAdditionResult, Facets, Settings, Unchecked,
 };
-pub use updates::{apply_settings_to_builder, Checked, Facets, Settings, Unchecked};
 
+mod boost;
+mod compression;
+mod custom_settings;
 mod dump;
 pub mod error;
+mod export;
+mod filter;
+mod locales;
+mod negation;
+mod ranking_score;
+mod read_snapshot;
 mod search;
+mod stop_words;
+mod suggest;
+pub mod synonyms;
+mod tokenization;
 pub mod updates;
+mod versioning;
 
 #[allow(clippy::module_inception)]
 mod index;
 
-pub use index::{Document, IndexMeta, IndexStats};
+pub use compression::DocumentCompressionStats;
+pub use index::{AppliedTask, Document, IndexMeta, IndexStats, IndexStorageStats};
+pub use read_snapshot::ReadSnapshot;
 
 #[cfg(not(test))]
 pub use index::Index;
@@ -28,16 +48,20 @@ pub mod test {
     use std::path::Path;
     use std::path::PathBuf;
     use std::sync::Arc;
+    use std::time::Duration;
 
     use milli::update::IndexerConfig;
-    use milli::update::{DocumentAdditionResult, DocumentDeletionResult, IndexDocumentsMethod};
+    use milli::update::{DocumentDeletionResult, IndexDocumentsMethod, UpdateIndexingStep};
     use nelson::Mocker;
     use serde_json::{Map, Value};
     use uuid::Uuid;
 
     use super::error::Result;
     use super::index::Index;
-    use super::{Checked, IndexMeta, IndexStats, SearchQuery, SearchResult, Settings};
+    use super::{
+        Checked, DocumentAdditionResult, ExportQuery, IndexMeta, IndexStats, IndexStorageStats,
+        ReadSnapshot, SearchQuery, SearchResult, Settings, SuggestQuery, SuggestResult,
+    };
     use crate::update_file_store::UpdateFileStore;
 
     #[derive(Clone)]
@@ -84,6 +108,13 @@ pub mod test {
             }
         }
 
+        pub fn storage_stats(&self) -> Result<IndexStorageStats> {
+            match self {
+                MockIndex::Real(index) => index.storage_stats(),
+                MockIndex::Mock(m) => unsafe { m.get("storage_stats").call(()) },
+            }
+        }
+
         pub fn meta(&self) -> Result<IndexMeta> {
             match self {
                 MockIndex::Real(index) => index.meta(),
@@ -111,6 +142,13 @@ pub mod test {
             }
         }
 
+        pub fn open_read_snapshot(&self, ttl: Duration) -> Result<ReadSnapshot> {
+            match self {
+                MockIndex::Real(index) => index.open_read_snapshot(ttl),
+                MockIndex::Mock(m) => unsafe { m.get("open_read_snapshot").call(ttl) },
+            }
+        }
+
         pub fn retrieve_document<S: AsRef<str>>(
             &self,
             doc_id: String,
@@ -125,7 +163,7 @@ pub mod test {
         pub fn size(&self) -> u64 {
             match self {
                 MockIndex::Real(index) => index.size(),
-                MockIndex::Mock(_) => todo!(),
+                MockIndex::Mock(m) => unsafe { m.get("size").call(()) },
             }
         }
 
@@ -143,6 +181,27 @@ pub mod test {
             }
         }
 
+        pub fn close_and_wait(self) {
+            match self {
+                MockIndex::Real(index) => index.close_and_wait(),
+                MockIndex::Mock(m) => unsafe { m.get("close_and_wait").call(()) },
+            }
+        }
+
+        pub fn handle_count(&self) -> usize {
+            match self {
+                MockIndex::Real(index) => index.handle_count(),
+                MockIndex::Mock(m) => Arc::strong_count(m),
+            }
+        }
+
+        pub fn compact_to(&self, path: impl AsRef<Path>) -> Result<()> {
+            match self {
+                MockIndex::Real(index) => index.compact_to(path),
+                MockIndex::Mock(m) => unsafe { m.get("compact_to").call(path.as_ref()) },
+            }
+        }
+
         pub fn perform_search(&self, query: SearchQuery) -> Result<SearchResult> {
             match self {
                 MockIndex::Real(index) => index.perform_search(query),
@@ -150,6 +209,38 @@ pub mod test {
             }
         }
 
+        pub fn assign_ruleset(&self) -> Result<Option<String>> {
+            match self {
+                MockIndex::Real(index) => index.assign_ruleset(),
+                MockIndex::Mock(m) => unsafe { m.get("assign_ruleset").call(()) },
+            }
+        }
+
+        pub fn suggest(&self, query: SuggestQuery) -> Result<SuggestResult> {
+            match self {
+                MockIndex::Real(index) => index.suggest(query),
+                MockIndex::Mock(m) => unsafe { m.get("suggest").call(query) },
+            }
+        }
+
+        pub fn reindex(&self) -> Result<()> {
+            match self {
+                MockIndex::Real(index) => index.reindex(),
+                MockIndex::Mock(m) => unsafe { m.get("reindex").call(()) },
+            }
+        }
+
+        pub fn export(
+            &self,
+            query: ExportQuery,
+            write: impl FnMut(Vec<u8>) -> bool,
+        ) -> Result<()> {
+            match self {
+                MockIndex::Real(index) => index.export(query, write),
+                MockIndex::Mock(m) => unsafe { m.get("export").call((query, write)) },
+            }
+        }
+
         pub fn dump(&self, path: impl AsRef<Path>) -> Result<()> {
             match self {
                 MockIndex::Real(index) => index.dump(path),
@@ -163,10 +254,11 @@ pub mod test {
             primary_key: Option<String>,
             file_store: UpdateFileStore,
             contents: impl Iterator<Item = Uuid>,
+            progress: impl Fn(UpdateIndexingStep) + Sync,
         ) -> Result<DocumentAdditionResult> {
             match self {
                 MockIndex::Real(index) => {
-                    index.update_documents(method, primary_key, file_store, contents)
+                    index.update_documents(method, primary_key, file_store, contents, progress)
                 }
                 MockIndex::Mock(mocker) => unsafe {
                     mocker
@@ -0,0 +1,132 @@
+use milli::heed::RoTxn;
+use milli::tokenizer::{Analyzer, AnalyzerConfig};
+use roaring::RoaringBitmap;
+
+use super::error::Result;
+use super::index::Index;
+
+/// Splits the negated terms out of a query. A negated term is a word or a phrase preceded by a
+/// `-`, e.g. `-cheese` or `-"blue cheese"`, the hyphens within words like `t-shirt` and the words
+/// of the other phrases being kept in the query. Returns the rest of the query along with the
+/// negated terms.
+pub fn split_negations(query: &str) -> (String, Vec<String>) {
+    let mut rest = String::new();
+    let mut negations = Vec::new();
+    let mut remaining = query;
+
+    loop {
+        let trimmed = remaining.trim_start();
+        rest.push_str(&remaining[..remaining.len() - trimmed.len()]);
+        remaining = trimmed;
+        if remaining.is_empty() {
+            break;
+        }
+
+        if let Some(phrase) = remaining.strip_prefix("-\"") {
+            let end = phrase.find('"').unwrap_or(phrase.len());
+            negations.push(phrase[..end].to_string());
+            remaining = phrase.get(end + 1..).unwrap_or_default();
+        } else if let Some(phrase) = remaining.strip_prefix('"') {
+            let end = phrase.find('"').map_or(remaining.len(), |end| end + 2);
+            rest.push_str(&remaining[..end]);
+            remaining = &remaining[end..];
+        } else {
+            let end = remaining
+                .find(char::is_whitespace)
+                .unwrap_or(remaining.len());
+            let (word, tail) = remaining.split_at(end);
+            match word.strip_prefix('-') {
+                Some(term) if !term.is_empty() => negations.push(term.to_string()),
+                _ => rest.push_str(word),
+            }
+            remaining = tail;
+        }
+    }
+
+    (rest, negations)
+}
+
+impl Index {
+    /// Returns the documents containing any of the negated terms. The documents excluded by a
+    /// negated phrase are the ones containing each pair of its consecutive words next to each
+    /// other, like the phrases of the engine.
+    pub(super) fn negated_documents(
+        &self,
+        rtxn: &RoTxn,
+        negations: &[String],
+    ) -> Result<RoaringBitmap> {
+        let mut excluded = RoaringBitmap::new();
+        if negations.is_empty() {
+            return Ok(excluded);
+        }
+
+        let stop_words = fst::Set::default();
+        let mut config = AnalyzerConfig::default();
+        config.stop_words(&stop_words);
+        let analyzer = Analyzer::new(config);
+
+        for negation in negations {
+            let words: Vec<String> = analyzer
+                .analyze(negation)
+                .reconstruct()
+                .filter(|(_, token)| token.is_word())
+                .map(|(_, token)| token.text().to_string())
+                .collect();
+
+            let docids = match words.as_slice() {
+                [] => continue,
+                [word] => self
+                    .word_docids
+                    .get(rtxn, word.as_str())?
+                    .unwrap_or_default(),
+                words => {
+                    let mut docids: Option<RoaringBitmap> = None;
+                    for pair in words.windows(2) {
+                        let key = (pair[0].as_str(), pair[1].as_str(), 1);
+                        let pair_docids = self
+                            .word_pair_proximity_docids
+                            .get(rtxn, &key)?
+                            .unwrap_or_default();
+                        docids = Some(match docids {
+                            Some(docids) => docids & pair_docids,
+                            None => pair_docids,
+                        });
+                    }
+                    docids.unwrap_or_default()
+                }
+            };
+            excluded |= docids;
+        }
+
+        Ok(excluded)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_negated_words() {
+        let (rest, negations) = split_negations("blue -cheese pie");
+        assert_eq!(rest, "blue  pie");
+        assert_eq!(negations, ["cheese"]);
+
+        // the hyphens within words and the lone ones are kept.
+        let (rest, negations) = split_negations("t-shirt - red");
+        assert_eq!(rest, "t-shirt - red");
+        assert!(negations.is_empty());
+    }
+
+    #[test]
+    fn split_negated_phrases() {
+        let (rest, negations) = split_negations(r#"pie -"blue cheese" -"unclosed phrase"#);
+        assert_eq!(rest, "pie  ");
+        assert_eq!(negations, ["blue cheese", "unclosed phrase"]);
+
+        // the words of the other phrases are never negated.
+        let (rest, negations) = split_negations(r#""blue -cheese" -pie"#);
+        assert_eq!(rest, r#""blue -cheese" "#);
+        assert_eq!(negations, ["pie"]);
+    }
+}
@@ -0,0 +1,451 @@
+use milli::tokenizer::Analyzer;
+use milli::Criterion;
+use serde_json::{json, Value};
+
+use super::search::Document;
+
+/// The maximum distance between two words taken into account by the proximity rule.
+const MAX_PROXIMITY: usize = 8;
+/// The maximum number of typos allowed on a single word.
+const MAX_TYPOS_PER_WORD: usize = 2;
+
+/// The relevancy of a hit, along with the contribution of each ranking rule.
+pub struct RankingScore {
+    pub score: f64,
+    pub details: Document,
+}
+
+/// Computes a normalized relevancy score for each hit of a search.
+///
+/// The engine does not expose the internal bucket of each document, so the score is recomputed
+/// from the document content following the ranking rules of the index. Each rule yields a score
+/// between `0.0` and `1.0`, and the global score is a mean of these scores weighted by the order
+/// of the rules, so that the first rules weight more than the following ones.
+pub struct ScoreComputer<'a> {
+    query_words: Vec<String>,
+    criteria: &'a [Criterion],
+    searchable_attributes: &'a [String],
+    sort: &'a [String],
+}
+
+/// How a query word matched a document.
+struct WordMatch {
+    typos: usize,
+    exact: bool,
+    attribute: usize,
+    position: usize,
+}
+
+impl<'a> ScoreComputer<'a> {
+    pub fn new<A: AsRef<[u8]>>(
+        query: Option<&str>,
+        analyzer: &Analyzer<A>,
+        criteria: &'a [Criterion],
+        searchable_attributes: &'a [String],
+        sort: &'a [String],
+    ) -> Self {
+        let query_words = query
+            .map(|query| {
+                analyzer
+                    .analyze(query)
+                    .reconstruct()
+                    .filter(|(_, token)| token.is_word())
+                    .map(|(_, token)| token.text().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            query_words,
+            criteria,
+            searchable_attributes,
+            sort,
+        }
+    }
+
+    /// The textual rules are computed on the whole `document`, while the values reported for
+    /// the sort rules are read from the `displayed` one, to not leak the hidden attributes.
+    pub fn compute<A: AsRef<[u8]>>(
+        &self,
+        document: &Document,
+        displayed: &Document,
+        analyzer: &Analyzer<A>,
+    ) -> RankingScore {
+        let matches = self.match_words(document, analyzer);
+        let mut details = Document::new();
+        let mut weighted_score = 0.0;
+        let mut total_weight = 0.0;
+
+        let mut order = 0;
+        for criterion in self.criteria {
+            let (name, detail, score) = match criterion {
+                // placeholder searches are not ranked by the textual rules.
+                Criterion::Words
+                | Criterion::Typo
+                | Criterion::Proximity
+                | Criterion::Attribute
+                | Criterion::Exactness
+                    if self.query_words.is_empty() =>
+                {
+                    continue
+                }
+                Criterion::Words => {
+                    let matched = matches.iter().flatten().count();
+                    let score = ratio(matched, self.query_words.len());
+                    let detail = json!({
+                        "order": order,
+                        "matchingWords": matched,
+                        "maxMatchingWords": self.query_words.len(),
+                        "score": score,
+                    });
+                    ("words".to_string(), detail, Some(score))
+                }
+                Criterion::Typo => {
+                    let matched = matches.iter().flatten().count();
+                    let typos: usize = matches.iter().flatten().map(|m| m.typos).sum();
+                    let max_typos = matched * MAX_TYPOS_PER_WORD;
+                    let score = if matched == 0 {
+                        0.0
+                    } else {
+                        1.0 - ratio(typos, max_typos)
+                    };
+                    let detail = json!({
+                        "order": order,
+                        "typoCount": typos,
+                        "maxTypoCount": max_typos,
+                        "score": score,
+                    });
+                    ("typo".to_string(), detail, Some(score))
+                }
+                Criterion::Proximity => {
+                    let score = self.proximity_score(&matches);
+                    let detail = json!({ "order": order, "score": score });
+                    ("proximity".to_string(), detail, Some(score))
+                }
+                Criterion::Attribute => {
+                    let ranks: Vec<_> = matches.iter().flatten().map(|m| m.attribute).collect();
+                    let nb_attributes = self.searchable_attributes.len().max(1);
+                    let score = if ranks.is_empty() {
+                        0.0
+                    } else {
+                        ranks
+                            .iter()
+                            .map(|&rank| 1.0 - ratio(rank, nb_attributes))
+                            .sum::<f64>()
+                            / ranks.len() as f64
+                    };
+                    let detail = json!({ "order": order, "score": score });
+                    ("attribute".to_string(), detail, Some(score))
+                }
+                Criterion::Exactness => {
+                    let exact = matches.iter().flatten().filter(|m| m.exact).count();
+                    let score = ratio(exact, self.query_words.len());
+                    let detail = json!({
+                        "order": order,
+                        "exactWords": exact,
+                        "maxExactWords": self.query_words.len(),
+                        "score": score,
+                    });
+                    ("exactness".to_string(), detail, Some(score))
+                }
+                // The sort rule stands for all the sort parameters of the query, we report the
+                // values the hit was sorted on.
+                Criterion::Sort if self.sort.is_empty() => continue,
+                Criterion::Sort => {
+                    for sort in self.sort {
+                        let field = sort.rsplit_once(':').map_or(sort.as_str(), |(f, _)| f);
+                        let value = displayed.get(field).cloned().unwrap_or(Value::Null);
+                        details.insert(sort.clone(), json!({ "order": order, "value": value }));
+                        order += 1;
+                    }
+                    continue;
+                }
+                Criterion::Asc(field) | Criterion::Desc(field) => {
+                    let value = displayed.get(field).cloned().unwrap_or(Value::Null);
+                    let detail = json!({ "order": order, "value": value });
+                    (criterion.to_string(), detail, None)
+                }
+            };
+
+            if let Some(score) = score {
+                let weight = 0.5f64.powi(order as i32);
+                weighted_score += weight * score;
+                total_weight += weight;
+            }
+
+            details.insert(name, detail);
+            order += 1;
+        }
+
+        let score = if total_weight == 0.0 {
+            1.0
+        } else {
+            weighted_score / total_weight
+        };
+
+        RankingScore { score, details }
+    }
+
+    /// Returns the product of the weights of the boosted attributes containing a query word.
+    /// The placeholder searches don't match any attribute.
+    pub fn attribute_boost<A: AsRef<[u8]>>(
+        &self,
+        document: &Document,
+        analyzer: &Analyzer<A>,
+        boosts: &[(String, f64)],
+    ) -> f64 {
+        let mut factor = 1.0;
+        for (attribute, weight) in boosts {
+            let value = match document.get(attribute) {
+                Some(value) => value,
+                None => continue,
+            };
+
+            let mut words = Vec::new();
+            collect_words(value, analyzer, &mut words);
+            let matched = self.query_words.iter().enumerate().any(|(i, query_word)| {
+                let is_last = i == self.query_words.len() - 1;
+                words
+                    .iter()
+                    .any(|word| match_word(query_word, word, is_last).is_some())
+            });
+            if matched {
+                factor *= weight;
+            }
+        }
+
+        factor
+    }
+
+    /// Returns, for each query word, the best match found in the searchable attributes.
+    fn match_words<A: AsRef<[u8]>>(
+        &self,
+        document: &Document,
+        analyzer: &Analyzer<A>,
+    ) -> Vec<Option<WordMatch>> {
+        let mut matches: Vec<Option<WordMatch>> = self.query_words.iter().map(|_| None).collect();
+        if self.query_words.is_empty() {
+            return matches;
+        }
+
+        for (attribute, name) in self.searchable_attributes.iter().enumerate() {
+            let value = match document.get(name) {
+                Some(value) => value,
+                None => continue,
+            };
+
+            let mut words = Vec::new();
+            collect_words(value, analyzer, &mut words);
+
+            for (position, word) in words.iter().enumerate() {
+                for (i, query_word) in self.query_words.iter().enumerate() {
+                    let is_last = i == self.query_words.len() - 1;
+                    let (typos, exact) = match match_word(query_word, word, is_last) {
+                        Some(found) => found,
+                        None => continue,
+                    };
+
+                    let better = match &matches[i] {
+                        Some(best) => {
+                            (typos, !exact, attribute) < (best.typos, !best.exact, best.attribute)
+                        }
+                        None => true,
+                    };
+
+                    if better {
+                        matches[i] = Some(WordMatch {
+                            typos,
+                            exact,
+                            attribute,
+                            position,
+                        });
+                    }
+                }
+            }
+        }
+
+        matches
+    }
+
+    fn proximity_score(&self, matches: &[Option<WordMatch>]) -> f64 {
+        let pairs = matches.len().saturating_sub(1);
+        if pairs == 0 {
+            return 1.0;
+        }
+
+        let cost: usize = matches
+            .windows(2)
+            .map(|pair| match (&pair[0], &pair[1]) {
+                (Some(left), Some(right)) if left.attribute == right.attribute => {
+                    let distance =
+                        (left.position as isize - right.position as isize).unsigned_abs();
+                    distance.clamp(1, MAX_PROXIMITY) - 1
+                }
+                _ => MAX_PROXIMITY - 1,
+            })
+            .sum();
+
+        1.0 - ratio(cost, pairs * (MAX_PROXIMITY - 1))
+    }
+}
+
+fn ratio(value: usize, max: usize) -> f64 {
+    if max == 0 {
+        1.0
+    } else {
+        value as f64 / max as f64
+    }
+}
+
+fn collect_words<A: AsRef<[u8]>>(value: &Value, analyzer: &Analyzer<A>, words: &mut Vec<String>) {
+    match value {
+        Value::String(s) => words.extend(
+            analyzer
+                .analyze(s)
+                .reconstruct()
+                .filter(|(_, token)| token.is_word())
+                .map(|(_, token)| token.text().to_string()),
+        ),
+        Value::Number(number) => words.push(number.to_string()),
+        Value::Array(values) => values
+            .iter()
+            .for_each(|value| collect_words(value, analyzer, words)),
+        Value::Object(object) => object
+            .values()
+            .for_each(|value| collect_words(value, analyzer, words)),
+        _ => (),
+    }
+}
+
+/// Returns the number of typos between the query word and the document word and whether the
+/// match is exact, following the default typo tolerance of the engine.
+fn match_word(query_word: &str, word: &str, is_prefix: bool) -> Option<(usize, bool)> {
+    if query_word == word {
+        return Some((0, true));
+    }
+
+    if is_prefix && word.starts_with(query_word) {
+        return Some((0, false));
+    }
+
+    let allowed_typos = match query_word.chars().count() {
+        0..=4 => 0,
+        5..=8 => 1,
+        _ => MAX_TYPOS_PER_WORD,
+    };
+
+    let typos = levenshtein(query_word, word);
+    (typos <= allowed_typos).then(|| (typos, false))
+}
+
+fn levenshtein(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    let mut previous: Vec<usize> = (0..=right.len()).collect();
+
+    for (i, l) in left.chars().enumerate() {
+        let mut current = vec![i + 1; right.len() + 1];
+        for (j, r) in right.iter().enumerate() {
+            let substitution = previous[j] + (l != *r) as usize;
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[right.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use milli::tokenizer::AnalyzerConfig;
+
+    use super::*;
+
+    fn analyzer() -> Analyzer<'static, Vec<u8>> {
+        Analyzer::new(AnalyzerConfig::default())
+    }
+
+    #[test]
+    fn levenshtein_distance() {
+        assert_eq!(levenshtein("hello", "hello"), 0);
+        assert_eq!(levenshtein("hello", "helo"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn perfect_match_scores_one() {
+        let analyzer = analyzer();
+        let criteria = [Criterion::Words, Criterion::Typo, Criterion::Exactness];
+        let searchable = ["title".to_string()];
+        let computer = ScoreComputer::new(
+            Some("captain marvel"),
+            &analyzer,
+            &criteria,
+            &searchable,
+            &[],
+        );
+
+        let document = json!({ "title": "Captain Marvel" });
+        let document = document.as_object().unwrap();
+        let score = computer.compute(document, document, &analyzer);
+
+        assert_eq!(score.score, 1.0);
+        assert_eq!(score.details["words"]["matchingWords"], json!(2));
+    }
+
+    #[test]
+    fn partial_match_scores_less() {
+        let analyzer = analyzer();
+        let criteria = [Criterion::Words, Criterion::Typo];
+        let searchable = ["title".to_string()];
+        let computer = ScoreComputer::new(
+            Some("captain america"),
+            &analyzer,
+            &criteria,
+            &searchable,
+            &[],
+        );
+
+        let document = json!({ "title": "Captain Marvel" });
+        let document = document.as_object().unwrap();
+        let score = computer.compute(document, document, &analyzer);
+
+        assert!(score.score < 1.0);
+        assert_eq!(score.details["words"]["score"], json!(0.5));
+    }
+
+    #[test]
+    fn sort_details_only_report_displayed_values() {
+        let analyzer = analyzer();
+        let criteria = [Criterion::Sort, Criterion::Desc("rank".to_string())];
+        let sort = ["price:asc".to_string(), "cost:asc".to_string()];
+        let computer = ScoreComputer::new(None, &analyzer, &criteria, &[], &sort);
+
+        let document = json!({ "price": 10, "cost": 3, "rank": 1 });
+        let displayed = json!({ "price": 10 });
+        let score = computer.compute(
+            document.as_object().unwrap(),
+            displayed.as_object().unwrap(),
+            &analyzer,
+        );
+
+        assert_eq!(score.details["price:asc"]["value"], json!(10));
+        assert_eq!(score.details["cost:asc"]["value"], Value::Null);
+        assert_eq!(score.details["rank:desc"]["value"], Value::Null);
+    }
+
+    #[test]
+    fn boosted_attributes_multiply_their_weights() {
+        let analyzer = analyzer();
+        let searchable = ["title".to_string(), "overview".to_string()];
+        let computer = ScoreComputer::new(Some("marvel"), &analyzer, &[], &searchable, &[]);
+        let boosts = [("title".to_string(), 2.0), ("overview".to_string(), 1.5)];
+
+        let document = json!({ "title": "Captain Marvel", "overview": "A Marvel movie" });
+        let factor = computer.attribute_boost(document.as_object().unwrap(), &analyzer, &boosts);
+        assert_eq!(factor, 3.0);
+
+        let document = json!({ "title": "Captain America", "overview": "A Marvel movie" });
+        let factor = computer.attribute_boost(document.as_object().unwrap(), &analyzer, &boosts);
+        assert_eq!(factor, 1.5);
+    }
+}
@@ -0,0 +1,108 @@
+//! Point-in-time reads of the documents of an index. A snapshot holds a read transaction on a
+//! thread of its own: the pages it reads are the ones of the index when it was opened, whatever
+//! the updates processed since then.
+//!
+//! LMDB can't reuse the pages freed by the updates while a read transaction is open, so a
+//! snapshot closes itself once it hasn't been read for its time to live.
+
+use std::future::Future;
+use std::sync::mpsc::{channel, sync_channel, Sender};
+use std::thread;
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use super::error::{IndexError, Result};
+use super::index::{Document, Index};
+
+struct DocumentsRequest {
+    offset: usize,
+    limit: usize,
+    attributes_to_retrieve: Option<Vec<String>>,
+    response: oneshot::Sender<Result<Vec<Document>>>,
+}
+
+/// A handle on an open snapshot. The snapshot is closed when all its handles are dropped, or
+/// when it expires.
+#[derive(Clone)]
+pub struct ReadSnapshot {
+    requests: Sender<DocumentsRequest>,
+    /// The number of documents of the index when the snapshot was opened.
+    pub number_of_documents: u64,
+}
+
+impl ReadSnapshot {
+    /// Reads a page of the documents of the snapshot, in the order of their internal ids. Returns
+    /// `None` if the snapshot is closed.
+    ///
+    /// The request is sent before the future is polled, so that the future doesn't borrow the
+    /// handle.
+    pub fn documents(
+        &self,
+        offset: usize,
+        limit: usize,
+        attributes_to_retrieve: Option<Vec<String>>,
+    ) -> impl Future<Output = Option<Result<Vec<Document>>>> {
+        let (response, receiver) = oneshot::channel();
+        let request = DocumentsRequest {
+            offset,
+            limit,
+            attributes_to_retrieve,
+            response,
+        };
+        let sent = self.requests.send(request).is_ok();
+        async move {
+            if sent {
+                receiver.await.ok()
+            } else {
+                None
+            }
+        }
+    }
+}
+
+impl Index {
+    /// Opens a read transaction kept until the snapshot isn't read for `ttl`.
+    pub fn open_read_snapshot(&self, ttl: Duration) -> Result<ReadSnapshot> {
+        let (requests, receiver) = channel::<DocumentsRequest>();
+        let (opened, opening) = sync_channel(1);
+
+        let index = self.clone();
+        thread::Builder::new()
+            .name(format!("read-snapshot-{}", self.uuid))
+            .spawn(move || {
+                let txn = match index.read_txn() {
+                    Ok(txn) => txn,
+                    Err(e) => {
+                        let _ = opened.send(Err(IndexError::from(e)));
+                        return;
+                    }
+                };
+                let number_of_documents = index.number_of_documents(&txn).map_err(Into::into);
+                if opened.send(number_of_documents).is_err() {
+                    return;
+                }
+
+                // Stops when the snapshot expires or when all its handles are dropped.
+                while let Ok(request) = receiver.recv_timeout(ttl) {
+                    let documents = index.documents_page(
+                        &txn,
+                        request.offset,
+                        request.limit,
+                        request.attributes_to_retrieve,
+                    );
+                    let _ = request.response.send(documents);
+                }
+            })?;
+
+        let number_of_documents = match opening.recv() {
+            Ok(result) => result?,
+            Err(e) => return Err(IndexError::Internal(Box::new(e))),
+        };
+
+        Ok(ReadSnapshot {
+            requests,
+            number_of_documents,
+        })
+    }
+}
@@ -1,19 +1,31 @@
-use std::cmp::min;
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::cmp::{max, min, Reverse};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::str::FromStr;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use either::Either;
+use indexmap::IndexMap;
+use milli::heed::RoTxn;
 use milli::tokenizer::{Analyzer, AnalyzerConfig, Token};
-use milli::{AscDesc, FieldId, FieldsIdsMap, Filter, MatchingWords, SortError};
+use milli::{AscDesc, DocumentId, FieldId, FieldsIdsMap, Filter, MatchingWords, SortError};
+use rand::Rng;
 use regex::Regex;
+use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use crate::index::error::FacetError;
 
+use super::compression;
+use super::custom_settings::{self, find_unsupported_locale};
 use super::error::{IndexError, Result};
+use super::filter::split_existence_conditions;
 use super::index::Index;
+use super::locales::{localize_query, LocaleRules};
+use super::negation::split_negations;
+use super::ranking_score::ScoreComputer;
+use super::tokenization::TokenRewriter;
+use super::updates::FacetValuesSort;
 
 pub type Document = serde_json::Map<String, Value>;
 type MatchesInfo = BTreeMap<String, Vec<MatchInfo>>;
@@ -25,58 +37,155 @@ pub struct MatchInfo {
 }
 
 pub const DEFAULT_SEARCH_LIMIT: usize = 20;
-const fn default_search_limit() -> usize {
-    DEFAULT_SEARCH_LIMIT
-}
-
 pub const DEFAULT_CROP_LENGTH: usize = 10;
-pub const fn default_crop_length() -> usize {
-    DEFAULT_CROP_LENGTH
-}
-
 pub const DEFAULT_CROP_MARKER: &str = "…";
-pub fn default_crop_marker() -> String {
-    DEFAULT_CROP_MARKER.to_string()
-}
-
 pub const DEFAULT_HIGHLIGHT_PRE_TAG: &str = "<em>";
-pub fn default_highlight_pre_tag() -> String {
-    DEFAULT_HIGHLIGHT_PRE_TAG.to_string()
+pub const DEFAULT_HIGHLIGHT_POST_TAG: &str = "</em>";
+
+/// The number of values of each facet returned in the facets distribution, unless the
+/// `faceting` setting of the index says otherwise.
+pub const DEFAULT_MAX_VALUES_PER_FACET: usize = 100;
+/// The values of a facet that can be sorted by count, the distribution of milli never containing
+/// more values than this.
+const MAX_SORTED_VALUES_PER_FACET: usize = 1000;
+
+/// Defines which words of the query can be ignored when not enough documents match all of them.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchingStrategy {
+    /// Remove the query words from the last to the first.
+    Last,
+    /// All the query words are mandatory.
+    All,
+    /// Remove the most frequent query words first.
+    Frequency,
 }
 
-pub const DEFAULT_HIGHLIGHT_POST_TAG: &str = "</em>";
-pub fn default_highlight_post_tag() -> String {
-    DEFAULT_HIGHLIGHT_POST_TAG.to_string()
+impl Default for MatchingStrategy {
+    fn default() -> Self {
+        Self::Last
+    }
 }
 
 /// The maximimum number of results that the engine
 /// will be able to return in one search call.
 pub const HARD_RESULT_LIMIT: usize = 1000;
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct SearchQuery {
     pub q: Option<String>,
     pub offset: Option<usize>,
-    #[serde(default = "default_search_limit")]
-    pub limit: usize,
+    /// Defaults to `DEFAULT_SEARCH_LIMIT`.
+    pub limit: Option<usize>,
+    pub page: Option<usize>,
+    pub hits_per_page: Option<usize>,
     pub attributes_to_retrieve: Option<BTreeSet<String>>,
     pub attributes_to_crop: Option<Vec<String>>,
-    #[serde(default = "default_crop_length")]
-    pub crop_length: usize,
+    /// Defaults to the `formatting` setting of the index, like the other formatting parameters.
+    pub crop_length: Option<usize>,
     pub attributes_to_highlight: Option<HashSet<String>>,
-    // Default to false
-    #[serde(default = "Default::default")]
-    pub matches: bool,
+    /// Defaults to false.
+    pub matches: Option<bool>,
     pub filter: Option<Value>,
     pub sort: Option<Vec<String>>,
     pub facets_distribution: Option<Vec<String>>,
-    #[serde(default = "default_highlight_pre_tag")]
-    pub highlight_pre_tag: String,
-    #[serde(default = "default_highlight_post_tag")]
-    pub highlight_post_tag: String,
-    #[serde(default = "default_crop_marker")]
-    pub crop_marker: String,
+    pub distinct: Option<String>,
+    /// Defaults to `MatchingStrategy::Last`.
+    pub matching_strategy: Option<MatchingStrategy>,
+    /// The languages of the query, as ISO 639-3 codes.
+    pub locales: Option<Vec<String>>,
+    pub highlight_pre_tag: Option<String>,
+    pub highlight_post_tag: Option<String>,
+    pub crop_marker: Option<String>,
+    /// Defaults to false.
+    pub show_ranking_score: Option<bool>,
+    /// Defaults to false.
+    pub show_ranking_score_details: Option<bool>,
+    /// The ruleset of the index the search is served with, one is drawn according to the traffic
+    /// shares of the rulesets when it is `None`.
+    pub ruleset: Option<String>,
+    /// The weights of the attributes to boost, the hits containing a query word in a boosted
+    /// attribute have their relevancy score multiplied by its weight.
+    pub boost: Option<BTreeMap<String, f64>>,
+    /// The hits matching this filter have their relevancy score multiplied by
+    /// `boost_filter_weight`.
+    pub boost_filter: Option<Value>,
+    /// Defaults to `DEFAULT_BOOST_FILTER_WEIGHT`.
+    pub boost_filter_weight: Option<f64>,
+    /// The time budget of the search, in milliseconds. Once it is spent, the stages left are cut
+    /// short and the search returns the hits ranked so far as a degraded result. Only the stages
+    /// around the engine are bounded: the ranking of a page of hits by milli always runs to its
+    /// end, a search can only be interrupted between two pages.
+    pub timeout_ms: Option<u64>,
+    /// The attributes the API key performing the search is allowed to retrieve, all of them
+    /// when it is `None`. It comes from the key, and can't be sent in the query.
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub retrievable_attributes: Option<BTreeSet<String>>,
+}
+
+impl SearchQuery {
+    /// Fills the parameters the query omits with the ones of `defaults`. A parameter sent with
+    /// its default value, such as a `limit` of 20, is kept.
+    pub fn merge_defaults(&mut self, defaults: SearchQuery) {
+        fn fill<T>(param: &mut Option<T>, default: Option<T>) {
+            if param.is_none() {
+                *param = default;
+            }
+        }
+
+        fill(&mut self.offset, defaults.offset);
+        fill(&mut self.limit, defaults.limit);
+        fill(&mut self.page, defaults.page);
+        fill(&mut self.hits_per_page, defaults.hits_per_page);
+        fill(
+            &mut self.attributes_to_retrieve,
+            defaults.attributes_to_retrieve,
+        );
+        fill(&mut self.attributes_to_crop, defaults.attributes_to_crop);
+        fill(&mut self.crop_length, defaults.crop_length);
+        fill(
+            &mut self.attributes_to_highlight,
+            defaults.attributes_to_highlight,
+        );
+        fill(&mut self.matches, defaults.matches);
+        fill(&mut self.filter, defaults.filter);
+        fill(&mut self.sort, defaults.sort);
+        fill(&mut self.facets_distribution, defaults.facets_distribution);
+        fill(&mut self.distinct, defaults.distinct);
+        fill(&mut self.matching_strategy, defaults.matching_strategy);
+        fill(&mut self.locales, defaults.locales);
+        fill(&mut self.highlight_pre_tag, defaults.highlight_pre_tag);
+        fill(&mut self.highlight_post_tag, defaults.highlight_post_tag);
+        fill(&mut self.crop_marker, defaults.crop_marker);
+        fill(&mut self.show_ranking_score, defaults.show_ranking_score);
+        fill(
+            &mut self.show_ranking_score_details,
+            defaults.show_ranking_score_details,
+        );
+        fill(&mut self.ruleset, defaults.ruleset);
+        fill(&mut self.boost, defaults.boost);
+        fill(&mut self.boost_filter, defaults.boost_filter);
+        fill(&mut self.boost_filter_weight, defaults.boost_filter_weight);
+        fill(&mut self.timeout_ms, defaults.timeout_ms);
+    }
+}
+
+/// The instant a search must be done by, if it has a time budget. The engine can't be
+/// interrupted, so the deadline is checked between the stages of the search, between the pages
+/// retrieved from the engine and between the hits. A slow ranking by milli isn't bounded.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Deadline(Option<Instant>);
+
+impl Deadline {
+    fn new(start: Instant, timeout_ms: Option<u64>) -> Self {
+        Self(timeout_ms.map(|timeout| start + Duration::from_millis(timeout)))
+    }
+
+    pub(super) fn is_elapsed(&self) -> bool {
+        self.0.map_or(false, |deadline| Instant::now() >= deadline)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq)]
@@ -87,6 +196,13 @@ pub struct SearchHit {
     pub formatted: Document,
     #[serde(rename = "_matchesInfo", skip_serializing_if = "Option::is_none")]
     pub matches_info: Option<MatchesInfo>,
+    #[serde(rename = "_rankingScore", skip_serializing_if = "Option::is_none")]
+    pub ranking_score: Option<f64>,
+    #[serde(
+        rename = "_rankingScoreDetails",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ranking_score_details: Option<Document>,
 }
 
 #[derive(Serialize, Debug, Clone, PartialEq)]
@@ -98,11 +214,45 @@ pub struct SearchResult {
     pub query: String,
     pub limit: usize,
     pub offset: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hits_per_page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_pages: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_hits: Option<u64>,
     pub processing_time_ms: u128,
+    /// Time spent waiting for a search worker to be available.
+    pub queue_wait_ms: u128,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub facets_distribution: Option<BTreeMap<String, BTreeMap<String, u64>>>,
+    pub facets_distribution: Option<BTreeMap<String, IndexMap<String, u64>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exhaustive_facets_count: Option<bool>,
+    /// The ruleset the search was served with, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ruleset: Option<String>,
+    /// Whether the time budget of the search was spent before it was done. The hits may then keep
+    /// the order of the engine, without their boosts, and the last ones are returned without their
+    /// `_formatted`, `_matchesInfo` and ranking score. The facets distribution is omitted if it
+    /// couldn't be computed in time.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub degraded: bool,
+    /// Time spent in each stage of the search, only reported in the slow query log.
+    #[serde(skip)]
+    pub timings: SearchTimings,
+}
+
+/// The time, in milliseconds, spent in each stage of a search.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchTimings {
+    /// Query parsing, filtering and ranking of the candidates by milli.
+    pub ranking_ms: u128,
+    /// Fetching, highlighting and cropping of the returned documents.
+    pub formatting_ms: u128,
+    /// Computation of the facets distribution.
+    pub facets_distribution_ms: u128,
 }
 
 #[derive(Copy, Clone, Default)]
@@ -121,25 +271,174 @@ impl FormatOptions {
 }
 
 impl Index {
-    pub fn perform_search(&self, query: SearchQuery) -> Result<SearchResult> {
+    pub fn perform_search(&self, mut query: SearchQuery) -> Result<SearchResult> {
         let before_search = Instant::now();
+        let deadline = Deadline::new(before_search, query.timeout_ms);
+        let mut degraded = false;
+
+        if let Some(ref locales) = query.locales {
+            if let Some(locale) = find_unsupported_locale(locales) {
+                return Err(IndexError::InvalidSearchLocales(locale.clone()));
+            }
+        }
+
+        // The facets distribution gives the values of the attributes, so its facets must be
+        // retrievable too.
+        if let Some(ref allowed) = query.retrievable_attributes {
+            let mut requested = query
+                .attributes_to_retrieve
+                .iter()
+                .flatten()
+                .chain(query.facets_distribution.iter().flatten());
+            if let Some(attribute) = requested.find(|attribute| {
+                *attribute != "*" && !is_attribute_retrievable(allowed, attribute)
+            }) {
+                return Err(IndexError::UnauthorizedAttributeToRetrieve(
+                    attribute.clone(),
+                ));
+            }
+        }
+
         let rtxn = self.read_txn()?;
 
+        let mut authorize_typos = None;
+        if let Some(ref name) = query.ruleset {
+            let ruleset = custom_settings::rulesets(self, &rtxn)?
+                .remove(name)
+                .ok_or_else(|| IndexError::InvalidSearchRuleset(name.clone()))?;
+            // Like the defaults of the keys, the ruleset only fills the parameters the query
+            // omits.
+            if query.sort.is_none() {
+                query.sort = ruleset.sort;
+            }
+            if query.matching_strategy.is_none() {
+                query.matching_strategy = ruleset.matching_strategy;
+            }
+            if query.distinct.is_none() {
+                query.distinct = ruleset.distinct;
+            }
+            authorize_typos = ruleset.typo_tolerance;
+        }
+
         let mut search = self.search(&rtxn);
+        if let Some(authorize_typos) = authorize_typos {
+            search.authorize_typos(authorize_typos);
+        }
 
-        if let Some(ref query) = query.q {
-            search.query(query);
+        // The negated terms are removed from the query, their documents are excluded from the
+        // results of the engine. Both are rewritten like the documents were for the tokenization
+        // settings and the locales, the query being rewritten for the locales of all the
+        // localized attributes when it doesn't give any.
+        let tokens = TokenRewriter::from_index(self, &rtxn)?;
+        let locales = match query.locales {
+            Some(ref locales) => locales.clone(),
+            None => custom_settings::localized_attributes(self, &rtxn)?
+                .and_then(LocaleRules::new)
+                .map(|rules| rules.all_locales())
+                .unwrap_or_default(),
+        };
+        let rewrite = |text: String| {
+            let text = match tokens {
+                Some(ref tokens) => tokens.rewrite(&text).into_owned(),
+                None => text,
+            };
+            localize_query(&text, &locales).into_owned()
+        };
+        let (q, negations) = match query.q {
+            Some(ref q) => {
+                let (q, negations) = split_negations(q);
+                (
+                    Some(rewrite(q)),
+                    negations.into_iter().map(rewrite).collect(),
+                )
+            }
+            None => (None, Vec::new()),
+        };
+        let excluded = self.negated_documents(&rtxn, &negations)?;
+
+        // The frequency strategy searches the query again without its most frequent words, one
+        // at a time, the engine only knowing how to remove the last ones.
+        let matching_strategy = query.matching_strategy.unwrap_or_default();
+        let frequency_queries = match (&q, matching_strategy) {
+            (Some(q), MatchingStrategy::Frequency) => self.frequency_queries(&rtxn, q)?,
+            _ => None,
+        };
+        if let Some(ref q) = q {
+            search.query(q);
         }
 
+        search.optional_words(
+            matching_strategy != MatchingStrategy::All && frequency_queries.is_none(),
+        );
+
+        // When `page` or `hitsPerPage` is provided, the results are paginated, and the exact
+        // number of reachable hits and pages is returned.
+        let pagination = (query.page.is_some() || query.hits_per_page.is_some()).then(|| {
+            (
+                query.page.unwrap_or(1),
+                query.hits_per_page.unwrap_or(DEFAULT_SEARCH_LIMIT),
+            )
+        });
+
+        let (offset, limit) = match pagination {
+            // There is no page 0, we return no hits.
+            Some((0, _)) => (0, 0),
+            Some((page, hits_per_page)) => {
+                ((page - 1).saturating_mul(hits_per_page), hits_per_page)
+            }
+            None => (
+                query.offset.unwrap_or(0),
+                query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+            ),
+        };
+
         // Make sure that a user can't get more documents than the hard limit,
         // we align that on the offset too.
-        let offset = min(query.offset.unwrap_or(0), HARD_RESULT_LIMIT);
-        let limit = min(query.limit, HARD_RESULT_LIMIT.saturating_sub(offset));
+        let offset = min(offset, HARD_RESULT_LIMIT);
+        let limit = min(limit, HARD_RESULT_LIMIT.saturating_sub(offset));
 
-        search.offset(offset);
-        search.limit(limit);
+        // The existence conditions of the filter are evaluated by us, milli filters the rest.
+        let filter = match query.filter {
+            Some(ref filter) => split_existence_conditions(filter)?,
+            None => Default::default(),
+        };
+        let allowed = self.existence_documents(&rtxn, &filter.conditions)?;
+
+        let boost = self.search_boost(
+            &rtxn,
+            query.boost.as_ref(),
+            query.boost_filter.as_ref(),
+            query.boost_filter_weight,
+        )?;
+
+        // A query-time distinct attribute, the boosts, the negated terms and the existence
+        // conditions are applied on top of the results of the engine, so we need to retrieve all
+        // the hits we could return before paginating them.
+        let distinct = match query.distinct {
+            Some(ref attribute) => Some(self.query_distinct_field(&rtxn, attribute)?),
+            None => None,
+        };
+        let post_filtered = distinct.is_some()
+            || boost.is_some()
+            || !excluded.is_empty()
+            || allowed.is_some()
+            || frequency_queries.is_some();
+        // The distinct attribute and the boosts are applied on all the hits that can be returned,
+        // the other post filters only need the requested page.
+        let needed = match distinct.is_some() || boost.is_some() {
+            true => HARD_RESULT_LIMIT,
+            false => offset + limit,
+        };
 
-        if let Some(ref filter) = query.filter {
+        if post_filtered {
+            search.offset(0);
+            search.limit(needed);
+        } else {
+            search.offset(offset);
+            search.limit(limit);
+        }
+
+        if let Some(ref filter) = filter.filter {
             if let Some(facets) = parse_filter(filter)? {
                 search.filter(facets);
             }
@@ -156,12 +455,68 @@ impl Index {
             search.sort_criteria(sort);
         }
 
+        let before_ranking = Instant::now();
+        let keep = |id| {
+            !excluded.contains(id)
+                && allowed
+                    .as_ref()
+                    .map_or(true, |allowed| allowed.contains(id))
+        };
+        let result = match frequency_queries {
+            Some(queries) => {
+                let (result, interrupted) =
+                    execute_by_frequency(&mut search, queries, needed, keep, &deadline)?;
+                degraded |= interrupted;
+                result
+            }
+            None if excluded.is_empty() && allowed.is_none() => search.execute()?,
+            None => {
+                let (result, interrupted) = execute_filtered(&mut search, needed, keep, &deadline)?;
+                degraded |= interrupted;
+                result
+            }
+        };
         let milli::SearchResult {
             documents_ids,
             matching_words,
             candidates,
             ..
-        } = search.execute()?;
+        } = result;
+        // Only the hits that can be returned are ranked again with their boosts. Past the
+        // deadline, they keep the order of the engine.
+        let boosted = match boost {
+            Some(ref boost) => {
+                let documents_ids = &documents_ids[..min(documents_ids.len(), HARD_RESULT_LIMIT)];
+                let boosted =
+                    self.boost_documents(&rtxn, documents_ids, boost, q.as_deref(), &deadline)?;
+                degraded |= boosted.is_none();
+                boosted
+            }
+            None => None,
+        };
+        let (documents_ids, boost_factors) =
+            boosted.unwrap_or_else(|| (documents_ids, HashMap::new()));
+        let mut candidates = candidates - &excluded;
+        if let Some(ref allowed) = allowed {
+            candidates &= allowed;
+        }
+        let (documents_ids, nb_hits) = match distinct {
+            Some(distinct) => {
+                let documents_ids = self.distinct_documents(&rtxn, documents_ids, distinct)?;
+                // We only know the distinct hits among the ones that can be returned.
+                let nb_hits = documents_ids.len() as u64;
+                let documents_ids = documents_ids.into_iter().skip(offset).take(limit).collect();
+                (documents_ids, nb_hits)
+            }
+            None if post_filtered => {
+                let documents_ids = documents_ids.into_iter().skip(offset).take(limit).collect();
+                (documents_ids, candidates.len())
+            }
+            None => (documents_ids, candidates.len()),
+        };
+        let ranking_ms = before_ranking.elapsed().as_millis();
+
+        let before_formatting = Instant::now();
 
         let fields_ids_map = self.fields_ids_map(&rtxn).unwrap();
 
@@ -170,6 +525,20 @@ impl Index {
             .map(|fields| fields.into_iter().collect::<BTreeSet<_>>())
             .unwrap_or_else(|| fields_ids_map.iter().map(|(id, _)| id).collect());
 
+        // The attributes the API key can't retrieve are never displayed, neither in the hits nor
+        // in their `_formatted` and `_matchesInfo`.
+        let displayed_ids: BTreeSet<_> = match query.retrievable_attributes {
+            Some(ref allowed) => displayed_ids
+                .into_iter()
+                .filter(|&id| {
+                    fields_ids_map
+                        .name(id)
+                        .map_or(false, |name| is_attribute_retrievable(allowed, name))
+                })
+                .collect(),
+            None => displayed_ids,
+        };
+
         let fids = |attrs: &BTreeSet<String>| {
             let mut ids = BTreeSet::new();
             for attr in attrs {
@@ -202,6 +571,12 @@ impl Index {
 
         let attr_to_crop = query.attributes_to_crop.unwrap_or_default();
 
+        // The formatting parameters missing from the query are the ones of the index.
+        let formatting = custom_settings::formatting(self, &rtxn)?;
+        let crop_length = query
+            .crop_length
+            .unwrap_or_else(|| formatting.crop_length());
+
         // Attributes in `formatted_options` correspond to the attributes that will be in `_formatted`
         // These attributes are:
         // - the attributes asked to be highlighted or cropped (with `attributesToCrop` or `attributesToHighlight`)
@@ -210,7 +585,7 @@ impl Index {
         let formatted_options = compute_formatted_options(
             &attr_to_highlight,
             &attr_to_crop,
-            query.crop_length,
+            crop_length,
             &to_retrieve_ids,
             &fields_ids_map,
             &displayed_ids,
@@ -223,15 +598,40 @@ impl Index {
 
         let formatter = Formatter::new(
             &analyzer,
-            (query.highlight_pre_tag, query.highlight_post_tag),
-            query.crop_marker,
+            (
+                query
+                    .highlight_pre_tag
+                    .unwrap_or_else(|| formatting.highlight_pre_tag()),
+                query
+                    .highlight_post_tag
+                    .unwrap_or_else(|| formatting.highlight_post_tag()),
+            ),
+            query
+                .crop_marker
+                .unwrap_or_else(|| formatting.crop_marker()),
         );
 
+        let criteria = self.criteria(&rtxn)?;
+        let searchable_attributes = self.searchable_attribute_names(&rtxn)?;
+        let all_ids: BTreeSet<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let show_ranking_score = query.show_ranking_score.unwrap_or(false);
+        let show_ranking_score_details = query.show_ranking_score_details.unwrap_or(false);
+        let score_computer = (show_ranking_score || show_ranking_score_details).then(|| {
+            ScoreComputer::new(
+                q.as_deref(),
+                &analyzer,
+                &criteria,
+                &searchable_attributes,
+                query.sort.as_deref().unwrap_or_default(),
+            )
+        });
+
         let mut documents = Vec::new();
 
-        let documents_iter = self.documents(&rtxn, documents_ids)?;
+        let documents_iter = compression::documents(self, &rtxn, documents_ids)?;
 
-        for (_id, obkv) in documents_iter {
+        for (id, document) in documents_iter {
+            let obkv = obkv::KvReaderU16::new(&document);
             // First generate a document with all the displayed fields
             let displayed_document = make_document(&displayed_ids, &fields_ids_map, obkv)?;
 
@@ -242,8 +642,25 @@ impl Index {
             let mut document =
                 permissive_json_pointer::select_values(&displayed_document, attributes_to_retrieve);
 
+            // Past the deadline, the hits left are only made of their retrieved attributes.
+            if deadline.is_elapsed() {
+                degraded = true;
+                if let Some(sort) = query.sort.as_ref() {
+                    insert_geo_distance(sort, &mut document);
+                }
+                documents.push(SearchHit {
+                    document,
+                    formatted: Document::new(),
+                    matches_info: None,
+                    ranking_score: None,
+                    ranking_score_details: None,
+                });
+                continue;
+            }
+
             let matches_info = query
                 .matches
+                .unwrap_or(false)
                 .then(|| compute_matches(&matching_words, &document, &analyzer));
 
             let formatted = format_fields(
@@ -258,46 +675,256 @@ impl Index {
                 insert_geo_distance(sort, &mut document);
             }
 
+            // The score is computed on the whole document, since the searchable attributes
+            // are not necessarily displayed.
+            let ranking_score = match score_computer {
+                Some(ref computer) => {
+                    let full_document = make_document(&all_ids, &fields_ids_map, obkv)?;
+                    Some(computer.compute(&full_document, &displayed_document, &analyzer))
+                }
+                None => None,
+            };
+            let (ranking_score, ranking_score_details) = match ranking_score {
+                Some(mut score) => {
+                    // The score doesn't include the boosts, which are reported aside.
+                    if let Some(factor) = boost_factors.get(&id) {
+                        score
+                            .details
+                            .insert("boost".to_string(), json!({ "factor": factor }));
+                    }
+                    (
+                        show_ranking_score.then(|| score.score),
+                        show_ranking_score_details.then(|| score.details),
+                    )
+                }
+                None => (None, None),
+            };
+
             let hit = SearchHit {
                 document,
                 formatted,
                 matches_info,
+                ranking_score,
+                ranking_score_details,
             };
             documents.push(hit);
         }
 
-        let nb_hits = candidates.len();
+        let formatting_ms = before_formatting.elapsed().as_millis();
 
+        let before_facets_distribution = Instant::now();
         let facets_distribution = match query.facets_distribution {
+            Some(_) if deadline.is_elapsed() => {
+                degraded = true;
+                None
+            }
             Some(ref fields) => {
+                let faceting = custom_settings::faceting(self, &rtxn)?;
+                let max_values = faceting.max_values_per_facet();
+
                 let mut facets_distribution = self.facets_distribution(&rtxn);
-                if fields.iter().all(|f| f != "*") {
-                    facets_distribution.facets(fields);
+                match query.retrievable_attributes {
+                    _ if fields.iter().all(|f| f != "*") => {
+                        facets_distribution.facets(fields);
+                    }
+                    // `*` only stands for the facets the API key can retrieve.
+                    Some(ref allowed) => {
+                        let facets: Vec<_> = self
+                            .filterable_fields(&rtxn)?
+                            .into_iter()
+                            .filter(|facet| is_attribute_retrievable(allowed, facet))
+                            .collect();
+                        facets_distribution.facets(facets);
+                    }
+                    None => (),
+                }
+                // the values sorted by count are all fetched, to keep the most frequent ones.
+                let sorted_by_count = faceting
+                    .sort_facet_values_by()
+                    .values()
+                    .any(|sort| *sort == FacetValuesSort::Count);
+                if sorted_by_count {
+                    facets_distribution
+                        .max_values_by_facet(max(max_values, MAX_SORTED_VALUES_PER_FACET));
+                } else {
+                    facets_distribution.max_values_by_facet(max_values);
                 }
                 let distribution = facets_distribution.candidates(candidates).execute()?;
 
+                let distribution = distribution
+                    .into_iter()
+                    .map(|(facet, values)| {
+                        let mut values: Vec<_> = values.into_iter().collect();
+                        if faceting.sort_of(&facet) == FacetValuesSort::Count {
+                            // the sort is stable, the values of the same count stay sorted.
+                            values.sort_by(|(_, a), (_, b)| b.cmp(a));
+                        }
+                        values.truncate(max_values);
+                        (facet, values.into_iter().collect())
+                    })
+                    .collect();
+
                 Some(distribution)
             }
             None => None,
         };
 
+        let facets_distribution_ms = before_facets_distribution.elapsed().as_millis();
+
         let exhaustive_facets_count = facets_distribution.as_ref().map(|_| false); // not implemented yet
 
+        // The hits beyond the hard limit can't be reached, so neither they nor their pages are
+        // counted, `nbHits` still counts all of them.
+        let reachable_hits = min(nb_hits, HARD_RESULT_LIMIT as u64);
+        let total_pages = pagination.map(|(_, hits_per_page)| {
+            if hits_per_page == 0 {
+                0
+            } else {
+                (reachable_hits as usize + hits_per_page - 1) / hits_per_page
+            }
+        });
+
         let result = SearchResult {
             exhaustive_nb_hits: false, // not implemented yet
             hits: documents,
             nb_hits,
             query: query.q.clone().unwrap_or_default(),
-            limit: query.limit,
-            offset: query.offset.unwrap_or_default(),
+            // The offset and limit actually applied, once clamped to the hard limit.
+            limit,
+            offset,
+            hits_per_page: pagination.map(|(_, hits_per_page)| hits_per_page),
+            page: pagination.map(|(page, _)| page),
+            total_pages,
+            total_hits: pagination.map(|_| reachable_hits),
             processing_time_ms: before_search.elapsed().as_millis(),
+            queue_wait_ms: 0,
             facets_distribution,
             exhaustive_facets_count,
+            ruleset: query.ruleset,
+            degraded,
+            timings: SearchTimings {
+                ranking_ms,
+                formatting_ms,
+                facets_distribution_ms,
+            },
         };
         Ok(result)
     }
 }
 
+impl Index {
+    /// Draws the ruleset serving a search that doesn't ask for one, according to the traffic
+    /// shares of the rulesets. The rest of the traffic is served with the settings of the index.
+    pub fn assign_ruleset(&self) -> Result<Option<String>> {
+        let rtxn = self.read_txn()?;
+        let rulesets = custom_settings::rulesets(self, &rtxn)?;
+        if rulesets.is_empty() {
+            return Ok(None);
+        }
+
+        let mut draw = rand::thread_rng().gen_range(0..100u32);
+        for (name, ruleset) in rulesets {
+            let traffic = u32::from(ruleset.traffic);
+            if draw < traffic {
+                return Ok(Some(name));
+            }
+            draw -= traffic;
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the field id of the distinct attribute of a query, which must be filterable.
+    fn query_distinct_field(&self, rtxn: &RoTxn, attribute: &str) -> Result<Option<FieldId>> {
+        let filterable = self.filterable_fields(rtxn)?;
+        if !filterable.contains(attribute) {
+            let mut filterable: Vec<_> = filterable.into_iter().collect();
+            filterable.sort_unstable();
+            return Err(IndexError::InvalidDistinctAttribute(
+                attribute.to_string(),
+                filterable,
+            ));
+        }
+
+        Ok(self.fields_ids_map(rtxn)?.id(attribute))
+    }
+
+    /// Returns the queries of the frequency strategy: the query, then the query without its most
+    /// frequent word, and so on until a single word is left. The words keep the order of the
+    /// query. Queries containing a phrase are left to the engine, which removes their last words,
+    /// since removing words from them would break the phrase.
+    fn frequency_queries(&self, rtxn: &RoTxn, query: &str) -> Result<Option<Vec<String>>> {
+        if query.contains('"') {
+            return Ok(None);
+        }
+
+        let stop_words = fst::Set::default();
+        let mut config = AnalyzerConfig::default();
+        config.stop_words(&stop_words);
+        let analyzer = Analyzer::new(config);
+
+        let mut words = Vec::new();
+        for (_, token) in analyzer.analyze(query).reconstruct() {
+            if token.is_word() {
+                let word = token.text().to_string();
+                let frequency = self
+                    .word_docids
+                    .get(rtxn, word.as_str())?
+                    .map_or(0, |docids| docids.len());
+                words.push((frequency, word));
+            }
+        }
+        if words.len() < 2 {
+            return Ok(None);
+        }
+
+        // The most frequent words are removed first, the last ones first among the words with
+        // the same frequency.
+        let mut removal_order: Vec<_> = (0..words.len()).collect();
+        removal_order.sort_by_key(|&i| (Reverse(words[i].0), Reverse(i)));
+
+        let mut removed = HashSet::new();
+        let mut queries = Vec::new();
+        for i in removal_order {
+            let query: Vec<_> = words
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| !removed.contains(j))
+                .map(|(_, (_, word))| word.as_str())
+                .collect();
+            queries.push(query.join(" "));
+            removed.insert(i);
+        }
+
+        Ok(Some(queries))
+    }
+
+    /// Keeps, in ranking order, the first document of each value of the distinct attribute.
+    /// The documents that don't have the distinct attribute are all kept.
+    fn distinct_documents(
+        &self,
+        rtxn: &RoTxn,
+        documents_ids: Vec<DocumentId>,
+        distinct: Option<FieldId>,
+    ) -> Result<Vec<DocumentId>> {
+        let distinct = match distinct {
+            Some(distinct) => distinct,
+            None => return Ok(documents_ids),
+        };
+
+        let mut seen = HashSet::new();
+        let mut distinct_ids = Vec::new();
+        for (id, document) in compression::documents(self, rtxn, documents_ids)? {
+            match obkv::KvReaderU16::new(&document).get(distinct) {
+                Some(value) if !seen.insert(value.to_vec()) => (),
+                _ => distinct_ids.push(id),
+            }
+        }
+
+        Ok(distinct_ids)
+    }
+}
+
 fn insert_geo_distance(sorts: &[String], document: &mut Document) {
     lazy_static::lazy_static! {
         static ref GEO_REGEX: Regex =
@@ -367,6 +994,12 @@ fn compute_value_matches<'a, A: AsRef<[u8]>>(
     }
 }
 
+/// Returns whether an API key allowed to retrieve the `allowed` attributes can retrieve the
+/// attribute, `*` allowing all of them.
+fn is_attribute_retrievable(allowed: &BTreeSet<String>, attribute: &str) -> bool {
+    allowed.contains("*") || allowed.contains(attribute)
+}
+
 fn compute_formatted_options(
     attr_to_highlight: &HashSet<String>,
     attr_to_crop: &[String],
@@ -767,7 +1400,85 @@ impl<'a, A: AsRef<[u8]>> Formatter<'a, A> {
     }
 }
 
-fn parse_filter(facets: &Value) -> Result<Option<Filter>> {
+/// Searches each query of the frequency strategy with all its words mandatory, until `needed` hits
+/// passing `keep` are found. The hits of a query are ranked after the ones of the previous
+/// queries, which contain more words of the query.
+fn execute_by_frequency(
+    search: &mut milli::Search,
+    queries: Vec<String>,
+    needed: usize,
+    keep: impl Fn(DocumentId) -> bool,
+    deadline: &Deadline,
+) -> Result<(milli::SearchResult, bool)> {
+    let mut documents_ids = Vec::new();
+    let mut candidates = RoaringBitmap::new();
+    let mut first = None;
+    let mut interrupted = false;
+    for query in queries {
+        search.query(query);
+        let remaining = needed.saturating_sub(documents_ids.len());
+        let previous = &candidates;
+        let (result, query_interrupted) = execute_filtered(
+            search,
+            remaining,
+            |id| keep(id) && !previous.contains(id),
+            deadline,
+        )?;
+
+        documents_ids.extend_from_slice(&result.documents_ids);
+        candidates |= &result.candidates;
+        interrupted = query_interrupted;
+        // The matching words of the first query, made of all the words, are highlighted.
+        if first.is_none() {
+            first = Some(result);
+        }
+        if interrupted || documents_ids.len() >= needed {
+            break;
+        }
+    }
+
+    let mut result = match first {
+        Some(result) => result,
+        None => search.execute()?,
+    };
+    result.documents_ids = documents_ids;
+    result.candidates = candidates;
+    Ok((result, interrupted))
+}
+
+/// Retrieves the hits of the engine passing `keep` page by page, the pages growing each time,
+/// until `needed` of them are found, the engine runs out of hits or the deadline is elapsed. milli
+/// can't restrict its candidates to a set of documents before ranking them, and the documents we
+/// filter can be ranked anywhere. Returns whether the search was interrupted by the deadline.
+fn execute_filtered(
+    search: &mut milli::Search,
+    needed: usize,
+    keep: impl Fn(DocumentId) -> bool,
+    deadline: &Deadline,
+) -> Result<(milli::SearchResult, bool)> {
+    let mut kept = Vec::new();
+    let mut offset = 0;
+    let mut limit = max(needed, DEFAULT_SEARCH_LIMIT);
+    loop {
+        search.offset(offset);
+        search.limit(limit);
+        let mut result = search.execute()?;
+        let retrieved = result.documents_ids.len();
+        kept.extend(result.documents_ids.iter().copied().filter(|id| keep(*id)));
+
+        let exhausted = retrieved < limit || offset + retrieved >= result.candidates.len() as usize;
+        let interrupted = !exhausted && kept.len() < needed && deadline.is_elapsed();
+        if exhausted || interrupted || kept.len() >= needed {
+            result.documents_ids = kept;
+            return Ok((result, interrupted));
+        }
+
+        offset += retrieved;
+        limit = limit.saturating_mul(2);
+    }
+}
+
+pub(super) fn parse_filter(facets: &Value) -> Result<Option<Filter>> {
     match facets {
         Value::String(expr) => {
             let condition = Filter::from_str(expr)?;
@@ -0,0 +1,41 @@
+//! The built-in stop words lists, selected by the `preset` of the `stopWords` setting. Each list
+//! holds one word per line.
+
+/// The stop words lists by ISO 639-1 language code.
+const PRESETS: &[(&str, &str)] = &[
+    ("de", include_str!("stop_words/de.txt")),
+    ("en", include_str!("stop_words/en.txt")),
+    ("es", include_str!("stop_words/es.txt")),
+    ("fr", include_str!("stop_words/fr.txt")),
+    ("it", include_str!("stop_words/it.txt")),
+    ("nl", include_str!("stop_words/nl.txt")),
+    ("pt", include_str!("stop_words/pt.txt")),
+];
+
+/// Returns the stop words of the preset, `None` if there is no list for the language.
+pub fn preset_words(language: &str) -> Option<impl Iterator<Item = &'static str>> {
+    PRESETS
+        .iter()
+        .find(|(code, _)| *code == language)
+        .map(|(_, words)| words.lines().map(str::trim).filter(|word| !word.is_empty()))
+}
+
+/// The language codes of the built-in lists.
+pub fn preset_languages() -> impl Iterator<Item = &'static str> {
+    PRESETS.iter().map(|(code, _)| *code)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn presets_hold_single_words() {
+        for language in preset_languages() {
+            let words: Vec<_> = preset_words(language).unwrap().collect();
+            assert!(!words.is_empty(), "{}", language);
+            assert!(words.iter().all(|word| !word.contains(' ')), "{}", language);
+        }
+        assert!(preset_words("xx").is_none());
+    }
+}
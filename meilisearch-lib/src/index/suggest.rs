@@ -0,0 +1,123 @@
+//! Completion suggestions built from the dictionary of the index: the last word of the query is
+//! completed with the indexed words it prefixes, the most frequent first. When the query has
+//! other words, the completions are counted among the documents containing all of them.
+
+use std::time::Instant;
+
+use fst::automaton::Str;
+use fst::{Automaton, IntoStreamer, Streamer};
+use milli::tokenizer::{Analyzer, AnalyzerConfig};
+use roaring::RoaringBitmap;
+use serde::Serialize;
+
+use super::error::Result;
+use super::index::Index;
+
+pub const DEFAULT_SUGGEST_LIMIT: usize = 10;
+
+/// The maximum number of dictionary words completing the query that are ranked, so that a short
+/// prefix doesn't read the whole dictionary.
+const MAX_COMPLETIONS: usize = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct SuggestQuery {
+    pub q: String,
+    pub limit: usize,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    pub suggestion: String,
+    /// The number of documents containing all the words of the suggestion.
+    pub count: u64,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestResult {
+    pub suggestions: Vec<Suggestion>,
+    pub query: String,
+    pub processing_time_ms: u128,
+}
+
+impl Index {
+    pub fn suggest(&self, query: SuggestQuery) -> Result<SuggestResult> {
+        let before_suggest = Instant::now();
+        let rtxn = self.read_txn()?;
+
+        let stop_words = fst::Set::default();
+        let mut config = AnalyzerConfig::default();
+        config.stop_words(&stop_words);
+        let analyzer = Analyzer::new(config);
+
+        let mut words: Vec<String> = analyzer
+            .analyze(&query.q)
+            .reconstruct()
+            .filter(|(_, token)| token.is_word())
+            .map(|(_, token)| token.text().to_string())
+            .collect();
+
+        let mut suggestions = Vec::new();
+        if let Some(prefix) = words.pop() {
+            // The documents containing the words preceding the completed one.
+            let mut context: Option<RoaringBitmap> = None;
+            for word in &words {
+                let docids = self
+                    .word_docids
+                    .get(&rtxn, word.as_str())?
+                    .unwrap_or_default();
+                context = Some(match context {
+                    Some(context) => context & docids,
+                    None => docids,
+                });
+            }
+
+            let words_fst = self.words_fst(&rtxn)?;
+            let mut stream = words_fst
+                .search(Str::new(&prefix).starts_with())
+                .into_stream();
+            let mut completions = Vec::new();
+            let mut ranked = 0;
+            while let Some(completion) = stream.next() {
+                if ranked == MAX_COMPLETIONS {
+                    break;
+                }
+                ranked += 1;
+                let completion = match std::str::from_utf8(completion) {
+                    Ok(completion) => completion,
+                    Err(_) => continue,
+                };
+                let docids = self.word_docids.get(&rtxn, completion)?.unwrap_or_default();
+                let count = match context {
+                    Some(ref context) => (context & &docids).len(),
+                    None => docids.len(),
+                };
+                if count > 0 {
+                    completions.push((count, completion.to_string()));
+                }
+            }
+
+            // the most frequent completions first, in alphabetical order otherwise.
+            completions.sort_by(|(a, a_word), (b, b_word)| b.cmp(a).then(a_word.cmp(b_word)));
+            suggestions = completions
+                .into_iter()
+                .take(query.limit)
+                .map(|(count, completion)| {
+                    let mut suggestion = words.clone();
+                    suggestion.push(completion);
+                    Suggestion {
+                        suggestion: suggestion.join(" "),
+                        count,
+                    }
+                })
+                .collect();
+        }
+
+        Ok(SuggestResult {
+            suggestions,
+            query: query.q,
+            processing_time_ms: before_suggest.elapsed().as_millis(),
+        })
+    }
+}
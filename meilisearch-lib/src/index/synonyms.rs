@@ -0,0 +1,354 @@
+//! Conversion of the synonyms setting from and to the synonyms file formats used by the
+//! spreadsheets and search engines the synonyms lists are usually maintained with.
+
+use std::collections::BTreeMap;
+use std::iter;
+
+use meilisearch_error::{Code, ErrorCode};
+use serde::{Deserialize, Serialize};
+
+pub type Synonyms = BTreeMap<String, Vec<String>>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SynonymsError {
+    #[error("The synonyms file is not valid UTF-8.")]
+    InvalidEncoding,
+    #[error("Invalid synonyms file at line {line}: {message}.")]
+    InvalidLine { line: usize, message: String },
+    #[error("Invalid synonyms file: {0}.")]
+    Csv(#[from] csv::Error),
+}
+
+impl ErrorCode for SynonymsError {
+    fn error_code(&self) -> Code {
+        Code::InvalidSynonymsFile
+    }
+}
+
+type Result<T> = std::result::Result<T, SynonymsError>;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SynonymsFormat {
+    /// One row per word, the first column is the word and the following columns its synonyms.
+    Csv,
+    /// The Solr synonyms file format: either a list of equivalent words, `tv, television`, or
+    /// an explicit mapping, `i-pod, i pod => ipod`. Lines starting with `#` are comments.
+    Solr,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SynonymsImportMode {
+    /// The imported synonyms are added to the existing ones.
+    Merge,
+    /// The imported synonyms replace the existing ones.
+    Replace,
+}
+
+impl Default for SynonymsImportMode {
+    fn default() -> Self {
+        Self::Merge
+    }
+}
+
+/// A word whose synonyms were already defined with different values when merging an import.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SynonymsConflict {
+    pub word: String,
+    pub existing: Vec<String>,
+    pub imported: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SynonymsImportReport {
+    /// The number of words for which synonyms were imported.
+    pub imported_words: usize,
+    /// The conflicting words, their synonyms were merged.
+    pub conflicts: Vec<SynonymsConflict>,
+}
+
+/// Parses a synonyms file. A word defined multiple times receives all the synonyms it is
+/// associated with.
+pub fn parse_synonyms(format: SynonymsFormat, content: &[u8]) -> Result<Synonyms> {
+    let content = std::str::from_utf8(content).map_err(|_| SynonymsError::InvalidEncoding)?;
+    let mut synonyms = Synonyms::new();
+
+    let mut insert = |word: String, words: Vec<String>| {
+        let entry = synonyms.entry(word.clone()).or_insert_with(Vec::new);
+        for synonym in words {
+            if synonym != word && !entry.contains(&synonym) {
+                entry.push(synonym);
+            }
+        }
+    };
+
+    match format {
+        SynonymsFormat::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .trim(csv::Trim::All)
+                .from_reader(content.as_bytes());
+
+            for (i, record) in reader.records().enumerate() {
+                let record = record?;
+                let mut words = record.iter().map(normalize).filter(|w| !w.is_empty());
+                let word = match words.next() {
+                    Some(word) => word,
+                    None => continue,
+                };
+                let words: Vec<_> = words.collect();
+                if words.is_empty() {
+                    return Err(SynonymsError::InvalidLine {
+                        line: i + 1,
+                        message: format!("`{}` has no synonym", word),
+                    });
+                }
+                insert(word, words);
+            }
+        }
+        SynonymsFormat::Solr => {
+            for (i, line) in content.lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+
+                let invalid = |message: &str| SynonymsError::InvalidLine {
+                    line: i + 1,
+                    message: message.to_string(),
+                };
+
+                match line.split_once("=>") {
+                    Some((words, synonyms)) => {
+                        let words = split_solr_words(words);
+                        let synonyms = split_solr_words(synonyms);
+                        if words.is_empty() || synonyms.is_empty() {
+                            return Err(invalid("both sides of `=>` must contain a word"));
+                        }
+                        for word in words {
+                            insert(word, synonyms.clone());
+                        }
+                    }
+                    None => {
+                        let words = split_solr_words(line);
+                        if words.len() < 2 {
+                            return Err(invalid("a list of equivalent words needs two words"));
+                        }
+                        for word in &words {
+                            insert(word.clone(), words.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    synonyms.retain(|_, synonyms| !synonyms.is_empty());
+
+    Ok(synonyms)
+}
+
+/// Writes the synonyms in the given format, each word being written along with its synonyms.
+pub fn export_synonyms(format: SynonymsFormat, synonyms: &Synonyms) -> Result<String> {
+    match format {
+        SynonymsFormat::Csv => {
+            let mut writer = csv::WriterBuilder::new()
+                .flexible(true)
+                .from_writer(Vec::new());
+            for (word, synonyms) in synonyms {
+                writer.write_record(iter::once(word).chain(synonyms))?;
+            }
+            let bytes = writer
+                .into_inner()
+                .map_err(|e| csv::Error::from(e.into_error()))?;
+            Ok(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        SynonymsFormat::Solr => {
+            let mut content = String::new();
+            for (word, synonyms) in synonyms {
+                let synonyms: Vec<_> = synonyms.iter().map(|s| escape_solr_word(s)).collect();
+                content.push_str(&escape_solr_word(word));
+                content.push_str(" => ");
+                content.push_str(&synonyms.join(", "));
+                content.push('\n');
+            }
+            Ok(content)
+        }
+    }
+}
+
+/// Combines the imported synonyms with the existing ones according to `mode`.
+pub fn import_synonyms(
+    existing: Synonyms,
+    imported: Synonyms,
+    mode: SynonymsImportMode,
+) -> (Synonyms, SynonymsImportReport) {
+    let mut report = SynonymsImportReport {
+        imported_words: imported.len(),
+        conflicts: Vec::new(),
+    };
+
+    let synonyms = match mode {
+        SynonymsImportMode::Replace => imported,
+        SynonymsImportMode::Merge => {
+            let mut synonyms = existing;
+            for (word, imported) in imported {
+                match synonyms.get_mut(&word) {
+                    Some(existing) if *existing != imported => {
+                        report.conflicts.push(SynonymsConflict {
+                            word,
+                            existing: existing.clone(),
+                            imported: imported.clone(),
+                        });
+                        for synonym in imported {
+                            if !existing.contains(&synonym) {
+                                existing.push(synonym);
+                            }
+                        }
+                    }
+                    Some(_) => (),
+                    None => {
+                        synonyms.insert(word, imported);
+                    }
+                }
+            }
+            synonyms
+        }
+    };
+
+    (synonyms, report)
+}
+
+/// The engine doesn't make a difference between the case and the spacing of the words.
+fn normalize(word: &str) -> String {
+    word.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Splits a list of Solr words on the commas that are not escaped with a `\`.
+fn split_solr_words(words: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut chars = words.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => current.extend(chars.next()),
+            ',' => result.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    result.push(current);
+
+    result
+        .iter()
+        .map(|word| normalize(word))
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+fn escape_solr_word(word: &str) -> String {
+    word.replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace("=>", "=\\>")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn synonyms(entries: &[(&str, &[&str])]) -> Synonyms {
+        entries
+            .iter()
+            .map(|(word, synonyms)| {
+                (
+                    word.to_string(),
+                    synonyms.iter().map(|s| s.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_csv() {
+        let content = b"TV, television,telly\nlaptop,notebook\n";
+        let parsed = parse_synonyms(SynonymsFormat::Csv, content).unwrap();
+
+        assert_eq!(
+            parsed,
+            synonyms(&[
+                ("tv", &["television", "telly"]),
+                ("laptop", &["notebook"])
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_csv_without_synonym() {
+        let content = b"tv,television\nlaptop\n";
+        let error = parse_synonyms(SynonymsFormat::Csv, content).unwrap_err();
+
+        assert!(matches!(error, SynonymsError::InvalidLine { line: 2, .. }));
+    }
+
+    #[test]
+    fn parse_solr() {
+        let content = b"# comment\ntv, television\ni-pod, i pod => ipod\n";
+        let parsed = parse_synonyms(SynonymsFormat::Solr, content).unwrap();
+
+        assert_eq!(
+            parsed,
+            synonyms(&[
+                ("television", &["tv"]),
+                ("tv", &["television"]),
+                ("i-pod", &["ipod"]),
+                ("i pod", &["ipod"]),
+            ])
+        );
+    }
+
+    #[test]
+    fn export_round_trip() {
+        let original = synonyms(&[("a\\,b", &["c", "d=>e"]), ("tv", &["television"])]);
+
+        for format in [SynonymsFormat::Csv, SynonymsFormat::Solr] {
+            let exported = export_synonyms(format, &original).unwrap();
+            let parsed = parse_synonyms(format, exported.as_bytes()).unwrap();
+            assert_eq!(parsed, original);
+        }
+    }
+
+    #[test]
+    fn merge_reports_conflicts() {
+        let existing = synonyms(&[("tv", &["television"]), ("car", &["automobile"])]);
+        let imported = synonyms(&[("tv", &["telly"]), ("car", &["automobile"])]);
+
+        let (merged, report) = import_synonyms(existing, imported, SynonymsImportMode::Merge);
+
+        assert_eq!(
+            merged,
+            synonyms(&[("tv", &["television", "telly"]), ("car", &["automobile"])])
+        );
+        assert_eq!(report.imported_words, 2);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].word, "tv");
+    }
+
+    #[test]
+    fn replace_drops_existing_synonyms() {
+        let existing = synonyms(&[("tv", &["television"])]);
+        let imported = synonyms(&[("car", &["automobile"])]);
+
+        let (replaced, report) =
+            import_synonyms(existing, imported.clone(), SynonymsImportMode::Replace);
+
+        assert_eq!(replaced, imported);
+        assert!(report.conflicts.is_empty());
+    }
+}
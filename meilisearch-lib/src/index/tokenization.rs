@@ -0,0 +1,596 @@
+//! The `separatorTokens`, `nonSeparatorTokens` and `dictionary` of the index. The tokenizer of
+//! milli can't be configured, so the texts it tokenizes are rewritten instead, the same way for
+//! the documents and the queries. milli is not aware of it: the documents it indexes or reads
+//! during an update are rewritten beforehand, and the original documents are written back at the
+//! end of the update.
+//!
+//! A separator token is replaced by a space. A non-separator token or a word of the dictionary is
+//! replaced by an alphanumeric word the tokenizer keeps whole, its other characters being replaced
+//! by their hexadecimal code, e.g. `c++` becomes `c2b2b`.
+//!
+//! milli reads the facet values and the ids from the same documents, so the filterable and
+//! sortable attributes, the distinct attribute and the primary key are never rewritten, and are
+//! tokenized as usual.
+//!
+//! The texts of the localized attributes are then rewritten for their locales, see
+//! [`super::locales`].
+
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+use milli::documents::{DocumentBatchBuilder, DocumentBatchReader};
+use milli::heed::types::ByteSlice;
+use milli::heed::{RoTxn, RwTxn};
+use milli::update::{IndexDocumentsMethod, IndexerConfig, Setting};
+use milli::{DocumentId, FieldId, FieldsIdsMap, BEU32};
+use obkv::{KvReaderU16, KvWriterU16};
+use rayon::prelude::*;
+use roaring::RoaringBitmap;
+use serde_json::{Map, Value};
+
+use super::custom_settings;
+use super::error::Result;
+use super::locales::{localize, LocaleRules};
+use super::updates::Settings;
+use super::Checked;
+
+/// The number of documents of a batch read at once, and rewritten in parallel, when the size of
+/// the chunks of documents isn't configured.
+const REWRITE_CHUNK_SIZE: usize = 1000;
+
+enum Replacement {
+    Separator,
+    Word(String),
+}
+
+/// Rewrites the texts for the tokenizer to segment them according to the tokenization settings.
+pub struct TokenRewriter {
+    /// The lowercased tokens, the longest first, so that `c++` is matched before `+`.
+    tokens: Vec<(String, Replacement)>,
+}
+
+impl TokenRewriter {
+    fn new(
+        separator_tokens: &BTreeSet<String>,
+        non_separator_tokens: &BTreeSet<String>,
+        dictionary: &BTreeSet<String>,
+    ) -> Option<Self> {
+        let separators = separator_tokens
+            .iter()
+            .map(|token| (token.to_lowercase(), Replacement::Separator));
+        let words = non_separator_tokens.iter().chain(dictionary).map(|token| {
+            let token = token.to_lowercase();
+            let word = glued(&token);
+            (token, Replacement::Word(word))
+        });
+
+        let mut tokens: Vec<_> = separators
+            .chain(words)
+            .filter(|(token, _)| !token.is_empty())
+            .collect();
+        if tokens.is_empty() {
+            return None;
+        }
+        tokens.sort_by_key(|(token, _)| Reverse(token.len()));
+
+        Some(Self { tokens })
+    }
+
+    /// Returns `None` when the index has no tokenization settings.
+    pub fn from_index(index: &milli::Index, rtxn: &RoTxn) -> Result<Option<Self>> {
+        Ok(Self::new(
+            &custom_settings::separator_tokens(index, rtxn)?.unwrap_or_default(),
+            &custom_settings::non_separator_tokens(index, rtxn)?.unwrap_or_default(),
+            &custom_settings::dictionary(index, rtxn)?.unwrap_or_default(),
+        ))
+    }
+
+    /// Rewrites a text, the texts without any token being returned as they are. The tokens are
+    /// matched regardless of the case, the tokenizer lowercasing the words anyway.
+    pub fn rewrite<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let lowercase = text.to_lowercase();
+        if !self
+            .tokens
+            .iter()
+            .any(|(token, _)| lowercase.contains(token.as_str()))
+        {
+            return Cow::Borrowed(text);
+        }
+
+        let mut rewritten = String::with_capacity(lowercase.len());
+        let mut rest = lowercase.as_str();
+        while let Some(c) = rest.chars().next() {
+            match self
+                .tokens
+                .iter()
+                .find(|(token, _)| rest.starts_with(token.as_str()))
+            {
+                Some((token, replacement)) => {
+                    match replacement {
+                        Replacement::Separator => rewritten.push(' '),
+                        Replacement::Word(word) => rewritten.push_str(word),
+                    }
+                    rest = &rest[token.len()..];
+                }
+                None => {
+                    rewritten.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+
+        Cow::Owned(rewritten)
+    }
+}
+
+/// Rewrites the strings of a value, and returns whether any of them changed.
+fn rewrite_value(value: &mut Value, rewrite: &dyn Fn(&str) -> Option<String>) -> bool {
+    match value {
+        Value::String(text) => match rewrite(text) {
+            Some(rewritten) => {
+                *text = rewritten;
+                true
+            }
+            None => false,
+        },
+        Value::Array(values) => values.iter_mut().fold(false, |changed, value| {
+            rewrite_value(value, rewrite) || changed
+        }),
+        Value::Object(object) => object.values_mut().fold(false, |changed, value| {
+            rewrite_value(value, rewrite) || changed
+        }),
+        _ => false,
+    }
+}
+
+fn glued(token: &str) -> String {
+    token
+        .chars()
+        .map(|c| match c {
+            c if c.is_alphanumeric() => c.to_string(),
+            c => format!("{:x}", c as u32),
+        })
+        .collect()
+}
+
+/// Rewrites the searchable attributes of the documents for milli to index them.
+pub struct DocumentRewriter {
+    tokens: Option<TokenRewriter>,
+    locales: Option<LocaleRules>,
+    /// `None` when all the attributes are searchable.
+    searchable: Option<HashSet<String>>,
+    /// The attributes milli reads the facet values from.
+    faceted: HashSet<String>,
+    primary_key: Option<String>,
+}
+
+impl DocumentRewriter {
+    /// Returns `None` when the index has neither tokenization settings nor localized attributes.
+    /// The settings of an update, if any, are taken into account, since the documents are
+    /// rewritten before milli applies them.
+    pub fn new(
+        index: &milli::Index,
+        rtxn: &RoTxn,
+        settings: Option<&Settings<Checked>>,
+    ) -> Result<Option<Self>> {
+        let tokens = TokenRewriter::new(
+            &updated(
+                settings.map(|settings| &settings.separator_tokens),
+                custom_settings::separator_tokens(index, rtxn)?,
+            )
+            .unwrap_or_default(),
+            &updated(
+                settings.map(|settings| &settings.non_separator_tokens),
+                custom_settings::non_separator_tokens(index, rtxn)?,
+            )
+            .unwrap_or_default(),
+            &updated(
+                settings.map(|settings| &settings.dictionary),
+                custom_settings::dictionary(index, rtxn)?,
+            )
+            .unwrap_or_default(),
+        );
+        let locales = updated(
+            settings.map(|settings| &settings.localized_attributes),
+            custom_settings::localized_attributes(index, rtxn)?,
+        )
+        .and_then(LocaleRules::new);
+        if tokens.is_none() && locales.is_none() {
+            return Ok(None);
+        }
+
+        let searchable = updated(
+            settings.map(|settings| &settings.searchable_attributes),
+            index
+                .searchable_fields(rtxn)?
+                .map(|fields| fields.into_iter().map(String::from).collect()),
+        );
+        let filterable = updated(
+            settings.map(|settings| &settings.filterable_attributes),
+            Some(index.filterable_fields(rtxn)?.into_iter().collect()),
+        );
+        let sortable = updated(
+            settings.map(|settings| &settings.sortable_attributes),
+            Some(index.sortable_fields(rtxn)?.into_iter().collect()),
+        );
+        let distinct = updated(
+            settings.map(|settings| &settings.distinct_attribute),
+            index.distinct_field(rtxn)?.map(String::from),
+        );
+
+        let mut faceted: HashSet<String> =
+            filterable.into_iter().chain(sortable).flatten().collect();
+        faceted.extend(distinct);
+
+        Ok(Some(Self {
+            tokens,
+            locales,
+            searchable: searchable.map(|fields| fields.into_iter().collect()),
+            faceted,
+            primary_key: index.primary_key(rtxn)?.map(String::from),
+        }))
+    }
+
+    fn rewrites(&self, name: &str) -> bool {
+        // milli infers the primary key of the first addition from the attributes containing `id`.
+        let primary_key = match self.primary_key {
+            Some(ref primary_key) => name == primary_key,
+            None => name.to_lowercase().contains("id"),
+        };
+
+        !primary_key
+            && !self.faceted.contains(name)
+            && self
+                .searchable
+                .as_ref()
+                .map_or(true, |searchable| searchable.contains(name))
+    }
+
+    /// Rewrites the value of an attribute, and returns whether it changed.
+    fn rewrite_field(&self, name: &str, value: &mut Value) -> bool {
+        let locales = self.locales.as_ref().and_then(|rules| rules.locales(name));
+        rewrite_value(value, &|text| {
+            let text = match self.tokens {
+                Some(ref tokens) => tokens.rewrite(text),
+                None => Cow::Borrowed(text),
+            };
+            let localized = match locales.map(|locales| localize(&text, locales)) {
+                Some(Cow::Owned(localized)) => Some(localized),
+                _ => None,
+            };
+            localized.or(match text {
+                Cow::Owned(text) => Some(text),
+                Cow::Borrowed(_) => None,
+            })
+        })
+    }
+
+    /// Rewrites the documents of a batch, and returns the rewritten documents along with the
+    /// original ones, to write them back once milli indexed the batch. The documents are read by
+    /// chunks of the `documents_chunk_size` of the indexer, and the documents of a chunk are
+    /// rewritten in parallel on the indexing thread pool.
+    pub fn rewrite_batch(
+        &self,
+        mut reader: DocumentBatchReader<File>,
+        config: &IndexerConfig,
+    ) -> Result<(DocumentBatchReader<File>, DocumentBatchReader<File>)> {
+        let mut rewritten_file = tempfile::tempfile()?;
+        let mut rewritten = DocumentBatchBuilder::new(rewritten_file.try_clone()?)?;
+        let mut original_file = tempfile::tempfile()?;
+        let mut original = DocumentBatchBuilder::new(original_file.try_clone()?)?;
+
+        let chunk_full = |len: usize, size: usize| match config.documents_chunk_size {
+            Some(chunk_size) => size >= chunk_size,
+            None => len >= REWRITE_CHUNK_SIZE,
+        };
+        let mut chunk = Vec::new();
+        loop {
+            chunk.clear();
+            let mut size = 0;
+            while !chunk_full(chunk.len(), size) {
+                let (fields, obkv) = match reader.next_document_with_index()? {
+                    Some(next) => next,
+                    None => break,
+                };
+                let mut document = Map::new();
+                for (field_id, content) in obkv.iter() {
+                    if let Some(name) = fields.name(field_id) {
+                        document.insert(name.to_string(), serde_json::from_slice(content)?);
+                    }
+                    size += content.len();
+                }
+                chunk.push(document);
+            }
+            if chunk.is_empty() {
+                break;
+            }
+
+            let mut rewrite = || {
+                chunk
+                    .par_iter_mut()
+                    .map(|document| -> Result<_> {
+                        let original = serde_json::to_vec(&*document)?;
+                        for (name, value) in document.iter_mut() {
+                            if self.rewrites(name) {
+                                self.rewrite_field(name, value);
+                            }
+                        }
+                        Ok((original, serde_json::to_vec(&*document)?))
+                    })
+                    .collect::<Result<Vec<_>>>()
+            };
+            let documents = match config.thread_pool {
+                Some(ref thread_pool) => thread_pool.install(rewrite)?,
+                None => rewrite()?,
+            };
+            for (original_document, rewritten_document) in documents {
+                original.extend_from_json(Cursor::new(original_document))?;
+                rewritten.extend_from_json(Cursor::new(rewritten_document))?;
+            }
+        }
+
+        rewritten.finish()?;
+        rewritten_file.seek(SeekFrom::Start(0))?;
+        original.finish()?;
+        original_file.seek(SeekFrom::Start(0))?;
+
+        Ok((
+            DocumentBatchReader::from_reader(rewritten_file)?,
+            DocumentBatchReader::from_reader(original_file)?,
+        ))
+    }
+
+    /// Rewrites the given stored documents in place, for milli to read them during an update, and
+    /// returns their originals. The documents must not be compressed.
+    pub fn rewrite_documents(
+        &self,
+        index: &milli::Index,
+        txn: &mut RwTxn,
+        docids: &RoaringBitmap,
+    ) -> Result<OriginalDocuments> {
+        let fields_ids_map = index.fields_ids_map(txn)?;
+        let fields = self.rewritten_fields(&fields_ids_map);
+
+        let mut originals = OriginalDocuments::new()?;
+        let stored = index.documents.remap_data_type::<ByteSlice>();
+        for id in docids {
+            let key = BEU32::new(id);
+            let bytes = match stored.get(txn, &key)? {
+                Some(bytes) => bytes.to_vec(),
+                None => continue,
+            };
+            // the unchanged documents are kept too, milli merging them with the updated ones.
+            originals.push(id, &bytes)?;
+            if let Some(rewritten) = self.rewrite_obkv(&fields, &bytes)? {
+                stored.put(txn, &key, &rewritten)?;
+            }
+        }
+
+        Ok(originals)
+    }
+
+    fn rewritten_fields<'a>(&self, fields_ids_map: &'a FieldsIdsMap) -> HashMap<FieldId, &'a str> {
+        fields_ids_map
+            .iter()
+            .filter(|(_, name)| self.rewrites(name))
+            .collect()
+    }
+
+    fn rewrite_obkv(
+        &self,
+        fields: &HashMap<FieldId, &str>,
+        bytes: &[u8],
+    ) -> Result<Option<Vec<u8>>> {
+        let mut writer = KvWriterU16::memory();
+        let mut changed = false;
+        for (field_id, content) in KvReaderU16::new(bytes).iter() {
+            if let Some(name) = fields.get(&field_id) {
+                let mut value: Value = serde_json::from_slice(content)?;
+                if self.rewrite_field(name, &mut value) {
+                    writer.insert(field_id, serde_json::to_vec(&value)?)?;
+                    changed = true;
+                    continue;
+                }
+            }
+            writer.insert(field_id, content)?;
+        }
+
+        if changed {
+            Ok(Some(writer.into_inner()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Writes the original documents back once milli indexed the rewritten ones: the documents
+    /// of the original batches, merged with their previous version when they were updated, and
+    /// the documents rewritten in place that milli didn't replace.
+    pub fn restore_documents(
+        &self,
+        index: &milli::Index,
+        txn: &mut RwTxn,
+        method: IndexDocumentsMethod,
+        batches: Vec<DocumentBatchReader<File>>,
+        mut originals: OriginalDocuments,
+    ) -> Result<()> {
+        let primary_key = match index.primary_key(txn)? {
+            Some(primary_key) => primary_key.to_string(),
+            None => return originals.restore(index, txn, &RoaringBitmap::new()),
+        };
+        let fields_ids_map = index.fields_ids_map(txn)?;
+        let external_ids = index.external_documents_ids(txn)?.into_static();
+        let stored = index.documents.remap_data_type::<ByteSlice>();
+
+        let mut restored = RoaringBitmap::new();
+        for mut batch in batches {
+            while let Some((fields, obkv)) = batch.next_document_with_index()? {
+                let content = obkv.iter().find(|(field_id, _)| {
+                    fields
+                        .name(*field_id)
+                        .map_or(false, |name| name == primary_key.as_str())
+                });
+                let id = match content {
+                    Some((_, content)) => match serde_json::from_slice(content)? {
+                        Value::String(id) => id,
+                        Value::Number(id) => id.to_string(),
+                        _ => continue,
+                    },
+                    None => continue,
+                };
+                let docid = match external_ids.get(id.as_bytes()) {
+                    Some(docid) => docid,
+                    None => continue,
+                };
+                let key = BEU32::new(docid);
+
+                let mut document = BTreeMap::new();
+                if method == IndexDocumentsMethod::UpdateDocuments {
+                    // a document updated twice in the addition is merged with its first version.
+                    let previous = if restored.contains(docid) {
+                        stored.get(txn, &key)?.map(<[u8]>::to_vec)
+                    } else {
+                        originals.get(docid)?
+                    };
+                    if let Some(previous) = previous {
+                        for (field_id, content) in KvReaderU16::new(&previous).iter() {
+                            document.insert(field_id, content.to_vec());
+                        }
+                    }
+                }
+                for (field_id, content) in obkv.iter() {
+                    let field_id = fields
+                        .name(field_id)
+                        .and_then(|name| fields_ids_map.id(name));
+                    if let Some(field_id) = field_id {
+                        document.insert(field_id, content.to_vec());
+                    }
+                }
+
+                let mut writer = KvWriterU16::memory();
+                for (field_id, content) in document {
+                    writer.insert(field_id, content)?;
+                }
+                stored.put(txn, &key, &writer.into_inner()?)?;
+                restored.insert(docid);
+            }
+        }
+
+        originals.restore(index, txn, &restored)
+    }
+}
+
+/// The value of a setting once the update, if any, is applied.
+fn updated<T: Clone>(update: Option<&Setting<T>>, current: Option<T>) -> Option<T> {
+    match update {
+        Some(Setting::Set(value)) => Some(value.clone()),
+        Some(Setting::Reset) => None,
+        Some(Setting::NotSet) | None => current,
+    }
+}
+
+/// The original version of the documents rewritten in place, kept in a temporary file since all
+/// the documents are rewritten when they are reindexed.
+pub struct OriginalDocuments {
+    file: File,
+    positions: HashMap<DocumentId, (u64, usize)>,
+}
+
+impl OriginalDocuments {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            file: tempfile::tempfile()?,
+            positions: HashMap::new(),
+        })
+    }
+
+    fn push(&mut self, docid: DocumentId, bytes: &[u8]) -> Result<()> {
+        let position = self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(bytes)?;
+        self.positions.insert(docid, (position, bytes.len()));
+
+        Ok(())
+    }
+
+    fn get(&mut self, docid: DocumentId) -> Result<Option<Vec<u8>>> {
+        let (position, len) = match self.positions.get(&docid) {
+            Some(&position) => position,
+            None => return Ok(None),
+        };
+        let mut bytes = vec![0; len];
+        self.file.seek(SeekFrom::Start(position))?;
+        self.file.read_exact(&mut bytes)?;
+
+        Ok(Some(bytes))
+    }
+
+    /// Writes the original documents back, except the given ones.
+    pub fn restore(
+        mut self,
+        index: &milli::Index,
+        txn: &mut RwTxn,
+        except: &RoaringBitmap,
+    ) -> Result<()> {
+        let mut docids: Vec<_> = self
+            .positions
+            .keys()
+            .copied()
+            .filter(|docid| !except.contains(*docid))
+            .collect();
+        docids.sort_unstable();
+
+        let stored = index.documents.remap_data_type::<ByteSlice>();
+        for docid in docids {
+            if let Some(bytes) = self.get(docid)? {
+                stored.put(txn, &BEU32::new(docid), &bytes)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rewriter(
+        separators: &[&str],
+        non_separators: &[&str],
+        dictionary: &[&str],
+    ) -> TokenRewriter {
+        let set = |tokens: &[&str]| tokens.iter().map(|token| token.to_string()).collect();
+        TokenRewriter::new(&set(separators), &set(non_separators), &set(dictionary)).unwrap()
+    }
+
+    #[test]
+    fn separator_tokens_split_words() {
+        let tokens = rewriter(&["|", "&"], &[], &[]);
+        assert_eq!(tokens.rewrite("Tom&Jerry|cartoon"), "tom jerry cartoon");
+    }
+
+    #[test]
+    fn non_separator_tokens_glue_words() {
+        let tokens = rewriter(&[], &["-", "@"], &[]);
+        assert_eq!(tokens.rewrite("e-mail me@home"), "e2dmail me40home");
+    }
+
+    #[test]
+    fn dictionary_words_are_kept_whole() {
+        let tokens = rewriter(&["+"], &[], &["C++", "J. R. R. Tolkien"]);
+        assert_eq!(tokens.rewrite("C++ and c"), "c2b2b and c");
+        assert_eq!(
+            tokens.rewrite("by J. R. R. Tolkien"),
+            "by j2e20r2e20r2e20tolkien"
+        );
+        assert_eq!(tokens.rewrite("1+1"), "1 1");
+    }
+
+    #[test]
+    fn texts_without_tokens_are_borrowed() {
+        let tokens = rewriter(&["|"], &["-"], &["C++"]);
+        assert!(matches!(tokens.rewrite("Learning Rust"), Cow::Borrowed(_)));
+    }
+}
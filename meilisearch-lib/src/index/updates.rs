@@ -5,14 +5,23 @@ use std::num::NonZeroUsize;
 use log::{debug, info, trace};
 use milli::documents::DocumentBatchReader;
 use milli::update::{
-    DocumentAdditionResult, DocumentDeletionResult, IndexDocumentsConfig, IndexDocumentsMethod,
-    Setting,
+    DocumentDeletionResult, IndexDocumentsConfig, IndexDocumentsMethod, Setting, UpdateIndexingStep,
 };
-use serde::{Deserialize, Serialize, Serializer};
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use uuid::Uuid;
 
+use super::compression;
+use super::custom_settings::{apply_custom_settings, check_custom_settings, tokenization_updated};
 use super::error::Result;
 use super::index::{Index, IndexMeta};
+use super::search::{
+    MatchingStrategy, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG,
+    DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_MAX_VALUES_PER_FACET,
+};
+use super::stop_words::preset_words;
+use super::tokenization::DocumentRewriter;
+use super::versioning::StaleDocumentsFilter;
 use crate::update_file_store::UpdateFileStore;
 
 fn serialize_with_wildcard<S>(
@@ -68,6 +77,263 @@ pub struct TypoSettings {
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     pub disable_on_attributes: Setting<BTreeSet<String>>,
 }
+
+/// The formatting parameters of the searches on the index that don't specify them.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct FormattingSettings {
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    pub highlight_pre_tag: Setting<String>,
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    pub highlight_post_tag: Setting<String>,
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    pub crop_length: Setting<usize>,
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    pub crop_marker: Setting<String>,
+}
+
+impl FormattingSettings {
+    pub fn highlight_pre_tag(&self) -> String {
+        self.highlight_pre_tag
+            .as_ref()
+            .set()
+            .map_or(DEFAULT_HIGHLIGHT_PRE_TAG, String::as_str)
+            .to_string()
+    }
+
+    pub fn highlight_post_tag(&self) -> String {
+        self.highlight_post_tag
+            .as_ref()
+            .set()
+            .map_or(DEFAULT_HIGHLIGHT_POST_TAG, String::as_str)
+            .to_string()
+    }
+
+    pub fn crop_length(&self) -> usize {
+        self.crop_length
+            .as_ref()
+            .set()
+            .copied()
+            .unwrap_or(DEFAULT_CROP_LENGTH)
+    }
+
+    pub fn crop_marker(&self) -> String {
+        self.crop_marker
+            .as_ref()
+            .set()
+            .map_or(DEFAULT_CROP_MARKER, String::as_str)
+            .to_string()
+    }
+
+    /// Applies an update on the settings, the reset parameters go back to their default value.
+    pub fn merge(&mut self, update: &FormattingSettings) {
+        merge_setting(&mut self.highlight_pre_tag, &update.highlight_pre_tag);
+        merge_setting(&mut self.highlight_post_tag, &update.highlight_post_tag);
+        merge_setting(&mut self.crop_length, &update.crop_length);
+        merge_setting(&mut self.crop_marker, &update.crop_marker);
+    }
+}
+
+/// The order of the values of a facet in the facets distribution.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FacetValuesSort {
+    /// In the alphanumerical order of the values.
+    Alpha,
+    /// The most frequent values first.
+    Count,
+}
+
+/// How the documents of the index are stored.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DocumentCompression {
+    /// As they are written by the engine.
+    None,
+    /// Compressed with zstd, using a dictionary trained on the documents of the index.
+    Zstd,
+}
+
+impl Default for DocumentCompression {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// An alternative configuration of the searches, served to a share of the traffic to compare its
+/// relevance with the one of the index. The parameters it sets fill the ones the query omits, an
+/// explicit `sort`, `matchingStrategy` or `distinct` of the query is kept.
+///
+/// A ruleset can't change the ranking rules: milli reads them from the settings of the index,
+/// which all the searches share. The ranking rules are compared with a `sort` on the sortable
+/// attributes instead, or with another index having the same documents.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct Ruleset {
+    /// The percentage of the searches not asking for a ruleset that are served with this one.
+    #[serde(default)]
+    pub traffic: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matching_strategy: Option<MatchingStrategy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distinct: Option<String>,
+    /// Whether the words of the query can match with typos, regardless of the typo tolerance of
+    /// the index.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typo_tolerance: Option<bool>,
+}
+
+/// The words ignored by the searches, either a list of words or the built-in lists of some
+/// languages along with custom words.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum StopWords {
+    Words(BTreeSet<String>),
+    Presets(StopWordsPresets),
+}
+
+impl StopWords {
+    /// The words to ignore, the ones of the presets along with the custom ones. The unknown
+    /// presets are refused when the settings are checked.
+    pub fn words(&self) -> BTreeSet<String> {
+        match self {
+            StopWords::Words(words) => words.clone(),
+            StopWords::Presets(presets) => presets
+                .preset
+                .iter()
+                .filter_map(|language| preset_words(language))
+                .flatten()
+                .map(String::from)
+                .chain(presets.words.iter().cloned())
+                .collect(),
+        }
+    }
+}
+
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct StopWordsPresets {
+    /// The language codes of the built-in lists, a single code or a list of them.
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub preset: BTreeSet<String>,
+    /// The words ignored on top of the ones of the presets.
+    #[serde(default)]
+    pub words: BTreeSet<String>,
+}
+
+fn deserialize_one_or_many<'de, D>(
+    deserializer: D,
+) -> std::result::Result<BTreeSet<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(BTreeSet<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => std::iter::once(value).collect(),
+        OneOrMany::Many(values) => values,
+    })
+}
+
+/// How the facets distribution of the searches on the index is built.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct FacetingSettings {
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    pub max_values_per_facet: Setting<usize>,
+    /// The order of the values of each facet, the `*` entry applying to the facets that are not
+    /// listed.
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    pub sort_facet_values_by: Setting<BTreeMap<String, FacetValuesSort>>,
+}
+
+impl FacetingSettings {
+    pub fn max_values_per_facet(&self) -> usize {
+        self.max_values_per_facet
+            .as_ref()
+            .set()
+            .copied()
+            .unwrap_or(DEFAULT_MAX_VALUES_PER_FACET)
+    }
+
+    pub fn sort_facet_values_by(&self) -> BTreeMap<String, FacetValuesSort> {
+        let mut sorts = self
+            .sort_facet_values_by
+            .as_ref()
+            .set()
+            .cloned()
+            .unwrap_or_default();
+        sorts
+            .entry("*".to_string())
+            .or_insert(FacetValuesSort::Alpha);
+        sorts
+    }
+
+    /// Returns the order of the values of the facet.
+    pub fn sort_of(&self, facet: &str) -> FacetValuesSort {
+        let sorts = match self.sort_facet_values_by {
+            Setting::Set(ref sorts) => sorts,
+            _ => return FacetValuesSort::Alpha,
+        };
+
+        sorts
+            .get(facet)
+            .or_else(|| sorts.get("*"))
+            .copied()
+            .unwrap_or(FacetValuesSort::Alpha)
+    }
+
+    /// Applies an update on the settings, the reset parameters go back to their default value.
+    pub fn merge(&mut self, update: &FacetingSettings) {
+        merge_setting(&mut self.max_values_per_facet, &update.max_values_per_facet);
+        merge_setting(&mut self.sort_facet_values_by, &update.sort_facet_values_by);
+    }
+}
+
+/// Applies the update of a nested setting, a reset setting going back to its default value.
+fn merge_setting<T: Clone>(current: &mut Setting<T>, update: &Setting<T>) {
+    match update {
+        Setting::Set(value) => *current = Setting::Set(value.clone()),
+        Setting::Reset => *current = Setting::NotSet,
+        Setting::NotSet => (),
+    }
+}
+
+/// Assigns locales to the attributes matching one of the patterns. A pattern is either an
+/// attribute name or contains a `*` wildcard at its start or end, such as `title_*`.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalizedAttributesRule {
+    pub attribute_patterns: Vec<String>,
+    pub locales: Vec<String>,
+}
+
 /// Holds all the settings for an index. `T` can either be `Checked` if they represents settings
 /// whose validity is guaranteed, or `Unchecked` if they need to be validated. In the later case, a
 /// call to `check` will return a `Settings<Checked>` from a `Settings<Unchecked>`.
@@ -104,7 +370,7 @@ pub struct Settings<T> {
     pub ranking_rules: Setting<Vec<String>>,
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
-    pub stop_words: Setting<BTreeSet<String>>,
+    pub stop_words: Setting<StopWords>,
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
     pub synonyms: Setting<BTreeMap<String, Vec<String>>>,
@@ -114,6 +380,39 @@ pub struct Settings<T> {
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
     pub typo_tolerance: Setting<TypoSettings>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub localized_attributes: Setting<Vec<LocalizedAttributesRule>>,
+    /// The tokens splitting or joining the words, along with the `dictionary`. They aren't applied
+    /// to the filterable and sortable attributes, the distinct attribute and the primary key,
+    /// which are tokenized as usual.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub separator_tokens: Setting<BTreeSet<String>>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub non_separator_tokens: Setting<BTreeSet<String>>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub dictionary: Setting<BTreeSet<String>>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub formatting: Setting<FormattingSettings>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub faceting: Setting<FacetingSettings>,
+    /// The numeric attribute holding the version of the documents. The documents whose version is
+    /// lower than the one of the stored document are skipped by the document additions.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub version_field: Setting<String>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub document_compression: Setting<DocumentCompression>,
+    /// The rulesets of the relevance experiments, by name.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub rulesets: Setting<BTreeMap<String, Ruleset>>,
 
     #[serde(skip)]
     pub _kind: PhantomData<T>,
@@ -131,6 +430,15 @@ impl Settings<Checked> {
             synonyms: Setting::Reset,
             distinct_attribute: Setting::Reset,
             typo_tolerance: Setting::Reset,
+            localized_attributes: Setting::Reset,
+            separator_tokens: Setting::Reset,
+            non_separator_tokens: Setting::Reset,
+            dictionary: Setting::Reset,
+            formatting: Setting::Reset,
+            faceting: Setting::Reset,
+            version_field: Setting::Reset,
+            document_compression: Setting::Reset,
+            rulesets: Setting::Reset,
             _kind: PhantomData,
         }
     }
@@ -146,6 +454,15 @@ impl Settings<Checked> {
             synonyms,
             distinct_attribute,
             typo_tolerance,
+            localized_attributes,
+            separator_tokens,
+            non_separator_tokens,
+            dictionary,
+            formatting,
+            faceting,
+            version_field,
+            document_compression,
+            rulesets,
             ..
         } = self;
 
@@ -159,6 +476,15 @@ impl Settings<Checked> {
             synonyms,
             distinct_attribute,
             typo_tolerance,
+            localized_attributes,
+            separator_tokens,
+            non_separator_tokens,
+            dictionary,
+            formatting,
+            faceting,
+            version_field,
+            document_compression,
+            rulesets,
             _kind: PhantomData,
         }
     }
@@ -198,11 +524,29 @@ impl Settings<Unchecked> {
             synonyms: self.synonyms,
             distinct_attribute: self.distinct_attribute,
             typo_tolerance: self.typo_tolerance,
+            localized_attributes: self.localized_attributes,
+            separator_tokens: self.separator_tokens,
+            non_separator_tokens: self.non_separator_tokens,
+            dictionary: self.dictionary,
+            formatting: self.formatting,
+            faceting: self.faceting,
+            version_field: self.version_field,
+            document_compression: self.document_compression,
+            rulesets: self.rulesets,
             _kind: PhantomData,
         }
     }
 }
 
+/// The result of a document addition, along with the number of documents skipped because they
+/// are older than the stored ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DocumentAdditionResult {
+    pub indexed_documents: u64,
+    pub number_of_documents: u64,
+    pub skipped_documents: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "camelCase")]
@@ -236,6 +580,20 @@ impl Index {
     /// Deletes `ids` from the index, and returns how many documents were deleted.
     pub fn delete_documents(&self, ids: &[String]) -> Result<DocumentDeletionResult> {
         let mut txn = self.write_txn()?;
+
+        // milli reads the documents it deletes to remove their words, they can't be compressed
+        // and must be rewritten like when they were indexed.
+        let docids: RoaringBitmap = {
+            let external_ids = self.external_documents_ids(&txn)?;
+            ids.iter()
+                .filter_map(|id| external_ids.get(id.as_bytes()))
+                .collect()
+        };
+        compression::decompress_documents(self, &mut txn, &docids)?;
+        if let Some(rewriter) = DocumentRewriter::new(self, &txn, None)? {
+            rewriter.rewrite_documents(self, &mut txn, &docids)?;
+        }
+
         let mut builder = milli::update::DeleteDocuments::new(&mut txn, self)?;
 
         // We ignore unexisting document ids
@@ -244,6 +602,8 @@ impl Index {
         });
 
         let deleted = builder.execute()?;
+        compression::sync_documents(self, &mut txn)?;
+        self.update_existence_docids_of(&mut txn, &docids)?;
 
         txn.commit()?;
 
@@ -253,6 +613,8 @@ impl Index {
     pub fn clear_documents(&self) -> Result<()> {
         let mut txn = self.write_txn()?;
         milli::update::ClearDocuments::new(&mut txn, self).execute()?;
+        compression::sync_documents(self, &mut txn)?;
+        self.update_existence_docids(&mut txn)?;
         txn.commit()?;
 
         Ok(())
@@ -264,6 +626,7 @@ impl Index {
         primary_key: Option<String>,
         file_store: UpdateFileStore,
         contents: impl IntoIterator<Item = Uuid>,
+        progress: impl Fn(UpdateIndexingStep) + Sync,
     ) -> Result<DocumentAdditionResult> {
         trace!("performing document addition");
         let mut txn = self.write_txn()?;
@@ -274,12 +637,71 @@ impl Index {
             }
         }
 
+        let contents: Vec<_> = contents.into_iter().collect();
+        let rewriter = DocumentRewriter::new(self, &txn, None)?;
+
+        // milli reads the documents it replaces or updates, they can't be compressed and must be
+        // rewritten like when they were indexed. Their existence documents are updated as well.
+        let mut replaced = RoaringBitmap::new();
+        if rewriter.is_some()
+            || compression::has_compressed_documents(self, &txn)?
+            || !self.filterable_fields(&txn)?.is_empty()
+        {
+            for content_uuid in &contents {
+                let content_file = file_store.get_update(*content_uuid)?;
+                let reader = DocumentBatchReader::from_reader(content_file)?;
+                replaced |= compression::replaced_documents(self, &txn, reader)?;
+            }
+            compression::decompress_documents(self, &mut txn, &replaced)?;
+        }
+        let previous_documents = self.documents_ids(&txn)?;
+
+        // The stale documents are skipped before the indexing, since it needs the transaction.
+        let mut readers = Vec::new();
+        let mut skipped_documents = 0;
+        {
+            let mut stale_filter = StaleDocumentsFilter::new(self, &txn)?;
+            for content_uuid in contents {
+                let content_file = file_store.get_update(content_uuid)?;
+                let reader = DocumentBatchReader::from_reader(content_file)?;
+                let reader = match stale_filter {
+                    Some(ref mut stale_filter) => stale_filter.filter(reader)?,
+                    None => reader,
+                };
+                readers.push(reader);
+            }
+            if let Some(stale_filter) = stale_filter {
+                skipped_documents = stale_filter.skipped_documents;
+            }
+        }
+
+        // The stored documents are rewritten once the stale filter, that reads them, is done.
+        let rewritten = match rewriter {
+            Some(rewriter) => {
+                let mut rewritten_readers = Vec::new();
+                let mut original_readers = Vec::new();
+                for reader in readers {
+                    let (rewritten, original) =
+                        rewriter.rewrite_batch(reader, &self.indexer_config)?;
+                    rewritten_readers.push(rewritten);
+                    original_readers.push(original);
+                }
+                readers = rewritten_readers;
+                let originals = rewriter.rewrite_documents(self, &mut txn, &replaced)?;
+                Some((rewriter, original_readers, originals))
+            }
+            None => None,
+        };
+
         let config = IndexDocumentsConfig {
             update_method: method,
             ..Default::default()
         };
 
-        let indexing_callback = |indexing_step| debug!("update: {:?}", indexing_step);
+        let indexing_callback = |indexing_step| {
+            debug!("update: {:?}", indexing_step);
+            progress(indexing_step);
+        };
         let mut builder = milli::update::IndexDocuments::new(
             &mut txn,
             self,
@@ -288,37 +710,131 @@ impl Index {
             indexing_callback,
         )?;
 
-        for content_uuid in contents.into_iter() {
-            let content_file = file_store.get_update(content_uuid)?;
-            let reader = DocumentBatchReader::from_reader(content_file)?;
+        for reader in readers {
             builder.add_documents(reader)?;
         }
 
         let addition = builder.execute()?;
+        if let Some((rewriter, original_readers, originals)) = rewritten {
+            rewriter.restore_documents(self, &mut txn, method, original_readers, originals)?;
+        }
+        compression::sync_documents(self, &mut txn)?;
+        let updated = (self.documents_ids(&txn)? - previous_documents) | replaced;
+        self.update_existence_docids_of(&mut txn, &updated)?;
 
         txn.commit()?;
 
-        info!("document addition done: {:?}", addition);
+        info!(
+            "document addition done: {:?}, {} stale documents skipped",
+            addition, skipped_documents
+        );
 
-        Ok(addition)
+        Ok(DocumentAdditionResult {
+            indexed_documents: addition.indexed_documents,
+            number_of_documents: addition.number_of_documents,
+            skipped_documents,
+        })
     }
 
     pub fn update_settings(&self, settings: &Settings<Checked>) -> Result<()> {
+        check_custom_settings(settings)?;
+
         // We must use the write transaction of the update here.
         let mut txn = self.write_txn()?;
+
+        // milli reads all the documents when it reindexes them, they can't be compressed and are
+        // rewritten with the tokenization settings being applied.
+        let mut originals = None;
+        if reindexes_documents(settings) {
+            let documents_ids = self.documents_ids(&txn)?;
+            compression::decompress_documents(self, &mut txn, &documents_ids)?;
+            if let Some(rewriter) = DocumentRewriter::new(self, &txn, Some(settings))? {
+                originals = Some(rewriter.rewrite_documents(self, &mut txn, &documents_ids)?);
+            }
+        }
+
+        let searchable_fields = self
+            .searchable_fields(&txn)?
+            .map(|fields| fields.into_iter().map(String::from).collect::<Vec<_>>());
         let mut builder =
             milli::update::Settings::new(&mut txn, self, self.indexer_config.as_ref());
 
         apply_settings_to_builder(settings, &mut builder);
 
+        // milli is not aware of the tokenization settings, so we write the searchable attributes
+        // back to force it to reindex the documents.
+        if settings.searchable_attributes.is_not_set() && tokenization_updated(settings) {
+            match searchable_fields {
+                Some(fields) => builder.set_searchable_fields(fields),
+                None => builder.reset_searchable_fields(),
+            }
+        }
+
         builder.execute(|indexing_step| debug!("update: {:?}", indexing_step))?;
 
+        apply_custom_settings(settings, self, &mut txn)?;
+        if let Some(originals) = originals {
+            originals.restore(self, &mut txn, &RoaringBitmap::new())?;
+        }
+        compression::sync_documents(self, &mut txn)?;
+
+        // The documents of the existence operators are only stored for the filterable attributes.
+        if !settings.filterable_attributes.is_not_set() {
+            self.update_existence_docids(&mut txn)?;
+        }
+
+        txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Reindexes all the documents with the current settings, rebuilding the word and facet
+    /// databases from scratch.
+    pub fn reindex(&self) -> Result<()> {
+        let mut txn = self.write_txn()?;
+
+        let documents_ids = self.documents_ids(&txn)?;
+        compression::decompress_documents(self, &mut txn, &documents_ids)?;
+        let originals = match DocumentRewriter::new(self, &txn, None)? {
+            Some(rewriter) => Some(rewriter.rewrite_documents(self, &mut txn, &documents_ids)?),
+            None => None,
+        };
+
+        // Like for the tokenization settings, writing the searchable attributes back makes milli
+        // reindex the documents.
+        let searchable_fields = self
+            .searchable_fields(&txn)?
+            .map(|fields| fields.into_iter().map(String::from).collect::<Vec<_>>());
+        let mut builder =
+            milli::update::Settings::new(&mut txn, self, self.indexer_config.as_ref());
+        match searchable_fields {
+            Some(fields) => builder.set_searchable_fields(fields),
+            None => builder.reset_searchable_fields(),
+        }
+        builder.execute(|indexing_step| debug!("reindex: {:?}", indexing_step))?;
+
+        if let Some(originals) = originals {
+            originals.restore(self, &mut txn, &RoaringBitmap::new())?;
+        }
+        compression::sync_documents(self, &mut txn)?;
+        self.update_existence_docids(&mut txn)?;
+
         txn.commit()?;
 
         Ok(())
     }
 }
 
+/// Whether milli or the tokenization settings reindex the documents.
+fn reindexes_documents(settings: &Settings<Checked>) -> bool {
+    !(settings.searchable_attributes.is_not_set()
+        && settings.filterable_attributes.is_not_set()
+        && settings.sortable_attributes.is_not_set()
+        && settings.stop_words.is_not_set()
+        && settings.synonyms.is_not_set())
+        || tokenization_updated(settings)
+}
+
 pub fn apply_settings_to_builder(
     settings: &Settings<Checked>,
     builder: &mut milli::update::Settings,
@@ -356,7 +872,7 @@ pub fn apply_settings_to_builder(
     }
 
     match settings.stop_words {
-        Setting::Set(ref stop_words) => builder.set_stop_words(stop_words.clone()),
+        Setting::Set(ref stop_words) => builder.set_stop_words(stop_words.words()),
         Setting::Reset => builder.reset_stop_words(),
         Setting::NotSet => (),
     }
@@ -456,6 +972,15 @@ pub(crate) mod test {
             synonyms: Setting::NotSet,
             distinct_attribute: Setting::NotSet,
             typo_tolerance: Setting::NotSet,
+            localized_attributes: Setting::NotSet,
+            separator_tokens: Setting::NotSet,
+            non_separator_tokens: Setting::NotSet,
+            dictionary: Setting::NotSet,
+            formatting: Setting::NotSet,
+            faceting: Setting::NotSet,
+            version_field: Setting::NotSet,
+            document_compression: Setting::NotSet,
+            rulesets: Setting::NotSet,
             _kind: PhantomData::<Unchecked>,
         };
 
@@ -478,6 +1003,15 @@ pub(crate) mod test {
             synonyms: Setting::NotSet,
             distinct_attribute: Setting::NotSet,
             typo_tolerance: Setting::NotSet,
+            localized_attributes: Setting::NotSet,
+            separator_tokens: Setting::NotSet,
+            non_separator_tokens: Setting::NotSet,
+            dictionary: Setting::NotSet,
+            formatting: Setting::NotSet,
+            faceting: Setting::NotSet,
+            version_field: Setting::NotSet,
+            document_compression: Setting::NotSet,
+            rulesets: Setting::NotSet,
             _kind: PhantomData::<Unchecked>,
         };
 
@@ -0,0 +1,170 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Cursor, Seek, SeekFrom};
+
+use milli::documents::{DocumentBatchBuilder, DocumentBatchReader};
+use milli::heed::RoTxn;
+use milli::{ExternalDocumentsIds, FieldId};
+use obkv::KvReaderU16;
+use serde_json::{Map, Number, Value};
+
+use super::compression;
+use super::custom_settings;
+use super::error::Result;
+use super::index::Index;
+
+/// Skips the documents of a document addition whose version, read from the `versionField` of the
+/// index, is lower than the version of the stored document with the same id, or of a document
+/// with the same id added earlier in the same addition. The documents without a numeric version
+/// are always added. The integer versions are compared exactly, even above 2^53.
+pub struct StaleDocumentsFilter<'t> {
+    index: &'t Index,
+    txn: &'t RoTxn<'t>,
+    external_ids: ExternalDocumentsIds<'t>,
+    primary_key: String,
+    version_field: String,
+    version_field_id: Option<FieldId>,
+    versions: HashMap<String, Number>,
+    pub skipped_documents: u64,
+}
+
+impl<'t> StaleDocumentsFilter<'t> {
+    /// Returns `None` when the index has no version field, or no primary key yet, in which case
+    /// it has no documents to compare the versions with.
+    pub fn new(index: &'t Index, txn: &'t RoTxn<'t>) -> Result<Option<Self>> {
+        let version_field = match custom_settings::version_field(index, txn)? {
+            Some(field) => field,
+            None => return Ok(None),
+        };
+        let primary_key = match index.primary_key(txn)? {
+            Some(primary_key) => primary_key.to_string(),
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self {
+            index,
+            txn,
+            external_ids: index.external_documents_ids(txn)?,
+            primary_key,
+            version_field_id: index.fields_ids_map(txn)?.id(&version_field),
+            version_field,
+            versions: HashMap::new(),
+            skipped_documents: 0,
+        }))
+    }
+
+    /// Writes the documents of the batch that are not stale to a new batch.
+    pub fn filter(
+        &mut self,
+        mut reader: DocumentBatchReader<File>,
+    ) -> Result<DocumentBatchReader<File>> {
+        let mut file = tempfile::tempfile()?;
+        let mut builder = DocumentBatchBuilder::new(file.try_clone()?)?;
+
+        let mut document = Map::new();
+        while let Some((fields, obkv)) = reader.next_document_with_index()? {
+            document.clear();
+            for (field_id, content) in obkv.iter() {
+                if let Some(name) = fields.name(field_id) {
+                    document.insert(name.to_string(), serde_json::from_slice(content)?);
+                }
+            }
+
+            let id = match document.get(&self.primary_key) {
+                Some(Value::String(id)) => Some(id.clone()),
+                Some(Value::Number(id)) => Some(id.to_string()),
+                // the invalid ids are reported by milli.
+                _ => None,
+            };
+            let version = match document.get(&self.version_field) {
+                Some(Value::Number(version)) => Some(version.clone()),
+                _ => None,
+            };
+
+            if let (Some(id), Some(version)) = (id, version) {
+                let current = match self.versions.get(&id) {
+                    Some(current) => Some(current.clone()),
+                    None => self.stored_version(&id)?,
+                };
+                let stale = current.map_or(false, |current| {
+                    compare_versions(&version, &current) == Some(Ordering::Less)
+                });
+                if stale {
+                    self.skipped_documents += 1;
+                    continue;
+                }
+                self.versions.insert(id, version);
+            }
+
+            builder.extend_from_json(Cursor::new(serde_json::to_vec(&document)?))?;
+        }
+
+        builder.finish()?;
+        file.seek(SeekFrom::Start(0))?;
+
+        Ok(DocumentBatchReader::from_reader(file)?)
+    }
+
+    fn stored_version(&self, id: &str) -> Result<Option<Number>> {
+        let (docid, field_id) = match (self.external_ids.get(id.as_bytes()), self.version_field_id)
+        {
+            (Some(docid), Some(field_id)) => (docid, field_id),
+            _ => return Ok(None),
+        };
+
+        let documents = compression::documents(self.index, self.txn, std::iter::once(docid))?;
+        let version = match documents.first() {
+            Some((_, document)) => match KvReaderU16::new(document).get(field_id) {
+                Some(content) => match serde_json::from_slice::<Value>(content)? {
+                    Value::Number(version) => Some(version),
+                    _ => None,
+                },
+                None => None,
+            },
+            None => None,
+        };
+
+        Ok(version)
+    }
+}
+
+/// Compares two versions, exactly when both are integers, since the integers above 2^53 can't all
+/// be represented by a float.
+fn compare_versions(version: &Number, other: &Number) -> Option<Ordering> {
+    let integer = |number: &Number| {
+        number
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| number.as_u64().map(i128::from))
+    };
+
+    match (integer(version), integer(other)) {
+        (Some(version), Some(other)) => Some(version.cmp(&other)),
+        _ => version.as_f64()?.partial_cmp(&other.as_f64()?),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn versions_are_compared_exactly() {
+        let number = |json: &str| serde_json::from_str::<Number>(json).unwrap();
+        let compare = |a, b| compare_versions(&number(a), &number(b));
+
+        assert_eq!(compare("1", "2"), Some(Ordering::Less));
+        assert_eq!(compare("-3", "2"), Some(Ordering::Less));
+        assert_eq!(compare("1.5", "1"), Some(Ordering::Greater));
+        // equal as floats.
+        assert_eq!(
+            compare("9007199254740993", "9007199254740992"),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            compare("18446744073709551615", "18446744073709551614"),
+            Some(Ordering::Greater)
+        );
+    }
+}
@@ -36,6 +36,7 @@ impl From<v2::UpdateResult> for TaskResult {
         match other {
             v2::UpdateResult::DocumentsAddition(result) => TaskResult::DocumentAddition {
                 indexed_documents: result.nb_documents as u64,
+                skipped_documents: 0,
             },
             v2::UpdateResult::DocumentDeletion { deleted } => TaskResult::DocumentDeletion {
                 deleted_documents: deleted,
@@ -74,6 +75,7 @@ impl From<Update> for TaskContent {
                 primary_key,
                 // document count is unknown for legacy updates
                 documents_count: 0,
+                payload_size: 0,
                 allow_index_creation: true,
             },
             Update::Settings(settings) => TaskContent::SettingsUpdate {
@@ -11,6 +11,9 @@ use crate::tasks::error::TaskError;
 use crate::update_file_store::UpdateFileStoreError;
 
 use super::dump_actor::error::DumpActorError;
+use super::migration::error::MigrationError;
+use super::replication::error::ReplicationError;
+use super::write_breaker::BreakerReason;
 use crate::index_resolver::error::IndexResolverError;
 
 pub type Result<T> = std::result::Result<T, IndexControllerError>;
@@ -30,11 +33,29 @@ pub enum IndexControllerError {
     #[error("{0}")]
     DumpError(#[from] DumpActorError),
     #[error("{0}")]
+    MigrationError(#[from] MigrationError),
+    #[error("{0}")]
+    ReplicationError(#[from] ReplicationError),
+    #[error("{0}")]
     DocumentFormatError(#[from] DocumentFormatError),
     #[error("A {0} payload is missing.")]
     MissingPayload(DocumentAdditionFormat),
     #[error("The provided payload reached the size limit.")]
     PayloadTooLarge,
+    #[error("Too many search requests are waiting to be processed. Please retry later.")]
+    TooManySearchRequests,
+    #[error("Invalid timeseries query: {0}.")]
+    InvalidTimeseriesQuery(String),
+    #[error("The document writes are suspended because {0}. Please retry later.")]
+    WritesSuspended(BreakerReason),
+    #[error(
+        "Read session `{0}` not found. A read session expires when it isn't read for its `ttl`."
+    )]
+    ReadSessionNotFound(String),
+    #[error("Too many read sessions are open. Please close one or retry later.")]
+    TooManyReadSessions,
+    #[error("The `ttl` of a read session must be between 1 and {0} seconds.")]
+    InvalidReadSessionTtl(u64),
 }
 
 internal_error!(IndexControllerError: JoinError, UpdateFileStoreError);
@@ -59,7 +80,15 @@ impl ErrorCode for IndexControllerError {
             IndexControllerError::DocumentFormatError(e) => e.error_code(),
             IndexControllerError::MissingPayload(_) => Code::MissingPayload,
             IndexControllerError::PayloadTooLarge => Code::PayloadTooLarge,
+            IndexControllerError::TooManySearchRequests => Code::TooManySearchRequests,
+            IndexControllerError::InvalidTimeseriesQuery(_) => Code::InvalidTimeseriesQuery,
+            IndexControllerError::WritesSuspended(_) => Code::WritesSuspended,
+            IndexControllerError::ReadSessionNotFound(_) => Code::ReadSessionNotFound,
+            IndexControllerError::TooManyReadSessions => Code::TooManyReadSessions,
+            IndexControllerError::InvalidReadSessionTtl(_) => Code::BadRequest,
             IndexControllerError::DumpError(e) => e.error_code(),
+            IndexControllerError::MigrationError(e) => e.error_code(),
+            IndexControllerError::ReplicationError(e) => e.error_code(),
         }
     }
 }
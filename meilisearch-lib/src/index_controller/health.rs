@@ -0,0 +1,197 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+use sysinfo::{DiskExt, RefreshKind, System, SystemExt};
+use time::OffsetDateTime;
+
+use super::write_breaker::WriteBreakerState;
+use crate::tasks::task::{Task, TaskEvent};
+
+/// The time after which the database is reported as not writable when no write transaction
+/// could be opened on it.
+pub const WRITE_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The state of the subsystems of the instance.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Health {
+    pub task_queue: TaskQueueHealth,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_snapshot_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_dump_at: Option<OffsetDateTime>,
+    /// Whether a write transaction can be opened on the database of the tasks.
+    pub database_writable: bool,
+    /// The space of the disk of the database, unknown on the platforms without disk information.
+    pub disk: Option<DiskHealth>,
+    pub write_breaker: WriteBreakerState,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskQueueHealth {
+    pub enqueued_tasks: usize,
+    pub processing_tasks: usize,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub oldest_enqueued_at: Option<OffsetDateTime>,
+    /// The number of seconds the oldest unfinished task has been waiting for.
+    pub lag_seconds: i64,
+    /// Whether tasks registered before the instance started are still unfinished.
+    pub replaying: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskHealth {
+    pub available_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// Inspects the files written by the instance: its database, its dumps and its snapshot.
+#[derive(Debug, Clone)]
+pub struct HealthProbe {
+    db_path: PathBuf,
+    dump_dir: PathBuf,
+    snapshot_path: Option<PathBuf>,
+    started_at: OffsetDateTime,
+}
+
+impl HealthProbe {
+    pub fn new(db_path: PathBuf, dump_dir: PathBuf, snapshot_path: Option<PathBuf>) -> Self {
+        Self {
+            db_path,
+            dump_dir,
+            snapshot_path,
+            started_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    /// Summarizes the unfinished tasks.
+    pub fn task_queue(&self, unfinished: &[Task], now: OffsetDateTime) -> TaskQueueHealth {
+        let mut health = TaskQueueHealth::default();
+        let mut oldest_created_at: Option<OffsetDateTime> = None;
+
+        for task in unfinished {
+            let created_at = match task.events.first() {
+                Some(TaskEvent::Created(created_at)) => *created_at,
+                _ => continue,
+            };
+            if task.events.len() == 1 {
+                health.enqueued_tasks += 1;
+                health.oldest_enqueued_at = Some(
+                    health
+                        .oldest_enqueued_at
+                        .map_or(created_at, |oldest| oldest.min(created_at)),
+                );
+            } else {
+                health.processing_tasks += 1;
+            }
+            oldest_created_at =
+                Some(oldest_created_at.map_or(created_at, |oldest| oldest.min(created_at)));
+        }
+
+        if let Some(created_at) = oldest_created_at {
+            health.lag_seconds = (now - created_at).whole_seconds().max(0);
+            health.replaying = created_at < self.started_at;
+        }
+
+        health
+    }
+
+    /// The date of the snapshot of the database, if there is one.
+    pub fn last_snapshot_at(&self) -> Option<OffsetDateTime> {
+        self.snapshot_path.as_deref().and_then(modified_at)
+    }
+
+    /// The date of the most recent dump of the dump directory.
+    pub fn last_dump_at(&self) -> Option<OffsetDateTime> {
+        fs::read_dir(&self.dump_dir)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "dump"))
+            .filter_map(|path| modified_at(&path))
+            .max()
+    }
+
+    pub fn disk(&self) -> Option<DiskHealth> {
+        disk_space(&self.db_path)
+    }
+}
+
+/// The space of the disk the path is stored on, the one of the deepest mount point containing
+/// it.
+pub fn disk_space(path: &Path) -> Option<DiskHealth> {
+    if !System::IS_SUPPORTED {
+        return None;
+    }
+
+    let path = path.canonicalize().ok()?;
+    let system = System::new_with_specifics(RefreshKind::new().with_disks_list());
+    system
+        .disks()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| DiskHealth {
+            available_bytes: disk.available_space(),
+            total_bytes: disk.total_space(),
+        })
+}
+
+fn modified_at(path: &Path) -> Option<OffsetDateTime> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(OffsetDateTime::from(modified))
+}
+
+#[cfg(test)]
+mod test {
+    use time::Duration;
+
+    use super::*;
+    use crate::index_resolver::IndexUid;
+    use crate::tasks::task::TaskContent;
+
+    fn task(id: u64, events: Vec<TaskEvent>) -> Task {
+        Task {
+            id,
+            index_uid: IndexUid::new_unchecked("test"),
+            content: TaskContent::IndexDeletion,
+            events,
+        }
+    }
+
+    #[test]
+    fn task_queue_lag_and_replay() {
+        let probe = HealthProbe::new("data.ms".into(), "dumps".into(), None);
+        let now = probe.started_at + Duration::seconds(30);
+        assert_eq!(probe.task_queue(&[], now), TaskQueueHealth::default());
+
+        let recent = probe.started_at + Duration::seconds(10);
+        let tasks = [
+            task(0, vec![TaskEvent::Created(recent)]),
+            task(
+                1,
+                vec![
+                    TaskEvent::Created(recent + Duration::seconds(5)),
+                    TaskEvent::Processing(now),
+                ],
+            ),
+        ];
+        let health = probe.task_queue(&tasks, now);
+        assert_eq!(health.enqueued_tasks, 1);
+        assert_eq!(health.processing_tasks, 1);
+        assert_eq!(health.oldest_enqueued_at, Some(recent));
+        assert_eq!(health.lag_seconds, 20);
+        assert!(!health.replaying);
+
+        // A task registered before the start is being replayed.
+        let before_start = probe.started_at - Duration::seconds(60);
+        let tasks = [task(0, vec![TaskEvent::Created(before_start)])];
+        let health = probe.task_queue(&tasks, now);
+        assert_eq!(health.lag_seconds, 90);
+        assert!(health.replaying);
+    }
+}
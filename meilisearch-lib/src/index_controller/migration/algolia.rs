@@ -0,0 +1,443 @@
+use std::collections::BTreeSet;
+
+use milli::update::Setting;
+use reqwest::{Client, RequestBuilder, Url};
+use serde_json::{json, Value};
+
+use super::error::Result;
+use super::{endpoint, send_json, strings, Source};
+use crate::index::synonyms::Synonyms;
+use crate::index::updates::{MinWordSizeTyposSetting, TypoSettings};
+use crate::index::{Document, Settings, Unchecked};
+
+/// Every Algolia record is identified by its `objectID`.
+const ALGOLIA_PRIMARY_KEY: &str = "objectID";
+/// The maximum number of synonyms Algolia returns in a page.
+const SYNONYMS_PAGE_SIZE: usize = 1000;
+
+pub struct AlgoliaSource {
+    client: Client,
+    host: Url,
+    app_id: String,
+    api_key: String,
+    index: String,
+    batch_size: usize,
+    /// The browse cursor of the next batch.
+    cursor: Option<String>,
+    finished: bool,
+    total_documents: Option<usize>,
+}
+
+impl AlgoliaSource {
+    pub fn new(
+        client: Client,
+        host: Url,
+        app_id: String,
+        api_key: String,
+        index: String,
+        batch_size: usize,
+    ) -> Self {
+        Self {
+            client,
+            host,
+            app_id,
+            api_key,
+            index,
+            batch_size,
+            cursor: None,
+            finished: false,
+            total_documents: None,
+        }
+    }
+
+    fn request(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+            .header("X-Algolia-Application-Id", &self.app_id)
+            .header("X-Algolia-API-Key", &self.api_key)
+    }
+
+    fn endpoint(&self, name: &str) -> Url {
+        endpoint(&self.host, &["1", "indexes", &self.index, name])
+    }
+
+    async fn synonyms(&self) -> Result<Synonyms> {
+        let url = endpoint(
+            &self.host,
+            &["1", "indexes", &self.index, "synonyms", "search"],
+        );
+        let mut synonyms = Synonyms::new();
+        let mut page = 0;
+        loop {
+            let request = self
+                .client
+                .post(url.clone())
+                .json(&json!({ "page": page, "hitsPerPage": SYNONYMS_PAGE_SIZE }));
+            let response = send_json(self.request(request)).await?;
+            let hits = match response["hits"].as_array() {
+                Some(hits) if !hits.is_empty() => hits,
+                _ => break,
+            };
+
+            for hit in hits {
+                add_synonyms(&mut synonyms, hit);
+            }
+
+            let fetched = page * SYNONYMS_PAGE_SIZE + hits.len();
+            if hits.len() < SYNONYMS_PAGE_SIZE
+                || response["nbHits"]
+                    .as_u64()
+                    .map_or(true, |total| fetched as u64 >= total)
+            {
+                break;
+            }
+            page += 1;
+        }
+
+        Ok(synonyms)
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Source for AlgoliaSource {
+    fn primary_key(&self) -> &str {
+        ALGOLIA_PRIMARY_KEY
+    }
+
+    async fn settings(&mut self) -> Result<Settings<Unchecked>> {
+        let request = self.client.get(self.endpoint("settings"));
+        let algolia = send_json(self.request(request)).await?;
+
+        let mut settings = map_settings(&algolia);
+        let synonyms = self.synonyms().await?;
+        if !synonyms.is_empty() {
+            settings.synonyms = Setting::Set(synonyms);
+        }
+
+        Ok(settings)
+    }
+
+    async fn next_batch(&mut self, primary_key: &str) -> Result<Option<Vec<Document>>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let body = match &self.cursor {
+            Some(cursor) => json!({ "cursor": cursor }),
+            None => json!({ "hitsPerPage": self.batch_size }),
+        };
+        let request = self.client.post(self.endpoint("browse")).json(&body);
+        let mut response = send_json(self.request(request)).await?;
+
+        if let Some(total) = response["nbHits"].as_u64() {
+            self.total_documents = Some(total as usize);
+        }
+        self.cursor = response["cursor"].as_str().map(String::from);
+        self.finished = self.cursor.is_none();
+
+        let documents = match response.get_mut("hits").map(Value::take) {
+            Some(Value::Array(hits)) => hits
+                .into_iter()
+                .filter_map(|hit| match hit {
+                    Value::Object(document) => Some(to_document(document, primary_key)),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        Ok(Some(documents))
+    }
+
+    fn total_documents(&self) -> Option<usize> {
+        self.total_documents
+    }
+}
+
+/// Removes the attributes only used by Algolia and copies the `objectID` in the primary key.
+fn to_document(mut document: Document, primary_key: &str) -> Document {
+    document.remove("_highlightResult");
+    document.remove("_snippetResult");
+    document.remove("_rankingInfo");
+    if primary_key != ALGOLIA_PRIMARY_KEY && !document.contains_key(primary_key) {
+        if let Some(id) = document.get(ALGOLIA_PRIMARY_KEY).cloned() {
+            document.insert(primary_key.to_string(), id);
+        }
+    }
+    document
+}
+
+/// Removes the modifiers Algolia wraps the attributes with, such as `unordered(title)` or
+/// `filterOnly(genre)`.
+fn attribute_name(attribute: &str) -> &str {
+    match (attribute.find('('), attribute.strip_suffix(')')) {
+        (Some(start), Some(attribute)) => &attribute[start + 1..],
+        _ => attribute,
+    }
+}
+
+/// Converts the Algolia ranking criteria, the sorts on attributes being expressed as
+/// `desc(popularity)`.
+fn ranking_rule(criterion: &str) -> Option<String> {
+    let rule = match criterion {
+        "typo" => "typo".to_string(),
+        "words" => "words".to_string(),
+        "proximity" => "proximity".to_string(),
+        "attribute" => "attribute".to_string(),
+        "exact" => "exactness".to_string(),
+        // The geo and filters criteria are always applied by Meilisearch.
+        "geo" | "filters" => return None,
+        criterion => match criterion.split_once('(') {
+            Some(("asc", attribute)) => format!("{}:asc", attribute.strip_suffix(')')?),
+            Some(("desc", attribute)) => format!("{}:desc", attribute.strip_suffix(')')?),
+            _ => return None,
+        },
+    };
+
+    Some(rule)
+}
+
+fn ranking_rules(ranking: &[String], custom_ranking: &[String]) -> Vec<String> {
+    let mut rules = Vec::new();
+    for criterion in ranking {
+        if criterion == "custom" {
+            // The sort parameter of the searches takes the place of the replicas of Algolia.
+            rules.push("sort".to_string());
+            rules.extend(custom_ranking.iter().filter_map(|c| ranking_rule(c)));
+        } else if let Some(rule) = ranking_rule(criterion) {
+            rules.push(rule);
+        }
+    }
+
+    if !rules.iter().any(|rule| rule == "sort") {
+        rules.push("sort".to_string());
+    }
+    rules
+}
+
+/// Converts the settings of an Algolia index to their Meilisearch equivalents. The settings
+/// without any equivalent are ignored.
+pub fn map_settings(algolia: &Value) -> Settings<Unchecked> {
+    let mut settings = Settings::default();
+
+    let searchable = strings(algolia.get("searchableAttributes"));
+    if !searchable.is_empty() {
+        // Attributes of the same priority are separated by commas.
+        settings.searchable_attributes = Setting::Set(
+            searchable
+                .iter()
+                .flat_map(|attributes| attributes.split(','))
+                .map(|attribute| attribute_name(attribute.trim()).to_string())
+                .collect(),
+        );
+    }
+
+    let facets: BTreeSet<_> = strings(algolia.get("attributesForFaceting"))
+        .iter()
+        .map(|attribute| attribute_name(attribute).to_string())
+        .collect();
+    if !facets.is_empty() {
+        settings.filterable_attributes = Setting::Set(facets);
+    }
+
+    if let Some(distinct) = algolia["attributeForDistinct"].as_str() {
+        settings.distinct_attribute = Setting::Set(distinct.to_string());
+    }
+
+    let displayed = strings(algolia.get("attributesToRetrieve"));
+    if !displayed.is_empty() && !displayed.iter().any(|attribute| attribute == "*") {
+        settings.displayed_attributes = Setting::Set(displayed);
+    }
+
+    let ranking = strings(algolia.get("ranking"));
+    let custom_ranking = strings(algolia.get("customRanking"));
+    if !ranking.is_empty() || !custom_ranking.is_empty() {
+        let ranking = if ranking.is_empty() {
+            [
+                "typo",
+                "geo",
+                "words",
+                "filters",
+                "proximity",
+                "attribute",
+                "exact",
+                "custom",
+            ]
+            .iter()
+            .map(|criterion| criterion.to_string())
+            .collect()
+        } else {
+            ranking
+        };
+        settings.ranking_rules = Setting::Set(ranking_rules(&ranking, &custom_ranking));
+    }
+
+    let mut typo_tolerance = TypoSettings::default();
+    if algolia["typoTolerance"] == Value::Bool(false) {
+        typo_tolerance.enabled = Setting::Set(false);
+    }
+    let min_word_size = MinWordSizeTyposSetting {
+        one_typo: algolia["minWordSizefor1Typo"]
+            .as_u64()
+            .map_or(Setting::NotSet, |size| Setting::Set(size as u8)),
+        two_typos: algolia["minWordSizefor2Typos"]
+            .as_u64()
+            .map_or(Setting::NotSet, |size| Setting::Set(size as u8)),
+    };
+    if min_word_size != MinWordSizeTyposSetting::default() {
+        typo_tolerance.min_word_size_for_typos = Setting::Set(min_word_size);
+    }
+    let disabled: BTreeSet<_> = strings(algolia.get("disableTypoToleranceOnAttributes"))
+        .into_iter()
+        .collect();
+    if !disabled.is_empty() {
+        typo_tolerance.disable_on_attributes = Setting::Set(disabled);
+    }
+    let disabled: BTreeSet<_> = strings(algolia.get("disableTypoToleranceOnWords"))
+        .into_iter()
+        .collect();
+    if !disabled.is_empty() {
+        typo_tolerance.disable_on_words = Setting::Set(disabled);
+    }
+    if typo_tolerance != TypoSettings::default() {
+        settings.typo_tolerance = Setting::Set(typo_tolerance);
+    }
+
+    settings
+}
+
+/// Adds an Algolia synonym to the synonyms. The regular synonyms are equivalent words, the one
+/// way synonyms are only used when searching their input. The alternative corrections and the
+/// placeholders have no equivalent.
+fn add_synonyms(synonyms: &mut Synonyms, synonym: &Value) {
+    let words = strings(synonym.get("synonyms"));
+    match synonym["type"].as_str() {
+        Some("synonym") => {
+            for word in &words {
+                let entry = synonyms.entry(word.clone()).or_default();
+                for other in words.iter().filter(|other| *other != word) {
+                    if !entry.contains(other) {
+                        entry.push(other.clone());
+                    }
+                }
+            }
+        }
+        Some("oneWaySynonym") => {
+            if let Some(input) = synonym["input"].as_str() {
+                let entry = synonyms.entry(input.to_string()).or_default();
+                for word in words {
+                    if !entry.contains(&word) {
+                        entry.push(word);
+                    }
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn map_algolia_settings() {
+        let algolia = json!({
+            "searchableAttributes": ["title,alternative_title", "unordered(overview)"],
+            "attributesForFaceting": ["genres", "filterOnly(year)", "searchable(director)"],
+            "attributeForDistinct": "saga",
+            "attributesToRetrieve": ["*"],
+            "ranking": ["typo", "geo", "words", "filters", "proximity", "attribute", "exact", "custom"],
+            "customRanking": ["desc(popularity)", "asc(price)"],
+            "minWordSizefor1Typo": 3,
+            "typoTolerance": true,
+        });
+
+        let settings = map_settings(&algolia);
+        assert_eq!(
+            settings.searchable_attributes,
+            Setting::Set(vec![
+                "title".to_string(),
+                "alternative_title".to_string(),
+                "overview".to_string()
+            ])
+        );
+        assert_eq!(
+            settings.filterable_attributes,
+            Setting::Set(
+                ["director", "genres", "year"]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect()
+            )
+        );
+        assert_eq!(
+            settings.distinct_attribute,
+            Setting::Set("saga".to_string())
+        );
+        assert_eq!(settings.displayed_attributes, Setting::NotSet);
+        assert_eq!(
+            settings.ranking_rules,
+            Setting::Set(
+                [
+                    "typo",
+                    "words",
+                    "proximity",
+                    "attribute",
+                    "exactness",
+                    "sort",
+                    "popularity:desc",
+                    "price:asc"
+                ]
+                .iter()
+                .map(|s| s.to_string())
+                .collect()
+            )
+        );
+        let typo_tolerance = settings.typo_tolerance.set().unwrap();
+        assert_eq!(typo_tolerance.enabled, Setting::NotSet);
+        assert_eq!(
+            typo_tolerance
+                .min_word_size_for_typos
+                .set()
+                .unwrap()
+                .one_typo,
+            Setting::Set(3)
+        );
+    }
+
+    #[test]
+    fn map_algolia_synonyms() {
+        let mut synonyms = Synonyms::new();
+        add_synonyms(
+            &mut synonyms,
+            &json!({ "type": "synonym", "synonyms": ["tv", "television"] }),
+        );
+        add_synonyms(
+            &mut synonyms,
+            &json!({ "type": "oneWaySynonym", "input": "phone", "synonyms": ["iphone"] }),
+        );
+        add_synonyms(
+            &mut synonyms,
+            &json!({ "type": "placeholder", "placeholder": "<street>", "replacements": ["1st"] }),
+        );
+
+        assert_eq!(synonyms.len(), 3);
+        assert_eq!(synonyms["tv"], vec!["television".to_string()]);
+        assert_eq!(synonyms["television"], vec!["tv".to_string()]);
+        assert_eq!(synonyms["phone"], vec!["iphone".to_string()]);
+    }
+
+    #[test]
+    fn algolia_documents_keep_their_object_id() {
+        let document = json!({ "objectID": "42", "title": "Carol", "_highlightResult": {} });
+        let document = match document {
+            Value::Object(document) => document,
+            _ => unreachable!(),
+        };
+
+        let document = to_document(document, "id");
+        assert_eq!(document["objectID"], "42");
+        assert_eq!(document["id"], "42");
+        assert!(document.get("_highlightResult").is_none());
+    }
+}
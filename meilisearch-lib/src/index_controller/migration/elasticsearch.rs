@@ -0,0 +1,400 @@
+use std::collections::BTreeSet;
+
+use milli::update::Setting;
+use reqwest::{Client, RequestBuilder, Url};
+use serde_json::{json, Map, Value};
+
+use super::error::Result;
+use super::{endpoint, send_json, strings, Source};
+use crate::index::synonyms::{self, SynonymsFormat};
+use crate::index::updates::{StopWords, StopWordsPresets};
+use crate::index::{Document, Settings, Unchecked};
+
+/// The attribute the `_id` of the Elasticsearch documents is copied in.
+const ELASTICSEARCH_PRIMARY_KEY: &str = "id";
+/// How long Elasticsearch keeps the search context between two batches.
+const SCROLL_DURATION: &str = "5m";
+
+pub enum Credentials {
+    None,
+    ApiKey(String),
+    Basic { username: String, password: String },
+}
+
+pub struct ElasticsearchSource {
+    client: Client,
+    url: Url,
+    credentials: Credentials,
+    index: String,
+    batch_size: usize,
+    scroll_id: Option<String>,
+    finished: bool,
+    total_documents: Option<usize>,
+}
+
+impl ElasticsearchSource {
+    pub fn new(
+        client: Client,
+        url: Url,
+        credentials: Credentials,
+        index: String,
+        batch_size: usize,
+    ) -> Self {
+        Self {
+            client,
+            url,
+            credentials,
+            index,
+            batch_size,
+            scroll_id: None,
+            finished: false,
+            total_documents: None,
+        }
+    }
+
+    fn request(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.credentials {
+            Credentials::None => request,
+            Credentials::ApiKey(api_key) => {
+                request.header("Authorization", format!("ApiKey {}", api_key))
+            }
+            Credentials::Basic { username, password } => {
+                request.basic_auth(username, Some(password))
+            }
+        }
+    }
+
+    /// Frees the search context, Elasticsearch would otherwise keep it until it expires.
+    async fn clear_scroll(&mut self) {
+        if let Some(scroll_id) = self.scroll_id.take() {
+            let request = self
+                .client
+                .delete(endpoint(&self.url, &["_search", "scroll"]))
+                .json(&json!({ "scroll_id": scroll_id }));
+            if let Err(e) = send_json(self.request(request)).await {
+                log::warn!("Could not clear the Elasticsearch scroll: {}", e);
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Source for ElasticsearchSource {
+    fn primary_key(&self) -> &str {
+        ELASTICSEARCH_PRIMARY_KEY
+    }
+
+    async fn settings(&mut self) -> Result<Settings<Unchecked>> {
+        let request = self
+            .client
+            .get(endpoint(&self.url, &[&self.index, "_mapping"]));
+        let mapping = send_json(self.request(request)).await?;
+        let request = self
+            .client
+            .get(endpoint(&self.url, &[&self.index, "_settings"]));
+        let index_settings = send_json(self.request(request)).await?;
+
+        // Both are keyed by the name of the index, which differs from the requested one when it is
+        // an alias.
+        let mapping = first_value(&mapping, "mappings");
+        let index_settings = first_value(&index_settings, "settings");
+
+        Ok(map_settings(&mapping, &index_settings))
+    }
+
+    async fn next_batch(&mut self, primary_key: &str) -> Result<Option<Vec<Document>>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let request = match &self.scroll_id {
+            Some(scroll_id) => self
+                .client
+                .post(endpoint(&self.url, &["_search", "scroll"]))
+                .json(&json!({ "scroll": SCROLL_DURATION, "scroll_id": scroll_id })),
+            None => {
+                let mut url = endpoint(&self.url, &[&self.index, "_search"]);
+                url.query_pairs_mut().append_pair("scroll", SCROLL_DURATION);
+                self.client.post(url).json(&json!({
+                    "size": self.batch_size,
+                    "sort": ["_doc"],
+                    "track_total_hits": true,
+                }))
+            }
+        };
+        let mut response = send_json(self.request(request)).await?;
+
+        self.scroll_id = response["_scroll_id"].as_str().map(String::from);
+        if let Some(total) = total_hits(&response["hits"]["total"]) {
+            self.total_documents = Some(total);
+        }
+
+        let hits = match response
+            .get_mut("hits")
+            .and_then(|hits| hits.get_mut("hits"))
+            .map(Value::take)
+        {
+            Some(Value::Array(hits)) => hits,
+            _ => Vec::new(),
+        };
+
+        if hits.is_empty() {
+            self.finished = true;
+            self.clear_scroll().await;
+            return Ok(None);
+        }
+
+        let documents = hits
+            .into_iter()
+            .filter_map(|hit| to_document(hit, primary_key))
+            .collect();
+
+        Ok(Some(documents))
+    }
+
+    fn total_documents(&self) -> Option<usize> {
+        self.total_documents
+    }
+}
+
+/// The total is a number before Elasticsearch 7 and an object afterwards.
+fn total_hits(total: &Value) -> Option<usize> {
+    match total {
+        Value::Number(total) => total.as_u64(),
+        total => total["value"].as_u64(),
+    }
+    .map(|total| total as usize)
+}
+
+fn first_value(response: &Value, field: &str) -> Value {
+    response
+        .as_object()
+        .and_then(|indexes| indexes.values().next())
+        .map(|index| index[field].clone())
+        .unwrap_or(Value::Null)
+}
+
+/// Returns the `_source` of a hit, with its `_id` copied in the primary key if it is missing.
+fn to_document(mut hit: Value, primary_key: &str) -> Option<Document> {
+    let id = hit.get_mut("_id").map(Value::take).unwrap_or(Value::Null);
+    let mut document = match hit.get_mut("_source").map(Value::take) {
+        Some(Value::Object(document)) => document,
+        _ => Map::new(),
+    };
+    if !document.contains_key(primary_key) && !id.is_null() {
+        document.insert(primary_key.to_string(), id);
+    }
+
+    if document.is_empty() {
+        None
+    } else {
+        Some(document)
+    }
+}
+
+/// The attributes of the mapping, with the nested object properties flattened with dots.
+fn mapped_fields(properties: &Value, prefix: &str, fields: &mut Vec<(String, String)>) {
+    if let Some(properties) = properties.as_object() {
+        for (name, property) in properties {
+            let name = format!("{}{}", prefix, name);
+            if let Some(kind) = property["type"].as_str() {
+                if kind != "object" && kind != "nested" {
+                    fields.push((name.clone(), kind.to_string()));
+                }
+            }
+            mapped_fields(&property["properties"], &format!("{}.", name), fields);
+        }
+    }
+}
+
+/// Converts the mapping and the analysis settings of an Elasticsearch index to their Meilisearch
+/// equivalents: the text fields are searchable, the exact-value fields are filterable and
+/// sortable, and the synonyms and stop words of the token filters are kept. The geo points are
+/// not converted since they are not stored in the `_geo` attribute.
+pub fn map_settings(mapping: &Value, index_settings: &Value) -> Settings<Unchecked> {
+    let mut settings = Settings::default();
+
+    let mut fields = Vec::new();
+    mapped_fields(&mapping["properties"], "", &mut fields);
+
+    let searchable: Vec<_> = fields
+        .iter()
+        .filter(|(_, kind)| matches!(kind.as_str(), "text" | "search_as_you_type"))
+        .map(|(name, _)| name.clone())
+        .collect();
+    if !searchable.is_empty() {
+        settings.searchable_attributes = Setting::Set(searchable);
+    }
+
+    let exact = |kind: &str| {
+        matches!(
+            kind,
+            "keyword"
+                | "constant_keyword"
+                | "boolean"
+                | "date"
+                | "long"
+                | "integer"
+                | "short"
+                | "byte"
+                | "double"
+                | "float"
+                | "half_float"
+                | "scaled_float"
+                | "unsigned_long"
+        )
+    };
+    let filterable: BTreeSet<_> = fields
+        .iter()
+        .filter(|(_, kind)| exact(kind))
+        .map(|(name, _)| name.clone())
+        .collect();
+    let sortable: BTreeSet<_> = fields
+        .iter()
+        .filter(|(_, kind)| exact(kind) && kind != "boolean")
+        .map(|(name, _)| name.clone())
+        .collect();
+    if !filterable.is_empty() {
+        settings.filterable_attributes = Setting::Set(filterable);
+    }
+    if !sortable.is_empty() {
+        settings.sortable_attributes = Setting::Set(sortable);
+    }
+
+    let filters = index_settings["index"]["analysis"]["filter"].as_object();
+    let mut rules = Vec::new();
+    let mut stop_words = BTreeSet::new();
+    let mut presets = BTreeSet::new();
+    for filter in filters.into_iter().flat_map(|filters| filters.values()) {
+        match filter["type"].as_str() {
+            Some("synonym" | "synonym_graph") => rules.extend(strings(filter.get("synonyms"))),
+            Some("stop") => match filter.get("stopwords") {
+                // The predefined lists, such as `_english_`, are strings.
+                Some(Value::String(list)) => presets.extend(stop_words_preset(list)),
+                stopwords => stop_words.extend(strings(stopwords)),
+            },
+            _ => (),
+        }
+    }
+
+    if !rules.is_empty() {
+        // The synonyms rules of Elasticsearch are written in the Solr format.
+        match synonyms::parse_synonyms(SynonymsFormat::Solr, rules.join("\n").as_bytes()) {
+            Ok(synonyms) => settings.synonyms = Setting::Set(synonyms),
+            Err(e) => log::warn!("Ignoring the Elasticsearch synonyms: {}", e),
+        }
+    }
+    if !presets.is_empty() {
+        settings.stop_words = Setting::Set(StopWords::Presets(StopWordsPresets {
+            preset: presets,
+            words: stop_words,
+        }));
+    } else if !stop_words.is_empty() {
+        settings.stop_words = Setting::Set(StopWords::Words(stop_words));
+    }
+
+    settings
+}
+
+/// The stop words preset matching a predefined list of Elasticsearch.
+fn stop_words_preset(list: &str) -> Option<String> {
+    let language = match list {
+        "_dutch_" => "nl",
+        "_english_" => "en",
+        "_french_" => "fr",
+        "_german_" => "de",
+        "_italian_" => "it",
+        "_portuguese_" => "pt",
+        "_spanish_" => "es",
+        _ => return None,
+    };
+    Some(language.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn map_elasticsearch_settings() {
+        let mapping = json!({
+            "properties": {
+                "title": { "type": "text", "fields": { "raw": { "type": "keyword" } } },
+                "genre": { "type": "keyword" },
+                "year": { "type": "integer" },
+                "available": { "type": "boolean" },
+                "location": { "type": "geo_point" },
+                "director": {
+                    "properties": {
+                        "name": { "type": "text" },
+                        "id": { "type": "keyword" },
+                    }
+                },
+            }
+        });
+        let index_settings = json!({
+            "index": {
+                "analysis": {
+                    "filter": {
+                        "movie_synonyms": {
+                            "type": "synonym",
+                            "synonyms": ["sci-fi, science fiction", "tv => television"]
+                        },
+                        "english_stop": { "type": "stop", "stopwords": "_english_" },
+                        "custom_stop": { "type": "stop", "stopwords": ["the", "a"] },
+                    }
+                }
+            }
+        });
+
+        let settings = map_settings(&mapping, &index_settings);
+        assert_eq!(
+            settings.searchable_attributes,
+            Setting::Set(vec!["title".to_string(), "director.name".to_string()])
+        );
+        let filterable: BTreeSet<_> = ["available", "director.id", "genre", "year"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(settings.filterable_attributes, Setting::Set(filterable));
+        let sortable: BTreeSet<_> = ["director.id", "genre", "year"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(settings.sortable_attributes, Setting::Set(sortable));
+        let stop_words = StopWordsPresets {
+            preset: std::iter::once("en".to_string()).collect(),
+            words: ["a", "the"].iter().map(|s| s.to_string()).collect(),
+        };
+        assert_eq!(
+            settings.stop_words,
+            Setting::Set(StopWords::Presets(stop_words))
+        );
+
+        let synonyms = settings.synonyms.set().unwrap();
+        assert!(synonyms["sci-fi"].contains(&"science fiction".to_string()));
+        assert_eq!(synonyms["tv"], vec!["television".to_string()]);
+    }
+
+    #[test]
+    fn elasticsearch_documents_receive_their_id() {
+        let hit = json!({ "_id": "42", "_source": { "title": "Carol" } });
+        let document = to_document(hit, "id").unwrap();
+        assert_eq!(document["id"], "42");
+        assert_eq!(document["title"], "Carol");
+
+        let hit = json!({ "_id": "42", "_source": { "id": 7 } });
+        let document = to_document(hit, "id").unwrap();
+        assert_eq!(document["id"], 7);
+    }
+
+    #[test]
+    fn elasticsearch_total_hits() {
+        assert_eq!(total_hits(&json!(12)), Some(12));
+        assert_eq!(
+            total_hits(&json!({ "value": 12, "relation": "eq" })),
+            Some(12)
+        );
+        assert_eq!(total_hits(&Value::Null), None);
+    }
+}
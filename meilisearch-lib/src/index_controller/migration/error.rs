@@ -0,0 +1,32 @@
+use meilisearch_error::{Code, ErrorCode};
+
+pub type Result<T> = std::result::Result<T, MigrationError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MigrationError {
+    #[error("Migration `{0}` not found.")]
+    MigrationNotFound(String),
+    #[error("Invalid migration source: {0}.")]
+    InvalidSource(String),
+    #[error("The migration source could not be reached: {0}.")]
+    SourceUnreachable(String),
+    #[error("The migration source answered with an error: {0}.")]
+    SourceError(String),
+}
+
+impl From<reqwest::Error> for MigrationError {
+    fn from(other: reqwest::Error) -> Self {
+        Self::SourceUnreachable(other.to_string())
+    }
+}
+
+impl ErrorCode for MigrationError {
+    fn error_code(&self) -> Code {
+        match self {
+            MigrationError::MigrationNotFound(_) => Code::MigrationNotFound,
+            MigrationError::InvalidSource(_) => Code::InvalidMigrationSource,
+            // These errors only happen while the migration runs, they are reported in its status.
+            MigrationError::SourceUnreachable(_) | MigrationError::SourceError(_) => Code::Internal,
+        }
+    }
+}
@@ -0,0 +1,348 @@
+//! Imports the documents and the settings of an index hosted by another search engine.
+//!
+//! A migration runs in the background: the settings of the source index are converted to their
+//! Meilisearch equivalents and registered first, then the documents are fetched batch by batch,
+//! each batch being registered as a document addition task. The status of the migration reports
+//! how many documents were fetched and the tasks that were registered.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use reqwest::{RequestBuilder, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+
+use crate::index::{Document, Settings, Unchecked};
+use crate::tasks::task::TaskId;
+use error::{MigrationError, Result};
+
+mod algolia;
+mod elasticsearch;
+pub mod error;
+
+/// The number of documents fetched from the source and registered in a single task.
+pub const DEFAULT_MIGRATION_BATCH_SIZE: usize = 1000;
+
+/// Where the documents and the settings are imported from.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MigrationSource {
+    #[serde(rename_all = "camelCase")]
+    Algolia {
+        app_id: String,
+        api_key: String,
+        index: String,
+        /// Replaces the default `https://{appId}-dsn.algolia.net` host.
+        host: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Elasticsearch {
+        url: String,
+        index: String,
+        api_key: Option<String>,
+        username: Option<String>,
+        password: Option<String>,
+    },
+}
+
+impl MigrationSource {
+    fn name(&self) -> &'static str {
+        match self {
+            MigrationSource::Algolia { .. } => "algolia",
+            MigrationSource::Elasticsearch { .. } => "elasticsearch",
+        }
+    }
+
+    fn index(&self) -> &str {
+        match self {
+            MigrationSource::Algolia { index, .. }
+            | MigrationSource::Elasticsearch { index, .. } => index,
+        }
+    }
+
+    /// Checks the source before it is contacted, so the obvious mistakes are reported on the
+    /// creation of the migration.
+    fn validate(&self) -> Result<()> {
+        if self.index().is_empty() {
+            return Err(MigrationError::InvalidSource(
+                "`index` must not be empty".to_string(),
+            ));
+        }
+
+        match self {
+            MigrationSource::Algolia {
+                app_id,
+                api_key,
+                host,
+                ..
+            } => {
+                if app_id.is_empty() || api_key.is_empty() {
+                    return Err(MigrationError::InvalidSource(
+                        "`appId` and `apiKey` must not be empty".to_string(),
+                    ));
+                }
+                if let Some(host) = host {
+                    parse_url(host)?;
+                }
+            }
+            MigrationSource::Elasticsearch {
+                url,
+                api_key,
+                username,
+                password,
+                ..
+            } => {
+                parse_url(url)?;
+                if api_key.is_some() && (username.is_some() || password.is_some()) {
+                    return Err(MigrationError::InvalidSource(
+                        "`apiKey` can't be used with `username` and `password`".to_string(),
+                    ));
+                }
+                if username.is_some() != password.is_some() {
+                    return Err(MigrationError::InvalidSource(
+                        "`username` and `password` must be provided together".to_string(),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn connect(self, batch_size: usize) -> Result<Box<dyn Source>> {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("Meilisearch/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+
+        let source: Box<dyn Source> = match self {
+            MigrationSource::Algolia {
+                app_id,
+                api_key,
+                index,
+                host,
+            } => {
+                let host = host.unwrap_or_else(|| format!("https://{}-dsn.algolia.net", app_id));
+                Box::new(algolia::AlgoliaSource::new(
+                    client,
+                    parse_url(&host)?,
+                    app_id,
+                    api_key,
+                    index,
+                    batch_size,
+                ))
+            }
+            MigrationSource::Elasticsearch {
+                url,
+                index,
+                api_key,
+                username,
+                password,
+            } => {
+                let credentials = match (api_key, username, password) {
+                    (Some(api_key), _, _) => elasticsearch::Credentials::ApiKey(api_key),
+                    (None, Some(username), Some(password)) => {
+                        elasticsearch::Credentials::Basic { username, password }
+                    }
+                    _ => elasticsearch::Credentials::None,
+                };
+                Box::new(elasticsearch::ElasticsearchSource::new(
+                    client,
+                    parse_url(&url)?,
+                    credentials,
+                    index,
+                    batch_size,
+                ))
+            }
+        };
+
+        Ok(source)
+    }
+}
+
+/// A migration, as requested by the user.
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub source: MigrationSource,
+    /// The index the documents are imported in, it is created if it doesn't exist.
+    pub index_uid: String,
+    /// Overrides the primary key of the source.
+    pub primary_key: Option<String>,
+    pub batch_size: usize,
+}
+
+impl Migration {
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.batch_size == 0 {
+            return Err(MigrationError::InvalidSource(
+                "`batchSize` must be greater than 0".to_string(),
+            ));
+        }
+
+        self.source.validate()
+    }
+}
+
+/// An index hosted by another search engine.
+#[async_trait::async_trait(?Send)]
+pub(crate) trait Source {
+    /// The attribute identifying the documents in the source.
+    fn primary_key(&self) -> &str;
+
+    /// Returns the settings of the source converted to their Meilisearch equivalents.
+    async fn settings(&mut self) -> Result<Settings<Unchecked>>;
+
+    /// Returns the next batch of documents, or `None` once all of them were fetched. The primary
+    /// key is added to the documents that miss it.
+    async fn next_batch(&mut self, primary_key: &str) -> Result<Option<Vec<Document>>>;
+
+    /// The number of documents of the source, once known.
+    fn total_documents(&self) -> Option<usize>;
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStatus {
+    InProgress,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationInfo {
+    pub uid: String,
+    pub status: MigrationStatus,
+    pub index_uid: String,
+    pub source: &'static str,
+    pub source_index: String,
+    pub documents_fetched: usize,
+    pub total_documents: Option<usize>,
+    /// The settings update and document addition tasks, in the order they were registered.
+    pub task_uids: Vec<TaskId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    started_at: OffsetDateTime,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    finished_at: Option<OffsetDateTime>,
+}
+
+impl MigrationInfo {
+    pub fn new(uid: String, migration: &Migration) -> Self {
+        Self {
+            uid,
+            status: MigrationStatus::InProgress,
+            index_uid: migration.index_uid.clone(),
+            source: migration.source.name(),
+            source_index: migration.source.index().to_string(),
+            documents_fetched: 0,
+            total_documents: None,
+            task_uids: Vec::new(),
+            error: None,
+            started_at: OffsetDateTime::now_utc(),
+            finished_at: None,
+        }
+    }
+
+    pub fn with_error(&mut self, error: String) {
+        self.status = MigrationStatus::Failed;
+        self.finished_at = Some(OffsetDateTime::now_utc());
+        self.error = Some(error);
+    }
+
+    pub fn done(&mut self) {
+        self.status = MigrationStatus::Done;
+        self.finished_at = Some(OffsetDateTime::now_utc());
+    }
+}
+
+/// The migrations of the instance, they are kept in memory until the instance is stopped.
+#[derive(Clone, Default)]
+pub struct Migrations {
+    infos: Arc<RwLock<HashMap<String, MigrationInfo>>>,
+}
+
+impl Migrations {
+    pub fn insert(&self, info: MigrationInfo) {
+        self.infos.write().insert(info.uid.clone(), info);
+    }
+
+    pub fn get(&self, uid: &str) -> Result<MigrationInfo> {
+        self.infos
+            .read()
+            .get(uid)
+            .cloned()
+            .ok_or_else(|| MigrationError::MigrationNotFound(uid.to_string()))
+    }
+
+    /// Returns all the migrations, the most recent first.
+    pub fn list(&self) -> Vec<MigrationInfo> {
+        let mut infos: Vec<_> = self.infos.read().values().cloned().collect();
+        infos.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        infos
+    }
+
+    pub fn update(&self, uid: &str, f: impl FnOnce(&mut MigrationInfo)) {
+        if let Some(info) = self.infos.write().get_mut(uid) {
+            f(info);
+        }
+    }
+}
+
+fn parse_url(url: &str) -> Result<Url> {
+    let url = Url::parse(url)
+        .map_err(|e| MigrationError::InvalidSource(format!("invalid url `{}`: {}", url, e)))?;
+    if url.cannot_be_a_base() {
+        return Err(MigrationError::InvalidSource(format!(
+            "invalid url `{}`",
+            url
+        )));
+    }
+
+    Ok(url)
+}
+
+/// Returns the url of an endpoint of the source, the segments are percent-encoded.
+fn endpoint(base: &Url, segments: &[&str]) -> Url {
+    let mut url = base.clone();
+    if let Ok(mut path) = url.path_segments_mut() {
+        path.pop_if_empty().extend(segments);
+    }
+    url
+}
+
+/// Sends a request to the source and returns the JSON it answered with.
+async fn send_json(request: RequestBuilder) -> Result<Value> {
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let mut message = response.text().await.unwrap_or_default();
+        // The sources sometimes answer with a whole HTML page.
+        message.truncate(200);
+        return Err(MigrationError::SourceError(format!(
+            "{} {}",
+            status, message
+        )));
+    }
+
+    Ok(response.json().await?)
+}
+
+/// Returns the strings of a JSON array, the other values are ignored.
+fn strings(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -1,45 +1,94 @@
-use meilisearch_auth::SearchRules;
+use meilisearch_auth::{AuthController, SearchRules};
 use std::collections::BTreeMap;
 use std::fmt;
-use std::io::Cursor;
+use std::io::{Cursor, Read, Seek, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use actix_web::error::PayloadError;
+use async_stream::stream;
 use bytes::Bytes;
 use futures::Stream;
 use futures::StreamExt;
 use milli::update::IndexDocumentsMethod;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::{mpsc, RwLock};
 use tokio::task::spawn_blocking;
 use tokio::time::sleep;
 use uuid::Uuid;
 
-use crate::document_formats::{read_csv, read_json, read_ndjson};
+#[cfg(feature = "parquet")]
+use crate::document_formats::read_parquet;
+#[cfg(feature = "xml")]
+use crate::document_formats::read_xml;
+use crate::document_formats::{read_csv, read_json, read_ndjson, DocumentFormatError};
+use crate::index::error::Result as IndexResult;
 use crate::index::{
-    Checked, Document, IndexMeta, IndexStats, SearchQuery, SearchResult, Settings, Unchecked,
+    AppliedTask, Checked, Document, ExportQuery, IndexMeta, IndexStats, SearchQuery, SearchResult,
+    Settings, SuggestQuery, SuggestResult, Unchecked,
 };
 use crate::index_controller::dump_actor::{load_dump, DumpActor, DumpActorHandleImpl};
 use crate::options::{IndexerOpts, SchedulerConfig};
-use crate::snapshot::{load_snapshot, SnapshotService};
+use crate::snapshot::{load_snapshot, snapshot_file_path, SnapshotService};
 use crate::tasks::error::TaskError;
-use crate::tasks::task::{DocumentDeletion, Task, TaskContent, TaskId};
-use crate::tasks::{Scheduler, TaskFilter, TaskStore};
+use crate::tasks::task::{DocumentDeletion, Task, TaskContent, TaskEvent, TaskId};
+use crate::tasks::watch::TaskProgress;
+use crate::tasks::{IndexingSettings, IndexingSettingsUpdate, Scheduler, TaskFilter, TaskStore};
 use error::Result;
 
 use self::dump_actor::{DumpActorHandle, DumpInfo};
 use self::error::IndexControllerError;
-use crate::index_resolver::index_store::{IndexStore, MapIndexStore};
+use self::health::{Health, HealthProbe, WRITE_CHECK_TIMEOUT};
+use self::migration::{Migration, MigrationInfo, Migrations};
+use self::read_session::{
+    ReadSessionView, ReadSessions, DEFAULT_READ_SESSION_TTL, MAX_READ_SESSION_TTL,
+};
+use self::replication::error::ReplicationError;
+use self::replication::{
+    recover_update_files, Primary, ReplicatedTask, RetainedUpdateFiles, POLL_INTERVAL,
+    REPLICATION_BATCH_SIZE,
+};
+use self::search_cache::{CacheKey, SearchCache, SearchCacheStats};
+use self::search_queue::{SearchQueue, DEFAULT_SEARCH_QUEUE_SIZE};
+use self::slow_query_log::{SlowQuery, SlowQueryLog};
+use self::stream_reader::{StreamReader, STREAM_CHUNKS_CAPACITY};
+use self::timeseries::{Timeseries, TimeseriesPoint};
+use self::usage_digest::run_usage_digests;
+use self::watched_import::run_watched_import;
+use self::write_breaker::{
+    run_write_breaker, WriteBreaker, WriteBreakerState, WriteBreakerThresholds,
+};
+use crate::index_resolver::disk_quota::DiskQuota;
+use crate::index_resolver::index_store::{open_indexes_capacity, IndexStore, MapIndexStore};
 use crate::index_resolver::meta_store::{HeedMetaStore, IndexMetaStore};
+use crate::index_resolver::templates::HeedTemplateStore;
+use crate::index_resolver::trash::HeedIndexTrash;
 use crate::index_resolver::{create_index_resolver, IndexResolver, IndexUid};
-use crate::update_file_store::UpdateFileStore;
+use crate::update_file_store::{UpdateFile, UpdateFileStore};
 
 mod dump_actor;
 pub mod error;
+pub mod health;
+pub mod migration;
+pub mod read_session;
+pub mod replication;
+pub mod search_cache;
+mod search_queue;
+pub mod slow_query_log;
+mod stream_reader;
+pub mod timeseries;
+pub mod usage_digest;
 pub mod versioning;
+pub mod watched_import;
+pub mod write_breaker;
+
+pub use crate::index_resolver::templates::IndexTemplate;
+
+/// The number of serialized chunks of documents an export buffers ahead of its reader.
+const EXPORT_BUFFERED_CHUNKS: usize = 4;
 
 /// Concrete implementation of the IndexController, exposed by meilisearch-lib
 pub type MeiliSearch = IndexController<HeedMetaStore, MapIndexStore>;
@@ -48,6 +97,113 @@ pub type Payload = Box<
     dyn Stream<Item = std::result::Result<Bytes, PayloadError>> + Send + Sync + 'static + Unpin,
 >;
 
+/// Parses the documents as their chunks are received, and writes them to the update file,
+/// so that the payload is never entirely held in memory. Returns the number of documents, the
+/// update file to persist, and the size of the payload.
+async fn stream_documents(
+    mut payload: Payload,
+    mut update_file: UpdateFile,
+    format: DocumentAdditionFormat,
+    disk_quota: Option<(DiskQuota, u64)>,
+) -> Result<(usize, UpdateFile, u64)> {
+    let (sender, receiver) = mpsc::channel(STREAM_CHUNKS_CAPACITY);
+    let parser = spawn_blocking(move || -> Result<_> {
+        let count = format.read_documents(StreamReader::new(receiver), &mut *update_file)?;
+        Ok((count, update_file))
+    });
+
+    let mut size = 0;
+    while let Some(bytes) = payload.next().await {
+        // The payload error is returned rather than the parsing error of the truncated payload.
+        let bytes = bytes?;
+        size += bytes.len() as u64;
+        // The payload is refused as soon as it exceeds the disk quota, rather than once written.
+        if let Some((ref quota, usage)) = disk_quota {
+            quota.check_usage(usage, size)?;
+        }
+        // The parser stopped on a malformed document, its error is returned below.
+        if sender.send(bytes).await.is_err() {
+            break;
+        }
+    }
+    drop(sender);
+
+    if size == 0 {
+        return Err(IndexControllerError::MissingPayload(format));
+    }
+
+    let (count, update_file) = parser.await??;
+    Ok((count, update_file, size))
+}
+
+/// Registers a rebuild of every index each `interval`. The scheduled rebuilds only compact the
+/// indexes, reindexing the documents is left to the rebuilds registered through the API.
+async fn schedule_index_rebuilds(
+    interval: Duration,
+    index_resolver: Arc<IndexResolver<HeedMetaStore, MapIndexStore>>,
+    task_store: TaskStore,
+    scheduler: Arc<RwLock<Scheduler>>,
+) {
+    log::info!("Index rebuilds scheduled every {}s.", interval.as_secs());
+    loop {
+        sleep(interval).await;
+
+        let registered: Result<()> = async {
+            for uid in index_resolver.list().await? {
+                let content = TaskContent::IndexRebuild { reindex: false };
+                task_store.register(IndexUid::new(uid)?, content).await?;
+            }
+            Ok(())
+        }
+        .await;
+        if let Err(e) = registered {
+            log::error!("error while scheduling the index rebuilds: {}", e);
+        }
+
+        scheduler.read().await.notify();
+    }
+}
+
+/// How often the trash is checked for the indexes whose retention period elapsed.
+const TRASH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Registers the purge of the trashed indexes once their retention period elapsed, the purge
+/// deletes their files.
+async fn schedule_index_purges(
+    trash: HeedIndexTrash,
+    task_store: TaskStore,
+    scheduler: Arc<RwLock<Scheduler>>,
+) {
+    let interval = TRASH_CHECK_INTERVAL
+        .min(trash.retention())
+        .max(Duration::from_secs(1));
+    loop {
+        sleep(interval).await;
+
+        let registered: Result<bool> = async {
+            let expired = trash.expired_indexes().await?;
+            for (uid, trashed) in &expired {
+                let content = TaskContent::IndexPurge {
+                    index_uuid: trashed.meta.uuid,
+                };
+                let task = task_store
+                    .register(IndexUid::new(uid.clone())?, content)
+                    .await?;
+                trash
+                    .register_purge(uid.clone(), trashed.meta.uuid, task.id)
+                    .await?;
+            }
+            Ok(!expired.is_empty())
+        }
+        .await;
+        match registered {
+            Ok(true) => scheduler.read().await.notify(),
+            Ok(false) => (),
+            Err(e) => log::error!("error while scheduling the index purges: {}", e),
+        }
+    }
+}
+
 pub fn open_meta_env(path: &Path, size: usize) -> milli::heed::Result<milli::heed::Env> {
     let mut options = milli::heed::EnvOpenOptions::new();
     options.map_size(size);
@@ -78,6 +234,19 @@ pub struct IndexController<U, I> {
     task_store: TaskStore,
     dump_handle: dump_actor::DumpActorHandleImpl,
     update_file_store: UpdateFileStore,
+    slow_query_log: Option<Arc<SlowQueryLog>>,
+    search_queue: SearchQueue,
+    /// The time budget of the searches that don't set one.
+    search_timeout: Option<Duration>,
+    search_cache: Option<Arc<SearchCache>>,
+    timeseries: Arc<Timeseries>,
+    migrations: Migrations,
+    /// The primary followed by the instance, when it is a replica.
+    primary: Option<Primary>,
+    templates: HeedTemplateStore,
+    health: HealthProbe,
+    write_breaker: Arc<WriteBreaker>,
+    read_sessions: Arc<ReadSessions>,
 }
 
 /// Need a custom implementation for clone because deriving require that U and I are clone.
@@ -89,15 +258,58 @@ impl<U, I> Clone for IndexController<U, I> {
             dump_handle: self.dump_handle.clone(),
             update_file_store: self.update_file_store.clone(),
             task_store: self.task_store.clone(),
+            slow_query_log: self.slow_query_log.clone(),
+            search_queue: self.search_queue.clone(),
+            search_timeout: self.search_timeout,
+            search_cache: self.search_cache.clone(),
+            timeseries: self.timeseries.clone(),
+            migrations: self.migrations.clone(),
+            primary: self.primary.clone(),
+            templates: self.templates.clone(),
+            health: self.health.clone(),
+            write_breaker: self.write_breaker.clone(),
+            read_sessions: self.read_sessions.clone(),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum DocumentAdditionFormat {
     Json,
     Csv,
     Ndjson,
+    #[cfg(feature = "xml")]
+    Xml,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl DocumentAdditionFormat {
+    /// Whether the documents are parsed as the chunks of the payload are received, rather than
+    /// once the whole payload is buffered.
+    fn is_streamed(&self) -> bool {
+        !matches!(
+            self,
+            DocumentAdditionFormat::Json | DocumentAdditionFormat::Csv
+        )
+    }
+
+    /// Reads the documents of the payload and writes them as an obkv batch to `writer`.
+    fn read_documents(
+        &self,
+        input: impl Read,
+        writer: impl Write + Seek,
+    ) -> std::result::Result<usize, DocumentFormatError> {
+        match self {
+            DocumentAdditionFormat::Json => read_json(input, writer),
+            DocumentAdditionFormat::Csv => read_csv(input, writer),
+            DocumentAdditionFormat::Ndjson => read_ndjson(input, writer),
+            #[cfg(feature = "xml")]
+            DocumentAdditionFormat::Xml => read_xml(input, writer),
+            #[cfg(feature = "parquet")]
+            DocumentAdditionFormat::Parquet => read_parquet(input, writer),
+        }
+    }
 }
 
 impl fmt::Display for DocumentAdditionFormat {
@@ -106,6 +318,10 @@ impl fmt::Display for DocumentAdditionFormat {
             DocumentAdditionFormat::Json => write!(f, "json"),
             DocumentAdditionFormat::Ndjson => write!(f, "ndjson"),
             DocumentAdditionFormat::Csv => write!(f, "csv"),
+            #[cfg(feature = "xml")]
+            DocumentAdditionFormat::Xml => write!(f, "xml"),
+            #[cfg(feature = "parquet")]
+            DocumentAdditionFormat::Parquet => write!(f, "parquet"),
         }
     }
 }
@@ -138,6 +354,9 @@ pub enum Update {
         method: IndexDocumentsMethod,
         format: DocumentAdditionFormat,
         allow_index_creation: bool,
+        /// The size of the payload announced by the client, such as the `Content-Length` of the
+        /// request, for the disk quota to refuse it before it is read.
+        payload_size: Option<u64>,
     },
     DeleteIndex,
     CreateIndex {
@@ -146,6 +365,13 @@ pub enum Update {
     UpdateIndex {
         primary_key: Option<String>,
     },
+    RenameIndex {
+        new_uid: String,
+    },
+    RebuildIndex {
+        reindex: bool,
+    },
+    RestoreIndex,
 }
 
 #[derive(Default, Debug)]
@@ -162,6 +388,21 @@ pub struct IndexControllerBuilder {
     dump_dst: Option<PathBuf>,
     ignore_dump_if_db_exists: bool,
     ignore_missing_dump: bool,
+    slow_query_threshold: Option<Duration>,
+    slow_query_log_size: usize,
+    search_pool_size: Option<usize>,
+    search_queue_size: Option<usize>,
+    search_timeout: Option<Duration>,
+    search_cache_size: usize,
+    max_open_indexes: Option<usize>,
+    max_open_indexes_map_size: Option<usize>,
+    max_disk_usage: Option<u64>,
+    replicate_from: Option<(String, String)>,
+    replication_log_size: usize,
+    index_rebuild_interval: Option<Duration>,
+    write_breaker: WriteBreakerThresholds,
+    watched_import: Option<(PathBuf, Duration)>,
+    index_trash_retention: Option<Duration>,
 }
 
 impl IndexControllerBuilder {
@@ -215,21 +456,74 @@ impl IndexControllerBuilder {
         // Create or overwrite the version file for this DB
         versioning::create_version_file(db_path.as_ref())?;
 
-        let index_resolver = Arc::new(create_index_resolver(
-            &db_path,
+        let timeseries = Arc::new(Timeseries::load(db_path.as_ref()));
+        tokio::task::spawn_local(timeseries.clone().run());
+
+        let task_store = TaskStore::new(meta_env.clone())?;
+
+        let search_cache = (self.search_cache_size > 0)
+            .then(|| Arc::new(SearchCache::new(self.search_cache_size)));
+
+        let max_open_indexes = open_indexes_capacity(
             index_size,
-            &indexer_options,
-            meta_env.clone(),
+            self.max_open_indexes,
+            self.max_open_indexes_map_size,
+        );
+        let index_store = MapIndexStore::new(&db_path, index_size, &indexer_options)?
+            .with_max_open_indexes(max_open_indexes);
+
+        let templates = HeedTemplateStore::new(meta_env.clone())?;
+        let trash = self
+            .index_trash_retention
+            .map(|retention| HeedIndexTrash::new(meta_env.clone(), retention))
+            .transpose()?;
+        let read_sessions = Arc::new(ReadSessions::default());
+        let mut index_resolver = create_index_resolver(
+            index_store,
+            meta_env,
             update_file_store.clone(),
-        )?);
+            timeseries.clone(),
+            task_store.watcher(),
+        )?
+        .with_templates(templates.clone())
+        .with_read_sessions(read_sessions.clone());
+        if let Some(ref search_cache) = search_cache {
+            index_resolver = index_resolver.with_search_cache(search_cache.clone());
+        }
+        if let Some(ref trash) = trash {
+            index_resolver = index_resolver.with_trash(trash.clone());
+        }
+        if let Some(max_disk_usage) = self.max_disk_usage {
+            index_resolver =
+                index_resolver.with_disk_quota(DiskQuota::new(&db_path, max_disk_usage));
+        }
+        if self.replication_log_size > 0 {
+            let retained = Arc::new(RetainedUpdateFiles::new(self.replication_log_size));
+            index_resolver = index_resolver.with_retained_update_files(retained.clone());
+            tokio::task::spawn_local(recover_update_files(
+                retained,
+                task_store.clone(),
+                update_file_store.clone(),
+            ));
+        }
+        let index_resolver = Arc::new(index_resolver);
 
-        let task_store = TaskStore::new(meta_env)?;
         let scheduler =
             Scheduler::new(task_store.clone(), index_resolver.clone(), scheduler_config)?;
 
         let dump_path = self
             .dump_dst
             .ok_or_else(|| anyhow::anyhow!("Missing dump directory path"))?;
+        let snapshot_path = self
+            .snapshot_dir
+            .as_ref()
+            .map(|dir| snapshot_file_path(&db_path, dir));
+        let health = HealthProbe::new(
+            db_path.as_ref().to_path_buf(),
+            dump_path.clone(),
+            snapshot_path,
+        );
+
         let dump_handle = {
             let analytics_path = &db_path;
             let (sender, receiver) = mpsc::channel(10);
@@ -268,13 +562,86 @@ impl IndexControllerBuilder {
             tokio::task::spawn_local(snapshot_service.run());
         }
 
-        Ok(IndexController {
+        if let Some(interval) = self.index_rebuild_interval {
+            // The tasks of a replica are the ones of its primary.
+            if self.replicate_from.is_some() {
+                log::warn!("The index rebuilds are not scheduled on a replica.");
+            } else {
+                tokio::task::spawn_local(schedule_index_rebuilds(
+                    interval,
+                    index_resolver.clone(),
+                    task_store.clone(),
+                    scheduler.clone(),
+                ));
+            }
+        }
+
+        if let Some(trash) = trash {
+            // The purges of a replica are the ones registered by its primary.
+            if self.replicate_from.is_none() {
+                tokio::task::spawn_local(schedule_index_purges(
+                    trash,
+                    task_store.clone(),
+                    scheduler.clone(),
+                ));
+            }
+        }
+
+        let write_breaker = Arc::new(WriteBreaker::new(self.write_breaker));
+        if write_breaker.is_enabled() {
+            tokio::task::spawn_local(run_write_breaker(
+                write_breaker.clone(),
+                task_store.clone(),
+                db_path.as_ref().to_path_buf(),
+            ));
+        }
+
+        let slow_query_log = self
+            .slow_query_threshold
+            .map(|threshold| Arc::new(SlowQueryLog::new(threshold, self.slow_query_log_size)));
+
+        let search_queue = SearchQueue::new(
+            self.search_pool_size.unwrap_or_else(num_cpus::get),
+            self.search_queue_size.unwrap_or(DEFAULT_SEARCH_QUEUE_SIZE),
+        );
+
+        let primary = self
+            .replicate_from
+            .map(|(url, master_key)| Primary::new(&url, master_key))
+            .transpose()?;
+
+        let meilisearch = IndexController {
             index_resolver,
             scheduler,
             dump_handle,
             update_file_store,
             task_store,
-        })
+            slow_query_log,
+            search_queue,
+            search_timeout: self.search_timeout,
+            search_cache,
+            timeseries,
+            migrations: Migrations::default(),
+            primary,
+            templates,
+            health,
+            write_breaker,
+            read_sessions,
+        };
+
+        if let Some((dir, interval)) = self.watched_import {
+            // The documents of a replica are the ones of its primary.
+            if meilisearch.is_replica() {
+                log::warn!(
+                    "The documents files dropped in `{}` are not imported on a replica.",
+                    dir.display()
+                );
+            } else {
+                tokio::task::spawn_local(run_watched_import(meilisearch.clone(), dir, interval));
+            }
+        }
+
+        Ok(meilisearch)
     }
 
     /// Set the index controller builder's max update store size.
@@ -350,6 +717,101 @@ impl IndexControllerBuilder {
         self.ignore_missing_dump = ignore_missing_dump;
         self
     }
+
+    /// Set the index controller builder's slow query threshold.
+    pub fn set_slow_query_threshold(&mut self, slow_query_threshold: Duration) -> &mut Self {
+        self.slow_query_threshold.replace(slow_query_threshold);
+        self
+    }
+
+    /// Set the index controller builder's slow query log size.
+    pub fn set_slow_query_log_size(&mut self, slow_query_log_size: usize) -> &mut Self {
+        self.slow_query_log_size = slow_query_log_size;
+        self
+    }
+
+    /// Set the index controller builder's search pool size.
+    pub fn set_search_pool_size(&mut self, search_pool_size: usize) -> &mut Self {
+        self.search_pool_size.replace(search_pool_size);
+        self
+    }
+
+    /// Set the index controller builder's search queue size.
+    pub fn set_search_queue_size(&mut self, search_queue_size: usize) -> &mut Self {
+        self.search_queue_size.replace(search_queue_size);
+        self
+    }
+
+    /// Set the index controller builder's search timeout.
+    pub fn set_search_timeout(&mut self, search_timeout: Duration) -> &mut Self {
+        self.search_timeout.replace(search_timeout);
+        self
+    }
+
+    /// Set the index controller builder's max open indexes.
+    pub fn set_max_open_indexes(&mut self, max_open_indexes: usize) -> &mut Self {
+        self.max_open_indexes.replace(max_open_indexes);
+        self
+    }
+
+    /// Set the index controller builder's max open indexes map size.
+    pub fn set_max_open_indexes_map_size(&mut self, map_size: usize) -> &mut Self {
+        self.max_open_indexes_map_size.replace(map_size);
+        self
+    }
+
+    /// Set the index controller builder's max disk usage.
+    pub fn set_max_disk_usage(&mut self, max_disk_usage: u64) -> &mut Self {
+        self.max_disk_usage.replace(max_disk_usage);
+        self
+    }
+
+    /// Set the index controller builder's search cache size.
+    pub fn set_search_cache_size(&mut self, search_cache_size: usize) -> &mut Self {
+        self.search_cache_size = search_cache_size;
+        self
+    }
+
+    /// Set the index controller builder's primary, the instance then becomes a read-only replica
+    /// of the instance at `url`. Both instances must share the same `master_key`.
+    pub fn set_replicate_from(&mut self, url: String, master_key: String) -> &mut Self {
+        self.replicate_from.replace((url, master_key));
+        self
+    }
+
+    /// Set the index controller builder's index rebuild interval, a rebuild compacting each index
+    /// is then registered at this interval.
+    pub fn set_index_rebuild_interval(&mut self, interval: Duration) -> &mut Self {
+        self.index_rebuild_interval.replace(interval);
+        self
+    }
+
+    /// Set the index controller builder's watched import, the documents files dropped in the
+    /// subdirectories of `dir` are then imported in the index of their subdirectory each
+    /// `interval`.
+    pub fn set_watched_import(&mut self, dir: PathBuf, interval: Duration) -> &mut Self {
+        self.watched_import.replace((dir, interval));
+        self
+    }
+
+    /// Set the index controller builder's index trash retention, the deleted indexes are then
+    /// kept in the trash for this period, during which they can be restored.
+    pub fn set_index_trash_retention(&mut self, retention: Duration) -> &mut Self {
+        self.index_trash_retention.replace(retention);
+        self
+    }
+
+    /// Refuse the document writes beyond these thresholds.
+    pub fn set_write_breaker(&mut self, thresholds: WriteBreakerThresholds) -> &mut Self {
+        self.write_breaker = thresholds;
+        self
+    }
+
+    /// Set the index controller builder's replication log size.
+    pub fn set_replication_log_size(&mut self, replication_log_size: usize) -> &mut Self {
+        self.replication_log_size = replication_log_size;
+        self
+    }
 }
 
 impl<U, I> IndexController<U, I>
@@ -362,7 +824,12 @@ where
     }
 
     pub async fn register_update(&self, uid: String, update: Update) -> Result<Task> {
+        if self.is_replica() {
+            return Err(ReplicationError::ReadOnlyReplica.into());
+        }
+
         let uid = IndexUid::new(uid)?;
+        self.check_write_breaker(&update)?;
         let content = match update {
             Update::DeleteDocuments(ids) => {
                 TaskContent::DocumentDeletion(DocumentDeletion::Ids(ids))
@@ -383,43 +850,62 @@ where
                 format,
                 method,
                 allow_index_creation,
+                payload_size,
             } => {
-                let mut buffer = Vec::new();
-                while let Some(bytes) = payload.next().await {
-                    let bytes = bytes?;
-                    buffer.extend_from_slice(&bytes);
+                let disk_quota = self.index_resolver.disk_quota_usage().await?;
+                if let (Some((quota, usage)), Some(payload_size)) = (&disk_quota, payload_size) {
+                    quota.check_usage(*usage, payload_size)?;
                 }
-                let (content_uuid, mut update_file) = self.update_file_store.new_update()?;
-                let documents_count = tokio::task::spawn_blocking(move || -> Result<_> {
-                    // check if the payload is empty, and return an error
-                    if buffer.is_empty() {
-                        return Err(IndexControllerError::MissingPayload(format));
-                    }
 
-                    let reader = Cursor::new(buffer);
-                    let count = match format {
-                        DocumentAdditionFormat::Json => read_json(reader, &mut *update_file)?,
-                        DocumentAdditionFormat::Csv => read_csv(reader, &mut *update_file)?,
-                        DocumentAdditionFormat::Ndjson => read_ndjson(reader, &mut *update_file)?,
-                    };
-
-                    update_file.persist()?;
+                let (content_uuid, update_file) = self.update_file_store.new_update()?;
+                let (documents_count, update_file, size) = match format {
+                    format if format.is_streamed() => {
+                        stream_documents(payload, update_file, format, disk_quota).await?
+                    }
+                    format => {
+                        let mut buffer = Vec::new();
+                        while let Some(bytes) = payload.next().await {
+                            let bytes = bytes?;
+                            buffer.extend_from_slice(&bytes);
+                            if let Some((ref quota, usage)) = disk_quota {
+                                quota.check_usage(usage, buffer.len() as u64)?;
+                            }
+                        }
+                        if buffer.is_empty() {
+                            return Err(IndexControllerError::MissingPayload(format));
+                        }
+                        let size = buffer.len() as u64;
+                        let mut update_file = update_file;
+                        let (count, update_file) = spawn_blocking(move || -> Result<_> {
+                            let reader = Cursor::new(buffer);
+                            let count = format.read_documents(reader, &mut *update_file)?;
+                            Ok((count, update_file))
+                        })
+                        .await??;
+                        (count, update_file, size)
+                    }
+                };
 
-                    Ok(count)
-                })
-                .await??;
+                self.index_resolver.check_disk_quota(size).await?;
+                spawn_blocking(move || update_file.persist()).await??;
 
                 TaskContent::DocumentAddition {
                     content_uuid,
                     merge_strategy: method,
                     primary_key,
                     documents_count,
+                    payload_size: size,
                     allow_index_creation,
                 }
             }
             Update::DeleteIndex => TaskContent::IndexDeletion,
             Update::CreateIndex { primary_key } => TaskContent::IndexCreation { primary_key },
             Update::UpdateIndex { primary_key } => TaskContent::IndexUpdate { primary_key },
+            Update::RenameIndex { new_uid } => TaskContent::IndexRename {
+                new_uid: IndexUid::new(new_uid)?,
+            },
+            Update::RebuildIndex { reindex } => TaskContent::IndexRebuild { reindex },
+            Update::RestoreIndex => TaskContent::IndexRestore,
         };
 
         let task = self.task_store.register(uid, content).await?;
@@ -498,10 +984,102 @@ where
         Ok(tasks)
     }
 
+    pub async fn indexing_settings(&self) -> IndexingSettings {
+        self.scheduler.read().await.indexing_settings()
+    }
+
+    /// Updates the auto-batching parameters of the scheduler, the new parameters are used from
+    /// the next prepared batch.
+    pub async fn update_indexing_settings(
+        &self,
+        update: IndexingSettingsUpdate,
+    ) -> IndexingSettings {
+        self.scheduler
+            .write()
+            .await
+            .update_indexing_settings(update)
+    }
+
+    pub async fn list_index_templates(&self) -> Result<Vec<IndexTemplate>> {
+        Ok(self.templates.list_templates().await?)
+    }
+
+    pub async fn get_index_template(&self, name: String) -> Result<IndexTemplate> {
+        Ok(self.templates.get_template(name).await?)
+    }
+
+    /// Creates or replaces the template `template.name`, the templates are only applied to the
+    /// indexes created after them.
+    pub async fn put_index_template(&self, template: IndexTemplate) -> Result<IndexTemplate> {
+        Ok(self.templates.put_template(template).await?)
+    }
+
+    pub async fn delete_index_template(&self, name: String) -> Result<IndexTemplate> {
+        Ok(self.templates.delete_template(name).await?)
+    }
+
+    /// Returns a stream of the changes of the tasks matching `filter`, starting now.
+    pub fn watch_tasks(
+        &self,
+        filter: Option<TaskFilter>,
+    ) -> impl Stream<Item = TaskProgress> + 'static {
+        let mut receiver = self.task_store.watcher().subscribe();
+        stream! {
+            loop {
+                match receiver.recv().await {
+                    Ok(progress) => {
+                        if filter.as_ref().map_or(true, |f| f.matches(&progress.task)) {
+                            yield progress;
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        log::warn!("a task watcher missed {} task changes", missed);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+
+    /// Returns a stream starting with the current state of the task, followed by its changes until
+    /// it is finished.
+    pub async fn watch_task(
+        &self,
+        id: TaskId,
+        filter: Option<TaskFilter>,
+    ) -> Result<impl Stream<Item = TaskProgress> + 'static> {
+        // Subscribe before fetching the task so no change is missed in between.
+        let mut filter_id = TaskFilter::default();
+        filter_id.filter_fn(move |task| task.id == id);
+        let changes = self.watch_tasks(Some(filter_id));
+        let task = self.get_task(id, filter).await?;
+
+        Ok(stream! {
+            let finished = task.is_finished();
+            let percentage = if finished { 100 } else { 0 };
+            yield TaskProgress { task, percentage };
+
+            if !finished {
+                for await progress in changes {
+                    let finished = progress.task.is_finished();
+                    yield progress;
+                    if finished {
+                        break;
+                    }
+                }
+            }
+        })
+    }
+
     pub async fn list_indexes(&self) -> Result<Vec<IndexMetadata>> {
-        let indexes = self.index_resolver.list().await?;
         let mut ret = Vec::new();
-        for (uid, index) in indexes {
+        // The indexes are opened one at a time, so that the least recently used ones can be
+        // closed in between.
+        for uid in self.index_resolver.list().await? {
+            let index = match self.index_resolver.find_index(uid.clone()).await? {
+                Some(index) => index,
+                None => continue,
+            };
             let meta = index.meta()?;
             let meta = IndexMetadata {
                 uuid: index.uuid(),
@@ -535,6 +1113,54 @@ where
         Ok(documents)
     }
 
+    /// Opens a read session on the index `uid`: the documents read from it are the ones of the
+    /// index at the time it was opened, whatever the updates processed since then. The session
+    /// expires once it hasn't been read for `ttl`.
+    pub async fn open_read_session(
+        &self,
+        uid: String,
+        ttl: Option<Duration>,
+    ) -> Result<ReadSessionView> {
+        let ttl = ttl.unwrap_or(DEFAULT_READ_SESSION_TTL);
+        if ttl.is_zero() || ttl > MAX_READ_SESSION_TTL {
+            return Err(IndexControllerError::InvalidReadSessionTtl(
+                MAX_READ_SESSION_TTL.as_secs(),
+            ));
+        }
+
+        let index = self.index_resolver.get_index(uid.clone()).await?;
+        let snapshot = spawn_blocking(move || index.open_read_snapshot(ttl)).await??;
+        self.read_sessions
+            .insert(uid, snapshot, ttl)
+            .ok_or(IndexControllerError::TooManyReadSessions)
+    }
+
+    /// Reads a page of the documents of the read session `token` of the index `uid`.
+    pub async fn read_session_documents(
+        &self,
+        uid: String,
+        token: String,
+        offset: usize,
+        limit: usize,
+        attributes_to_retrieve: Option<Vec<String>>,
+    ) -> Result<Vec<Document>> {
+        let not_found = || IndexControllerError::ReadSessionNotFound(token.clone());
+        let snapshot = self.read_sessions.get(&uid, &token).ok_or_else(not_found)?;
+        let documents = snapshot
+            .documents(offset, limit, attributes_to_retrieve)
+            .await
+            .ok_or_else(not_found)??;
+        Ok(documents)
+    }
+
+    pub fn close_read_session(&self, uid: &str, token: &str) -> Result<()> {
+        if self.read_sessions.remove(uid, token) {
+            Ok(())
+        } else {
+            Err(IndexControllerError::ReadSessionNotFound(token.to_string()))
+        }
+    }
+
     pub async fn document(
         &self,
         uid: String,
@@ -548,12 +1174,191 @@ where
         Ok(document)
     }
 
-    pub async fn search(&self, uid: String, query: SearchQuery) -> Result<SearchResult> {
+    /// Performs a search on the index `uid`, once a search worker is available.
+    ///
+    /// `requester` identifies the client performing the search, the waiting searches of
+    /// the different requesters are processed in turn. The searches found in the search cache
+    /// are answered right away.
+    pub async fn search(
+        &self,
+        uid: String,
+        mut query: SearchQuery,
+        requester: &str,
+    ) -> Result<SearchResult> {
+        let before_search = Instant::now();
+        let index = self.index_resolver.get_index(uid.clone()).await?;
+
+        // The ruleset is drawn before looking up the cache, so that the cached results of a
+        // ruleset are only served to the share of the traffic assigned to it.
+        if query.ruleset.is_none() {
+            let index = index.clone();
+            query.ruleset = spawn_blocking(move || index.assign_ruleset()).await??;
+        }
+        if query.timeout_ms.is_none() {
+            query.timeout_ms = self
+                .search_timeout
+                .map(|timeout| timeout.as_millis() as u64);
+        }
+
+        // The generation is read before the search so that a result computed concurrently with
+        // a write isn't cached.
+        let cache_entry = self
+            .search_cache
+            .as_ref()
+            .map(|cache| (cache, CacheKey::new(&uid, &query), cache.generation(&uid)));
+
+        if let Some((cache, key, _)) = &cache_entry {
+            if let Some(mut result) = cache.get(key) {
+                result.processing_time_ms = before_search.elapsed().as_millis();
+                result.queue_wait_ms = 0;
+                self.timeseries.record_search(&uid);
+                return Ok(result);
+            }
+        }
+
+        let logged_query = self.slow_query_log.as_ref().map(|_| query.clone());
+
+        let permit = self.search_queue.acquire(requester).await?;
+        let queue_wait_ms = before_search.elapsed().as_millis();
+        let mut result = spawn_blocking(move || index.perform_search(query)).await??;
+        drop(permit);
+        result.queue_wait_ms = queue_wait_ms;
+
+        if let Some((log, query)) = self.slow_query_log.as_ref().zip(logged_query) {
+            log.observe(&uid, query, before_search.elapsed(), &result.timings);
+        }
+        // A degraded result depends on the load of the instance, it isn't reused.
+        if let Some((cache, key, generation)) = cache_entry.filter(|_| !result.degraded) {
+            cache.insert(key, generation, result.clone());
+        }
+        self.timeseries.record_search(&uid);
+
+        Ok(result)
+    }
+
+    pub async fn suggest(&self, uid: String, query: SuggestQuery) -> Result<SuggestResult> {
         let index = self.index_resolver.get_index(uid).await?;
-        let result = spawn_blocking(move || index.perform_search(query)).await??;
+        let result = spawn_blocking(move || index.suggest(query)).await??;
         Ok(result)
     }
 
+    /// Exports the documents of the index `uid` matching the filter of the query, as a stream of
+    /// serialized chunks. At most `EXPORT_BUFFERED_CHUNKS` chunks are buffered: the export waits
+    /// for the stream to be consumed, and stops once it is dropped. The errors found before the
+    /// first chunk, such as an invalid filter, are returned instead of the stream.
+    pub async fn export_documents(
+        &self,
+        uid: String,
+        query: ExportQuery,
+    ) -> Result<impl Stream<Item = Result<Bytes>> + 'static> {
+        let index = self.index_resolver.get_index(uid).await?;
+        let (sender, mut receiver) = mpsc::channel(EXPORT_BUFFERED_CHUNKS);
+
+        let chunks = sender.clone();
+        let export = spawn_blocking(move || {
+            index.export(query, |chunk| {
+                chunks.blocking_send(Ok(Bytes::from(chunk))).is_ok()
+            })
+        });
+        tokio::spawn(async move {
+            let error = match export.await {
+                Ok(Ok(())) => return,
+                Ok(Err(e)) => IndexControllerError::from(e),
+                Err(e) => IndexControllerError::from(e),
+            };
+            let _ = sender.send(Err(error)).await;
+        });
+
+        let first = receiver.recv().await.transpose()?;
+        Ok(stream! {
+            if let Some(first) = first {
+                yield Ok(first);
+                while let Some(chunk) = receiver.recv().await {
+                    yield chunk;
+                }
+            }
+        })
+    }
+
+    /// Returns the hit and miss counters of the search cache, which are all 0 when it is
+    /// disabled.
+    pub fn search_cache_stats(&self) -> SearchCacheStats {
+        self.search_cache
+            .as_ref()
+            .map(|cache| cache.stats())
+            .unwrap_or_default()
+    }
+
+    /// Returns the activity of the authorized indexes over the last `window` seconds, aggregated
+    /// by `interval` seconds.
+    pub fn timeseries(
+        &self,
+        window: i64,
+        interval: i64,
+        search_rules: &SearchRules,
+    ) -> Result<BTreeMap<String, Vec<TimeseriesPoint>>> {
+        self.timeseries.query(window, interval, |index_uid| {
+            search_rules.is_index_authorized(index_uid)
+        })
+    }
+
+    /// Returns an error when the write breaker refuses the document additions, or deletions.
+    pub fn check_document_write(&self, is_deletion: bool) -> Result<()> {
+        self.write_breaker
+            .check(is_deletion)
+            .map_err(IndexControllerError::WritesSuspended)
+    }
+
+    fn check_write_breaker(&self, update: &Update) -> Result<()> {
+        match update {
+            Update::DeleteDocuments(_) | Update::ClearDocuments => self.check_document_write(true),
+            Update::DocumentAddition { .. } => self.check_document_write(false),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn write_breaker_state(&self) -> WriteBreakerState {
+        self.write_breaker.state()
+    }
+
+    /// Returns the state of the task queue and of the database, dumps and snapshots.
+    pub async fn health(&self) -> Result<Health> {
+        let mut filter = TaskFilter::default();
+        filter.filter_fn(|task| !task.is_finished());
+        let unfinished = self.task_store.list_tasks(None, Some(filter), None).await?;
+        let task_queue = self
+            .health
+            .task_queue(&unfinished, OffsetDateTime::now_utc());
+
+        // Opening a write transaction waits for the one of the scheduler to be committed.
+        let database_writable = matches!(
+            tokio::time::timeout(WRITE_CHECK_TIMEOUT, self.task_store.next_task_id()).await,
+            Ok(Ok(_))
+        );
+
+        let probe = self.health.clone();
+        let (last_snapshot_at, last_dump_at, disk) =
+            spawn_blocking(move || (probe.last_snapshot_at(), probe.last_dump_at(), probe.disk()))
+                .await?;
+
+        Ok(Health {
+            task_queue,
+            last_snapshot_at,
+            last_dump_at,
+            database_writable,
+            disk,
+            write_breaker: self.write_breaker.state(),
+        })
+    }
+
+    /// Returns the searches recorded by the slow query log, the most recent first.
+    pub fn slow_queries(&self) -> Vec<SlowQuery> {
+        self.slow_query_log
+            .as_ref()
+            .map(|log| log.list())
+            .unwrap_or_default()
+    }
+
     pub async fn get_index(&self, uid: String) -> Result<IndexMetadata> {
         let index = self.index_resolver.get_index(uid.clone()).await?;
         let uuid = index.uuid();
@@ -575,9 +1380,25 @@ where
             .map(|task| task.index_uid.as_str() == uid)
             .unwrap_or_default();
 
-        let index = self.index_resolver.get_index(uid).await?;
-        let mut stats = spawn_blocking(move || index.stats()).await??;
+        let index = self.index_resolver.get_index(uid.clone()).await?;
+        let mut stats = spawn_blocking(move || -> IndexResult<_> {
+            let mut stats = index.stats()?;
+            stats.storage = Some(index.storage_stats()?);
+            Ok(stats)
+        })
+        .await??;
         stats.is_indexing = Some(is_indexing);
+        stats.last_applied_task =
+            self.task_store
+                .last_succeeded_task(uid)
+                .await?
+                .and_then(|task| match task.events.last() {
+                    Some(TaskEvent::Succeded { timestamp, .. }) => Some(AppliedTask {
+                        uid: task.id,
+                        finished_at: *timestamp,
+                    }),
+                    _ => None,
+                });
 
         Ok(stats)
     }
@@ -588,10 +1409,14 @@ where
         let mut database_size = 0;
         let processing_tasks = self.scheduler.read().await.get_processing_tasks().await?;
 
-        for (index_uid, index) in self.index_resolver.list().await? {
+        for index_uid in self.index_resolver.list().await? {
             if !search_rules.is_index_authorized(&index_uid) {
                 continue;
             }
+            let index = match self.index_resolver.find_index(index_uid.clone()).await? {
+                Some(index) => index,
+                None => continue,
+            };
 
             let (mut stats, meta) =
                 spawn_blocking::<_, Result<(IndexStats, IndexMeta)>>(move || {
@@ -621,6 +1446,231 @@ where
         })
     }
 
+    /// Starts importing an index from another search engine, the migration runs in the background.
+    pub fn create_migration(&self, migration: Migration) -> Result<MigrationInfo>
+    where
+        U: 'static,
+        I: 'static,
+    {
+        IndexUid::new(migration.index_uid.clone())?;
+        migration.validate()?;
+
+        let info = MigrationInfo::new(Uuid::new_v4().to_string(), &migration);
+        self.migrations.insert(info.clone());
+
+        let controller = self.clone();
+        let uid = info.uid.clone();
+        tokio::task::spawn_local(async move {
+            let result = controller.run_migration(&uid, migration).await;
+            controller.migrations.update(&uid, |info| match result {
+                Ok(()) => info.done(),
+                Err(e) => {
+                    log::error!("Migration `{}` failed: {}", info.uid, e);
+                    info.with_error(e.to_string());
+                }
+            });
+        });
+
+        Ok(info)
+    }
+
+    /// Registers the settings of the source, then a document addition per batch of documents.
+    async fn run_migration(&self, uid: &str, migration: Migration) -> Result<()> {
+        let Migration {
+            source,
+            index_uid,
+            primary_key,
+            batch_size,
+        } = migration;
+        let mut source = source.connect(batch_size)?;
+        let primary_key = primary_key.unwrap_or_else(|| source.primary_key().to_string());
+
+        let settings = source.settings().await?;
+        let update = Update::Settings {
+            settings,
+            is_deletion: false,
+            allow_index_creation: true,
+        };
+        let task = self.register_update(index_uid.clone(), update).await?;
+        self.migrations
+            .update(uid, |info| info.task_uids.push(task.id));
+
+        while let Some(documents) = source.next_batch(&primary_key).await? {
+            if documents.is_empty() {
+                continue;
+            }
+
+            let count = documents.len();
+            let content = serde_json::to_vec(&documents)
+                .map_err(|e| IndexControllerError::Internal(Box::new(e)))?;
+            let payload_size = Some(content.len() as u64);
+            let update = Update::DocumentAddition {
+                payload: Box::new(futures::stream::once(futures::future::ready(Ok::<
+                    _,
+                    PayloadError,
+                >(
+                    Bytes::from(content),
+                )))),
+                primary_key: Some(primary_key.clone()),
+                method: IndexDocumentsMethod::ReplaceDocuments,
+                format: DocumentAdditionFormat::Json,
+                allow_index_creation: true,
+                payload_size,
+            };
+            let task = self.register_update(index_uid.clone(), update).await?;
+
+            let total_documents = source.total_documents();
+            self.migrations.update(uid, |info| {
+                info.documents_fetched += count;
+                info.total_documents = total_documents;
+                info.task_uids.push(task.id);
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn migration_info(&self, uid: &str) -> Result<MigrationInfo> {
+        Ok(self.migrations.get(uid)?)
+    }
+
+    pub fn list_migrations(&self) -> Vec<MigrationInfo> {
+        self.migrations.list()
+    }
+
+    /// Returns whether the instance is a read-only replica of another instance.
+    pub fn is_replica(&self) -> bool {
+        self.primary.is_some()
+    }
+
+    /// Returns the tasks registered since `from`, the oldest first, for the replicas.
+    pub async fn replication_tasks(
+        &self,
+        from: TaskId,
+        limit: usize,
+    ) -> Result<Vec<ReplicatedTask>> {
+        let mut tasks = self
+            .task_store
+            .list_tasks(Some(from), None, Some(limit))
+            .await?;
+        // The tasks are listed from the most recent.
+        tasks.reverse();
+        Ok(tasks.into_iter().map(ReplicatedTask::from).collect())
+    }
+
+    /// Returns the documents of a document addition as NDJSON, for the replicas. They are only
+    /// available until the update file of the task is deleted.
+    pub async fn replication_content(&self, uid: TaskId) -> Result<Vec<u8>> {
+        let task = self.task_store.get_task(uid, None).await?;
+        let content_uuid = task
+            .get_content_uuid()
+            .filter(|uuid| self.update_file_store.get_size(*uuid).is_ok())
+            .ok_or(ReplicationError::ContentUnavailable(uid))?;
+
+        let update_file_store = self.update_file_store.clone();
+        let content = spawn_blocking(move || -> Result<_> {
+            let mut content = Vec::new();
+            update_file_store.write_ndjson(content_uuid, &mut content)?;
+            Ok(content)
+        })
+        .await??;
+
+        Ok(content)
+    }
+
+    /// Starts following the primary in the background when the instance is a replica. The API
+    /// keys of the primary are replicated in `auth`.
+    pub fn start_replication(&self, auth: AuthController)
+    where
+        U: 'static,
+        I: 'static,
+    {
+        let primary = match self.primary {
+            Some(ref primary) => primary.clone(),
+            None => return,
+        };
+
+        let controller = self.clone();
+        tokio::task::spawn_local(async move {
+            loop {
+                match controller.replicate(&primary, &auth).await {
+                    // More tasks are waiting on the primary.
+                    Ok(true) => continue,
+                    Ok(false) => (),
+                    Err(e) => log::error!("Replication of the primary failed: {}", e),
+                }
+                sleep(POLL_INTERVAL).await;
+            }
+        });
+    }
+
+    /// Starts sending the usage digests of the API keys to their webhooks. The replicas, which
+    /// copy the keys of their primary, leave the digests to it.
+    pub fn start_usage_digests(&self, auth: AuthController) {
+        if self.primary.is_none() {
+            tokio::task::spawn_local(run_usage_digests(auth));
+        }
+    }
+
+    /// Replicates the API keys, then registers the next tasks of the primary. Returns whether
+    /// more tasks are waiting on the primary.
+    ///
+    /// The tasks of the primary are fetched from the uid of the next local task, a replica must
+    /// thus start from an empty database or from a snapshot of its primary.
+    async fn replicate(&self, primary: &Primary, auth: &AuthController) -> Result<bool> {
+        let keys = primary.keys().await?;
+        let auth = auth.clone();
+        spawn_blocking(move || auth.replicate_keys(keys))
+            .await?
+            .map_err(|e| ReplicationError::KeyReplication(e.to_string()))?;
+
+        let from = self.task_store.next_task_id().await?;
+        let tasks = primary.tasks(from).await?;
+        let more = tasks.len() == REPLICATION_BATCH_SIZE;
+        for task in tasks {
+            self.replicate_task(primary, task).await?;
+        }
+
+        Ok(more)
+    }
+
+    async fn replicate_task(&self, primary: &Primary, task: ReplicatedTask) -> Result<Task> {
+        let content = match task.content {
+            TaskContent::DocumentAddition {
+                merge_strategy,
+                primary_key,
+                documents_count,
+                payload_size,
+                allow_index_creation,
+                ..
+            } => {
+                let documents = primary.content(task.uid).await?;
+                let (content_uuid, mut update_file) = self.update_file_store.new_update()?;
+                spawn_blocking(move || -> Result<_> {
+                    read_ndjson(Cursor::new(documents), &mut *update_file)?;
+                    update_file.persist()?;
+                    Ok(())
+                })
+                .await??;
+
+                TaskContent::DocumentAddition {
+                    content_uuid,
+                    merge_strategy,
+                    primary_key,
+                    documents_count,
+                    payload_size,
+                    allow_index_creation,
+                }
+            }
+            content => content,
+        };
+
+        let task = self.task_store.register(task.index_uid, content).await?;
+        self.scheduler.read().await.notify();
+
+        Ok(task)
+    }
+
     pub async fn create_dump(&self) -> Result<DumpInfo> {
         Ok(self.dump_handle.create_dump().await?)
     }
@@ -651,9 +1701,7 @@ mod test {
 
     use crate::index::error::Result as IndexResult;
     use crate::index::Index;
-    use crate::index::{
-        default_crop_marker, default_highlight_post_tag, default_highlight_pre_tag,
-    };
+    use crate::index::SearchTimings;
     use crate::index_resolver::index_store::MockIndexStore;
     use crate::index_resolver::meta_store::MockIndexMetaStore;
     use crate::index_resolver::IndexResolver;
@@ -674,10 +1722,28 @@ mod test {
                 dump_handle,
                 update_file_store,
                 scheduler,
+                slow_query_log: None,
+                search_queue: SearchQueue::new(1, DEFAULT_SEARCH_QUEUE_SIZE),
+                search_timeout: None,
+                search_cache: None,
+                timeseries: Arc::new(Timeseries::default()),
+                migrations: Migrations::default(),
+                primary: None,
+                templates: mock_templates(),
+                health: HealthProbe::new("data.ms".into(), "dumps".into(), None),
+                write_breaker: Arc::new(WriteBreaker::new(Default::default())),
+                read_sessions: Arc::default(),
             }
         }
     }
 
+    fn mock_templates() -> HeedTemplateStore {
+        // the directory is kept, so that the environment stays usable for the whole test.
+        let path = tempfile::tempdir().unwrap().into_path();
+        let env = open_meta_env(&path, 4096 * 1000).unwrap();
+        HeedTemplateStore::new(Arc::new(env)).unwrap()
+    }
+
     #[actix_rt::test]
     async fn test_search_simple() {
         let index_uid = "test";
@@ -685,18 +1751,31 @@ mod test {
         let query = SearchQuery {
             q: Some(String::from("hello world")),
             offset: Some(10),
-            limit: 0,
+            limit: Some(0),
+            page: None,
+            hits_per_page: None,
             attributes_to_retrieve: Some(vec!["string".to_owned()].into_iter().collect()),
             attributes_to_crop: None,
-            crop_length: 18,
+            crop_length: Some(18),
             attributes_to_highlight: None,
-            matches: true,
+            matches: Some(true),
             filter: None,
             sort: None,
             facets_distribution: None,
-            highlight_pre_tag: default_highlight_pre_tag(),
-            highlight_post_tag: default_highlight_post_tag(),
-            crop_marker: default_crop_marker(),
+            distinct: None,
+            matching_strategy: None,
+            locales: None,
+            highlight_pre_tag: None,
+            highlight_post_tag: None,
+            crop_marker: None,
+            show_ranking_score: None,
+            show_ranking_score_details: None,
+            ruleset: None,
+            boost: None,
+            boost_filter: None,
+            boost_filter_weight: None,
+            timeout_ms: None,
+            retrievable_attributes: None,
         };
 
         let result = SearchResult {
@@ -706,9 +1785,17 @@ mod test {
             query: "hello world".to_string(),
             limit: 24,
             offset: 0,
+            hits_per_page: None,
+            page: None,
+            total_pages: None,
+            total_hits: None,
             processing_time_ms: 50,
+            queue_wait_ms: 0,
             facets_distribution: None,
             exhaustive_facets_count: Some(true),
+            ruleset: None,
+            degraded: false,
+            timings: SearchTimings::default(),
         };
 
         let mut uuid_store = MockIndexMetaStore::new();
@@ -735,6 +1822,10 @@ mod test {
                 let result = result_clone.clone();
                 let query = query_clone.clone();
                 let mocker = Mocker::default();
+                mocker
+                    .when::<(), IndexResult<Option<String>>>("assign_ruleset")
+                    .once()
+                    .then(|()| Ok(None));
                 mocker
                     .when::<SearchQuery, IndexResult<SearchResult>>("perform_search")
                     .once()
@@ -771,10 +1862,12 @@ mod test {
             scheduler,
         );
 
-        let r = index_controller
-            .search(index_uid.to_owned(), query.clone())
+        let mut r = index_controller
+            .search(index_uid.to_owned(), query.clone(), "")
             .await
             .unwrap();
+        // the queue wait time depends on the scheduling of the test.
+        r.queue_wait_ms = 0;
         assert_eq!(r, result);
     }
 }
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::index::ReadSnapshot;
+
+/// The time to live of a read session that doesn't set one.
+pub const DEFAULT_READ_SESSION_TTL: Duration = Duration::from_secs(60);
+/// The longest time to live of a read session. The pages freed by the updates can't be reused
+/// while a session is open, so they can't be kept for long.
+pub const MAX_READ_SESSION_TTL: Duration = Duration::from_secs(600);
+/// The number of read sessions open at the same time, each of them holding a thread.
+pub const MAX_READ_SESSIONS: usize = 32;
+
+/// A read session, as returned when it is opened.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadSessionView {
+    /// The token to pass to the documents route to read from the session.
+    pub token: String,
+    pub index_uid: String,
+    /// The number of documents of the index when the session was opened.
+    pub number_of_documents: u64,
+    /// The session expires after this number of seconds without being read.
+    pub ttl: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+struct ReadSession {
+    index_uid: String,
+    snapshot: ReadSnapshot,
+    ttl: Duration,
+    last_read: Instant,
+}
+
+impl ReadSession {
+    fn is_expired(&self, now: Instant) -> bool {
+        now.duration_since(self.last_read) >= self.ttl
+    }
+}
+
+/// The read sessions of the instance, by token. A session reads from a snapshot of its index,
+/// so that the pages of a paginated read are consistent with one another while the index is
+/// updated.
+#[derive(Default)]
+pub struct ReadSessions {
+    sessions: Mutex<HashMap<String, ReadSession>>,
+}
+
+impl ReadSessions {
+    /// Registers a session on the snapshot, returns `None` if there are already
+    /// `MAX_READ_SESSIONS` sessions open.
+    pub fn insert(
+        &self,
+        index_uid: String,
+        snapshot: ReadSnapshot,
+        ttl: Duration,
+    ) -> Option<ReadSessionView> {
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock();
+        sessions.retain(|_, session| !session.is_expired(now));
+        if sessions.len() >= MAX_READ_SESSIONS {
+            return None;
+        }
+
+        let token = Uuid::new_v4().to_simple().to_string();
+        let view = ReadSessionView {
+            token: token.clone(),
+            index_uid: index_uid.clone(),
+            number_of_documents: snapshot.number_of_documents,
+            ttl: ttl.as_secs(),
+            expires_at: OffsetDateTime::now_utc() + ttl,
+        };
+        sessions.insert(
+            token,
+            ReadSession {
+                index_uid,
+                snapshot,
+                ttl,
+                last_read: now,
+            },
+        );
+
+        Some(view)
+    }
+
+    /// Returns the snapshot of the session of the index and postpones its expiry, `None` if there
+    /// is no such session or if it expired.
+    pub fn get(&self, index_uid: &str, token: &str) -> Option<ReadSnapshot> {
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock();
+        let session = sessions
+            .get_mut(token)
+            .filter(|session| session.index_uid == index_uid)?;
+        if session.is_expired(now) {
+            sessions.remove(token);
+            return None;
+        }
+
+        session.last_read = now;
+        Some(session.snapshot.clone())
+    }
+
+    /// Closes all the sessions of the index, so that their snapshots release it.
+    pub fn close_index(&self, index_uid: &str) {
+        self.sessions
+            .lock()
+            .retain(|_, session| session.index_uid != index_uid);
+    }
+
+    /// Closes the session of the index, returns whether it was open.
+    pub fn remove(&self, index_uid: &str, token: &str) -> bool {
+        let mut sessions = self.sessions.lock();
+        let is_session_of_index = sessions
+            .get(token)
+            .map_or(false, |session| session.index_uid == index_uid);
+        is_session_of_index
+            && sessions
+                .remove(token)
+                .map_or(false, |session| !session.is_expired(Instant::now()))
+    }
+}
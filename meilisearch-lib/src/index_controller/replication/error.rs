@@ -0,0 +1,41 @@
+use meilisearch_error::{Code, ErrorCode};
+
+use crate::tasks::task::TaskId;
+
+pub type Result<T> = std::result::Result<T, ReplicationError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplicationError {
+    #[error("This instance is a read-only replica, the writes must be sent to its primary.")]
+    ReadOnlyReplica,
+    #[error("Invalid primary url `{0}`.")]
+    InvalidPrimary(String),
+    #[error("The primary could not be reached: {0}.")]
+    PrimaryUnreachable(String),
+    #[error("The primary answered with an error: {0}.")]
+    PrimaryError(String),
+    #[error("The content of task `{0}` is not available anymore. The replicas lagging behind must be recreated from a snapshot of the primary.")]
+    ContentUnavailable(TaskId),
+    #[error("The API keys of the primary could not be replicated: {0}.")]
+    KeyReplication(String),
+}
+
+impl From<reqwest::Error> for ReplicationError {
+    fn from(other: reqwest::Error) -> Self {
+        Self::PrimaryUnreachable(other.to_string())
+    }
+}
+
+impl ErrorCode for ReplicationError {
+    fn error_code(&self) -> Code {
+        match self {
+            ReplicationError::ReadOnlyReplica => Code::ReadOnlyReplica,
+            ReplicationError::ContentUnavailable(_) => Code::ReplicationContentUnavailable,
+            // These errors only happen while the replica follows its primary, they are logged.
+            ReplicationError::InvalidPrimary(_)
+            | ReplicationError::PrimaryUnreachable(_)
+            | ReplicationError::PrimaryError(_)
+            | ReplicationError::KeyReplication(_) => Code::Internal,
+        }
+    }
+}
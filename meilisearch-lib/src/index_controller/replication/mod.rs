@@ -0,0 +1,234 @@
+//! Replication of an instance, the primary, by read-only replicas.
+//!
+//! A replica polls the task queue of the primary over HTTP and registers the same tasks in the
+//! same order, so the task uids of both instances match. The documents of the document additions
+//! are fetched separately, as NDJSON. The API keys of the primary are copied at each poll: since
+//! a replica shares the master key of its primary, it accepts the same API keys.
+//!
+//! The primary keeps the update files of its last processed tasks, so that a replica lagging
+//! behind can still fetch their documents.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use meilisearch_auth::Key;
+use parking_lot::Mutex;
+use reqwest::{Client, RequestBuilder, Response, Url};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::index_resolver::IndexUid;
+use crate::tasks::task::{Task, TaskContent, TaskId};
+use crate::tasks::TaskStore;
+use crate::update_file_store::UpdateFileStore;
+use error::{ReplicationError, Result};
+
+pub mod error;
+
+/// The maximum number of tasks a replica fetches from its primary in a single request.
+pub const REPLICATION_BATCH_SIZE: usize = 100;
+/// How long a replica waits before polling its primary again once it caught up.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A task of the primary, as sent to its replicas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicatedTask {
+    pub uid: TaskId,
+    pub index_uid: IndexUid,
+    pub content: TaskContent,
+}
+
+impl From<Task> for ReplicatedTask {
+    fn from(task: Task) -> Self {
+        Self {
+            uid: task.id,
+            index_uid: task.index_uid,
+            content: task.content,
+        }
+    }
+}
+
+/// The primary a replica follows.
+#[derive(Debug, Clone)]
+pub struct Primary {
+    client: Client,
+    url: Url,
+    /// The master key, shared by the primary and its replicas.
+    master_key: String,
+}
+
+impl Primary {
+    pub fn new(url: &str, master_key: String) -> Result<Self> {
+        let url = Url::parse(url).map_err(|_| ReplicationError::InvalidPrimary(url.to_string()))?;
+        if url.cannot_be_a_base() {
+            return Err(ReplicationError::InvalidPrimary(url.to_string()));
+        }
+        let client = Client::builder()
+            .user_agent(concat!("Meilisearch/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+
+        Ok(Self {
+            client,
+            url,
+            master_key,
+        })
+    }
+
+    /// Returns the tasks of the primary starting at `from`, in order.
+    pub async fn tasks(&self, from: TaskId) -> Result<Vec<ReplicatedTask>> {
+        let request = self
+            .get(&["replication", "tasks"])
+            .query(&[("from", from), ("limit", REPLICATION_BATCH_SIZE as u64)]);
+        Ok(send(request).await?.json().await?)
+    }
+
+    /// Returns the documents of a document addition, as NDJSON.
+    pub async fn content(&self, uid: TaskId) -> Result<Bytes> {
+        let request = self.get(&["replication", "tasks", &uid.to_string(), "content"]);
+        match send(request).await {
+            Ok(response) => Ok(response.bytes().await?),
+            Err(ReplicationError::PrimaryError(e)) if e.starts_with("410") => {
+                Err(ReplicationError::ContentUnavailable(uid))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns all the API keys of the primary.
+    pub async fn keys(&self) -> Result<Vec<Key>> {
+        Ok(send(self.get(&["replication", "keys"]))
+            .await?
+            .json()
+            .await?)
+    }
+
+    fn get(&self, segments: &[&str]) -> RequestBuilder {
+        let mut url = self.url.clone();
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.pop_if_empty().extend(segments);
+        }
+        self.client.get(url).bearer_auth(&self.master_key)
+    }
+}
+
+async fn send(request: RequestBuilder) -> Result<Response> {
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let message = response.text().await.unwrap_or_default();
+        return Err(ReplicationError::PrimaryError(format!(
+            "{} {}",
+            status.as_u16(),
+            message
+        )));
+    }
+
+    Ok(response)
+}
+
+/// The update files of the last processed tasks, kept on the primary for its replicas instead of
+/// being deleted right away.
+pub struct RetainedUpdateFiles {
+    capacity: usize,
+    /// The oldest update file first.
+    files: Mutex<VecDeque<Uuid>>,
+}
+
+impl RetainedUpdateFiles {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            files: Mutex::default(),
+        }
+    }
+
+    /// Keeps the update file of a processed task, and returns the oldest one when more than
+    /// `capacity` files are kept. The returned file must be deleted.
+    pub fn retain(&self, uuid: Uuid) -> Option<Uuid> {
+        let mut files = self.files.lock();
+        files.push_back(uuid);
+        if files.len() > self.capacity {
+            files.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Registers the update files kept before the instance restarted, the oldest first, and
+    /// returns the ones exceeding `capacity`. The returned files must be deleted.
+    pub fn recover(&self, uuids: impl IntoIterator<Item = Uuid>) -> Vec<Uuid> {
+        let mut files = self.files.lock();
+        let mut recovered: VecDeque<_> = uuids
+            .into_iter()
+            .filter(|uuid| !files.contains(uuid))
+            .collect();
+        recovered.append(&mut files);
+
+        let excess = recovered.len().saturating_sub(self.capacity);
+        let evicted = recovered.drain(..excess).collect();
+        *files = recovered;
+        evicted
+    }
+}
+
+/// Registers the update files of the processed tasks that were kept before the instance
+/// restarted, and deletes the ones exceeding the capacity of `retained`.
+pub(crate) async fn recover_update_files(
+    retained: Arc<RetainedUpdateFiles>,
+    task_store: TaskStore,
+    update_file_store: UpdateFileStore,
+) {
+    let tasks = match task_store.list_tasks(None, None, None).await {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            log::error!(
+                "Could not recover the update files kept for the replicas: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    // The tasks are listed from the most recent.
+    let uuids: Vec<_> = tasks
+        .into_iter()
+        .rev()
+        .filter(|task| task.is_finished())
+        .filter_map(|task| task.get_content_uuid())
+        .filter(|uuid| update_file_store.get_size(*uuid).is_ok())
+        .collect();
+    for uuid in retained.recover(uuids) {
+        if let Err(e) = update_file_store.delete(uuid).await {
+            log::error!("error deleting update file: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn retains_the_last_update_files() {
+        let retained = RetainedUpdateFiles::new(2);
+        let uuids: Vec<_> = (0..4).map(|_| Uuid::new_v4()).collect();
+
+        assert_eq!(retained.retain(uuids[2]), None);
+        assert_eq!(retained.retain(uuids[3]), None);
+        // The files kept before a restart are older than the ones processed since.
+        assert_eq!(retained.recover(uuids[..3].to_vec()), uuids[..2]);
+        assert_eq!(retained.retain(Uuid::nil()), Some(uuids[2]));
+    }
+
+    #[test]
+    fn rejects_invalid_primaries() {
+        assert!(Primary::new("http://127.0.0.1:7700", String::new()).is_ok());
+        assert!(matches!(
+            Primary::new("127.0.0.1:7700", String::new()),
+            Err(ReplicationError::InvalidPrimary(_))
+        ));
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::index::{SearchQuery, SearchResult};
+
+/// Identifies a search by the searched index and the normalized parameters of the query.
+///
+/// The search rules of the API key are merged in the filter of the query before it reaches the
+/// index controller, so two keys restricted by different rules never share their results.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    index_uid: String,
+    query: String,
+}
+
+impl CacheKey {
+    pub fn new(index_uid: &str, query: &SearchQuery) -> Self {
+        let mut query = serde_json::to_value(query).unwrap_or_default();
+        // The attributes to highlight are a set, their order doesn't change the results.
+        if let Some(Value::Array(attributes)) = query.get_mut("attributesToHighlight") {
+            attributes.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+        }
+
+        Self {
+            index_uid: index_uid.to_string(),
+            query: query.to_string(),
+        }
+    }
+}
+
+/// The counters of the search cache, returned by the `/stats/search-cache` route.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchCacheStats {
+    /// The maximum number of cached search results, the cache is disabled when it is 0.
+    pub capacity: usize,
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub invalidations: u64,
+}
+
+struct Entry {
+    result: SearchResult,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<CacheKey, Entry>,
+    /// The cached keys by last use, the least recently used first.
+    recency: BTreeMap<u64, CacheKey>,
+    clock: u64,
+    /// Incremented each time an index is invalidated, so that a search performed before a write
+    /// isn't cached once the write has been committed.
+    generations: HashMap<String, u64>,
+}
+
+impl Inner {
+    fn touch(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
+
+/// Keeps the results of the last `capacity` distinct searches in memory. The results of an index
+/// are invalidated whenever a task is committed to it.
+pub struct SearchCache {
+    capacity: usize,
+    inner: Mutex<Inner>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+impl SearchCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the current generation of the index, to pass to `insert` once the search is done.
+    pub fn generation(&self, index_uid: &str) -> u64 {
+        self.inner
+            .lock()
+            .generations
+            .get(index_uid)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the cached result of the search, and marks it as the most recently used.
+    pub fn get(&self, key: &CacheKey) -> Option<SearchResult> {
+        let mut inner = self.inner.lock();
+        let now = inner.touch();
+        let Inner {
+            entries, recency, ..
+        } = &mut *inner;
+
+        match entries.get_mut(key) {
+            Some(entry) => {
+                recency.remove(&entry.last_used);
+                recency.insert(now, key.clone());
+                entry.last_used = now;
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(entry.result.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Caches the result of a search, unless the index has been invalidated since `generation`
+    /// was read. The least recently used result is evicted when the cache is full.
+    pub fn insert(&self, key: CacheKey, generation: u64, result: SearchResult) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock();
+        let current = inner
+            .generations
+            .get(&key.index_uid)
+            .copied()
+            .unwrap_or_default();
+        if current != generation {
+            return;
+        }
+
+        let now = inner.touch();
+        if let Some(previous) = inner.entries.remove(&key) {
+            inner.recency.remove(&previous.last_used);
+        } else if inner.entries.len() >= self.capacity {
+            let oldest = inner.recency.keys().next().copied();
+            if let Some(evicted) = oldest.and_then(|tick| inner.recency.remove(&tick)) {
+                inner.entries.remove(&evicted);
+            }
+        }
+
+        inner.recency.insert(now, key.clone());
+        inner.entries.insert(
+            key,
+            Entry {
+                result,
+                last_used: now,
+            },
+        );
+    }
+
+    /// Drops all the cached results of the index.
+    pub fn invalidate(&self, index_uid: &str) {
+        let mut inner = self.inner.lock();
+        *inner.generations.entry(index_uid.to_string()).or_default() += 1;
+        inner.entries.retain(|key, _| key.index_uid != index_uid);
+        inner.recency.retain(|_, key| key.index_uid != index_uid);
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> SearchCacheStats {
+        SearchCacheStats {
+            capacity: self.capacity,
+            entries: self.inner.lock().entries.len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::index::{SearchTimings, DEFAULT_SEARCH_LIMIT};
+
+    use super::*;
+
+    fn query(q: &str) -> SearchQuery {
+        SearchQuery {
+            q: Some(q.to_string()),
+            offset: None,
+            limit: None,
+            page: None,
+            hits_per_page: None,
+            attributes_to_retrieve: None,
+            attributes_to_crop: None,
+            crop_length: None,
+            attributes_to_highlight: None,
+            matches: None,
+            filter: None,
+            sort: None,
+            facets_distribution: None,
+            distinct: None,
+            matching_strategy: None,
+            locales: None,
+            highlight_pre_tag: None,
+            highlight_post_tag: None,
+            crop_marker: None,
+            show_ranking_score: None,
+            show_ranking_score_details: None,
+            ruleset: None,
+            boost: None,
+            boost_filter: None,
+            boost_filter_weight: None,
+            timeout_ms: None,
+            retrievable_attributes: None,
+        }
+    }
+
+    fn result(q: &str) -> SearchResult {
+        SearchResult {
+            hits: Vec::new(),
+            nb_hits: 0,
+            exhaustive_nb_hits: false,
+            query: q.to_string(),
+            limit: DEFAULT_SEARCH_LIMIT,
+            offset: 0,
+            hits_per_page: None,
+            page: None,
+            total_pages: None,
+            total_hits: None,
+            processing_time_ms: 0,
+            queue_wait_ms: 0,
+            facets_distribution: None,
+            exhaustive_facets_count: None,
+            ruleset: None,
+            degraded: false,
+            timings: SearchTimings::default(),
+        }
+    }
+
+    #[test]
+    fn normalizes_the_attributes_to_highlight() {
+        let mut first = query("hello");
+        first.attributes_to_highlight =
+            Some(["a", "b", "c"].iter().map(|s| s.to_string()).collect());
+        let mut second = query("hello");
+        second.attributes_to_highlight =
+            Some(["c", "a", "b"].iter().map(|s| s.to_string()).collect());
+
+        assert_eq!(
+            CacheKey::new("test", &first),
+            CacheKey::new("test", &second)
+        );
+        assert_ne!(
+            CacheKey::new("test", &first),
+            CacheKey::new("other", &first)
+        );
+    }
+
+    #[test]
+    fn evicts_least_recently_used_results() {
+        let cache = SearchCache::new(2);
+        for q in ["first", "second"] {
+            cache.insert(CacheKey::new("test", &query(q)), 0, result(q));
+        }
+        // `first` becomes the most recently used result.
+        assert!(cache.get(&CacheKey::new("test", &query("first"))).is_some());
+        cache.insert(CacheKey::new("test", &query("third")), 0, result("third"));
+
+        assert!(cache
+            .get(&CacheKey::new("test", &query("second")))
+            .is_none());
+        assert!(cache.get(&CacheKey::new("test", &query("first"))).is_some());
+        assert!(cache.get(&CacheKey::new("test", &query("third"))).is_some());
+
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.misses, 1);
+    }
+
+    #[test]
+    fn invalidates_the_results_of_an_index() {
+        let cache = SearchCache::new(10);
+        let key = CacheKey::new("test", &query("hello"));
+        let other = CacheKey::new("other", &query("hello"));
+
+        let generation = cache.generation("test");
+        cache.insert(key.clone(), generation, result("hello"));
+        cache.insert(other.clone(), cache.generation("other"), result("hello"));
+        cache.invalidate("test");
+
+        assert!(cache.get(&key).is_none());
+        assert!(cache.get(&other).is_some());
+
+        // a search started before the invalidation isn't cached.
+        cache.insert(key.clone(), generation, result("hello"));
+        assert!(cache.get(&key).is_none());
+        assert_eq!(cache.stats().invalidations, 1);
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+
+use super::error::{IndexControllerError, Result};
+
+/// The maximum number of searches waiting for a worker if not specified otherwise.
+pub const DEFAULT_SEARCH_QUEUE_SIZE: usize = 1000;
+
+/// Bounds the number of searches processed concurrently.
+///
+/// Searches that can't be processed right away are queued per requester, and the requesters are
+/// served in a round-robin fashion, so that a single client bursting requests can't starve the
+/// others. When the queue is full, the search is rejected.
+#[derive(Clone)]
+pub struct SearchQueue {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    pool_size: usize,
+    queue_size: usize,
+    state: Mutex<QueueState>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    /// The number of searches currently holding a permit.
+    running: usize,
+    /// The total number of searches waiting for a permit.
+    waiting: usize,
+    queues: HashMap<String, VecDeque<oneshot::Sender<SearchPermit>>>,
+    /// The requesters having at least one search waiting, in the order they will be served.
+    order: VecDeque<String>,
+}
+
+impl QueueState {
+    fn enqueue(&mut self, requester: &str, sender: oneshot::Sender<SearchPermit>) {
+        let queue = self.queues.entry(requester.to_string()).or_default();
+        if queue.is_empty() {
+            self.order.push_back(requester.to_string());
+        }
+        queue.push_back(sender);
+        self.waiting += 1;
+    }
+
+    fn dequeue(&mut self) -> Option<oneshot::Sender<SearchPermit>> {
+        let requester = self.order.pop_front()?;
+        let queue = self.queues.get_mut(&requester)?;
+        let sender = queue.pop_front();
+
+        if queue.is_empty() {
+            self.queues.remove(&requester);
+        } else {
+            self.order.push_back(requester);
+        }
+
+        self.waiting -= 1;
+        sender
+    }
+}
+
+/// Allows a search to be processed, the next queued search is allowed when it is dropped.
+pub struct SearchPermit {
+    inner: Option<Arc<Inner>>,
+}
+
+impl Drop for SearchPermit {
+    fn drop(&mut self) {
+        let inner = match self.inner.take() {
+            Some(inner) => inner,
+            None => return,
+        };
+
+        let mut state = inner.state.lock();
+        // Hand the permit over to the next requester, skipping the searches that were cancelled
+        // while waiting.
+        while let Some(sender) = state.dequeue() {
+            let permit = SearchPermit {
+                inner: Some(inner.clone()),
+            };
+            match sender.send(permit) {
+                Ok(()) => return,
+                Err(mut permit) => {
+                    permit.inner.take();
+                }
+            }
+        }
+
+        state.running -= 1;
+    }
+}
+
+impl SearchQueue {
+    pub fn new(pool_size: usize, queue_size: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                // A pool size of 0 would block all the searches.
+                pool_size: pool_size.max(1),
+                queue_size,
+                state: Mutex::new(QueueState::default()),
+            }),
+        }
+    }
+
+    /// Waits for a search permit on behalf of `requester`.
+    pub async fn acquire(&self, requester: &str) -> Result<SearchPermit> {
+        let receiver = {
+            let mut state = self.inner.state.lock();
+            if state.running < self.inner.pool_size && state.waiting == 0 {
+                state.running += 1;
+                return Ok(SearchPermit {
+                    inner: Some(self.inner.clone()),
+                });
+            }
+
+            if state.waiting >= self.inner.queue_size {
+                return Err(IndexControllerError::TooManySearchRequests);
+            }
+
+            let (sender, receiver) = oneshot::channel();
+            state.enqueue(requester, sender);
+            receiver
+        };
+
+        receiver
+            .await
+            .map_err(|e| IndexControllerError::Internal(Box::new(e)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use futures::FutureExt;
+
+    use super::*;
+
+    #[actix_rt::test]
+    async fn rejects_searches_when_queue_is_full() {
+        let queue = SearchQueue::new(1, 1);
+
+        let _running = queue.acquire("a").await.unwrap();
+        let waiting = queue.acquire("a");
+        futures::pin_mut!(waiting);
+        assert!(waiting.as_mut().now_or_never().is_none());
+
+        assert!(matches!(
+            queue.acquire("b").await,
+            Err(IndexControllerError::TooManySearchRequests)
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn serves_requesters_in_turn() {
+        let queue = SearchQueue::new(1, 10);
+        let served = Arc::new(Mutex::new(Vec::new()));
+
+        let running = queue.acquire("a").await.unwrap();
+
+        let mut handles = Vec::new();
+        for requester in ["a", "a", "a", "b"] {
+            let queue = queue.clone();
+            let served = served.clone();
+            handles.push(tokio::spawn(async move {
+                let _permit = queue.acquire(requester).await.unwrap();
+                served.lock().push(requester);
+            }));
+            // make sure the searches are queued in order.
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        drop(running);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(*served.lock(), vec!["a", "b", "a", "a"]);
+    }
+}
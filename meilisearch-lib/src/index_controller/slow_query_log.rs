@@ -0,0 +1,149 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use crate::index::{SearchQuery, SearchTimings};
+
+/// A search that took longer than the configured threshold to be processed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowQuery {
+    pub index_uid: String,
+    pub query: SearchQuery,
+    pub processing_time_ms: u128,
+    pub timings: SearchTimings,
+    #[serde(with = "time::serde::rfc3339")]
+    pub searched_at: OffsetDateTime,
+}
+
+/// Logs every search exceeding `threshold` and keeps the last `capacity` of them in memory.
+pub struct SlowQueryLog {
+    threshold: Duration,
+    capacity: usize,
+    entries: Mutex<VecDeque<SlowQuery>>,
+}
+
+impl SlowQueryLog {
+    pub fn new(threshold: Duration, capacity: usize) -> Self {
+        Self {
+            threshold,
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Check the duration of a search against the threshold, and record it if it is too slow.
+    pub fn observe(
+        &self,
+        index_uid: &str,
+        query: SearchQuery,
+        elapsed: Duration,
+        timings: &SearchTimings,
+    ) {
+        if elapsed < self.threshold {
+            return;
+        }
+
+        let entry = SlowQuery {
+            index_uid: index_uid.to_string(),
+            query,
+            processing_time_ms: elapsed.as_millis(),
+            timings: timings.clone(),
+            searched_at: OffsetDateTime::now_utc(),
+        };
+
+        log::warn!(
+            "slow search on index `{}` took {}ms: {}",
+            entry.index_uid,
+            entry.processing_time_ms,
+            serde_json::to_string(&entry).unwrap_or_default(),
+        );
+
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Returns the recorded slow queries, the most recent first.
+    pub fn list(&self) -> Vec<SlowQuery> {
+        self.entries.lock().iter().rev().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn query(q: &str) -> SearchQuery {
+        SearchQuery {
+            q: Some(q.to_string()),
+            offset: None,
+            limit: None,
+            page: None,
+            hits_per_page: None,
+            attributes_to_retrieve: None,
+            attributes_to_crop: None,
+            crop_length: None,
+            attributes_to_highlight: None,
+            matches: None,
+            filter: None,
+            sort: None,
+            facets_distribution: None,
+            distinct: None,
+            matching_strategy: None,
+            locales: None,
+            highlight_pre_tag: None,
+            highlight_post_tag: None,
+            crop_marker: None,
+            show_ranking_score: None,
+            show_ranking_score_details: None,
+            ruleset: None,
+            boost: None,
+            boost_filter: None,
+            boost_filter_weight: None,
+            timeout_ms: None,
+            retrievable_attributes: None,
+        }
+    }
+
+    #[test]
+    fn ignores_fast_queries() {
+        let log = SlowQueryLog::new(Duration::from_millis(100), 10);
+        log.observe(
+            "test",
+            query("fast"),
+            Duration::from_millis(10),
+            &SearchTimings::default(),
+        );
+        assert!(log.list().is_empty());
+    }
+
+    #[test]
+    fn evicts_oldest_queries() {
+        let log = SlowQueryLog::new(Duration::from_millis(100), 2);
+        for q in ["first", "second", "third"] {
+            log.observe(
+                "test",
+                query(q),
+                Duration::from_millis(200),
+                &SearchTimings::default(),
+            );
+        }
+
+        let queries: Vec<_> = log
+            .list()
+            .into_iter()
+            .map(|entry| entry.query.q.unwrap())
+            .collect();
+        assert_eq!(queries, vec!["third", "second"]);
+    }
+}
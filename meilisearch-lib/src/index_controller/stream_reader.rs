@@ -0,0 +1,60 @@
+use std::io::{self, Read};
+
+use bytes::{Buf, Bytes};
+use tokio::sync::mpsc;
+
+/// The number of chunks of a payload buffered between the HTTP handler and the thread parsing
+/// them. Each chunk is at most a few dozen kilobytes, so that an NDJSON payload of any size is
+/// streamed to disk with a bounded amount of memory.
+pub const STREAM_CHUNKS_CAPACITY: usize = 32;
+
+/// A blocking reader over the chunks of a payload sent through a channel. The end of the channel
+/// is the end of the payload.
+pub struct StreamReader {
+    receiver: mpsc::Receiver<Bytes>,
+    current: Bytes,
+}
+
+impl StreamReader {
+    pub fn new(receiver: mpsc::Receiver<Bytes>) -> Self {
+        Self {
+            receiver,
+            current: Bytes::new(),
+        }
+    }
+}
+
+impl Read for StreamReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while !self.current.has_remaining() {
+            match self.receiver.blocking_recv() {
+                Some(chunk) => self.current = chunk,
+                None => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.current.remaining());
+        self.current.copy_to_slice(&mut buf[..len]);
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reads_the_chunks_in_order() {
+        let (sender, receiver) = mpsc::channel(STREAM_CHUNKS_CAPACITY);
+        for chunk in ["{\"id\": 1}\n{\"i", "", "d\": 2}\n"] {
+            sender.try_send(Bytes::from(chunk)).unwrap();
+        }
+        drop(sender);
+
+        let mut content = String::new();
+        StreamReader::new(receiver)
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "{\"id\": 1}\n{\"id\": 2}\n");
+    }
+}
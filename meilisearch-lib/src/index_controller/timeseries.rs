@@ -0,0 +1,289 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tokio::task::spawn_blocking;
+
+use super::error::{IndexControllerError, Result};
+
+/// The duration covered by a bucket, in seconds.
+pub const BUCKET_SECS: i64 = 60;
+/// The number of buckets kept per index, the oldest buckets are dropped first.
+pub const RETENTION_BUCKETS: usize = 24 * 60;
+/// How often the buckets are written on disk.
+pub const PERSIST_INTERVAL: Duration = Duration::from_secs(60);
+
+const TIMESERIES_FILE_NAME: &str = "timeseries.json";
+
+/// The activity of an index during a bucket.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Bucket {
+    /// The unix timestamp of the beginning of the bucket.
+    start: i64,
+    searches: u64,
+    indexed_documents: u64,
+    /// The number of documents of the index at the end of its last indexing in the bucket.
+    number_of_documents: Option<u64>,
+}
+
+/// The activity of an index during an interval of a timeseries query.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeseriesPoint {
+    #[serde(with = "time::serde::rfc3339")]
+    pub start: OffsetDateTime,
+    pub searches: u64,
+    pub indexed_documents: u64,
+    /// The last known number of documents of the index, `None` if the index was never indexed
+    /// since the beginning of the recording.
+    pub number_of_documents: Option<u64>,
+}
+
+/// Records the search and indexing activity of each index in ring buffers of one minute buckets.
+///
+/// The buckets are kept in memory and regularly written in the database directory, so the
+/// activity survives the restarts.
+#[derive(Default)]
+pub struct Timeseries {
+    path: Option<PathBuf>,
+    series: Mutex<HashMap<String, VecDeque<Bucket>>>,
+}
+
+impl Timeseries {
+    /// Load the timeseries stored in `db_path`. A corrupted file is discarded, the timeseries are
+    /// only indicative.
+    pub fn load(db_path: impl AsRef<Path>) -> Self {
+        let path = db_path.as_ref().join(TIMESERIES_FILE_NAME);
+        let series = match File::open(&path) {
+            Ok(file) => serde_json::from_reader(BufReader::new(file)).unwrap_or_else(|e| {
+                log::warn!("discarding the corrupted timeseries file: {}", e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        Self {
+            path: Some(path),
+            series: Mutex::new(series),
+        }
+    }
+
+    /// Write the timeseries on disk, the previous file is atomically replaced.
+    pub fn persist(&self) -> Result<()> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let file = tempfile::NamedTempFile::new_in(dir)
+            .map_err(|e| IndexControllerError::Internal(Box::new(e)))?;
+        {
+            let series = self.series.lock();
+            let mut writer = BufWriter::new(file.as_file());
+            serde_json::to_writer(&mut writer, &*series)
+                .map_err(|e| IndexControllerError::Internal(Box::new(e)))?;
+            writer
+                .flush()
+                .map_err(|e| IndexControllerError::Internal(Box::new(e)))?;
+        }
+        file.persist(path)
+            .map_err(|e| IndexControllerError::Internal(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    /// Regularly write the timeseries on disk, this never returns.
+    pub async fn run(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(PERSIST_INTERVAL);
+        loop {
+            interval.tick().await;
+            let timeseries = self.clone();
+            match spawn_blocking(move || timeseries.persist()).await {
+                Ok(Ok(())) => (),
+                Ok(Err(e)) => log::error!("error while persisting the timeseries: {}", e),
+                Err(e) => log::error!("error while persisting the timeseries: {}", e),
+            }
+        }
+    }
+
+    pub fn record_search(&self, index_uid: &str) {
+        self.record(index_uid, |bucket| bucket.searches += 1);
+    }
+
+    pub fn record_indexing(
+        &self,
+        index_uid: &str,
+        indexed_documents: u64,
+        number_of_documents: u64,
+    ) {
+        self.record(index_uid, |bucket| {
+            bucket.indexed_documents += indexed_documents;
+            bucket.number_of_documents = Some(number_of_documents);
+        });
+    }
+
+    /// Forget the activity of a deleted index.
+    pub fn remove(&self, index_uid: &str) {
+        self.series.lock().remove(index_uid);
+    }
+
+    /// Move the activity of a renamed index to its new uid.
+    pub fn rename(&self, index_uid: &str, new_uid: &str) {
+        let mut series = self.series.lock();
+        if let Some(buckets) = series.remove(index_uid) {
+            series.insert(new_uid.to_string(), buckets);
+        }
+    }
+
+    fn record(&self, index_uid: &str, f: impl FnOnce(&mut Bucket)) {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let start = now - now.rem_euclid(BUCKET_SECS);
+
+        let mut series = self.series.lock();
+        let buckets = series.entry(index_uid.to_string()).or_default();
+        match buckets.back_mut() {
+            // If the clock went backward, the activity is accounted in the last bucket.
+            Some(bucket) if bucket.start >= start => f(bucket),
+            _ => {
+                let mut bucket = Bucket {
+                    start,
+                    ..Bucket::default()
+                };
+                f(&mut bucket);
+                buckets.push_back(bucket);
+                while buckets.len() > RETENTION_BUCKETS {
+                    buckets.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Returns the activity of the indexes accepted by `filter` over the last `window` seconds,
+    /// aggregated by `interval` seconds. The interval must be a multiple of the bucket duration.
+    pub fn query(
+        &self,
+        window: i64,
+        interval: i64,
+        filter: impl Fn(&str) -> bool,
+    ) -> Result<BTreeMap<String, Vec<TimeseriesPoint>>> {
+        let max_window = BUCKET_SECS * RETENTION_BUCKETS as i64;
+        if interval <= 0 || interval % BUCKET_SECS != 0 {
+            return Err(IndexControllerError::InvalidTimeseriesQuery(format!(
+                "`interval` must be a positive multiple of {} seconds",
+                BUCKET_SECS
+            )));
+        }
+        if window < interval || window > max_window {
+            return Err(IndexControllerError::InvalidTimeseriesQuery(format!(
+                "`window` must be between the `interval` and {} seconds",
+                max_window
+            )));
+        }
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let end = now - now.rem_euclid(interval) + interval;
+        let points = ((window + interval - 1) / interval) as usize;
+        let first = end - points as i64 * interval;
+
+        let series = self.series.lock();
+        let result = series
+            .iter()
+            .filter(|(index_uid, _)| filter(index_uid))
+            .map(|(index_uid, buckets)| {
+                let mut result: Vec<_> = (0..points)
+                    .map(|i| TimeseriesPoint {
+                        start: OffsetDateTime::from_unix_timestamp(first + i as i64 * interval)
+                            .unwrap_or(OffsetDateTime::UNIX_EPOCH),
+                        searches: 0,
+                        indexed_documents: 0,
+                        number_of_documents: None,
+                    })
+                    .collect();
+
+                let mut known_documents = None;
+                for bucket in buckets {
+                    if bucket.start < first {
+                        known_documents = bucket.number_of_documents.or(known_documents);
+                        continue;
+                    }
+                    let point = match result.get_mut(((bucket.start - first) / interval) as usize) {
+                        Some(point) => point,
+                        None => break,
+                    };
+                    point.searches += bucket.searches;
+                    point.indexed_documents += bucket.indexed_documents;
+                    point.number_of_documents =
+                        bucket.number_of_documents.or(point.number_of_documents);
+                }
+
+                // Carry the number of documents over the intervals without indexing.
+                for point in result.iter_mut() {
+                    match point.number_of_documents {
+                        Some(count) => known_documents = Some(count),
+                        None => point.number_of_documents = known_documents,
+                    }
+                }
+
+                (index_uid.clone(), result)
+            })
+            .collect();
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn aggregates_activity() {
+        let timeseries = Timeseries::default();
+        timeseries.record_search("movies");
+        timeseries.record_search("movies");
+        timeseries.record_indexing("movies", 10, 10);
+        timeseries.record_search("books");
+
+        let result = timeseries
+            .query(3600, 600, |index_uid| index_uid == "movies")
+            .unwrap();
+
+        assert_eq!(result.len(), 1);
+        let points = &result["movies"];
+        assert_eq!(points.len(), 6);
+        let last = points.last().unwrap();
+        assert_eq!(last.searches, 2);
+        assert_eq!(last.indexed_documents, 10);
+        assert_eq!(last.number_of_documents, Some(10));
+        assert!(points[..5].iter().all(|point| point.searches == 0));
+    }
+
+    #[test]
+    fn rejects_invalid_intervals() {
+        let timeseries = Timeseries::default();
+
+        assert!(timeseries.query(3600, 90, |_| true).is_err());
+        assert!(timeseries.query(60, 120, |_| true).is_err());
+        assert!(timeseries.query(10 * 86400, 60, |_| true).is_err());
+    }
+
+    #[test]
+    fn persists_buckets() {
+        let dir = tempfile::tempdir().unwrap();
+        let timeseries = Timeseries::load(dir.path());
+        timeseries.record_indexing("movies", 3, 3);
+        timeseries.persist().unwrap();
+
+        let timeseries = Timeseries::load(dir.path());
+        let result = timeseries.query(60, 60, |_| true).unwrap();
+        assert_eq!(result["movies"][0].number_of_documents, Some(3));
+    }
+}
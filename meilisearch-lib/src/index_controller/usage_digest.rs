@@ -0,0 +1,112 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use meilisearch_auth::{is_public_address, webhook_host, AuthController, PendingDigest};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::redirect::Policy;
+use reqwest::Client;
+use time::OffsetDateTime;
+use tokio::net::lookup_host;
+use tokio::task::spawn_blocking;
+use tokio::time::sleep;
+
+/// How often the periods of the usage webhooks are checked.
+pub const DIGEST_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// The time after which the delivery of a digest is abandoned.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// The header carrying the signature of a digest.
+pub const SIGNATURE_HEADER: &str = "X-Meilisearch-Signature";
+
+/// Sends the usage digests of the keys to their webhooks at the end of each of their periods.
+///
+/// A digest that can't be delivered is dropped, it is not sent again with the next one.
+pub async fn run_usage_digests(auth: AuthController) {
+    loop {
+        let auth = auth.clone();
+        let digests =
+            spawn_blocking(move || auth.take_due_usage_digests(OffsetDateTime::now_utc())).await;
+        match digests {
+            Ok(Ok(digests)) => {
+                for digest in digests {
+                    send_digest(digest).await;
+                }
+            }
+            Ok(Err(e)) => log::error!("error while preparing the usage digests: {}", e),
+            Err(e) => log::error!("error while preparing the usage digests: {}", e),
+        }
+
+        sleep(DIGEST_CHECK_INTERVAL).await;
+    }
+}
+
+/// Sends a digest to the address the host of its webhook resolves to, once checked to be a public
+/// one. The client is bound to this address, and doesn't follow the redirections, so that the
+/// digest can't be sent anywhere else.
+async fn send_digest(digest: PendingDigest) {
+    let PendingDigest {
+        url,
+        body,
+        signature,
+    } = digest;
+    let (host, address) = match resolve_public_address(&url).await {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            log::warn!("The usage webhook `{}` is refused: {}", url, e);
+            return;
+        }
+    };
+    let client = match Client::builder()
+        .user_agent(concat!("Meilisearch/", env!("CARGO_PKG_VERSION")))
+        .timeout(DELIVERY_TIMEOUT)
+        .redirect(Policy::none())
+        .resolve(&host, address)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            log::error!("The usage digests can't be sent: {}", e);
+            return;
+        }
+    };
+
+    let response = client
+        .post(&url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, signature)
+        .body(body)
+        .send()
+        .await;
+
+    match response {
+        Ok(response) if response.status().is_success() => (),
+        Ok(response) => log::warn!(
+            "The usage webhook `{}` answered with the status {}.",
+            url,
+            response.status()
+        ),
+        Err(e) => log::warn!("The usage webhook `{}` is unreachable: {}", url, e),
+    }
+}
+
+async fn resolve_public_address(url: &str) -> Result<(String, SocketAddr), String> {
+    let (host, port) = webhook_host(url).ok_or_else(|| "the URL is invalid".to_string())?;
+    let addresses: Vec<_> = lookup_host((host, port))
+        .await
+        .map_err(|e| e.to_string())?
+        .collect();
+
+    if addresses
+        .iter()
+        .any(|address| !is_public_address(address.ip()))
+    {
+        return Err(format!(
+            "`{}` resolves to an address that isn't public",
+            host
+        ));
+    }
+
+    match addresses.first() {
+        Some(address) => Ok((host.to_string(), *address)),
+        None => Err(format!("`{}` can't be resolved", host)),
+    }
+}
@@ -0,0 +1,269 @@
+//! Imports the documents files dropped in a watched directory. Each file of a subdirectory,
+//! `<dir>/<index_uid>/<name>.ndjson` or `<name>.csv`, is registered as a document addition to the
+//! index named after the subdirectory, which is created if it doesn't exist. The file is then
+//! moved to `<dir>/.processed/<index_uid>/`, or to `<dir>/.failed/<index_uid>/` if it couldn't be
+//! registered.
+//!
+//! The files registered but not moved yet are recorded in the state file of the directory, so
+//! that an instance stopped in between doesn't import them again when it restarts.
+//!
+//! The hidden files and the files modified less than a second ago are left for a later scan: a
+//! file can be written under a hidden name, then renamed once it is complete.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use actix_web::error::PayloadError;
+use bytes::Bytes;
+use milli::update::IndexDocumentsMethod;
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+
+use super::error::{IndexControllerError, Result};
+use super::{DocumentAdditionFormat, MeiliSearch, Update};
+use crate::index_resolver::IndexUid;
+use crate::tasks::task::TaskId;
+
+/// The files modified more recently may still be written.
+const MIN_FILE_AGE: Duration = Duration::from_secs(1);
+const PROCESSED_DIR: &str = ".processed";
+const FAILED_DIR: &str = ".failed";
+const STATE_FILE: &str = ".import-state.json";
+
+/// The files registered but not moved to the processed directory yet, by their path relative to
+/// the watched directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ImportState {
+    registered: BTreeMap<String, TaskId>,
+}
+
+impl ImportState {
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        match fs::read(path) {
+            Ok(content) => Ok(serde_json::from_slice(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Replaces the state file, through a temporary file so that it is never left truncated.
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+/// A documents file waiting to be imported.
+#[derive(Debug)]
+struct PendingFile {
+    index_uid: String,
+    /// The path of the file relative to the watched directory.
+    name: String,
+    format: DocumentAdditionFormat,
+}
+
+/// Scans `dir` at each `interval` and imports the documents files dropped in it.
+pub async fn run_watched_import(meilisearch: MeiliSearch, dir: PathBuf, interval: Duration) {
+    log::info!(
+        "Importing the documents files dropped in `{}` every {}s.",
+        dir.display(),
+        interval.as_secs()
+    );
+    loop {
+        if let Err(e) = import_files(&meilisearch, &dir).await {
+            log::error!(
+                "error while importing the documents files of `{}`: {}",
+                dir.display(),
+                e
+            );
+        }
+        sleep(interval).await;
+    }
+}
+
+async fn import_files(meilisearch: &MeiliSearch, dir: &Path) -> anyhow::Result<()> {
+    let state_path = dir.join(STATE_FILE);
+    let mut state = ImportState::load(&state_path)?;
+
+    // The files registered before the instance was stopped are only moved.
+    if !state.registered.is_empty() {
+        for (name, task_id) in std::mem::take(&mut state.registered) {
+            if dir.join(&name).exists() {
+                log::info!("`{}` was imported by the task {}.", name, task_id);
+                move_file(dir, PROCESSED_DIR, &name)?;
+            }
+        }
+        state.save(&state_path)?;
+    }
+
+    for file in pending_files(dir, SystemTime::now())? {
+        match register_file(meilisearch, dir, &file).await {
+            Ok(task_id) => {
+                state.registered.insert(file.name.clone(), task_id);
+                state.save(&state_path)?;
+                move_file(dir, PROCESSED_DIR, &file.name)?;
+                state.registered.remove(&file.name);
+                state.save(&state_path)?;
+                log::info!("`{}` is imported by the task {}.", file.name, task_id);
+            }
+            // The writes are refused for a while, the file is registered by a later scan.
+            Err(IndexControllerError::WritesSuspended(reason)) => {
+                log::warn!("`{}` is not imported yet because {}.", file.name, reason);
+                break;
+            }
+            Err(e) => {
+                log::error!("`{}` can't be imported: {}", file.name, e);
+                move_file(dir, FAILED_DIR, &file.name)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn register_file(
+    meilisearch: &MeiliSearch,
+    dir: &Path,
+    file: &PendingFile,
+) -> Result<TaskId> {
+    let content = tokio::fs::read(dir.join(&file.name))
+        .await
+        .map_err(|e| IndexControllerError::Internal(Box::new(e)))?;
+    let payload_size = Some(content.len() as u64);
+    let update = Update::DocumentAddition {
+        payload: Box::new(futures::stream::once(futures::future::ready(Ok::<
+            _,
+            PayloadError,
+        >(
+            Bytes::from(content),
+        )))),
+        primary_key: None,
+        method: IndexDocumentsMethod::ReplaceDocuments,
+        format: file.format,
+        allow_index_creation: true,
+        payload_size,
+    };
+    let task = meilisearch
+        .register_update(file.index_uid.clone(), update)
+        .await?;
+
+    Ok(task.id)
+}
+
+/// Returns the documents files of the subdirectories of `dir` ready to be imported, in the order
+/// of their names.
+fn pending_files(dir: &Path, now: SystemTime) -> anyhow::Result<Vec<PendingFile>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let index_uid = match entry.file_name().into_string() {
+            Ok(uid) if entry.file_type()?.is_dir() && IndexUid::new(uid.clone()).is_ok() => uid,
+            _ => continue,
+        };
+
+        for entry in fs::read_dir(entry.path())? {
+            let entry = entry?;
+            let file_name = match entry.file_name().into_string() {
+                Ok(name) if !name.starts_with('.') => name,
+                _ => continue,
+            };
+            let format = match Path::new(&file_name).extension().and_then(|e| e.to_str()) {
+                Some("ndjson") | Some("jsonl") => DocumentAdditionFormat::Ndjson,
+                Some("csv") => DocumentAdditionFormat::Csv,
+                _ => continue,
+            };
+            let metadata = entry.metadata()?;
+            let age = now.duration_since(metadata.modified()?).unwrap_or_default();
+            if !metadata.is_file() || age < MIN_FILE_AGE {
+                continue;
+            }
+
+            files.push(PendingFile {
+                name: format!("{}/{}", index_uid, file_name),
+                index_uid: index_uid.clone(),
+                format,
+            });
+        }
+    }
+
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(files)
+}
+
+/// Moves the file to the `target` directory, the file already there under the same name, if any,
+/// is replaced.
+fn move_file(dir: &Path, target: &str, name: &str) -> anyhow::Result<()> {
+    let destination = dir.join(target).join(name);
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(dir.join(name), destination)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pending_files_are_mapped_to_the_index_of_their_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let later = SystemTime::now() + Duration::from_secs(60);
+        for (path, content) in [
+            ("movies/b.csv", "id\n1\n"),
+            ("movies/a.ndjson", "{\"id\":1}\n"),
+            ("movies/.c.ndjson", "{\"id\":1}\n"),
+            ("movies/notes.txt", ""),
+            ("books/a.jsonl", "{\"id\":1}\n"),
+            ("not an index/a.ndjson", "{\"id\":1}\n"),
+            (".processed/movies/d.ndjson", "{\"id\":1}\n"),
+            ("e.ndjson", "{\"id\":1}\n"),
+        ] {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        let files = pending_files(dir.path(), later).unwrap();
+        let names: Vec<_> = files
+            .iter()
+            .map(|file| (file.index_uid.as_str(), file.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("books", "books/a.jsonl"),
+                ("movies", "movies/a.ndjson"),
+                ("movies", "movies/b.csv"),
+            ]
+        );
+        assert!(matches!(files[2].format, DocumentAdditionFormat::Csv));
+
+        // The files just written may still be written.
+        assert!(pending_files(dir.path(), SystemTime::now())
+            .unwrap()
+            .is_empty());
+
+        move_file(dir.path(), PROCESSED_DIR, "movies/a.ndjson").unwrap();
+        assert!(dir.path().join(".processed/movies/a.ndjson").exists());
+        assert_eq!(pending_files(dir.path(), later).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn import_state_is_saved() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(STATE_FILE);
+        assert!(ImportState::load(&path).unwrap().registered.is_empty());
+
+        let mut state = ImportState::default();
+        state.registered.insert("movies/a.ndjson".to_string(), 3);
+        state.save(&path).unwrap();
+
+        let state = ImportState::load(&path).unwrap();
+        assert_eq!(state.registered.get("movies/a.ndjson"), Some(&3));
+    }
+}
@@ -0,0 +1,218 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use time::OffsetDateTime;
+use tokio::time::sleep;
+
+use super::health::disk_space;
+use crate::tasks::task::TaskId;
+use crate::tasks::{TaskFilter, TaskStore};
+
+/// The interval at which the task queue and the disk are measured.
+pub const WRITE_BREAKER_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A breaker opened by the task queue is closed once the queue is back under this share of its
+/// threshold, so that it doesn't flap around it.
+const CLOSING_RATIO: f64 = 0.9;
+
+/// The thresholds beyond which the document writes are refused. No threshold by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteBreakerThresholds {
+    /// The maximum number of unfinished tasks.
+    pub max_enqueued_tasks: Option<usize>,
+    /// The minimum space, in bytes, left on the disk of the database.
+    pub min_free_disk: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BreakerReason {
+    TaskQueue,
+    Disk,
+}
+
+impl fmt::Display for BreakerReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BreakerReason::TaskQueue => write!(f, "too many tasks are waiting to be processed"),
+            BreakerReason::Disk => write!(f, "the disk of the database is almost full"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteBreakerState {
+    /// Whether the document writes are refused.
+    pub open: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<BreakerReason>,
+    #[serde(
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub opened_at: Option<OffsetDateTime>,
+    /// The number of unfinished tasks at the last measure.
+    pub enqueued_tasks: usize,
+}
+
+/// Refuses the document writes while the task queue or the disk usage is beyond its thresholds,
+/// so that the queue can't grow until the disk is full. The searches are still served.
+pub struct WriteBreaker {
+    thresholds: WriteBreakerThresholds,
+    state: RwLock<WriteBreakerState>,
+}
+
+impl WriteBreaker {
+    pub fn new(thresholds: WriteBreakerThresholds) -> Self {
+        Self {
+            thresholds,
+            state: RwLock::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.thresholds.max_enqueued_tasks.is_some() || self.thresholds.min_free_disk.is_some()
+    }
+
+    pub fn state(&self) -> WriteBreakerState {
+        self.state.read().clone()
+    }
+
+    /// Returns why a document write is refused. The deletions, which free some space, are still
+    /// accepted while the disk is almost full.
+    pub fn check(&self, is_deletion: bool) -> Result<(), BreakerReason> {
+        match self.state.read().reason {
+            Some(BreakerReason::Disk) if is_deletion => Ok(()),
+            Some(reason) => Err(reason),
+            None => Ok(()),
+        }
+    }
+
+    /// Opens or closes the breaker from the number of unfinished tasks and the free space of
+    /// the disk, unknown on the platforms without disk information.
+    pub fn update(&self, enqueued_tasks: usize, free_disk: Option<u64>) {
+        let WriteBreakerThresholds {
+            max_enqueued_tasks,
+            min_free_disk,
+        } = self.thresholds;
+        let mut state = self.state.write();
+
+        let queue_limit = match (max_enqueued_tasks, state.reason) {
+            (Some(max), Some(BreakerReason::TaskQueue)) => {
+                Some((max as f64 * CLOSING_RATIO) as usize)
+            }
+            (max, _) => max,
+        };
+        let disk_full = matches!((free_disk, min_free_disk), (Some(free), Some(min)) if free < min);
+        let reason = if disk_full {
+            Some(BreakerReason::Disk)
+        } else if queue_limit.map_or(false, |limit| enqueued_tasks >= limit) {
+            Some(BreakerReason::TaskQueue)
+        } else {
+            None
+        };
+
+        match (reason, state.open) {
+            (Some(reason), false) => {
+                log::warn!("The document writes are suspended: {}.", reason);
+                state.opened_at = Some(OffsetDateTime::now_utc());
+            }
+            (None, true) => {
+                log::info!("The document writes are resumed.");
+                state.opened_at = None;
+            }
+            _ => (),
+        }
+        state.open = reason.is_some();
+        state.reason = reason;
+        state.enqueued_tasks = enqueued_tasks;
+    }
+}
+
+/// Measures the task queue and the disk of the database at every check interval.
+pub async fn run_write_breaker(
+    breaker: Arc<WriteBreaker>,
+    task_store: TaskStore,
+    db_path: PathBuf,
+) {
+    // All the tasks before this one are finished, they are not counted again.
+    let mut first_unfinished: TaskId = 0;
+    loop {
+        let mut filter = TaskFilter::default();
+        filter.filter_fn(|task| !task.is_finished());
+        match task_store
+            .list_tasks(Some(first_unfinished), Some(filter), None)
+            .await
+        {
+            Ok(unfinished) => {
+                // The tasks are listed from the most recent one.
+                match unfinished.last() {
+                    Some(task) => first_unfinished = task.id,
+                    None => {
+                        if let Ok(next) = task_store.next_task_id().await {
+                            first_unfinished = next;
+                        }
+                    }
+                }
+                let db_path = db_path.clone();
+                let free_disk = tokio::task::spawn_blocking(move || disk_space(&db_path))
+                    .await
+                    .ok()
+                    .flatten()
+                    .map(|disk| disk.available_bytes);
+                breaker.update(unfinished.len(), free_disk);
+            }
+            Err(e) => log::error!("error while measuring the task queue: {}", e),
+        }
+
+        sleep(WRITE_BREAKER_CHECK_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn breaker_opens_beyond_the_thresholds() {
+        let breaker = WriteBreaker::new(WriteBreakerThresholds {
+            max_enqueued_tasks: Some(100),
+            min_free_disk: Some(1000),
+        });
+        breaker.update(99, Some(1000));
+        assert!(!breaker.state().open);
+
+        breaker.update(100, Some(1000));
+        let state = breaker.state();
+        assert!(state.open);
+        assert_eq!(state.reason, Some(BreakerReason::TaskQueue));
+        assert!(state.opened_at.is_some());
+        assert_eq!(breaker.check(true), Err(BreakerReason::TaskQueue));
+
+        // The breaker is closed once the queue is well under its threshold.
+        breaker.update(95, Some(1000));
+        assert!(breaker.state().open);
+        breaker.update(89, Some(1000));
+        assert_eq!(
+            breaker.state(),
+            WriteBreakerState {
+                enqueued_tasks: 89,
+                ..Default::default()
+            }
+        );
+
+        assert_eq!(breaker.check(false), Ok(()));
+
+        breaker.update(0, Some(999));
+        assert_eq!(breaker.state().reason, Some(BreakerReason::Disk));
+        assert_eq!(breaker.check(false), Err(BreakerReason::Disk));
+        assert_eq!(breaker.check(true), Ok(()));
+        breaker.update(0, None);
+        assert!(!breaker.state().open);
+    }
+}
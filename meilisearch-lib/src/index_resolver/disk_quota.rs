@@ -0,0 +1,78 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use super::error::{IndexResolverError, Result};
+
+/// Limits the disk space used by the database, the writes that would make it grow are refused
+/// once `max_disk_usage` bytes are used.
+#[derive(Debug, Clone)]
+pub struct DiskQuota {
+    db_path: PathBuf,
+    max_disk_usage: u64,
+}
+
+impl DiskQuota {
+    pub fn new(db_path: impl AsRef<Path>, max_disk_usage: u64) -> Self {
+        Self {
+            db_path: db_path.as_ref().to_path_buf(),
+            max_disk_usage,
+        }
+    }
+
+    /// Returns the size of all the files of the database: the indexes, the tasks and the
+    /// content of the enqueued document additions. The LMDB files never shrink, the pages freed
+    /// by the deletions are reused but stay counted until the index is rebuilt.
+    pub fn usage(&self) -> Result<u64> {
+        let mut usage = 0;
+        for entry in WalkDir::new(&self.db_path) {
+            let entry = entry.map_err(io::Error::from)?;
+            if entry.file_type().is_file() {
+                usage += entry.metadata().map_err(io::Error::from)?.len();
+            }
+        }
+        Ok(usage)
+    }
+
+    /// Returns an error if writing `additional` more bytes would exceed the quota.
+    pub fn check(&self, additional: u64) -> Result<()> {
+        self.check_usage(self.usage()?, additional)
+    }
+
+    /// Returns an error if writing `additional` more bytes to the database, once it uses `usage`
+    /// bytes, would exceed the quota. The usage is computed once by the callers checking many
+    /// writes, such as the chunks of a payload, since walking the database is costly.
+    pub fn check_usage(&self, usage: u64, additional: u64) -> Result<()> {
+        if usage.saturating_add(additional) > self.max_disk_usage {
+            return Err(IndexResolverError::DiskQuotaExceeded {
+                usage,
+                quota: self.max_disk_usage,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn refuses_the_writes_exceeding_the_quota() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(temp.path().join("indexes")).unwrap();
+        std::fs::write(temp.path().join("indexes/data.mdb"), [0; 100]).unwrap();
+
+        let quota = DiskQuota::new(temp.path(), 150);
+        assert_eq!(quota.usage().unwrap(), 100);
+        assert!(quota.check(50).is_ok());
+        assert!(matches!(
+            quota.check(51),
+            Err(IndexResolverError::DiskQuotaExceeded {
+                usage: 100,
+                quota: 150
+            })
+        ));
+    }
+}
@@ -27,6 +27,16 @@ pub enum IndexResolverError {
     Milli(#[from] milli::Error),
     #[error("`{0}` is not a valid index uid. Index uid can be an integer or a string containing only alphanumeric characters, hyphens (-) and underscores (_).")]
     BadlyFormatted(String),
+    #[error("The disk quota of {quota} bytes is exceeded, {usage} bytes are used. Delete indexes, or delete documents and rebuild their index to compact its file, to free some space.")]
+    DiskQuotaExceeded { usage: u64, quota: u64 },
+    #[error("Index template `{0}` not found.")]
+    IndexTemplateNotFound(String),
+    #[error("{0}")]
+    InvalidIndexTemplate(String),
+    #[error("The index is still in use and couldn't be compacted. Retry once its long-running requests, such as exports, are finished.")]
+    IndexInUse,
+    #[error("Index `{0}` is not in the trash. A deleted index can only be restored until the retention period of the trash elapses.")]
+    IndexNotInTrash(String),
 }
 
 impl<T> From<MpscSendError<T>> for IndexResolverError
@@ -59,10 +69,32 @@ impl ErrorCode for IndexResolverError {
             IndexResolverError::IndexAlreadyExists(_) => Code::IndexAlreadyExists,
             IndexResolverError::UnexistingIndex(_) => Code::IndexNotFound,
             IndexResolverError::ExistingPrimaryKey => Code::PrimaryKeyAlreadyPresent,
-            IndexResolverError::Internal(_) => Code::Internal,
+            IndexResolverError::Internal(e) => internal_error_code(e.as_ref()),
             IndexResolverError::UuidAlreadyExists(_) => Code::CreateIndex,
             IndexResolverError::Milli(e) => MilliError(e).error_code(),
             IndexResolverError::BadlyFormatted(_) => Code::InvalidIndexUid,
+            IndexResolverError::DiskQuotaExceeded { .. } => Code::DiskQuotaExceeded,
+            IndexResolverError::IndexTemplateNotFound(_) => Code::IndexTemplateNotFound,
+            IndexResolverError::InvalidIndexTemplate(_) => Code::InvalidIndexTemplate,
+            IndexResolverError::IndexInUse => Code::IndexInUse,
+            IndexResolverError::IndexNotInTrash(_) => Code::IndexNotInTrash,
+        }
+    }
+}
+
+/// Internal errors caused by the exhaustion of the disk or of the memory map of an index get
+/// their own error code, so that the tasks hitting these limits fail with a clear error.
+fn internal_error_code(error: &(dyn std::error::Error + Send + Sync + 'static)) -> Code {
+    const ENOSPC: i32 = 28;
+
+    if let Some(error) = error.downcast_ref::<std::io::Error>() {
+        if error.raw_os_error() == Some(ENOSPC) {
+            return Code::NoSpaceLeftOnDevice;
         }
     }
+    if let Some(milli::heed::Error::Mdb(milli::heed::MdbError::MapFull)) = error.downcast_ref() {
+        return Code::DatabaseSizeLimitReached;
+    }
+
+    Code::Internal
 }
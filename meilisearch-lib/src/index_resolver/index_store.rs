@@ -1,12 +1,16 @@
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use milli::update::IndexerConfig;
+use parking_lot::Mutex;
 use tokio::fs;
 use tokio::sync::RwLock;
 use tokio::task::spawn_blocking;
+use tokio::time::sleep;
 use uuid::Uuid;
 
 use super::error::{IndexResolverError, Result};
@@ -15,19 +19,56 @@ use crate::options::IndexerOpts;
 
 type AsyncMap<K, V> = Arc<RwLock<HashMap<K, V>>>;
 
+/// The name of the compacted copy of an index file, in the directory of the index.
+const COMPACTED_FILE_NAME: &str = "data.mdb.compacted";
+
+/// How long a compaction waits for the requests using an index to release it before replacing
+/// its file. The index can't be opened in the meantime, the other indexes are still served.
+const COMPACTION_RELEASE_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[async_trait::async_trait]
 #[cfg_attr(test, mockall::automock)]
 pub trait IndexStore {
     async fn create(&self, uuid: Uuid) -> Result<Index>;
     async fn get(&self, uuid: Uuid) -> Result<Option<Index>>;
     async fn delete(&self, uuid: Uuid) -> Result<Option<Index>>;
+    /// Replaces the file of the index by a compacted copy, and returns its new size. Returns
+    /// `None` if the index doesn't exist.
+    async fn compact(&self, uuid: Uuid) -> Result<Option<u64>>;
+}
+
+/// Returns the number of index environments that can be kept open at the same time, given the
+/// maximum number of open indexes and the maximum size of their memory maps. It is unlimited
+/// when none of them is set, and at least one index can always be opened.
+pub fn open_indexes_capacity(
+    index_size: usize,
+    max_open_indexes: Option<usize>,
+    max_map_size: Option<usize>,
+) -> Option<usize> {
+    let by_map_size = max_map_size.map(|size| size / index_size.max(1));
+    let capacity = match (max_open_indexes, by_map_size) {
+        (Some(count), Some(by_map_size)) => count.min(by_map_size),
+        (count, by_map_size) => count.or(by_map_size)?,
+    };
+    Some(capacity.max(1))
+}
+
+struct OpenIndex {
+    index: Index,
+    last_access: AtomicU64,
 }
 
 pub struct MapIndexStore {
-    index_store: AsyncMap<Uuid, Index>,
+    index_store: AsyncMap<Uuid, OpenIndex>,
     path: PathBuf,
     index_size: usize,
     indexer_config: Arc<IndexerConfig>,
+    /// The maximum number of index environments kept open, unlimited when `None`.
+    max_open_indexes: Option<usize>,
+    clock: AtomicU64,
+    /// The indexes whose file is being replaced by a compacted copy. Their gate is write-locked
+    /// until the compaction is done, the requests opening them wait on it.
+    compactions: Mutex<HashMap<Uuid, Arc<RwLock<()>>>>,
 }
 
 impl MapIndexStore {
@@ -44,8 +85,131 @@ impl MapIndexStore {
             path,
             index_size,
             indexer_config,
+            max_open_indexes: None,
+            clock: AtomicU64::new(0),
+            compactions: Mutex::new(HashMap::new()),
         })
     }
+
+    /// Close the least recently used indexes when more than `max_open_indexes` are open. Each
+    /// open index holds a memory map of `index_size` bytes and its own file descriptors.
+    pub fn with_max_open_indexes(mut self, max_open_indexes: Option<usize>) -> Self {
+        self.max_open_indexes = max_open_indexes;
+        self
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Closes the least recently used indexes until there is room for a new one. The indexes in
+    /// use elsewhere can't be closed, the budget is then temporarily exceeded.
+    fn evict(&self, open_indexes: &mut HashMap<Uuid, OpenIndex>) {
+        let max_open_indexes = match self.max_open_indexes {
+            Some(max) => max,
+            None => return,
+        };
+
+        while open_indexes.len() >= max_open_indexes {
+            let unused = open_indexes
+                .iter()
+                .filter(|(_, open)| open.index.handle_count() == 1)
+                .min_by_key(|(_, open)| open.last_access.load(Ordering::Relaxed))
+                .map(|(uuid, _)| *uuid);
+
+            match unused.and_then(|uuid| open_indexes.remove(&uuid)) {
+                Some(open) => {
+                    log::debug!("closing the least recently used index {}", open.index.uuid);
+                    open.index.close();
+                }
+                None => {
+                    log::warn!(
+                        "{} indexes are in use, exceeding the budget of {} open indexes",
+                        open_indexes.len(),
+                        max_open_indexes
+                    );
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn open(&self, uuid: Uuid, path: PathBuf) -> Result<Index> {
+        let index_size = self.index_size;
+        let update_handler = self.indexer_config.clone();
+        let index = spawn_blocking(move || -> Result<Index> {
+            let index = Index::open(path, index_size, uuid, update_handler)?;
+            Ok(index)
+        })
+        .await??;
+        Ok(index)
+    }
+
+    fn is_compacting(&self, uuid: Uuid) -> bool {
+        self.compactions.lock().contains_key(&uuid)
+    }
+
+    /// Waits for the compaction of the index to be done, returns whether it was being compacted.
+    async fn wait_compaction(&self, uuid: Uuid) -> bool {
+        let gate = self.compactions.lock().get(&uuid).cloned();
+        match gate {
+            Some(gate) => {
+                drop(gate.read().await);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn insert(&self, open_indexes: &mut HashMap<Uuid, OpenIndex>, uuid: Uuid, index: Index) {
+        let last_access = AtomicU64::new(self.tick());
+        open_indexes.insert(uuid, OpenIndex { index, last_access });
+    }
+
+    /// Waits for the index to be the last handle on its environment, up to
+    /// `COMPACTION_RELEASE_TIMEOUT`. No other handle can be created meanwhile, as the index is
+    /// being compacted and isn't in the store anymore.
+    async fn wait_release(index: &Index) -> bool {
+        let start = Instant::now();
+        while index.handle_count() > 1 {
+            if start.elapsed() > COMPACTION_RELEASE_TIMEOUT {
+                return false;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        true
+    }
+
+    /// Takes the index out of the store, and replaces its file by the compacted copy once it is
+    /// released. Returns the new size of the index.
+    async fn replace_by_compacted(
+        &self,
+        uuid: Uuid,
+        index: Index,
+        path: PathBuf,
+        compacted: PathBuf,
+    ) -> Result<u64> {
+        self.index_store.write().await.remove(&uuid);
+        if !Self::wait_release(&index).await {
+            self.insert(&mut *self.index_store.write().await, uuid, index);
+            fs::remove_file(compacted).await?;
+            return Err(IndexResolverError::IndexInUse);
+        }
+
+        let data = path.join("data.mdb");
+        spawn_blocking(move || -> Result<()> {
+            index.close_and_wait();
+            std::fs::rename(compacted, data)?;
+            Ok(())
+        })
+        .await??;
+
+        let index = self.open(uuid, path).await?;
+        let size = index.size();
+        self.insert(&mut *self.index_store.write().await, uuid, index);
+
+        Ok(size)
+    }
 }
 
 #[async_trait::async_trait]
@@ -55,54 +219,180 @@ impl IndexStore for MapIndexStore {
         // ensure that another db is not created at the same time.
         let mut lock = self.index_store.write().await;
 
-        if let Some(index) = lock.get(&uuid) {
-            return Ok(index.clone());
+        if let Some(open) = lock.get(&uuid) {
+            return Ok(open.index.clone());
         }
         let path = self.path.join(format!("{}", uuid));
         if path.exists() {
             return Err(IndexResolverError::UuidAlreadyExists(uuid));
         }
 
-        let index_size = self.index_size;
-        let update_handler = self.indexer_config.clone();
-        let index = spawn_blocking(move || -> Result<Index> {
-            let index = Index::open(path, index_size, uuid, update_handler)?;
-            Ok(index)
-        })
-        .await??;
-
-        lock.insert(uuid, index.clone());
+        self.evict(&mut lock);
+        let index = self.open(uuid, path).await?;
+        self.insert(&mut lock, uuid, index.clone());
 
         Ok(index)
     }
 
     async fn get(&self, uuid: Uuid) -> Result<Option<Index>> {
-        let guard = self.index_store.read().await;
-        match guard.get(&uuid) {
-            Some(index) => Ok(Some(index.clone())),
-            None => {
-                // drop the guard here so we can perform the write after without deadlocking;
-                drop(guard);
-                let path = self.path.join(format!("{}", uuid));
-                if !path.exists() {
-                    return Ok(None);
-                }
+        loop {
+            if let Some(open) = self.index_store.read().await.get(&uuid) {
+                open.last_access.store(self.tick(), Ordering::Relaxed);
+                return Ok(Some(open.index.clone()));
+            }
+            // The index is served again once its file is replaced.
+            if self.wait_compaction(uuid).await {
+                continue;
+            }
+
+            let path = self.path.join(format!("{}", uuid));
+            if !path.exists() {
+                return Ok(None);
+            }
 
-                let index_size = self.index_size;
-                let update_handler = self.indexer_config.clone();
-                let index =
-                    spawn_blocking(move || Index::open(path, index_size, uuid, update_handler))
-                        .await??;
-                self.index_store.write().await.insert(uuid, index.clone());
-                Ok(Some(index))
+            // The index is opened while holding the lock, so that it is only opened once.
+            let mut lock = self.index_store.write().await;
+            if let Some(open) = lock.get(&uuid) {
+                return Ok(Some(open.index.clone()));
             }
+            if self.is_compacting(uuid) {
+                continue;
+            }
+
+            self.evict(&mut lock);
+            let index = self.open(uuid, path).await?;
+            self.insert(&mut lock, uuid, index.clone());
+            return Ok(Some(index));
         }
     }
 
     async fn delete(&self, uuid: Uuid) -> Result<Option<Index>> {
         let db_path = self.path.join(format!("{}", uuid));
         fs::remove_dir_all(db_path).await?;
-        let index = self.index_store.write().await.remove(&uuid);
+        let index = self
+            .index_store
+            .write()
+            .await
+            .remove(&uuid)
+            .map(|open| open.index);
         Ok(index)
     }
+
+    async fn compact(&self, uuid: Uuid) -> Result<Option<u64>> {
+        let index = match self.get(uuid).await? {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let path = self.path.join(format!("{}", uuid));
+        let compacted = path.join(COMPACTED_FILE_NAME);
+
+        // The copy of an interrupted compaction is overwritten.
+        if compacted.exists() {
+            fs::remove_file(&compacted).await?;
+        }
+
+        // The copy is made while the index is still served, the tasks are processed one at a time
+        // so it can't be modified in the meantime.
+        let copy = index.clone();
+        let destination = compacted.clone();
+        if let Err(e) = spawn_blocking(move || copy.compact_to(destination)).await? {
+            let _ = fs::remove_file(&compacted).await;
+            return Err(e.into());
+        }
+
+        // Only this index is unavailable while its file is replaced, the store isn't locked.
+        let gate = Arc::new(RwLock::new(()));
+        let closed = gate.clone().write_owned().await;
+        self.compactions.lock().insert(uuid, gate);
+        let replaced = self
+            .replace_by_compacted(uuid, index, path, compacted)
+            .await;
+        self.compactions.lock().remove(&uuid);
+        drop(closed);
+
+        replaced.map(Some)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn open_indexes_capacity_from_the_budget() {
+        let gib = 1024 * 1024 * 1024;
+        assert_eq!(open_indexes_capacity(gib, None, None), None);
+        assert_eq!(open_indexes_capacity(gib, Some(100), None), Some(100));
+        assert_eq!(open_indexes_capacity(gib, None, Some(10 * gib)), Some(10));
+        assert_eq!(open_indexes_capacity(gib, Some(5), Some(10 * gib)), Some(5));
+        // at least one index can be opened.
+        assert_eq!(open_indexes_capacity(gib, Some(0), Some(gib / 2)), Some(1));
+    }
+
+    #[actix_rt::test]
+    async fn closes_the_least_recently_used_indexes() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = MapIndexStore::new(temp.path(), 4096 * 100, &IndexerOpts::default())
+            .unwrap()
+            .with_max_open_indexes(Some(2));
+
+        let uuids: Vec<_> = (0..3).map(|_| Uuid::new_v4()).collect();
+        for uuid in &uuids[..2] {
+            store.create(*uuid).await.unwrap();
+        }
+        // the first index becomes the most recently used.
+        store.get(uuids[0]).await.unwrap().unwrap();
+        store.create(uuids[2]).await.unwrap();
+
+        let open = store.index_store.read().await;
+        assert_eq!(open.len(), 2);
+        assert!(open.contains_key(&uuids[0]));
+        assert!(!open.contains_key(&uuids[1]));
+        drop(open);
+
+        // a closed index is reopened on access.
+        assert!(store.get(uuids[1]).await.unwrap().is_some());
+    }
+
+    #[actix_rt::test]
+    async fn compacts_an_index() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = MapIndexStore::new(temp.path(), 4096 * 100, &IndexerOpts::default()).unwrap();
+
+        let uuid = Uuid::new_v4();
+        let size = store.create(uuid).await.unwrap().size();
+
+        let compacted = store.compact(uuid).await.unwrap().unwrap();
+        assert!(compacted <= size);
+        assert!(!temp
+            .path()
+            .join(format!("indexes/{}/{}", uuid, COMPACTED_FILE_NAME))
+            .exists());
+        // the compacted index is served in place of the previous one.
+        assert!(store.get(uuid).await.unwrap().is_some());
+
+        assert!(store.compact(Uuid::new_v4()).await.unwrap().is_none());
+    }
+
+    #[actix_rt::test]
+    async fn serves_the_other_indexes_during_a_compaction() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = MapIndexStore::new(temp.path(), 4096 * 100, &IndexerOpts::default()).unwrap();
+
+        let uuid = Uuid::new_v4();
+        let held = store.create(uuid).await.unwrap();
+        let other = Uuid::new_v4();
+        let (compacted, ()) = tokio::join!(store.compact(uuid), async {
+            // the compaction waits for `held` to be released, out of the store.
+            while !store.is_compacting(uuid) || store.index_store.read().await.contains_key(&uuid) {
+                sleep(Duration::from_millis(10)).await;
+            }
+            store.create(other).await.unwrap();
+            assert!(store.get(other).await.unwrap().is_some());
+            drop(held);
+        });
+
+        assert!(compacted.unwrap().is_some());
+        assert!(store.get(uuid).await.unwrap().is_some());
+    }
 }
@@ -30,6 +30,8 @@ pub trait IndexMetaStore: Sized {
     async fn delete(&self, uid: String) -> Result<Option<IndexMeta>>;
     async fn list(&self) -> Result<Vec<(String, IndexMeta)>>;
     async fn insert(&self, name: String, meta: IndexMeta) -> Result<()>;
+    /// Move the entry of `uid` to `new_uid`, failing if `new_uid` is already taken.
+    async fn rename(&self, uid: String, new_uid: String) -> Result<()>;
     async fn snapshot(&self, path: PathBuf) -> Result<HashSet<Uuid>>;
     async fn get_size(&self) -> Result<u64>;
     async fn dump(&self, path: PathBuf) -> Result<()>;
@@ -111,6 +113,26 @@ impl HeedMetaStore {
         Ok(())
     }
 
+    fn rename(&self, uid: String, new_uid: String) -> Result<()> {
+        let env = self.env.clone();
+        let db = self.db;
+        let mut txn = env.write_txn()?;
+
+        let meta = match db.get(&txn, &uid)? {
+            Some(meta) => meta,
+            None => return Err(IndexResolverError::UnexistingIndex(uid)),
+        };
+
+        if db.get(&txn, &new_uid)?.is_some() {
+            return Err(IndexResolverError::IndexAlreadyExists(new_uid));
+        }
+
+        db.delete(&mut txn, &uid)?;
+        db.put(&mut txn, &new_uid, &meta)?;
+        txn.commit()?;
+        Ok(())
+    }
+
     fn snapshot(&self, mut path: PathBuf) -> Result<HashSet<Uuid>> {
         // Write transaction to acquire a lock on the database.
         let txn = self.env.write_txn()?;
@@ -202,6 +224,11 @@ impl IndexMetaStore for HeedMetaStore {
         tokio::task::spawn_blocking(move || this.insert(name, meta)).await?
     }
 
+    async fn rename(&self, uid: String, new_uid: String) -> Result<()> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.rename(uid, new_uid)).await?
+    }
+
     async fn snapshot(&self, path: PathBuf) -> Result<HashSet<Uuid>> {
         let this = self.clone();
         tokio::task::spawn_blocking(move || this.snapshot(path)).await?
@@ -1,11 +1,16 @@
+pub mod disk_quota;
 pub mod error;
 pub mod index_store;
 pub mod meta_store;
+pub mod templates;
+pub mod trash;
 
 use std::convert::{TryFrom, TryInto};
 use std::path::Path;
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::Arc;
 
+use disk_quota::DiskQuota;
 use error::{IndexResolverError, Result};
 use index_store::{IndexStore, MapIndexStore};
 use meilisearch_error::ResponseError;
@@ -13,15 +18,22 @@ use meta_store::{HeedMetaStore, IndexMetaStore};
 use milli::heed::Env;
 use milli::update::{DocumentDeletionResult, IndexerConfig};
 use serde::{Deserialize, Serialize};
+use templates::HeedTemplateStore;
 use time::OffsetDateTime;
 use tokio::sync::oneshot;
 use tokio::task::spawn_blocking;
+use trash::HeedIndexTrash;
 use uuid::Uuid;
 
 use crate::index::{error::Result as IndexResult, Index};
+use crate::index_controller::read_session::ReadSessions;
+use crate::index_controller::replication::RetainedUpdateFiles;
+use crate::index_controller::search_cache::SearchCache;
+use crate::index_controller::timeseries::Timeseries;
 use crate::options::IndexerOpts;
 use crate::tasks::batch::Batch;
 use crate::tasks::task::{DocumentDeletion, Job, Task, TaskContent, TaskEvent, TaskId, TaskResult};
+use crate::tasks::watch::{indexing_percentage, TaskWatcher};
 use crate::tasks::TaskPerformer;
 use crate::update_file_store::UpdateFileStore;
 
@@ -36,15 +48,16 @@ pub type HardStateIndexResolver = IndexResolver<HeedMetaStore, MapIndexStore>;
 pub struct IndexUid(#[cfg_attr(test, proptest(regex("[a-zA-Z0-9_-]{1,400}")))] String);
 
 pub fn create_index_resolver(
-    path: impl AsRef<Path>,
-    index_size: usize,
-    indexer_opts: &IndexerOpts,
+    index_store: MapIndexStore,
     meta_env: Arc<milli::heed::Env>,
     file_store: UpdateFileStore,
+    timeseries: Arc<Timeseries>,
+    watcher: TaskWatcher,
 ) -> anyhow::Result<HardStateIndexResolver> {
     let uuid_store = HeedMetaStore::new(meta_env)?;
-    let index_store = MapIndexStore::new(&path, index_size, indexer_opts)?;
-    Ok(IndexResolver::new(uuid_store, index_store, file_store))
+    Ok(IndexResolver::new(uuid_store, index_store, file_store)
+        .with_timeseries(timeseries)
+        .with_watcher(watcher))
 }
 
 impl IndexUid {
@@ -98,8 +111,19 @@ where
     I: IndexStore + Send + Sync + 'static,
 {
     async fn process_batch(&self, mut batch: Batch) -> Batch {
+        if let Err(error) = self.check_batch_disk_quota(&batch).await {
+            let error = ResponseError::from(error);
+            for task in &mut batch.tasks {
+                task.events.push(TaskEvent::Failed {
+                    error: error.clone(),
+                    timestamp: OffsetDateTime::now_utc(),
+                });
+            }
+            return batch;
+        }
+
         // If a batch contains multiple tasks, then it must be a document addition batch
-        if let Some(Task {
+        let batch = if let Some(Task {
             content: TaskContent::DocumentAddition { .. },
             ..
         }) = batch.tasks.first()
@@ -132,7 +156,19 @@ where
                 }
             }
             batch
+        };
+
+        // The cached searches are invalidated before the tasks are reported as finished.
+        if let Some(ref search_cache) = self.search_cache {
+            for task in &batch.tasks {
+                search_cache.invalidate(&task.index_uid);
+                if let TaskContent::IndexRename { new_uid } = &task.content {
+                    search_cache.invalidate(new_uid);
+                }
+            }
         }
+
+        batch
     }
 
     async fn process_job(&self, job: Job) {
@@ -142,11 +178,22 @@ where
     async fn finish(&self, batch: &Batch) {
         for task in &batch.tasks {
             if let Some(content_uuid) = task.get_content_uuid() {
+                let content_uuid = match self.retained_update_files {
+                    Some(ref retained) => match retained.retain(content_uuid) {
+                        Some(evicted) => evicted,
+                        None => continue,
+                    },
+                    None => content_uuid,
+                };
                 if let Err(e) = self.file_store.delete(content_uuid).await {
                     log::error!("error deleting update file: {}", e);
                 }
             }
         }
+
+        if let Some(ref timeseries) = self.timeseries {
+            self.record_activity(timeseries, batch).await;
+        }
     }
 }
 
@@ -154,6 +201,14 @@ pub struct IndexResolver<U, I> {
     index_uuid_store: U,
     index_store: I,
     file_store: UpdateFileStore,
+    timeseries: Option<Arc<Timeseries>>,
+    search_cache: Option<Arc<SearchCache>>,
+    disk_quota: Option<DiskQuota>,
+    retained_update_files: Option<Arc<RetainedUpdateFiles>>,
+    watcher: Option<TaskWatcher>,
+    templates: Option<HeedTemplateStore>,
+    trash: Option<HeedIndexTrash>,
+    read_sessions: Option<Arc<ReadSessions>>,
 }
 
 impl IndexResolver<HeedMetaStore, MapIndexStore> {
@@ -164,6 +219,7 @@ impl IndexResolver<HeedMetaStore, MapIndexStore> {
         env: Arc<Env>,
         indexer_opts: &IndexerOpts,
     ) -> anyhow::Result<()> {
+        HeedTemplateStore::load_dump(&src, env.clone())?;
         HeedMetaStore::load_dump(&src, env)?;
         let indexes_path = src.as_ref().join("indexes");
         let indexes = indexes_path.read_dir()?;
@@ -186,6 +242,156 @@ where
             index_uuid_store,
             index_store,
             file_store,
+            timeseries: None,
+            search_cache: None,
+            disk_quota: None,
+            retained_update_files: None,
+            watcher: None,
+            templates: None,
+            trash: None,
+            read_sessions: None,
+        }
+    }
+
+    /// Record the indexing activity of the processed batches in `timeseries`.
+    pub fn with_timeseries(mut self, timeseries: Arc<Timeseries>) -> Self {
+        self.timeseries = Some(timeseries);
+        self
+    }
+
+    /// Invalidate the cached searches of the indexes the processed batches are committed to.
+    pub fn with_search_cache(mut self, search_cache: Arc<SearchCache>) -> Self {
+        self.search_cache = Some(search_cache);
+        self
+    }
+
+    /// Refuse the tasks that would make the database grow once `disk_quota` is exceeded.
+    pub fn with_disk_quota(mut self, disk_quota: DiskQuota) -> Self {
+        self.disk_quota = Some(disk_quota);
+        self
+    }
+
+    /// Keep the update files of the processed tasks in `retained_update_files` instead of
+    /// deleting them, so that the replicas can still fetch them.
+    pub fn with_retained_update_files(mut self, retained: Arc<RetainedUpdateFiles>) -> Self {
+        self.retained_update_files = Some(retained);
+        self
+    }
+
+    /// Report the progress of the document additions to `watcher`.
+    pub fn with_watcher(mut self, watcher: TaskWatcher) -> Self {
+        self.watcher = Some(watcher);
+        self
+    }
+
+    /// Apply the settings of the matching index template to the indexes when they get created.
+    pub fn with_templates(mut self, templates: HeedTemplateStore) -> Self {
+        self.templates = Some(templates);
+        self
+    }
+
+    /// Move the deleted indexes to `trash` instead of deleting their files, so that they can be
+    /// restored until they are purged.
+    pub fn with_trash(mut self, trash: HeedIndexTrash) -> Self {
+        self.trash = Some(trash);
+        self
+    }
+
+    /// Close the `read_sessions` of the indexes before compacting them, each session holding its
+    /// index open.
+    pub fn with_read_sessions(mut self, read_sessions: Arc<ReadSessions>) -> Self {
+        self.read_sessions = Some(read_sessions);
+        self
+    }
+
+    /// Returns an error if writing `additional` more bytes would exceed the disk quota.
+    pub async fn check_disk_quota(&self, additional: u64) -> Result<()> {
+        match self.disk_quota.clone() {
+            Some(disk_quota) => spawn_blocking(move || disk_quota.check(additional)).await?,
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the disk quota along with the current usage of the database, `None` when there is
+    /// no quota.
+    pub async fn disk_quota_usage(&self) -> Result<Option<(DiskQuota, u64)>> {
+        match self.disk_quota.clone() {
+            Some(disk_quota) => {
+                spawn_blocking(move || -> Result<_> {
+                    let usage = disk_quota.usage()?;
+                    Ok(Some((disk_quota, usage)))
+                })
+                .await?
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn check_batch_disk_quota(&self, batch: &Batch) -> Result<()> {
+        // The deletions and the rebuilds are always processed, so that some space can be freed
+        // once the quota is exceeded. The document deletions only free space once their index
+        // is rebuilt.
+        let grows_the_database = batch.tasks.iter().any(|task| {
+            matches!(
+                task.content,
+                TaskContent::DocumentAddition { .. }
+                    | TaskContent::SettingsUpdate { .. }
+                    | TaskContent::IndexCreation { .. }
+            )
+        });
+
+        if grows_the_database {
+            self.check_disk_quota(0).await
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn record_activity(&self, timeseries: &Timeseries, batch: &Batch) {
+        // The tasks of a batch all belong to the same index and share the same result.
+        let task = match batch.tasks.first() {
+            Some(task) => task,
+            None => return,
+        };
+        let result = match task.events.last() {
+            Some(TaskEvent::Succeded { result, .. }) => result,
+            _ => return,
+        };
+
+        let indexed_documents = match (&task.content, result) {
+            (TaskContent::IndexDeletion, _) => {
+                timeseries.remove(&task.index_uid);
+                return;
+            }
+            (TaskContent::IndexRename { new_uid }, _) => {
+                timeseries.rename(&task.index_uid, new_uid);
+                return;
+            }
+            (
+                _,
+                TaskResult::DocumentAddition {
+                    indexed_documents, ..
+                },
+            ) => *indexed_documents,
+            (_, TaskResult::DocumentDeletion { .. } | TaskResult::ClearAll { .. }) => 0,
+            (
+                _,
+                TaskResult::IndexRebuild { .. } | TaskResult::IndexPurge { .. } | TaskResult::Other,
+            ) => return,
+        };
+
+        let number_of_documents = match self.get_index(task.index_uid.as_str().to_string()).await {
+            Ok(index) => spawn_blocking(move || index.stats()).await,
+            Err(_) => return,
+        };
+        match number_of_documents {
+            Ok(Ok(stats)) => timeseries.record_indexing(
+                &task.index_uid,
+                indexed_documents,
+                stats.number_of_documents,
+            ),
+            Ok(Err(e)) => log::error!("error while recording the indexing activity: {}", e),
+            Err(e) => log::error!("error while recording the indexing activity: {}", e),
         }
     }
 
@@ -242,12 +448,28 @@ where
                 };
 
                 let file_store = self.file_store.clone();
+                let watcher = self.watcher.clone();
+                let tasks = batch.tasks.clone();
                 let result = spawn_blocking(move || {
+                    let last_percentage = AtomicU8::new(0);
+                    let progress = |step| {
+                        let percentage = indexing_percentage(&step);
+                        if let Some(ref watcher) = watcher {
+                            // Only notify the watchers when the percentage changes.
+                            if last_percentage.swap(percentage, Ordering::Relaxed) != percentage {
+                                for task in &tasks {
+                                    watcher.notify_progress(task, percentage);
+                                }
+                            }
+                        }
+                    };
+
                     index.update_documents(
                         method,
                         primary_key,
                         file_store,
                         content_uuids.into_iter(),
+                        progress,
                     )
                 })
                 .await;
@@ -255,9 +477,7 @@ where
                 let event = match result {
                     Ok(Ok(result)) => TaskEvent::Succeded {
                         timestamp: OffsetDateTime::now_utc(),
-                        result: TaskResult::DocumentAddition {
-                            indexed_documents: result.indexed_documents,
-                        },
+                        result: TaskResult::from(result),
                     },
                     Ok(Err(e)) => TaskEvent::Failed {
                         timestamp: OffsetDateTime::now_utc(),
@@ -321,7 +541,10 @@ where
                 Ok(TaskResult::Other)
             }
             TaskContent::IndexDeletion => {
-                let index = self.delete_index(index_uid.into_inner()).await?;
+                let index = match self.trash {
+                    Some(ref trash) => self.trash_index(trash, index_uid.into_inner()).await?,
+                    None => self.delete_index(index_uid.into_inner()).await?,
+                };
 
                 let deleted_documents = spawn_blocking(move || -> IndexResult<u64> {
                     Ok(index.stats()?.number_of_documents)
@@ -350,6 +573,61 @@ where
 
                 Ok(TaskResult::Other)
             }
+            TaskContent::IndexRename { new_uid } => {
+                self.index_uuid_store
+                    .rename(index_uid.into_inner(), new_uid.as_str().to_string())
+                    .await?;
+
+                Ok(TaskResult::Other)
+            }
+            TaskContent::IndexRebuild { reindex } => {
+                let uid = index_uid.into_inner();
+                let index = self.get_index(uid.clone()).await?;
+                let uuid = index.uuid();
+                let size = index.size();
+
+                if *reindex {
+                    spawn_blocking(move || index.reindex()).await??;
+                } else {
+                    drop(index);
+                }
+                if let Some(ref read_sessions) = self.read_sessions {
+                    read_sessions.close_index(&uid);
+                }
+
+                let compacted = self
+                    .index_store
+                    .compact(uuid)
+                    .await?
+                    .ok_or(IndexResolverError::UnexistingIndex(uid))?;
+
+                Ok(TaskResult::IndexRebuild {
+                    reclaimed_bytes: size.saturating_sub(compacted),
+                })
+            }
+            TaskContent::IndexRestore => {
+                self.restore_index(index_uid.into_inner()).await?;
+
+                Ok(TaskResult::Other)
+            }
+            TaskContent::IndexPurge { index_uuid } => {
+                let trashed = match self.trash {
+                    Some(ref trash) => {
+                        trash
+                            .take_trashed(index_uid.into_inner(), Some(*index_uuid))
+                            .await?
+                    }
+                    None => None,
+                };
+
+                // The index may have been restored in the meantime.
+                let reclaimed_bytes = match trashed {
+                    Some(trashed) => self.purge_index(trashed.meta.uuid).await?,
+                    None => 0,
+                };
+
+                Ok(TaskResult::IndexPurge { reclaimed_bytes })
+            }
         }
     }
 
@@ -376,10 +654,15 @@ where
     }
 
     pub async fn dump(&self, path: impl AsRef<Path>) -> Result<()> {
-        for (_, index) in self.list().await? {
-            index.dump(&path)?;
+        for uid in self.list().await? {
+            if let Some(index) = self.find_index(uid).await? {
+                index.dump(&path)?;
+            }
         }
         self.index_uuid_store.dump(path.as_ref().to_owned()).await?;
+        if let Some(ref templates) = self.templates {
+            templates.dump_templates(path.as_ref().to_owned()).await?;
+        }
         Ok(())
     }
 
@@ -392,7 +675,7 @@ where
                 match self
                     .index_uuid_store
                     .insert(
-                        uid,
+                        uid.clone(),
                         IndexMeta {
                             uuid,
                             creation_task_id,
@@ -410,12 +693,42 @@ where
                         }
                         Err(e)
                     }
-                    Ok(()) => Ok(index),
+                    Ok(()) => match self.apply_template(&index, uid.clone()).await {
+                        Ok(()) => Ok(index),
+                        Err(e) => {
+                            // The index is removed so that its creation can be retried once the
+                            // template is fixed.
+                            if let Err(e) = self.delete_index(uid).await {
+                                log::error!("Error while deleting index: {:?}", e);
+                            }
+                            Err(e)
+                        }
+                    },
                 }
             }
         }
     }
 
+    /// Applies the settings of the most specific template matching `uid` to its new `index`.
+    async fn apply_template(&self, index: &Index, uid: String) -> Result<()> {
+        let templates = match self.templates {
+            Some(ref templates) => templates,
+            None => return Ok(()),
+        };
+
+        if let Some(template) = templates.find_template(uid.clone()).await? {
+            log::info!(
+                "Applying the `{}` template to the `{}` index.",
+                template.name,
+                uid
+            );
+            let index = index.clone();
+            spawn_blocking(move || index.update_settings(&template.settings.check())).await??;
+        }
+
+        Ok(())
+    }
+
     /// Get or create an index with name `uid`.
     pub async fn get_or_create_index(&self, uid: IndexUid, task_id: TaskId) -> Result<Index> {
         match self.create_index(uid, task_id).await {
@@ -425,20 +738,20 @@ where
         }
     }
 
-    pub async fn list(&self) -> Result<Vec<(String, Index)>> {
+    /// Returns the uids of all the indexes. The indexes aren't opened, they are meant to be
+    /// opened one at a time with `find_index` so that they can be closed in between.
+    pub async fn list(&self) -> Result<Vec<String>> {
         let uuids = self.index_uuid_store.list().await?;
-        let mut indexes = Vec::new();
-        for (name, IndexMeta { uuid, .. }) in uuids {
-            match self.index_store.get(uuid).await? {
-                Some(index) => indexes.push((name, index)),
-                None => {
-                    // we found an unexisting index, we remove it from the uuid store
-                    let _ = self.index_uuid_store.delete(name).await;
-                }
-            }
-        }
+        Ok(uuids.into_iter().map(|(name, _)| name).collect())
+    }
 
-        Ok(indexes)
+    /// Returns the index `uid`, or `None` if it doesn't exist anymore.
+    pub async fn find_index(&self, uid: String) -> Result<Option<Index>> {
+        match self.get_index(uid).await {
+            Ok(index) => Ok(Some(index)),
+            Err(IndexResolverError::UnexistingIndex(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 
     pub async fn delete_index(&self, uid: String) -> Result<Index> {
@@ -454,6 +767,70 @@ where
         }
     }
 
+    /// Moves the index to the trash, the index it replaces in the trash is purged.
+    async fn trash_index(&self, trash: &HeedIndexTrash, uid: String) -> Result<Index> {
+        // The index is fetched before its uid is deleted, so that a missing index is left as is.
+        let uuid = match self.index_uuid_store.get(uid.clone()).await? {
+            (_, Some(meta)) => meta.uuid,
+            (uid, None) => return Err(IndexResolverError::UnexistingIndex(uid)),
+        };
+        let index = self
+            .index_store
+            .get(uuid)
+            .await?
+            .ok_or_else(|| IndexResolverError::UnexistingIndex(uid.clone()))?;
+        let meta = self
+            .index_uuid_store
+            .delete(uid.clone())
+            .await?
+            .ok_or_else(|| IndexResolverError::UnexistingIndex(uid.clone()))?;
+
+        if let Some(replaced) = trash.trash_index(uid, meta).await? {
+            self.purge_index(replaced.meta.uuid).await?;
+        }
+
+        Ok(index)
+    }
+
+    /// Moves the index back from the trash, failing if an index was created under its uid since.
+    async fn restore_index(&self, uid: String) -> Result<()> {
+        let trash = self
+            .trash
+            .as_ref()
+            .ok_or_else(|| IndexResolverError::IndexNotInTrash(uid.clone()))?;
+        let trashed = trash
+            .get_trashed(uid.clone())
+            .await?
+            .ok_or_else(|| IndexResolverError::IndexNotInTrash(uid.clone()))?;
+
+        // The files of the trashed indexes aren't kept by the snapshots and the dumps.
+        if self.index_store.get(trashed.meta.uuid).await?.is_none() {
+            trash.take_trashed(uid.clone(), None).await?;
+            return Err(IndexResolverError::IndexNotInTrash(uid));
+        }
+
+        let uuid = trashed.meta.uuid;
+        self.index_uuid_store
+            .insert(uid.clone(), trashed.meta)
+            .await?;
+        trash.take_trashed(uid, Some(uuid)).await?;
+
+        Ok(())
+    }
+
+    /// Deletes the files of the index, returns their size.
+    async fn purge_index(&self, uuid: Uuid) -> Result<u64> {
+        let size = match self.index_store.get(uuid).await? {
+            Some(index) => index.size(),
+            None => return Ok(0),
+        };
+        if let Some(index) = self.index_store.delete(uuid).await? {
+            index.close();
+        }
+
+        Ok(size)
+    }
+
     pub async fn get_index(&self, uid: String) -> Result<Index> {
         match self.index_uuid_store.get(uid).await? {
             (name, Some(IndexMeta { uuid, .. })) => {
@@ -488,14 +865,14 @@ mod test {
 
     use super::*;
 
-    use futures::future::ok;
-    use milli::update::{DocumentAdditionResult, IndexDocumentsMethod};
+    use futures::future::{ok, ready};
+    use milli::update::IndexDocumentsMethod;
     use nelson::Mocker;
     use proptest::prelude::*;
 
     use crate::index::{
         error::{IndexError, Result as IndexResult},
-        Checked, IndexMeta, IndexStats, Settings,
+        Checked, DocumentAdditionResult, IndexMeta, IndexStats, Settings,
     };
     use index_store::MockIndexStore;
     use meta_store::MockIndexMetaStore;
@@ -518,7 +895,7 @@ mod test {
                 match &task.content {
                     TaskContent::DocumentAddition{primary_key, ..} => {
                         let result = move || if !index_op_fails {
-                            Ok(DocumentAdditionResult { indexed_documents: any_int, number_of_documents: any_int })
+                            Ok(DocumentAdditionResult { indexed_documents: any_int, number_of_documents: any_int, skipped_documents: 0 })
                         } else {
                             // return this error because it's easy to generate...
                             Err(IndexError::DocumentNotFound("a doc".into()))
@@ -566,6 +943,23 @@ mod test {
                             .times(index_exists as usize)
                             .then(move |_| ());
                     }
+                    TaskContent::IndexRename { .. }
+                    | TaskContent::IndexRestore
+                    | TaskContent::IndexPurge { .. } => (),
+                    TaskContent::IndexRebuild { reindex } => {
+                        let result = move || if !index_op_fails {
+                            Ok(())
+                        } else {
+                            // return this error because it's easy to generate...
+                            Err(IndexError::DocumentNotFound("a doc".into()))
+                        };
+                        mocker.when::<(), Uuid>("uuid").then(move |_| uuid);
+                        mocker.when::<(), u64>("size").then(|_| 4096);
+                        if *reindex {
+                            mocker.when::<(), IndexResult<()>>("reindex")
+                                .then(move |_| result());
+                        }
+                    }
                     TaskContent::IndexUpdate { primary_key }
                     | TaskContent::IndexCreation { primary_key } => {
                         if primary_key.is_some() {
@@ -582,7 +976,7 @@ mod test {
                 }
 
                 mocker.when::<(), IndexResult<IndexStats>>("stats")
-            .then(|()| Ok(IndexStats { size: 0, number_of_documents: 0, is_indexing: Some(false), field_distribution: BTreeMap::new() }));
+            .then(|()| Ok(IndexStats { size: 0, number_of_documents: 0, is_indexing: Some(false), field_distribution: BTreeMap::new(), document_compression: None, storage: None, last_applied_task: None }));
 
                 let index = Index::mock(mocker);
 
@@ -607,6 +1001,22 @@ mod test {
                     }
                     // if index already exists, create index will return an error
                     TaskContent::IndexCreation { .. } if index_exists => (),
+                    // renaming an index only updates the meta store
+                    TaskContent::IndexRename { .. } => (),
+                    // without a trash, an index can't be restored and there is nothing to purge
+                    TaskContent::IndexRestore | TaskContent::IndexPurge { .. } => (),
+                    // The index exists and get should be called, then the rebuilt index is compacted
+                    TaskContent::IndexRebuild { .. } if index_exists => {
+                        index_store
+                            .expect_get()
+                            .once()
+                            .withf(move |&found| found == uuid)
+                            .returning(move |_| Box::pin(ok(Some(index.clone()))));
+                        index_store
+                            .expect_compact()
+                            .withf(move |&found| found == uuid)
+                            .returning(|_| Box::pin(ok(Some(1024))));
+                    },
                     // The index exists and get should be called
                     _ if index_exists => {
                         index_store
@@ -632,6 +1042,18 @@ mod test {
                     .withf(move |_, _| !index_exists)
                     .returning(|_, _| Box::pin(ok(())));
 
+                uuid_store
+                    .expect_rename()
+                    .times(matches!(task.content, TaskContent::IndexRename { .. }) as usize)
+                    .returning(move |uid, _| {
+                        let result = if index_exists {
+                            Ok(())
+                        } else {
+                            Err(IndexResolverError::UnexistingIndex(uid))
+                        };
+                        Box::pin(ready(result))
+                    });
+
                 uuid_store
                     .expect_delete()
                     .times(matches!(task.content, TaskContent::IndexDeletion) as usize)
@@ -647,14 +1069,17 @@ mod test {
                 // Test for some expected output scenarios:
                 // Index creation and deletion cannot fail because of a failed index op, since they
                 // don't perform index ops.
-                if index_op_fails && !matches!(task.content, TaskContent::IndexDeletion | TaskContent::IndexCreation { primary_key: None } | TaskContent::IndexUpdate { primary_key: None })
+                if index_op_fails && !matches!(task.content, TaskContent::IndexDeletion | TaskContent::IndexCreation { primary_key: None } | TaskContent::IndexUpdate { primary_key: None } | TaskContent::IndexRename { .. } | TaskContent::IndexRebuild { reindex: false } | TaskContent::IndexPurge { .. })
                     || (index_exists && matches!(task.content, TaskContent::IndexCreation { .. }))
+                    || matches!(task.content, TaskContent::IndexRestore)
                     || (!index_exists && matches!(task.content, TaskContent::IndexDeletion
                                                                 | TaskContent::DocumentDeletion(_)
                                                                 | TaskContent::SettingsUpdate { is_deletion: true, ..}
                                                                 | TaskContent::SettingsUpdate { allow_index_creation: false, ..}
                                                                 | TaskContent::DocumentAddition { allow_index_creation: false, ..}
-                                                                | TaskContent::IndexUpdate { .. } ))
+                                                                | TaskContent::IndexUpdate { .. }
+                                                                | TaskContent::IndexRename { .. }
+                                                                | TaskContent::IndexRebuild { .. } ))
                 {
                     assert!(matches!(result.tasks[0].events.last().unwrap(), TaskEvent::Failed { .. }), "{:?}", result);
                 } else {
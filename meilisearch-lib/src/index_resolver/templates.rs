@@ -0,0 +1,276 @@
+use std::fs::{create_dir_all, File};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use milli::heed::types::{SerdeJson, Str};
+use milli::heed::{Database, Env};
+use serde::{Deserialize, Serialize};
+
+use super::error::{IndexResolverError, Result};
+use crate::index::{Settings, Unchecked};
+
+const TEMPLATES_DB_PATH: &str = "index_templates";
+
+/// A bundle of settings applied to the indexes created with an uid matching `index_pattern`, in
+/// which `*` matches any sequence of characters.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexTemplate {
+    pub name: String,
+    pub index_pattern: String,
+    pub settings: Settings<Unchecked>,
+}
+
+impl IndexTemplate {
+    pub fn matches(&self, uid: &str) -> bool {
+        pattern_matches(&self.index_pattern, uid)
+    }
+
+    /// The number of literal characters of the pattern, the most specific of the templates
+    /// matching an index uid is applied to the index.
+    fn specificity(&self) -> usize {
+        self.index_pattern.chars().filter(|c| *c != '*').count()
+    }
+
+    fn validate(&self) -> Result<()> {
+        let is_valid = |s: &str, wildcard: bool| {
+            !s.is_empty()
+                && s.len() <= 400
+                && s.chars().all(|c| {
+                    c.is_ascii_alphanumeric() || c == '-' || c == '_' || (wildcard && c == '*')
+                })
+        };
+
+        if !is_valid(&self.name, false) {
+            return Err(IndexResolverError::InvalidIndexTemplate(format!(
+                "`{}` is not a valid template name. A template name can only contain alphanumeric characters, hyphens (-) and underscores (_).",
+                self.name
+            )));
+        }
+        if !is_valid(&self.index_pattern, true) {
+            return Err(IndexResolverError::InvalidIndexTemplate(format!(
+                "`{}` is not a valid index pattern. An index pattern can only contain alphanumeric characters, hyphens (-), underscores (_) and wildcards (*).",
+                self.index_pattern
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Matches `uid` against `pattern`, in which `*` matches any sequence of characters.
+fn pattern_matches(pattern: &str, uid: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // split always yields at least one part.
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match uid.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+
+    let mut parts: Vec<_> = parts.collect();
+    let last = match parts.pop() {
+        Some(last) => last,
+        // no wildcard in the pattern.
+        None => return rest.is_empty(),
+    };
+
+    for part in parts {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(last)
+}
+
+/// Stores the index templates in the meta environment, along with the index metas.
+#[derive(Clone)]
+pub struct HeedTemplateStore {
+    env: Arc<Env>,
+    db: Database<Str, SerdeJson<IndexTemplate>>,
+}
+
+impl HeedTemplateStore {
+    pub fn new(env: Arc<Env>) -> Result<Self> {
+        let db = env.create_database(Some("index-templates"))?;
+        Ok(Self { env, db })
+    }
+
+    fn get(&self, name: &str) -> Result<IndexTemplate> {
+        let txn = self.env.read_txn()?;
+        self.db
+            .get(&txn, name)?
+            .ok_or_else(|| IndexResolverError::IndexTemplateNotFound(name.to_string()))
+    }
+
+    fn list(&self) -> Result<Vec<IndexTemplate>> {
+        let txn = self.env.read_txn()?;
+        let mut templates = Vec::new();
+        for entry in self.db.iter(&txn)? {
+            let (_, template) = entry?;
+            templates.push(template);
+        }
+        Ok(templates)
+    }
+
+    fn put(&self, template: &IndexTemplate) -> Result<()> {
+        template.validate()?;
+
+        let mut txn = self.env.write_txn()?;
+        self.db.put(&mut txn, &template.name, template)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn delete(&self, name: &str) -> Result<IndexTemplate> {
+        let mut txn = self.env.write_txn()?;
+        let template = self
+            .db
+            .get(&txn, name)?
+            .ok_or_else(|| IndexResolverError::IndexTemplateNotFound(name.to_string()))?;
+        self.db.delete(&mut txn, name)?;
+        txn.commit()?;
+        Ok(template)
+    }
+
+    /// Returns the most specific template matching `uid`, the templates with a pattern of the
+    /// same specificity are ordered by name.
+    fn find_match(&self, uid: &str) -> Result<Option<IndexTemplate>> {
+        let mut found: Option<IndexTemplate> = None;
+        for template in self.list()? {
+            let is_better = template.matches(uid)
+                && found
+                    .as_ref()
+                    .map_or(true, |found| template.specificity() > found.specificity());
+            if is_better {
+                found = Some(template);
+            }
+        }
+        Ok(found)
+    }
+
+    pub fn dump(&self, path: &Path) -> Result<()> {
+        let dump_path = path.join(TEMPLATES_DB_PATH);
+        create_dir_all(&dump_path)?;
+        let mut dump_file = File::create(dump_path.join("data.jsonl"))?;
+
+        for template in self.list()? {
+            serde_json::to_writer(&mut dump_file, &template)?;
+            dump_file.write_all(b"\n")?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads the templates of a dump, the dumps made before the templates were introduced don't
+    /// contain any.
+    pub fn load_dump(src: impl AsRef<Path>, env: Arc<Env>) -> Result<()> {
+        let src_templates = src.as_ref().join(TEMPLATES_DB_PATH).join("data.jsonl");
+        if !src_templates.exists() {
+            return Ok(());
+        }
+
+        let store = Self::new(env)?;
+        let mut txn = store.env.write_txn()?;
+        for line in BufReader::new(File::open(&src_templates)?).lines() {
+            let template: IndexTemplate = serde_json::from_str(&line?)?;
+            store.db.put(&mut txn, &template.name, &template)?;
+        }
+        txn.commit()?;
+
+        Ok(())
+    }
+
+    pub async fn get_template(&self, name: String) -> Result<IndexTemplate> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get(&name)).await?
+    }
+
+    pub async fn list_templates(&self) -> Result<Vec<IndexTemplate>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.list()).await?
+    }
+
+    pub async fn put_template(&self, template: IndexTemplate) -> Result<IndexTemplate> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.put(&template).map(|_| template)).await?
+    }
+
+    pub async fn delete_template(&self, name: String) -> Result<IndexTemplate> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.delete(&name)).await?
+    }
+
+    pub async fn find_template(&self, uid: String) -> Result<Option<IndexTemplate>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.find_match(&uid)).await?
+    }
+
+    pub async fn dump_templates(&self, path: PathBuf) -> Result<()> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.dump(&path)).await?
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn template(name: &str, index_pattern: &str) -> IndexTemplate {
+        IndexTemplate {
+            name: name.to_string(),
+            index_pattern: index_pattern.to_string(),
+            settings: Settings::default(),
+        }
+    }
+
+    #[test]
+    fn patterns() {
+        assert!(pattern_matches("logs-*", "logs-2022"));
+        assert!(pattern_matches("logs-*", "logs-"));
+        assert!(!pattern_matches("logs-*", "log-2022"));
+        assert!(pattern_matches("*-logs", "app-logs"));
+        assert!(pattern_matches("*", "movies"));
+        assert!(pattern_matches("a*b*c", "a-b-b-c"));
+        assert!(!pattern_matches("a*b*c", "a-c-b"));
+        assert!(pattern_matches("movies", "movies"));
+        assert!(!pattern_matches("movies", "movies-2"));
+    }
+
+    #[test]
+    fn validation() {
+        assert!(template("logs", "logs-*").validate().is_ok());
+        assert!(template("logs*", "logs-*").validate().is_err());
+        assert!(template("logs", "logs/*").validate().is_err());
+        assert!(template("", "logs-*").validate().is_err());
+    }
+
+    #[test]
+    fn most_specific_template_is_found() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut options = milli::heed::EnvOpenOptions::new();
+        options.map_size(4096 * 1000);
+        options.max_dbs(1);
+        let env = Arc::new(options.open(temp.path()).unwrap());
+        let store = HeedTemplateStore::new(env).unwrap();
+
+        store.put(&template("all", "*")).unwrap();
+        store.put(&template("logs", "logs-*")).unwrap();
+        store.put(&template("app-logs", "logs-app-*")).unwrap();
+
+        let found = |uid| store.find_match(uid).unwrap().map(|t| t.name);
+        assert_eq!(found("logs-app-1").as_deref(), Some("app-logs"));
+        assert_eq!(found("logs-db-1").as_deref(), Some("logs"));
+        assert_eq!(found("movies").as_deref(), Some("all"));
+
+        store.delete("all").unwrap();
+        assert_eq!(found("movies"), None);
+        assert!(matches!(
+            store.delete("all"),
+            Err(IndexResolverError::IndexTemplateNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use milli::heed::types::{SerdeJson, Str};
+use milli::heed::{Database, Env};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::error::Result;
+use super::meta_store::IndexMeta;
+use crate::tasks::task::TaskId;
+
+/// An index deleted less than the retention period of the trash ago. Its files are kept until it
+/// is purged, so that it can be restored.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedIndex {
+    pub meta: IndexMeta,
+    #[serde(with = "time::serde::rfc3339")]
+    pub deleted_at: OffsetDateTime,
+    /// The task purging the index, once registered.
+    pub purge_task_id: Option<TaskId>,
+}
+
+/// Stores the deleted indexes in the meta environment, by uid. An uid holds a single trashed
+/// index: the index deleted again under the same uid replaces the previous one, which is purged.
+#[derive(Clone)]
+pub struct HeedIndexTrash {
+    env: Arc<Env>,
+    db: Database<Str, SerdeJson<TrashedIndex>>,
+    retention: Duration,
+}
+
+impl HeedIndexTrash {
+    pub fn new(env: Arc<Env>, retention: Duration) -> Result<Self> {
+        let db = env.create_database(Some("trashed-indexes"))?;
+        Ok(Self { env, db, retention })
+    }
+
+    pub fn retention(&self) -> Duration {
+        self.retention
+    }
+
+    /// Moves the index to the trash, returns the index it replaces.
+    fn put(&self, uid: &str, meta: IndexMeta) -> Result<Option<TrashedIndex>> {
+        let mut txn = self.env.write_txn()?;
+        let replaced = self.db.get(&txn, uid)?;
+        let trashed = TrashedIndex {
+            meta,
+            deleted_at: OffsetDateTime::now_utc(),
+            purge_task_id: None,
+        };
+        self.db.put(&mut txn, uid, &trashed)?;
+        txn.commit()?;
+        Ok(replaced)
+    }
+
+    fn get(&self, uid: &str) -> Result<Option<TrashedIndex>> {
+        let txn = self.env.read_txn()?;
+        Ok(self.db.get(&txn, uid)?)
+    }
+
+    /// Removes the index from the trash if it is the index of `uuid`, or whatever its uuid when
+    /// `None`.
+    fn take(&self, uid: &str, uuid: Option<Uuid>) -> Result<Option<TrashedIndex>> {
+        let mut txn = self.env.write_txn()?;
+        let trashed = match self.db.get(&txn, uid)? {
+            Some(trashed) if uuid.map_or(true, |uuid| trashed.meta.uuid == uuid) => trashed,
+            _ => return Ok(None),
+        };
+        self.db.delete(&mut txn, uid)?;
+        txn.commit()?;
+        Ok(Some(trashed))
+    }
+
+    /// Returns the indexes whose retention period elapsed and whose purge isn't registered yet.
+    fn expired(&self, now: OffsetDateTime) -> Result<Vec<(String, TrashedIndex)>> {
+        let txn = self.env.read_txn()?;
+        let mut expired = Vec::new();
+        for entry in self.db.iter(&txn)? {
+            let (uid, trashed) = entry?;
+            if trashed.purge_task_id.is_none() && trashed.deleted_at + self.retention <= now {
+                expired.push((uid.to_string(), trashed));
+            }
+        }
+        Ok(expired)
+    }
+
+    /// Records the purge task of the index of `uuid`, if it is still in the trash.
+    fn set_purge_task(&self, uid: &str, uuid: Uuid, task_id: TaskId) -> Result<()> {
+        let mut txn = self.env.write_txn()?;
+        if let Some(mut trashed) = self.db.get(&txn, uid)? {
+            if trashed.meta.uuid == uuid {
+                trashed.purge_task_id = Some(task_id);
+                self.db.put(&mut txn, uid, &trashed)?;
+                txn.commit()?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn trash_index(&self, uid: String, meta: IndexMeta) -> Result<Option<TrashedIndex>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.put(&uid, meta)).await?
+    }
+
+    pub async fn get_trashed(&self, uid: String) -> Result<Option<TrashedIndex>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get(&uid)).await?
+    }
+
+    pub async fn take_trashed(
+        &self,
+        uid: String,
+        uuid: Option<Uuid>,
+    ) -> Result<Option<TrashedIndex>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.take(&uid, uuid)).await?
+    }
+
+    pub async fn expired_indexes(&self) -> Result<Vec<(String, TrashedIndex)>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.expired(OffsetDateTime::now_utc())).await?
+    }
+
+    pub async fn register_purge(&self, uid: String, uuid: Uuid, task_id: TaskId) -> Result<()> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.set_purge_task(&uid, uuid, task_id)).await?
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn trash(retention: Duration) -> (tempfile::TempDir, HeedIndexTrash) {
+        let temp = tempfile::tempdir().unwrap();
+        let mut options = milli::heed::EnvOpenOptions::new();
+        options.map_size(4096 * 1000);
+        options.max_dbs(1);
+        let env = Arc::new(options.open(temp.path()).unwrap());
+        (temp, HeedIndexTrash::new(env, retention).unwrap())
+    }
+
+    fn meta(uuid: Uuid) -> IndexMeta {
+        IndexMeta {
+            uuid,
+            creation_task_id: 0,
+        }
+    }
+
+    #[test]
+    fn an_index_deleted_again_replaces_the_trashed_one() {
+        let (_temp, trash) = trash(Duration::from_secs(60));
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert!(trash.put("movies", meta(first)).unwrap().is_none());
+        let replaced = trash.put("movies", meta(second)).unwrap().unwrap();
+        assert_eq!(replaced.meta.uuid, first);
+
+        assert!(trash.take("movies", Some(first)).unwrap().is_none());
+        assert_eq!(trash.get("movies").unwrap().unwrap().meta.uuid, second);
+        assert_eq!(
+            trash.take("movies", None).unwrap().unwrap().meta.uuid,
+            second
+        );
+        assert!(trash.get("movies").unwrap().is_none());
+    }
+
+    #[test]
+    fn expired_indexes_are_purged_once() {
+        let (_temp, trash) = trash(Duration::from_secs(60));
+        let uuid = Uuid::new_v4();
+        trash.put("movies", meta(uuid)).unwrap();
+
+        let now = OffsetDateTime::now_utc();
+        assert!(trash.expired(now).unwrap().is_empty());
+        let later = now + Duration::from_secs(61);
+        let expired = trash.expired(later).unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0, "movies");
+
+        trash.set_purge_task("movies", uuid, 4).unwrap();
+        assert!(trash.expired(later).unwrap().is_empty());
+        assert_eq!(trash.get("movies").unwrap().unwrap().purge_task_id, Some(4));
+    }
+}
@@ -30,13 +30,24 @@ pub struct IndexerOpts {
     #[clap(long, env = "MEILI_MAX_INDEXING_MEMORY", default_value_t)]
     pub max_indexing_memory: MaxMemory,
 
-    /// The maximum number of threads the indexer will use.
+    /// The maximum number of threads the indexer will use, for the extraction of milli as well as
+    /// the rewriting of the documents for the tokenization settings and the scans of the
+    /// existence filters.
     /// If the number set is higher than the real number of cores available in the machine,
     /// it will use the maximum number of available cores.
     ///
     /// It defaults to half of the available threads.
     #[clap(long, env = "MEILI_MAX_INDEXING_THREADS", default_value_t)]
     pub max_indexing_threads: MaxThreads,
+
+    /// The size of the chunks the documents of a task are split in, each chunk being extracted
+    /// by one of the indexing threads before the results are merged into the index. The documents
+    /// rewritten for the tokenization settings are read by chunks of the same size.
+    ///
+    /// Smaller chunks spread large imports over more threads, at the cost of more merges.
+    /// It defaults to splitting the documents in as many chunks as there are indexing threads.
+    #[clap(long, env = "MEILI_INDEXING_DOCUMENTS_CHUNK_SIZE")]
+    pub indexing_documents_chunk_size: Option<Byte>,
 }
 
 #[derive(Debug, Clone, Parser, Default, Serialize)]
@@ -69,12 +80,16 @@ impl TryFrom<&IndexerOpts> for IndexerConfig {
 
     fn try_from(other: &IndexerOpts) -> Result<Self, Self::Error> {
         let thread_pool = rayon::ThreadPoolBuilder::new()
-            .num_threads(*other.max_indexing_threads)
+            .num_threads(other.max_indexing_threads.budget())
+            .thread_name(|index| format!("indexing-{}", index))
             .build()?;
 
         Ok(Self {
             log_every_n: Some(other.log_every_n),
             max_nb_chunks: other.max_nb_chunks,
+            documents_chunk_size: other
+                .indexing_documents_chunk_size
+                .map(|b| b.get_bytes() as usize),
             max_memory: other.max_indexing_memory.map(|b| b.get_bytes() as usize),
             thread_pool: Some(thread_pool),
             max_positions_per_attributes: None,
@@ -90,6 +105,7 @@ impl Default for IndexerOpts {
             max_nb_chunks: None,
             max_indexing_memory: MaxMemory::default(),
             max_indexing_threads: MaxThreads::default(),
+            indexing_documents_chunk_size: None,
         }
     }
 }
@@ -162,6 +178,14 @@ impl FromStr for MaxThreads {
     }
 }
 
+impl MaxThreads {
+    /// The number of threads of the indexing pool, at least one and never more than the
+    /// available threads.
+    pub fn budget(&self) -> usize {
+        self.0.clamp(1, num_cpus::get().max(1))
+    }
+}
+
 impl Default for MaxThreads {
     fn default() -> Self {
         MaxThreads(num_cpus::get() / 2)
@@ -181,3 +205,27 @@ impl Deref for MaxThreads {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn indexing_threads_budget() {
+        assert_eq!(MaxThreads(0).budget(), 1);
+        assert_eq!(MaxThreads(1).budget(), 1);
+        assert_eq!(MaxThreads(usize::MAX).budget(), num_cpus::get());
+    }
+
+    #[test]
+    fn indexer_config_from_options() {
+        let opts = IndexerOpts {
+            max_indexing_threads: MaxThreads(1),
+            indexing_documents_chunk_size: Some(Byte::from_bytes(4096)),
+            ..Default::default()
+        };
+        let config = IndexerConfig::try_from(&opts).unwrap();
+        assert_eq!(config.documents_chunk_size, Some(4096));
+        assert_eq!(config.thread_pool.unwrap().current_num_threads(), 1);
+    }
+}
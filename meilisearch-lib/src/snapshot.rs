@@ -79,6 +79,17 @@ pub fn load_snapshot(
     }
 }
 
+/// Returns the path of the snapshot of the database in the snapshot directory.
+pub fn snapshot_file_path(db_path: impl AsRef<Path>, snapshot_dir: impl AsRef<Path>) -> PathBuf {
+    let db_name = db_path
+        .as_ref()
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("data.ms");
+
+    snapshot_dir.as_ref().join(format!("{}.snapshot", db_name))
+}
+
 #[derive(Debug)]
 pub struct SnapshotJob {
     dest_path: PathBuf,
@@ -109,14 +120,7 @@ impl SnapshotJob {
         self.snapshot_indexes(temp_snapshot_path)?;
         self.snapshot_auth(temp_snapshot_path)?;
 
-        let db_name = self
-            .src_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("data.ms")
-            .to_string();
-
-        let snapshot_path = self.dest_path.join(format!("{}.snapshot", db_name));
+        let snapshot_path = snapshot_file_path(&self.src_path, &self.dest_path);
         let temp_snapshot_file = tempfile::NamedTempFile::new_in(&snapshot_dir)?;
         let temp_snapshot_file_path = temp_snapshot_file.path().to_owned();
         crate::compression::to_tar_gz(temp_snapshot_path, temp_snapshot_file_path)?;
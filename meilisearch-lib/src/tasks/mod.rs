@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 
-pub use scheduler::Scheduler;
+pub use scheduler::{IndexingSettings, IndexingSettingsUpdate, Scheduler};
 pub use task_store::TaskFilter;
 
 #[cfg(test)]
@@ -19,6 +19,7 @@ mod scheduler;
 pub mod task;
 mod task_store;
 pub mod update_loop;
+pub mod watch;
 
 #[cfg_attr(test, mockall::automock(type Error=test::DebugError;))]
 #[async_trait]
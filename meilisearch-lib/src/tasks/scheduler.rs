@@ -6,7 +6,8 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use atomic_refcell::AtomicRefCell;
-use milli::update::IndexDocumentsMethod;
+use milli::update::{IndexDocumentsMethod, Setting};
+use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use tokio::sync::{watch, RwLock};
 
@@ -21,8 +22,8 @@ use super::{TaskFilter, TaskPerformer, TaskStore};
 
 #[derive(Eq, Debug, Clone, Copy)]
 enum TaskType {
-    DocumentAddition { number: usize },
-    DocumentUpdate { number: usize },
+    DocumentAddition { number: usize, size: u64 },
+    DocumentUpdate { number: usize, size: u64 },
     Other,
 }
 
@@ -130,17 +131,21 @@ impl TaskQueue {
         let kind = match task.content {
             TaskContent::DocumentAddition {
                 documents_count,
+                payload_size,
                 merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
                 ..
             } => TaskType::DocumentAddition {
                 number: documents_count,
+                size: payload_size,
             },
             TaskContent::DocumentAddition {
                 documents_count,
+                payload_size,
                 merge_strategy: IndexDocumentsMethod::UpdateDocuments,
                 ..
             } => TaskType::DocumentUpdate {
                 number: documents_count,
+                size: payload_size,
             },
             _ => TaskType::Other,
         };
@@ -192,6 +197,80 @@ impl TaskQueue {
     }
 }
 
+/// The auto-batching parameters of the scheduler. They are initialized from the
+/// `SchedulerConfig` and can be changed at runtime.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexingSettings {
+    /// The maximum number of tasks of the same type that can be batched together. Unlimited if
+    /// unspecified, a value of 0 is interpreted as 1.
+    pub max_batch_size: Option<usize>,
+    /// The maximum number of documents in a document batch.
+    pub max_documents_per_batch: Option<usize>,
+    /// The maximum cumulated size, in bytes, of the payloads of a document batch.
+    pub max_payload_bytes: Option<u64>,
+    /// The time, in milliseconds, the scheduler waits for new tasks before processing a batch.
+    pub max_wait_time_ms: u64,
+}
+
+impl IndexingSettings {
+    pub fn update(&mut self, update: IndexingSettingsUpdate) {
+        fn apply<T>(current: &mut Option<T>, setting: Setting<T>) {
+            match setting {
+                Setting::Set(value) => *current = Some(value),
+                Setting::Reset => *current = None,
+                Setting::NotSet => (),
+            }
+        }
+
+        apply(&mut self.max_batch_size, update.max_batch_size);
+        apply(
+            &mut self.max_documents_per_batch,
+            update.max_documents_per_batch,
+        );
+        apply(&mut self.max_payload_bytes, update.max_payload_bytes);
+        match update.max_wait_time_ms {
+            Setting::Set(ms) => self.max_wait_time_ms = ms,
+            Setting::Reset => self.max_wait_time_ms = 0,
+            Setting::NotSet => (),
+        }
+    }
+
+    fn wait_time(&self) -> Option<Duration> {
+        (self.max_wait_time_ms > 0).then(|| Duration::from_millis(self.max_wait_time_ms))
+    }
+}
+
+impl From<&SchedulerConfig> for IndexingSettings {
+    fn from(config: &SchedulerConfig) -> Self {
+        Self {
+            // Disable autobatching
+            max_batch_size: if config.enable_auto_batching {
+                config.max_batch_size
+            } else {
+                Some(1)
+            },
+            max_documents_per_batch: config.max_documents_per_batch,
+            max_payload_bytes: None,
+            max_wait_time_ms: config.debounce_duration_sec.unwrap_or(0) * 1000,
+        }
+    }
+}
+
+/// A partial update of the `IndexingSettings`, resetting a parameter removes its limit.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct IndexingSettingsUpdate {
+    #[serde(default)]
+    pub max_batch_size: Setting<usize>,
+    #[serde(default)]
+    pub max_documents_per_batch: Setting<usize>,
+    #[serde(default)]
+    pub max_payload_bytes: Setting<u64>,
+    #[serde(default)]
+    pub max_wait_time_ms: Setting<u64>,
+}
+
 pub struct Scheduler {
     jobs: VecDeque<Job>,
     tasks: TaskQueue,
@@ -199,7 +278,7 @@ pub struct Scheduler {
     store: TaskStore,
     processing: Vec<TaskId>,
     next_fetched_task_id: TaskId,
-    config: SchedulerConfig,
+    indexing_settings: IndexingSettings,
     /// Notifies the update loop that a new task was received
     notifier: watch::Sender<()>,
 }
@@ -208,20 +287,13 @@ impl Scheduler {
     pub fn new<P>(
         store: TaskStore,
         performer: Arc<P>,
-        mut config: SchedulerConfig,
+        config: SchedulerConfig,
     ) -> Result<Arc<RwLock<Self>>>
     where
         P: TaskPerformer,
     {
         let (notifier, rcv) = watch::channel(());
 
-        let debounce_time = config.debounce_duration_sec;
-
-        // Disable autobatching
-        if !config.enable_auto_batching {
-            config.max_batch_size = Some(1);
-        }
-
         let this = Self {
             jobs: VecDeque::new(),
             tasks: TaskQueue::default(),
@@ -229,7 +301,7 @@ impl Scheduler {
             store,
             processing: Vec::new(),
             next_fetched_task_id: 0,
-            config,
+            indexing_settings: IndexingSettings::from(&config),
             notifier,
         };
 
@@ -238,12 +310,7 @@ impl Scheduler {
 
         let this = Arc::new(RwLock::new(this));
 
-        let update_loop = UpdateLoop::new(
-            this.clone(),
-            performer,
-            debounce_time.filter(|&v| v > 0).map(Duration::from_secs),
-            rcv,
-        );
+        let update_loop = UpdateLoop::new(this.clone(), performer, rcv);
 
         tokio::task::spawn_local(update_loop.run());
 
@@ -264,6 +331,20 @@ impl Scheduler {
         self.processing.clear();
     }
 
+    pub fn indexing_settings(&self) -> IndexingSettings {
+        self.indexing_settings
+    }
+
+    pub fn update_indexing_settings(&mut self, update: IndexingSettingsUpdate) -> IndexingSettings {
+        self.indexing_settings.update(update);
+        self.indexing_settings
+    }
+
+    /// The time the update loop waits for new tasks before preparing a batch.
+    pub fn debounce_duration(&self) -> Option<Duration> {
+        self.indexing_settings.wait_time()
+    }
+
     pub fn notify(&self) {
         let _ = self.notifier.send(());
     }
@@ -339,7 +420,11 @@ impl Scheduler {
         // Try to fill the queue with pending tasks.
         self.fetch_pending_tasks().await?;
 
-        make_batch(&mut self.tasks, &mut self.processing, &self.config);
+        make_batch(
+            &mut self.tasks,
+            &mut self.processing,
+            &self.indexing_settings,
+        );
 
         log::debug!("prepared batch with {} tasks", self.processing.len());
 
@@ -386,10 +471,11 @@ pub enum Pending {
     Nothing,
 }
 
-fn make_batch(tasks: &mut TaskQueue, processing: &mut Vec<TaskId>, config: &SchedulerConfig) {
+fn make_batch(tasks: &mut TaskQueue, processing: &mut Vec<TaskId>, settings: &IndexingSettings) {
     processing.clear();
 
     let mut doc_count = 0;
+    let mut payload_size = 0;
     tasks.head_mut(|list| match list.peek().copied() {
         Some(PendingTask {
             kind: TaskType::Other,
@@ -402,22 +488,25 @@ fn make_batch(tasks: &mut TaskQueue, processing: &mut Vec<TaskId>, config: &Sche
             match list.peek() {
                 Some(pending) if pending.kind == kind => {
                     // We always need to process at least one task for the scheduler to make progress.
-                    if processing.len() >= config.max_batch_size.unwrap_or(usize::MAX).max(1) {
+                    if processing.len() >= settings.max_batch_size.unwrap_or(usize::MAX).max(1) {
                         break;
                     }
                     let pending = list.pop().unwrap();
                     processing.push(pending.id);
 
-                    // We add the number of documents to the count if we are scheduling document additions and
-                    // stop adding if we already have enough.
+                    // We add the number of documents and the size of the payload to the counts if we are
+                    // scheduling document additions and stop adding if we already have enough.
                     //
-                    // We check that bound only after adding the current task to the batch, so that a batch contains at least one task.
+                    // We check these bounds only after adding the current task to the batch, so that a batch contains at least one task.
                     match pending.kind {
-                        TaskType::DocumentUpdate { number }
-                        | TaskType::DocumentAddition { number } => {
+                        TaskType::DocumentUpdate { number, size }
+                        | TaskType::DocumentAddition { number, size } => {
                             doc_count += number;
+                            payload_size += size;
 
-                            if doc_count >= config.max_documents_per_batch.unwrap_or(usize::MAX) {
+                            if doc_count >= settings.max_documents_per_batch.unwrap_or(usize::MAX)
+                                || payload_size >= settings.max_payload_bytes.unwrap_or(u64::MAX)
+                            {
                                 break;
                             }
                         }
@@ -484,6 +573,7 @@ mod test {
             merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
             primary_key: Some("test".to_string()),
             documents_count: 0,
+            payload_size: 0,
             allow_index_creation: true,
         };
         queue.insert(gen_task(0, "test1", content.clone()));
@@ -497,30 +587,68 @@ mod test {
 
         let mut batch = Vec::new();
 
-        let config = SchedulerConfig::default();
-        make_batch(&mut queue, &mut batch, &config);
+        let settings = IndexingSettings::default();
+        make_batch(&mut queue, &mut batch, &settings);
         assert_eq!(batch, &[0, 4]);
 
         batch.clear();
-        make_batch(&mut queue, &mut batch, &config);
+        make_batch(&mut queue, &mut batch, &settings);
         assert_eq!(batch, &[1]);
 
         batch.clear();
-        make_batch(&mut queue, &mut batch, &config);
+        make_batch(&mut queue, &mut batch, &settings);
         assert_eq!(batch, &[2]);
 
         batch.clear();
-        make_batch(&mut queue, &mut batch, &config);
+        make_batch(&mut queue, &mut batch, &settings);
         assert_eq!(batch, &[3, 6]);
 
         batch.clear();
-        make_batch(&mut queue, &mut batch, &config);
+        make_batch(&mut queue, &mut batch, &settings);
         assert_eq!(batch, &[5]);
 
         batch.clear();
-        make_batch(&mut queue, &mut batch, &config);
+        make_batch(&mut queue, &mut batch, &settings);
         assert_eq!(batch, &[7]);
 
         assert!(queue.is_empty());
     }
+
+    #[test]
+    fn test_make_batch_with_payload_limit() {
+        let mut queue = TaskQueue::default();
+        let content = |payload_size| TaskContent::DocumentAddition {
+            content_uuid: Uuid::new_v4(),
+            merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+            primary_key: Some("test".to_string()),
+            documents_count: 1,
+            payload_size,
+            allow_index_creation: true,
+        };
+        queue.insert(gen_task(0, "test", content(100)));
+        queue.insert(gen_task(1, "test", content(50)));
+        queue.insert(gen_task(2, "test", content(500)));
+        queue.insert(gen_task(3, "test", content(10)));
+
+        let mut batch = Vec::new();
+
+        let mut settings = IndexingSettings::default();
+        settings.update(IndexingSettingsUpdate {
+            max_payload_bytes: Setting::Set(150),
+            ..Default::default()
+        });
+        make_batch(&mut queue, &mut batch, &settings);
+        assert_eq!(batch, &[0, 1]);
+
+        // a batch contains at least one task, even when its payload exceeds the limit.
+        batch.clear();
+        make_batch(&mut queue, &mut batch, &settings);
+        assert_eq!(batch, &[2]);
+
+        batch.clear();
+        make_batch(&mut queue, &mut batch, &settings);
+        assert_eq!(batch, &[3]);
+
+        assert!(queue.is_empty());
+    }
 }
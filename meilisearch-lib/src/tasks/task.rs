@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use meilisearch_error::ResponseError;
-use milli::update::{DocumentAdditionResult, IndexDocumentsMethod};
+use milli::update::IndexDocumentsMethod;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use tokio::sync::oneshot;
@@ -9,7 +9,7 @@ use uuid::Uuid;
 
 use super::batch::BatchId;
 use crate::{
-    index::{Settings, Unchecked},
+    index::{DocumentAdditionResult, Settings, Unchecked},
     index_resolver::{error::IndexResolverError, IndexUid},
     snapshot::SnapshotJob,
 };
@@ -19,9 +19,25 @@ pub type TaskId = u64;
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(test, derive(proptest_derive::Arbitrary))]
 pub enum TaskResult {
-    DocumentAddition { indexed_documents: u64 },
-    DocumentDeletion { deleted_documents: u64 },
-    ClearAll { deleted_documents: u64 },
+    DocumentAddition {
+        indexed_documents: u64,
+        #[serde(default)]
+        skipped_documents: u64,
+    },
+    DocumentDeletion {
+        deleted_documents: u64,
+    },
+    ClearAll {
+        deleted_documents: u64,
+    },
+    IndexRebuild {
+        /// The size the index file shrunk by, 0 when it grew.
+        reclaimed_bytes: u64,
+    },
+    IndexPurge {
+        /// The size of the files of the purged index.
+        reclaimed_bytes: u64,
+    },
     Other,
 }
 
@@ -29,6 +45,7 @@ impl From<DocumentAdditionResult> for TaskResult {
     fn from(other: DocumentAdditionResult) -> Self {
         Self::DocumentAddition {
             indexed_documents: other.indexed_documents,
+            skipped_documents: other.skipped_documents,
         }
     }
 }
@@ -145,6 +162,9 @@ pub enum TaskContent {
         merge_strategy: IndexDocumentsMethod,
         primary_key: Option<String>,
         documents_count: usize,
+        /// The size in bytes of the payload, used to bound the size of the document batches.
+        #[serde(default)]
+        payload_size: u64,
         allow_index_creation: bool,
     },
     DocumentDeletion(DocumentDeletion),
@@ -161,6 +181,22 @@ pub enum TaskContent {
     IndexUpdate {
         primary_key: Option<String>,
     },
+    IndexRename {
+        new_uid: IndexUid,
+    },
+    /// Rebuilds the databases of the index: the documents are reindexed with the current
+    /// settings when `reindex` is set, then the index file is compacted.
+    IndexRebuild {
+        reindex: bool,
+    },
+    /// Restores the index from the trash, under the uid it was deleted with.
+    IndexRestore,
+    /// Deletes the files of the trashed index once its retention period elapsed. The index is
+    /// identified by its uuid, so that an index deleted again under the same uid isn't purged.
+    IndexPurge {
+        #[cfg_attr(test, proptest(value = "Uuid::new_v4()"))]
+        index_uuid: Uuid,
+    },
 }
 
 #[cfg(test)]
@@ -11,6 +11,7 @@ use time::OffsetDateTime;
 
 use super::error::TaskError;
 use super::task::{Task, TaskContent, TaskId};
+use super::watch::TaskWatcher;
 use super::Result;
 use crate::index_resolver::IndexUid;
 use crate::tasks::task::TaskEvent;
@@ -36,6 +37,11 @@ impl TaskFilter {
             .unwrap_or(true)
     }
 
+    /// Returns whether the task matches both the indexes and the filter function.
+    pub fn matches(&self, task: &Task) -> bool {
+        self.pass(task) && self.filter_fn.as_ref().map_or(true, |f| f(task))
+    }
+
     /// Adds an index to the filter, so the filter must match this index.
     pub fn filter_index(&mut self, index: String) {
         self.indexes
@@ -50,12 +56,14 @@ impl TaskFilter {
 
 pub struct TaskStore {
     store: Arc<Store>,
+    watcher: TaskWatcher,
 }
 
 impl Clone for TaskStore {
     fn clone(&self) -> Self {
         Self {
             store: self.store.clone(),
+            watcher: self.watcher.clone(),
         }
     }
 }
@@ -63,7 +71,15 @@ impl Clone for TaskStore {
 impl TaskStore {
     pub fn new(env: Arc<milli::heed::Env>) -> Result<Self> {
         let store = Arc::new(Store::new(env)?);
-        Ok(Self { store })
+        Ok(Self {
+            store,
+            watcher: TaskWatcher::default(),
+        })
+    }
+
+    /// Returns the watcher notified each time a task is registered or updated.
+    pub fn watcher(&self) -> TaskWatcher {
+        self.watcher.clone()
     }
 
     pub async fn register(&self, index_uid: IndexUid, content: TaskContent) -> Result<Task> {
@@ -87,6 +103,8 @@ impl TaskStore {
         })
         .await??;
 
+        self.watcher.notify(&task);
+
         Ok(task)
     }
 
@@ -107,7 +125,7 @@ impl TaskStore {
 
         match filter {
             Some(filter) => filter
-                .pass(&task)
+                .matches(&task)
                 .then(|| task)
                 .ok_or(TaskError::UnexistingTask(id)),
             None => Ok(task),
@@ -149,6 +167,10 @@ impl TaskStore {
         })
         .await??;
 
+        for task in &tasks {
+            self.watcher.notify(task);
+        }
+
         Ok(tasks)
     }
 
@@ -168,6 +190,30 @@ impl TaskStore {
         .await?
     }
 
+    /// Returns the last task of `index_uid` that has been successfully applied to the index.
+    pub async fn last_succeeded_task(&self, index_uid: String) -> Result<Option<Task>> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let txn = store.rtxn()?;
+            store.last_index_task(&txn, &index_uid, |task| {
+                matches!(task.events.last(), Some(TaskEvent::Succeded { .. }))
+            })
+        })
+        .await?
+    }
+
+    /// Returns the uid the next registered task will get.
+    pub async fn next_task_id(&self) -> Result<TaskId> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut txn = store.wtxn()?;
+            store.next_task_id(&mut txn)
+        })
+        .await?
+    }
+
     pub async fn dump(
         &self,
         dir_path: impl AsRef<Path>,
@@ -256,6 +302,13 @@ pub mod test {
             Self::Mock(Arc::new(mocker))
         }
 
+        pub fn watcher(&self) -> TaskWatcher {
+            match self {
+                Self::Real(s) => s.watcher(),
+                Self::Mock(_m) => TaskWatcher::default(),
+            }
+        }
+
         pub async fn update_tasks(&self, tasks: Vec<Task>) -> Result<Vec<Task>> {
             match self {
                 Self::Real(s) => s.update_tasks(tasks).await,
@@ -294,6 +347,20 @@ pub mod test {
             }
         }
 
+        pub async fn last_succeeded_task(&self, index_uid: String) -> Result<Option<Task>> {
+            match self {
+                Self::Real(s) => s.last_succeeded_task(index_uid).await,
+                Self::Mock(m) => unsafe { m.get("last_succeeded_task").call(index_uid) },
+            }
+        }
+
+        pub async fn next_task_id(&self) -> Result<TaskId> {
+            match self {
+                Self::Real(s) => s.next_task_id().await,
+                Self::Mock(m) => unsafe { m.get("next_task_id").call(()) },
+            }
+        }
+
         pub async fn dump(
             &self,
             path: impl AsRef<Path>,
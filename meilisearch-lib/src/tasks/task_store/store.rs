@@ -170,6 +170,33 @@ impl Store {
         Ok(tasks)
     }
 
+    /// Returns the most recent task of `index_uid` for which `filter` returns true.
+    pub fn last_index_task(
+        &self,
+        txn: &RoTxn,
+        index_uid: &str,
+        filter: impl Fn(&Task) -> bool,
+    ) -> Result<Option<Task>> {
+        let mut prefix = index_uid.as_bytes().to_vec();
+        prefix.push(0);
+
+        let ids = self
+            .uids_task_ids
+            .remap_key_type::<ByteSlice>()
+            .rev_prefix_iter(txn, &prefix)?;
+        for entry in ids {
+            let (key, _) = entry?;
+            let (_, id) =
+                IndexUidTaskIdCodec::bytes_decode(key).ok_or(milli::heed::Error::Decoding)?;
+            match self.get(txn, id)? {
+                Some(task) if filter(&task) => return Ok(Some(task)),
+                _ => (),
+            }
+        }
+
+        Ok(None)
+    }
+
     fn compute_candidates(
         &self,
         txn: &milli::heed::RoTxn,
@@ -315,6 +342,18 @@ pub mod test {
                 MockStore::Fake(_) => todo!(),
             }
         }
+
+        pub fn last_index_task(
+            &self,
+            txn: &RoTxn,
+            index_uid: &str,
+            filter: impl Fn(&Task) -> bool,
+        ) -> Result<Option<Task>> {
+            match self {
+                MockStore::Real(index) => index.last_index_task(txn, index_uid, filter),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
     }
 
     #[test]
@@ -408,6 +447,31 @@ pub mod test {
         assert_eq!(&*tasks.first().unwrap().index_uid, "test");
     }
 
+    #[test]
+    fn test_last_index_task() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let mut txn = store.wtxn().unwrap();
+        for (id, index_uid) in [(0, "test"), (1, "test1"), (2, "test"), (3, "test1")] {
+            let task = Task {
+                id,
+                index_uid: IndexUid::new_unchecked(index_uid),
+                content: TaskContent::IndexDeletion,
+                events: vec![],
+            };
+            store.put(&mut txn, &task).unwrap();
+        }
+
+        let last = |filter: fn(&Task) -> bool| {
+            let task = store.last_index_task(&txn, "test", filter).unwrap();
+            task.map(|task| task.id)
+        };
+        assert_eq!(last(|_| true), Some(2));
+        assert_eq!(last(|task| task.id < 2), Some(0));
+        assert_eq!(last(|_| false), None);
+    }
+
     proptest! {
         #[test]
         fn encode_decode_roundtrip(index_uid in any::<IndexUid>(), task_id in 0..TaskId::MAX) {
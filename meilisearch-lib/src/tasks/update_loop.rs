@@ -1,5 +1,4 @@
 use std::sync::Arc;
-use std::time::Duration;
 
 use time::OffsetDateTime;
 use tokio::sync::{watch, RwLock};
@@ -18,7 +17,6 @@ pub struct UpdateLoop<P: TaskPerformer> {
     performer: Arc<P>,
 
     notifier: Option<watch::Receiver<()>>,
-    debounce_duration: Option<Duration>,
 }
 
 impl<P> UpdateLoop<P>
@@ -28,13 +26,11 @@ where
     pub fn new(
         scheduler: Arc<RwLock<Scheduler>>,
         performer: Arc<P>,
-        debuf_duration: Option<Duration>,
         notifier: watch::Receiver<()>,
     ) -> Self {
         Self {
             scheduler,
             performer,
-            debounce_duration: debuf_duration,
             notifier: Some(notifier),
         }
     }
@@ -47,7 +43,9 @@ where
                 break;
             }
 
-            if let Some(t) = self.debounce_duration {
+            // The debounce duration is read on each iteration since it can be updated at runtime.
+            let debounce_duration = self.scheduler.read().await.debounce_duration();
+            if let Some(t) = debounce_duration {
                 let mut interval = interval_at(tokio::time::Instant::now() + t, t);
                 interval.tick().await;
             };
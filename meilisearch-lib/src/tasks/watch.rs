@@ -0,0 +1,102 @@
+use milli::update::UpdateIndexingStep;
+use tokio::sync::broadcast;
+
+use super::task::Task;
+
+/// The number of changes a slow watcher can lag behind before missing some of them.
+const WATCH_CHANNEL_CAPACITY: usize = 1024;
+
+/// A change in the state of a task.
+#[derive(Debug, Clone)]
+pub struct TaskProgress {
+    pub task: Task,
+    /// How much of the task is done, between 0 and 100.
+    pub percentage: u8,
+}
+
+/// Broadcasts the changes of the tasks to the watchers, so they don't have to poll the task store.
+#[derive(Clone)]
+pub struct TaskWatcher {
+    sender: broadcast::Sender<TaskProgress>,
+}
+
+impl Default for TaskWatcher {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(WATCH_CHANNEL_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl TaskWatcher {
+    pub fn subscribe(&self) -> broadcast::Receiver<TaskProgress> {
+        self.sender.subscribe()
+    }
+
+    /// Notifies the new state of the task, a finished task is completely done.
+    pub fn notify(&self, task: &Task) {
+        let percentage = if task.is_finished() { 100 } else { 0 };
+        self.notify_progress(task, percentage);
+    }
+
+    pub fn notify_progress(&self, task: &Task, percentage: u8) {
+        // Nobody is watching the tasks.
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+
+        let _ = self.sender.send(TaskProgress {
+            task: task.clone(),
+            percentage,
+        });
+    }
+}
+
+/// Converts an indexing step to the percentage of the indexing that is done. The step is never
+/// reported as finished, the task only reaches 100% once its result is committed.
+pub fn indexing_percentage(step: &UpdateIndexingStep) -> u8 {
+    let (index, seen, total) = match *step {
+        UpdateIndexingStep::RemapDocumentAddition { .. } => (0, 0, 1),
+        UpdateIndexingStep::ComputeIdsAndMergeDocuments {
+            documents_seen,
+            total_documents,
+        } => (1, documents_seen, total_documents),
+        UpdateIndexingStep::IndexDocuments {
+            documents_seen,
+            total_documents,
+        } => (2, documents_seen, total_documents),
+        UpdateIndexingStep::MergeDataIntoFinalDatabase {
+            databases_seen,
+            total_databases,
+        } => (3, databases_seen, total_databases),
+    };
+
+    let step_done = seen.min(total) as f64 / total.max(1) as f64;
+    ((index as f64 + step_done) * 99.0 / 4.0) as u8
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn indexing_percentage_increases_with_steps() {
+        let steps = [
+            UpdateIndexingStep::RemapDocumentAddition { documents_seen: 10 },
+            UpdateIndexingStep::ComputeIdsAndMergeDocuments {
+                documents_seen: 5,
+                total_documents: 10,
+            },
+            UpdateIndexingStep::IndexDocuments {
+                documents_seen: 10,
+                total_documents: 10,
+            },
+            UpdateIndexingStep::MergeDataIntoFinalDatabase {
+                databases_seen: 10,
+                total_databases: 10,
+            },
+        ];
+
+        let percentages: Vec<_> = steps.iter().map(indexing_percentage).collect();
+        assert_eq!(percentages, [0, 37, 74, 99]);
+    }
+}
@@ -142,13 +142,21 @@ mod store {
         /// Peforms a dump of the given update file uuid into the provided dump path.
         pub fn dump(&self, uuid: Uuid, dump_path: impl AsRef<Path>) -> Result<()> {
             let uuid_string = uuid.to_string();
-            let update_file_path = self.path.join(&uuid_string);
             let mut dst = dump_path.as_ref().join(UPDATE_FILES_PATH);
             std::fs::create_dir_all(&dst)?;
             dst.push(&uuid_string);
 
-            let update_file = File::open(update_file_path)?;
             let mut dst_file = NamedTempFile::new_in(&dump_path)?;
+            self.write_ndjson(uuid, &mut dst_file)?;
+            dst_file.persist(dst)?;
+
+            Ok(())
+        }
+
+        /// Writes the documents of the update file pointed to by `uuid` to `writer`, one JSON
+        /// document per line.
+        pub fn write_ndjson(&self, uuid: Uuid, mut writer: impl Write) -> Result<()> {
+            let update_file = File::open(self.path.join(uuid.to_string()))?;
             let mut document_reader = DocumentBatchReader::from_reader(update_file)?;
 
             let mut document_buffer = Map::new();
@@ -162,13 +170,11 @@ mod store {
                     }
                 }
 
-                serde_json::to_writer(&mut dst_file, &document_buffer)?;
-                dst_file.write_all(b"\n")?;
+                serde_json::to_writer(&mut writer, &document_buffer)?;
+                writer.write_all(b"\n")?;
                 document_buffer.clear();
             }
 
-            dst_file.persist(dst)?;
-
             Ok(())
         }
 
@@ -239,6 +245,13 @@ mod test {
             }
         }
 
+        pub fn write_ndjson(&self, uuid: Uuid, writer: impl Write) -> Result<()> {
+            match self {
+                MockUpdateFileStore::Real(s) => s.write_ndjson(uuid, writer),
+                MockUpdateFileStore::Mock(_) => todo!(),
+            }
+        }
+
         pub fn get_size(&self, uuid: Uuid) -> Result<u64> {
             match self {
                 MockUpdateFileStore::Real(s) => s.get_size(uuid),